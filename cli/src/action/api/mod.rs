@@ -22,6 +22,8 @@ use serde::Deserialize;
 
 use super::CliError;
 
+#[cfg(feature = "api-keys")]
+pub use rbac::api_keys::{ApiKey, CreatedApiKey, NewApiKey, NewApiKeyBuilder};
 #[cfg(feature = "authorization-handler-rbac")]
 pub use rbac::{
     assignments::{
@@ -153,12 +155,27 @@ impl SplinterRestClient {
             })
     }
 
-    /// Turns maintenance mode on or off for the Splinter node.
+    /// Turns maintenance mode on or off for the Splinter node, optionally giving a reason for the
+    /// change and, when turning it on, the time (in seconds since the epoch) at which it should
+    /// automatically turn off.
     #[cfg(feature = "authorization-handler-maintenance")]
-    pub fn set_maintenance_mode(&self, enabled: bool) -> Result<(), CliError> {
+    pub fn set_maintenance_mode(
+        &self,
+        enabled: bool,
+        reason: Option<&str>,
+        ends_at: Option<u64>,
+    ) -> Result<(), CliError> {
+        let mut query = vec![("enabled", enabled.to_string())];
+        if let Some(reason) = reason {
+            query.push(("reason", reason.to_string()));
+        }
+        if let Some(ends_at) = ends_at {
+            query.push(("ends_at", ends_at.to_string()));
+        }
+
         Client::new()
             .post(&format!("{}/authorization/maintenance", self.url))
-            .query(&[("enabled", enabled)])
+            .query(&query)
             .header("Authorization", &self.auth)
             .send()
             .map_err(|err| {
@@ -188,6 +205,47 @@ impl SplinterRestClient {
             })
     }
 
+    /// Lists the changes made to maintenance mode on the Splinter node, from oldest to most
+    /// recent.
+    #[cfg(feature = "authorization-handler-maintenance")]
+    pub fn list_maintenance_mode_changes(&self) -> Result<Vec<MaintenanceModeChange>, CliError> {
+        Client::new()
+            .get(&format!("{}/authorization/maintenance/changes", self.url))
+            .header("Authorization", &self.auth)
+            .send()
+            .map_err(|err| {
+                CliError::ActionError(format!("Failed to get maintenance mode changes: {}", err))
+            })
+            .and_then(|res| {
+                let status = res.status();
+                if status.is_success() {
+                    res.json::<MaintenanceModeChangesResponse>()
+                        .map(|response| response.data)
+                        .map_err(|_| {
+                            CliError::ActionError(
+                                "Request was successful, but received an invalid response".into(),
+                            )
+                        })
+                } else {
+                    let message = res
+                        .json::<ServerError>()
+                        .map_err(|_| {
+                            CliError::ActionError(format!(
+                                "Maintenance mode changes request failed with status code '{}', \
+                                 but error response was not valid",
+                                status
+                            ))
+                        })?
+                        .message;
+
+                    Err(CliError::ActionError(format!(
+                        "Failed to get maintenance mode changes: {}",
+                        message
+                    )))
+                }
+            })
+    }
+
     /// Lists all REST API permissions for a Splinter node.
     #[cfg(feature = "permissions")]
     pub fn list_permissions(&self) -> Result<Vec<Permission>, CliError> {
@@ -289,6 +347,30 @@ impl SplinterRestClient {
     pub fn delete_assignment(&self, identity: &Identity) -> Result<(), CliError> {
         rbac::assignments::delete_assignment(&self.url, &self.auth, identity)
     }
+
+    #[cfg(feature = "api-keys")]
+    pub fn list_api_keys(&self) -> Result<rbac::PagingIter<ApiKey>, CliError> {
+        Ok(rbac::PagingIter::new(
+            &self.url,
+            &self.auth,
+            "/authorization/api_keys",
+        ))
+    }
+
+    #[cfg(feature = "api-keys")]
+    pub fn get_api_key(&self, key_id: &str) -> Result<ApiKey, CliError> {
+        rbac::api_keys::get_api_key(&self.url, &self.auth, key_id)
+    }
+
+    #[cfg(feature = "api-keys")]
+    pub fn create_api_key(&self, new_api_key: NewApiKey) -> Result<CreatedApiKey, CliError> {
+        rbac::api_keys::create_api_key(&self.url, &self.auth, new_api_key)
+    }
+
+    #[cfg(feature = "api-keys")]
+    pub fn revoke_api_key(&self, key_id: &str) -> Result<(), CliError> {
+        rbac::api_keys::revoke_api_key(&self.url, &self.auth, key_id)
+    }
 }
 
 #[derive(Deserialize)]
@@ -305,6 +387,23 @@ pub struct NodeStatus {
     pub version: String,
 }
 
+#[cfg(feature = "authorization-handler-maintenance")]
+#[derive(Deserialize)]
+struct MaintenanceModeChangesResponse {
+    pub data: Vec<MaintenanceModeChange>,
+}
+
+/// A change made to maintenance mode; times are in seconds since the epoch.
+#[cfg(feature = "authorization-handler-maintenance")]
+#[derive(Deserialize)]
+pub struct MaintenanceModeChange {
+    pub enabled: bool,
+    pub changed_by: String,
+    pub changed_at: u64,
+    pub reason: Option<String>,
+    pub ends_at: Option<u64>,
+}
+
 #[derive(Deserialize)]
 struct PermissionsResponse {
     pub data: Vec<Permission>,
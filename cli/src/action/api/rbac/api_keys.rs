@@ -0,0 +1,323 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::action::api::ServerError;
+use crate::error::CliError;
+
+use super::{Pageable, RBAC_PROTOCOL_VERSION};
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ApiKey {
+    pub id: String,
+    pub display_name: String,
+    pub scopes: Vec<String>,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub expired: bool,
+}
+
+impl fmt::Display for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Id: {}", self.id)?;
+        write!(f, "\n    Name: {}", self.display_name)?;
+        write!(f, "\n    Created: {}", self.created_at)?;
+        write!(f, "\n    Expires: {}", self.expires_at)?;
+        if self.expired {
+            f.write_str(" (expired)")?;
+        }
+        f.write_str("\n    Scopes:")?;
+
+        for scope in self.scopes.iter() {
+            write!(f, "\n        {}", scope)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Pageable for ApiKey {
+    fn label() -> &'static str {
+        "API key list"
+    }
+}
+
+#[derive(Deserialize)]
+struct ApiKeyGet {
+    #[serde(rename = "data")]
+    api_key: ApiKey,
+}
+
+/// An API key that has just been created, which is the only time the key's token is available
+#[derive(Deserialize)]
+pub struct CreatedApiKey {
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+    pub token: String,
+}
+
+#[derive(Deserialize)]
+struct CreatedApiKeyPost {
+    #[serde(rename = "data")]
+    created: CreatedApiKey,
+}
+
+/// A request for a splinter node to issue a new API key
+#[derive(Debug, Serialize)]
+pub struct NewApiKey {
+    display_name: String,
+    scopes: Vec<String>,
+    expires_in: u64,
+}
+
+/// Constructs new API key requests for submission to a splinter node.
+#[derive(Default)]
+pub struct NewApiKeyBuilder {
+    display_name: Option<String>,
+    scopes: Vec<String>,
+    expires_in: Option<u64>,
+}
+
+impl NewApiKeyBuilder {
+    /// Sets the display name of the resulting API key.
+    ///
+    /// Must not be empty.
+    pub fn with_display_name(mut self, display_name: String) -> Self {
+        self.display_name = Some(display_name);
+        self
+    }
+
+    /// Sets the permissions that the resulting API key may be used for.
+    ///
+    /// Must not be empty.
+    pub fn with_scopes(mut self, scopes: Vec<String>) -> Self {
+        self.scopes = scopes;
+        self
+    }
+
+    /// Sets the number of seconds after creation that the resulting API key expires.
+    ///
+    /// Must be greater than zero.
+    pub fn with_expires_in(mut self, expires_in: u64) -> Self {
+        self.expires_in = Some(expires_in);
+        self
+    }
+
+    /// Constructs the new API key request.
+    pub fn build(self) -> Result<NewApiKey, CliError> {
+        let NewApiKeyBuilder {
+            display_name,
+            scopes,
+            expires_in,
+        } = self;
+
+        let display_name = display_name
+            .filter(|display_name| !display_name.is_empty())
+            .ok_or_else(|| CliError::ActionError("An API key must have a display name".into()))?;
+
+        if scopes.is_empty() {
+            return Err(CliError::ActionError(
+                "An API key must have at least one scope".into(),
+            ));
+        }
+
+        let expires_in = expires_in
+            .filter(|expires_in| *expires_in > 0)
+            .ok_or_else(|| {
+                CliError::ActionError("An API key must have an expiration time".into())
+            })?;
+
+        Ok(NewApiKey {
+            display_name,
+            scopes,
+            expires_in,
+        })
+    }
+}
+
+pub fn create_api_key(
+    base_url: &str,
+    auth: &str,
+    new_api_key: NewApiKey,
+) -> Result<CreatedApiKey, CliError> {
+    Client::new()
+        .post(&format!("{}/authorization/api_keys", base_url))
+        .header("SplinterProtocolVersion", RBAC_PROTOCOL_VERSION)
+        .header("Authorization", auth)
+        .json(&new_api_key)
+        .send()
+        .map_err(|err| CliError::ActionError(format!("Failed to create API key: {}", err)))
+        .and_then(|res| {
+            let status = res.status();
+            if status.is_success() {
+                res.json::<CreatedApiKeyPost>().map_err(|_| {
+                    CliError::ActionError(
+                        "Request was successful, but received an invalid response".into(),
+                    )
+                })
+            } else if status.as_u16() == 401 {
+                Err(CliError::ActionError("Not Authorized".into()))
+            } else {
+                let message = res
+                    .json::<ServerError>()
+                    .map_err(|_| {
+                        CliError::ActionError(format!(
+                            "Create API key request failed with status code '{}', but error \
+                            response was not valid",
+                            status
+                        ))
+                    })?
+                    .message;
+
+                Err(CliError::ActionError(format!(
+                    "Failed to create API key: {}",
+                    message
+                )))
+            }
+        })
+        .map(|wrapper| wrapper.created)
+}
+
+pub fn get_api_key(base_url: &str, auth: &str, key_id: &str) -> Result<ApiKey, CliError> {
+    Client::new()
+        .get(&format!("{}/authorization/api_keys/{}", base_url, key_id))
+        .header("SplinterProtocolVersion", RBAC_PROTOCOL_VERSION)
+        .header("Authorization", auth)
+        .send()
+        .map_err(|err| {
+            CliError::ActionError(format!("Failed to fetch API key {}: {}", key_id, err))
+        })
+        .and_then(|res| {
+            let status = res.status();
+            if status.is_success() {
+                res.json::<ApiKeyGet>().map_err(|_| {
+                    CliError::ActionError(
+                        "Request was successful, but received an invalid response".into(),
+                    )
+                })
+            } else if status.as_u16() == 401 {
+                Err(CliError::ActionError("Not Authorized".into()))
+            } else if status.as_u16() == 404 {
+                Err(CliError::ActionError(format!(
+                    "API key {} does not exist",
+                    key_id
+                )))
+            } else {
+                let message = res
+                    .json::<ServerError>()
+                    .map_err(|_| {
+                        CliError::ActionError(format!(
+                            "Get API key request failed with status code '{}', but error response \
+                            was not valid",
+                            status
+                        ))
+                    })?
+                    .message;
+
+                Err(CliError::ActionError(format!(
+                    "Failed to get API key {}: {}",
+                    key_id, message
+                )))
+            }
+        })
+        .map(|wrapper| wrapper.api_key)
+}
+
+pub fn revoke_api_key(base_url: &str, auth: &str, key_id: &str) -> Result<(), CliError> {
+    Client::new()
+        .delete(&format!("{}/authorization/api_keys/{}", base_url, key_id))
+        .header("SplinterProtocolVersion", RBAC_PROTOCOL_VERSION)
+        .header("Authorization", auth)
+        .send()
+        .map_err(|err| {
+            CliError::ActionError(format!("Failed to revoke API key {}: {}", key_id, err))
+        })
+        .and_then(|res| {
+            let status = res.status();
+            if status.is_success() {
+                Ok(())
+            } else if status.as_u16() == 401 {
+                Err(CliError::ActionError("Not Authorized".into()))
+            } else if status.as_u16() == 404 {
+                Err(CliError::ActionError(format!(
+                    "API key {} does not exist",
+                    key_id
+                )))
+            } else {
+                let message = res
+                    .json::<ServerError>()
+                    .map_err(|_| {
+                        CliError::ActionError(format!(
+                            "Revoke API key request failed with status code '{}', but error \
+                            response was not valid",
+                            status
+                        ))
+                    })?
+                    .message;
+
+                Err(CliError::ActionError(format!(
+                    "Failed to revoke API key {}: {}",
+                    key_id, message
+                )))
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that the `NewApiKeyBuilder` validates the new API key request.
+    ///
+    /// 1. Construct a valid request
+    /// 2. Fail with an empty display name
+    /// 3. Fail with no scopes
+    /// 4. Fail with no expiration time
+    #[test]
+    fn test_new_api_key_builder() {
+        let new_api_key = NewApiKeyBuilder::default()
+            .with_display_name("Nightly report".into())
+            .with_scopes(vec!["circuit.read".into()])
+            .with_expires_in(3600)
+            .build()
+            .expect("Could not build a valid API key request");
+
+        assert_eq!("Nightly report", new_api_key.display_name);
+        assert_eq!(vec!["circuit.read".to_string()], new_api_key.scopes);
+        assert_eq!(3600, new_api_key.expires_in);
+
+        let res = NewApiKeyBuilder::default()
+            .with_display_name("".into())
+            .with_scopes(vec!["circuit.read".into()])
+            .with_expires_in(3600)
+            .build();
+        assert!(matches!(res, Err(CliError::ActionError(_))));
+
+        let res = NewApiKeyBuilder::default()
+            .with_display_name("Nightly report".into())
+            .with_expires_in(3600)
+            .build();
+        assert!(matches!(res, Err(CliError::ActionError(_))));
+
+        let res = NewApiKeyBuilder::default()
+            .with_display_name("Nightly report".into())
+            .with_scopes(vec!["circuit.read".into()])
+            .build();
+        assert!(matches!(res, Err(CliError::ActionError(_))));
+    }
+}
@@ -24,15 +24,18 @@ use super::{Pageable, RBAC_PROTOCOL_VERSION};
 #[serde(tag = "identity_type", content = "identity")]
 #[serde(rename_all = "lowercase")]
 pub enum Identity {
+    #[serde(rename = "api_key")]
+    ApiKey(String),
     Key(String),
     User(String),
 }
 
 impl Identity {
     /// Returns a tuple of the parts (id, id_type)
-    /// Type can be "key" or "user"
+    /// Type can be "api_key", "key" or "user"
     pub fn parts(&self) -> (&str, &str) {
         match self {
+            Identity::ApiKey(api_key) => (api_key, "api_key"),
             Identity::Key(key) => (key, "key"),
             Identity::User(user) => (user, "user"),
         }
@@ -44,6 +47,8 @@ pub struct Assignment {
     #[serde(flatten)]
     pub identity: Identity,
     pub roles: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub circuits: Vec<String>,
 }
 
 impl Pageable for Assignment {
@@ -62,6 +67,7 @@ struct AssignmentGet {
 pub struct AssignmentBuilder {
     identity: Option<Identity>,
     roles: Vec<String>,
+    circuits: Vec<String>,
 }
 
 impl AssignmentBuilder {
@@ -75,8 +81,17 @@ impl AssignmentBuilder {
         self
     }
 
+    pub fn with_circuits(mut self, circuits: Vec<String>) -> Self {
+        self.circuits = circuits;
+        self
+    }
+
     pub fn build(self) -> Result<Assignment, CliError> {
-        let AssignmentBuilder { identity, roles } = self;
+        let AssignmentBuilder {
+            identity,
+            roles,
+            circuits,
+        } = self;
 
         if roles.is_empty() {
             return Err(CliError::ActionError(
@@ -88,6 +103,13 @@ impl AssignmentBuilder {
         })?;
 
         match &identity {
+            Identity::ApiKey(api_key) => {
+                if api_key.is_empty() {
+                    return Err(CliError::ActionError(
+                        "An API key ID must not be empty".into(),
+                    ));
+                }
+            }
             Identity::Key(key) => {
                 if key.is_empty() {
                     return Err(CliError::ActionError("A key must not be empty".into()));
@@ -100,7 +122,17 @@ impl AssignmentBuilder {
             }
         }
 
-        Ok(Assignment { identity, roles })
+        if circuits.iter().any(|circuit| circuit.is_empty()) {
+            return Err(CliError::ActionError(
+                "A circuit pattern must not be empty".into(),
+            ));
+        }
+
+        Ok(Assignment {
+            identity,
+            roles,
+            circuits,
+        })
     }
 }
 
@@ -110,12 +142,15 @@ pub struct AssignmentUpdate {
     identity: Identity,
     #[serde(skip_serializing_if = "Option::is_none")]
     roles: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    circuits: Option<Vec<String>>,
 }
 
 #[derive(Default)]
 pub struct AssignmentUpdateBuilder {
     identity: Option<Identity>,
     roles: Option<Vec<String>>,
+    circuits: Option<Vec<String>>,
 }
 
 impl AssignmentUpdateBuilder {
@@ -129,8 +164,17 @@ impl AssignmentUpdateBuilder {
         self
     }
 
+    pub fn with_circuits(mut self, circuits: Option<Vec<String>>) -> Self {
+        self.circuits = circuits;
+        self
+    }
+
     pub fn build(self) -> Result<AssignmentUpdate, CliError> {
-        let AssignmentUpdateBuilder { identity, roles } = self;
+        let AssignmentUpdateBuilder {
+            identity,
+            roles,
+            circuits,
+        } = self;
 
         let identity = identity.ok_or_else(|| {
             CliError::ActionError("An assignment must have an associated identity".into())
@@ -144,7 +188,19 @@ impl AssignmentUpdateBuilder {
             }
         }
 
-        Ok(AssignmentUpdate { identity, roles })
+        if let Some(circuits) = circuits.as_ref() {
+            if circuits.iter().any(|circuit| circuit.is_empty()) {
+                return Err(CliError::ActionError(
+                    "A circuit pattern must not be empty".into(),
+                ));
+            }
+        }
+
+        Ok(AssignmentUpdate {
+            identity,
+            roles,
+            circuits,
+        })
     }
 }
 
@@ -346,10 +402,13 @@ mod tests {
     /// Tests the assignment builder in both Ok and Err scenarios
     /// 1. Construct a valid assignment (key)
     /// 2. Construct a valid assignment (user)
-    /// 3. Fail with no identity
-    /// 4. Fail with empty identity value (key)
-    /// 5. Fail with empty identity value (user)
-    /// 6. Fail with empty roles
+    /// 3. Construct a valid assignment (API key)
+    /// 4. Fail with no identity
+    /// 5. Fail with empty identity value (key)
+    /// 6. Fail with empty identity value (user)
+    /// 7. Fail with empty roles
+    /// 8. Construct a valid assignment with circuits
+    /// 9. Fail with an empty circuit pattern
     #[test]
     fn test_assignment_builder() {
         // Valid assignment with key
@@ -378,6 +437,17 @@ mod tests {
             assignment.roles
         );
 
+        // Valid assignment with API key
+        let assignment = AssignmentBuilder::default()
+            .with_identity(Identity::ApiKey("0123456789abcdef".into()))
+            .with_roles(vec!["role1".to_string()])
+            .build()
+            .expect("Could not build a valid role");
+
+        assert!(
+            matches!(assignment.identity, Identity::ApiKey(key_id) if key_id == "0123456789abcdef")
+        );
+
         // Fail with missing identity
         let res = AssignmentBuilder::default()
             .with_roles(vec!["role1".to_string(), "role2".to_string()])
@@ -403,6 +473,27 @@ mod tests {
             .with_identity(Identity::Key("abcd".into()))
             .build();
         assert!(res.is_err());
+
+        // Valid assignment with circuits
+        let assignment = AssignmentBuilder::default()
+            .with_identity(Identity::Key("abcd".into()))
+            .with_roles(vec!["role1".to_string()])
+            .with_circuits(vec!["abcde-01234".to_string(), "fghij-*".to_string()])
+            .build()
+            .expect("Could not build a valid role");
+
+        assert_eq!(
+            vec!["abcde-01234".to_string(), "fghij-*".to_string()],
+            assignment.circuits
+        );
+
+        // Fail with empty circuit pattern
+        let res = AssignmentBuilder::default()
+            .with_identity(Identity::Key("abcd".into()))
+            .with_roles(vec!["role1".to_string()])
+            .with_circuits(vec![String::new()])
+            .build();
+        assert!(res.is_err());
     }
 
     /// Tests the assignment builder in both Ok and Err scenarios
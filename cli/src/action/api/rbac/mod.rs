@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "api-keys")]
+pub mod api_keys;
 pub mod assignments;
 pub mod roles;
 
@@ -1,4 +1,4 @@
-// Copyright 2018-2020 Cargill Incorporated
+// Copyright 2018-2021 Cargill Incorporated
 // Copyright 2018 Intel Corporation
 //
 // Licensed under the Apache License, Version 2.0 (the "License");
@@ -13,6 +13,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use clap::ArgMatches;
 
 use crate::error::CliError;
@@ -23,16 +25,39 @@ use super::{
     create_cylinder_jwt_auth, Action, DEFAULT_SPLINTER_REST_API_URL, SPLINTER_REST_API_URL_ENV,
 };
 
+const SECONDS_PER_MINUTE: u64 = 60;
+
 pub struct StatusAction;
 
 impl Action for StatusAction {
     fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
-        let status = if new_client(arg_matches)?.is_maintenance_mode_enabled()? {
+        let client = new_client(arg_matches)?;
+        let status = if client.is_maintenance_mode_enabled()? {
             "enabled"
         } else {
             "disabled"
         };
         println!("Maintenance mode is currently {}", status);
+
+        if let Some(change) = client.list_maintenance_mode_changes()?.pop() {
+            println!(
+                "Last {} by {} at {}",
+                if change.enabled {
+                    "enabled"
+                } else {
+                    "disabled"
+                },
+                change.changed_by,
+                change.changed_at
+            );
+            if let Some(reason) = change.reason {
+                println!("Reason: {}", reason);
+            }
+            if let Some(ends_at) = change.ends_at {
+                println!("Scheduled to end at {}", ends_at);
+            }
+        }
+
         Ok(())
     }
 }
@@ -41,8 +66,33 @@ pub struct EnableAction;
 
 impl Action for EnableAction {
     fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
-        new_client(arg_matches)?.set_maintenance_mode(true)?;
-        println!("Maintenance mode has been enabled");
+        let ends_at = match arg_matches.and_then(|args| args.value_of("duration")) {
+            Some(duration) => {
+                let minutes = duration
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|minutes| *minutes > 0)
+                    .ok_or_else(|| {
+                        CliError::ActionError(
+                            "The duration must be a positive number of minutes".into(),
+                        )
+                    })?;
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_err(|err| {
+                        CliError::ActionError(format!("Unable to get current time: {}", err))
+                    })?
+                    .as_secs();
+                Some(now.saturating_add(minutes.saturating_mul(SECONDS_PER_MINUTE)))
+            }
+            None => None,
+        };
+
+        new_client(arg_matches)?.set_maintenance_mode(true, reason(arg_matches), ends_at)?;
+        match ends_at {
+            Some(ends_at) => println!("Maintenance mode has been enabled until {}", ends_at),
+            None => println!("Maintenance mode has been enabled"),
+        }
         Ok(())
     }
 }
@@ -51,12 +101,16 @@ pub struct DisableAction;
 
 impl Action for DisableAction {
     fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
-        new_client(arg_matches)?.set_maintenance_mode(false)?;
+        new_client(arg_matches)?.set_maintenance_mode(false, reason(arg_matches), None)?;
         println!("Maintenance mode has been disabled");
         Ok(())
     }
 }
 
+fn reason<'a>(arg_matches: Option<&'a ArgMatches<'_>>) -> Option<&'a str> {
+    arg_matches.and_then(|args| args.value_of("reason"))
+}
+
 fn new_client(arg_matches: Option<&ArgMatches<'_>>) -> Result<SplinterRestClient, CliError> {
     let url = arg_matches
         .and_then(|args| args.value_of("url"))
@@ -0,0 +1,156 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap::ArgMatches;
+
+use crate::action::{api::NewApiKeyBuilder, print_table, Action};
+use crate::error::CliError;
+
+use super::new_client;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+pub struct ListApiKeysAction;
+
+impl Action for ListApiKeysAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let format = arg_matches
+            .and_then(|args| args.value_of("format"))
+            .unwrap_or("human");
+
+        let client = new_client(&arg_matches)?;
+
+        let mut api_keys = client
+            .list_api_keys()?
+            .map(|res| {
+                res.map(|api_key| {
+                    vec![
+                        api_key.id,
+                        api_key.display_name,
+                        api_key.scopes.len().to_string(),
+                        api_key.expired.to_string(),
+                    ]
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let header = vec![
+            "ID".to_string(),
+            "NAME".to_string(),
+            "SCOPES".to_string(),
+            "EXPIRED".to_string(),
+        ];
+        if format == "csv" {
+            println!("{}", header.join(","));
+            for row in api_keys {
+                println!("{}", row.join(","));
+            }
+        } else {
+            let mut rows = vec![header];
+            rows.append(&mut api_keys);
+            print_table(rows);
+        }
+
+        Ok(())
+    }
+}
+
+pub struct ShowApiKeyAction;
+
+impl Action for ShowApiKeyAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let format = arg_matches
+            .and_then(|args| args.value_of("format"))
+            .unwrap_or("human");
+
+        let key_id = arg_matches
+            .and_then(|args| args.value_of("key_id"))
+            .ok_or_else(|| CliError::ActionError("An API key ID must be specified".into()))?;
+
+        let api_key = new_client(&arg_matches)?.get_api_key(key_id)?;
+
+        match format {
+            "json" => println!(
+                "\n {}",
+                serde_json::to_string(&api_key).map_err(|err| CliError::ActionError(format!(
+                    "Cannot format API key into json: {}",
+                    err
+                )))?
+            ),
+            "yaml" => println!(
+                "{}",
+                serde_yaml::to_string(&api_key).map_err(|err| CliError::ActionError(format!(
+                    "Cannot format API key into yaml: {}",
+                    err
+                )))?
+            ),
+            _ => println!("{}", api_key),
+        }
+
+        Ok(())
+    }
+}
+
+pub struct CreateApiKeyAction;
+
+impl Action for CreateApiKeyAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let display_name = arg_matches
+            .and_then(|args| args.value_of("display_name"))
+            .ok_or_else(|| CliError::ActionError("An API key must have a display name".into()))?;
+
+        let scopes = arg_matches
+            .and_then(|args| args.values_of("scope"))
+            .ok_or_else(|| CliError::ActionError("An API key must have at least one scope".into()))?
+            .map(|s| s.to_owned())
+            .collect();
+
+        let expires_in_days = arg_matches
+            .and_then(|args| args.value_of("expires_in"))
+            .ok_or_else(|| CliError::ActionError("An API key must have an expiration".into()))?
+            .parse::<u64>()
+            .map_err(|_| {
+                CliError::ActionError("The expiration must be a positive number of days".into())
+            })?;
+
+        let created = new_client(&arg_matches)?.create_api_key(
+            NewApiKeyBuilder::default()
+                .with_display_name(display_name.into())
+                .with_scopes(scopes)
+                .with_expires_in(expires_in_days.saturating_mul(SECONDS_PER_DAY))
+                .build()?,
+        )?;
+
+        println!("{}", created.api_key);
+        println!(
+            "\nToken: {}\n\nThe token will not be shown again; present it to the REST API as \
+             \"Authorization: Bearer ApiKey:<token>\".",
+            created.token
+        );
+
+        Ok(())
+    }
+}
+
+pub struct RevokeApiKeyAction;
+
+impl Action for RevokeApiKeyAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let key_id = arg_matches
+            .and_then(|args| args.value_of("key_id"))
+            .ok_or_else(|| CliError::ActionError("An API key ID must be specified".into()))?;
+
+        new_client(&arg_matches)?.revoke_api_key(key_id)
+    }
+}
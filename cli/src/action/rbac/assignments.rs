@@ -43,6 +43,11 @@ impl Action for ListAssignmentsAction {
                         id.to_string(),
                         id_type.to_string(),
                         assignment.roles.len().to_string(),
+                        if assignment.circuits.is_empty() {
+                            "all".to_string()
+                        } else {
+                            assignment.circuits.len().to_string()
+                        },
                     ]
                 })
             })
@@ -52,6 +57,7 @@ impl Action for ListAssignmentsAction {
             "IDENTITY".to_string(),
             "TYPE".to_string(),
             "ROLES".to_string(),
+            "CIRCUITS".to_string(),
         ];
         if format == "csv" {
             println!("{}", header.join(","));
@@ -80,10 +86,16 @@ impl Action for CreateAssignmentAction {
             .map(|s| s.to_owned())
             .collect();
 
+        let circuits = arg_matches
+            .and_then(|args| args.values_of("circuit"))
+            .map(|vals| vals.map(|s| s.to_owned()).collect())
+            .unwrap_or_else(Vec::new);
+
         new_client(&arg_matches)?.create_assignment(
             AssignmentBuilder::default()
                 .with_identity(identity)
                 .with_roles(roles)
+                .with_circuits(circuits)
                 .build()?,
         )
     }
@@ -150,11 +162,23 @@ impl Action for UpdateAssignmentAction {
             )
         };
 
+        let circuits_to_add = arg_matches
+            .and_then(|args| args.values_of("add_circuit"))
+            .map(|vals| vals.map(|s| s.to_owned()).collect())
+            .unwrap_or_else(Vec::new);
+
+        let circuits_to_rm = arg_matches
+            .and_then(|args| args.values_of("rm_circuit"))
+            .map(|vals| vals.map(|s| s.to_owned()).collect())
+            .unwrap_or_else(Vec::new);
+
         update_assignment(
             new_client(&arg_matches)?,
             identity,
             roles_to_add,
             role_removal,
+            circuits_to_add,
+            circuits_to_rm,
             force,
         )
     }
@@ -170,10 +194,23 @@ fn update_assignment(
     identity: Identity,
     roles_to_add: Vec<String>,
     role_removal: RoleRemoval,
+    circuits_to_add: Vec<String>,
+    circuits_to_rm: Vec<String>,
     force: bool,
 ) -> Result<(), CliError> {
     let assignment = client.get_assignment(&identity)?;
 
+    let circuits = if circuits_to_add.is_empty() && circuits_to_rm.is_empty() {
+        None
+    } else {
+        Some(update_circuits(
+            &assignment.circuits,
+            circuits_to_add,
+            circuits_to_rm,
+            force,
+        )?)
+    };
+
     let roles = match role_removal {
         RoleRemoval::RemoveAll => {
             println!("Removing roles {}", assignment.roles.join(", "));
@@ -223,10 +260,65 @@ fn update_assignment(
         AssignmentUpdateBuilder::default()
             .with_identity(identity)
             .with_roles(Some(roles))
+            .with_circuits(circuits)
             .build()?,
     )
 }
 
+/// Applies the circuit pattern additions and removals to an assignment's current circuits.
+///
+/// Removing every circuit pattern from an assignment makes it apply to the whole node, so this is
+/// only allowed with `force`.
+fn update_circuits(
+    current_circuits: &[String],
+    circuits_to_add: Vec<String>,
+    circuits_to_rm: Vec<String>,
+    force: bool,
+) -> Result<Vec<String>, CliError> {
+    let mut circuits_to_add = circuits_to_add.into_iter().collect::<BTreeSet<_>>();
+    let mut circuits_to_rm = circuits_to_rm.into_iter().collect::<BTreeSet<_>>();
+
+    if !force && circuits_to_add.intersection(&circuits_to_rm).count() > 0 {
+        return Err(CliError::ActionError(format!(
+            "Cannot add and remove the same circuits: {}",
+            circuits_to_add
+                .intersection(&circuits_to_rm)
+                .map(|s| s.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+    }
+
+    let mut circuits = current_circuits
+        .iter()
+        .filter(|circuit| !circuits_to_rm.remove(*circuit))
+        .cloned()
+        .collect::<BTreeSet<_>>();
+
+    circuits.append(&mut circuits_to_add);
+
+    if !force && !circuits_to_rm.is_empty() {
+        return Err(CliError::ActionError(format!(
+            "Cannot remove circuits that do not belong to the assignment: {}",
+            circuits_to_rm
+                .iter()
+                .map(|s| s.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+    }
+
+    if !force && circuits.is_empty() && !current_circuits.is_empty() {
+        return Err(CliError::ActionError(
+            "Removing all of the circuits would make the assignment apply to the whole node; \
+            use --force to do so"
+                .into(),
+        ));
+    }
+
+    Ok(circuits.into_iter().collect())
+}
+
 pub struct DeleteAssignmentAction;
 
 impl Action for DeleteAssignmentAction {
@@ -244,6 +336,14 @@ fn display_human_readable(assignment: &Assignment) {
     for role in &assignment.roles {
         println!("        {}", role);
     }
+    if assignment.circuits.is_empty() {
+        println!("    Circuits: all");
+    } else {
+        println!("    Circuits:");
+        for circuit in &assignment.circuits {
+            println!("        {}", circuit);
+        }
+    }
 }
 
 fn get_identity_arg<'a>(arg_matches: &Option<&ArgMatches<'a>>) -> Result<Identity, CliError> {
@@ -261,7 +361,14 @@ fn get_identity_arg<'a>(arg_matches: &Option<&ArgMatches<'a>>) -> Result<Identit
         return Ok(Identity::User(user_id));
     }
 
+    if let Some(key_id) = arg_matches
+        .and_then(|args| args.value_of("id_api_key"))
+        .map(|s| s.to_string())
+    {
+        return Ok(Identity::ApiKey(key_id));
+    }
+
     Err(CliError::ActionError(
-        "Must specify either key or user identity".into(),
+        "Must specify either key, user, or API key identity".into(),
     ))
 }
@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "api-keys")]
+mod api_keys;
 mod assignments;
 mod roles;
 
@@ -24,6 +26,8 @@ use crate::action::{
 use crate::error::CliError;
 use crate::signing::load_signer;
 
+#[cfg(feature = "api-keys")]
+pub use api_keys::{CreateApiKeyAction, ListApiKeysAction, RevokeApiKeyAction, ShowApiKeyAction};
 pub use assignments::{
     CreateAssignmentAction, DeleteAssignmentAction, ListAssignmentsAction, ShowAssignmentAction,
     UpdateAssignmentAction,
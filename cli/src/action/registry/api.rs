@@ -12,10 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::convert::TryFrom;
 use std::fmt;
 
 use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
+use splinter::registry::Node;
 use std::collections::HashMap;
 
 use crate::action::api::{ServerError, SplinterRestClient};
@@ -55,6 +57,53 @@ impl SplinterRestClient {
             })
     }
 
+    /// Retrieves all nodes in the registry, following the pages of the node list.
+    pub fn list_nodes(&self) -> Result<Vec<RegistryNode>, CliError> {
+        let mut nodes = vec![];
+        let mut link = "/registry/nodes".to_string();
+
+        loop {
+            let page = Client::new()
+                .get(&format!("{}{}", self.url, link))
+                .header("Authorization", &self.auth)
+                .send()
+                .map_err(|err| CliError::ActionError(format!("Failed to list nodes: {}", err)))
+                .and_then(|res| {
+                    let status = res.status();
+                    if status.is_success() {
+                        res.json::<NodeListPage>().map_err(|_| {
+                            CliError::ActionError(
+                                "Request was successful, but received an invalid response".into(),
+                            )
+                        })
+                    } else {
+                        let message = res
+                            .json::<ServerError>()
+                            .map_err(|_| {
+                                CliError::ActionError(format!(
+                                    "Registry list nodes request failed with status code '{}', \
+                                     but error response was not valid",
+                                    status
+                                ))
+                            })?
+                            .message;
+
+                        Err(CliError::ActionError(format!(
+                            "Failed to list nodes: {}",
+                            message
+                        )))
+                    }
+                })?;
+
+            nodes.extend(page.data);
+
+            if page.paging.offset + page.paging.limit >= page.paging.total {
+                break Ok(nodes);
+            }
+            link = page.paging.next;
+        }
+    }
+
     /// Retrieves the node with the given identity from the registry.
     pub fn get_node(&self, identity: &str) -> Result<Option<RegistryNode>, CliError> {
         let request = Client::new()
@@ -99,6 +148,52 @@ pub struct RegistryNode {
     pub display_name: String,
     pub keys: Vec<String>,
     pub metadata: HashMap<String, String>,
+    #[cfg(feature = "registry-signed-nodes")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    #[cfg(feature = "registry-signed-nodes")]
+    #[serde(default)]
+    pub version: u64,
+}
+
+#[cfg(feature = "registry")]
+impl TryFrom<RegistryNode> for Node {
+    type Error = CliError;
+
+    fn try_from(node: RegistryNode) -> Result<Self, Self::Error> {
+        let identity = node.identity.clone();
+        let mut builder = Node::builder(node.identity)
+            .with_endpoints(node.endpoints)
+            .with_display_name(node.display_name)
+            .with_keys(node.keys);
+        for (key, value) in node.metadata {
+            builder = builder.with_metadata(key, value);
+        }
+        #[cfg(feature = "registry-signed-nodes")]
+        {
+            if let Some(signature) = node.signature {
+                builder = builder.with_signature(signature);
+            }
+            builder = builder.with_version(node.version);
+        }
+        builder.build().map_err(|err| {
+            CliError::ActionError(format!("Registry node '{}' is invalid: {}", identity, err))
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct NodeListPage {
+    data: Vec<RegistryNode>,
+    paging: NodeListPaging,
+}
+
+#[derive(Deserialize)]
+struct NodeListPaging {
+    next: String,
+    total: usize,
+    limit: usize,
+    offset: usize,
 }
 
 #[cfg(feature = "registry")]
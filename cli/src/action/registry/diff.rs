@@ -0,0 +1,163 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Comparison of the nodes in two registries.
+
+use std::fmt;
+
+use splinter::registry::Node;
+
+/// A difference between two registries.
+#[derive(Debug, PartialEq)]
+pub enum NodeDiff {
+    /// The node is only in the first registry
+    Removed(Node),
+    /// The node is only in the second registry
+    Added(Node),
+    /// The node is in both registries, but differs; holds the first and second versions
+    Changed(Node, Node),
+}
+
+/// Returns the differences between the `source` and `target` registries. Nodes are matched by
+/// identity, and the differences are sorted by identity.
+pub fn diff_nodes(source: &[Node], target: &[Node]) -> Vec<NodeDiff> {
+    let mut diffs = source
+        .iter()
+        .filter_map(
+            |node| match target.iter().find(|n| n.identity() == node.identity()) {
+                None => Some(NodeDiff::Removed(node.clone())),
+                Some(target_node) if target_node != node => {
+                    Some(NodeDiff::Changed(node.clone(), target_node.clone()))
+                }
+                Some(_) => None,
+            },
+        )
+        .chain(
+            target
+                .iter()
+                .filter(|node| !source.iter().any(|n| n.identity() == node.identity()))
+                .map(|node| NodeDiff::Added(node.clone())),
+        )
+        .collect::<Vec<_>>();
+
+    diffs.sort_by(|a, b| a.identity().cmp(b.identity()));
+    diffs
+}
+
+impl NodeDiff {
+    fn identity(&self) -> &str {
+        match self {
+            NodeDiff::Removed(node) | NodeDiff::Added(node) | NodeDiff::Changed(node, _) => {
+                node.identity()
+            }
+        }
+    }
+}
+
+impl fmt::Display for NodeDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NodeDiff::Removed(node) => write!(f, "- {}", node.identity()),
+            NodeDiff::Added(node) => write!(f, "+ {}", node.identity()),
+            NodeDiff::Changed(source, target) => {
+                write!(f, "~ {}", source.identity())?;
+                if source.display_name() != target.display_name() {
+                    write!(
+                        f,
+                        "\n    display name: {} -> {}",
+                        source.display_name(),
+                        target.display_name()
+                    )?;
+                }
+                if source.endpoints() != target.endpoints() {
+                    write!(
+                        f,
+                        "\n    endpoints: {:?} -> {:?}",
+                        source.endpoints(),
+                        target.endpoints()
+                    )?;
+                }
+                if source.keys() != target.keys() {
+                    write!(f, "\n    keys: {:?} -> {:?}", source.keys(), target.keys())?;
+                }
+                let mut metadata_keys = source
+                    .metadata()
+                    .keys()
+                    .chain(target.metadata().keys())
+                    .collect::<Vec<_>>();
+                metadata_keys.sort();
+                metadata_keys.dedup();
+                for key in metadata_keys {
+                    let (source_value, target_value) =
+                        (source.metadata().get(key), target.metadata().get(key));
+                    if source_value != target_value {
+                        write!(
+                            f,
+                            "\n    metadata {}: {} -> {}",
+                            key,
+                            source_value.map(String::as_str).unwrap_or("<none>"),
+                            target_value.map(String::as_str).unwrap_or("<none>")
+                        )?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_node(identity: &str, endpoint: &str, company: &str) -> Node {
+        Node::builder(identity)
+            .with_endpoint(endpoint)
+            .with_key("abcd")
+            .with_metadata("company", company)
+            .build()
+            .expect("Failed to build node")
+    }
+
+    /// Verify that the diff of two registries lists the removed, added, and changed nodes sorted
+    /// by identity, and leaves out nodes that are the same in both.
+    #[test]
+    fn diff() {
+        let node_1 = new_node("node-1", "tcps://localhost:8044", "Cargill");
+        let node_2 = new_node("node-2", "tcps://localhost:8045", "Cargill");
+        let node_2_changed = new_node("node-2", "tcps://localhost:8046", "Bitwise IO");
+        let node_3 = new_node("node-3", "tcps://localhost:8047", "Cargill");
+        let node_4 = new_node("node-4", "tcps://localhost:8048", "Cargill");
+
+        let diffs = diff_nodes(
+            &[node_3.clone(), node_2.clone(), node_1.clone()],
+            &[node_4.clone(), node_2_changed.clone(), node_3],
+        );
+
+        assert_eq!(
+            diffs,
+            vec![
+                NodeDiff::Removed(node_1),
+                NodeDiff::Changed(node_2, node_2_changed),
+                NodeDiff::Added(node_4),
+            ]
+        );
+        assert_eq!(
+            diffs[1].to_string(),
+            "~ node-2\n    \
+             endpoints: [\"tcps://localhost:8045\"] -> [\"tcps://localhost:8046\"]\n    \
+             metadata company: Cargill -> Bitwise IO"
+        );
+    }
+}
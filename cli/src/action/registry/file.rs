@@ -0,0 +1,270 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reading and writing registry files, which hold a list of nodes in YAML or JSON.
+
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use splinter::registry::{Node, YamlNode};
+
+use crate::action::msg_from_io_error;
+use crate::error::CliError;
+
+/// The format of a registry file.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RegistryFileFormat {
+    Yaml,
+    Json,
+}
+
+impl RegistryFileFormat {
+    /// Returns the given format if there is one, otherwise guesses the format from the file's
+    /// extension; files that do not end in `.json` are assumed to be YAML.
+    pub fn from_args(format: Option<&str>, path: Option<&str>) -> Result<Self, CliError> {
+        match format {
+            Some(format) => format.parse(),
+            None => match path.and_then(|path| Path::new(path).extension()) {
+                Some(extension) if extension == "json" => Ok(RegistryFileFormat::Json),
+                _ => Ok(RegistryFileFormat::Yaml),
+            },
+        }
+    }
+}
+
+impl FromStr for RegistryFileFormat {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "yaml" => Ok(RegistryFileFormat::Yaml),
+            "json" => Ok(RegistryFileFormat::Json),
+            _ => Err(CliError::ActionError(format!(
+                "Unsupported registry file format '{}': must be 'yaml' or 'json'",
+                s
+            ))),
+        }
+    }
+}
+
+/// Reads the nodes in the registry file at the given path.
+pub fn read_nodes(path: &str, format: RegistryFileFormat) -> Result<Vec<Node>, CliError> {
+    let file = File::open(path).map_err(|err| {
+        CliError::ActionError(format!(
+            "Failed to open '{}': {}",
+            path,
+            msg_from_io_error(err)
+        ))
+    })?;
+
+    parse_nodes(file, path, format)
+}
+
+/// Parses the nodes of a registry file, which are all checked before any are returned: each node
+/// must be valid, and no two nodes may have the same identity or endpoint.
+fn parse_nodes<R: Read>(
+    reader: R,
+    path: &str,
+    format: RegistryFileFormat,
+) -> Result<Vec<Node>, CliError> {
+    let yaml_nodes: Vec<YamlNode> = match format {
+        RegistryFileFormat::Yaml => serde_yaml::from_reader(reader).map_err(|_| {
+            CliError::ActionError(format!(
+                "Failed to read registry file '{}': Not a valid YAML sequence of nodes",
+                path
+            ))
+        })?,
+        RegistryFileFormat::Json => serde_json::from_reader(reader).map_err(|_| {
+            CliError::ActionError(format!(
+                "Failed to read registry file '{}': Not a valid JSON array of nodes",
+                path
+            ))
+        })?,
+    };
+
+    let nodes = yaml_nodes
+        .into_iter()
+        .map(|node| {
+            let identity = node.identity().to_string();
+            Node::try_from(node).map_err(|err| {
+                CliError::ActionError(format!(
+                    "Registry file '{}' has an invalid node '{}': {}",
+                    path, identity, err
+                ))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut identities = HashSet::new();
+    let mut endpoints = HashSet::new();
+    for node in &nodes {
+        if !identities.insert(node.identity()) {
+            return Err(CliError::ActionError(format!(
+                "Registry file '{}' has more than one node with identity '{}'",
+                path,
+                node.identity()
+            )));
+        }
+        if let Some(endpoint) = node
+            .endpoints()
+            .iter()
+            .find(|endpoint| !endpoints.insert(endpoint.as_str()))
+        {
+            return Err(CliError::ActionError(format!(
+                "Registry file '{}' has more than one node with endpoint '{}'",
+                path, endpoint
+            )));
+        }
+    }
+
+    Ok(nodes)
+}
+
+/// Writes the nodes to the given writer as a registry file.
+pub fn write_nodes<W: Write>(
+    nodes: Vec<Node>,
+    writer: &mut W,
+    format: RegistryFileFormat,
+) -> Result<(), CliError> {
+    let yaml_nodes = nodes.into_iter().map(YamlNode::from).collect::<Vec<_>>();
+
+    let bytes = match format {
+        RegistryFileFormat::Yaml => serde_yaml::to_vec(&yaml_nodes).map_err(|err| {
+            CliError::ActionError(format!("Cannot format node list into yaml: {}", err))
+        })?,
+        RegistryFileFormat::Json => serde_json::to_vec_pretty(&yaml_nodes).map_err(|err| {
+            CliError::ActionError(format!("Cannot format node list into json: {}", err))
+        })?,
+    };
+
+    writer
+        .write_all(&bytes)
+        .and_then(|_| writeln!(writer))
+        .map_err(|err| {
+            CliError::ActionError(format!(
+                "Failed to write registry file: {}",
+                msg_from_io_error(err)
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_node(identity: &str, endpoint: &str) -> Node {
+        Node::builder(identity)
+            .with_endpoint(endpoint)
+            .with_key("abcd")
+            .with_metadata("company", "Cargill")
+            .build()
+            .expect("Failed to build node")
+    }
+
+    /// Verify that the format is taken from the arguments if given, and otherwise from the file's
+    /// extension.
+    #[test]
+    fn format_from_args() {
+        assert_eq!(
+            RegistryFileFormat::from_args(Some("json"), Some("nodes.yaml")).unwrap(),
+            RegistryFileFormat::Json
+        );
+        assert_eq!(
+            RegistryFileFormat::from_args(None, Some("nodes.json")).unwrap(),
+            RegistryFileFormat::Json
+        );
+        assert_eq!(
+            RegistryFileFormat::from_args(None, Some("nodes")).unwrap(),
+            RegistryFileFormat::Yaml
+        );
+        assert_eq!(
+            RegistryFileFormat::from_args(None, None).unwrap(),
+            RegistryFileFormat::Yaml
+        );
+        assert!(RegistryFileFormat::from_args(Some("toml"), None).is_err());
+    }
+
+    /// Verify that nodes written in either format are read back unchanged.
+    #[test]
+    fn write_and_read() {
+        let nodes = vec![
+            new_node("node-1", "tcps://localhost:8044"),
+            new_node("node-2", "tcps://localhost:8045"),
+        ];
+
+        for format in &[RegistryFileFormat::Yaml, RegistryFileFormat::Json] {
+            let mut bytes = vec![];
+            write_nodes(nodes.clone(), &mut bytes, *format).expect("Failed to write nodes");
+            assert_eq!(
+                parse_nodes(&bytes[..], "nodes", *format).expect("Failed to read nodes"),
+                nodes
+            );
+        }
+    }
+
+    /// Verify that the signature and version of a signed node are written and read back.
+    #[cfg(feature = "registry-signed-nodes")]
+    #[test]
+    fn write_and_read_signed() {
+        let node = Node::builder("node-1")
+            .with_endpoint("tcps://localhost:8044")
+            .with_key("abcd")
+            .with_signature("0123")
+            .with_version(2)
+            .build()
+            .expect("Failed to build node");
+
+        let mut bytes = vec![];
+        write_nodes(vec![node.clone()], &mut bytes, RegistryFileFormat::Yaml)
+            .expect("Failed to write nodes");
+        let nodes =
+            parse_nodes(&bytes[..], "nodes", RegistryFileFormat::Yaml).expect("Failed to read");
+
+        assert_eq!(nodes[0].signature(), Some("0123"));
+        assert_eq!(nodes[0].version(), 2);
+        assert_eq!(nodes, vec![node]);
+    }
+
+    /// Verify that a file is rejected if it is malformed, has an invalid node, or has two nodes
+    /// with the same identity or endpoint.
+    #[test]
+    fn read_invalid() {
+        assert!(parse_nodes(&b"not a list"[..], "nodes", RegistryFileFormat::Yaml).is_err());
+        assert!(parse_nodes(&b"[{}]"[..], "nodes", RegistryFileFormat::Json).is_err());
+
+        let invalid = b"- identity: node-1\n  endpoints: []\n  display_name: Node 1\n  \
+                        keys: [abcd]\n  metadata: {}\n";
+        assert!(parse_nodes(&invalid[..], "nodes", RegistryFileFormat::Yaml).is_err());
+
+        for nodes in &[
+            vec![
+                new_node("node-1", "tcps://localhost:8044"),
+                new_node("node-1", "tcps://localhost:8045"),
+            ],
+            vec![
+                new_node("node-1", "tcps://localhost:8044"),
+                new_node("node-2", "tcps://localhost:8044"),
+            ],
+        ] {
+            let mut bytes = vec![];
+            write_nodes(nodes.clone(), &mut bytes, RegistryFileFormat::Yaml)
+                .expect("Failed to write nodes");
+            assert!(parse_nodes(&bytes[..], "nodes", RegistryFileFormat::Yaml).is_err());
+        }
+    }
+}
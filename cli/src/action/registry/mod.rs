@@ -14,20 +14,39 @@
 
 #[cfg(feature = "registry")]
 mod api;
+#[cfg(feature = "registry")]
+mod diff;
+#[cfg(feature = "registry")]
+mod file;
 
 use clap::ArgMatches;
+#[cfg(all(feature = "registry", feature = "database"))]
+use diesel::r2d2::{ConnectionManager, Pool};
+#[cfg(all(feature = "registry", feature = "database"))]
+use splinter::registry::{DieselRegistry, RegistryError, RegistryReader};
 use splinter::registry::{Node, YamlNode};
 #[cfg(feature = "registry")]
 use std::collections::HashMap;
+#[cfg(feature = "registry")]
+use std::convert::TryFrom;
 use std::fs::File;
 use std::io::{Read, Write};
 use std::path::Path;
+#[cfg(all(feature = "registry", feature = "database"))]
+use std::str::FromStr;
 
 use crate::error::CliError;
 #[cfg(feature = "registry")]
 use crate::registry::api::RegistryNode;
+#[cfg(feature = "registry")]
+use crate::registry::diff::diff_nodes;
+#[cfg(feature = "registry")]
+use crate::registry::file::{read_nodes, write_nodes, RegistryFileFormat};
 use crate::signing::load_signer;
 
+#[cfg(all(feature = "registry", feature = "database"))]
+use super::database::{get_default_database, ConnectionUri};
+
 use super::api::SplinterRestClientBuilder;
 use super::{msg_from_io_error, Action, DEFAULT_SPLINTER_REST_API_URL, SPLINTER_REST_API_URL_ENV};
 
@@ -220,6 +239,10 @@ impl Action for RegistryAddAction {
                 display_name: remote_node.display_name,
                 keys: remote_node.keys,
                 metadata: remote_node.metadata,
+                #[cfg(feature = "registry-signed-nodes")]
+                signature: remote_node.signature,
+                #[cfg(feature = "registry-signed-nodes")]
+                version: remote_node.version,
             };
 
             if !args.is_present("dry_run") {
@@ -252,6 +275,10 @@ impl Action for RegistryAddAction {
                 display_name,
                 keys,
                 metadata: node_metadata,
+                #[cfg(feature = "registry-signed-nodes")]
+                signature: None,
+                #[cfg(feature = "registry-signed-nodes")]
+                version: 0,
             };
 
             if !args.is_present("dry_run") {
@@ -265,6 +292,277 @@ impl Action for RegistryAddAction {
     }
 }
 
+#[cfg(feature = "registry")]
+pub struct RegistryExportAction;
+
+#[cfg(feature = "registry")]
+impl Action for RegistryExportAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+
+        let url = args
+            .value_of("url")
+            .map(ToOwned::to_owned)
+            .or_else(|| std::env::var(SPLINTER_REST_API_URL_ENV).ok())
+            .unwrap_or_else(|| DEFAULT_SPLINTER_REST_API_URL.to_string());
+
+        let output = args.value_of("output");
+        let format = RegistryFileFormat::from_args(args.value_of("format"), output)?;
+
+        let nodes = list_remote_nodes(url, args.value_of("private_key_file"))?;
+        let node_count = nodes.len();
+
+        match output {
+            Some(output) => {
+                let mut file = File::create(output).map_err(|err| {
+                    CliError::ActionError(format!(
+                        "Failed to create or overwrite '{}': {}",
+                        output,
+                        msg_from_io_error(err)
+                    ))
+                })?;
+                write_nodes(nodes, &mut file, format)?;
+                info!("Exported {} nodes to '{}'", node_count, output);
+            }
+            None => write_nodes(nodes, &mut std::io::stdout(), format)?,
+        }
+
+        Ok(())
+    }
+}
+
+/// How to handle nodes being imported that are already in the registry
+#[cfg(all(feature = "registry", feature = "database"))]
+#[derive(Clone, Copy, PartialEq)]
+enum ImportConflictStrategy {
+    /// Keep the existing node
+    Skip,
+    /// Replace the existing node
+    Overwrite,
+    /// Import nothing
+    Fail,
+}
+
+#[cfg(all(feature = "registry", feature = "database"))]
+impl FromStr for ImportConflictStrategy {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(ImportConflictStrategy::Skip),
+            "overwrite" => Ok(ImportConflictStrategy::Overwrite),
+            "fail" => Ok(ImportConflictStrategy::Fail),
+            _ => Err(CliError::ActionError(format!(
+                "Unsupported conflict strategy '{}': must be 'skip', 'overwrite', or 'fail'",
+                s
+            ))),
+        }
+    }
+}
+
+#[cfg(all(feature = "registry", feature = "database"))]
+pub struct RegistryImportAction;
+
+#[cfg(all(feature = "registry", feature = "database"))]
+impl Action for RegistryImportAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+
+        let path = args
+            .value_of("file")
+            .ok_or_else(|| CliError::ActionError("A registry file must be specified".into()))?;
+        let format = RegistryFileFormat::from_args(args.value_of("format"), Some(path))?;
+        let strategy = args
+            .value_of("on_conflict")
+            .unwrap_or("fail")
+            .parse::<ImportConflictStrategy>()?;
+        let url = match args.value_of("connect") {
+            Some(url) => url.to_owned(),
+            None => get_default_database()?,
+        };
+
+        // The whole file is read and checked before anything is written
+        let nodes = read_nodes(path, format)?;
+        let registry = open_local_registry(&url)?;
+        let counts = import_nodes(&*registry, nodes, strategy)?;
+
+        info!(
+            "Imported '{}': {} added, {} updated, {} skipped",
+            path, counts.added, counts.updated, counts.skipped
+        );
+
+        Ok(())
+    }
+}
+
+/// The number of nodes added, updated, and skipped by an import
+#[cfg(all(feature = "registry", feature = "database"))]
+#[derive(Debug, Default, PartialEq)]
+struct ImportCounts {
+    added: usize,
+    updated: usize,
+    skipped: usize,
+}
+
+/// Imports the nodes into the registry. The nodes that are already in the registry are found
+/// before anything is written, and then all of the nodes are written in a single transaction, so
+/// either every node is imported or none are.
+#[cfg(all(feature = "registry", feature = "database"))]
+fn import_nodes(
+    registry: &dyn ImportRegistry,
+    nodes: Vec<Node>,
+    strategy: ImportConflictStrategy,
+) -> Result<ImportCounts, CliError> {
+    let (mut added, mut updated, mut existing) = (vec![], vec![], vec![]);
+    for node in nodes {
+        if !registry
+            .has_node(node.identity())
+            .map_err(|err| CliError::ActionError(format!("Failed to check node: {}", err)))?
+        {
+            added.push(node);
+            continue;
+        }
+
+        existing.push(node.identity().to_string());
+        match strategy {
+            ImportConflictStrategy::Overwrite => updated.push(node),
+            ImportConflictStrategy::Skip => debug!("Skipping existing node '{}'", node.identity()),
+            ImportConflictStrategy::Fail => (),
+        }
+    }
+
+    if strategy == ImportConflictStrategy::Fail && !existing.is_empty() {
+        return Err(CliError::ActionError(format!(
+            "Nodes already exist in the registry: {}; use '--on-conflict skip' or \
+             '--on-conflict overwrite' to import the file",
+            existing.join(", ")
+        )));
+    }
+
+    let counts = ImportCounts {
+        added: added.len(),
+        updated: updated.len(),
+        skipped: existing.len() - updated.len(),
+    };
+
+    registry
+        .import(added, updated)
+        .map_err(|err| CliError::ActionError(format!("Failed to import nodes: {}", err)))?;
+
+    Ok(counts)
+}
+
+/// A database registry that nodes can be imported into.
+#[cfg(all(feature = "registry", feature = "database"))]
+trait ImportRegistry: RegistryReader {
+    /// Adds the `added` nodes and replaces the `updated` nodes in a single transaction.
+    fn import(&self, added: Vec<Node>, updated: Vec<Node>) -> Result<(), RegistryError>;
+}
+
+#[cfg(all(feature = "registry", feature = "postgres"))]
+impl ImportRegistry for DieselRegistry<diesel::pg::PgConnection> {
+    fn import(&self, added: Vec<Node>, updated: Vec<Node>) -> Result<(), RegistryError> {
+        self.import_nodes(added, updated)
+    }
+}
+
+#[cfg(all(feature = "registry", feature = "sqlite"))]
+impl ImportRegistry for DieselRegistry<diesel::sqlite::SqliteConnection> {
+    fn import(&self, added: Vec<Node>, updated: Vec<Node>) -> Result<(), RegistryError> {
+        self.import_nodes(added, updated)
+    }
+}
+
+#[cfg(feature = "registry")]
+pub struct RegistryDiffAction;
+
+#[cfg(feature = "registry")]
+impl Action for RegistryDiffAction {
+    fn run<'a>(&mut self, arg_matches: Option<&ArgMatches<'a>>) -> Result<(), CliError> {
+        let args = arg_matches.ok_or(CliError::RequiresArgs)?;
+
+        let private_key_file = args.value_of("private_key_file");
+        let source = args
+            .value_of("source")
+            .ok_or_else(|| CliError::ActionError("A source registry must be specified".into()))?;
+        let target = args
+            .value_of("target")
+            .ok_or_else(|| CliError::ActionError("A target registry must be specified".into()))?;
+
+        let diffs = diff_nodes(
+            &load_registry(source, private_key_file)?,
+            &load_registry(target, private_key_file)?,
+        );
+
+        if diffs.is_empty() {
+            info!("Registries '{}' and '{}' are the same", source, target);
+        }
+        for diff in diffs {
+            println!("{}", diff);
+        }
+
+        Ok(())
+    }
+}
+
+/// Loads the nodes of a registry, which is either a splinter REST API URL or a registry file.
+#[cfg(feature = "registry")]
+fn load_registry(location: &str, private_key_file: Option<&str>) -> Result<Vec<Node>, CliError> {
+    if location.starts_with("http://") || location.starts_with("https://") {
+        list_remote_nodes(location.to_string(), private_key_file)
+    } else {
+        read_nodes(
+            location,
+            RegistryFileFormat::from_args(None, Some(location))?,
+        )
+    }
+}
+
+/// Lists all nodes in the registry of the splinter REST API at the given URL.
+#[cfg(feature = "registry")]
+fn list_remote_nodes(url: String, private_key_file: Option<&str>) -> Result<Vec<Node>, CliError> {
+    let signer = load_signer(private_key_file)?;
+
+    let client = SplinterRestClientBuilder::new()
+        .with_url(url)
+        .with_auth(create_cylinder_jwt_auth(signer)?)
+        .build()?;
+
+    client
+        .list_nodes()?
+        .into_iter()
+        .map(Node::try_from)
+        .collect()
+}
+
+/// Opens the registry in the database at the given URL.
+#[cfg(all(feature = "registry", feature = "database"))]
+fn open_local_registry(url: &str) -> Result<Box<dyn ImportRegistry>, CliError> {
+    match ConnectionUri::from_str(url)? {
+        #[cfg(feature = "postgres")]
+        ConnectionUri::Postgres(url) => {
+            let connection_manager = ConnectionManager::<diesel::pg::PgConnection>::new(url);
+            let pool = Pool::builder()
+                .build(connection_manager)
+                .map_err(|_| CliError::ActionError("Failed to build connection pool".into()))?;
+            Ok(Box::new(DieselRegistry::new(pool)))
+        }
+        #[cfg(feature = "sqlite")]
+        ConnectionUri::Sqlite(connection_string) => {
+            let connection_manager =
+                ConnectionManager::<diesel::sqlite::SqliteConnection>::new(&connection_string);
+            let mut pool_builder = Pool::builder();
+            if connection_string == ":memory:" {
+                pool_builder = pool_builder.max_size(1)
+            }
+            let pool = pool_builder
+                .build(connection_manager)
+                .map_err(|_| CliError::ActionError("Failed to build connection pool".into()))?;
+            Ok(Box::new(DieselRegistry::new(pool)))
+        }
+    }
+}
+
 #[cfg(feature = "registry")]
 fn parse_metadata(metadata: &str) -> Result<(String, String), CliError> {
     let mut parts = metadata.splitn(2, ':');
@@ -311,3 +609,174 @@ fn read_private_key(file_name: &str) -> Result<String, CliError> {
 
     Ok(key)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(all(feature = "registry", feature = "sqlite"))]
+    use diesel::sqlite::SqliteConnection;
+    #[cfg(all(feature = "registry", feature = "sqlite"))]
+    use splinter::migrations::run_sqlite_migrations;
+    #[cfg(all(feature = "registry", feature = "sqlite"))]
+    use splinter::registry::RegistryWriter;
+
+    #[cfg(all(feature = "registry", feature = "sqlite"))]
+    fn new_node(identity: &str, endpoint: &str, company: &str) -> Node {
+        Node::builder(identity)
+            .with_endpoint(endpoint)
+            .with_key("abcd")
+            .with_metadata("company", company)
+            .build()
+            .expect("Failed to build node")
+    }
+
+    /// Creates a registry in an in-memory SQLite database that holds node-1.
+    #[cfg(all(feature = "registry", feature = "sqlite"))]
+    fn new_registry() -> DieselRegistry<SqliteConnection> {
+        let pool = Pool::builder()
+            .max_size(1)
+            .build(ConnectionManager::<SqliteConnection>::new(":memory:"))
+            .expect("Failed to build connection pool");
+        run_sqlite_migrations(&*pool.get().expect("Failed to get connection"))
+            .expect("Failed to run migrations");
+
+        let registry = DieselRegistry::new(pool);
+        registry
+            .add_node(new_node("node-1", "tcps://localhost:8044", "Cargill"))
+            .expect("Failed to add node");
+        registry
+    }
+
+    /// Verify that with the `skip` strategy, new nodes are added and the nodes already in the
+    /// registry are left as they are.
+    #[cfg(all(feature = "registry", feature = "sqlite"))]
+    #[test]
+    fn import_skip() {
+        let registry = new_registry();
+        let node_1 = new_node("node-1", "tcps://localhost:8044", "Bitwise IO");
+        let node_2 = new_node("node-2", "tcps://localhost:8045", "Cargill");
+
+        let counts = import_nodes(
+            &registry,
+            vec![node_1, node_2.clone()],
+            ImportConflictStrategy::Skip,
+        )
+        .expect("Failed to import nodes");
+
+        assert_eq!(
+            counts,
+            ImportCounts {
+                added: 1,
+                updated: 0,
+                skipped: 1
+            }
+        );
+        assert_eq!(
+            registry.get_node("node-1").expect("Failed to get node"),
+            Some(new_node("node-1", "tcps://localhost:8044", "Cargill"))
+        );
+        assert_eq!(
+            registry.get_node("node-2").expect("Failed to get node"),
+            Some(node_2)
+        );
+    }
+
+    /// Verify that with the `overwrite` strategy, new nodes are added and the nodes already in the
+    /// registry are replaced.
+    #[cfg(all(feature = "registry", feature = "sqlite"))]
+    #[test]
+    fn import_overwrite() {
+        let registry = new_registry();
+        let node_1 = new_node("node-1", "tcps://localhost:8044", "Bitwise IO");
+        let node_2 = new_node("node-2", "tcps://localhost:8045", "Cargill");
+
+        let counts = import_nodes(
+            &registry,
+            vec![node_1.clone(), node_2.clone()],
+            ImportConflictStrategy::Overwrite,
+        )
+        .expect("Failed to import nodes");
+
+        assert_eq!(
+            counts,
+            ImportCounts {
+                added: 1,
+                updated: 1,
+                skipped: 0
+            }
+        );
+        assert_eq!(
+            registry.get_node("node-1").expect("Failed to get node"),
+            Some(node_1)
+        );
+        assert_eq!(
+            registry.get_node("node-2").expect("Failed to get node"),
+            Some(node_2)
+        );
+    }
+
+    /// Verify that with the `fail` strategy, nothing is imported if any node is already in the
+    /// registry, and that nothing is imported if writing any one node fails.
+    #[cfg(all(feature = "registry", feature = "sqlite"))]
+    #[test]
+    fn import_fail() {
+        let registry = new_registry();
+
+        assert!(import_nodes(
+            &registry,
+            vec![
+                new_node("node-2", "tcps://localhost:8045", "Cargill"),
+                new_node("node-1", "tcps://localhost:8044", "Bitwise IO"),
+            ],
+            ImportConflictStrategy::Fail,
+        )
+        .is_err());
+        assert!(!registry.has_node("node-2").expect("Failed to check node"));
+
+        // node-3 takes node-1's endpoint, so it cannot be added
+        assert!(import_nodes(
+            &registry,
+            vec![
+                new_node("node-2", "tcps://localhost:8045", "Cargill"),
+                new_node("node-3", "tcps://localhost:8044", "Cargill"),
+            ],
+            ImportConflictStrategy::Fail,
+        )
+        .is_err());
+        assert!(!registry.has_node("node-2").expect("Failed to check node"));
+    }
+
+    /// Verify that exporting a node listed by the REST API keeps its signature and version.
+    #[cfg(feature = "registry-signed-nodes")]
+    #[test]
+    fn export_signed_node() {
+        let registry_node: RegistryNode = serde_json::from_str(
+            r#"{
+                "identity": "node-1",
+                "endpoints": ["tcps://localhost:8044"],
+                "display_name": "Node 1",
+                "keys": ["abcd"],
+                "metadata": {},
+                "signature": "0123",
+                "version": 2
+            }"#,
+        )
+        .expect("Failed to parse node");
+        let node = Node::try_from(registry_node).expect("Failed to convert node");
+
+        let mut bytes = vec![];
+        write_nodes(vec![node], &mut bytes, RegistryFileFormat::Yaml)
+            .expect("Failed to write nodes");
+        let yaml_nodes: Vec<YamlNode> =
+            serde_yaml::from_slice(&bytes).expect("Failed to parse export");
+        let nodes = yaml_nodes
+            .into_iter()
+            .map(Node::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Failed to convert exported nodes");
+
+        assert_eq!(nodes[0].signature(), Some("0123"));
+        assert_eq!(nodes[0].version(), 2);
+    }
+}
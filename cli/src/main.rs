@@ -862,6 +862,102 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
             ),
     );
 
+    #[cfg(feature = "registry")]
+    let registry_command = registry_command
+        .subcommand(
+            SubCommand::with_name("export")
+                .about("Export all nodes in a node's registry to a file")
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .takes_value(true)
+                        .possible_values(&["yaml", "json"])
+                        .help(
+                            "Format of the exported file; defaults to JSON if the output file \
+                             ends in '.json', YAML otherwise",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .short("o")
+                        .long("output")
+                        .takes_value(true)
+                        .help("Path of the file to write the nodes to; defaults to stdout"),
+                )
+                .arg(
+                    Arg::with_name("private_key_file")
+                        .value_name("private-key-file")
+                        .short("k")
+                        .long("key")
+                        .takes_value(true)
+                        .help("Name or path of private key to be used for REST API authorization"),
+                )
+                .arg(
+                    Arg::with_name("url")
+                        .short("U")
+                        .long("url")
+                        .takes_value(true)
+                        .help("URL of the splinter REST API"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("diff")
+                .about("Show the differences between the nodes in two registries")
+                .arg(
+                    Arg::with_name("source")
+                        .required(true)
+                        .help("Registry file or splinter REST API URL to compare from"),
+                )
+                .arg(
+                    Arg::with_name("target")
+                        .required(true)
+                        .help("Registry file or splinter REST API URL to compare to"),
+                )
+                .arg(
+                    Arg::with_name("private_key_file")
+                        .value_name("private-key-file")
+                        .short("k")
+                        .long("key")
+                        .takes_value(true)
+                        .help("Name or path of private key to be used for REST API authorization"),
+                ),
+        );
+
+    #[cfg(all(feature = "registry", feature = "database"))]
+    let registry_command = registry_command.subcommand(
+        SubCommand::with_name("import")
+            .about("Import the nodes in a file into the local database registry")
+            .arg(
+                Arg::with_name("file")
+                    .required(true)
+                    .help("Path of the registry file to import"),
+            )
+            .arg(
+                Arg::with_name("connect")
+                    .short("C")
+                    .takes_value(true)
+                    .help("Database connection URI"),
+            )
+            .arg(
+                Arg::with_name("format")
+                    .long("format")
+                    .takes_value(true)
+                    .possible_values(&["yaml", "json"])
+                    .help(
+                        "Format of the registry file; defaults to JSON if the file ends in \
+                         '.json', YAML otherwise",
+                    ),
+            )
+            .arg(
+                Arg::with_name("on_conflict")
+                    .long("on-conflict")
+                    .takes_value(true)
+                    .possible_values(&["skip", "overwrite", "fail"])
+                    .default_value("fail")
+                    .help("How to handle nodes that are already in the registry"),
+            ),
+    );
+
     app = app.subcommand(registry_command);
 
     #[cfg(feature = "health")]
@@ -956,6 +1052,22 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
                                 .long("key")
                                 .takes_value(true)
                                 .help("Name or path of private key"),
+                        )
+                        .arg(
+                            Arg::with_name("reason")
+                                .long("reason")
+                                .takes_value(true)
+                                .help("Reason for enabling maintenance mode"),
+                        )
+                        .arg(
+                            Arg::with_name("duration")
+                                .value_name("minutes")
+                                .long("duration")
+                                .takes_value(true)
+                                .help(
+                                    "Number of minutes after which maintenance mode is \
+                                     automatically disabled",
+                                ),
                         ),
                 )
                 .subcommand(
@@ -975,11 +1087,161 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
                                 .long("key")
                                 .takes_value(true)
                                 .help("Name or path of private key"),
+                        )
+                        .arg(
+                            Arg::with_name("reason")
+                                .long("reason")
+                                .takes_value(true)
+                                .help("Reason for disabling maintenance mode"),
                         ),
                 ),
         )
     }
 
+    #[cfg(feature = "api-keys")]
+    {
+        app = app.subcommand(
+            SubCommand::with_name("apikey")
+                .about("API key commands for machine clients of the REST API")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("list")
+                        .about("Lists the API keys issued by a Splinter node")
+                        .arg(
+                            Arg::with_name("url")
+                                .short("U")
+                                .long("url")
+                                .help("URL of the Splinter daemon REST API")
+                                .takes_value(true),
+                        )
+                        .arg(
+                            Arg::with_name("private_key_file")
+                                .value_name("private-key-file")
+                                .short("k")
+                                .long("key")
+                                .takes_value(true)
+                                .help("Name or path of private key"),
+                        )
+                        .arg(
+                            Arg::with_name("format")
+                                .short("F")
+                                .long("format")
+                                .help("Output format")
+                                .possible_values(&["human", "csv"])
+                                .default_value("human")
+                                .takes_value(true),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("show")
+                        .about("Show a specific API key issued by a Splinter node")
+                        .arg(
+                            Arg::with_name("url")
+                                .short("U")
+                                .long("url")
+                                .help("URL of the Splinter daemon REST API")
+                                .takes_value(true),
+                        )
+                        .arg(
+                            Arg::with_name("private_key_file")
+                                .value_name("private-key-file")
+                                .short("k")
+                                .long("key")
+                                .takes_value(true)
+                                .help("Name or path of private key"),
+                        )
+                        .arg(
+                            Arg::with_name("format")
+                                .short("F")
+                                .long("format")
+                                .help("Output format")
+                                .possible_values(&["human", "json", "yaml"])
+                                .default_value("human")
+                                .takes_value(true),
+                        )
+                        .arg(
+                            Arg::with_name("key_id")
+                                .required(true)
+                                .takes_value(true)
+                                .value_name("KEY ID")
+                                .help("ID of API key to be shown"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("create")
+                        .about("Create a new API key on a Splinter node and display its token")
+                        .arg(
+                            Arg::with_name("url")
+                                .short("U")
+                                .long("url")
+                                .help("URL of the Splinter daemon REST API")
+                                .takes_value(true),
+                        )
+                        .arg(
+                            Arg::with_name("private_key_file")
+                                .value_name("private-key-file")
+                                .short("k")
+                                .long("key")
+                                .takes_value(true)
+                                .help("Name or path of private key"),
+                        )
+                        .arg(
+                            Arg::with_name("display_name")
+                                .value_name("display-name")
+                                .short("D")
+                                .long("display")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Display name of the API key"),
+                        )
+                        .arg(
+                            Arg::with_name("scope")
+                                .value_name("permission")
+                                .long("scope")
+                                .takes_value(true)
+                                .multiple(true)
+                                .number_of_values(1)
+                                .required(true)
+                                .help("A permission that the API key may be used for"),
+                        )
+                        .arg(
+                            Arg::with_name("expires_in")
+                                .value_name("days")
+                                .long("expires-in")
+                                .takes_value(true)
+                                .default_value("90")
+                                .help("Number of days until the API key expires"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("revoke")
+                        .about("Revoke an API key on a Splinter node")
+                        .arg(
+                            Arg::with_name("url")
+                                .short("U")
+                                .long("url")
+                                .help("URL of the Splinter daemon REST API")
+                                .takes_value(true),
+                        )
+                        .arg(
+                            Arg::with_name("private_key_file")
+                                .value_name("private-key-file")
+                                .short("k")
+                                .long("key")
+                                .takes_value(true)
+                                .help("Name or path of private key"),
+                        )
+                        .arg(
+                            Arg::with_name("key_id")
+                                .required(true)
+                                .takes_value(true)
+                                .value_name("KEY ID")
+                                .help("ID of API key to be revoked"),
+                        ),
+                ),
+        );
+    }
+
     #[cfg(feature = "authorization-handler-rbac")]
     {
         app = app.subcommand(
@@ -1253,8 +1515,8 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
                                 .value_name("public-key")
                                 .long("id-key")
                                 .takes_value(true)
-                                .required_unless("id_user")
-                                .conflicts_with("id_user")
+                                .required_unless_one(&["id_user", "id_api_key"])
+                                .conflicts_with_all(&["id_user", "id_api_key"])
                                 .help("A public key identity to show"),
                         )
                         .arg(
@@ -1262,9 +1524,18 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
                                 .value_name("user-id")
                                 .long("id-user")
                                 .takes_value(true)
-                                .required_unless("id_key")
-                                .conflicts_with("id_key")
+                                .required_unless_one(&["id_key", "id_api_key"])
+                                .conflicts_with_all(&["id_key", "id_api_key"])
                                 .help("A user identity to show"),
+                        )
+                        .arg(
+                            Arg::with_name("id_api_key")
+                                .value_name("key-id")
+                                .long("id-api-key")
+                                .takes_value(true)
+                                .required_unless_one(&["id_key", "id_user"])
+                                .conflicts_with_all(&["id_key", "id_user"])
+                                .help("An API key identity to show"),
                         ),
                 )
                 .subcommand(
@@ -1290,8 +1561,8 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
                                 .value_name("public-key")
                                 .long("id-key")
                                 .takes_value(true)
-                                .required_unless("id_user")
-                                .conflicts_with("id_user")
+                                .required_unless_one(&["id_user", "id_api_key"])
+                                .conflicts_with_all(&["id_user", "id_api_key"])
                                 .help("The public key identity being assigned roles"),
                         )
                         .arg(
@@ -1299,10 +1570,19 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
                                 .value_name("user-id")
                                 .long("id-user")
                                 .takes_value(true)
-                                .required_unless("id_key")
-                                .conflicts_with("id_key")
+                                .required_unless_one(&["id_key", "id_api_key"])
+                                .conflicts_with_all(&["id_key", "id_api_key"])
                                 .help("The user identity being assigned roles"),
                         )
+                        .arg(
+                            Arg::with_name("id_api_key")
+                                .value_name("key-id")
+                                .long("id-api-key")
+                                .takes_value(true)
+                                .required_unless_one(&["id_key", "id_user"])
+                                .conflicts_with_all(&["id_key", "id_user"])
+                                .help("The API key identity being assigned roles"),
+                        )
                         .arg(
                             Arg::with_name("role")
                                 .value_name("role")
@@ -1312,6 +1592,19 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
                                 .number_of_values(1)
                                 .required(true)
                                 .help("A role to be assigned to the provided identity"),
+                        )
+                        .arg(
+                            Arg::with_name("circuit")
+                                .value_name("circuit")
+                                .long("circuit")
+                                .takes_value(true)
+                                .multiple(true)
+                                .number_of_values(1)
+                                .help(
+                                    "A circuit ID, or circuit ID pattern using '*', to limit the \
+                                    assignment to; by default, the assignment applies to the \
+                                    whole node",
+                                ),
                         ),
                 )
                 .subcommand(
@@ -1337,8 +1630,8 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
                                 .value_name("public-key")
                                 .long("id-key")
                                 .takes_value(true)
-                                .required_unless("id_user")
-                                .conflicts_with("id_user")
+                                .required_unless_one(&["id_user", "id_api_key"])
+                                .conflicts_with_all(&["id_user", "id_api_key"])
                                 .help("The public key identity being assigned roles"),
                         )
                         .arg(
@@ -1346,10 +1639,19 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
                                 .value_name("user-id")
                                 .long("id-user")
                                 .takes_value(true)
-                                .required_unless("id_key")
-                                .conflicts_with("id_key")
+                                .required_unless_one(&["id_key", "id_api_key"])
+                                .conflicts_with_all(&["id_key", "id_api_key"])
                                 .help("The user identity being assigned roles"),
                         )
+                        .arg(
+                            Arg::with_name("id_api_key")
+                                .value_name("key-id")
+                                .long("id-api-key")
+                                .takes_value(true)
+                                .required_unless_one(&["id_key", "id_user"])
+                                .conflicts_with_all(&["id_key", "id_user"])
+                                .help("The API key identity being assigned roles"),
+                        )
                         .arg(
                             Arg::with_name("add_role")
                                 .value_name("role")
@@ -1386,6 +1688,30 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
                                     identity",
                                 ),
                         )
+                        .arg(
+                            Arg::with_name("add_circuit")
+                                .value_name("circuit")
+                                .long("add-circuit")
+                                .takes_value(true)
+                                .multiple(true)
+                                .number_of_values(1)
+                                .help(
+                                    "A circuit ID, or circuit ID pattern using '*', to add to the \
+                                    circuits that the assignment is limited to",
+                                ),
+                        )
+                        .arg(
+                            Arg::with_name("rm_circuit")
+                                .value_name("circuit")
+                                .long("rm-circuit")
+                                .takes_value(true)
+                                .multiple(true)
+                                .number_of_values(1)
+                                .help(
+                                    "A circuit ID or pattern to remove from the circuits that the \
+                                    assignment is limited to",
+                                ),
+                        )
                 )
                 .subcommand(
                     SubCommand::with_name("delete")
@@ -1410,8 +1736,8 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
                                 .value_name("public-key")
                                 .long("id-key")
                                 .takes_value(true)
-                                .required_unless("id_user")
-                                .conflicts_with("id_user")
+                                .required_unless_one(&["id_user", "id_api_key"])
+                                .conflicts_with_all(&["id_user", "id_api_key"])
                                 .help("The public key identity being deleted"),
                         )
                         .arg(
@@ -1419,10 +1745,19 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
                                 .value_name("user-id")
                                 .long("id-user")
                                 .takes_value(true)
-                                .required_unless("id_key")
-                                .conflicts_with("id_key")
+                                .required_unless_one(&["id_key", "id_api_key"])
+                                .conflicts_with_all(&["id_key", "id_api_key"])
                                 .help("The user identity being deleted"),
                         )
+                        .arg(
+                            Arg::with_name("id_api_key")
+                                .value_name("key-id")
+                                .long("id-api-key")
+                                .takes_value(true)
+                                .required_unless_one(&["id_key", "id_user"])
+                                .conflicts_with_all(&["id_key", "id_user"])
+                                .help("The API key identity being deleted"),
+                        )
                 )
         );
     }
@@ -1534,7 +1869,13 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
         SubcommandActions::new().with_command("build", registry::RegistryGenerateAction);
 
     #[cfg(feature = "registry")]
-    let registry_command = registry_command.with_command("add", registry::RegistryAddAction);
+    let registry_command = registry_command
+        .with_command("add", registry::RegistryAddAction)
+        .with_command("export", registry::RegistryExportAction)
+        .with_command("diff", registry::RegistryDiffAction);
+
+    #[cfg(all(feature = "registry", feature = "database"))]
+    let registry_command = registry_command.with_command("import", registry::RegistryImportAction);
 
     subcommands = subcommands.with_command("registry", registry_command);
 
@@ -1567,6 +1908,18 @@ fn run<I: IntoIterator<Item = T>, T: Into<OsString> + Clone>(args: I) -> Result<
                 .with_command("disable", maintenance::DisableAction),
         )
     }
+    #[cfg(feature = "api-keys")]
+    {
+        use action::rbac;
+        subcommands = subcommands.with_command(
+            "apikey",
+            SubcommandActions::new()
+                .with_command("create", rbac::CreateApiKeyAction)
+                .with_command("list", rbac::ListApiKeysAction)
+                .with_command("show", rbac::ShowApiKeyAction)
+                .with_command("revoke", rbac::RevokeApiKeyAction),
+        )
+    }
     #[cfg(feature = "authorization-handler-rbac")]
     {
         use action::rbac;
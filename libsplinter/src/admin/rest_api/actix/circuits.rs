@@ -20,9 +20,11 @@ use futures::{future::IntoFuture, Future};
 use std::collections::HashMap;
 
 #[cfg(feature = "authorization")]
-use crate::admin::rest_api::CIRCUIT_READ_PERMISSION;
+use crate::admin::rest_api::CIRCUIT_LIST_PERMISSION;
 use crate::admin::store::{AdminServiceStore, CircuitPredicate, CircuitStatus};
 use crate::protocol;
+#[cfg(feature = "authorization")]
+use crate::rest_api::auth::authorization::CircuitScope;
 use crate::rest_api::{
     actix_web_1::{Method, ProtocolVersionRangeGuard, Resource},
     paging::{get_response_paging_info, DEFAULT_LIMIT, DEFAULT_OFFSET},
@@ -40,7 +42,7 @@ pub fn make_list_circuits_resource(store: Box<dyn AdminServiceStore>) -> Resourc
         ));
     #[cfg(feature = "authorization")]
     {
-        resource.add_method(Method::Get, CIRCUIT_READ_PERMISSION, move |r, _| {
+        resource.add_method(Method::Get, CIRCUIT_LIST_PERMISSION, move |r, _| {
             list_circuits(r, web::Data::new(store.clone()))
         })
     }
@@ -138,11 +140,17 @@ fn list_circuits(
         None => format!("{}", protocol::ADMIN_PROTOCOL_VERSION),
     };
 
+    // A client that may only read some circuits is only shown those circuits
+    #[cfg(feature = "authorization")]
+    let circuit_scope = req.extensions().get::<CircuitScope>().cloned();
+
     Box::new(query_list_circuits(
         store,
         link,
         member_filter,
         status_filter,
+        #[cfg(feature = "authorization")]
+        circuit_scope,
         Some(offset),
         Some(limit),
         protocol_version,
@@ -154,6 +162,7 @@ fn query_list_circuits(
     link: String,
     member_filter: Option<String>,
     status_filter: Option<String>,
+    #[cfg(feature = "authorization")] circuit_scope: Option<CircuitScope>,
     offset: Option<usize>,
     limit: Option<usize>,
     protocol_version: String,
@@ -172,13 +181,24 @@ fn query_list_circuits(
 
         let circuits = store
             .list_circuits(&filters)
-            .map_err(|err| CircuitListError::CircuitStoreError(err.to_string()))?;
+            .map_err(|err| CircuitListError::CircuitStoreError(err.to_string()))?
+            .collect::<Vec<_>>();
+
+        #[cfg(feature = "authorization")]
+        let circuits = match circuit_scope {
+            Some(circuit_scope) => circuits
+                .into_iter()
+                .filter(|circuit| circuit_scope.contains(circuit.circuit_id()))
+                .collect(),
+            None => circuits,
+        };
 
         let offset_value = offset.unwrap_or(0);
         let total = circuits.len();
         let limit_value = limit.unwrap_or_else(|| total as usize);
 
         let circuits = circuits
+            .into_iter()
             .skip(offset_value)
             .take(limit_value)
             .collect::<Vec<_>>();
@@ -20,10 +20,12 @@ use actix_web::{error::BlockingError, web, Error, HttpRequest, HttpResponse};
 use futures::{future::IntoFuture, Future};
 
 #[cfg(feature = "authorization")]
-use crate::admin::rest_api::CIRCUIT_READ_PERMISSION;
+use crate::admin::rest_api::CIRCUIT_LIST_PERMISSION;
 use crate::admin::service::proposal_store::ProposalStore;
 use crate::admin::store::CircuitPredicate;
 use crate::protocol;
+#[cfg(feature = "authorization")]
+use crate::rest_api::auth::authorization::CircuitScope;
 use crate::rest_api::{
     actix_web_1::{Method, ProtocolVersionRangeGuard, Resource},
     paging::{get_response_paging_info, DEFAULT_LIMIT, DEFAULT_OFFSET},
@@ -42,7 +44,7 @@ pub fn make_list_proposals_resource<PS: ProposalStore + 'static>(proposal_store:
 
     #[cfg(feature = "authorization")]
     {
-        resource.add_method(Method::Get, CIRCUIT_READ_PERMISSION, move |r, _| {
+        resource.add_method(Method::Get, CIRCUIT_LIST_PERMISSION, move |r, _| {
             list_proposals(r, web::Data::new(proposal_store.clone()))
         })
     }
@@ -134,11 +136,17 @@ fn list_proposals<PS: ProposalStore + 'static>(
         None => format!("{}", protocol::ADMIN_PROTOCOL_VERSION),
     };
 
+    // A client that may only read some circuits is only shown proposals for those circuits
+    #[cfg(feature = "authorization")]
+    let circuit_scope = req.extensions().get::<CircuitScope>().cloned();
+
     Box::new(query_list_proposals(
         proposal_store,
         link,
         management_type_filter,
         member_filter,
+        #[cfg(feature = "authorization")]
+        circuit_scope,
         Some(offset),
         Some(limit),
         protocol_version,
@@ -150,6 +158,7 @@ fn query_list_proposals<PS: ProposalStore + 'static>(
     link: String,
     management_type_filter: Option<String>,
     member_filter: Option<String>,
+    #[cfg(feature = "authorization")] circuit_scope: Option<CircuitScope>,
     offset: Option<usize>,
     limit: Option<usize>,
     protocol_version: String,
@@ -165,12 +174,24 @@ fn query_list_proposals<PS: ProposalStore + 'static>(
 
         let proposals = proposal_store
             .proposals(filters)
-            .map_err(|err| ProposalListError::InternalError(err.to_string()))?;
+            .map_err(|err| ProposalListError::InternalError(err.to_string()))?
+            .collect::<Vec<_>>();
+
+        #[cfg(feature = "authorization")]
+        let proposals = match circuit_scope {
+            Some(circuit_scope) => proposals
+                .into_iter()
+                .filter(|proposal| circuit_scope.contains(&proposal.circuit_id))
+                .collect(),
+            None => proposals,
+        };
+
         let offset_value = offset.unwrap_or(0);
-        let total = proposals.total() as usize;
+        let total = proposals.len();
         let limit_value = limit.unwrap_or(total);
 
         let proposals = proposals
+            .into_iter()
             .skip(offset_value)
             .take(limit_value)
             .collect::<Vec<_>>();
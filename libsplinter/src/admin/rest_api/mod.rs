@@ -34,6 +34,14 @@ const CIRCUIT_READ_PERMISSION: Permission = Permission::Check {
     permission_display_name: "Circuit read",
     permission_description: "Allows the client to read circuit state",
 };
+/// The permission for listing circuit data, which clients that can only read some circuits are
+/// also allowed; the listed data is limited to those circuits.
+#[cfg(all(feature = "authorization", feature = "rest-api-actix"))]
+const CIRCUIT_LIST_PERMISSION: Permission = Permission::CheckForCircuits {
+    permission_id: "circuit.read",
+    permission_display_name: "Circuit read",
+    permission_description: "Allows the client to read circuit state",
+};
 #[cfg(all(feature = "authorization", feature = "rest-api-actix"))]
 const CIRCUIT_WRITE_PERMISSION: Permission = Permission::Check {
     permission_id: "circuit.write",
@@ -129,10 +129,6 @@ impl ProposalIter {
     pub fn new(iter: Box<dyn ExactSizeIterator<Item = StoreProposal>>) -> Self {
         Self { inner: iter }
     }
-
-    pub fn total(&self) -> usize {
-        self.inner.len()
-    }
 }
 
 impl Iterator for ProposalIter {
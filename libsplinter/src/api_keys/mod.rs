@@ -0,0 +1,179 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Long-lived API keys for machine clients of the REST API
+//!
+//! API keys allow automated clients to authenticate with the REST API without holding a private
+//! key at runtime or going through an interactive login. Each key is issued by an administrator
+//! and has a display name, a set of scopes, and an expiration time.
+//!
+//! A client presents its key as a bearer token in the form `ApiKey:<key ID>.<secret>`. Only a hash
+//! of the secret is stored, so the full token is only available when the key is created. An API
+//! key is identified by its ID, which may be assigned roles like any other identity; a key may
+//! only be used for the permissions in its scopes, regardless of the roles it has been assigned.
+//! Keys are looked up for every request, so removing a key revokes it immediately.
+
+#[cfg(feature = "rest-api")]
+pub mod rest_api;
+pub mod store;
+
+use std::fmt;
+use std::str::FromStr;
+
+use openssl::hash::{hash, MessageDigest};
+use openssl::memcmp;
+use openssl::rand::rand_bytes;
+
+use crate::error::{InternalError, InvalidArgumentError};
+use crate::hex::to_hex;
+
+use store::ApiKey;
+
+/// The number of random bytes in an API key's ID
+const ID_LENGTH: usize = 8;
+/// The number of random bytes in an API key's secret
+const SECRET_LENGTH: usize = 32;
+
+/// The token that a client presents to authenticate with an API key, which is made up of the key's
+/// ID and secret
+///
+/// The token's string representation is `<key ID>.<secret>`, where both parts are hex-encoded.
+pub struct ApiKeyToken {
+    id: String,
+    secret: String,
+}
+
+impl ApiKeyToken {
+    /// Generates a new token with a random ID and secret.
+    pub fn generate() -> Result<Self, InternalError> {
+        let mut id = [0u8; ID_LENGTH];
+        rand_bytes(&mut id).map_err(|err| InternalError::from_source(Box::new(err)))?;
+        let mut secret = [0u8; SECRET_LENGTH];
+        rand_bytes(&mut secret).map_err(|err| InternalError::from_source(Box::new(err)))?;
+
+        Ok(Self {
+            id: to_hex(&id),
+            secret: to_hex(&secret),
+        })
+    }
+
+    /// Returns the ID of the API key that the token is for.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the hex-encoded SHA-256 hash of the token's secret, which is stored in place of the
+    /// secret itself.
+    ///
+    /// The secret is a long random value, so it is not salted or stretched before it is hashed.
+    pub fn secret_hash(&self) -> Result<String, InternalError> {
+        hash(MessageDigest::sha256(), self.secret.as_bytes())
+            .map(|digest| to_hex(&digest))
+            .map_err(|err| InternalError::from_source(Box::new(err)))
+    }
+
+    /// Returns whether or not this token authenticates the given API key.
+    pub fn matches(&self, api_key: &ApiKey) -> Result<bool, InternalError> {
+        if self.id != api_key.id() {
+            return Ok(false);
+        }
+
+        let secret_hash = self.secret_hash()?;
+        // Compare the hashes in constant time
+        Ok(secret_hash.len() == api_key.secret_hash().len()
+            && memcmp::eq(secret_hash.as_bytes(), api_key.secret_hash().as_bytes()))
+    }
+}
+
+impl fmt::Display for ApiKeyToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.id, self.secret)
+    }
+}
+
+impl FromStr for ApiKeyToken {
+    type Err = InvalidArgumentError;
+
+    fn from_str(token: &str) -> Result<Self, Self::Err> {
+        let mut parts = token.splitn(2, '.');
+        match (parts.next(), parts.next()) {
+            (Some(id), Some(secret))
+                if is_hex(id) && is_hex(secret) && secret.len() == SECRET_LENGTH * 2 =>
+            {
+                Ok(Self {
+                    id: id.into(),
+                    secret: secret.into(),
+                })
+            }
+            _ => Err(InvalidArgumentError::new(
+                "token".into(),
+                "API key tokens must be in the format <key ID>.<secret>".into(),
+            )),
+        }
+    }
+}
+
+fn is_hex(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::{Duration, SystemTime};
+
+    use store::ApiKeyBuilder;
+
+    /// Verify that a generated token can be parsed from its string representation, and that it
+    /// only matches the API key it was generated for.
+    ///
+    /// 1. Generate a token and create an API key with its ID and secret hash
+    /// 2. Verify that the token parsed from the string representation matches the API key
+    /// 3. Verify that a token with the same ID but a different secret does not match the key
+    /// 4. Verify that malformed tokens are rejected
+    #[test]
+    fn generate_and_parse_token() {
+        let token = ApiKeyToken::generate().expect("Failed to generate token");
+        let api_key = ApiKeyBuilder::new()
+            .with_id(token.id().into())
+            .with_display_name("Test key".into())
+            .with_secret_hash(token.secret_hash().expect("Failed to hash secret"))
+            .with_scopes(vec!["circuit.read".into()])
+            .with_expires_at(SystemTime::now() + Duration::from_secs(60))
+            .build()
+            .expect("Failed to build API key");
+
+        let parsed = token
+            .to_string()
+            .parse::<ApiKeyToken>()
+            .expect("Failed to parse token");
+        assert_eq!(parsed.id(), api_key.id());
+        assert!(parsed.matches(&api_key).expect("Failed to match token"));
+
+        let other = ApiKeyToken::generate().expect("Failed to generate token");
+        let forged = format!("{}.{}", token.id(), other.secret)
+            .parse::<ApiKeyToken>()
+            .expect("Failed to parse token");
+        assert!(!forged.matches(&api_key).expect("Failed to match token"));
+        assert!(!other.matches(&api_key).expect("Failed to match token"));
+
+        assert!("".parse::<ApiKeyToken>().is_err());
+        assert!(token.id().parse::<ApiKeyToken>().is_err());
+        assert!(format!(".{}", other.secret).parse::<ApiKeyToken>().is_err());
+        assert!(format!("{}.not-hex", token.id())
+            .parse::<ApiKeyToken>()
+            .is_err());
+    }
+}
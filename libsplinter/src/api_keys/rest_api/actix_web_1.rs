@@ -0,0 +1,504 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module provides the following endpoints:
+//!
+//! * `GET /authorization/api_keys` for listing API keys
+//! * `POST /authorization/api_keys` for creating an API key
+//! * `GET /authorization/api_keys/{key_id}` for getting an API key
+//! * `DELETE /authorization/api_keys/{key_id}` for revoking an API key
+
+use std::error::Error;
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+use crate::actix_web::{error::BlockingError, web, Error as ActixError, HttpRequest, HttpResponse};
+use crate::api_keys::store::{ApiKeyBuilder, ApiKeyStore, ApiKeyStoreError};
+use crate::api_keys::ApiKeyToken;
+use crate::futures::{stream::Stream, Future, IntoFuture};
+use crate::protocol;
+use crate::rest_api::{
+    actix_web_1::{Method, ProtocolVersionRangeGuard, Resource},
+    paging::get_response_paging_info,
+    ErrorResponse,
+};
+
+use super::resources::{
+    ApiKeyPayload, ApiKeyResponse, ListApiKeysResponse, NewApiKeyResponse, PagingQuery,
+};
+use super::{API_KEYS_READ_PERMISSION, API_KEYS_WRITE_PERMISSION};
+
+pub fn make_api_keys_resource(api_key_store: Box<dyn ApiKeyStore>) -> Resource {
+    let list_store = api_key_store.clone();
+    let post_store = api_key_store;
+    Resource::build("/authorization/api_keys")
+        .add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::AUTHORIZATION_API_KEYS_MIN,
+            protocol::AUTHORIZATION_PROTOCOL_VERSION,
+        ))
+        .add_method(Method::Get, API_KEYS_READ_PERMISSION, move |r, _| {
+            list_api_keys(r, web::Data::new(list_store.clone()))
+        })
+        .add_method(Method::Post, API_KEYS_WRITE_PERMISSION, move |_, p| {
+            add_api_key(p, web::Data::new(post_store.clone()))
+        })
+}
+
+pub fn make_api_key_resource(api_key_store: Box<dyn ApiKeyStore>) -> Resource {
+    let get_store = api_key_store.clone();
+    let delete_store = api_key_store;
+    Resource::build("/authorization/api_keys/{key_id}")
+        .add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::AUTHORIZATION_API_KEY_MIN,
+            protocol::AUTHORIZATION_PROTOCOL_VERSION,
+        ))
+        .add_method(Method::Get, API_KEYS_READ_PERMISSION, move |r, _| {
+            get_api_key(r, web::Data::new(get_store.clone()))
+        })
+        .add_method(Method::Delete, API_KEYS_WRITE_PERMISSION, move |r, _| {
+            delete_api_key(r, web::Data::new(delete_store.clone()))
+        })
+}
+
+fn list_api_keys(
+    req: HttpRequest,
+    api_key_store: web::Data<Box<dyn ApiKeyStore>>,
+) -> Box<dyn Future<Item = HttpResponse, Error = ActixError>> {
+    let web::Query(paging_query): web::Query<PagingQuery> =
+        match web::Query::from_query(req.query_string()) {
+            Ok(paging_query) => paging_query,
+            Err(_) => {
+                return Box::new(
+                    HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request("Invalid query"))
+                        .into_future(),
+                )
+            }
+        };
+
+    let link = format!("{}?", req.uri().path());
+
+    Box::new(
+        web::block(move || {
+            let api_keys = api_key_store
+                .list_api_keys()
+                .map_err(SendableApiKeyStoreError::from)?;
+
+            let total = api_keys.len();
+            let api_keys = api_keys
+                .skip(paging_query.offset)
+                .take(paging_query.limit)
+                .collect::<Vec<_>>();
+
+            Ok((api_keys, link, paging_query, total))
+        })
+        .then(
+            |res: Result<_, BlockingError<SendableApiKeyStoreError>>| match res {
+                Ok((api_keys, link, paging_query, total)) => {
+                    Ok(HttpResponse::Ok().json(ListApiKeysResponse {
+                        data: api_keys.iter().map(ApiKeyResponse::from).collect(),
+                        paging: get_response_paging_info(
+                            Some(paging_query.limit),
+                            Some(paging_query.offset),
+                            &link,
+                            total,
+                        ),
+                    }))
+                }
+                Err(err) => {
+                    error!("Unable to list API keys: {}", err);
+                    Ok(HttpResponse::InternalServerError().json(ErrorResponse::internal_error()))
+                }
+            },
+        ),
+    )
+}
+
+fn add_api_key(
+    payload: web::Payload,
+    api_key_store: web::Data<Box<dyn ApiKeyStore>>,
+) -> Box<dyn Future<Item = HttpResponse, Error = ActixError>> {
+    Box::new(
+        payload
+            .from_err::<ActixError>()
+            .fold(web::BytesMut::new(), move |mut body, chunk| {
+                body.extend_from_slice(&chunk);
+                Ok::<_, ActixError>(body)
+            })
+            .into_future()
+            .and_then(move |body| {
+                let api_key_payload = match serde_json::from_slice::<ApiKeyPayload>(&body) {
+                    Ok(api_key_payload) => api_key_payload,
+                    Err(err) => {
+                        return Box::new(
+                            HttpResponse::BadRequest()
+                                .json(ErrorResponse::bad_request(&format!(
+                                    "Invalid API key payload: {}",
+                                    err
+                                )))
+                                .into_future(),
+                        )
+                            as Box<dyn Future<Item = HttpResponse, Error = ActixError>>;
+                    }
+                };
+
+                Box::new(
+                    web::block(move || create_api_key(&**api_key_store, api_key_payload)).then(
+                        |res| {
+                            Ok(match res {
+                                Ok((api_key, token)) => HttpResponse::Ok().json(json!({
+                                    "data": NewApiKeyResponse {
+                                        api_key: ApiKeyResponse::from(&api_key),
+                                        token: &token,
+                                    },
+                                })),
+                                Err(BlockingError::Error(
+                                    SendableApiKeyStoreError::InvalidState(msg),
+                                )) => HttpResponse::BadRequest().json(ErrorResponse::bad_request(
+                                    &format!("Invalid API key payload: {}", msg),
+                                )),
+                                Err(BlockingError::Error(
+                                    SendableApiKeyStoreError::ConstraintViolation(msg),
+                                )) => HttpResponse::Conflict().json(ErrorResponse::conflict(&msg)),
+                                Err(err) => {
+                                    error!("Unable to add API key: {}", err);
+                                    HttpResponse::InternalServerError()
+                                        .json(ErrorResponse::internal_error())
+                                }
+                            })
+                        },
+                    ),
+                ) as Box<dyn Future<Item = HttpResponse, Error = ActixError>>
+            }),
+    )
+}
+
+/// Generates a new token and adds an API key for it to the store, returning the key and the
+/// token's string representation
+fn create_api_key(
+    api_key_store: &dyn ApiKeyStore,
+    api_key_payload: ApiKeyPayload,
+) -> Result<(crate::api_keys::store::ApiKey, String), SendableApiKeyStoreError> {
+    let ApiKeyPayload {
+        display_name,
+        scopes,
+        expires_in,
+    } = api_key_payload;
+
+    let token = ApiKeyToken::generate()
+        .map_err(|err| SendableApiKeyStoreError::InternalError(err.reduce_to_string()))?;
+    let secret_hash = token
+        .secret_hash()
+        .map_err(|err| SendableApiKeyStoreError::InternalError(err.reduce_to_string()))?;
+
+    let created_at = SystemTime::now();
+    let expires_at = created_at
+        .checked_add(Duration::from_secs(expires_in))
+        .ok_or_else(|| SendableApiKeyStoreError::InvalidState("expires_in is too large".into()))?;
+
+    let api_key = ApiKeyBuilder::new()
+        .with_id(token.id().into())
+        .with_display_name(display_name)
+        .with_secret_hash(secret_hash)
+        .with_scopes(scopes)
+        .with_created_at(created_at)
+        .with_expires_at(expires_at)
+        .build()
+        .map_err(|err| SendableApiKeyStoreError::InvalidState(err.to_string()))?;
+
+    api_key_store
+        .add_api_key(api_key.clone())
+        .map_err(SendableApiKeyStoreError::from)?;
+
+    Ok((api_key, token.to_string()))
+}
+
+fn get_api_key(
+    req: HttpRequest,
+    api_key_store: web::Data<Box<dyn ApiKeyStore>>,
+) -> Box<dyn Future<Item = HttpResponse, Error = ActixError>> {
+    let key_id = req.match_info().get("key_id").unwrap_or("").to_string();
+    Box::new(
+        web::block(move || {
+            api_key_store
+                .get_api_key(&key_id)
+                .map_err(SendableApiKeyStoreError::from)
+        })
+        .then(|res| {
+            Ok(match res {
+                Ok(Some(api_key)) => HttpResponse::Ok().json(json!({
+                    "data": ApiKeyResponse::from(&api_key),
+                })),
+                Ok(None) => {
+                    HttpResponse::NotFound().json(ErrorResponse::not_found("API key not found"))
+                }
+                Err(err) => {
+                    error!("Unable to get API key: {}", err);
+                    HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+                }
+            })
+        }),
+    )
+}
+
+fn delete_api_key(
+    req: HttpRequest,
+    api_key_store: web::Data<Box<dyn ApiKeyStore>>,
+) -> Box<dyn Future<Item = HttpResponse, Error = ActixError>> {
+    let key_id = req.match_info().get("key_id").unwrap_or("").to_string();
+    Box::new(
+        web::block(move || {
+            api_key_store
+                .remove_api_key(&key_id)
+                .map_err(SendableApiKeyStoreError::from)
+        })
+        .then(|res| {
+            Ok(match res {
+                Ok(()) => HttpResponse::Ok().finish(),
+                Err(BlockingError::Error(SendableApiKeyStoreError::InvalidState(_))) => {
+                    HttpResponse::NotFound().json(ErrorResponse::not_found("API key not found"))
+                }
+                Err(err) => {
+                    error!("Unable to revoke API key: {}", err);
+                    HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+                }
+            })
+        }),
+    )
+}
+
+/// Thread-friendly version of the ApiKeyStoreError
+#[derive(Debug)]
+enum SendableApiKeyStoreError {
+    ConstraintViolation(String),
+    InternalError(String),
+    InvalidState(String),
+}
+
+impl Error for SendableApiKeyStoreError {}
+
+impl fmt::Display for SendableApiKeyStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SendableApiKeyStoreError::ConstraintViolation(msg) => f.write_str(msg),
+            SendableApiKeyStoreError::InternalError(msg) => f.write_str(msg),
+            SendableApiKeyStoreError::InvalidState(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl From<ApiKeyStoreError> for SendableApiKeyStoreError {
+    fn from(err: ApiKeyStoreError) -> Self {
+        match err {
+            ApiKeyStoreError::ConstraintViolation(err) => {
+                SendableApiKeyStoreError::ConstraintViolation(err.to_string())
+            }
+            ApiKeyStoreError::InvalidState(err) => {
+                SendableApiKeyStoreError::InvalidState(err.to_string())
+            }
+            ApiKeyStoreError::InternalError(err) => {
+                SendableApiKeyStoreError::InternalError(err.reduce_to_string())
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    use diesel::{
+        r2d2::{ConnectionManager, Pool},
+        sqlite::SqliteConnection,
+    };
+    use reqwest::{blocking::Client, StatusCode, Url};
+    use serde_json::Value as JsonValue;
+
+    use crate::api_keys::store::DieselApiKeyStore;
+    use crate::migrations::run_sqlite_migrations;
+    use crate::rest_api::actix_web_1::{RestApiBuilder, RestApiShutdownHandle};
+    use crate::store::ForeignKeyCustomizer;
+
+    /// Verify the lifecycle of an API key through the REST API.
+    ///
+    /// 1. Create an API key with a POST request and verify that the response includes a token for
+    ///    the key, which can be used to authenticate the key
+    /// 2. Verify that the key is listed and can be fetched by its ID, and that neither response
+    ///    includes the token
+    /// 3. Revoke the key with a DELETE request and verify that it can no longer be fetched
+    /// 4. Verify that revoking the key again returns a 404
+    #[test]
+    fn api_key_lifecycle() {
+        let api_key_store = create_api_key_store();
+        let (shutdown_handle, join_handle, bind_url) = run_rest_api_on_open_port(vec![
+            make_api_keys_resource(api_key_store.clone()),
+            make_api_key_resource(api_key_store.clone()),
+        ]);
+
+        let url = Url::parse(&format!("http://{}/authorization/api_keys", bind_url))
+            .expect("Failed to parse URL");
+        let resp = Client::new()
+            .post(url.clone())
+            .header("SplinterProtocolVersion", protocol::AUTHORIZATION_PROTOCOL_VERSION)
+            .json(&json!({
+                "display_name": "CI pipeline",
+                "scopes": ["circuit.read"],
+                "expires_in": 3600,
+            }))
+            .send()
+            .expect("Failed to perform request");
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: JsonValue = resp.json().expect("Failed to deserialize body");
+        let key_id = body["data"]["id"].as_str().expect("No key ID").to_string();
+        assert_eq!(body["data"]["display_name"], "CI pipeline");
+        assert_eq!(body["data"]["scopes"], json!(["circuit.read"]));
+        assert_eq!(body["data"]["expired"], false);
+        let token = body["data"]["token"]
+            .as_str()
+            .expect("No token")
+            .parse::<ApiKeyToken>()
+            .expect("Failed to parse token");
+        let api_key = api_key_store
+            .get_api_key(&key_id)
+            .expect("Failed to get API key")
+            .expect("API key not found");
+        assert!(token.matches(&api_key).expect("Failed to match token"));
+
+        let resp = Client::new()
+            .get(url.clone())
+            .header("SplinterProtocolVersion", protocol::AUTHORIZATION_PROTOCOL_VERSION)
+            .send()
+            .expect("Failed to perform request");
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: JsonValue = resp.json().expect("Failed to deserialize body");
+        assert_eq!(body["data"].as_array().map(Vec::len), Some(1));
+        assert_eq!(body["data"][0]["id"], key_id.as_str());
+        assert!(body["data"][0].get("token").is_none());
+
+        let key_url = Url::parse(&format!(
+            "http://{}/authorization/api_keys/{}",
+            bind_url, key_id
+        ))
+        .expect("Failed to parse URL");
+        let resp = Client::new()
+            .get(key_url.clone())
+            .header("SplinterProtocolVersion", protocol::AUTHORIZATION_PROTOCOL_VERSION)
+            .send()
+            .expect("Failed to perform request");
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: JsonValue = resp.json().expect("Failed to deserialize body");
+        assert_eq!(body["data"]["id"], key_id.as_str());
+        assert!(body["data"].get("token").is_none());
+
+        let resp = Client::new()
+            .delete(key_url.clone())
+            .header("SplinterProtocolVersion", protocol::AUTHORIZATION_PROTOCOL_VERSION)
+            .send()
+            .expect("Failed to perform request");
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let resp = Client::new()
+            .get(key_url.clone())
+            .header("SplinterProtocolVersion", protocol::AUTHORIZATION_PROTOCOL_VERSION)
+            .send()
+            .expect("Failed to perform request");
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        let resp = Client::new()
+            .delete(key_url)
+            .header("SplinterProtocolVersion", protocol::AUTHORIZATION_PROTOCOL_VERSION)
+            .send()
+            .expect("Failed to perform request");
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        shutdown_handle
+            .shutdown()
+            .expect("Unable to shutdown rest api");
+        join_handle.join().expect("Unable to join rest api thread");
+    }
+
+    /// Verify that creating an API key with an invalid payload returns a 400.
+    #[test]
+    fn add_api_key_invalid_payload() {
+        let api_key_store = create_api_key_store();
+        let (shutdown_handle, join_handle, bind_url) =
+            run_rest_api_on_open_port(vec![make_api_keys_resource(api_key_store.clone())]);
+
+        let url = Url::parse(&format!("http://{}/authorization/api_keys", bind_url))
+            .expect("Failed to parse URL");
+        for payload in vec![
+            json!({"display_name": "No scopes", "scopes": [], "expires_in": 3600}),
+            json!({"display_name": "Expired", "scopes": ["circuit.read"], "expires_in": 0}),
+            json!({"scopes": ["circuit.read"], "expires_in": 3600}),
+        ] {
+            let resp = Client::new()
+                .post(url.clone())
+                .header("SplinterProtocolVersion", protocol::AUTHORIZATION_PROTOCOL_VERSION)
+                .json(&payload)
+                .send()
+                .expect("Failed to perform request");
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        }
+
+        assert_eq!(
+            api_key_store
+                .list_api_keys()
+                .expect("Failed to list API keys")
+                .len(),
+            0
+        );
+
+        shutdown_handle
+            .shutdown()
+            .expect("Unable to shutdown rest api");
+        join_handle.join().expect("Unable to join rest api thread");
+    }
+
+    fn create_api_key_store() -> Box<dyn ApiKeyStore> {
+        let connection_manager = ConnectionManager::<SqliteConnection>::new(":memory:");
+        let pool = Pool::builder()
+            .max_size(1)
+            .connection_customizer(Box::new(ForeignKeyCustomizer))
+            .build(connection_manager)
+            .expect("Failed to build connection pool");
+
+        run_sqlite_migrations(&*pool.get().expect("Failed to get connection for migrations"))
+            .expect("Failed to run migrations");
+
+        Box::new(DieselApiKeyStore::new(pool))
+    }
+
+    fn run_rest_api_on_open_port(
+        resources: Vec<Resource>,
+    ) -> (RestApiShutdownHandle, std::thread::JoinHandle<()>, String) {
+        #[cfg(not(feature = "https-bind"))]
+        let bind = "127.0.0.1:0";
+        #[cfg(feature = "https-bind")]
+        let bind = crate::rest_api::BindConfig::Http("127.0.0.1:0".into());
+
+        let result = RestApiBuilder::new()
+            .with_bind(bind)
+            .add_resources(resources.clone())
+            .build_insecure()
+            .expect("Failed to build REST API")
+            .run_insecure();
+        match result {
+            Ok((shutdown_handle, join_handle)) => {
+                let port = shutdown_handle.port_numbers()[0];
+                (shutdown_handle, join_handle, format!("127.0.0.1:{}", port))
+            }
+            Err(err) => panic!("Failed to run REST API: {}", err),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! API key REST API endpoints
+
+#[cfg(feature = "rest-api-actix")]
+mod actix_web_1;
+mod resources;
+
+#[cfg(feature = "rest-api-actix")]
+use crate::rest_api::auth::authorization::Permission;
+use crate::rest_api::{Resource, RestResourceProvider};
+
+use super::store::ApiKeyStore;
+
+#[cfg(feature = "rest-api-actix")]
+const API_KEYS_READ_PERMISSION: Permission = Permission::Check {
+    permission_id: "authorization.api_keys.read",
+    permission_display_name: "API keys read",
+    permission_description: "Allows the client to read API keys",
+};
+
+#[cfg(feature = "rest-api-actix")]
+const API_KEYS_WRITE_PERMISSION: Permission = Permission::Check {
+    permission_id: "authorization.api_keys.write",
+    permission_display_name: "API keys write",
+    permission_description: "Allows the client to create and revoke API keys",
+};
+
+/// Provides the REST API [Resource](../../../rest_api/struct.Resource.html) definitions for
+/// managing API keys. The following endpoints are provided:
+///
+/// * `GET /authorization/api_keys` - List API keys
+/// * `POST /authorization/api_keys` - Create an API key, returning its token
+/// * `GET /authorization/api_keys/{key_id}` - Get an API key
+/// * `DELETE /authorization/api_keys/{key_id}` - Revoke an API key
+///
+/// These endpoints are only available if the following REST API backend feature is enabled:
+///
+/// * `rest-api-actix`
+#[derive(Clone)]
+pub struct ApiKeyResourceProvider {
+    api_key_store: Box<dyn ApiKeyStore>,
+}
+
+impl ApiKeyResourceProvider {
+    /// Constructs a new resource provider with the given store.
+    pub fn new(api_key_store: Box<dyn ApiKeyStore>) -> Self {
+        Self { api_key_store }
+    }
+}
+
+impl RestResourceProvider for ApiKeyResourceProvider {
+    fn resources(&self) -> Vec<Resource> {
+        // Allowing unused_mut because resources must be mutable if feature `rest-api-actix` is
+        // enabled
+        #[allow(unused_mut)]
+        let mut resources = Vec::new();
+
+        #[cfg(feature = "rest-api-actix")]
+        {
+            resources.append(&mut vec![
+                actix_web_1::make_api_keys_resource(self.api_key_store.clone()),
+                actix_web_1::make_api_key_resource(self.api_key_store.clone()),
+            ]);
+        }
+
+        resources
+    }
+}
@@ -0,0 +1,87 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Web-framework-agnostic resources.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::api_keys::store::ApiKey;
+use crate::rest_api::paging::{Paging, DEFAULT_LIMIT, DEFAULT_OFFSET};
+
+#[derive(Deserialize)]
+pub struct PagingQuery {
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+    #[serde(default = "default_offset")]
+    pub offset: usize,
+}
+
+fn default_limit() -> usize {
+    DEFAULT_LIMIT
+}
+
+fn default_offset() -> usize {
+    DEFAULT_OFFSET
+}
+
+#[derive(Serialize)]
+pub struct ListApiKeysResponse<'a> {
+    pub data: Vec<ApiKeyResponse<'a>>,
+    pub paging: Paging,
+}
+
+#[derive(Serialize)]
+pub struct ApiKeyResponse<'a> {
+    id: &'a str,
+    display_name: &'a str,
+    scopes: &'a [String],
+    created_at: u64,
+    expires_at: u64,
+    expired: bool,
+}
+
+impl<'a> From<&'a ApiKey> for ApiKeyResponse<'a> {
+    fn from(api_key: &'a ApiKey) -> Self {
+        Self {
+            id: api_key.id(),
+            display_name: api_key.display_name(),
+            scopes: api_key.scopes(),
+            created_at: to_secs(api_key.created_at()),
+            expires_at: to_secs(api_key.expires_at()),
+            expired: api_key.is_expired(),
+        }
+    }
+}
+
+/// The response to creating an API key, which is the only response that includes the key's token
+#[derive(Serialize)]
+pub struct NewApiKeyResponse<'a> {
+    #[serde(flatten)]
+    pub api_key: ApiKeyResponse<'a>,
+    pub token: &'a str,
+}
+
+#[derive(Deserialize)]
+pub struct ApiKeyPayload {
+    pub display_name: String,
+    pub scopes: Vec<String>,
+    /// The number of seconds after creation that the key expires
+    pub expires_in: u64,
+}
+
+fn to_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
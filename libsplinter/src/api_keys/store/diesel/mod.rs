@@ -0,0 +1,357 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Database-backed implementation of the [ApiKeyStore], powered by [diesel].
+
+mod models;
+mod operations;
+mod schema;
+
+use std::convert::TryFrom;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use diesel::r2d2::{ConnectionManager, Pool};
+
+use crate::error::{ConstraintViolationError, ConstraintViolationType, InternalError};
+
+use super::{ApiKey, ApiKeyStore, ApiKeyStoreError};
+
+use operations::add_api_key::ApiKeyStoreAddApiKey as _;
+use operations::get_api_key::ApiKeyStoreGetApiKey as _;
+use operations::list_api_keys::ApiKeyStoreListApiKeys as _;
+use operations::remove_api_key::ApiKeyStoreRemoveApiKey as _;
+use operations::ApiKeyStoreOperations;
+
+/// A database-backed [ApiKeyStore], powered by [diesel].
+pub struct DieselApiKeyStore<C: diesel::Connection + 'static> {
+    connection_pool: Pool<ConnectionManager<C>>,
+}
+
+impl<C: diesel::Connection + 'static> DieselApiKeyStore<C> {
+    /// Creates a new DieselApiKeyStore
+    ///
+    /// # Arguments
+    ///
+    ///  * `connection_pool`: connection pool to the database
+    pub fn new(connection_pool: Pool<ConnectionManager<C>>) -> Self {
+        Self { connection_pool }
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl ApiKeyStore for DieselApiKeyStore<diesel::pg::PgConnection> {
+    fn get_api_key(&self, id: &str) -> Result<Option<ApiKey>, ApiKeyStoreError> {
+        let connection = self.connection_pool.get()?;
+        ApiKeyStoreOperations::new(&*connection).get_api_key(id)
+    }
+
+    fn list_api_keys(&self) -> Result<Box<dyn ExactSizeIterator<Item = ApiKey>>, ApiKeyStoreError> {
+        let connection = self.connection_pool.get()?;
+        ApiKeyStoreOperations::new(&*connection).list_api_keys()
+    }
+
+    fn add_api_key(&self, api_key: ApiKey) -> Result<(), ApiKeyStoreError> {
+        let connection = self.connection_pool.get()?;
+        ApiKeyStoreOperations::new(&*connection).add_api_key(api_key)
+    }
+
+    fn remove_api_key(&self, id: &str) -> Result<(), ApiKeyStoreError> {
+        let connection = self.connection_pool.get()?;
+        ApiKeyStoreOperations::new(&*connection).remove_api_key(id)
+    }
+
+    fn clone_box(&self) -> Box<dyn ApiKeyStore> {
+        Box::new(Self {
+            connection_pool: self.connection_pool.clone(),
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl ApiKeyStore for DieselApiKeyStore<diesel::sqlite::SqliteConnection> {
+    fn get_api_key(&self, id: &str) -> Result<Option<ApiKey>, ApiKeyStoreError> {
+        let connection = self.connection_pool.get()?;
+        ApiKeyStoreOperations::new(&*connection).get_api_key(id)
+    }
+
+    fn list_api_keys(&self) -> Result<Box<dyn ExactSizeIterator<Item = ApiKey>>, ApiKeyStoreError> {
+        let connection = self.connection_pool.get()?;
+        ApiKeyStoreOperations::new(&*connection).list_api_keys()
+    }
+
+    fn add_api_key(&self, api_key: ApiKey) -> Result<(), ApiKeyStoreError> {
+        let connection = self.connection_pool.get()?;
+        ApiKeyStoreOperations::new(&*connection).add_api_key(api_key)
+    }
+
+    fn remove_api_key(&self, id: &str) -> Result<(), ApiKeyStoreError> {
+        let connection = self.connection_pool.get()?;
+        ApiKeyStoreOperations::new(&*connection).remove_api_key(id)
+    }
+
+    fn clone_box(&self) -> Box<dyn ApiKeyStore> {
+        Box::new(Self {
+            connection_pool: self.connection_pool.clone(),
+        })
+    }
+}
+
+impl TryFrom<ApiKey> for (models::ApiKeyModel, Vec<models::ApiKeyScopeModel>) {
+    type Error = ApiKeyStoreError;
+
+    fn try_from(api_key: ApiKey) -> Result<Self, Self::Error> {
+        let (id, display_name, secret_hash, scopes, created_at, expires_at) = api_key.into_parts();
+
+        let scope_models = scopes
+            .into_iter()
+            .map(|scope| models::ApiKeyScopeModel {
+                api_key_id: id.clone(),
+                scope,
+            })
+            .collect::<Vec<_>>();
+
+        Ok((
+            models::ApiKeyModel {
+                id,
+                display_name,
+                secret_hash,
+                created_at: to_timestamp(created_at)?,
+                expires_at: to_timestamp(expires_at)?,
+            },
+            scope_models,
+        ))
+    }
+}
+
+impl TryFrom<(models::ApiKeyModel, Vec<models::ApiKeyScopeModel>)> for ApiKey {
+    type Error = ApiKeyStoreError;
+
+    fn try_from(
+        (api_key, scopes): (models::ApiKeyModel, Vec<models::ApiKeyScopeModel>),
+    ) -> Result<Self, Self::Error> {
+        // The key is created directly, vs using the builder, as it was validated by the builder
+        // before it was added to the database.
+        Ok(ApiKey {
+            id: api_key.id,
+            display_name: api_key.display_name,
+            secret_hash: api_key.secret_hash,
+            scopes: scopes
+                .into_iter()
+                .map(|models::ApiKeyScopeModel { scope, .. }| scope)
+                .collect(),
+            created_at: from_timestamp(api_key.created_at)?,
+            expires_at: from_timestamp(api_key.expires_at)?,
+        })
+    }
+}
+
+/// Converts a `SystemTime` to the number of seconds since the epoch, as stored in the database
+fn to_timestamp(time: SystemTime) -> Result<i64, ApiKeyStoreError> {
+    time.duration_since(UNIX_EPOCH)
+        .map_err(|err| ApiKeyStoreError::InternalError(InternalError::from_source(Box::new(err))))
+        .and_then(|duration| {
+            i64::try_from(duration.as_secs()).map_err(|_| {
+                ApiKeyStoreError::InternalError(InternalError::with_message(
+                    "timestamp could not be converted from u64 to i64".to_string(),
+                ))
+            })
+        })
+}
+
+/// Converts a number of seconds since the epoch, as stored in the database, to a `SystemTime`
+fn from_timestamp(timestamp: i64) -> Result<SystemTime, ApiKeyStoreError> {
+    u64::try_from(timestamp)
+        .ok()
+        .and_then(|secs| UNIX_EPOCH.checked_add(Duration::from_secs(secs)))
+        .ok_or_else(|| {
+            ApiKeyStoreError::InternalError(InternalError::with_message(
+                "timestamp could not be represented as a `SystemTime`".to_string(),
+            ))
+        })
+}
+
+impl From<diesel::result::Error> for ApiKeyStoreError {
+    fn from(err: diesel::result::Error) -> Self {
+        match err {
+            diesel::result::Error::DatabaseError(ref kind, _) => match kind {
+                diesel::result::DatabaseErrorKind::UniqueViolation => {
+                    ApiKeyStoreError::ConstraintViolation(
+                        ConstraintViolationError::from_source_with_violation_type(
+                            ConstraintViolationType::Unique,
+                            Box::new(err),
+                        ),
+                    )
+                }
+                diesel::result::DatabaseErrorKind::ForeignKeyViolation => {
+                    ApiKeyStoreError::ConstraintViolation(
+                        ConstraintViolationError::from_source_with_violation_type(
+                            ConstraintViolationType::ForeignKey,
+                            Box::new(err),
+                        ),
+                    )
+                }
+                _ => ApiKeyStoreError::InternalError(InternalError::from_source(Box::new(err))),
+            },
+            _ => ApiKeyStoreError::InternalError(InternalError::from_source(Box::new(err))),
+        }
+    }
+}
+
+impl From<diesel::r2d2::PoolError> for ApiKeyStoreError {
+    fn from(err: diesel::r2d2::PoolError) -> Self {
+        ApiKeyStoreError::InternalError(InternalError::from_source(Box::new(err)))
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    use crate::api_keys::store::ApiKeyBuilder;
+    use crate::migrations::run_sqlite_migrations;
+    use crate::store::ForeignKeyCustomizer;
+
+    use diesel::{
+        r2d2::{ConnectionManager, Pool},
+        sqlite::SqliteConnection,
+    };
+
+    /// Verify that a SQLite-backed `DieselApiKeyStore` correctly supports adding, getting, and
+    /// listing API keys.
+    ///
+    /// 1. Create a connection pool for an in-memory SQLite database and run migrations.
+    /// 2. Create the `DieselApiKeyStore`.
+    /// 3. Add two API keys.
+    /// 4. Verify that `get_api_key` returns each key with all fields intact, and `None` for an
+    ///    unknown ID.
+    /// 5. Verify that `list_api_keys` returns both keys.
+    /// 6. Verify that adding a key with a duplicate ID returns a `ConstraintViolation` error.
+    #[test]
+    fn sqlite_add_get_and_list_api_keys() {
+        let pool = create_connection_pool_and_migrate();
+
+        let api_key_store = DieselApiKeyStore::new(pool);
+
+        let first = build_api_key("first", &["circuit.read"]);
+        let second = build_api_key("second", &["circuit.read", "circuit.write"]);
+        api_key_store
+            .add_api_key(first.clone())
+            .expect("Unable to add API key");
+        api_key_store
+            .add_api_key(second.clone())
+            .expect("Unable to add API key");
+
+        assert_eq!(
+            api_key_store
+                .get_api_key("first")
+                .expect("Unable to get API key"),
+            Some(first.clone())
+        );
+        assert_eq!(
+            api_key_store
+                .get_api_key("second")
+                .expect("Unable to get API key"),
+            Some(second.clone())
+        );
+        assert_eq!(
+            api_key_store
+                .get_api_key("unknown")
+                .expect("Unable to get API key"),
+            None
+        );
+
+        assert_eq!(
+            api_key_store
+                .list_api_keys()
+                .expect("Unable to list API keys")
+                .collect::<Vec<_>>(),
+            vec![first, second]
+        );
+
+        assert!(matches!(
+            api_key_store.add_api_key(build_api_key("first", &["registry.read"])),
+            Err(ApiKeyStoreError::ConstraintViolation(_))
+        ));
+    }
+
+    /// Verify that a SQLite-backed `DieselApiKeyStore` correctly supports removing API keys.
+    ///
+    /// 1. Create a connection pool for an in-memory SQLite database and run migrations.
+    /// 2. Create the `DieselApiKeyStore`.
+    /// 3. Add two API keys.
+    /// 4. Remove the first key and verify that it can no longer be fetched, while the second key
+    ///    is still present.
+    /// 5. Verify that removing the first key again returns an `InvalidState` error.
+    #[test]
+    fn sqlite_remove_api_key() {
+        let pool = create_connection_pool_and_migrate();
+
+        let api_key_store = DieselApiKeyStore::new(pool);
+
+        api_key_store
+            .add_api_key(build_api_key("first", &["circuit.read"]))
+            .expect("Unable to add API key");
+        api_key_store
+            .add_api_key(build_api_key("second", &["circuit.read"]))
+            .expect("Unable to add API key");
+
+        api_key_store
+            .remove_api_key("first")
+            .expect("Unable to remove API key");
+
+        assert!(api_key_store
+            .get_api_key("first")
+            .expect("Unable to get API key")
+            .is_none());
+        assert!(api_key_store
+            .get_api_key("second")
+            .expect("Unable to get API key")
+            .is_some());
+
+        assert!(matches!(
+            api_key_store.remove_api_key("first"),
+            Err(ApiKeyStoreError::InvalidState(_))
+        ));
+    }
+
+    fn build_api_key(id: &str, scopes: &[&str]) -> ApiKey {
+        let created_at = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        ApiKeyBuilder::new()
+            .with_id(id.into())
+            .with_display_name(format!("{} key", id))
+            .with_secret_hash(format!("{}-hash", id))
+            .with_scopes(scopes.iter().map(ToString::to_string).collect())
+            .with_created_at(created_at)
+            .with_expires_at(created_at + Duration::from_secs(3600))
+            .build()
+            .expect("Unable to build API key")
+    }
+
+    /// Creates a connection pool for an in-memory SQLite database with only a single connection
+    /// available. Each connection is backed by a different in-memory SQLite database, so limiting
+    /// the pool to a single connection ensures that the same DB is used for all operations.
+    fn create_connection_pool_and_migrate() -> Pool<ConnectionManager<SqliteConnection>> {
+        let connection_manager = ConnectionManager::<SqliteConnection>::new(":memory:");
+        let pool = Pool::builder()
+            .max_size(1)
+            .connection_customizer(Box::new(ForeignKeyCustomizer))
+            .build(connection_manager)
+            .expect("Failed to build connection pool");
+
+        run_sqlite_migrations(&*pool.get().expect("Failed to get connection for migrations"))
+            .expect("Failed to run migrations");
+
+        pool
+    }
+}
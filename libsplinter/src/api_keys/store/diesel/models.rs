@@ -0,0 +1,35 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::schema::{api_key_scopes, api_keys};
+
+#[derive(Debug, PartialEq, Associations, Identifiable, Insertable, Queryable)]
+#[table_name = "api_keys"]
+#[primary_key(id)]
+pub(super) struct ApiKeyModel {
+    pub id: String,
+    pub display_name: String,
+    pub secret_hash: String,
+    pub created_at: i64,
+    pub expires_at: i64,
+}
+
+#[derive(Debug, PartialEq, Associations, Identifiable, Insertable, Queryable)]
+#[table_name = "api_key_scopes"]
+#[belongs_to(ApiKeyModel, foreign_key = "api_key_id")]
+#[primary_key(api_key_id, scope)]
+pub(super) struct ApiKeyScopeModel {
+    pub api_key_id: String,
+    pub scope: String,
+}
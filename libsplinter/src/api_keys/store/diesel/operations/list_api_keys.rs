@@ -0,0 +1,58 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::convert::TryInto;
+
+use diesel::prelude::*;
+
+use crate::api_keys::store::{
+    diesel::{
+        models::{ApiKeyModel, ApiKeyScopeModel},
+        schema::api_keys,
+    },
+    ApiKey, ApiKeyStoreError,
+};
+
+use super::ApiKeyStoreOperations;
+
+pub trait ApiKeyStoreListApiKeys {
+    fn list_api_keys(&self) -> Result<Box<dyn ExactSizeIterator<Item = ApiKey>>, ApiKeyStoreError>;
+}
+
+impl<'a, C> ApiKeyStoreListApiKeys for ApiKeyStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+    String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
+    i64: diesel::deserialize::FromSql<diesel::sql_types::BigInt, C::Backend>,
+{
+    fn list_api_keys(&self) -> Result<Box<dyn ExactSizeIterator<Item = ApiKey>>, ApiKeyStoreError> {
+        self.conn.transaction(|| {
+            let api_keys = api_keys::table
+                .order(api_keys::id)
+                .load::<ApiKeyModel>(self.conn)?;
+
+            let scopes = ApiKeyScopeModel::belonging_to(&api_keys)
+                .load::<ApiKeyScopeModel>(self.conn)?
+                .grouped_by(&api_keys);
+
+            let api_keys = api_keys
+                .into_iter()
+                .zip(scopes)
+                .map(|models| models.try_into())
+                .collect::<Result<Vec<ApiKey>, _>>()?;
+
+            Ok(Box::new(api_keys.into_iter()) as Box<dyn ExactSizeIterator<Item = ApiKey>>)
+        })
+    }
+}
@@ -0,0 +1,49 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diesel::{dsl::delete, prelude::*};
+
+use crate::api_keys::store::{
+    diesel::schema::{api_key_scopes, api_keys},
+    ApiKeyStoreError,
+};
+use crate::error::InvalidStateError;
+
+use super::ApiKeyStoreOperations;
+
+pub trait ApiKeyStoreRemoveApiKey {
+    fn remove_api_key(&self, id: &str) -> Result<(), ApiKeyStoreError>;
+}
+
+impl<'a, C> ApiKeyStoreRemoveApiKey for ApiKeyStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+    String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
+{
+    fn remove_api_key(&self, id: &str) -> Result<(), ApiKeyStoreError> {
+        self.conn.transaction::<_, _, _>(|| {
+            delete(api_key_scopes::table.filter(api_key_scopes::api_key_id.eq(id)))
+                .execute(self.conn)?;
+
+            let removed = delete(api_keys::table.filter(api_keys::id.eq(id))).execute(self.conn)?;
+            if removed == 0 {
+                return Err(ApiKeyStoreError::InvalidState(
+                    InvalidStateError::with_message(format!("API key {} does not exist", id)),
+                ));
+            }
+
+            Ok(())
+        })
+    }
+}
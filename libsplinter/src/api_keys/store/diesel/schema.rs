@@ -0,0 +1,33 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+table! {
+    api_keys (id) {
+        id -> Text,
+        display_name -> Text,
+        secret_hash -> Text,
+        created_at -> BigInt,
+        expires_at -> BigInt,
+    }
+}
+
+table! {
+    api_key_scopes (api_key_id, scope) {
+        api_key_id -> Text,
+        scope -> Text,
+    }
+}
+
+joinable!(api_key_scopes -> api_keys (api_key_id));
+allow_tables_to_appear_in_same_query!(api_keys, api_key_scopes);
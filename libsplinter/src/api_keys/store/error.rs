@@ -0,0 +1,64 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::error::{ConstraintViolationError, InternalError, InvalidStateError};
+
+/// Errors that may occur during [ApiKeyStore](super::ApiKeyStore) operations.
+#[derive(Debug)]
+pub enum ApiKeyStoreError {
+    InternalError(InternalError),
+    InvalidState(InvalidStateError),
+    ConstraintViolation(ConstraintViolationError),
+}
+
+impl fmt::Display for ApiKeyStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ApiKeyStoreError::InternalError(err) => err.fmt(f),
+            ApiKeyStoreError::InvalidState(err) => err.fmt(f),
+            ApiKeyStoreError::ConstraintViolation(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for ApiKeyStoreError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ApiKeyStoreError::InternalError(err) => Some(err),
+            ApiKeyStoreError::InvalidState(err) => Some(err),
+            ApiKeyStoreError::ConstraintViolation(err) => Some(err),
+        }
+    }
+}
+
+impl From<InternalError> for ApiKeyStoreError {
+    fn from(err: InternalError) -> Self {
+        ApiKeyStoreError::InternalError(err)
+    }
+}
+
+impl From<InvalidStateError> for ApiKeyStoreError {
+    fn from(err: InvalidStateError) -> Self {
+        ApiKeyStoreError::InvalidState(err)
+    }
+}
+
+impl From<ConstraintViolationError> for ApiKeyStoreError {
+    fn from(err: ConstraintViolationError) -> Self {
+        ApiKeyStoreError::ConstraintViolation(err)
+    }
+}
@@ -0,0 +1,242 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module defines the store trait for API keys.
+
+#[cfg(feature = "diesel")]
+mod diesel;
+mod error;
+
+use std::time::SystemTime;
+
+use crate::error::InvalidStateError;
+
+#[cfg(feature = "diesel")]
+pub use self::diesel::DieselApiKeyStore;
+
+pub use error::ApiKeyStoreError;
+
+/// An API key that has been issued to a machine client.
+///
+/// Only a hash of the key's secret is stored; the secret itself is only known to the client that
+/// the key was issued to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ApiKey {
+    id: String,
+    display_name: String,
+    secret_hash: String,
+    scopes: Vec<String>,
+    created_at: SystemTime,
+    expires_at: SystemTime,
+}
+
+impl ApiKey {
+    /// Returns the key's ID.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the key's display name.
+    pub fn display_name(&self) -> &str {
+        &self.display_name
+    }
+
+    /// Returns the hex-encoded SHA-256 hash of the key's secret.
+    pub fn secret_hash(&self) -> &str {
+        &self.secret_hash
+    }
+
+    /// Returns the permissions that the key may be used for.
+    pub fn scopes(&self) -> &[String] {
+        &self.scopes
+    }
+
+    /// Returns the time at which the key was created.
+    pub fn created_at(&self) -> SystemTime {
+        self.created_at
+    }
+
+    /// Returns the time at which the key expires.
+    pub fn expires_at(&self) -> SystemTime {
+        self.expires_at
+    }
+
+    /// Returns whether or not the key has expired.
+    pub fn is_expired(&self) -> bool {
+        SystemTime::now() >= self.expires_at
+    }
+
+    /// Returns whether or not the key's scopes include the given permission.
+    pub fn has_scope(&self, permission_id: &str) -> bool {
+        self.scopes.iter().any(|scope| scope == permission_id)
+    }
+
+    /// Converts this API key into its constituent parts.  These parts are in the tuple:
+    /// `(id, display_name, secret_hash, scopes, created_at, expires_at)`.
+    pub fn into_parts(self) -> (String, String, String, Vec<String>, SystemTime, SystemTime) {
+        (
+            self.id,
+            self.display_name,
+            self.secret_hash,
+            self.scopes,
+            self.created_at,
+            self.expires_at,
+        )
+    }
+}
+
+/// A builder to create new API keys.
+#[derive(Default)]
+pub struct ApiKeyBuilder {
+    id: Option<String>,
+    display_name: Option<String>,
+    secret_hash: Option<String>,
+    scopes: Vec<String>,
+    created_at: Option<SystemTime>,
+    expires_at: Option<SystemTime>,
+}
+
+impl ApiKeyBuilder {
+    /// Constructs a new builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the ID for the new API key.
+    pub fn with_id(mut self, id: String) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Sets the display name for the new API key.
+    pub fn with_display_name(mut self, display_name: String) -> Self {
+        self.display_name = Some(display_name);
+        self
+    }
+
+    /// Sets the hex-encoded SHA-256 hash of the new API key's secret.
+    pub fn with_secret_hash(mut self, secret_hash: String) -> Self {
+        self.secret_hash = Some(secret_hash);
+        self
+    }
+
+    /// Sets the permissions that the new API key may be used for.
+    pub fn with_scopes(mut self, scopes: Vec<String>) -> Self {
+        self.scopes = scopes;
+        self
+    }
+
+    /// Sets the time at which the new API key was created. Defaults to the current time.
+    pub fn with_created_at(mut self, created_at: SystemTime) -> Self {
+        self.created_at = Some(created_at);
+        self
+    }
+
+    /// Sets the time at which the new API key expires.
+    pub fn with_expires_at(mut self, expires_at: SystemTime) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    /// Builds the new API key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`InvalidStateError`] under the following conditions:
+    /// * no ID or an empty ID was provided
+    /// * no display name or an empty display name was provided
+    /// * no secret hash or an empty secret hash was provided
+    /// * empty scopes were provided
+    /// * no expiration time was provided, or it is not after the creation time
+    pub fn build(self) -> Result<ApiKey, InvalidStateError> {
+        let id = self.id.filter(|id| !id.is_empty()).ok_or_else(|| {
+            InvalidStateError::with_message("An API key requires a non-empty id field".into())
+        })?;
+
+        let display_name = self
+            .display_name
+            .filter(|display_name| !display_name.is_empty())
+            .ok_or_else(|| {
+                InvalidStateError::with_message(
+                    "An API key requires a non-empty display_name field".into(),
+                )
+            })?;
+
+        let secret_hash = self
+            .secret_hash
+            .filter(|secret_hash| !secret_hash.is_empty())
+            .ok_or_else(|| {
+                InvalidStateError::with_message(
+                    "An API key requires a non-empty secret_hash field".into(),
+                )
+            })?;
+
+        if self.scopes.is_empty() {
+            return Err(InvalidStateError::with_message(
+                "An API key requires at least one scope".into(),
+            ));
+        }
+
+        let created_at = self.created_at.unwrap_or_else(SystemTime::now);
+        let expires_at = self.expires_at.ok_or_else(|| {
+            InvalidStateError::with_message("An API key requires an expires_at field".into())
+        })?;
+        if expires_at <= created_at {
+            return Err(InvalidStateError::with_message(
+                "An API key must expire after it is created".into(),
+            ));
+        }
+
+        Ok(ApiKey {
+            id,
+            display_name,
+            secret_hash,
+            scopes: self.scopes,
+            created_at,
+            expires_at,
+        })
+    }
+}
+
+/// Defines methods for CRUD operations on API keys.
+pub trait ApiKeyStore: Send + Sync {
+    /// Returns the API key with the given ID, if one exists.
+    fn get_api_key(&self, id: &str) -> Result<Option<ApiKey>, ApiKeyStoreError>;
+
+    /// Lists all API keys.
+    fn list_api_keys(&self) -> Result<Box<dyn ExactSizeIterator<Item = ApiKey>>, ApiKeyStoreError>;
+
+    /// Adds an API key.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ConstraintViolation` error if a duplicate API key ID is added.
+    fn add_api_key(&self, api_key: ApiKey) -> Result<(), ApiKeyStoreError>;
+
+    /// Removes an API key.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `InvalidState` error if the API key does not exist.
+    fn remove_api_key(&self, id: &str) -> Result<(), ApiKeyStoreError>;
+
+    /// Clone into a boxed, dynamically dispatched store
+    fn clone_box(&self) -> Box<dyn ApiKeyStore>;
+}
+
+impl Clone for Box<dyn ApiKeyStore> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
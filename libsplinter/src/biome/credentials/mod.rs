@@ -15,6 +15,10 @@
 //! Defines a basic API to register and authenticate a User using a username and a password.
 //! Not recommended for use in production.
 
+#[cfg(feature = "biome-password-reset")]
+pub mod password_reset;
 #[cfg(feature = "rest-api")]
 pub mod rest_api;
 pub mod store;
+#[cfg(feature = "biome-totp")]
+pub mod totp;
@@ -0,0 +1,76 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Password reset tokens, which let a user who has forgotten their password set a new one.
+//!
+//! Tokens are delivered to the user out of band by a [`PasswordResetSender`], such as by email.
+//! Only the hash of a token is stored.
+
+use std::time::SystemTime;
+
+use openssl::rand::rand_bytes;
+use openssl::sha::sha256;
+
+use crate::error::InternalError;
+use crate::hex::to_hex;
+
+/// Length of a generated token in bytes
+const TOKEN_LENGTH: usize = 32;
+
+/// Delivers password reset tokens to users
+pub trait PasswordResetSender: Send + Sync {
+    /// Sends a password reset token to a user.
+    ///
+    /// # Arguments
+    ///
+    /// * `username` - The username of the user the token is for
+    /// * `token` - The password reset token
+    /// * `expires` - The time after which the token can no longer be used
+    fn send_password_reset_token(
+        &self,
+        username: &str,
+        token: &str,
+        expires: SystemTime,
+    ) -> Result<(), InternalError>;
+}
+
+/// Generates a new random password reset token
+pub fn generate_token() -> Result<String, InternalError> {
+    let mut token = [0; TOKEN_LENGTH];
+    rand_bytes(&mut token).map_err(|err| InternalError::from_source(Box::new(err)))?;
+    Ok(to_hex(&token))
+}
+
+/// Returns the hash of a password reset token, which is what gets stored. Tokens are random, so a
+/// single round of SHA-256 is sufficient.
+pub fn hash_token(token: &str) -> String {
+    to_hex(&sha256(token.trim().as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that generated tokens are unique and that a token's hash does not depend on
+    /// surrounding whitespace.
+    #[test]
+    fn generate_and_hash_token() {
+        let token = generate_token().expect("Failed to generate token");
+        assert_eq!(token.len(), TOKEN_LENGTH * 2);
+        assert_ne!(token, generate_token().expect("Failed to generate token"));
+
+        assert_eq!(hash_token(&token), hash_token(&format!(" {}\n", token)));
+        assert_ne!(hash_token(&token), token);
+    }
+}
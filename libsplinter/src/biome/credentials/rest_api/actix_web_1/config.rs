@@ -20,6 +20,12 @@ use crate::error::InvalidStateError;
 const DEFAULT_ISSUER: &str = "self-issued";
 const DEFAULT_DURATION: u64 = 5400; // in seconds = 90 minutes
 const DEFAULT_REFRESH_DURATION: u64 = 5_184_000; // in seconds = 60 days
+#[cfg(feature = "biome-login-lockout")]
+const DEFAULT_MAX_FAILED_LOGINS: u32 = 5;
+#[cfg(feature = "biome-login-lockout")]
+const DEFAULT_LOCKOUT_DURATION: u64 = 900; // in seconds = 15 minutes
+#[cfg(feature = "biome-password-reset")]
+const DEFAULT_PASSWORD_RESET_TOKEN_DURATION: u64 = 3600; // in seconds = 1 hour
 
 /// Configuration for Biome credentials REST resources
 #[derive(Deserialize, Debug)]
@@ -32,6 +38,15 @@ pub struct BiomeCredentialsRestConfig {
     refresh_token_duration: Duration,
     /// Cost for encrypting user's password
     password_encryption_cost: PasswordEncryptionCost,
+    /// Number of consecutive failed logins after which a user is locked out
+    #[cfg(feature = "biome-login-lockout")]
+    max_failed_logins: u32,
+    /// Duration that a user is locked out for
+    #[cfg(feature = "biome-login-lockout")]
+    lockout_duration: Duration,
+    /// Duration of password reset tokens issued by this service
+    #[cfg(feature = "biome-password-reset")]
+    password_reset_token_duration: Duration,
 }
 
 impl BiomeCredentialsRestConfig {
@@ -58,6 +73,27 @@ impl BiomeCredentialsRestConfig {
     pub fn password_encryption_cost(&self) -> PasswordEncryptionCost {
         self.password_encryption_cost
     }
+
+    /// Returns the number of consecutive failed logins after which a user is locked out. A value
+    /// of 0 disables lockout. Defaults to 5.
+    #[cfg(feature = "biome-login-lockout")]
+    pub fn max_failed_logins(&self) -> u32 {
+        self.max_failed_logins
+    }
+
+    /// Returns duration that a user is locked out for.
+    /// Defaults to 15 minutes.
+    #[cfg(feature = "biome-login-lockout")]
+    pub fn lockout_duration(&self) -> Duration {
+        self.lockout_duration
+    }
+
+    /// Returns duration that a password reset token is valid.
+    /// Defaults to 1 hour.
+    #[cfg(feature = "biome-password-reset")]
+    pub fn password_reset_token_duration(&self) -> Duration {
+        self.password_reset_token_duration
+    }
 }
 
 /// Builder for BiomeCredentialsRestConfig
@@ -66,6 +102,12 @@ pub struct BiomeCredentialsRestConfigBuilder {
     access_token_duration: Option<Duration>,
     refresh_token_duration: Option<Duration>,
     password_encryption_cost: Option<String>,
+    #[cfg(feature = "biome-login-lockout")]
+    max_failed_logins: Option<u32>,
+    #[cfg(feature = "biome-login-lockout")]
+    lockout_duration: Option<Duration>,
+    #[cfg(feature = "biome-password-reset")]
+    password_reset_token_duration: Option<Duration>,
 }
 
 impl Default for BiomeCredentialsRestConfigBuilder {
@@ -75,6 +117,14 @@ impl Default for BiomeCredentialsRestConfigBuilder {
             access_token_duration: Some(Duration::from_secs(DEFAULT_DURATION)),
             refresh_token_duration: Some(Duration::from_secs(DEFAULT_REFRESH_DURATION)),
             password_encryption_cost: Some("high".to_string()),
+            #[cfg(feature = "biome-login-lockout")]
+            max_failed_logins: Some(DEFAULT_MAX_FAILED_LOGINS),
+            #[cfg(feature = "biome-login-lockout")]
+            lockout_duration: Some(Duration::from_secs(DEFAULT_LOCKOUT_DURATION)),
+            #[cfg(feature = "biome-password-reset")]
+            password_reset_token_duration: Some(Duration::from_secs(
+                DEFAULT_PASSWORD_RESET_TOKEN_DURATION,
+            )),
         }
     }
 }
@@ -87,6 +137,12 @@ impl BiomeCredentialsRestConfigBuilder {
             access_token_duration: None,
             refresh_token_duration: None,
             password_encryption_cost: None,
+            #[cfg(feature = "biome-login-lockout")]
+            max_failed_logins: None,
+            #[cfg(feature = "biome-login-lockout")]
+            lockout_duration: None,
+            #[cfg(feature = "biome-password-reset")]
+            password_reset_token_duration: None,
         }
     }
 
@@ -115,6 +171,28 @@ impl BiomeCredentialsRestConfigBuilder {
         self
     }
 
+    /// Adds the number of consecutive failed logins after which a user is locked out. A value of
+    /// 0 disables lockout.
+    #[cfg(feature = "biome-login-lockout")]
+    pub fn with_max_failed_logins(mut self, max_failed_logins: u32) -> Self {
+        self.max_failed_logins = Some(max_failed_logins);
+        self
+    }
+
+    /// Adds a lockout duration in seconds.
+    #[cfg(feature = "biome-login-lockout")]
+    pub fn with_lockout_duration_in_secs(mut self, duration: u64) -> Self {
+        self.lockout_duration = Some(Duration::from_secs(duration));
+        self
+    }
+
+    /// Adds a password reset token duration in seconds.
+    #[cfg(feature = "biome-password-reset")]
+    pub fn with_password_reset_token_duration_in_secs(mut self, duration: u64) -> Self {
+        self.password_reset_token_duration = Some(Duration::from_secs(duration));
+        self
+    }
+
     /// Creates a new BiomeCredentialsRestConfig.
     pub fn build(self) -> Result<BiomeCredentialsRestConfig, InvalidStateError> {
         let issuer = self.issuer.unwrap_or_else(|| {
@@ -142,11 +220,30 @@ impl BiomeCredentialsRestConfigBuilder {
                 ))
             })?;
 
+        #[cfg(feature = "biome-login-lockout")]
+        let max_failed_logins = self.max_failed_logins.unwrap_or(DEFAULT_MAX_FAILED_LOGINS);
+
+        #[cfg(feature = "biome-login-lockout")]
+        let lockout_duration = self
+            .lockout_duration
+            .unwrap_or_else(|| Duration::from_secs(DEFAULT_LOCKOUT_DURATION));
+
+        #[cfg(feature = "biome-password-reset")]
+        let password_reset_token_duration = self
+            .password_reset_token_duration
+            .unwrap_or_else(|| Duration::from_secs(DEFAULT_PASSWORD_RESET_TOKEN_DURATION));
+
         Ok(BiomeCredentialsRestConfig {
             issuer,
             access_token_duration,
             refresh_token_duration,
             password_encryption_cost,
+            #[cfg(feature = "biome-login-lockout")]
+            max_failed_logins,
+            #[cfg(feature = "biome-login-lockout")]
+            lockout_duration,
+            #[cfg(feature = "biome-password-reset")]
+            password_reset_token_duration,
         })
     }
 }
@@ -0,0 +1,126 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use crate::actix_web::HttpResponse;
+use crate::biome::credentials::rest_api::actix_web_1::config::BiomeCredentialsRestConfig;
+#[cfg(feature = "authorization")]
+use crate::biome::credentials::rest_api::BIOME_USER_WRITE_PERMISSION;
+use crate::biome::credentials::store::{CredentialsStore, CredentialsStoreError, LoginFailures};
+use crate::futures::IntoFuture;
+use crate::protocol;
+use crate::rest_api::{
+    actix_web_1::{HandlerFunction, Method, ProtocolVersionRangeGuard, Resource},
+    ErrorResponse,
+};
+
+/// Returns whether or not a user is currently locked out
+pub(in crate::biome) fn is_locked_out(
+    credentials_store: &dyn CredentialsStore,
+    user_id: &str,
+) -> Result<bool, CredentialsStoreError> {
+    let failures = credentials_store.fetch_login_failures(user_id)?;
+    Ok(matches!(failures.locked_until, Some(locked_until) if locked_until > SystemTime::now()))
+}
+
+/// Records a failed login for a user. Once the configured number of consecutive failures is
+/// reached, the user is locked out for the configured duration and the count starts over.
+pub(in crate::biome) fn record_failed_login(
+    credentials_store: &dyn CredentialsStore,
+    rest_config: &BiomeCredentialsRestConfig,
+    user_id: &str,
+) -> Result<(), CredentialsStoreError> {
+    let max_failed_logins = rest_config.max_failed_logins();
+    if max_failed_logins == 0 {
+        return Ok(());
+    }
+
+    // The count is incremented in the store so that concurrent failures are all counted
+    let failed_attempts = credentials_store.add_failed_login(user_id)?;
+    if failed_attempts >= max_failed_logins {
+        credentials_store.set_login_failures(LoginFailures {
+            user_id: user_id.to_string(),
+            failed_attempts: 0,
+            locked_until: Some(SystemTime::now() + rest_config.lockout_duration()),
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Clears a user's failed logins after a successful login
+pub(in crate::biome) fn reset_failed_logins(
+    credentials_store: &dyn CredentialsStore,
+    user_id: &str,
+) -> Result<(), CredentialsStoreError> {
+    let failures = credentials_store.fetch_login_failures(user_id)?;
+    if failures.failed_attempts > 0 || failures.locked_until.is_some() {
+        credentials_store.remove_login_failures(user_id)?;
+    }
+    Ok(())
+}
+
+/// Defines the `/biome/users/{id}/lockout` REST resource for unlocking a user who has been locked
+/// out after too many failed logins
+pub fn make_unlock_route(credentials_store: Arc<dyn CredentialsStore>) -> Resource {
+    let resource = Resource::build("/biome/users/{id}/lockout").add_request_guard(
+        ProtocolVersionRangeGuard::new(
+            protocol::BIOME_LOGIN_LOCKOUT_PROTOCOL_MIN,
+            protocol::BIOME_PROTOCOL_VERSION,
+        ),
+    );
+    #[cfg(feature = "authorization")]
+    {
+        resource.add_method(
+            Method::Delete,
+            BIOME_USER_WRITE_PERMISSION,
+            add_unlock_method(credentials_store),
+        )
+    }
+    #[cfg(not(feature = "authorization"))]
+    {
+        resource.add_method(Method::Delete, add_unlock_method(credentials_store))
+    }
+}
+
+fn add_unlock_method(credentials_store: Arc<dyn CredentialsStore>) -> HandlerFunction {
+    Box::new(move |request, _| {
+        let user_id = match request.match_info().get("id") {
+            Some(user_id) => user_id.to_string(),
+            None => {
+                return Box::new(
+                    HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request(
+                            "Failed to process request: no user id",
+                        ))
+                        .into_future(),
+                )
+            }
+        };
+
+        Box::new(match credentials_store.remove_login_failures(&user_id) {
+            Ok(()) => HttpResponse::Ok()
+                .json(json!({ "message": "User successfully unlocked" }))
+                .into_future(),
+            Err(err) => {
+                error!("Failed to unlock user: {}", err);
+                HttpResponse::InternalServerError()
+                    .json(ErrorResponse::internal_error())
+                    .into_future()
+            }
+        })
+    })
+}
@@ -14,34 +14,67 @@
 
 use std::sync::Arc;
 
+#[cfg(feature = "biome-sessions")]
+use uuid::Uuid;
+
 use crate::actix_web::HttpResponse;
 use crate::biome::refresh_tokens::store::RefreshTokenStore;
+#[cfg(feature = "biome-sessions")]
+use crate::biome::refresh_tokens::store::{RefreshTokenSession, RefreshTokenSessionBuilder};
+#[cfg(feature = "biome-sessions")]
+use crate::error::InvalidStateError;
 use crate::futures::{Future, IntoFuture};
 use crate::protocol;
 #[cfg(feature = "authorization")]
 use crate::rest_api::auth::authorization::Permission;
 use crate::rest_api::{
-    actix_web_1::{into_bytes, Method, ProtocolVersionRangeGuard, Resource},
+    actix_web_1::{into_bytes, HandlerFunction, Method, ProtocolVersionRangeGuard, Resource},
     ErrorResponse,
 };
 
+#[cfg(feature = "biome-login-lockout")]
+use crate::biome::credentials::rest_api::actix_web_1::lockout::{
+    is_locked_out, record_failed_login, reset_failed_logins,
+};
+#[cfg(feature = "biome-sessions")]
+use crate::biome::credentials::rest_api::actix_web_1::sessions::SESSION_ID_CLAIM;
+#[cfg(feature = "biome-totp")]
+use crate::biome::credentials::rest_api::actix_web_1::totp::{
+    verify_second_factor, SecondFactorResult,
+};
 use crate::biome::credentials::rest_api::actix_web_1::BiomeCredentialsRestConfig;
 use crate::biome::credentials::rest_api::resources::credentials::UsernamePassword;
 use crate::biome::credentials::store::{CredentialsStore, CredentialsStoreError};
+#[cfg(feature = "biome-totp")]
+use crate::rest_api::secrets::SecretManager;
 use crate::rest_api::sessions::{AccessTokenIssuer, ClaimsBuilder, TokenIssuer};
 
 /// Defines a REST endpoint for login
 ///
+/// If Biome sessions are enabled, each successful login starts a new session for the user, so
+/// logging in on one device does not end the user's sessions on other devices.
+///
+/// If the user has enabled TOTP, either the current TOTP code or one of the user's recovery codes
+/// is also required. Without one, the response is a 401 with `"totp_required": true`.
+///
+/// If login lockout is enabled, a user who fails to log in too many times in a row is locked out
+/// for a while. Logins for a locked out user are rejected with a 403, even if the password is
+/// correct.
+///
 /// The payload should be in the JSON format:
 ///   {
 ///       "username": <existing username of the user>
 ///       "hashed_password": <hash of the user's existing password>
+///       "display_name": <optional name for the session, if Biome sessions are enabled>
+///       "totp_code": <the current TOTP code, if the user has enabled TOTP>
+///       "recovery_code": <a recovery code, which may be used once instead of a TOTP code>
 ///   }
 pub fn make_login_route(
     credentials_store: Arc<dyn CredentialsStore>,
     refresh_token_store: Arc<dyn RefreshTokenStore>,
     rest_config: Arc<BiomeCredentialsRestConfig>,
     token_issuer: Arc<AccessTokenIssuer>,
+    #[cfg(feature = "biome-totp")] totp_secret_manager: Arc<dyn SecretManager>,
 ) -> Resource {
     let resource =
         Resource::build("/biome/login").add_request_guard(ProtocolVersionRangeGuard::new(
@@ -53,164 +86,69 @@ pub fn make_login_route(
         resource.add_method(
             Method::Post,
             Permission::AllowUnauthenticated,
-            move |_, payload| {
-                let credentials_store = credentials_store.clone();
-                let rest_config = rest_config.clone();
-                let token_issuer = token_issuer.clone();
-                let refresh_token_store = refresh_token_store.clone();
-                Box::new(into_bytes(payload).and_then(move |bytes| {
-                    let username_password = match serde_json::from_slice::<UsernamePassword>(&bytes)
-                    {
-                        Ok(val) => val,
-                        Err(err) => {
-                            debug!("Error parsing payload {}", err);
-                            return HttpResponse::BadRequest()
-                                .json(ErrorResponse::bad_request(&format!(
-                                    "Failed to parse payload: {}",
-                                    err
-                                )))
-                                .into_future();
-                        }
-                    };
-
-                    let credentials = match credentials_store
-                        .fetch_credential_by_username(&username_password.username)
-                    {
-                        Ok(credentials) => credentials,
-                        Err(err) => {
-                            debug!("Failed to fetch credentials {}", err);
-                            match err {
-                                CredentialsStoreError::NotFoundError(_) => {
-                                    return HttpResponse::BadRequest()
-                                        .json(ErrorResponse::bad_request(&format!(
-                                            "Username not found: {}",
-                                            username_password.username
-                                        )))
-                                        .into_future();
-                                }
-                                _ => {
-                                    return HttpResponse::InternalServerError()
-                                        .json(ErrorResponse::internal_error())
-                                        .into_future()
-                                }
-                            }
-                        }
-                    };
-
-                    match credentials.verify_password(&username_password.hashed_password) {
-                        Ok(is_valid) => {
-                            if is_valid {
-                                let claim_builder = ClaimsBuilder::default();
-                                let claim = match claim_builder
-                                    .with_user_id(&credentials.user_id)
-                                    .with_issuer(&rest_config.issuer())
-                                    .with_duration(rest_config.access_token_duration())
-                                    .build()
-                                {
-                                    Ok(claim) => claim,
-                                    Err(err) => {
-                                        debug!("Failed to build claim {}", err);
-                                        return HttpResponse::InternalServerError()
-                                            .json(ErrorResponse::internal_error())
-                                            .into_future();
-                                    }
-                                };
-
-                                let token = match token_issuer.issue_token_with_claims(claim) {
-                                    Ok(token) => format!("Biome:{}", token),
-                                    Err(err) => {
-                                        debug!("Failed to issue token {}", err);
-                                        return HttpResponse::InternalServerError()
-                                            .json(ErrorResponse::internal_error())
-                                            .into_future();
-                                    }
-                                };
-
-                                let refresh_claims = match ClaimsBuilder::default()
-                                    .with_user_id(&credentials.user_id)
-                                    .with_issuer(&rest_config.issuer())
-                                    .with_duration(rest_config.refresh_token_duration())
-                                    .build()
-                                {
-                                    Ok(claims) => claims,
-                                    Err(err) => {
-                                        debug!("Failed to build refresh claim {}", err);
-                                        return HttpResponse::InternalServerError()
-                                            .json(ErrorResponse::internal_error())
-                                            .into_future();
-                                    }
-                                };
-
-                                let refresh_token = match token_issuer
-                                    .issue_refresh_token_with_claims(refresh_claims)
-                                {
-                                    Ok(token) => token,
-                                    Err(err) => {
-                                        debug!("Failed to issue refresh token {}", err);
-                                        return HttpResponse::InternalServerError()
-                                            .json(ErrorResponse::internal_error())
-                                            .into_future();
-                                    }
-                                };
-
-                                if let Err(err) = refresh_token_store
-                                    .add_token(&credentials.user_id, &refresh_token)
-                                {
-                                    debug!("Failed to store refresh token {}", err);
-                                    return HttpResponse::InternalServerError()
-                                        .json(ErrorResponse::internal_error())
-                                        .into_future();
-                                }
-
-                                HttpResponse::Ok()
-                                    .json(json!({
-                                        "message": "Successful login",
-                                        "user_id": credentials.user_id,
-                                        "token": token,
-                                        "refresh_token": refresh_token,
-                                    }))
-                                    .into_future()
-                            } else {
-                                HttpResponse::BadRequest()
-                                    .json(ErrorResponse::bad_request("Invalid password"))
-                                    .into_future()
-                            }
-                        }
-                        Err(err) => {
-                            debug!("Failed to verify password {}", err);
-                            HttpResponse::InternalServerError()
-                                .json(ErrorResponse::internal_error())
-                                .into_future()
-                        }
-                    }
-                }))
-            },
+            add_login_method(
+                credentials_store,
+                refresh_token_store,
+                rest_config,
+                token_issuer,
+                #[cfg(feature = "biome-totp")]
+                totp_secret_manager,
+            ),
         )
     }
     #[cfg(not(feature = "authorization"))]
     {
-        resource.add_method(Method::Post, move |_, payload| {
-            let credentials_store = credentials_store.clone();
-            let rest_config = rest_config.clone();
-            let token_issuer = token_issuer.clone();
-            let refresh_token_store = refresh_token_store.clone();
-            Box::new(into_bytes(payload).and_then(move |bytes| {
-                let username_password = match serde_json::from_slice::<UsernamePassword>(&bytes) {
-                    Ok(val) => val,
-                    Err(err) => {
-                        debug!("Error parsing payload {}", err);
-                        return HttpResponse::BadRequest()
-                            .json(ErrorResponse::bad_request(&format!(
-                                "Failed to parse payload: {}",
-                                err
-                            )))
-                            .into_future();
-                    }
-                };
+        resource.add_method(
+            Method::Post,
+            add_login_method(
+                credentials_store,
+                refresh_token_store,
+                rest_config,
+                token_issuer,
+                #[cfg(feature = "biome-totp")]
+                totp_secret_manager,
+            ),
+        )
+    }
+}
 
-                let credentials = match credentials_store
-                    .fetch_credential_by_username(&username_password.username)
-                {
+#[cfg_attr(not(feature = "biome-sessions"), allow(unused_variables))]
+fn add_login_method(
+    credentials_store: Arc<dyn CredentialsStore>,
+    refresh_token_store: Arc<dyn RefreshTokenStore>,
+    rest_config: Arc<BiomeCredentialsRestConfig>,
+    token_issuer: Arc<AccessTokenIssuer>,
+    #[cfg(feature = "biome-totp")] totp_secret_manager: Arc<dyn SecretManager>,
+) -> HandlerFunction {
+    Box::new(move |request, payload| {
+        #[cfg(feature = "biome-sessions")]
+        let user_agent = request
+            .headers()
+            .get("User-Agent")
+            .and_then(|value| value.to_str().ok())
+            .map(String::from);
+        let credentials_store = credentials_store.clone();
+        let rest_config = rest_config.clone();
+        let token_issuer = token_issuer.clone();
+        let refresh_token_store = refresh_token_store.clone();
+        #[cfg(feature = "biome-totp")]
+        let totp_secret_manager = totp_secret_manager.clone();
+        Box::new(into_bytes(payload).and_then(move |bytes| {
+            let username_password = match serde_json::from_slice::<UsernamePassword>(&bytes) {
+                Ok(val) => val,
+                Err(err) => {
+                    debug!("Error parsing payload {}", err);
+                    return HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request(&format!(
+                            "Failed to parse payload: {}",
+                            err
+                        )))
+                        .into_future();
+                }
+            };
+
+            let credentials =
+                match credentials_store.fetch_credential_by_username(&username_password.username) {
                     Ok(credentials) => credentials,
                     Err(err) => {
                         debug!("Failed to fetch credentials {}", err);
@@ -232,62 +170,170 @@ pub fn make_login_route(
                     }
                 };
 
-                match credentials.verify_password(&username_password.hashed_password) {
-                    Ok(is_valid) => {
-                        if is_valid {
-                            let claim_builder = ClaimsBuilder::default();
-                            let claim = match claim_builder
-                                .with_user_id(&credentials.user_id)
-                                .with_issuer(&rest_config.issuer())
-                                .with_duration(rest_config.access_token_duration())
-                                .build()
-                            {
-                                Ok(claim) => claim,
-                                Err(err) => {
-                                    debug!("Failed to build claim {}", err);
-                                    return HttpResponse::InternalServerError()
-                                        .json(ErrorResponse::internal_error())
-                                        .into_future();
-                                }
-                            };
+            #[cfg(feature = "biome-login-lockout")]
+            match is_locked_out(&*credentials_store, &credentials.user_id) {
+                Ok(false) => (),
+                Ok(true) => {
+                    return HttpResponse::Forbidden()
+                        .json(ErrorResponse::forbidden("Account is locked"))
+                        .into_future();
+                }
+                Err(err) => {
+                    error!("Failed to check lockout {}", err);
+                    return HttpResponse::InternalServerError()
+                        .json(ErrorResponse::internal_error())
+                        .into_future();
+                }
+            }
 
-                            let token = match token_issuer.issue_token_with_claims(claim) {
-                                Ok(token) => format!("Biome:{}", token),
-                                Err(err) => {
-                                    debug!("Failed to issue token {}", err);
+            match credentials.verify_password(&username_password.hashed_password) {
+                Ok(is_valid) => {
+                    if is_valid {
+                        #[cfg(feature = "biome-totp")]
+                        match verify_second_factor(
+                            &*credentials_store,
+                            &*totp_secret_manager,
+                            &credentials.user_id,
+                            username_password.totp_code.as_deref(),
+                            username_password.recovery_code.as_deref(),
+                        ) {
+                            SecondFactorResult::Verified => (),
+                            SecondFactorResult::Required => {
+                                return HttpResponse::Unauthorized()
+                                    .json(json!({
+                                        "code": "401",
+                                        "message": "A TOTP code is required",
+                                        "totp_required": true,
+                                    }))
+                                    .into_future();
+                            }
+                            SecondFactorResult::Invalid => {
+                                #[cfg(feature = "biome-login-lockout")]
+                                if let Err(err) = record_failed_login(
+                                    &*credentials_store,
+                                    &rest_config,
+                                    &credentials.user_id,
+                                ) {
+                                    error!("Failed to record failed login {}", err);
                                     return HttpResponse::InternalServerError()
                                         .json(ErrorResponse::internal_error())
                                         .into_future();
                                 }
-                            };
+                                return HttpResponse::BadRequest()
+                                    .json(ErrorResponse::bad_request("Invalid TOTP code"))
+                                    .into_future();
+                            }
+                            SecondFactorResult::Failed => {
+                                return HttpResponse::InternalServerError()
+                                    .json(ErrorResponse::internal_error())
+                                    .into_future();
+                            }
+                        }
 
-                            let refresh_claims = match ClaimsBuilder::default()
-                                .with_user_id(&credentials.user_id)
-                                .with_issuer(&rest_config.issuer())
-                                .with_duration(rest_config.refresh_token_duration())
-                                .build()
-                            {
-                                Ok(claims) => claims,
+                        #[cfg(feature = "biome-login-lockout")]
+                        if let Err(err) =
+                            reset_failed_logins(&*credentials_store, &credentials.user_id)
+                        {
+                            error!("Failed to reset failed logins {}", err);
+                            return HttpResponse::InternalServerError()
+                                .json(ErrorResponse::internal_error())
+                                .into_future();
+                        }
+
+                        #[cfg(feature = "biome-sessions")]
+                        let session_id = Uuid::new_v4().to_string();
+                        let claim_builder = ClaimsBuilder::default()
+                            .with_user_id(&credentials.user_id)
+                            .with_issuer(&rest_config.issuer())
+                            .with_duration(rest_config.access_token_duration());
+                        #[cfg(feature = "biome-sessions")]
+                        let claim_builder =
+                            claim_builder.with_custom_claim(SESSION_ID_CLAIM, &session_id);
+                        let claim = match claim_builder.build() {
+                            Ok(claim) => claim,
+                            Err(err) => {
+                                debug!("Failed to build claim {}", err);
+                                return HttpResponse::InternalServerError()
+                                    .json(ErrorResponse::internal_error())
+                                    .into_future();
+                            }
+                        };
+
+                        let token = match token_issuer.issue_token_with_claims(claim) {
+                            Ok(token) => format!("Biome:{}", token),
+                            Err(err) => {
+                                debug!("Failed to issue token {}", err);
+                                return HttpResponse::InternalServerError()
+                                    .json(ErrorResponse::internal_error())
+                                    .into_future();
+                            }
+                        };
+
+                        let refresh_claim_builder = ClaimsBuilder::default()
+                            .with_user_id(&credentials.user_id)
+                            .with_issuer(&rest_config.issuer())
+                            .with_duration(rest_config.refresh_token_duration());
+                        #[cfg(feature = "biome-sessions")]
+                        let refresh_claim_builder =
+                            refresh_claim_builder.with_custom_claim(SESSION_ID_CLAIM, &session_id);
+                        let refresh_claims = match refresh_claim_builder.build() {
+                            Ok(claims) => claims,
+                            Err(err) => {
+                                debug!("Failed to build refresh claim {}", err);
+                                return HttpResponse::InternalServerError()
+                                    .json(ErrorResponse::internal_error())
+                                    .into_future();
+                            }
+                        };
+
+                        let refresh_token =
+                            match token_issuer.issue_refresh_token_with_claims(refresh_claims) {
+                                Ok(token) => token,
                                 Err(err) => {
-                                    debug!("Failed to build refresh claim {}", err);
+                                    debug!("Failed to issue refresh token {}", err);
                                     return HttpResponse::InternalServerError()
                                         .json(ErrorResponse::internal_error())
                                         .into_future();
                                 }
                             };
 
-                            let refresh_token = match token_issuer
-                                .issue_refresh_token_with_claims(refresh_claims)
-                            {
-                                Ok(token) => token,
+                        #[cfg(feature = "biome-sessions")]
+                        {
+                            let session = match build_session(
+                                &session_id,
+                                &credentials.user_id,
+                                &refresh_token,
+                                username_password.display_name,
+                                user_agent,
+                            ) {
+                                Ok(session) => session,
                                 Err(err) => {
-                                    debug!("Failed to issue refresh token {}", err);
+                                    debug!("Failed to build session {}", err);
                                     return HttpResponse::InternalServerError()
                                         .json(ErrorResponse::internal_error())
                                         .into_future();
                                 }
                             };
 
+                            if let Err(err) = refresh_token_store.add_session(session) {
+                                debug!("Failed to store refresh token {}", err);
+                                return HttpResponse::InternalServerError()
+                                    .json(ErrorResponse::internal_error())
+                                    .into_future();
+                            }
+
+                            HttpResponse::Ok()
+                                .json(json!({
+                                    "message": "Successful login",
+                                    "user_id": credentials.user_id,
+                                    "token": token,
+                                    "refresh_token": refresh_token,
+                                    "session_id": session_id,
+                                }))
+                                .into_future()
+                        }
+                        #[cfg(not(feature = "biome-sessions"))]
+                        {
                             if let Err(err) =
                                 refresh_token_store.add_token(&credentials.user_id, &refresh_token)
                             {
@@ -305,20 +351,53 @@ pub fn make_login_route(
                                     "refresh_token": refresh_token,
                                 }))
                                 .into_future()
-                        } else {
-                            HttpResponse::BadRequest()
-                                .json(ErrorResponse::bad_request("Invalid password"))
-                                .into_future()
                         }
-                    }
-                    Err(err) => {
-                        debug!("Failed to verify password {}", err);
-                        HttpResponse::InternalServerError()
-                            .json(ErrorResponse::internal_error())
+                    } else {
+                        #[cfg(feature = "biome-login-lockout")]
+                        if let Err(err) = record_failed_login(
+                            &*credentials_store,
+                            &rest_config,
+                            &credentials.user_id,
+                        ) {
+                            error!("Failed to record failed login {}", err);
+                            return HttpResponse::InternalServerError()
+                                .json(ErrorResponse::internal_error())
+                                .into_future();
+                        }
+                        HttpResponse::BadRequest()
+                            .json(ErrorResponse::bad_request("Invalid password"))
                             .into_future()
                     }
                 }
-            }))
-        })
+                Err(err) => {
+                    debug!("Failed to verify password {}", err);
+                    HttpResponse::InternalServerError()
+                        .json(ErrorResponse::internal_error())
+                        .into_future()
+                }
+            }
+        }))
+    })
+}
+
+/// Builds the session started by a login
+#[cfg(feature = "biome-sessions")]
+fn build_session(
+    session_id: &str,
+    user_id: &str,
+    refresh_token: &str,
+    display_name: Option<String>,
+    user_agent: Option<String>,
+) -> Result<RefreshTokenSession, InvalidStateError> {
+    let mut builder = RefreshTokenSessionBuilder::new()
+        .with_session_id(session_id.to_string())
+        .with_user_id(user_id.to_string())
+        .with_token(refresh_token.to_string());
+    if let Some(display_name) = display_name {
+        builder = builder.with_display_name(display_name);
+    }
+    if let Some(user_agent) = user_agent {
+        builder = builder.with_user_agent(user_agent);
     }
+    builder.build()
 }
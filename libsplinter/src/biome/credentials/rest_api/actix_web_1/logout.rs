@@ -15,6 +15,8 @@
 use std::sync::Arc;
 
 use crate::actix_web::HttpResponse;
+#[cfg(feature = "biome-sessions")]
+use crate::biome::credentials::rest_api::actix_web_1::sessions::session_id_from_claims;
 use crate::biome::credentials::rest_api::{
     actix_web_1::{authorize::authorize_user, config::BiomeCredentialsRestConfig},
     resources::authorize::AuthorizationResult,
@@ -33,6 +35,9 @@ use crate::rest_api::{
 
 /// Defines a REST endpoint to remove any refresh tokens belonging to the user.
 ///
+/// If Biome sessions are enabled, only the session the request was made with is logged out, by
+/// removing the session's refresh token. Tokens issued before sessions were introduced do not
+/// identify a session; all of the user's refresh tokens are removed instead.
 pub fn make_logout_route(
     refresh_token_store: Arc<dyn RefreshTokenStore>,
     secret_manager: Arc<dyn SecretManager>,
@@ -70,8 +75,8 @@ pub fn add_logout_route(
         let secret_manager = secret_manager.clone();
         let refresh_token_store = refresh_token_store.clone();
         let validation = default_validation(&rest_config.issuer());
-        let user_id = match authorize_user(&request, &secret_manager, &validation) {
-            AuthorizationResult::Authorized(claims) => claims.user_id(),
+        let claims = match authorize_user(&request, &secret_manager, &validation) {
+            AuthorizationResult::Authorized(claims) => claims,
             AuthorizationResult::Unauthorized => {
                 return Box::new(
                     HttpResponse::Unauthorized()
@@ -88,7 +93,16 @@ pub fn add_logout_route(
             }
         };
 
-        Box::new(match refresh_token_store.remove_token(&user_id) {
+        let user_id = claims.user_id();
+        #[cfg(feature = "biome-sessions")]
+        let result = match session_id_from_claims(&claims) {
+            Some(session_id) => refresh_token_store.remove_session(&user_id, &session_id),
+            None => refresh_token_store.remove_token(&user_id),
+        };
+        #[cfg(not(feature = "biome-sessions"))]
+        let result = refresh_token_store.remove_token(&user_id);
+
+        Box::new(match result {
             Ok(()) => HttpResponse::Ok()
                 .json(json!({
                     "message": "User successfully logged out"
@@ -14,15 +14,25 @@
 
 mod authorize;
 mod config;
+#[cfg(feature = "biome-login-lockout")]
+pub(super) mod lockout;
 mod login;
 mod logout;
+#[cfg(feature = "biome-password-reset")]
+mod password_reset;
 mod register;
+#[cfg(feature = "biome-sessions")]
+mod sessions;
 mod token;
+#[cfg(feature = "biome-totp")]
+mod totp;
 mod user;
 mod verify;
 
 use std::sync::Arc;
 
+#[cfg(feature = "biome-password-reset")]
+use crate::biome::credentials::password_reset::PasswordResetSender;
 #[cfg(feature = "biome-key-management")]
 use crate::biome::key_management::store::KeyStore;
 use crate::biome::{
@@ -41,20 +51,35 @@ pub use config::{BiomeCredentialsRestConfig, BiomeCredentialsRestConfigBuilder};
 /// Provides the following REST API endpoints for Biome credentials:
 ///
 /// * `POST /biome/login` - Login enpoint for getting access tokens and refresh tokens
-/// * `PATCH /biome/logout` - Login endpoint for removing refresh tokens
+/// * `PATCH /biome/logout` - Logout endpoint for removing the refresh token of the current session
+/// * `POST /biome/password_reset` - Send a password reset token to a user, if a
+///   `PasswordResetSender` has been set
+/// * `POST /biome/password_reset/confirm` - Set a user's new password with a password reset token
 /// * `POST /biome/register - Creates credentials for a user
+/// * `GET /biome/sessions` - List the authorized user's sessions
+/// * `DELETE /biome/sessions` - Log the authorized user out of all of their sessions
+/// * `DELETE /biome/sessions/{id}` - Revoke one of the authorized user's sessions
 /// * `POST /biome/token` - Creates a new access token for the authorized user
+/// * `GET /biome/totp` - Get the authorized user's TOTP enrollment status
+/// * `POST /biome/totp` - Start TOTP enrollment for the authorized user
+/// * `POST /biome/totp/confirm` - Confirm the authorized user's TOTP enrollment
 /// * `POST /biome/verify` - Verify a users password
 /// * `GET /biome/user` - Get a list of all users in biome
 /// * `PUT /biome/user/{id}` - Update user with specified ID
 /// * `GET /biome/user/{id}` - Retrieve user with specified ID
 /// * `DELETE /biome/user/{id}` - Remove user with specified ID
+/// * `DELETE /biome/users/{id}/lockout` - Unlock user with specified ID
+/// * `DELETE /biome/users/{id}/totp` - Reset TOTP for user with specified ID
 pub struct BiomeCredentialsRestResourceProvider {
     #[cfg(feature = "biome-key-management")]
     key_store: Arc<dyn KeyStore>,
     credentials_config: Arc<BiomeCredentialsRestConfig>,
     token_secret_manager: Arc<dyn SecretManager>,
     refresh_token_secret_manager: Arc<dyn SecretManager>,
+    #[cfg(feature = "biome-totp")]
+    totp_secret_manager: Arc<dyn SecretManager>,
+    #[cfg(feature = "biome-password-reset")]
+    password_reset_sender: Option<Arc<dyn PasswordResetSender>>,
     refresh_token_store: Arc<dyn RefreshTokenStore>,
     credentials_store: Arc<dyn CredentialsStore>,
 }
@@ -87,6 +112,8 @@ impl RestResourceProvider for BiomeCredentialsRestResourceProvider {
                 self.token_secret_manager.clone(),
                 self.refresh_token_secret_manager.clone(),
             )),
+            #[cfg(feature = "biome-totp")]
+            self.totp_secret_manager.clone(),
         ));
         resources.push(token::make_token_route(
             self.refresh_token_store.clone(),
@@ -103,12 +130,60 @@ impl RestResourceProvider for BiomeCredentialsRestResourceProvider {
             self.token_secret_manager.clone(),
             self.credentials_config.clone(),
         ));
+        #[cfg(feature = "biome-sessions")]
+        {
+            resources.push(sessions::make_sessions_route(
+                self.refresh_token_store.clone(),
+                self.token_secret_manager.clone(),
+                self.credentials_config.clone(),
+            ));
+            resources.push(sessions::make_session_route(
+                self.refresh_token_store.clone(),
+                self.token_secret_manager.clone(),
+                self.credentials_config.clone(),
+            ));
+        }
 
         resources.push(register::make_register_route(
             self.credentials_store.clone(),
             self.credentials_config.clone(),
         ));
 
+        #[cfg(feature = "biome-totp")]
+        {
+            resources.push(totp::make_totp_route(
+                self.credentials_store.clone(),
+                self.totp_secret_manager.clone(),
+                self.token_secret_manager.clone(),
+                self.credentials_config.clone(),
+            ));
+            resources.push(totp::make_confirm_totp_route(
+                self.credentials_store.clone(),
+                self.totp_secret_manager.clone(),
+                self.token_secret_manager.clone(),
+                self.credentials_config.clone(),
+            ));
+            resources.push(totp::make_reset_totp_route(self.credentials_store.clone()));
+        }
+        #[cfg(feature = "biome-login-lockout")]
+        resources.push(lockout::make_unlock_route(self.credentials_store.clone()));
+        #[cfg(feature = "biome-password-reset")]
+        match &self.password_reset_sender {
+            Some(sender) => {
+                resources.push(password_reset::make_password_reset_route(
+                    self.credentials_store.clone(),
+                    self.credentials_config.clone(),
+                    sender.clone(),
+                ));
+                resources.push(password_reset::make_confirm_password_reset_route(
+                    self.credentials_store.clone(),
+                    self.refresh_token_store.clone(),
+                    self.credentials_config.clone(),
+                ));
+            }
+            None => debug!("No PasswordResetSender set; password reset endpoints are disabled"),
+        }
+
         #[cfg(feature = "biome-key-management")]
         {
             resources.push(user::make_user_routes(
@@ -130,6 +205,10 @@ pub struct BiomeCredentialsRestResourceProviderBuilder {
     credentials_config: Option<BiomeCredentialsRestConfig>,
     token_secret_manager: Option<Arc<dyn SecretManager>>,
     refresh_token_secret_manager: Option<Arc<dyn SecretManager>>,
+    #[cfg(feature = "biome-totp")]
+    totp_secret_manager: Option<Arc<dyn SecretManager>>,
+    #[cfg(feature = "biome-password-reset")]
+    password_reset_sender: Option<Arc<dyn PasswordResetSender>>,
     refresh_token_store: Option<Arc<dyn RefreshTokenStore>>,
     credentials_store: Option<Arc<dyn CredentialsStore>>,
 }
@@ -203,6 +282,38 @@ impl BiomeCredentialsRestResourceProviderBuilder {
         self
     }
 
+    /// Sets a SecretManager for encrypting TOTP secrets for the
+    /// BiomeCredentialsRestResourceProvider
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_manager`: the SecretManager to be used for fetching the secret that TOTP secrets
+    ///   are encrypted with. The secret must not change, or users will be unable to log in with
+    ///   TOTP; a `FileSecretManager` is suitable.
+    #[cfg(feature = "biome-totp")]
+    pub fn with_totp_secret_manager(
+        mut self,
+        secret_manager: impl SecretManager + 'static,
+    ) -> BiomeCredentialsRestResourceProviderBuilder {
+        self.totp_secret_manager = Some(Arc::new(secret_manager));
+        self
+    }
+
+    /// Sets a PasswordResetSender for the BiomeCredentialsRestResourceProvider
+    ///
+    /// # Arguments
+    ///
+    /// * `sender`: the PasswordResetSender to be used for delivering password reset tokens to
+    ///   users. The password reset endpoints are only provided if a sender is set.
+    #[cfg(feature = "biome-password-reset")]
+    pub fn with_password_reset_sender(
+        mut self,
+        sender: impl PasswordResetSender + 'static,
+    ) -> BiomeCredentialsRestResourceProviderBuilder {
+        self.password_reset_sender = Some(Arc::new(sender));
+        self
+    }
+
     /// Sets a Refresh token store for the refresh tokens for the BiomeCredentialsRestResourceProvider
     ///
     /// # Arguments
@@ -244,6 +355,15 @@ impl BiomeCredentialsRestResourceProviderBuilder {
             Arc::new(AutoSecretManager::default())
         });
 
+        #[cfg(feature = "biome-totp")]
+        let totp_secret_manager = self.totp_secret_manager.unwrap_or_else(|| {
+            warn!(
+                "Building BiomeCredentialsRestResourceProvider with default TOTP SecretManager; \
+                 TOTP secrets will not be readable after a restart"
+            );
+            Arc::new(AutoSecretManager::default())
+        });
+
         let refresh_token_store = self.refresh_token_store.ok_or_else(|| {
             InvalidStateError::with_message("Missing refresh token store".to_string())
         })?;
@@ -258,6 +378,10 @@ impl BiomeCredentialsRestResourceProviderBuilder {
             credentials_config: Arc::new(credentials_config),
             token_secret_manager,
             refresh_token_secret_manager,
+            #[cfg(feature = "biome-totp")]
+            totp_secret_manager,
+            #[cfg(feature = "biome-password-reset")]
+            password_reset_sender: self.password_reset_sender,
             refresh_token_store,
             credentials_store,
         })
@@ -308,6 +432,58 @@ mod tests {
         pub user_id: String,
         pub token: String,
         pub refresh_token: String,
+        #[cfg(feature = "biome-sessions")]
+        pub session_id: String,
+    }
+
+    #[cfg(feature = "biome-sessions")]
+    #[derive(Serialize)]
+    struct PostLogin {
+        pub username: String,
+        pub hashed_password: String,
+        pub display_name: String,
+    }
+
+    #[cfg(feature = "biome-sessions")]
+    #[derive(Deserialize)]
+    struct ListSessionsResponse {
+        pub data: Vec<SessionResponse>,
+    }
+
+    #[cfg(feature = "biome-sessions")]
+    #[derive(Deserialize)]
+    struct SessionResponse {
+        pub session_id: String,
+        pub display_name: Option<String>,
+        pub current: bool,
+    }
+
+    #[cfg(feature = "biome-totp")]
+    #[derive(Serialize)]
+    struct PostTotpLogin {
+        pub username: String,
+        pub hashed_password: String,
+        pub totp_code: Option<String>,
+        pub recovery_code: Option<String>,
+    }
+
+    #[cfg(feature = "biome-totp")]
+    #[derive(Deserialize)]
+    struct TotpEnrollmentResponse {
+        pub data: TotpEnrollment,
+    }
+
+    #[cfg(feature = "biome-totp")]
+    #[derive(Deserialize)]
+    struct TotpEnrollment {
+        pub secret: String,
+        pub provisioning_uri: String,
+    }
+
+    #[cfg(feature = "biome-totp")]
+    #[derive(Deserialize)]
+    struct TotpConfirmResponse {
+        pub recovery_codes: Vec<String>,
     }
 
     #[cfg(feature = "biome-key-management")]
@@ -334,6 +510,29 @@ mod tests {
         pub display_name: String,
     }
 
+    /// A `PasswordResetSender` that keeps the last token sent to each user
+    #[cfg(feature = "biome-password-reset")]
+    #[derive(Clone, Default)]
+    struct CapturingPasswordResetSender {
+        tokens: Arc<std::sync::Mutex<std::collections::HashMap<String, String>>>,
+    }
+
+    #[cfg(feature = "biome-password-reset")]
+    impl PasswordResetSender for CapturingPasswordResetSender {
+        fn send_password_reset_token(
+            &self,
+            username: &str,
+            token: &str,
+            _expires: std::time::SystemTime,
+        ) -> Result<(), crate::error::InternalError> {
+            self.tokens
+                .lock()
+                .expect("lock poisoned")
+                .insert(username.to_string(), token.to_string());
+            Ok(())
+        }
+    }
+
     #[derive(Deserialize, Serialize)]
     struct PostVerify {
         username: String,
@@ -351,7 +550,14 @@ mod tests {
         token: String,
     }
 
-    fn start_biome_rest_api() -> (RestApiShutdownHandle, thread::JoinHandle<()>) {
+    /// Starts the REST API, applying `configure` to the resource provider builder after the
+    /// defaults used by all tests have been set
+    fn start_biome_rest_api<C>(configure: C) -> (RestApiShutdownHandle, thread::JoinHandle<()>)
+    where
+        C: FnOnce(
+            BiomeCredentialsRestResourceProviderBuilder,
+        ) -> BiomeCredentialsRestResourceProviderBuilder,
+    {
         let refresh_token_store = MemoryRefreshTokenStore::new();
         let cred_store = MemoryCredentialsStore::new();
         #[cfg(feature = "biome-key-management")]
@@ -376,7 +582,9 @@ mod tests {
         }
 
         let biome_credentials_resource_provider =
-            biome_credentials_resource_provider_builder.build().unwrap();
+            configure(biome_credentials_resource_provider_builder)
+                .build()
+                .unwrap();
 
         let mut rest_api_builder = RestApiBuilder::new();
 
@@ -454,7 +662,17 @@ mod tests {
     where
         F: FnOnce(&str, Client) -> () + panic::UnwindSafe,
     {
-        let (handle, join_handle) = start_biome_rest_api();
+        run_test_with(|builder| builder, f)
+    }
+
+    fn run_test_with<C, F>(configure: C, f: F)
+    where
+        C: FnOnce(
+            BiomeCredentialsRestResourceProviderBuilder,
+        ) -> BiomeCredentialsRestResourceProviderBuilder,
+        F: FnOnce(&str, Client) -> () + panic::UnwindSafe,
+    {
+        let (handle, join_handle) = start_biome_rest_api(configure);
 
         let port_no = handle.port_numbers()[0];
 
@@ -751,6 +969,381 @@ mod tests {
         });
     }
 
+    /// Test happy path for GET /biome/sessions, DELETE /biome/sessions/{id}, and
+    /// DELETE /biome/sessions
+    ///
+    /// Verify that each login starts a separate session, that revoking one session leaves the
+    /// other usable, and that logging out everywhere revokes all of the user's sessions.
+    ///
+    /// Procedure
+    ///
+    /// 1) Create a new user and log in as that user twice, naming the second session
+    /// 2) List the sessions via GET /biome/sessions and verify both are returned
+    /// 3) Revoke the second session via DELETE /biome/sessions/{id}
+    /// 4) Verify that the second session's refresh token is rejected by POST /biome/token, while
+    ///    the first session's is still accepted
+    /// 5) Log out everywhere via DELETE /biome/sessions
+    /// 6) Verify that the first session's refresh token is rejected by POST /biome/token
+    #[cfg(feature = "biome-sessions")]
+    #[test]
+    fn test_sessions() {
+        run_test(|url, client| {
+            let first =
+                create_and_authorize_user(url, &client, "test_sessions@gmail.com", "Admin2193!");
+
+            let second = client
+                .post(&format!("{}/biome/login", url))
+                .json(&PostLogin {
+                    username: "test_sessions@gmail.com".to_string(),
+                    hashed_password: "Admin2193!".to_string(),
+                    display_name: "phone".to_string(),
+                })
+                .send()
+                .unwrap()
+                .json::<LoginResponse>()
+                .unwrap();
+            assert_ne!(first.session_id, second.session_id);
+
+            let sessions = client
+                .get(&format!("{}/biome/sessions", url))
+                .header("Authorization", format!("Bearer {}", first.token))
+                .send()
+                .unwrap()
+                .json::<ListSessionsResponse>()
+                .unwrap()
+                .data;
+            assert_eq!(sessions.len(), 2);
+            let current = sessions
+                .iter()
+                .find(|session| session.current)
+                .expect("No current session");
+            assert_eq!(current.session_id, first.session_id);
+            let other = sessions
+                .iter()
+                .find(|session| !session.current)
+                .expect("No other session");
+            assert_eq!(other.session_id, second.session_id);
+            assert_eq!(other.display_name.as_deref(), Some("phone"));
+
+            assert_eq!(
+                client
+                    .delete(&format!("{}/biome/sessions/{}", url, second.session_id))
+                    .header("Authorization", format!("Bearer {}", first.token))
+                    .send()
+                    .unwrap()
+                    .status()
+                    .as_u16(),
+                200
+            );
+
+            assert_eq!(
+                client
+                    .post(&format!("{}/biome/token", url))
+                    .header("Authorization", format!("Bearer {}", second.token))
+                    .json(&PostToken {
+                        token: second.refresh_token
+                    })
+                    .send()
+                    .unwrap()
+                    .status()
+                    .as_u16(),
+                403
+            );
+            assert_eq!(
+                client
+                    .post(&format!("{}/biome/token", url))
+                    .header("Authorization", format!("Bearer {}", first.token))
+                    .json(&PostToken {
+                        token: first.refresh_token.clone()
+                    })
+                    .send()
+                    .unwrap()
+                    .status()
+                    .as_u16(),
+                200
+            );
+
+            assert_eq!(
+                client
+                    .delete(&format!("{}/biome/sessions", url))
+                    .header("Authorization", format!("Bearer {}", first.token))
+                    .send()
+                    .unwrap()
+                    .status()
+                    .as_u16(),
+                200
+            );
+
+            assert_eq!(
+                client
+                    .post(&format!("{}/biome/token", url))
+                    .header("Authorization", format!("Bearer {}", first.token))
+                    .json(&PostToken {
+                        token: first.refresh_token
+                    })
+                    .send()
+                    .unwrap()
+                    .status()
+                    .as_u16(),
+                403
+            );
+        });
+    }
+
+    /// Test TOTP enrollment, login with TOTP, and TOTP reset
+    ///
+    /// Verify that once a user confirms TOTP enrollment, logging in requires a TOTP code or
+    /// recovery code that has not been used before, and that resetting TOTP via
+    /// DELETE /biome/users/{id}/totp removes the requirement.
+    ///
+    /// Procedure
+    ///
+    /// 1) Create a new user and log in as that user
+    /// 2) Start enrollment via POST /biome/totp and confirm it via POST /biome/totp/confirm with a
+    ///    code generated from the returned secret
+    /// 3) Verify that logging in without a code is rejected with `totp_required`, and that
+    ///    logging in with the code already used to confirm enrollment is rejected
+    /// 4) Log in with a recovery code and verify the same recovery code cannot be used again
+    /// 5) Reset TOTP and verify that the user can log in with their password alone
+    #[cfg(feature = "biome-totp")]
+    #[test]
+    fn test_totp() {
+        use std::time::SystemTime;
+
+        use crate::biome::credentials::totp::generate_code;
+
+        run_test(|url, client| {
+            let login =
+                create_and_authorize_user(url, &client, "test_totp@gmail.com", "Admin2193!");
+
+            let enrollment = client
+                .post(&format!("{}/biome/totp", url))
+                .header("Authorization", format!("Bearer {}", login.token))
+                .send()
+                .unwrap()
+                .json::<TotpEnrollmentResponse>()
+                .unwrap()
+                .data;
+            assert!(enrollment
+                .provisioning_uri
+                .starts_with("otpauth://totp/Splinter:test_totp%40gmail.com?"));
+            let secret = decode_base32(&enrollment.secret);
+
+            let code = generate_code(&secret, SystemTime::now()).unwrap();
+            let confirm_response = client
+                .post(&format!("{}/biome/totp/confirm", url))
+                .header("Authorization", format!("Bearer {}", login.token))
+                .json(&json!({ "code": code }))
+                .send()
+                .unwrap();
+            assert_eq!(confirm_response.status().as_u16(), 200);
+            let recovery_codes = confirm_response
+                .json::<TotpConfirmResponse>()
+                .unwrap()
+                .recovery_codes;
+            assert_eq!(recovery_codes.len(), 10);
+
+            let login_with = |totp_code: Option<&str>, recovery_code: Option<&str>| {
+                client
+                    .post(&format!("{}/biome/login", url))
+                    .json(&PostTotpLogin {
+                        username: "test_totp@gmail.com".to_string(),
+                        hashed_password: "Admin2193!".to_string(),
+                        totp_code: totp_code.map(String::from),
+                        recovery_code: recovery_code.map(String::from),
+                    })
+                    .send()
+                    .unwrap()
+            };
+
+            let response = login_with(None, None);
+            assert_eq!(response.status().as_u16(), 401);
+            assert_eq!(
+                response.json::<serde_json::Value>().unwrap()["totp_required"],
+                json!(true)
+            );
+            assert_eq!(login_with(Some(code.as_str()), None).status().as_u16(), 400);
+
+            assert_eq!(
+                login_with(None, Some(recovery_codes[0].as_str()))
+                    .status()
+                    .as_u16(),
+                200
+            );
+            assert_eq!(
+                login_with(None, Some(recovery_codes[0].as_str()))
+                    .status()
+                    .as_u16(),
+                400
+            );
+
+            assert_eq!(
+                client
+                    .delete(&format!("{}/biome/users/{}/totp", url, login.user_id))
+                    .header("Authorization", format!("Bearer {}", login.token))
+                    .send()
+                    .unwrap()
+                    .status()
+                    .as_u16(),
+                200
+            );
+            assert_eq!(login_with(None, None).status().as_u16(), 200);
+        });
+    }
+
+    /// Test the password reset flow
+    ///
+    /// Procedure
+    ///
+    /// 1) Create a new user and log in as that user
+    /// 2) Request a password reset for an unknown username and verify that the response is the
+    ///    same as for an existing user, but no token is sent
+    /// 3) Request a password reset for the user and verify that a token is sent
+    /// 4) Verify that an invalid token is rejected
+    /// 5) Set a new password with the token and verify that the token cannot be used again
+    /// 6) Verify that the user's existing session was ended
+    /// 7) Verify that only the new password can be used to log in
+    #[cfg(feature = "biome-password-reset")]
+    #[test]
+    fn test_password_reset() {
+        let sender = CapturingPasswordResetSender::default();
+        let tokens = sender.tokens.clone();
+
+        run_test_with(
+            move |builder| builder.with_password_reset_sender(sender),
+            move |url, client| {
+                let login = create_and_authorize_user(
+                    url,
+                    &client,
+                    "test_password_reset@gmail.com",
+                    "Admin2193!",
+                );
+
+                let request_reset = |username: &str| {
+                    client
+                        .post(&format!("{}/biome/password_reset", url))
+                        .json(&json!({ "username": username }))
+                        .send()
+                        .unwrap()
+                        .status()
+                        .as_u16()
+                };
+                let confirm_reset = |token: &str, hashed_password: &str| {
+                    client
+                        .post(&format!("{}/biome/password_reset/confirm", url))
+                        .json(&json!({ "token": token, "hashed_password": hashed_password }))
+                        .send()
+                        .unwrap()
+                        .status()
+                        .as_u16()
+                };
+
+                assert_eq!(request_reset("unknown@gmail.com"), 200);
+                assert!(tokens.lock().unwrap().is_empty());
+
+                assert_eq!(request_reset("test_password_reset@gmail.com"), 200);
+                let token = tokens
+                    .lock()
+                    .unwrap()
+                    .get("test_password_reset@gmail.com")
+                    .cloned()
+                    .expect("No token was sent");
+
+                assert_eq!(confirm_reset("invalid", "NewPassword1!"), 400);
+                assert_eq!(confirm_reset(&token, "NewPassword1!"), 200);
+                assert_eq!(confirm_reset(&token, "NewPassword2!"), 400);
+
+                let token_response = client
+                    .post(&format!("{}/biome/token", url))
+                    .header("Authorization", format!("Bearer {}", login.token))
+                    .json(&PostToken {
+                        token: login.refresh_token,
+                    })
+                    .send()
+                    .unwrap();
+                assert!(!token_response.status().is_success());
+
+                let login_with = |hashed_password: &str| {
+                    client
+                        .post(&format!("{}/biome/login", url))
+                        .json(&UsernamePassword {
+                            username: "test_password_reset@gmail.com".to_string(),
+                            hashed_password: hashed_password.to_string(),
+                        })
+                        .send()
+                        .unwrap()
+                        .status()
+                        .as_u16()
+                };
+                assert_eq!(login_with("Admin2193!"), 400);
+                assert_eq!(login_with("NewPassword1!"), 200);
+            },
+        );
+    }
+
+    /// Test that users are locked out after too many failed logins
+    ///
+    /// Procedure
+    ///
+    /// 1) Start the REST API with a limit of 3 failed logins
+    /// 2) Create a new user, then fail to log in as that user 3 times
+    /// 3) Verify that logging in with the correct password is now rejected with a 403
+    /// 4) Unlock the user via DELETE /biome/users/{id}/lockout
+    /// 5) Verify that the user can log in again
+    #[cfg(feature = "biome-login-lockout")]
+    #[test]
+    fn test_login_lockout() {
+        run_test_with(
+            |builder| {
+                builder.with_credentials_config(
+                    BiomeCredentialsRestConfigBuilder::default()
+                        .with_password_encryption_cost("low")
+                        .with_max_failed_logins(3)
+                        .build()
+                        .unwrap(),
+                )
+            },
+            |url, client| {
+                let login = create_and_authorize_user(
+                    url,
+                    &client,
+                    "test_login_lockout@gmail.com",
+                    "Admin2193!",
+                );
+
+                let login_with = |hashed_password: &str| {
+                    client
+                        .post(&format!("{}/biome/login", url))
+                        .json(&UsernamePassword {
+                            username: "test_login_lockout@gmail.com".to_string(),
+                            hashed_password: hashed_password.to_string(),
+                        })
+                        .send()
+                        .unwrap()
+                        .status()
+                        .as_u16()
+                };
+
+                for _ in 0..3 {
+                    assert_eq!(login_with("wrong"), 400);
+                }
+                assert_eq!(login_with("Admin2193!"), 403);
+
+                assert_eq!(
+                    client
+                        .delete(&format!("{}/biome/users/{}/lockout", url, login.user_id))
+                        .header("Authorization", format!("Bearer {}", login.token))
+                        .send()
+                        .unwrap()
+                        .status()
+                        .as_u16(),
+                    200
+                );
+                assert_eq!(login_with("Admin2193!"), 200);
+            },
+        );
+    }
+
     /// Test Happy path for POST /biome/verify
     ///
     /// Verify that POST /biome/verify returns a status code
@@ -762,6 +1355,7 @@ mod tests {
     /// 2) Verify the user's password via POST /biome/verify
     /// 3) Verify the correct status code and user id was
     ///    returned
+    /// 4) Create a second user and verify that they cannot verify the first user's password
     #[test]
     fn test_password_verify() {
         run_test(|url, client| {
@@ -790,9 +1384,82 @@ mod tests {
                 .user_id;
 
             assert_eq!(login.user_id, verify_user_id);
+
+            let other_login = create_and_authorize_user(
+                url,
+                &client,
+                "test_password_verify_other@gmail.com",
+                "Admin2193!",
+            );
+
+            assert_eq!(
+                client
+                    .post(&format!("{}/biome/verify", url))
+                    .header("Authorization", format!("Bearer {}", other_login.token))
+                    .json(&PostVerify {
+                        username: "test_password_verify@gmail.com".to_string(),
+                        hashed_password: "Admin2193!".to_string(),
+                    })
+                    .send()
+                    .unwrap()
+                    .status()
+                    .as_u16(),
+                403
+            );
         });
     }
 
+    /// Test that POST /biome/verify counts towards and respects login lockout
+    ///
+    /// Procedure
+    ///
+    /// 1) Start the REST API with a limit of 3 failed logins
+    /// 2) Create a new user and log in as that user
+    /// 3) Fail to verify the user's password via POST /biome/verify 3 times
+    /// 4) Verify that verifying the correct password is now rejected with a 403
+    #[cfg(feature = "biome-login-lockout")]
+    #[test]
+    fn test_password_verify_lockout() {
+        run_test_with(
+            |builder| {
+                builder.with_credentials_config(
+                    BiomeCredentialsRestConfigBuilder::default()
+                        .with_password_encryption_cost("low")
+                        .with_max_failed_logins(3)
+                        .build()
+                        .unwrap(),
+                )
+            },
+            |url, client| {
+                let login = create_and_authorize_user(
+                    url,
+                    &client,
+                    "test_password_verify_lockout@gmail.com",
+                    "Admin2193!",
+                );
+
+                let verify_with = |hashed_password: &str| {
+                    client
+                        .post(&format!("{}/biome/verify", url))
+                        .header("Authorization", format!("Bearer {}", login.token))
+                        .json(&PostVerify {
+                            username: "test_password_verify_lockout@gmail.com".to_string(),
+                            hashed_password: hashed_password.to_string(),
+                        })
+                        .send()
+                        .unwrap()
+                        .status()
+                        .as_u16()
+                };
+
+                for _ in 0..3 {
+                    assert_eq!(verify_with("wrong"), 400);
+                }
+                assert_eq!(verify_with("Admin2193!"), 403);
+            },
+        );
+    }
+
     /// Test Happy path for POST /biome/token
     ///
     /// Verify that POST /biome/token returns a new
@@ -823,4 +1490,22 @@ mod tests {
             token_response.json::<PostToken>().unwrap();
         });
     }
+
+    /// Decodes an unpadded base32 string, as returned by POST /biome/totp
+    #[cfg(feature = "biome-totp")]
+    fn decode_base32(encoded: &str) -> Vec<u8> {
+        const ALPHABET: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+        let mut decoded = vec![];
+        let mut buffer: u16 = 0;
+        let mut bits = 0;
+        for c in encoded.chars() {
+            buffer = (buffer << 5) | ALPHABET.find(c).expect("Invalid base32") as u16;
+            bits += 5;
+            if bits >= 8 {
+                bits -= 8;
+                decoded.push((buffer >> bits) as u8);
+            }
+        }
+        decoded
+    }
 }
@@ -0,0 +1,288 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use crate::actix_web::HttpResponse;
+use crate::biome::credentials::password_reset::{generate_token, hash_token, PasswordResetSender};
+use crate::biome::credentials::rest_api::{
+    actix_web_1::config::BiomeCredentialsRestConfig,
+    resources::password_reset::{PasswordResetConfirmation, PasswordResetRequest},
+};
+use crate::biome::credentials::store::{
+    CredentialsStore, CredentialsStoreError, PasswordResetToken,
+};
+use crate::biome::refresh_tokens::store::{RefreshTokenError, RefreshTokenStore};
+use crate::futures::{Future, IntoFuture};
+use crate::protocol;
+#[cfg(feature = "authorization")]
+use crate::rest_api::auth::authorization::Permission;
+use crate::rest_api::{
+    actix_web_1::{into_bytes, HandlerFunction, Method, ProtocolVersionRangeGuard, Resource},
+    ErrorResponse,
+};
+
+const REQUESTED_MESSAGE: &str = "If the user exists, a password reset token has been sent";
+const INVALID_TOKEN_MESSAGE: &str = "Invalid or expired password reset token";
+
+/// Defines the `/biome/password_reset` REST resource for requesting a password reset token
+pub fn make_password_reset_route(
+    credentials_store: Arc<dyn CredentialsStore>,
+    rest_config: Arc<BiomeCredentialsRestConfig>,
+    sender: Arc<dyn PasswordResetSender>,
+) -> Resource {
+    let resource =
+        Resource::build("/biome/password_reset").add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::BIOME_PASSWORD_RESET_PROTOCOL_MIN,
+            protocol::BIOME_PROTOCOL_VERSION,
+        ));
+    #[cfg(feature = "authorization")]
+    {
+        resource.add_method(
+            Method::Post,
+            Permission::AllowUnauthenticated,
+            add_request_password_reset_method(credentials_store, rest_config, sender),
+        )
+    }
+    #[cfg(not(feature = "authorization"))]
+    {
+        resource.add_method(
+            Method::Post,
+            add_request_password_reset_method(credentials_store, rest_config, sender),
+        )
+    }
+}
+
+/// Defines the `/biome/password_reset/confirm` REST resource for setting a new password with a
+/// password reset token
+pub fn make_confirm_password_reset_route(
+    credentials_store: Arc<dyn CredentialsStore>,
+    refresh_token_store: Arc<dyn RefreshTokenStore>,
+    rest_config: Arc<BiomeCredentialsRestConfig>,
+) -> Resource {
+    let resource = Resource::build("/biome/password_reset/confirm").add_request_guard(
+        ProtocolVersionRangeGuard::new(
+            protocol::BIOME_PASSWORD_RESET_PROTOCOL_MIN,
+            protocol::BIOME_PROTOCOL_VERSION,
+        ),
+    );
+    #[cfg(feature = "authorization")]
+    {
+        resource.add_method(
+            Method::Post,
+            Permission::AllowUnauthenticated,
+            add_confirm_password_reset_method(credentials_store, refresh_token_store, rest_config),
+        )
+    }
+    #[cfg(not(feature = "authorization"))]
+    {
+        resource.add_method(
+            Method::Post,
+            add_confirm_password_reset_method(credentials_store, refresh_token_store, rest_config),
+        )
+    }
+}
+
+/// Defines a REST endpoint that sends a password reset token to a user
+///
+/// The response is the same whether or not the user exists, so that the endpoint cannot be used
+/// to find out which usernames are in use.
+///
+/// The payload should be in the JSON format:
+///   {
+///       "username": <existing username of the user>
+///   }
+fn add_request_password_reset_method(
+    credentials_store: Arc<dyn CredentialsStore>,
+    rest_config: Arc<BiomeCredentialsRestConfig>,
+    sender: Arc<dyn PasswordResetSender>,
+) -> HandlerFunction {
+    Box::new(move |_, payload| {
+        let credentials_store = credentials_store.clone();
+        let rest_config = rest_config.clone();
+        let sender = sender.clone();
+        Box::new(into_bytes(payload).and_then(move |bytes| {
+            let request = match serde_json::from_slice::<PasswordResetRequest>(&bytes) {
+                Ok(val) => val,
+                Err(err) => {
+                    debug!("Error parsing payload {}", err);
+                    return HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request(&format!(
+                            "Failed to parse payload: {}",
+                            err
+                        )))
+                        .into_future();
+                }
+            };
+
+            let credentials =
+                match credentials_store.fetch_credential_by_username(&request.username) {
+                    Ok(credentials) => credentials,
+                    Err(CredentialsStoreError::NotFoundError(_)) => {
+                        debug!(
+                            "Password reset requested for unknown username {}",
+                            request.username
+                        );
+                        return HttpResponse::Ok()
+                            .json(json!({ "message": REQUESTED_MESSAGE }))
+                            .into_future();
+                    }
+                    Err(err) => {
+                        error!("Failed to fetch credentials {}", err);
+                        return HttpResponse::InternalServerError()
+                            .json(ErrorResponse::internal_error())
+                            .into_future();
+                    }
+                };
+
+            let token = match generate_token() {
+                Ok(token) => token,
+                Err(err) => {
+                    error!("Failed to generate password reset token {}", err);
+                    return HttpResponse::InternalServerError()
+                        .json(ErrorResponse::internal_error())
+                        .into_future();
+                }
+            };
+            let expires = SystemTime::now() + rest_config.password_reset_token_duration();
+
+            if let Err(err) = credentials_store.add_password_reset_token(PasswordResetToken {
+                user_id: credentials.user_id,
+                token_hash: hash_token(&token),
+                expires,
+            }) {
+                error!("Failed to store password reset token {}", err);
+                return HttpResponse::InternalServerError()
+                    .json(ErrorResponse::internal_error())
+                    .into_future();
+            }
+
+            if let Err(err) =
+                sender.send_password_reset_token(&credentials.username, &token, expires)
+            {
+                error!("Failed to send password reset token {}", err);
+                return HttpResponse::InternalServerError()
+                    .json(ErrorResponse::internal_error())
+                    .into_future();
+            }
+
+            HttpResponse::Ok()
+                .json(json!({ "message": REQUESTED_MESSAGE }))
+                .into_future()
+        }))
+    })
+}
+
+/// Defines a REST endpoint that sets a user's new password using a password reset token
+///
+/// Each token can only be used once, and using one invalidates any other tokens the user has. On
+/// success, all of the user's sessions are ended.
+///
+/// The payload should be in the JSON format:
+///   {
+///       "token": <the password reset token sent to the user>
+///       "hashed_password": <hash of the user's new password>
+///   }
+fn add_confirm_password_reset_method(
+    credentials_store: Arc<dyn CredentialsStore>,
+    refresh_token_store: Arc<dyn RefreshTokenStore>,
+    rest_config: Arc<BiomeCredentialsRestConfig>,
+) -> HandlerFunction {
+    let encryption_cost = rest_config.password_encryption_cost();
+    Box::new(move |_, payload| {
+        let credentials_store = credentials_store.clone();
+        let refresh_token_store = refresh_token_store.clone();
+        Box::new(into_bytes(payload).and_then(move |bytes| {
+            let confirmation = match serde_json::from_slice::<PasswordResetConfirmation>(&bytes) {
+                Ok(val) => val,
+                Err(err) => {
+                    debug!("Error parsing payload {}", err);
+                    return HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request(&format!(
+                            "Failed to parse payload: {}",
+                            err
+                        )))
+                        .into_future();
+                }
+            };
+
+            let token = match credentials_store
+                .take_password_reset_token(&hash_token(&confirmation.token))
+            {
+                Ok(token) if token.expires > SystemTime::now() => token,
+                Ok(_) | Err(CredentialsStoreError::NotFoundError(_)) => {
+                    return HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request(INVALID_TOKEN_MESSAGE))
+                        .into_future();
+                }
+                Err(err) => {
+                    error!("Failed to fetch password reset token {}", err);
+                    return HttpResponse::InternalServerError()
+                        .json(ErrorResponse::internal_error())
+                        .into_future();
+                }
+            };
+
+            let username = match credentials_store.fetch_username_by_id(&token.user_id) {
+                Ok(username) => username.username,
+                Err(CredentialsStoreError::NotFoundError(_)) => {
+                    return HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request(INVALID_TOKEN_MESSAGE))
+                        .into_future();
+                }
+                Err(err) => {
+                    error!("Failed to fetch username {}", err);
+                    return HttpResponse::InternalServerError()
+                        .json(ErrorResponse::internal_error())
+                        .into_future();
+                }
+            };
+
+            if let Err(err) = credentials_store.update_credentials(
+                &token.user_id,
+                &username,
+                &confirmation.hashed_password,
+                encryption_cost,
+            ) {
+                error!("Failed to update credentials {}", err);
+                return HttpResponse::InternalServerError()
+                    .json(ErrorResponse::internal_error())
+                    .into_future();
+            }
+
+            match refresh_token_store.remove_token(&token.user_id) {
+                Ok(()) | Err(RefreshTokenError::NotFoundError(_)) => (),
+                Err(err) => {
+                    error!("Failed to end sessions after password reset {}", err);
+                    return HttpResponse::InternalServerError()
+                        .json(ErrorResponse::internal_error())
+                        .into_future();
+                }
+            }
+
+            #[cfg(feature = "biome-login-lockout")]
+            if let Err(err) = credentials_store.remove_login_failures(&token.user_id) {
+                error!("Failed to unlock user after password reset {}", err);
+                return HttpResponse::InternalServerError()
+                    .json(ErrorResponse::internal_error())
+                    .into_future();
+            }
+
+            HttpResponse::Ok()
+                .json(json!({ "message": "Password successfully reset" }))
+                .into_future()
+        }))
+    })
+}
@@ -0,0 +1,270 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use crate::actix_web::HttpResponse;
+use crate::biome::credentials::rest_api::{
+    actix_web_1::{authorize::authorize_user, config::BiomeCredentialsRestConfig},
+    resources::{authorize::AuthorizationResult, session::SessionResponse},
+};
+use crate::biome::refresh_tokens::store::{RefreshTokenError, RefreshTokenStore};
+use crate::futures::IntoFuture;
+use crate::protocol;
+#[cfg(feature = "authorization")]
+use crate::rest_api::auth::authorization::Permission;
+use crate::rest_api::{
+    actix_web_1::{HandlerFunction, Method, ProtocolVersionRangeGuard, Resource},
+    secrets::SecretManager,
+    sessions::{default_validation, Claims},
+    ErrorResponse,
+};
+
+/// The custom claim that identifies the session an access or refresh token was issued for
+pub(super) const SESSION_ID_CLAIM: &str = "session_id";
+
+/// Returns the ID of the session the given claims were issued for, if any. Tokens issued before
+/// sessions were introduced do not have a session ID.
+pub(super) fn session_id_from_claims(claims: &Claims) -> Option<String> {
+    claims.custom_claims().remove(SESSION_ID_CLAIM)
+}
+
+/// Defines the `/biome/sessions` REST resource for listing the authorized user's sessions and
+/// for logging out of all of them
+pub fn make_sessions_route(
+    refresh_token_store: Arc<dyn RefreshTokenStore>,
+    secret_manager: Arc<dyn SecretManager>,
+    rest_config: Arc<BiomeCredentialsRestConfig>,
+) -> Resource {
+    let resource =
+        Resource::build("/biome/sessions").add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::BIOME_SESSIONS_PROTOCOL_MIN,
+            protocol::BIOME_PROTOCOL_VERSION,
+        ));
+    #[cfg(feature = "authorization")]
+    {
+        resource
+            .add_method(
+                Method::Get,
+                Permission::AllowAuthenticated,
+                add_list_sessions_method(
+                    refresh_token_store.clone(),
+                    secret_manager.clone(),
+                    rest_config.clone(),
+                ),
+            )
+            .add_method(
+                Method::Delete,
+                Permission::AllowAuthenticated,
+                add_remove_all_sessions_method(refresh_token_store, secret_manager, rest_config),
+            )
+    }
+    #[cfg(not(feature = "authorization"))]
+    {
+        resource
+            .add_method(
+                Method::Get,
+                add_list_sessions_method(
+                    refresh_token_store.clone(),
+                    secret_manager.clone(),
+                    rest_config.clone(),
+                ),
+            )
+            .add_method(
+                Method::Delete,
+                add_remove_all_sessions_method(refresh_token_store, secret_manager, rest_config),
+            )
+    }
+}
+
+/// Defines the `/biome/sessions/{id}` REST resource for revoking one of the authorized user's
+/// sessions
+pub fn make_session_route(
+    refresh_token_store: Arc<dyn RefreshTokenStore>,
+    secret_manager: Arc<dyn SecretManager>,
+    rest_config: Arc<BiomeCredentialsRestConfig>,
+) -> Resource {
+    let resource =
+        Resource::build("/biome/sessions/{id}").add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::BIOME_SESSIONS_PROTOCOL_MIN,
+            protocol::BIOME_PROTOCOL_VERSION,
+        ));
+    #[cfg(feature = "authorization")]
+    {
+        resource.add_method(
+            Method::Delete,
+            Permission::AllowAuthenticated,
+            add_remove_session_method(refresh_token_store, secret_manager, rest_config),
+        )
+    }
+    #[cfg(not(feature = "authorization"))]
+    {
+        resource.add_method(
+            Method::Delete,
+            add_remove_session_method(refresh_token_store, secret_manager, rest_config),
+        )
+    }
+}
+
+/// Defines a REST endpoint that lists the authorized user's sessions, most recently used first
+fn add_list_sessions_method(
+    refresh_token_store: Arc<dyn RefreshTokenStore>,
+    secret_manager: Arc<dyn SecretManager>,
+    rest_config: Arc<BiomeCredentialsRestConfig>,
+) -> HandlerFunction {
+    Box::new(move |request, _| {
+        let validation = default_validation(&rest_config.issuer());
+        let claims = match authorize_user(&request, &secret_manager, &validation) {
+            AuthorizationResult::Authorized(claims) => claims,
+            AuthorizationResult::Unauthorized => {
+                return Box::new(
+                    HttpResponse::Unauthorized()
+                        .json(ErrorResponse::unauthorized())
+                        .into_future(),
+                )
+            }
+            AuthorizationResult::Failed => {
+                return Box::new(
+                    HttpResponse::InternalServerError()
+                        .json(ErrorResponse::internal_error())
+                        .into_future(),
+                );
+            }
+        };
+        let current_session_id = session_id_from_claims(&claims);
+
+        Box::new(match refresh_token_store.list_sessions(&claims.user_id()) {
+            Ok(sessions) => HttpResponse::Ok()
+                .json(json!({
+                    "data": sessions
+                        .iter()
+                        .map(|session| SessionResponse::new(session, current_session_id.as_deref()))
+                        .collect::<Vec<_>>()
+                }))
+                .into_future(),
+            Err(err) => {
+                error!("Failed to list sessions: {}", err);
+                HttpResponse::InternalServerError()
+                    .json(ErrorResponse::internal_error())
+                    .into_future()
+            }
+        })
+    })
+}
+
+/// Defines a REST endpoint that revokes one of the authorized user's sessions. The refresh token
+/// of the session can no longer be used; access tokens already issued for it remain valid until
+/// they expire.
+fn add_remove_session_method(
+    refresh_token_store: Arc<dyn RefreshTokenStore>,
+    secret_manager: Arc<dyn SecretManager>,
+    rest_config: Arc<BiomeCredentialsRestConfig>,
+) -> HandlerFunction {
+    Box::new(move |request, _| {
+        let validation = default_validation(&rest_config.issuer());
+        let user_id = match authorize_user(&request, &secret_manager, &validation) {
+            AuthorizationResult::Authorized(claims) => claims.user_id(),
+            AuthorizationResult::Unauthorized => {
+                return Box::new(
+                    HttpResponse::Unauthorized()
+                        .json(ErrorResponse::unauthorized())
+                        .into_future(),
+                )
+            }
+            AuthorizationResult::Failed => {
+                return Box::new(
+                    HttpResponse::InternalServerError()
+                        .json(ErrorResponse::internal_error())
+                        .into_future(),
+                );
+            }
+        };
+
+        let session_id = if let Some(session_id) = request.match_info().get("id") {
+            session_id.to_string()
+        } else {
+            return Box::new(
+                HttpResponse::BadRequest()
+                    .json(ErrorResponse::bad_request(
+                        &"Failed to process request: no session id".to_string(),
+                    ))
+                    .into_future(),
+            );
+        };
+
+        Box::new(
+            match refresh_token_store.remove_session(&user_id, &session_id) {
+                Ok(()) => HttpResponse::Ok()
+                    .json(json!({ "message": "Session successfully revoked" }))
+                    .into_future(),
+                Err(RefreshTokenError::NotFoundError(_)) => HttpResponse::NotFound()
+                    .json(ErrorResponse::not_found(&format!(
+                        "Session not found: {}",
+                        session_id
+                    )))
+                    .into_future(),
+                Err(err) => {
+                    error!("Failed to remove session: {}", err);
+                    HttpResponse::InternalServerError()
+                        .json(ErrorResponse::internal_error())
+                        .into_future()
+                }
+            },
+        )
+    })
+}
+
+/// Defines a REST endpoint that logs the authorized user out everywhere by revoking all of their
+/// sessions
+fn add_remove_all_sessions_method(
+    refresh_token_store: Arc<dyn RefreshTokenStore>,
+    secret_manager: Arc<dyn SecretManager>,
+    rest_config: Arc<BiomeCredentialsRestConfig>,
+) -> HandlerFunction {
+    Box::new(move |request, _| {
+        let validation = default_validation(&rest_config.issuer());
+        let user_id = match authorize_user(&request, &secret_manager, &validation) {
+            AuthorizationResult::Authorized(claims) => claims.user_id(),
+            AuthorizationResult::Unauthorized => {
+                return Box::new(
+                    HttpResponse::Unauthorized()
+                        .json(ErrorResponse::unauthorized())
+                        .into_future(),
+                )
+            }
+            AuthorizationResult::Failed => {
+                return Box::new(
+                    HttpResponse::InternalServerError()
+                        .json(ErrorResponse::internal_error())
+                        .into_future(),
+                );
+            }
+        };
+
+        Box::new(match refresh_token_store.remove_token(&user_id) {
+            // A user without any sessions is already logged out everywhere
+            Ok(()) | Err(RefreshTokenError::NotFoundError(_)) => HttpResponse::Ok()
+                .json(json!({
+                    "message": "User successfully logged out of all sessions"
+                }))
+                .into_future(),
+            Err(err) => {
+                error!("Failed to remove sessions: {}", err);
+                HttpResponse::InternalServerError()
+                    .json(ErrorResponse::internal_error())
+                    .into_future()
+            }
+        })
+    })
+}
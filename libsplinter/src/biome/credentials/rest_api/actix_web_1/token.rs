@@ -13,8 +13,14 @@
 // limitations under the License.
 
 use std::sync::Arc;
+#[cfg(feature = "biome-sessions")]
+use std::time::SystemTime;
 
 use crate::actix_web::HttpResponse;
+#[cfg(feature = "biome-sessions")]
+use crate::biome::credentials::rest_api::actix_web_1::sessions::SESSION_ID_CLAIM;
+#[cfg(not(feature = "biome-sessions"))]
+use crate::biome::refresh_tokens::store::RefreshTokenError;
 use crate::biome::{
     credentials::rest_api::{
         actix_web_1::{
@@ -23,7 +29,7 @@ use crate::biome::{
         },
         resources::{authorize::AuthorizationResult, token::RefreshToken},
     },
-    refresh_tokens::store::{RefreshTokenError, RefreshTokenStore},
+    refresh_tokens::store::RefreshTokenStore,
 };
 use crate::futures::{Future, IntoFuture};
 use crate::protocol;
@@ -40,6 +46,9 @@ use crate::rest_api::{
 
 /// Defines a REST endpoint for requesting a new authorization token
 ///
+/// If Biome sessions are enabled, the refresh token must belong to one of the user's sessions; the
+/// session's last used time is updated, and the new auth token is issued for the same session.
+///
 /// The payload should be in the JSON format:
 ///   {
 ///       "token": <refresh token for requesting a new auth token>
@@ -102,26 +111,51 @@ pub fn make_token_route(
                         }
                     };
 
-                    let refresh_token_from_db =
-                        match refresh_token_store.fetch_token(&claims.user_id()) {
-                            Ok(token) => token,
-                            Err(RefreshTokenError::NotFoundError(msg)) => {
+                    // Find the session the refresh token was issued for
+                    #[cfg(feature = "biome-sessions")]
+                    let session = match refresh_token_store.list_sessions(&claims.user_id()) {
+                        Ok(sessions) => match sessions
+                            .into_iter()
+                            .find(|session| session.token() == refresh_token)
+                        {
+                            Some(session) => session,
+                            None => {
                                 return HttpResponse::Forbidden()
-                                    .json(ErrorResponse::forbidden(&msg))
-                                    .into_future();
-                            }
-                            Err(err) => {
-                                error!("Failed to retrieve user refresh token {}", err);
-                                return HttpResponse::InternalServerError()
-                                    .json(ErrorResponse::internal_error())
+                                    .json(ErrorResponse::forbidden("Invalid Refresh Token"))
                                     .into_future();
                             }
-                        };
+                        },
+                        Err(err) => {
+                            error!("Failed to retrieve user refresh token {}", err);
+                            return HttpResponse::InternalServerError()
+                                .json(ErrorResponse::internal_error())
+                                .into_future();
+                        }
+                    };
 
-                    if refresh_token != refresh_token_from_db {
-                        return HttpResponse::Forbidden()
-                            .json(ErrorResponse::forbidden("Invalid Refresh Token"))
-                            .into_future();
+                    #[cfg(not(feature = "biome-sessions"))]
+                    {
+                        let refresh_token_from_db =
+                            match refresh_token_store.fetch_token(&claims.user_id()) {
+                                Ok(token) => token,
+                                Err(RefreshTokenError::NotFoundError(msg)) => {
+                                    return HttpResponse::Forbidden()
+                                        .json(ErrorResponse::forbidden(&msg))
+                                        .into_future();
+                                }
+                                Err(err) => {
+                                    error!("Failed to retrieve user refresh token {}", err);
+                                    return HttpResponse::InternalServerError()
+                                        .json(ErrorResponse::internal_error())
+                                        .into_future();
+                                }
+                            };
+
+                        if refresh_token != refresh_token_from_db {
+                            return HttpResponse::Forbidden()
+                                .json(ErrorResponse::forbidden("Invalid Refresh Token"))
+                                .into_future();
+                        }
                     }
 
                     match validate_claims(
@@ -131,7 +165,12 @@ pub fn make_token_route(
                     ) {
                         AuthorizationResult::Authorized(_) => (),
                         AuthorizationResult::Unauthorized => {
-                            if let Err(err) = refresh_token_store.remove_token(&claims.user_id()) {
+                            #[cfg(feature = "biome-sessions")]
+                            let removed = refresh_token_store
+                                .remove_session(&claims.user_id(), session.session_id());
+                            #[cfg(not(feature = "biome-sessions"))]
+                            let removed = refresh_token_store.remove_token(&claims.user_id());
+                            if let Err(err) = removed {
                                 error!("Failed to delete refresh token {}", err);
                                 return HttpResponse::InternalServerError()
                                     .json(ErrorResponse::internal_error())
@@ -148,13 +187,26 @@ pub fn make_token_route(
                                 .into_future();
                         }
                     }
-                    let claim_builder = ClaimsBuilder::default();
-                    let claim = match claim_builder
+                    #[cfg(feature = "biome-sessions")]
+                    if let Err(err) = refresh_token_store.update_last_used(
+                        &claims.user_id(),
+                        session.session_id(),
+                        SystemTime::now(),
+                    ) {
+                        error!("Failed to update session {}", err);
+                        return HttpResponse::InternalServerError()
+                            .json(ErrorResponse::internal_error())
+                            .into_future();
+                    }
+
+                    let claim_builder = ClaimsBuilder::default()
                         .with_user_id(&claims.user_id())
                         .with_issuer(&rest_config.issuer())
-                        .with_duration(rest_config.access_token_duration())
-                        .build()
-                    {
+                        .with_duration(rest_config.access_token_duration());
+                    #[cfg(feature = "biome-sessions")]
+                    let claim_builder =
+                        claim_builder.with_custom_claim(SESSION_ID_CLAIM, session.session_id());
+                    let claim = match claim_builder.build() {
                         Ok(claim) => claim,
                         Err(err) => {
                             error!("Failed to build claim {}", err);
@@ -219,14 +271,20 @@ pub fn make_token_route(
                     }
                 };
 
-                let refresh_token_from_db = match refresh_token_store.fetch_token(&claims.user_id())
-                {
-                    Ok(token) => token,
-                    Err(RefreshTokenError::NotFoundError(msg)) => {
-                        return HttpResponse::Forbidden()
-                            .json(ErrorResponse::forbidden(&msg))
-                            .into_future();
-                    }
+                // Find the session the refresh token was issued for
+                #[cfg(feature = "biome-sessions")]
+                let session = match refresh_token_store.list_sessions(&claims.user_id()) {
+                    Ok(sessions) => match sessions
+                        .into_iter()
+                        .find(|session| session.token() == refresh_token)
+                    {
+                        Some(session) => session,
+                        None => {
+                            return HttpResponse::Forbidden()
+                                .json(ErrorResponse::forbidden("Invalid Refresh Token"))
+                                .into_future();
+                        }
+                    },
                     Err(err) => {
                         error!("Failed to retrieve user refresh token {}", err);
                         return HttpResponse::InternalServerError()
@@ -235,10 +293,29 @@ pub fn make_token_route(
                     }
                 };
 
-                if refresh_token != refresh_token_from_db {
-                    return HttpResponse::Forbidden()
-                        .json(ErrorResponse::forbidden("Invalid Refresh Token"))
-                        .into_future();
+                #[cfg(not(feature = "biome-sessions"))]
+                {
+                    let refresh_token_from_db =
+                        match refresh_token_store.fetch_token(&claims.user_id()) {
+                            Ok(token) => token,
+                            Err(RefreshTokenError::NotFoundError(msg)) => {
+                                return HttpResponse::Forbidden()
+                                    .json(ErrorResponse::forbidden(&msg))
+                                    .into_future();
+                            }
+                            Err(err) => {
+                                error!("Failed to retrieve user refresh token {}", err);
+                                return HttpResponse::InternalServerError()
+                                    .json(ErrorResponse::internal_error())
+                                    .into_future();
+                            }
+                        };
+
+                    if refresh_token != refresh_token_from_db {
+                        return HttpResponse::Forbidden()
+                            .json(ErrorResponse::forbidden("Invalid Refresh Token"))
+                            .into_future();
+                    }
                 }
 
                 match validate_claims(
@@ -248,7 +325,12 @@ pub fn make_token_route(
                 ) {
                     AuthorizationResult::Authorized(_) => (),
                     AuthorizationResult::Unauthorized => {
-                        if let Err(err) = refresh_token_store.remove_token(&claims.user_id()) {
+                        #[cfg(feature = "biome-sessions")]
+                        let removed = refresh_token_store
+                            .remove_session(&claims.user_id(), session.session_id());
+                        #[cfg(not(feature = "biome-sessions"))]
+                        let removed = refresh_token_store.remove_token(&claims.user_id());
+                        if let Err(err) = removed {
                             error!("Failed to delete refresh token {}", err);
                             return HttpResponse::InternalServerError()
                                 .json(ErrorResponse::internal_error())
@@ -265,13 +347,26 @@ pub fn make_token_route(
                             .into_future();
                     }
                 }
-                let claim_builder = ClaimsBuilder::default();
-                let claim = match claim_builder
+                #[cfg(feature = "biome-sessions")]
+                if let Err(err) = refresh_token_store.update_last_used(
+                    &claims.user_id(),
+                    session.session_id(),
+                    SystemTime::now(),
+                ) {
+                    error!("Failed to update session {}", err);
+                    return HttpResponse::InternalServerError()
+                        .json(ErrorResponse::internal_error())
+                        .into_future();
+                }
+
+                let claim_builder = ClaimsBuilder::default()
                     .with_user_id(&claims.user_id())
                     .with_issuer(&rest_config.issuer())
-                    .with_duration(rest_config.access_token_duration())
-                    .build()
-                {
+                    .with_duration(rest_config.access_token_duration());
+                #[cfg(feature = "biome-sessions")]
+                let claim_builder =
+                    claim_builder.with_custom_claim(SESSION_ID_CLAIM, session.session_id());
+                let claim = match claim_builder.build() {
                     Ok(claim) => claim,
                     Err(err) => {
                         error!("Failed to build claim {}", err);
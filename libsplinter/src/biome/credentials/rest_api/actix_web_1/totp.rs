@@ -0,0 +1,608 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use openssl::memcmp;
+
+use crate::actix_web::HttpResponse;
+#[cfg(feature = "authorization")]
+use crate::biome::credentials::rest_api::BIOME_USER_WRITE_PERMISSION;
+use crate::biome::credentials::rest_api::{
+    actix_web_1::{authorize::authorize_user, config::BiomeCredentialsRestConfig},
+    resources::{
+        authorize::AuthorizationResult,
+        totp::{TotpCode, TotpEnrollmentResponse, TotpStatusResponse},
+    },
+};
+use crate::biome::credentials::{
+    store::{CredentialsStore, CredentialsStoreError, TotpCredentials},
+    totp::{
+        encode_base32, generate_recovery_codes, generate_secret, hash_recovery_code,
+        provisioning_uri, verify_code, TotpSecretCipher,
+    },
+};
+use crate::error::InternalError;
+use crate::futures::{Future, IntoFuture};
+use crate::protocol;
+#[cfg(feature = "authorization")]
+use crate::rest_api::auth::authorization::Permission;
+use crate::rest_api::{
+    actix_web_1::{into_bytes, HandlerFunction, Method, ProtocolVersionRangeGuard, Resource},
+    secrets::SecretManager,
+    sessions::default_validation,
+    ErrorResponse,
+};
+
+/// The issuer shown for Splinter accounts in authenticator apps
+const TOTP_ISSUER: &str = "Splinter";
+
+/// The result of checking a user's second factor at login
+pub(super) enum SecondFactorResult {
+    /// The user does not use a second factor, or provided a valid code
+    Verified,
+    /// The user uses a second factor but did not provide a code
+    Required,
+    /// The code provided is not valid or has already been used
+    Invalid,
+    /// The second factor could not be checked
+    Failed,
+}
+
+/// Checks the second factor of a user who has provided a valid password.
+///
+/// Users who have not confirmed TOTP enrollment do not need a second factor. Otherwise either a
+/// TOTP code that has not been used before or an unused recovery code is required; the code is
+/// recorded as used.
+pub(super) fn verify_second_factor(
+    credentials_store: &dyn CredentialsStore,
+    totp_secret_manager: &dyn SecretManager,
+    user_id: &str,
+    totp_code: Option<&str>,
+    recovery_code: Option<&str>,
+) -> SecondFactorResult {
+    let totp = match credentials_store.fetch_totp(user_id) {
+        Ok(totp) if totp.confirmed => totp,
+        Ok(_) | Err(CredentialsStoreError::NotFoundError(_)) => {
+            return SecondFactorResult::Verified
+        }
+        Err(err) => {
+            error!("Failed to fetch TOTP: {}", err);
+            return SecondFactorResult::Failed;
+        }
+    };
+
+    // Codes are consumed with compare-and-set operations so that concurrent logins cannot use
+    // the same code twice
+    let used = if let Some(code) = totp_code {
+        let step = match decrypt_secret(totp_secret_manager, &totp)
+            .and_then(|secret| verify_code(&secret, code, SystemTime::now()))
+        {
+            Ok(Some(step)) => step,
+            Ok(None) => return SecondFactorResult::Invalid,
+            Err(err) => {
+                error!("Failed to verify TOTP code: {}", err);
+                return SecondFactorResult::Failed;
+            }
+        };
+        credentials_store.use_totp_step(user_id, step)
+    } else if let Some(code) = recovery_code {
+        let hash = hash_recovery_code(code);
+        // Check every stored hash in constant time so the comparison does not leak how much of
+        // a hash matched
+        let matched = totp
+            .recovery_code_hashes
+            .iter()
+            .fold(None, |matched, stored| {
+                if stored.len() == hash.len() && memcmp::eq(stored.as_bytes(), hash.as_bytes()) {
+                    Some(stored)
+                } else {
+                    matched
+                }
+            });
+        match matched {
+            Some(stored) => credentials_store
+                .use_recovery_code(user_id, stored)
+                .map(|used| {
+                    if used {
+                        info!(
+                            "User {} logged in with a recovery code; {} recovery codes remaining",
+                            user_id,
+                            totp.recovery_code_hashes.len() - 1
+                        );
+                    }
+                    used
+                }),
+            None => return SecondFactorResult::Invalid,
+        }
+    } else {
+        return SecondFactorResult::Required;
+    };
+
+    match used {
+        Ok(true) => SecondFactorResult::Verified,
+        Ok(false) => SecondFactorResult::Invalid,
+        Err(err) => {
+            error!("Failed to update TOTP: {}", err);
+            SecondFactorResult::Failed
+        }
+    }
+}
+
+/// Defines the `/biome/totp` REST resource for checking and starting the authorized user's
+/// TOTP enrollment
+pub fn make_totp_route(
+    credentials_store: Arc<dyn CredentialsStore>,
+    totp_secret_manager: Arc<dyn SecretManager>,
+    secret_manager: Arc<dyn SecretManager>,
+    rest_config: Arc<BiomeCredentialsRestConfig>,
+) -> Resource {
+    let resource =
+        Resource::build("/biome/totp").add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::BIOME_TOTP_PROTOCOL_MIN,
+            protocol::BIOME_PROTOCOL_VERSION,
+        ));
+    #[cfg(feature = "authorization")]
+    {
+        resource
+            .add_method(
+                Method::Get,
+                Permission::AllowAuthenticated,
+                add_fetch_totp_status_method(
+                    credentials_store.clone(),
+                    secret_manager.clone(),
+                    rest_config.clone(),
+                ),
+            )
+            .add_method(
+                Method::Post,
+                Permission::AllowAuthenticated,
+                add_enroll_totp_method(
+                    credentials_store,
+                    totp_secret_manager,
+                    secret_manager,
+                    rest_config,
+                ),
+            )
+    }
+    #[cfg(not(feature = "authorization"))]
+    {
+        resource
+            .add_method(
+                Method::Get,
+                add_fetch_totp_status_method(
+                    credentials_store.clone(),
+                    secret_manager.clone(),
+                    rest_config.clone(),
+                ),
+            )
+            .add_method(
+                Method::Post,
+                add_enroll_totp_method(
+                    credentials_store,
+                    totp_secret_manager,
+                    secret_manager,
+                    rest_config,
+                ),
+            )
+    }
+}
+
+/// Defines the `/biome/totp/confirm` REST resource for completing the authorized user's TOTP
+/// enrollment
+pub fn make_confirm_totp_route(
+    credentials_store: Arc<dyn CredentialsStore>,
+    totp_secret_manager: Arc<dyn SecretManager>,
+    secret_manager: Arc<dyn SecretManager>,
+    rest_config: Arc<BiomeCredentialsRestConfig>,
+) -> Resource {
+    let resource =
+        Resource::build("/biome/totp/confirm").add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::BIOME_TOTP_PROTOCOL_MIN,
+            protocol::BIOME_PROTOCOL_VERSION,
+        ));
+    #[cfg(feature = "authorization")]
+    {
+        resource.add_method(
+            Method::Post,
+            Permission::AllowAuthenticated,
+            add_confirm_totp_method(
+                credentials_store,
+                totp_secret_manager,
+                secret_manager,
+                rest_config,
+            ),
+        )
+    }
+    #[cfg(not(feature = "authorization"))]
+    {
+        resource.add_method(
+            Method::Post,
+            add_confirm_totp_method(
+                credentials_store,
+                totp_secret_manager,
+                secret_manager,
+                rest_config,
+            ),
+        )
+    }
+}
+
+/// Defines the `/biome/users/{id}/totp` REST resource for resetting a user's TOTP, such as when
+/// the user has lost their authenticator app and recovery codes
+pub fn make_reset_totp_route(credentials_store: Arc<dyn CredentialsStore>) -> Resource {
+    let resource = Resource::build("/biome/users/{id}/totp").add_request_guard(
+        ProtocolVersionRangeGuard::new(
+            protocol::BIOME_TOTP_PROTOCOL_MIN,
+            protocol::BIOME_PROTOCOL_VERSION,
+        ),
+    );
+    #[cfg(feature = "authorization")]
+    {
+        resource.add_method(
+            Method::Delete,
+            BIOME_USER_WRITE_PERMISSION,
+            add_reset_totp_method(credentials_store),
+        )
+    }
+    #[cfg(not(feature = "authorization"))]
+    {
+        resource.add_method(Method::Delete, add_reset_totp_method(credentials_store))
+    }
+}
+
+/// Defines a REST endpoint that returns the authorized user's TOTP enrollment status
+fn add_fetch_totp_status_method(
+    credentials_store: Arc<dyn CredentialsStore>,
+    secret_manager: Arc<dyn SecretManager>,
+    rest_config: Arc<BiomeCredentialsRestConfig>,
+) -> HandlerFunction {
+    Box::new(move |request, _| {
+        let validation = default_validation(&rest_config.issuer());
+        let user_id = match authorize_user(&request, &secret_manager, &validation) {
+            AuthorizationResult::Authorized(claims) => claims.user_id(),
+            AuthorizationResult::Unauthorized => {
+                return Box::new(
+                    HttpResponse::Unauthorized()
+                        .json(ErrorResponse::unauthorized())
+                        .into_future(),
+                )
+            }
+            AuthorizationResult::Failed => {
+                return Box::new(
+                    HttpResponse::InternalServerError()
+                        .json(ErrorResponse::internal_error())
+                        .into_future(),
+                );
+            }
+        };
+
+        let status = match credentials_store.fetch_totp(&user_id) {
+            Ok(totp) => TotpStatusResponse {
+                enrolled: true,
+                confirmed: totp.confirmed,
+                recovery_codes_remaining: totp.recovery_code_hashes.len(),
+            },
+            Err(CredentialsStoreError::NotFoundError(_)) => TotpStatusResponse {
+                enrolled: false,
+                confirmed: false,
+                recovery_codes_remaining: 0,
+            },
+            Err(err) => {
+                error!("Failed to fetch TOTP: {}", err);
+                return Box::new(
+                    HttpResponse::InternalServerError()
+                        .json(ErrorResponse::internal_error())
+                        .into_future(),
+                );
+            }
+        };
+
+        Box::new(
+            HttpResponse::Ok()
+                .json(json!({ "data": status }))
+                .into_future(),
+        )
+    })
+}
+
+/// Defines a REST endpoint that starts TOTP enrollment for the authorized user by generating a
+/// new secret. TOTP is not required at login until the enrollment is confirmed. Starting again
+/// before confirming replaces the secret.
+fn add_enroll_totp_method(
+    credentials_store: Arc<dyn CredentialsStore>,
+    totp_secret_manager: Arc<dyn SecretManager>,
+    secret_manager: Arc<dyn SecretManager>,
+    rest_config: Arc<BiomeCredentialsRestConfig>,
+) -> HandlerFunction {
+    Box::new(move |request, _| {
+        let validation = default_validation(&rest_config.issuer());
+        let user_id = match authorize_user(&request, &secret_manager, &validation) {
+            AuthorizationResult::Authorized(claims) => claims.user_id(),
+            AuthorizationResult::Unauthorized => {
+                return Box::new(
+                    HttpResponse::Unauthorized()
+                        .json(ErrorResponse::unauthorized())
+                        .into_future(),
+                )
+            }
+            AuthorizationResult::Failed => {
+                return Box::new(
+                    HttpResponse::InternalServerError()
+                        .json(ErrorResponse::internal_error())
+                        .into_future(),
+                );
+            }
+        };
+
+        match credentials_store.fetch_totp(&user_id) {
+            Ok(totp) if totp.confirmed => {
+                return Box::new(
+                    HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request(
+                            "TOTP is already enabled for this user",
+                        ))
+                        .into_future(),
+                )
+            }
+            Ok(_) | Err(CredentialsStoreError::NotFoundError(_)) => (),
+            Err(err) => {
+                error!("Failed to fetch TOTP: {}", err);
+                return Box::new(
+                    HttpResponse::InternalServerError()
+                        .json(ErrorResponse::internal_error())
+                        .into_future(),
+                );
+            }
+        }
+
+        let username = match credentials_store.fetch_username_by_id(&user_id) {
+            Ok(username_id) => username_id.username,
+            Err(err) => {
+                error!("Failed to fetch username: {}", err);
+                return Box::new(
+                    HttpResponse::InternalServerError()
+                        .json(ErrorResponse::internal_error())
+                        .into_future(),
+                );
+            }
+        };
+
+        let secret = match generate_secret() {
+            Ok(secret) => secret,
+            Err(err) => {
+                error!("Failed to generate TOTP secret: {}", err);
+                return Box::new(
+                    HttpResponse::InternalServerError()
+                        .json(ErrorResponse::internal_error())
+                        .into_future(),
+                );
+            }
+        };
+
+        let encrypted_secret = match encrypt_secret(&*totp_secret_manager, &user_id, &secret) {
+            Ok(encrypted_secret) => encrypted_secret,
+            Err(err) => {
+                error!("Failed to encrypt TOTP secret: {}", err);
+                return Box::new(
+                    HttpResponse::InternalServerError()
+                        .json(ErrorResponse::internal_error())
+                        .into_future(),
+                );
+            }
+        };
+
+        if let Err(err) = credentials_store.set_totp(TotpCredentials {
+            user_id,
+            encrypted_secret,
+            confirmed: false,
+            last_used_step: None,
+            recovery_code_hashes: vec![],
+        }) {
+            error!("Failed to set TOTP: {}", err);
+            return Box::new(
+                HttpResponse::InternalServerError()
+                    .json(ErrorResponse::internal_error())
+                    .into_future(),
+            );
+        }
+
+        Box::new(
+            HttpResponse::Ok()
+                .json(json!({
+                    "data": TotpEnrollmentResponse {
+                        secret: encode_base32(&secret),
+                        provisioning_uri: provisioning_uri(TOTP_ISSUER, &username, &secret),
+                    }
+                }))
+                .into_future(),
+        )
+    })
+}
+
+/// Defines a REST endpoint that confirms the authorized user's TOTP enrollment with a code from
+/// their authenticator app. Once confirmed, TOTP is required at login. The response contains the
+/// user's recovery codes; they are only stored hashed, so this is the only time they are shown.
+///
+/// The payload should be in the JSON format:
+///   {
+///       "code": <the current code shown by the authenticator app>
+///   }
+fn add_confirm_totp_method(
+    credentials_store: Arc<dyn CredentialsStore>,
+    totp_secret_manager: Arc<dyn SecretManager>,
+    secret_manager: Arc<dyn SecretManager>,
+    rest_config: Arc<BiomeCredentialsRestConfig>,
+) -> HandlerFunction {
+    Box::new(move |request, payload| {
+        let credentials_store = credentials_store.clone();
+        let totp_secret_manager = totp_secret_manager.clone();
+        let validation = default_validation(&rest_config.issuer());
+        let user_id = match authorize_user(&request, &secret_manager, &validation) {
+            AuthorizationResult::Authorized(claims) => claims.user_id(),
+            AuthorizationResult::Unauthorized => {
+                return Box::new(
+                    HttpResponse::Unauthorized()
+                        .json(ErrorResponse::unauthorized())
+                        .into_future(),
+                )
+            }
+            AuthorizationResult::Failed => {
+                return Box::new(
+                    HttpResponse::InternalServerError()
+                        .json(ErrorResponse::internal_error())
+                        .into_future(),
+                );
+            }
+        };
+
+        Box::new(into_bytes(payload).and_then(move |bytes| {
+            let totp_code = match serde_json::from_slice::<TotpCode>(&bytes) {
+                Ok(val) => val,
+                Err(err) => {
+                    debug!("Error parsing payload {}", err);
+                    return HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request(&format!(
+                            "Failed to parse payload: {}",
+                            err
+                        )))
+                        .into_future();
+                }
+            };
+
+            let mut totp = match credentials_store.fetch_totp(&user_id) {
+                Ok(totp) if totp.confirmed => {
+                    return HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request(
+                            "TOTP is already enabled for this user",
+                        ))
+                        .into_future()
+                }
+                Ok(totp) => totp,
+                Err(CredentialsStoreError::NotFoundError(_)) => {
+                    return HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request(
+                            "TOTP enrollment has not been started for this user",
+                        ))
+                        .into_future()
+                }
+                Err(err) => {
+                    error!("Failed to fetch TOTP: {}", err);
+                    return HttpResponse::InternalServerError()
+                        .json(ErrorResponse::internal_error())
+                        .into_future();
+                }
+            };
+
+            let step = match decrypt_secret(&*totp_secret_manager, &totp)
+                .and_then(|secret| verify_code(&secret, &totp_code.code, SystemTime::now()))
+            {
+                Ok(Some(step)) => step,
+                Ok(None) => {
+                    return HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request("Invalid TOTP code"))
+                        .into_future()
+                }
+                Err(err) => {
+                    error!("Failed to verify TOTP code: {}", err);
+                    return HttpResponse::InternalServerError()
+                        .json(ErrorResponse::internal_error())
+                        .into_future();
+                }
+            };
+
+            let recovery_codes = generate_recovery_codes();
+            totp.confirmed = true;
+            totp.last_used_step = Some(step);
+            totp.recovery_code_hashes = recovery_codes
+                .iter()
+                .map(|code| hash_recovery_code(code))
+                .collect();
+
+            match credentials_store.set_totp(totp) {
+                Ok(()) => HttpResponse::Ok()
+                    .json(json!({
+                        "message": "TOTP successfully enabled",
+                        "recovery_codes": recovery_codes,
+                    }))
+                    .into_future(),
+                Err(err) => {
+                    error!("Failed to set TOTP: {}", err);
+                    HttpResponse::InternalServerError()
+                        .json(ErrorResponse::internal_error())
+                        .into_future()
+                }
+            }
+        }))
+    })
+}
+
+/// Defines a REST endpoint that removes a user's TOTP, so the user can log in with their
+/// password alone and enroll again
+fn add_reset_totp_method(credentials_store: Arc<dyn CredentialsStore>) -> HandlerFunction {
+    Box::new(move |request, _| {
+        let user_id = match request.match_info().get("id") {
+            Some(user_id) => user_id.to_string(),
+            None => {
+                return Box::new(
+                    HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request(
+                            "Failed to process request: no user id",
+                        ))
+                        .into_future(),
+                )
+            }
+        };
+
+        Box::new(match credentials_store.remove_totp(&user_id) {
+            Ok(()) => HttpResponse::Ok()
+                .json(json!({ "message": "TOTP successfully reset" }))
+                .into_future(),
+            Err(CredentialsStoreError::NotFoundError(_)) => HttpResponse::NotFound()
+                .json(ErrorResponse::not_found(&format!(
+                    "TOTP not found for user: {}",
+                    user_id
+                )))
+                .into_future(),
+            Err(err) => {
+                error!("Failed to reset TOTP: {}", err);
+                HttpResponse::InternalServerError()
+                    .json(ErrorResponse::internal_error())
+                    .into_future()
+            }
+        })
+    })
+}
+
+fn encrypt_secret(
+    totp_secret_manager: &dyn SecretManager,
+    user_id: &str,
+    secret: &[u8],
+) -> Result<String, InternalError> {
+    let key = totp_secret_manager
+        .secret()
+        .map_err(|err| InternalError::from_source(Box::new(err)))?;
+    TotpSecretCipher::new(&key).encrypt(user_id, secret)
+}
+
+fn decrypt_secret(
+    totp_secret_manager: &dyn SecretManager,
+    totp: &TotpCredentials,
+) -> Result<Vec<u8>, InternalError> {
+    let key = totp_secret_manager
+        .secret()
+        .map_err(|err| InternalError::from_source(Box::new(err)))?;
+    TotpSecretCipher::new(&key).decrypt(&totp.user_id, &totp.encrypted_secret)
+}
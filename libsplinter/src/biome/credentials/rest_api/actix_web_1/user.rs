@@ -24,6 +24,10 @@ use crate::rest_api::{
     ErrorResponse,
 };
 
+#[cfg(all(feature = "biome-key-management", feature = "biome-login-lockout"))]
+use crate::biome::credentials::rest_api::actix_web_1::lockout::{
+    is_locked_out, record_failed_login, reset_failed_logins,
+};
 #[cfg(feature = "biome-key-management")]
 use crate::biome::key_management::{
     store::{KeyStore, KeyStoreError},
@@ -186,6 +190,8 @@ fn add_modify_user_method(
     let encryption_cost = rest_config.password_encryption_cost();
     Box::new(move |request, payload| {
         let credentials_store = credentials_store.clone();
+        #[cfg(feature = "biome-login-lockout")]
+        let rest_config = rest_config.clone();
         let key_store = key_store.clone();
         let user = match request.match_info().get("id") {
             Some(t) => t.to_string(),
@@ -248,8 +254,34 @@ fn add_modify_user_method(
                         }
                     }
                 };
+
+            #[cfg(feature = "biome-login-lockout")]
+            match is_locked_out(&*credentials_store, &credentials.user_id) {
+                Ok(false) => (),
+                Ok(true) => {
+                    return HttpResponse::Forbidden()
+                        .json(ErrorResponse::forbidden("Account is locked"))
+                        .into_future();
+                }
+                Err(err) => {
+                    error!("Failed to check lockout {}", err);
+                    return HttpResponse::InternalServerError()
+                        .json(ErrorResponse::internal_error())
+                        .into_future();
+                }
+            }
+
             match credentials.verify_password(&modify_user.hashed_password) {
                 Ok(true) => {
+                    #[cfg(feature = "biome-login-lockout")]
+                    if let Err(err) = reset_failed_logins(&*credentials_store, &credentials.user_id)
+                    {
+                        error!("Failed to reset failed logins {}", err);
+                        return HttpResponse::InternalServerError()
+                            .json(ErrorResponse::internal_error())
+                            .into_future();
+                    }
+
                     let new_password = match modify_user.new_password {
                         Some(val) => val,
                         // If no new password, pull old password for update operation
@@ -286,9 +318,20 @@ fn add_modify_user_method(
                         },
                     }
                 }
-                Ok(false) => HttpResponse::BadRequest()
-                    .json(ErrorResponse::bad_request("Invalid password"))
-                    .into_future(),
+                Ok(false) => {
+                    #[cfg(feature = "biome-login-lockout")]
+                    if let Err(err) =
+                        record_failed_login(&*credentials_store, &rest_config, &credentials.user_id)
+                    {
+                        error!("Failed to record failed login {}", err);
+                        return HttpResponse::InternalServerError()
+                            .json(ErrorResponse::internal_error())
+                            .into_future();
+                    }
+                    HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request("Invalid password"))
+                        .into_future()
+                }
                 Err(err) => {
                     error!("Failed to verify password {}", err);
                     HttpResponse::InternalServerError()
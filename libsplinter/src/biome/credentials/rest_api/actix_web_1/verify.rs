@@ -27,7 +27,11 @@ use crate::rest_api::{
 };
 
 use crate::biome::credentials::rest_api::actix_web_1::config::BiomeCredentialsRestConfig;
-use crate::biome::credentials::store::{CredentialsStore, CredentialsStoreError};
+#[cfg(feature = "biome-login-lockout")]
+use crate::biome::credentials::rest_api::actix_web_1::lockout::{
+    is_locked_out, record_failed_login, reset_failed_logins,
+};
+use crate::biome::credentials::store::{Credentials, CredentialsStore, CredentialsStoreError};
 
 use super::super::resources::authorize::AuthorizationResult;
 use super::super::resources::credentials::UsernamePassword;
@@ -35,6 +39,9 @@ use super::authorize::authorize_user;
 
 /// Defines a REST endpoint to verify a user's password
 ///
+/// Only the authorized user's own password may be verified. If login lockout is enabled, the
+/// password is checked the same way as at login, so a locked out user cannot be verified.
+///
 /// The payload should be in the JSON format:
 ///   {
 ///       "username": <existing username of the user>
@@ -101,26 +108,14 @@ pub fn make_verify_route(
 
                     let validation = default_validation(&rest_config.issuer());
                     match authorize_user(&request, &secret_manager, &validation) {
-                        AuthorizationResult::Authorized(_) => {
-                            match credentials.verify_password(&username_password.hashed_password) {
-                                Ok(true) => HttpResponse::Ok()
-                                    .json(json!(
-                                        {
-                                            "message": "Successful verification",
-                                            "user_id": credentials.user_id
-                                    }))
-                                    .into_future(),
-                                Ok(false) => HttpResponse::BadRequest()
-                                    .json(ErrorResponse::bad_request("Invalid password"))
-                                    .into_future(),
-                                Err(err) => {
-                                    error!("Failed to verify password: {}", err);
-                                    HttpResponse::InternalServerError()
-                                        .json(ErrorResponse::internal_error())
-                                        .into_future()
-                                }
-                            }
-                        }
+                        AuthorizationResult::Authorized(claims) => verify_password(
+                            &*credentials_store,
+                            &rest_config,
+                            &claims.user_id(),
+                            &credentials,
+                            &username_password.hashed_password,
+                        )
+                        .into_future(),
                         AuthorizationResult::Unauthorized => HttpResponse::Unauthorized()
                             .json(ErrorResponse::unauthorized())
                             .into_future(),
@@ -182,26 +177,14 @@ pub fn make_verify_route(
 
                 let validation = default_validation(&rest_config.issuer());
                 match authorize_user(&request, &secret_manager, &validation) {
-                    AuthorizationResult::Authorized(_) => {
-                        match credentials.verify_password(&username_password.hashed_password) {
-                            Ok(true) => HttpResponse::Ok()
-                                .json(json!(
-                                    {
-                                        "message": "Successful verification",
-                                        "user_id": credentials.user_id
-                                }))
-                                .into_future(),
-                            Ok(false) => HttpResponse::BadRequest()
-                                .json(ErrorResponse::bad_request("Invalid password"))
-                                .into_future(),
-                            Err(err) => {
-                                error!("Failed to verify password: {}", err);
-                                HttpResponse::InternalServerError()
-                                    .json(ErrorResponse::internal_error())
-                                    .into_future()
-                            }
-                        }
-                    }
+                    AuthorizationResult::Authorized(claims) => verify_password(
+                        &*credentials_store,
+                        &rest_config,
+                        &claims.user_id(),
+                        &credentials,
+                        &username_password.hashed_password,
+                    )
+                    .into_future(),
                     AuthorizationResult::Unauthorized => HttpResponse::Unauthorized()
                         .json(ErrorResponse::unauthorized())
                         .into_future(),
@@ -216,3 +199,60 @@ pub fn make_verify_route(
         })
     }
 }
+
+/// Verifies the password of the authorized user, who must be the owner of the credentials
+#[cfg_attr(not(feature = "biome-login-lockout"), allow(unused_variables))]
+fn verify_password(
+    credentials_store: &dyn CredentialsStore,
+    rest_config: &BiomeCredentialsRestConfig,
+    authorized_user_id: &str,
+    credentials: &Credentials,
+    hashed_password: &str,
+) -> HttpResponse {
+    if credentials.user_id != authorized_user_id {
+        return HttpResponse::Forbidden().json(ErrorResponse::forbidden(
+            "Only the password of the authorized user can be verified",
+        ));
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    match is_locked_out(credentials_store, &credentials.user_id) {
+        Ok(false) => (),
+        Ok(true) => {
+            return HttpResponse::Forbidden().json(ErrorResponse::forbidden("Account is locked"));
+        }
+        Err(err) => {
+            error!("Failed to check lockout: {}", err);
+            return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+        }
+    }
+
+    match credentials.verify_password(hashed_password) {
+        Ok(true) => (),
+        Ok(false) => {
+            #[cfg(feature = "biome-login-lockout")]
+            if let Err(err) =
+                record_failed_login(credentials_store, rest_config, &credentials.user_id)
+            {
+                error!("Failed to record failed login: {}", err);
+                return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+            }
+            return HttpResponse::BadRequest().json(ErrorResponse::bad_request("Invalid password"));
+        }
+        Err(err) => {
+            error!("Failed to verify password: {}", err);
+            return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+        }
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    if let Err(err) = reset_failed_logins(credentials_store, &credentials.user_id) {
+        error!("Failed to reset failed logins: {}", err);
+        return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+    }
+
+    HttpResponse::Ok().json(json!({
+        "message": "Successful verification",
+        "user_id": credentials.user_id
+    }))
+}
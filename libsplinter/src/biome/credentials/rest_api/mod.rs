@@ -19,6 +19,10 @@ mod resources;
 #[cfg(all(feature = "authorization", feature = "rest-api-actix"))]
 use crate::rest_api::auth::authorization::Permission;
 
+#[cfg(all(feature = "biome-login-lockout", feature = "rest-api-actix"))]
+pub(in crate::biome) use actix_web_1::lockout::{
+    is_locked_out, record_failed_login, reset_failed_logins,
+};
 #[cfg(feature = "rest-api-actix")]
 pub use actix_web_1::{
     BiomeCredentialsRestConfig, BiomeCredentialsRestConfigBuilder,
@@ -18,6 +18,15 @@
 pub(crate) struct UsernamePassword {
     pub username: String,
     pub hashed_password: String,
+    /// An optional name for the session started by a login, such as the name of the device
+    #[cfg(feature = "biome-sessions")]
+    pub display_name: Option<String>,
+    /// The current TOTP code, required if the user has enrolled in TOTP
+    #[cfg(feature = "biome-totp")]
+    pub totp_code: Option<String>,
+    /// A recovery code, which may be used once in place of a TOTP code
+    #[cfg(feature = "biome-totp")]
+    pub recovery_code: Option<String>,
 }
 
 #[derive(Serialize)]
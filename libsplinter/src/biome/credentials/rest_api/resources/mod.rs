@@ -18,5 +18,11 @@ pub(super) mod authorize;
 pub(super) mod credentials;
 #[cfg(feature = "biome-key-management")]
 pub(super) mod key_management;
+#[cfg(feature = "biome-password-reset")]
+pub(super) mod password_reset;
+#[cfg(feature = "biome-sessions")]
+pub(super) mod session;
 pub(super) mod token;
+#[cfg(feature = "biome-totp")]
+pub(super) mod totp;
 pub(super) mod user;
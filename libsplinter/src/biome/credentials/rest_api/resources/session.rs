@@ -0,0 +1,49 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::biome::refresh_tokens::store::RefreshTokenSession;
+
+#[derive(Serialize)]
+pub(crate) struct SessionResponse<'a> {
+    session_id: &'a str,
+    display_name: Option<&'a str>,
+    user_agent: Option<&'a str>,
+    /// Seconds since the Unix epoch
+    created: u64,
+    /// Seconds since the Unix epoch
+    last_used: u64,
+    /// Whether or not this is the session the request was made with
+    current: bool,
+}
+
+impl<'a> SessionResponse<'a> {
+    pub fn new(session: &'a RefreshTokenSession, current_session_id: Option<&str>) -> Self {
+        SessionResponse {
+            session_id: session.session_id(),
+            display_name: session.display_name(),
+            user_agent: session.user_agent(),
+            created: to_secs(session.created()),
+            last_used: to_secs(session.last_used()),
+            current: current_session_id == Some(session.session_id()),
+        }
+    }
+}
+
+fn to_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
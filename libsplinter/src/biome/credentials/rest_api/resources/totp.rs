@@ -0,0 +1,38 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Defines the payloads and responses of the TOTP endpoints.
+
+#[derive(Deserialize)]
+pub(crate) struct TotpCode {
+    pub code: String,
+}
+
+#[derive(Serialize)]
+pub(crate) struct TotpStatusResponse {
+    /// Whether or not the user has started enrolling in TOTP
+    pub enrolled: bool,
+    /// Whether or not TOTP is required when the user logs in
+    pub confirmed: bool,
+    /// The number of unused recovery codes the user has left
+    pub recovery_codes_remaining: usize,
+}
+
+#[derive(Serialize)]
+pub(crate) struct TotpEnrollmentResponse {
+    /// The base32-encoded TOTP secret, for entering into an authenticator app by hand
+    pub secret: String,
+    /// The `otpauth://` URI of the secret, for rendering as a QR code
+    pub provisioning_uri: String,
+}
@@ -18,18 +18,46 @@ pub(in crate::biome) mod schema;
 
 use diesel::r2d2::{ConnectionManager, Pool};
 
+#[cfg(feature = "biome-login-lockout")]
+use super::LoginFailures;
+#[cfg(feature = "biome-password-reset")]
+use super::PasswordResetToken;
+#[cfg(feature = "biome-totp")]
+use super::TotpCredentials;
 use super::{
     Credentials, CredentialsStore, CredentialsStoreError, PasswordEncryptionCost, UsernameId,
 };
 
 use models::CredentialsModel;
 use operations::add_credentials::CredentialsStoreAddCredentialsOperation as _;
+#[cfg(feature = "biome-login-lockout")]
+use operations::add_failed_login::CredentialsStoreAddFailedLoginOperation as _;
+#[cfg(feature = "biome-password-reset")]
+use operations::add_password_reset_token::CredentialsStoreAddPasswordResetTokenOperation as _;
 use operations::fetch_credential_by_id::CredentialsStoreFetchCredentialByIdOperation as _;
 use operations::fetch_credential_by_username::CredentialsStoreFetchCredentialByUsernameOperation as _;
+#[cfg(feature = "biome-login-lockout")]
+use operations::fetch_login_failures::CredentialsStoreFetchLoginFailuresOperation as _;
+#[cfg(feature = "biome-totp")]
+use operations::fetch_totp::CredentialsStoreFetchTotpOperation as _;
 use operations::fetch_username::CredentialsStoreFetchUsernameOperation as _;
 use operations::list_usernames::CredentialsStoreListUsernamesOperation as _;
 use operations::remove_credentials::CredentialsStoreRemoveCredentialsOperation as _;
+#[cfg(feature = "biome-login-lockout")]
+use operations::remove_login_failures::CredentialsStoreRemoveLoginFailuresOperation as _;
+#[cfg(feature = "biome-totp")]
+use operations::remove_totp::CredentialsStoreRemoveTotpOperation as _;
+#[cfg(feature = "biome-login-lockout")]
+use operations::set_login_failures::CredentialsStoreSetLoginFailuresOperation as _;
+#[cfg(feature = "biome-totp")]
+use operations::set_totp::CredentialsStoreSetTotpOperation as _;
+#[cfg(feature = "biome-password-reset")]
+use operations::take_password_reset_token::CredentialsStoreTakePasswordResetTokenOperation as _;
 use operations::update_credentials::CredentialsStoreUpdateCredentialsOperation as _;
+#[cfg(feature = "biome-totp")]
+use operations::use_recovery_code::CredentialsStoreUseRecoveryCodeOperation as _;
+#[cfg(feature = "biome-totp")]
+use operations::use_totp_step::CredentialsStoreUseTotpStepOperation as _;
 use operations::CredentialsStoreOperations;
 
 /// Manages creating, updating and fetching SplinterCredentials from the database
@@ -96,6 +124,75 @@ impl CredentialsStore for DieselCredentialsStore<diesel::pg::PgConnection> {
     fn list_usernames(&self) -> Result<Vec<UsernameId>, CredentialsStoreError> {
         CredentialsStoreOperations::new(&*self.connection_pool.get()?).list_usernames()
     }
+
+    #[cfg(feature = "biome-totp")]
+    fn set_totp(&self, totp: TotpCredentials) -> Result<(), CredentialsStoreError> {
+        CredentialsStoreOperations::new(&*self.connection_pool.get()?).set_totp(totp)
+    }
+
+    #[cfg(feature = "biome-totp")]
+    fn fetch_totp(&self, user_id: &str) -> Result<TotpCredentials, CredentialsStoreError> {
+        CredentialsStoreOperations::new(&*self.connection_pool.get()?).fetch_totp(user_id)
+    }
+
+    #[cfg(feature = "biome-totp")]
+    fn remove_totp(&self, user_id: &str) -> Result<(), CredentialsStoreError> {
+        CredentialsStoreOperations::new(&*self.connection_pool.get()?).remove_totp(user_id)
+    }
+
+    #[cfg(feature = "biome-totp")]
+    fn use_totp_step(&self, user_id: &str, step: u64) -> Result<bool, CredentialsStoreError> {
+        CredentialsStoreOperations::new(&*self.connection_pool.get()?).use_totp_step(user_id, step)
+    }
+
+    #[cfg(feature = "biome-totp")]
+    fn use_recovery_code(
+        &self,
+        user_id: &str,
+        code_hash: &str,
+    ) -> Result<bool, CredentialsStoreError> {
+        CredentialsStoreOperations::new(&*self.connection_pool.get()?)
+            .use_recovery_code(user_id, code_hash)
+    }
+
+    #[cfg(feature = "biome-password-reset")]
+    fn add_password_reset_token(
+        &self,
+        token: PasswordResetToken,
+    ) -> Result<(), CredentialsStoreError> {
+        CredentialsStoreOperations::new(&*self.connection_pool.get()?)
+            .add_password_reset_token(token)
+    }
+
+    #[cfg(feature = "biome-password-reset")]
+    fn take_password_reset_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<PasswordResetToken, CredentialsStoreError> {
+        CredentialsStoreOperations::new(&*self.connection_pool.get()?)
+            .take_password_reset_token(token_hash)
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    fn fetch_login_failures(&self, user_id: &str) -> Result<LoginFailures, CredentialsStoreError> {
+        CredentialsStoreOperations::new(&*self.connection_pool.get()?).fetch_login_failures(user_id)
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    fn set_login_failures(&self, failures: LoginFailures) -> Result<(), CredentialsStoreError> {
+        CredentialsStoreOperations::new(&*self.connection_pool.get()?).set_login_failures(failures)
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    fn add_failed_login(&self, user_id: &str) -> Result<u32, CredentialsStoreError> {
+        CredentialsStoreOperations::new(&*self.connection_pool.get()?).add_failed_login(user_id)
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    fn remove_login_failures(&self, user_id: &str) -> Result<(), CredentialsStoreError> {
+        CredentialsStoreOperations::new(&*self.connection_pool.get()?)
+            .remove_login_failures(user_id)
+    }
 }
 
 #[cfg(feature = "sqlite")]
@@ -146,6 +243,75 @@ impl CredentialsStore for DieselCredentialsStore<diesel::sqlite::SqliteConnectio
     fn list_usernames(&self) -> Result<Vec<UsernameId>, CredentialsStoreError> {
         CredentialsStoreOperations::new(&*self.connection_pool.get()?).list_usernames()
     }
+
+    #[cfg(feature = "biome-totp")]
+    fn set_totp(&self, totp: TotpCredentials) -> Result<(), CredentialsStoreError> {
+        CredentialsStoreOperations::new(&*self.connection_pool.get()?).set_totp(totp)
+    }
+
+    #[cfg(feature = "biome-totp")]
+    fn fetch_totp(&self, user_id: &str) -> Result<TotpCredentials, CredentialsStoreError> {
+        CredentialsStoreOperations::new(&*self.connection_pool.get()?).fetch_totp(user_id)
+    }
+
+    #[cfg(feature = "biome-totp")]
+    fn remove_totp(&self, user_id: &str) -> Result<(), CredentialsStoreError> {
+        CredentialsStoreOperations::new(&*self.connection_pool.get()?).remove_totp(user_id)
+    }
+
+    #[cfg(feature = "biome-totp")]
+    fn use_totp_step(&self, user_id: &str, step: u64) -> Result<bool, CredentialsStoreError> {
+        CredentialsStoreOperations::new(&*self.connection_pool.get()?).use_totp_step(user_id, step)
+    }
+
+    #[cfg(feature = "biome-totp")]
+    fn use_recovery_code(
+        &self,
+        user_id: &str,
+        code_hash: &str,
+    ) -> Result<bool, CredentialsStoreError> {
+        CredentialsStoreOperations::new(&*self.connection_pool.get()?)
+            .use_recovery_code(user_id, code_hash)
+    }
+
+    #[cfg(feature = "biome-password-reset")]
+    fn add_password_reset_token(
+        &self,
+        token: PasswordResetToken,
+    ) -> Result<(), CredentialsStoreError> {
+        CredentialsStoreOperations::new(&*self.connection_pool.get()?)
+            .add_password_reset_token(token)
+    }
+
+    #[cfg(feature = "biome-password-reset")]
+    fn take_password_reset_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<PasswordResetToken, CredentialsStoreError> {
+        CredentialsStoreOperations::new(&*self.connection_pool.get()?)
+            .take_password_reset_token(token_hash)
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    fn fetch_login_failures(&self, user_id: &str) -> Result<LoginFailures, CredentialsStoreError> {
+        CredentialsStoreOperations::new(&*self.connection_pool.get()?).fetch_login_failures(user_id)
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    fn set_login_failures(&self, failures: LoginFailures) -> Result<(), CredentialsStoreError> {
+        CredentialsStoreOperations::new(&*self.connection_pool.get()?).set_login_failures(failures)
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    fn add_failed_login(&self, user_id: &str) -> Result<u32, CredentialsStoreError> {
+        CredentialsStoreOperations::new(&*self.connection_pool.get()?).add_failed_login(user_id)
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    fn remove_login_failures(&self, user_id: &str) -> Result<(), CredentialsStoreError> {
+        CredentialsStoreOperations::new(&*self.connection_pool.get()?)
+            .remove_login_failures(user_id)
+    }
 }
 
 impl From<CredentialsModel> for UsernameId {
@@ -574,6 +740,203 @@ pub mod tests {
         }));
     }
 
+    /// Verify that a SQLite-backed `DieselCredentialsStore` correctly supports setting, fetching,
+    /// and removing a user's TOTP second factor.
+    ///
+    /// 1. Create a connection pool for an in-memory SQLite database and run migrations.
+    /// 2. Create the `DieselCredentialsStore` and add a credential.
+    /// 3. Verify that fetching TOTP returns a `CredentialsStoreError::NotFoundError`.
+    /// 4. Set TOTP, then replace it, and verify that the replacement is returned.
+    /// 5. Remove the credential and verify that TOTP was removed with it.
+    #[cfg(feature = "biome-totp")]
+    #[test]
+    fn sqlite_totp() {
+        let pool = create_connection_pool_and_migrate();
+
+        let store = DieselCredentialsStore::new(pool);
+
+        let cred = CredentialsBuilder::default()
+            .with_user_id("id")
+            .with_username("user")
+            .with_password("pwd")
+            .with_password_encryption_cost(PasswordEncryptionCost::Low)
+            .build()
+            .expect("Failed to build cred");
+        store.add_credentials(cred).expect("Failed to add cred");
+
+        match store.fetch_totp("id") {
+            Err(CredentialsStoreError::NotFoundError(_)) => {}
+            res => panic!(
+                "Expected Err(CredentialsStoreError::NotFoundError), got {:?} instead",
+                res
+            ),
+        }
+
+        let mut totp = TotpCredentials {
+            user_id: "id".into(),
+            encrypted_secret: "secret".into(),
+            confirmed: false,
+            last_used_step: None,
+            recovery_code_hashes: vec![],
+        };
+        store.set_totp(totp.clone()).expect("Failed to set TOTP");
+        assert_eq!(store.fetch_totp("id").expect("Failed to fetch TOTP"), totp);
+
+        totp.confirmed = true;
+        totp.last_used_step = Some(10);
+        totp.recovery_code_hashes = vec!["hash1".into(), "hash2".into()];
+        store.set_totp(totp.clone()).expect("Failed to set TOTP");
+        let mut fetched = store.fetch_totp("id").expect("Failed to fetch TOTP");
+        fetched.recovery_code_hashes.sort();
+        assert_eq!(fetched, totp);
+
+        store
+            .remove_credentials("id")
+            .expect("Failed to remove cred");
+        match store.fetch_totp("id") {
+            Err(CredentialsStoreError::NotFoundError(_)) => {}
+            res => panic!(
+                "Expected Err(CredentialsStoreError::NotFoundError), got {:?} instead",
+                res
+            ),
+        }
+        match store.remove_totp("id") {
+            Err(CredentialsStoreError::NotFoundError(_)) => {}
+            res => panic!(
+                "Expected Err(CredentialsStoreError::NotFoundError), got {:?} instead",
+                res
+            ),
+        }
+    }
+
+    /// Verify that a SQLite-backed `DieselCredentialsStore` correctly supports adding and taking
+    /// password reset tokens.
+    ///
+    /// 1. Create a connection pool for an in-memory SQLite database and run migrations.
+    /// 2. Create the `DieselCredentialsStore` and add two tokens for the same user.
+    /// 3. Take one of the tokens and verify that it is returned with its expiry intact.
+    /// 4. Verify that both tokens are now gone.
+    #[cfg(feature = "biome-password-reset")]
+    #[test]
+    fn sqlite_password_reset_tokens() {
+        let pool = create_connection_pool_and_migrate();
+
+        let store = DieselCredentialsStore::new(pool);
+
+        let token1 = PasswordResetToken {
+            user_id: "id".into(),
+            token_hash: "hash1".into(),
+            expires: std::time::UNIX_EPOCH + std::time::Duration::from_secs(1000),
+        };
+        let token2 = PasswordResetToken {
+            user_id: "id".into(),
+            token_hash: "hash2".into(),
+            expires: std::time::UNIX_EPOCH + std::time::Duration::from_secs(2000),
+        };
+        store
+            .add_password_reset_token(token1.clone())
+            .expect("Failed to add token1");
+        store
+            .add_password_reset_token(token2)
+            .expect("Failed to add token2");
+
+        assert_eq!(
+            store
+                .take_password_reset_token("hash1")
+                .expect("Failed to take token1"),
+            token1
+        );
+
+        for hash in &["hash1", "hash2"] {
+            match store.take_password_reset_token(hash) {
+                Err(CredentialsStoreError::NotFoundError(_)) => {}
+                res => panic!(
+                    "Expected Err(CredentialsStoreError::NotFoundError), got {:?} instead",
+                    res
+                ),
+            }
+        }
+    }
+
+    /// Verify that a SQLite-backed `DieselCredentialsStore` correctly supports setting, fetching,
+    /// and removing a user's failed login attempts.
+    ///
+    /// 1. Create a connection pool for an in-memory SQLite database and run migrations.
+    /// 2. Create the `DieselCredentialsStore`.
+    /// 3. Verify that a user without failures has a count of zero and is not locked out.
+    /// 4. Set the failures twice and verify that the latest value is returned.
+    /// 5. Remove the failures and verify that the count is zero again.
+    /// 6. Add two failed logins and verify that the count is incremented each time.
+    #[cfg(feature = "biome-login-lockout")]
+    #[test]
+    fn sqlite_login_failures() {
+        let pool = create_connection_pool_and_migrate();
+
+        let store = DieselCredentialsStore::new(pool);
+
+        let none = LoginFailures {
+            user_id: "id".into(),
+            failed_attempts: 0,
+            locked_until: None,
+        };
+        assert_eq!(
+            store
+                .fetch_login_failures("id")
+                .expect("Failed to fetch failures"),
+            none
+        );
+
+        let mut failures = LoginFailures {
+            user_id: "id".into(),
+            failed_attempts: 2,
+            locked_until: None,
+        };
+        store
+            .set_login_failures(failures.clone())
+            .expect("Failed to set failures");
+        failures.failed_attempts = 0;
+        failures.locked_until = Some(std::time::UNIX_EPOCH + std::time::Duration::from_secs(1000));
+        store
+            .set_login_failures(failures.clone())
+            .expect("Failed to set failures");
+        assert_eq!(
+            store
+                .fetch_login_failures("id")
+                .expect("Failed to fetch failures"),
+            failures
+        );
+
+        store
+            .remove_login_failures("id")
+            .expect("Failed to remove failures");
+        assert_eq!(
+            store
+                .fetch_login_failures("id")
+                .expect("Failed to fetch failures"),
+            none
+        );
+
+        assert_eq!(
+            store
+                .add_failed_login("id")
+                .expect("Failed to add failed login"),
+            1
+        );
+        assert_eq!(
+            store
+                .add_failed_login("id")
+                .expect("Failed to add failed login"),
+            2
+        );
+        assert_eq!(
+            store
+                .fetch_login_failures("id")
+                .expect("Failed to fetch failures")
+                .failed_attempts,
+            2
+        );
+    }
+
     /// Creates a conneciton pool for an in-memory SQLite database with only a single connection
     /// available. Each connection is backed by a different in-memory SQLite database, so limiting
     /// the pool to a single connection insures that the same DB is used for all operations.
@@ -12,7 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "biome-password-reset")]
+use super::schema::password_reset_tokens;
 use super::schema::user_credentials;
+#[cfg(feature = "biome-login-lockout")]
+use super::schema::user_login_failures;
+#[cfg(feature = "biome-totp")]
+use super::schema::{user_totp, user_totp_recovery_codes};
 
 #[derive(Queryable, Identifiable, Associations, PartialEq, Debug)]
 #[table_name = "user_credentials"]
@@ -30,3 +36,39 @@ pub struct NewCredentialsModel {
     pub username: String,
     pub password: String,
 }
+
+#[cfg(feature = "biome-totp")]
+#[derive(Insertable, Queryable, PartialEq, Debug)]
+#[table_name = "user_totp"]
+pub struct TotpModel {
+    pub user_id: String,
+    pub encrypted_secret: String,
+    pub confirmed: bool,
+    pub last_used_step: Option<i64>,
+}
+
+#[cfg(feature = "biome-totp")]
+#[derive(Insertable, Queryable, PartialEq, Debug)]
+#[table_name = "user_totp_recovery_codes"]
+pub struct TotpRecoveryCodeModel {
+    pub user_id: String,
+    pub code_hash: String,
+}
+
+#[cfg(feature = "biome-password-reset")]
+#[derive(Insertable, Queryable, PartialEq, Debug)]
+#[table_name = "password_reset_tokens"]
+pub struct PasswordResetTokenModel {
+    pub token_hash: String,
+    pub user_id: String,
+    pub expires: i64,
+}
+
+#[cfg(feature = "biome-login-lockout")]
+#[derive(Insertable, Queryable, PartialEq, Debug)]
+#[table_name = "user_login_failures"]
+pub struct LoginFailuresModel {
+    pub user_id: String,
+    pub failed_attempts: i32,
+    pub locked_until: Option<i64>,
+}
@@ -0,0 +1,98 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::convert::TryFrom;
+
+use super::CredentialsStoreOperations;
+use crate::biome::credentials::store::diesel::{
+    models::LoginFailuresModel, schema::user_login_failures, CredentialsStoreError,
+};
+use diesel::{dsl::insert_into, prelude::*};
+
+pub(in crate::biome::credentials) trait CredentialsStoreAddFailedLoginOperation {
+    fn add_failed_login(&self, user_id: &str) -> Result<u32, CredentialsStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> CredentialsStoreAddFailedLoginOperation
+    for CredentialsStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn add_failed_login(&self, user_id: &str) -> Result<u32, CredentialsStoreError> {
+        let failed_attempts = insert_into(user_login_failures::table)
+            .values(&first_failure(user_id))
+            .on_conflict(user_login_failures::user_id)
+            .do_update()
+            .set(user_login_failures::failed_attempts.eq(user_login_failures::failed_attempts + 1))
+            .returning(user_login_failures::failed_attempts)
+            .get_result::<i32>(self.conn)
+            .map_err(|err| CredentialsStoreError::OperationError {
+                context: "Failed to add failed login".to_string(),
+                source: Box::new(err),
+            })?;
+
+        from_failed_attempts(failed_attempts)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> CredentialsStoreAddFailedLoginOperation
+    for CredentialsStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn add_failed_login(&self, user_id: &str) -> Result<u32, CredentialsStoreError> {
+        // An immediate transaction takes the write lock up front, so concurrent increments are
+        // serialized
+        let failed_attempts = self
+            .conn
+            .immediate_transaction::<_, diesel::result::Error, _>(|| {
+                let updated = diesel::update(
+                    user_login_failures::table.filter(user_login_failures::user_id.eq(user_id)),
+                )
+                .set(
+                    user_login_failures::failed_attempts
+                        .eq(user_login_failures::failed_attempts + 1),
+                )
+                .execute(self.conn)?;
+                if updated == 0 {
+                    insert_into(user_login_failures::table)
+                        .values(&first_failure(user_id))
+                        .execute(self.conn)?;
+                }
+
+                user_login_failures::table
+                    .filter(user_login_failures::user_id.eq(user_id))
+                    .select(user_login_failures::failed_attempts)
+                    .first::<i32>(self.conn)
+            })
+            .map_err(|err| CredentialsStoreError::OperationError {
+                context: "Failed to add failed login".to_string(),
+                source: Box::new(err),
+            })?;
+
+        from_failed_attempts(failed_attempts)
+    }
+}
+
+fn first_failure(user_id: &str) -> LoginFailuresModel {
+    LoginFailuresModel {
+        user_id: user_id.to_string(),
+        failed_attempts: 1,
+        locked_until: None,
+    }
+}
+
+fn from_failed_attempts(failed_attempts: i32) -> Result<u32, CredentialsStoreError> {
+    u32::try_from(failed_attempts).map_err(|err| CredentialsStoreError::OperationError {
+        context: "Failed login attempts are out of range".to_string(),
+        source: Box::new(err),
+    })
+}
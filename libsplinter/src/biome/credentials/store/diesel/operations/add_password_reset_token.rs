@@ -0,0 +1,90 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::convert::TryFrom;
+use std::time::UNIX_EPOCH;
+
+use super::CredentialsStoreOperations;
+use crate::biome::credentials::store::diesel::{
+    models::PasswordResetTokenModel, schema::password_reset_tokens, CredentialsStoreError,
+};
+use crate::biome::credentials::store::PasswordResetToken;
+use diesel::{dsl::insert_into, prelude::*};
+
+pub(in crate::biome::credentials) trait CredentialsStoreAddPasswordResetTokenOperation {
+    fn add_password_reset_token(
+        &self,
+        token: PasswordResetToken,
+    ) -> Result<(), CredentialsStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> CredentialsStoreAddPasswordResetTokenOperation
+    for CredentialsStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn add_password_reset_token(
+        &self,
+        token: PasswordResetToken,
+    ) -> Result<(), CredentialsStoreError> {
+        insert_into(password_reset_tokens::table)
+            .values(into_model(token)?)
+            .execute(self.conn)
+            .map(|_| ())
+            .map_err(|err| CredentialsStoreError::OperationError {
+                context: "Failed to add password reset token".to_string(),
+                source: Box::new(err),
+            })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> CredentialsStoreAddPasswordResetTokenOperation
+    for CredentialsStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn add_password_reset_token(
+        &self,
+        token: PasswordResetToken,
+    ) -> Result<(), CredentialsStoreError> {
+        insert_into(password_reset_tokens::table)
+            .values(into_model(token)?)
+            .execute(self.conn)
+            .map(|_| ())
+            .map_err(|err| CredentialsStoreError::OperationError {
+                context: "Failed to add password reset token".to_string(),
+                source: Box::new(err),
+            })
+    }
+}
+
+fn into_model(token: PasswordResetToken) -> Result<PasswordResetTokenModel, CredentialsStoreError> {
+    let expires = token
+        .expires
+        .duration_since(UNIX_EPOCH)
+        .map_err(|err| CredentialsStoreError::OperationError {
+            context: "Password reset token expiry is before the epoch".to_string(),
+            source: Box::new(err),
+        })
+        .and_then(|duration| {
+            i64::try_from(duration.as_secs()).map_err(|err| CredentialsStoreError::OperationError {
+                context: "Password reset token expiry is out of range".to_string(),
+                source: Box::new(err),
+            })
+        })?;
+
+    Ok(PasswordResetTokenModel {
+        token_hash: token.token_hash,
+        user_id: token.user_id,
+        expires,
+    })
+}
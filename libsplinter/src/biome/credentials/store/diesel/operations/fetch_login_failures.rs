@@ -0,0 +1,61 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::{Duration, UNIX_EPOCH};
+
+use super::CredentialsStoreOperations;
+use crate::biome::credentials::store::diesel::{
+    models::LoginFailuresModel, schema::user_login_failures, CredentialsStoreError,
+};
+use crate::biome::credentials::store::LoginFailures;
+use diesel::{prelude::*, result::Error::NotFound};
+
+pub(in crate::biome::credentials) trait CredentialsStoreFetchLoginFailuresOperation {
+    fn fetch_login_failures(&self, user_id: &str) -> Result<LoginFailures, CredentialsStoreError>;
+}
+
+impl<'a, C> CredentialsStoreFetchLoginFailuresOperation for CredentialsStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+    i32: diesel::deserialize::FromSql<diesel::sql_types::Integer, C::Backend>,
+    i64: diesel::deserialize::FromSql<diesel::sql_types::BigInt, C::Backend>,
+    String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
+{
+    fn fetch_login_failures(&self, user_id: &str) -> Result<LoginFailures, CredentialsStoreError> {
+        let failures = user_login_failures::table
+            .filter(user_login_failures::user_id.eq(user_id))
+            .first::<LoginFailuresModel>(self.conn)
+            .map(Some)
+            .or_else(|err| if err == NotFound { Ok(None) } else { Err(err) })
+            .map_err(|err| CredentialsStoreError::QueryError {
+                context: "Failed to fetch login failures".to_string(),
+                source: Box::new(err),
+            })?;
+
+        Ok(match failures {
+            Some(failures) => LoginFailures {
+                user_id: failures.user_id,
+                failed_attempts: failures.failed_attempts.max(0) as u32,
+                locked_until: failures
+                    .locked_until
+                    .map(|secs| UNIX_EPOCH + Duration::from_secs(secs.max(0) as u64)),
+            },
+            None => LoginFailures {
+                user_id: user_id.to_string(),
+                failed_attempts: 0,
+                locked_until: None,
+            },
+        })
+    }
+}
@@ -0,0 +1,81 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::convert::TryFrom;
+
+use super::CredentialsStoreOperations;
+use crate::biome::credentials::store::diesel::{
+    models::TotpModel,
+    schema::{user_totp, user_totp_recovery_codes},
+    CredentialsStoreError,
+};
+use crate::biome::credentials::store::TotpCredentials;
+use diesel::{prelude::*, result::Error::NotFound};
+
+pub(in crate::biome::credentials) trait CredentialsStoreFetchTotpOperation {
+    fn fetch_totp(&self, user_id: &str) -> Result<TotpCredentials, CredentialsStoreError>;
+}
+
+impl<'a, C> CredentialsStoreFetchTotpOperation for CredentialsStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+    i64: diesel::deserialize::FromSql<diesel::sql_types::BigInt, C::Backend>,
+    String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
+    bool: diesel::deserialize::FromSql<diesel::sql_types::Bool, C::Backend>,
+    C::Backend: diesel::sql_types::HasSqlType<diesel::sql_types::Bool>,
+{
+    fn fetch_totp(&self, user_id: &str) -> Result<TotpCredentials, CredentialsStoreError> {
+        let totp = user_totp::table
+            .filter(user_totp::user_id.eq(user_id))
+            .first::<TotpModel>(self.conn)
+            .map(Some)
+            .or_else(|err| if err == NotFound { Ok(None) } else { Err(err) })
+            .map_err(|err| CredentialsStoreError::QueryError {
+                context: "Failed to fetch TOTP".to_string(),
+                source: Box::new(err),
+            })?
+            .ok_or_else(|| {
+                CredentialsStoreError::NotFoundError(format!(
+                    "TOTP not found for user id: {}",
+                    user_id
+                ))
+            })?;
+
+        let recovery_code_hashes = user_totp_recovery_codes::table
+            .filter(user_totp_recovery_codes::user_id.eq(user_id))
+            .select(user_totp_recovery_codes::code_hash)
+            .load::<String>(self.conn)
+            .map_err(|err| CredentialsStoreError::QueryError {
+                context: "Failed to fetch TOTP recovery codes".to_string(),
+                source: Box::new(err),
+            })?;
+
+        let last_used_step = totp
+            .last_used_step
+            .map(u64::try_from)
+            .transpose()
+            .map_err(|err| CredentialsStoreError::OperationError {
+                context: "Stored TOTP time step is invalid".to_string(),
+                source: Box::new(err),
+            })?;
+
+        Ok(TotpCredentials {
+            user_id: totp.user_id,
+            encrypted_secret: totp.encrypted_secret,
+            confirmed: totp.confirmed,
+            last_used_step,
+            recovery_code_hashes,
+        })
+    }
+}
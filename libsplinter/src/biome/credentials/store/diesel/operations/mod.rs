@@ -15,12 +15,34 @@
 //! Provides CredentialsStoreOperations implemented for a diesel backend
 
 pub(super) mod add_credentials;
+#[cfg(feature = "biome-login-lockout")]
+pub(super) mod add_failed_login;
+#[cfg(feature = "biome-password-reset")]
+pub(super) mod add_password_reset_token;
 pub(super) mod fetch_credential_by_id;
 pub(super) mod fetch_credential_by_username;
+#[cfg(feature = "biome-login-lockout")]
+pub(super) mod fetch_login_failures;
+#[cfg(feature = "biome-totp")]
+pub(super) mod fetch_totp;
 pub(super) mod fetch_username;
 pub(super) mod list_usernames;
 pub(super) mod remove_credentials;
+#[cfg(feature = "biome-login-lockout")]
+pub(super) mod remove_login_failures;
+#[cfg(feature = "biome-totp")]
+pub(super) mod remove_totp;
+#[cfg(feature = "biome-login-lockout")]
+pub(super) mod set_login_failures;
+#[cfg(feature = "biome-totp")]
+pub(super) mod set_totp;
+#[cfg(feature = "biome-password-reset")]
+pub(super) mod take_password_reset_token;
 pub(super) mod update_credentials;
+#[cfg(feature = "biome-totp")]
+pub(super) mod use_recovery_code;
+#[cfg(feature = "biome-totp")]
+pub(super) mod use_totp_step;
 
 pub(super) struct CredentialsStoreOperations<'a, C> {
     conn: &'a C,
@@ -13,6 +13,12 @@
 // limitations under the License.
 
 use super::CredentialsStoreOperations;
+#[cfg(feature = "biome-password-reset")]
+use crate::biome::credentials::store::diesel::schema::password_reset_tokens;
+#[cfg(feature = "biome-login-lockout")]
+use crate::biome::credentials::store::diesel::schema::user_login_failures;
+#[cfg(feature = "biome-totp")]
+use crate::biome::credentials::store::diesel::schema::{user_totp, user_totp_recovery_codes};
 use crate::biome::credentials::store::diesel::{schema::user_credentials, CredentialsStoreError};
 use crate::biome::credentials::store::CredentialsModel;
 use diesel::{dsl::delete, prelude::*, result::Error::NotFound};
@@ -44,9 +50,31 @@ where
             )));
         }
 
-        delete(user_credentials::table.filter(user_credentials::user_id.eq(user_id)))
-            .execute(self.conn)
-            .map(|_| ())
+        self.conn
+            .transaction::<_, diesel::result::Error, _>(|| {
+                #[cfg(feature = "biome-totp")]
+                {
+                    delete(
+                        user_totp_recovery_codes::table
+                            .filter(user_totp_recovery_codes::user_id.eq(user_id)),
+                    )
+                    .execute(self.conn)?;
+                    delete(user_totp::table.filter(user_totp::user_id.eq(user_id)))
+                        .execute(self.conn)?;
+                }
+                #[cfg(feature = "biome-password-reset")]
+                delete(
+                    password_reset_tokens::table.filter(password_reset_tokens::user_id.eq(user_id)),
+                )
+                .execute(self.conn)?;
+                #[cfg(feature = "biome-login-lockout")]
+                delete(user_login_failures::table.filter(user_login_failures::user_id.eq(user_id)))
+                    .execute(self.conn)?;
+
+                delete(user_credentials::table.filter(user_credentials::user_id.eq(user_id)))
+                    .execute(self.conn)
+                    .map(|_| ())
+            })
             .map_err(|err| CredentialsStoreError::OperationError {
                 context: "Failed to delete credentials".to_string(),
                 source: Box::new(err),
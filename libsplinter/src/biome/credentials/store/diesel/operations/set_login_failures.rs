@@ -0,0 +1,115 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::convert::TryFrom;
+use std::time::UNIX_EPOCH;
+
+use super::CredentialsStoreOperations;
+use crate::biome::credentials::store::diesel::{
+    models::LoginFailuresModel, schema::user_login_failures, CredentialsStoreError,
+};
+use crate::biome::credentials::store::LoginFailures;
+use diesel::{
+    dsl::{delete, insert_into},
+    prelude::*,
+};
+
+pub(in crate::biome::credentials) trait CredentialsStoreSetLoginFailuresOperation {
+    fn set_login_failures(&self, failures: LoginFailures) -> Result<(), CredentialsStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> CredentialsStoreSetLoginFailuresOperation
+    for CredentialsStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn set_login_failures(&self, failures: LoginFailures) -> Result<(), CredentialsStoreError> {
+        let failures = into_model(failures)?;
+
+        self.conn
+            .transaction::<_, diesel::result::Error, _>(|| {
+                delete(
+                    user_login_failures::table
+                        .filter(user_login_failures::user_id.eq(&failures.user_id)),
+                )
+                .execute(self.conn)?;
+                insert_into(user_login_failures::table)
+                    .values(&failures)
+                    .execute(self.conn)
+                    .map(|_| ())
+            })
+            .map_err(|err| CredentialsStoreError::OperationError {
+                context: "Failed to set login failures".to_string(),
+                source: Box::new(err),
+            })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> CredentialsStoreSetLoginFailuresOperation
+    for CredentialsStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn set_login_failures(&self, failures: LoginFailures) -> Result<(), CredentialsStoreError> {
+        let failures = into_model(failures)?;
+
+        self.conn
+            .transaction::<_, diesel::result::Error, _>(|| {
+                delete(
+                    user_login_failures::table
+                        .filter(user_login_failures::user_id.eq(&failures.user_id)),
+                )
+                .execute(self.conn)?;
+                insert_into(user_login_failures::table)
+                    .values(&failures)
+                    .execute(self.conn)
+                    .map(|_| ())
+            })
+            .map_err(|err| CredentialsStoreError::OperationError {
+                context: "Failed to set login failures".to_string(),
+                source: Box::new(err),
+            })
+    }
+}
+
+fn into_model(failures: LoginFailures) -> Result<LoginFailuresModel, CredentialsStoreError> {
+    let failed_attempts = i32::try_from(failures.failed_attempts).map_err(|err| {
+        CredentialsStoreError::OperationError {
+            context: "Failed login attempts are out of range".to_string(),
+            source: Box::new(err),
+        }
+    })?;
+    let locked_until = failures
+        .locked_until
+        .map(|locked_until| {
+            locked_until
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .map_err(|err| CredentialsStoreError::OperationError {
+                    context: "Lockout end is before the epoch".to_string(),
+                    source: Box::new(err),
+                })
+                .and_then(|secs| {
+                    i64::try_from(secs).map_err(|err| CredentialsStoreError::OperationError {
+                        context: "Lockout end is out of range".to_string(),
+                        source: Box::new(err),
+                    })
+                })
+        })
+        .transpose()?;
+
+    Ok(LoginFailuresModel {
+        user_id: failures.user_id,
+        failed_attempts,
+        locked_until,
+    })
+}
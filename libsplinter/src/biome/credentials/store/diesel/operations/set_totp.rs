@@ -0,0 +1,134 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::convert::TryFrom;
+
+use super::CredentialsStoreOperations;
+use crate::biome::credentials::store::diesel::{
+    models::{TotpModel, TotpRecoveryCodeModel},
+    schema::{user_totp, user_totp_recovery_codes},
+    CredentialsStoreError,
+};
+use crate::biome::credentials::store::TotpCredentials;
+use diesel::{
+    dsl::{delete, insert_into},
+    prelude::*,
+};
+
+pub(in crate::biome::credentials) trait CredentialsStoreSetTotpOperation {
+    fn set_totp(&self, totp: TotpCredentials) -> Result<(), CredentialsStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> CredentialsStoreSetTotpOperation
+    for CredentialsStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn set_totp(&self, totp: TotpCredentials) -> Result<(), CredentialsStoreError> {
+        let (totp, recovery_codes) = into_models(totp)?;
+
+        self.conn
+            .transaction::<_, diesel::result::Error, _>(|| {
+                delete(
+                    user_totp_recovery_codes::table
+                        .filter(user_totp_recovery_codes::user_id.eq(&totp.user_id)),
+                )
+                .execute(self.conn)?;
+                delete(user_totp::table.filter(user_totp::user_id.eq(&totp.user_id)))
+                    .execute(self.conn)?;
+
+                insert_into(user_totp::table)
+                    .values(&totp)
+                    .execute(self.conn)?;
+                if !recovery_codes.is_empty() {
+                    insert_into(user_totp_recovery_codes::table)
+                        .values(&recovery_codes)
+                        .execute(self.conn)?;
+                }
+
+                Ok(())
+            })
+            .map_err(|err| CredentialsStoreError::OperationError {
+                context: "Failed to set TOTP".to_string(),
+                source: Box::new(err),
+            })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> CredentialsStoreSetTotpOperation
+    for CredentialsStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn set_totp(&self, totp: TotpCredentials) -> Result<(), CredentialsStoreError> {
+        let (totp, recovery_codes) = into_models(totp)?;
+
+        self.conn
+            .transaction::<_, diesel::result::Error, _>(|| {
+                delete(
+                    user_totp_recovery_codes::table
+                        .filter(user_totp_recovery_codes::user_id.eq(&totp.user_id)),
+                )
+                .execute(self.conn)?;
+                delete(user_totp::table.filter(user_totp::user_id.eq(&totp.user_id)))
+                    .execute(self.conn)?;
+
+                insert_into(user_totp::table)
+                    .values(&totp)
+                    .execute(self.conn)?;
+                if !recovery_codes.is_empty() {
+                    insert_into(user_totp_recovery_codes::table)
+                        .values(&recovery_codes)
+                        .execute(self.conn)?;
+                }
+
+                Ok(())
+            })
+            .map_err(|err| CredentialsStoreError::OperationError {
+                context: "Failed to set TOTP".to_string(),
+                source: Box::new(err),
+            })
+    }
+}
+
+fn into_models(
+    totp: TotpCredentials,
+) -> Result<(TotpModel, Vec<TotpRecoveryCodeModel>), CredentialsStoreError> {
+    let last_used_step = totp
+        .last_used_step
+        .map(i64::try_from)
+        .transpose()
+        .map_err(|err| CredentialsStoreError::OperationError {
+            context: "TOTP time step is out of range".to_string(),
+            source: Box::new(err),
+        })?;
+
+    let user_id = &totp.user_id;
+    let recovery_codes = totp
+        .recovery_code_hashes
+        .iter()
+        .map(|code_hash| TotpRecoveryCodeModel {
+            user_id: user_id.clone(),
+            code_hash: code_hash.clone(),
+        })
+        .collect();
+
+    Ok((
+        TotpModel {
+            user_id: totp.user_id,
+            encrypted_secret: totp.encrypted_secret,
+            confirmed: totp.confirmed,
+            last_used_step,
+        },
+        recovery_codes,
+    ))
+}
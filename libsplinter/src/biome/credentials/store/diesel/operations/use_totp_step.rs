@@ -0,0 +1,83 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::convert::TryFrom;
+
+use super::CredentialsStoreOperations;
+use crate::biome::credentials::store::diesel::{schema::user_totp, CredentialsStoreError};
+use diesel::{dsl::update, prelude::*};
+
+pub(in crate::biome::credentials) trait CredentialsStoreUseTotpStepOperation {
+    fn use_totp_step(&self, user_id: &str, step: u64) -> Result<bool, CredentialsStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> CredentialsStoreUseTotpStepOperation
+    for CredentialsStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn use_totp_step(&self, user_id: &str, step: u64) -> Result<bool, CredentialsStoreError> {
+        let step = to_step_value(step)?;
+
+        let updated = update(
+            user_totp::table
+                .filter(user_totp::user_id.eq(user_id))
+                .filter(
+                    user_totp::last_used_step
+                        .is_null()
+                        .or(user_totp::last_used_step.lt(step)),
+                ),
+        )
+        .set(user_totp::last_used_step.eq(Some(step)))
+        .execute(self.conn)
+        .map_err(|err| CredentialsStoreError::OperationError {
+            context: "Failed to use TOTP time step".to_string(),
+            source: Box::new(err),
+        })?;
+
+        Ok(updated == 1)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> CredentialsStoreUseTotpStepOperation
+    for CredentialsStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn use_totp_step(&self, user_id: &str, step: u64) -> Result<bool, CredentialsStoreError> {
+        let step = to_step_value(step)?;
+
+        let updated = update(
+            user_totp::table
+                .filter(user_totp::user_id.eq(user_id))
+                .filter(
+                    user_totp::last_used_step
+                        .is_null()
+                        .or(user_totp::last_used_step.lt(step)),
+                ),
+        )
+        .set(user_totp::last_used_step.eq(Some(step)))
+        .execute(self.conn)
+        .map_err(|err| CredentialsStoreError::OperationError {
+            context: "Failed to use TOTP time step".to_string(),
+            source: Box::new(err),
+        })?;
+
+        Ok(updated == 1)
+    }
+}
+
+fn to_step_value(step: u64) -> Result<i64, CredentialsStoreError> {
+    i64::try_from(step).map_err(|err| CredentialsStoreError::OperationError {
+        context: "TOTP time step is out of range".to_string(),
+        source: Box::new(err),
+    })
+}
@@ -20,3 +20,35 @@ table! {
         password -> Text,
     }
 }
+
+table! {
+    user_totp (user_id) {
+        user_id -> Text,
+        encrypted_secret -> Text,
+        confirmed -> Bool,
+        last_used_step -> Nullable<Int8>,
+    }
+}
+
+table! {
+    user_totp_recovery_codes (user_id, code_hash) {
+        user_id -> Text,
+        code_hash -> Text,
+    }
+}
+
+table! {
+    password_reset_tokens (token_hash) {
+        token_hash -> Text,
+        user_id -> Text,
+        expires -> Int8,
+    }
+}
+
+table! {
+    user_login_failures (user_id) {
+        user_id -> Text,
+        failed_attempts -> Int4,
+        locked_until -> Nullable<Int8>,
+    }
+}
@@ -15,6 +15,12 @@
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
+#[cfg(feature = "biome-login-lockout")]
+use crate::biome::credentials::store::LoginFailures;
+#[cfg(feature = "biome-password-reset")]
+use crate::biome::credentials::store::PasswordResetToken;
+#[cfg(feature = "biome-totp")]
+use crate::biome::credentials::store::TotpCredentials;
 use crate::biome::credentials::store::{
     error::CredentialsStoreError, Credentials, CredentialsBuilder, CredentialsStore,
     PasswordEncryptionCost, UsernameId,
@@ -23,12 +29,25 @@ use crate::biome::credentials::store::{
 #[derive(Default, Clone)]
 pub struct MemoryCredentialsStore {
     inner: Arc<Mutex<HashMap<String, Credentials>>>,
+    #[cfg(feature = "biome-totp")]
+    totp: Arc<Mutex<HashMap<String, TotpCredentials>>>,
+    // Password reset tokens, keyed by token hash
+    #[cfg(feature = "biome-password-reset")]
+    password_reset_tokens: Arc<Mutex<HashMap<String, PasswordResetToken>>>,
+    #[cfg(feature = "biome-login-lockout")]
+    login_failures: Arc<Mutex<HashMap<String, LoginFailures>>>,
 }
 
 impl MemoryCredentialsStore {
     pub fn new() -> Self {
         MemoryCredentialsStore {
             inner: Arc::new(Mutex::new(HashMap::new())),
+            #[cfg(feature = "biome-totp")]
+            totp: Arc::new(Mutex::new(HashMap::new())),
+            #[cfg(feature = "biome-password-reset")]
+            password_reset_tokens: Arc::new(Mutex::new(HashMap::new())),
+            #[cfg(feature = "biome-login-lockout")]
+            login_failures: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 }
@@ -90,6 +109,30 @@ impl CredentialsStore for MemoryCredentialsStore {
                 source: None,
             })?;
         if inner.remove(user_id).is_some() {
+            #[cfg(feature = "biome-totp")]
+            self.totp
+                .lock()
+                .map_err(|_| CredentialsStoreError::StorageError {
+                    context: "Cannot access TOTP credentials: mutex lock poisoned".to_string(),
+                    source: None,
+                })?
+                .remove(user_id);
+            #[cfg(feature = "biome-password-reset")]
+            self.password_reset_tokens
+                .lock()
+                .map_err(|_| CredentialsStoreError::StorageError {
+                    context: "Cannot access password reset tokens: mutex lock poisoned".to_string(),
+                    source: None,
+                })?
+                .retain(|_, token| token.user_id != user_id);
+            #[cfg(feature = "biome-login-lockout")]
+            self.login_failures
+                .lock()
+                .map_err(|_| CredentialsStoreError::StorageError {
+                    context: "Cannot access login failures: mutex lock poisoned".to_string(),
+                    source: None,
+                })?
+                .remove(user_id);
             Ok(())
         } else {
             Err(CredentialsStoreError::NotFoundError(format!(
@@ -180,4 +223,201 @@ impl CredentialsStore for MemoryCredentialsStore {
             })
             .collect())
     }
+
+    #[cfg(feature = "biome-totp")]
+    fn set_totp(&self, totp: TotpCredentials) -> Result<(), CredentialsStoreError> {
+        let mut inner = self
+            .totp
+            .lock()
+            .map_err(|_| CredentialsStoreError::StorageError {
+                context: "Cannot access TOTP credentials: mutex lock poisoned".to_string(),
+                source: None,
+            })?;
+        inner.insert(totp.user_id.clone(), totp);
+        Ok(())
+    }
+
+    #[cfg(feature = "biome-totp")]
+    fn fetch_totp(&self, user_id: &str) -> Result<TotpCredentials, CredentialsStoreError> {
+        let inner = self
+            .totp
+            .lock()
+            .map_err(|_| CredentialsStoreError::StorageError {
+                context: "Cannot access TOTP credentials: mutex lock poisoned".to_string(),
+                source: None,
+            })?;
+        inner.get(user_id).cloned().ok_or_else(|| {
+            CredentialsStoreError::NotFoundError(format!(
+                "TOTP for user with id {} not found.",
+                user_id
+            ))
+        })
+    }
+
+    #[cfg(feature = "biome-totp")]
+    fn remove_totp(&self, user_id: &str) -> Result<(), CredentialsStoreError> {
+        let mut inner = self
+            .totp
+            .lock()
+            .map_err(|_| CredentialsStoreError::StorageError {
+                context: "Cannot access TOTP credentials: mutex lock poisoned".to_string(),
+                source: None,
+            })?;
+        if inner.remove(user_id).is_some() {
+            Ok(())
+        } else {
+            Err(CredentialsStoreError::NotFoundError(format!(
+                "TOTP for user with id {} not found.",
+                user_id
+            )))
+        }
+    }
+
+    #[cfg(feature = "biome-totp")]
+    fn use_totp_step(&self, user_id: &str, step: u64) -> Result<bool, CredentialsStoreError> {
+        let mut inner = self
+            .totp
+            .lock()
+            .map_err(|_| CredentialsStoreError::StorageError {
+                context: "Cannot access TOTP credentials: mutex lock poisoned".to_string(),
+                source: None,
+            })?;
+        match inner.get_mut(user_id) {
+            Some(totp) if totp.last_used_step.map_or(true, |last| last < step) => {
+                totp.last_used_step = Some(step);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    #[cfg(feature = "biome-totp")]
+    fn use_recovery_code(
+        &self,
+        user_id: &str,
+        code_hash: &str,
+    ) -> Result<bool, CredentialsStoreError> {
+        let mut inner = self
+            .totp
+            .lock()
+            .map_err(|_| CredentialsStoreError::StorageError {
+                context: "Cannot access TOTP credentials: mutex lock poisoned".to_string(),
+                source: None,
+            })?;
+        let totp = match inner.get_mut(user_id) {
+            Some(totp) => totp,
+            None => return Ok(false),
+        };
+        match totp
+            .recovery_code_hashes
+            .iter()
+            .position(|hash| hash == code_hash)
+        {
+            Some(index) => {
+                totp.recovery_code_hashes.remove(index);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    #[cfg(feature = "biome-password-reset")]
+    fn add_password_reset_token(
+        &self,
+        token: PasswordResetToken,
+    ) -> Result<(), CredentialsStoreError> {
+        let mut inner =
+            self.password_reset_tokens
+                .lock()
+                .map_err(|_| CredentialsStoreError::StorageError {
+                    context: "Cannot access password reset tokens: mutex lock poisoned".to_string(),
+                    source: None,
+                })?;
+        inner.insert(token.token_hash.clone(), token);
+        Ok(())
+    }
+
+    #[cfg(feature = "biome-password-reset")]
+    fn take_password_reset_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<PasswordResetToken, CredentialsStoreError> {
+        let mut inner =
+            self.password_reset_tokens
+                .lock()
+                .map_err(|_| CredentialsStoreError::StorageError {
+                    context: "Cannot access password reset tokens: mutex lock poisoned".to_string(),
+                    source: None,
+                })?;
+        let token = inner.remove(token_hash).ok_or_else(|| {
+            CredentialsStoreError::NotFoundError("Password reset token not found.".to_string())
+        })?;
+        inner.retain(|_, other| other.user_id != token.user_id);
+        Ok(token)
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    fn fetch_login_failures(&self, user_id: &str) -> Result<LoginFailures, CredentialsStoreError> {
+        let inner =
+            self.login_failures
+                .lock()
+                .map_err(|_| CredentialsStoreError::StorageError {
+                    context: "Cannot access login failures: mutex lock poisoned".to_string(),
+                    source: None,
+                })?;
+        Ok(inner
+            .get(user_id)
+            .cloned()
+            .unwrap_or_else(|| LoginFailures {
+                user_id: user_id.to_string(),
+                failed_attempts: 0,
+                locked_until: None,
+            }))
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    fn set_login_failures(&self, failures: LoginFailures) -> Result<(), CredentialsStoreError> {
+        let mut inner =
+            self.login_failures
+                .lock()
+                .map_err(|_| CredentialsStoreError::StorageError {
+                    context: "Cannot access login failures: mutex lock poisoned".to_string(),
+                    source: None,
+                })?;
+        inner.insert(failures.user_id.clone(), failures);
+        Ok(())
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    fn add_failed_login(&self, user_id: &str) -> Result<u32, CredentialsStoreError> {
+        let mut inner =
+            self.login_failures
+                .lock()
+                .map_err(|_| CredentialsStoreError::StorageError {
+                    context: "Cannot access login failures: mutex lock poisoned".to_string(),
+                    source: None,
+                })?;
+        let failures = inner
+            .entry(user_id.to_string())
+            .or_insert_with(|| LoginFailures {
+                user_id: user_id.to_string(),
+                failed_attempts: 0,
+                locked_until: None,
+            });
+        failures.failed_attempts += 1;
+        Ok(failures.failed_attempts)
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    fn remove_login_failures(&self, user_id: &str) -> Result<(), CredentialsStoreError> {
+        let mut inner =
+            self.login_failures
+                .lock()
+                .map_err(|_| CredentialsStoreError::StorageError {
+                    context: "Cannot access login failures: mutex lock poisoned".to_string(),
+                    source: None,
+                })?;
+        inner.remove(user_id);
+        Ok(())
+    }
 }
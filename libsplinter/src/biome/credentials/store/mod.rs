@@ -18,6 +18,8 @@
 pub(in crate::biome) mod diesel;
 pub(in crate::biome) mod memory;
 use std::str::FromStr;
+#[cfg(any(feature = "biome-login-lockout", feature = "biome-password-reset"))]
+use std::time::SystemTime;
 mod error;
 
 pub use error::CredentialsStoreError;
@@ -51,6 +53,48 @@ impl Credentials {
     }
 }
 
+/// Represents a user's TOTP second factor
+///
+/// The TOTP secret is stored encrypted; it is encrypted and decrypted by the REST API, so the
+/// store never sees the plain secret.
+#[cfg(feature = "biome-totp")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TotpCredentials {
+    pub user_id: String,
+    /// The encrypted TOTP secret
+    pub encrypted_secret: String,
+    /// Whether or not the user has confirmed enrollment by providing a valid code; TOTP is only
+    /// required at login once enrollment is confirmed
+    pub confirmed: bool,
+    /// The time step of the last code that was accepted, used to prevent a code from being
+    /// used twice
+    pub last_used_step: Option<u64>,
+    /// The hashes of the user's unused recovery codes
+    pub recovery_code_hashes: Vec<String>,
+}
+
+/// Represents a token that allows a user to set a new password without knowing their current one
+#[cfg(feature = "biome-password-reset")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PasswordResetToken {
+    pub user_id: String,
+    /// The hash of the token; the token itself is never stored
+    pub token_hash: String,
+    /// The time after which the token can no longer be used
+    pub expires: SystemTime,
+}
+
+/// Represents a user's recent failed login attempts
+#[cfg(feature = "biome-login-lockout")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct LoginFailures {
+    pub user_id: String,
+    /// The number of failed login attempts since the last successful login or lockout
+    pub failed_attempts: u32,
+    /// If the user is locked out, the time at which the lockout ends
+    pub locked_until: Option<SystemTime>,
+}
+
 /// Represents a user's username
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 pub struct UsernameId {
@@ -236,6 +280,167 @@ pub trait CredentialsStore: Send + Sync {
     ///
     /// Returns a CredentialsStoreError if implementation cannot fetch the user IDs
     fn list_usernames(&self) -> Result<Vec<UsernameId>, CredentialsStoreError>;
+
+    /// Sets the TOTP second factor for a user, replacing any existing one
+    ///
+    /// # Arguments
+    ///
+    ///  * `totp` - The TOTP second factor to be set
+    ///
+    /// # Errors
+    ///
+    /// Returns a CredentialsStoreError if the implementation cannot set the TOTP second factor
+    #[cfg(feature = "biome-totp")]
+    fn set_totp(&self, totp: TotpCredentials) -> Result<(), CredentialsStoreError>;
+
+    /// Fetches the TOTP second factor for a user
+    ///
+    /// # Arguments
+    ///
+    ///  * `user_id` - The unique identifier of the user the TOTP second factor belongs to
+    ///
+    /// # Errors
+    ///
+    /// Returns a CredentialsStoreError if the implementation cannot fetch the TOTP second factor
+    /// or if the user does not have one
+    #[cfg(feature = "biome-totp")]
+    fn fetch_totp(&self, user_id: &str) -> Result<TotpCredentials, CredentialsStoreError>;
+
+    /// Removes the TOTP second factor for a user
+    ///
+    /// # Arguments
+    ///
+    ///  * `user_id` - The unique identifier of the user the TOTP second factor belongs to
+    ///
+    /// # Errors
+    ///
+    /// Returns a CredentialsStoreError if the implementation cannot remove the TOTP second factor
+    /// or if the user does not have one
+    #[cfg(feature = "biome-totp")]
+    fn remove_totp(&self, user_id: &str) -> Result<(), CredentialsStoreError>;
+
+    /// Records that a TOTP code from the given time step has been used by a user
+    ///
+    /// The step is only recorded if no code from the same or a later time step has been used, so
+    /// that each code can be used at most once even when several requests race.
+    ///
+    /// # Arguments
+    ///
+    ///  * `user_id` - The unique identifier of the user the TOTP second factor belongs to
+    ///  * `step` - The time step of the code that was verified
+    ///
+    /// Returns `true` if the step was recorded, `false` if it had already been used
+    ///
+    /// # Errors
+    ///
+    /// Returns a CredentialsStoreError if the implementation cannot record the time step
+    #[cfg(feature = "biome-totp")]
+    fn use_totp_step(&self, user_id: &str, step: u64) -> Result<bool, CredentialsStoreError>;
+
+    /// Removes a TOTP recovery code of a user, consuming it
+    ///
+    /// # Arguments
+    ///
+    ///  * `user_id` - The unique identifier of the user the recovery code belongs to
+    ///  * `code_hash` - The hash of the recovery code to be used
+    ///
+    /// Returns `true` if the recovery code was removed, `false` if it did not exist or had
+    /// already been used
+    ///
+    /// # Errors
+    ///
+    /// Returns a CredentialsStoreError if the implementation cannot remove the recovery code
+    #[cfg(feature = "biome-totp")]
+    fn use_recovery_code(
+        &self,
+        user_id: &str,
+        code_hash: &str,
+    ) -> Result<bool, CredentialsStoreError>;
+
+    /// Adds a password reset token to the underlying storage
+    ///
+    /// # Arguments
+    ///
+    ///  * `token` - The password reset token to be added
+    ///
+    /// # Errors
+    ///
+    /// Returns a CredentialsStoreError if the implementation cannot add the token
+    #[cfg(feature = "biome-password-reset")]
+    fn add_password_reset_token(
+        &self,
+        token: PasswordResetToken,
+    ) -> Result<(), CredentialsStoreError>;
+
+    /// Removes and returns the password reset token with the given hash. All other password reset
+    /// tokens of the same user are removed as well, so only one of them can ever be used.
+    ///
+    /// The token is returned even if it has expired; checking the expiry is up to the caller.
+    ///
+    /// # Arguments
+    ///
+    ///  * `token_hash` - The hash of the password reset token
+    ///
+    /// # Errors
+    ///
+    /// Returns a CredentialsStoreError if the implementation cannot remove the token or if the
+    /// token does not exist
+    #[cfg(feature = "biome-password-reset")]
+    fn take_password_reset_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<PasswordResetToken, CredentialsStoreError>;
+
+    /// Fetches a user's recent failed login attempts. A user without any failed login attempts
+    /// has a failure count of zero and is not locked out.
+    ///
+    /// # Arguments
+    ///
+    ///  * `user_id` - The unique identifier of the user
+    ///
+    /// # Errors
+    ///
+    /// Returns a CredentialsStoreError if the implementation cannot fetch the failed attempts
+    #[cfg(feature = "biome-login-lockout")]
+    fn fetch_login_failures(&self, user_id: &str) -> Result<LoginFailures, CredentialsStoreError>;
+
+    /// Sets a user's recent failed login attempts, replacing any existing ones
+    ///
+    /// # Arguments
+    ///
+    ///  * `failures` - The failed login attempts to be set
+    ///
+    /// # Errors
+    ///
+    /// Returns a CredentialsStoreError if the implementation cannot set the failed attempts
+    #[cfg(feature = "biome-login-lockout")]
+    fn set_login_failures(&self, failures: LoginFailures) -> Result<(), CredentialsStoreError>;
+
+    /// Atomically adds a failed login attempt for a user
+    ///
+    /// # Arguments
+    ///
+    ///  * `user_id` - The unique identifier of the user
+    ///
+    /// Returns the number of failed login attempts including the new one
+    ///
+    /// # Errors
+    ///
+    /// Returns a CredentialsStoreError if the implementation cannot add the failed attempt
+    #[cfg(feature = "biome-login-lockout")]
+    fn add_failed_login(&self, user_id: &str) -> Result<u32, CredentialsStoreError>;
+
+    /// Removes a user's recent failed login attempts, ending any lockout
+    ///
+    /// # Arguments
+    ///
+    ///  * `user_id` - The unique identifier of the user
+    ///
+    /// # Errors
+    ///
+    /// Returns a CredentialsStoreError if the implementation cannot remove the failed attempts
+    #[cfg(feature = "biome-login-lockout")]
+    fn remove_login_failures(&self, user_id: &str) -> Result<(), CredentialsStoreError>;
 }
 
 impl<CS> CredentialsStore for Box<CS>
@@ -286,6 +491,71 @@ where
     fn list_usernames(&self) -> Result<Vec<UsernameId>, CredentialsStoreError> {
         (**self).list_usernames()
     }
+
+    #[cfg(feature = "biome-totp")]
+    fn set_totp(&self, totp: TotpCredentials) -> Result<(), CredentialsStoreError> {
+        (**self).set_totp(totp)
+    }
+
+    #[cfg(feature = "biome-totp")]
+    fn fetch_totp(&self, user_id: &str) -> Result<TotpCredentials, CredentialsStoreError> {
+        (**self).fetch_totp(user_id)
+    }
+
+    #[cfg(feature = "biome-totp")]
+    fn remove_totp(&self, user_id: &str) -> Result<(), CredentialsStoreError> {
+        (**self).remove_totp(user_id)
+    }
+
+    #[cfg(feature = "biome-totp")]
+    fn use_totp_step(&self, user_id: &str, step: u64) -> Result<bool, CredentialsStoreError> {
+        (**self).use_totp_step(user_id, step)
+    }
+
+    #[cfg(feature = "biome-totp")]
+    fn use_recovery_code(
+        &self,
+        user_id: &str,
+        code_hash: &str,
+    ) -> Result<bool, CredentialsStoreError> {
+        (**self).use_recovery_code(user_id, code_hash)
+    }
+
+    #[cfg(feature = "biome-password-reset")]
+    fn add_password_reset_token(
+        &self,
+        token: PasswordResetToken,
+    ) -> Result<(), CredentialsStoreError> {
+        (**self).add_password_reset_token(token)
+    }
+
+    #[cfg(feature = "biome-password-reset")]
+    fn take_password_reset_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<PasswordResetToken, CredentialsStoreError> {
+        (**self).take_password_reset_token(token_hash)
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    fn fetch_login_failures(&self, user_id: &str) -> Result<LoginFailures, CredentialsStoreError> {
+        (**self).fetch_login_failures(user_id)
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    fn set_login_failures(&self, failures: LoginFailures) -> Result<(), CredentialsStoreError> {
+        (**self).set_login_failures(failures)
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    fn add_failed_login(&self, user_id: &str) -> Result<u32, CredentialsStoreError> {
+        (**self).add_failed_login(user_id)
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    fn remove_login_failures(&self, user_id: &str) -> Result<(), CredentialsStoreError> {
+        (**self).remove_login_failures(user_id)
+    }
 }
 
 #[cfg(feature = "diesel")]
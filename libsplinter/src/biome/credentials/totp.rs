@@ -0,0 +1,385 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Time-based one-time passwords (TOTP), as defined by RFC 6238, used as a second factor for
+//! Biome credentials.
+//!
+//! Codes are six digits long, use HMAC-SHA1, and change every 30 seconds, which is what common
+//! authenticator apps expect from the provisioning URI.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::rand::rand_bytes;
+use openssl::sha::sha256;
+use openssl::sign::Signer;
+use openssl::symm::{decrypt_aead, encrypt_aead, Cipher};
+use rand::Rng;
+
+use crate::error::InternalError;
+use crate::hex::{parse_hex, to_hex};
+
+/// Length of a generated secret in bytes, as recommended by RFC 4226
+const SECRET_LENGTH: usize = 20;
+/// Length of a time step in seconds
+const TIME_STEP: u64 = 30;
+/// Number of digits in a code
+const DIGITS: u32 = 6;
+/// Number of time steps before or after the current one for which a code is still accepted, to
+/// allow for clock drift
+const ALLOWED_DRIFT: u64 = 1;
+
+const BASE32_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+const RECOVERY_CODE_COUNT: usize = 10;
+/// Number of characters in each half of a recovery code
+const RECOVERY_CODE_HALF_LENGTH: usize = 5;
+/// Characters used in recovery codes; easily confused characters are left out
+const RECOVERY_CODE_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+const NONCE_LENGTH: usize = 12;
+const TAG_LENGTH: usize = 16;
+
+/// Generates a new random TOTP secret
+pub fn generate_secret() -> Result<Vec<u8>, InternalError> {
+    let mut secret = vec![0; SECRET_LENGTH];
+    rand_bytes(&mut secret).map_err(|err| InternalError::from_source(Box::new(err)))?;
+    Ok(secret)
+}
+
+/// Encodes a secret as unpadded base32, the form in which authenticator apps accept secrets
+pub fn encode_base32(bytes: &[u8]) -> String {
+    let mut encoded = String::new();
+    let mut buffer: u16 = 0;
+    let mut bits = 0;
+    for byte in bytes {
+        buffer = (buffer << 8) | u16::from(*byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(BASE32_ALPHABET[usize::from((buffer >> bits) & 0x1f)] as char);
+        }
+    }
+    if bits > 0 {
+        encoded.push(BASE32_ALPHABET[usize::from((buffer << (5 - bits)) & 0x1f)] as char);
+    }
+    encoded
+}
+
+/// Returns the `otpauth://` URI for a secret. Clients render this URI as a QR code to be scanned
+/// by an authenticator app.
+///
+/// # Arguments
+///
+/// * `issuer` - The name of the service the codes are for
+/// * `account` - The name of the user's account, usually their username
+/// * `secret` - The TOTP secret
+pub fn provisioning_uri(issuer: &str, account: &str, secret: &[u8]) -> String {
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        percent_encode(issuer),
+        percent_encode(account),
+        encode_base32(secret),
+        percent_encode(issuer),
+        DIGITS,
+        TIME_STEP,
+    )
+}
+
+/// Generates the code for a secret at the given time
+pub fn generate_code(secret: &[u8], time: SystemTime) -> Result<String, InternalError> {
+    Ok(format!(
+        "{:0width$}",
+        code_for_step(secret, time_step(time)?)?,
+        width = DIGITS as usize
+    ))
+}
+
+/// Verifies a code for a secret at the given time, allowing for a small amount of clock drift.
+///
+/// Returns the time step the code is valid for, or `None` if the code is not valid. To prevent a
+/// code from being used twice, callers should reject codes whose time step is not later than
+/// the last one accepted for the user.
+pub fn verify_code(
+    secret: &[u8],
+    code: &str,
+    time: SystemTime,
+) -> Result<Option<u64>, InternalError> {
+    let code = code.trim();
+    if code.len() != DIGITS as usize || !code.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Ok(None);
+    }
+    let code = code
+        .parse::<u32>()
+        .map_err(|err| InternalError::from_source(Box::new(err)))?;
+
+    let current_step = time_step(time)?;
+    for step in current_step.saturating_sub(ALLOWED_DRIFT)..=current_step + ALLOWED_DRIFT {
+        if code_for_step(secret, step)? == code {
+            return Ok(Some(step));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Generates a new set of single-use recovery codes, which may be used in place of a TOTP code
+/// if the user loses access to their authenticator app
+pub fn generate_recovery_codes() -> Vec<String> {
+    let mut rng = rand::thread_rng();
+    let mut random_half = || {
+        (0..RECOVERY_CODE_HALF_LENGTH)
+            .map(|_| RECOVERY_CODE_ALPHABET[rng.gen_range(0, RECOVERY_CODE_ALPHABET.len())] as char)
+            .collect::<String>()
+    };
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| format!("{}-{}", random_half(), random_half()))
+        .collect()
+}
+
+/// Returns the hash of a recovery code, which is what gets stored. Recovery codes are random, so
+/// a single round of SHA-256 is sufficient. Case, whitespace, and dashes are ignored.
+pub fn hash_recovery_code(code: &str) -> String {
+    let normalized = code
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect::<String>()
+        .to_lowercase();
+    to_hex(&sha256(normalized.as_bytes()))
+}
+
+/// Encrypts and decrypts TOTP secrets for storage
+///
+/// Secrets are encrypted with AES-256-GCM, using a key derived from a node-level secret. The ID
+/// of the user is authenticated along with the secret, so an encrypted secret cannot be moved to
+/// another user.
+pub struct TotpSecretCipher {
+    key: [u8; 32],
+}
+
+impl TotpSecretCipher {
+    /// Creates a new cipher from a node-level secret
+    pub fn new(secret: &str) -> Self {
+        TotpSecretCipher {
+            key: sha256(secret.as_bytes()),
+        }
+    }
+
+    /// Encrypts a user's TOTP secret, returning it hex-encoded
+    pub fn encrypt(&self, user_id: &str, secret: &[u8]) -> Result<String, InternalError> {
+        let mut nonce = [0; NONCE_LENGTH];
+        rand_bytes(&mut nonce).map_err(|err| InternalError::from_source(Box::new(err)))?;
+
+        let mut tag = [0; TAG_LENGTH];
+        let ciphertext = encrypt_aead(
+            Cipher::aes_256_gcm(),
+            &self.key,
+            Some(&nonce),
+            user_id.as_bytes(),
+            secret,
+            &mut tag,
+        )
+        .map_err(|err| InternalError::from_source(Box::new(err)))?;
+
+        let mut encrypted = nonce.to_vec();
+        encrypted.extend(ciphertext);
+        encrypted.extend_from_slice(&tag);
+        Ok(to_hex(&encrypted))
+    }
+
+    /// Decrypts a user's TOTP secret that was encrypted with `encrypt`
+    pub fn decrypt(&self, user_id: &str, encrypted: &str) -> Result<Vec<u8>, InternalError> {
+        let encrypted =
+            parse_hex(encrypted).map_err(|err| InternalError::from_source(Box::new(err)))?;
+        if encrypted.len() < NONCE_LENGTH + TAG_LENGTH {
+            return Err(InternalError::with_message(
+                "Encrypted TOTP secret is too short".to_string(),
+            ));
+        }
+        let (nonce, rest) = encrypted.split_at(NONCE_LENGTH);
+        let (ciphertext, tag) = rest.split_at(rest.len() - TAG_LENGTH);
+
+        decrypt_aead(
+            Cipher::aes_256_gcm(),
+            &self.key,
+            Some(nonce),
+            user_id.as_bytes(),
+            ciphertext,
+            tag,
+        )
+        .map_err(|err| {
+            InternalError::from_source_with_message(
+                Box::new(err),
+                "Failed to decrypt TOTP secret".to_string(),
+            )
+        })
+    }
+}
+
+fn time_step(time: SystemTime) -> Result<u64, InternalError> {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() / TIME_STEP)
+        .map_err(|err| InternalError::from_source(Box::new(err)))
+}
+
+/// Computes the HOTP value, as defined by RFC 4226, of a secret for a time step
+fn code_for_step(secret: &[u8], step: u64) -> Result<u32, InternalError> {
+    let key = PKey::hmac(secret).map_err(|err| InternalError::from_source(Box::new(err)))?;
+    let mut signer = Signer::new(MessageDigest::sha1(), &key)
+        .map_err(|err| InternalError::from_source(Box::new(err)))?;
+    signer
+        .update(&step.to_be_bytes())
+        .map_err(|err| InternalError::from_source(Box::new(err)))?;
+    let hmac = signer
+        .sign_to_vec()
+        .map_err(|err| InternalError::from_source(Box::new(err)))?;
+
+    // Dynamic truncation
+    let offset = usize::from(hmac[hmac.len() - 1] & 0x0f);
+    let binary = u32::from_be_bytes([
+        hmac[offset] & 0x7f,
+        hmac[offset + 1],
+        hmac[offset + 2],
+        hmac[offset + 3],
+    ]);
+
+    Ok(binary % 10u32.pow(DIGITS))
+}
+
+/// Percent-encodes everything but unreserved characters, as required for the parts of an
+/// `otpauth://` URI
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    /// The SHA1 secret used by the test vectors in RFC 6238
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    /// Verify that generated codes match the SHA1 test vectors of RFC 6238, truncated to six
+    /// digits.
+    #[test]
+    fn rfc_6238_test_vectors() {
+        for (time, code) in &[
+            (59, "287082"),
+            (1_111_111_109, "081804"),
+            (1_234_567_890, "005924"),
+            (2_000_000_000, "279037"),
+        ] {
+            assert_eq!(
+                &generate_code(RFC_SECRET, UNIX_EPOCH + Duration::from_secs(*time))
+                    .expect("Failed to generate code"),
+                code
+            );
+        }
+    }
+
+    /// Verify that a code is accepted in its own time step and the adjacent ones, and that
+    /// `verify_code` returns the time step the code belongs to.
+    #[test]
+    fn verify_code_with_drift() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_111_111_109);
+        let step = 1_111_111_109 / TIME_STEP;
+
+        assert_eq!(
+            verify_code(RFC_SECRET, "081804", time).expect("Failed to verify code"),
+            Some(step)
+        );
+        assert_eq!(
+            verify_code(RFC_SECRET, "081804", time + Duration::from_secs(TIME_STEP))
+                .expect("Failed to verify code"),
+            Some(step)
+        );
+        assert_eq!(
+            verify_code(
+                RFC_SECRET,
+                "081804",
+                time + Duration::from_secs(3 * TIME_STEP)
+            )
+            .expect("Failed to verify code"),
+            None
+        );
+        assert_eq!(
+            verify_code(RFC_SECRET, "not a code", time).expect("Failed to verify code"),
+            None
+        );
+    }
+
+    /// Verify that secrets are encoded as base32 and included in the provisioning URI.
+    #[test]
+    fn provisioning() {
+        assert_eq!(encode_base32(b"foobar"), "MZXW6YTBOI");
+        assert_eq!(
+            provisioning_uri("Splinter", "alice@acme.com", b"foobar"),
+            "otpauth://totp/Splinter:alice%40acme.com?secret=MZXW6YTBOI&issuer=Splinter\
+             &algorithm=SHA1&digits=6&period=30"
+        );
+    }
+
+    /// Verify that an encrypted secret can only be decrypted with the same node-level secret and
+    /// for the same user.
+    #[test]
+    fn encrypt_and_decrypt_secret() {
+        let cipher = TotpSecretCipher::new("node secret");
+        let secret = generate_secret().expect("Failed to generate secret");
+
+        let encrypted = cipher
+            .encrypt("alice", &secret)
+            .expect("Failed to encrypt secret");
+        assert_eq!(
+            cipher
+                .decrypt("alice", &encrypted)
+                .expect("Failed to decrypt secret"),
+            secret
+        );
+        assert!(cipher.decrypt("bob", &encrypted).is_err());
+        assert!(TotpSecretCipher::new("other secret")
+            .decrypt("alice", &encrypted)
+            .is_err());
+    }
+
+    /// Verify that recovery codes are unique, and that their hashes ignore formatting.
+    #[test]
+    fn recovery_codes() {
+        let codes = generate_recovery_codes();
+        assert_eq!(codes.len(), RECOVERY_CODE_COUNT);
+        let mut unique = codes.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), RECOVERY_CODE_COUNT);
+
+        assert_eq!(
+            hash_recovery_code("abcde-fghjk"),
+            hash_recovery_code(" ABCDEFGHJK ")
+        );
+        assert_ne!(
+            hash_recovery_code("abcde-fghjk"),
+            hash_recovery_code("abcde-fghjm")
+        );
+    }
+}
@@ -17,6 +17,8 @@
 #[cfg(feature = "rest-api")]
 pub mod rest_api;
 pub mod store;
+#[cfg(feature = "biome-key-wrapping")]
+pub mod wrapping;
 
 #[cfg(feature = "diesel")]
 use store::diesel::models::KeyModel;
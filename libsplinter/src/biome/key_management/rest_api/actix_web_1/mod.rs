@@ -13,10 +13,18 @@
 // limitations under the License.
 
 mod key_management;
+#[cfg(feature = "biome-key-wrapping")]
+mod wrapped_keys;
 
 use std::sync::Arc;
 
+#[cfg(all(feature = "biome-key-wrapping", feature = "biome-login-lockout"))]
+use crate::biome::credentials::rest_api::BiomeCredentialsRestConfig;
+#[cfg(feature = "biome-key-wrapping")]
+use crate::biome::credentials::store::CredentialsStore;
 use crate::biome::key_management::store::KeyStore;
+#[cfg(feature = "biome-key-wrapping")]
+use crate::biome::key_management::wrapping::KeyWrapper;
 use crate::rest_api::{Resource, RestResourceProvider};
 
 /// Provides the following REST API endpoints for Biome key management:
@@ -28,22 +36,95 @@ use crate::rest_api::{Resource, RestResourceProvider};
 ///   `public_key`
 /// * `DELETE /biome/keys/{public_key}` - Delete the authorized user's key that corresponds to
 ///   `public key`
+///
+/// If key wrapping is enabled with `with_key_wrapping`, the following endpoints are provided as
+/// well:
+///
+/// * `POST /biome/wrapped_keys` - Generate a new key for the authorized user, whose private key
+///   is wrapped by the server
+/// * `POST /biome/wrapped_keys/{public_key}/sign` - Sign a circuit management payload header with
+///   the authorized user's wrapped key that corresponds to `public_key`
+/// * `GET /biome/wrapped_keys/{public_key}/usage` - List the recorded uses of the authorized
+///   user's wrapped key that corresponds to `public_key`
 pub struct BiomeKeyManagementRestResourceProvider {
     key_store: Arc<dyn KeyStore>,
+    #[cfg(feature = "biome-key-wrapping")]
+    key_wrapping: Option<KeyWrapping>,
+}
+
+/// The parts needed to provide the wrapped key endpoints
+#[cfg(feature = "biome-key-wrapping")]
+struct KeyWrapping {
+    key_wrapper: Arc<dyn KeyWrapper>,
+    credentials_store: Arc<dyn CredentialsStore>,
+    #[cfg(feature = "biome-login-lockout")]
+    credentials_config: Arc<BiomeCredentialsRestConfig>,
 }
 
 impl BiomeKeyManagementRestResourceProvider {
     pub fn new(key_store: Arc<dyn KeyStore>) -> Self {
-        Self { key_store }
+        Self {
+            key_store,
+            #[cfg(feature = "biome-key-wrapping")]
+            key_wrapping: None,
+        }
+    }
+
+    /// Enables keys that are wrapped by the server, which can be used to sign on the user's
+    /// behalf
+    ///
+    /// # Arguments
+    ///
+    /// * `key_wrapper` - Wraps and unwraps the private keys
+    /// * `credentials_store` - Used to verify the user's password before a key is used
+    /// * `credentials_config` - The Biome credentials configuration; a wrong password counts as
+    ///   a failed login, so that the sign endpoint cannot be used to guess passwords
+    #[cfg(feature = "biome-key-wrapping")]
+    pub fn with_key_wrapping(
+        mut self,
+        key_wrapper: Arc<dyn KeyWrapper>,
+        credentials_store: Arc<dyn CredentialsStore>,
+        #[cfg(feature = "biome-login-lockout")] credentials_config: Arc<BiomeCredentialsRestConfig>,
+    ) -> Self {
+        self.key_wrapping = Some(KeyWrapping {
+            key_wrapper,
+            credentials_store,
+            #[cfg(feature = "biome-login-lockout")]
+            credentials_config,
+        });
+        self
     }
 }
 
 impl RestResourceProvider for BiomeKeyManagementRestResourceProvider {
     fn resources(&self) -> Vec<Resource> {
-        vec![
+        #[allow(unused_mut)]
+        let mut resources = vec![
             key_management::make_key_management_route(self.key_store.clone()),
             key_management::make_key_management_route_with_public_key(self.key_store.clone()),
-        ]
+        ];
+
+        #[cfg(feature = "biome-key-wrapping")]
+        {
+            if let Some(key_wrapping) = &self.key_wrapping {
+                resources.push(wrapped_keys::make_wrapped_keys_route(
+                    self.key_store.clone(),
+                    key_wrapping.key_wrapper.clone(),
+                ));
+                resources.push(wrapped_keys::make_wrapped_key_sign_route(
+                    self.key_store.clone(),
+                    key_wrapping.key_wrapper.clone(),
+                    key_wrapping.credentials_store.clone(),
+                    #[cfg(feature = "biome-login-lockout")]
+                    key_wrapping.credentials_config.clone(),
+                ));
+                resources.push(wrapped_keys::make_wrapped_key_usage_route(
+                    self.key_store.clone(),
+                ));
+            }
+        }
+
+        resources
     }
 }
 
@@ -54,8 +135,14 @@ mod tests {
 
     use std::{panic, thread};
 
+    #[cfg(feature = "biome-key-wrapping")]
+    use cylinder::{secp256k1::Secp256k1Context, Context, PublicKey, Signature};
+    #[cfg(feature = "biome-key-wrapping")]
+    use protobuf::Message;
     use reqwest::blocking::Client;
 
+    #[cfg(feature = "biome-key-wrapping")]
+    use crate::biome::key_management::wrapping::LocalKeyWrapper;
     use crate::biome::{
         credentials::rest_api::{
             BiomeCredentialsRestConfigBuilder, BiomeCredentialsRestResourceProviderBuilder,
@@ -64,6 +151,10 @@ mod tests {
     };
     #[cfg(feature = "authorization")]
     use crate::error::InternalError;
+    #[cfg(feature = "biome-key-wrapping")]
+    use crate::hex::{parse_hex, to_hex};
+    #[cfg(feature = "biome-key-wrapping")]
+    use crate::protos::admin::{CircuitManagementPayload_Action, CircuitManagementPayload_Header};
     use crate::rest_api::actix_web_1::{AuthConfig, RestApiBuilder, RestApiShutdownHandle};
     #[cfg(feature = "authorization")]
     use crate::rest_api::auth::{
@@ -122,6 +213,39 @@ mod tests {
         pub new_display_name: String,
     }
 
+    #[cfg(feature = "biome-key-wrapping")]
+    #[derive(Serialize)]
+    struct PostWrappedKey {
+        pub display_name: String,
+    }
+
+    #[cfg(feature = "biome-key-wrapping")]
+    #[derive(Serialize)]
+    struct PostSignature {
+        pub hashed_password: String,
+        pub header: String,
+    }
+
+    #[cfg(feature = "biome-key-wrapping")]
+    #[derive(Deserialize)]
+    struct SignatureResponse {
+        pub signature: String,
+    }
+
+    #[cfg(feature = "biome-key-wrapping")]
+    #[derive(Deserialize)]
+    struct KeyUsage {
+        pub public_key: String,
+        pub action: String,
+        pub payload_sha512: String,
+    }
+
+    #[cfg(feature = "biome-key-wrapping")]
+    #[derive(Deserialize)]
+    struct GetKeyUsageResponse {
+        pub data: Vec<KeyUsage>,
+    }
+
     fn start_biome_rest_api() -> (RestApiShutdownHandle, thread::JoinHandle<()>) {
         let refresh_token_store = MemoryRefreshTokenStore::new();
         let cred_store = MemoryCredentialsStore::new();
@@ -131,6 +255,9 @@ mod tests {
             .build()
             .unwrap();
 
+        #[cfg(feature = "biome-key-wrapping")]
+        let wrapping_cred_store = cred_store.clone();
+
         let biome_credentials_resource_provider =
             BiomeCredentialsRestResourceProviderBuilder::default()
                 .with_refresh_token_store(refresh_token_store)
@@ -142,6 +269,19 @@ mod tests {
 
         let biome_key_management_resource_provider =
             BiomeKeyManagementRestResourceProvider::new(Arc::new(key_store));
+        #[cfg(feature = "biome-key-wrapping")]
+        let biome_key_management_resource_provider = biome_key_management_resource_provider
+            .with_key_wrapping(
+                Arc::new(LocalKeyWrapper::new([1; 32])),
+                Arc::new(wrapping_cred_store),
+                #[cfg(feature = "biome-login-lockout")]
+                Arc::new(
+                    BiomeCredentialsRestConfigBuilder::default()
+                        .with_password_encryption_cost("low")
+                        .build()
+                        .unwrap(),
+                ),
+            );
 
         let mut rest_api_builder = RestApiBuilder::new();
 
@@ -536,4 +676,118 @@ mod tests {
             );
         });
     }
+    /// Test signing with a key that is wrapped by the server
+    ///
+    /// Verify that POST /biome/wrapped_keys generates a key whose private key is not returned,
+    /// that POST /biome/wrapped_keys/{public_key}/sign only signs a header for the key's owner
+    /// with a valid password, and that each signature is recorded in
+    /// GET /biome/wrapped_keys/{public_key}/usage.
+    ///
+    /// Procedure
+    ///
+    /// 1) Create a user and log in as that user
+    /// 2) Generate a new key via POST /biome/wrapped_keys
+    /// 3) Sign a circuit management payload header and verify the signature
+    /// 4) Verify that signing with an invalid password returns a 400
+    /// 5) Verify that signing a header with a different requester returns a 400
+    /// 6) Verify that only the successful signature was recorded
+    #[cfg(feature = "biome-key-wrapping")]
+    #[test]
+    fn test_wrapped_key_sign() {
+        run_test(|url, client| {
+            let login = create_and_authorize_user(
+                url,
+                &client,
+                "test_wrapped_key_sign@gmail.com",
+                "Admin2193!",
+            );
+
+            let key_response = client
+                .post(&format!("{}/biome/wrapped_keys", url))
+                .header("Authorization", format!("Bearer {}", login.token))
+                .json(&PostWrappedKey {
+                    display_name: "test_wrapped_key_sign@gmail.com".to_string(),
+                })
+                .send()
+                .unwrap();
+            assert_eq!(key_response.status().as_u16(), 200);
+            let key = key_response.json::<PostKeyResponse>().unwrap().data;
+            assert_eq!(key.user_id, login.user_id);
+
+            let mut header = CircuitManagementPayload_Header::new();
+            header.set_action(CircuitManagementPayload_Action::CIRCUIT_CREATE_REQUEST);
+            header.set_requester(parse_hex(&key.public_key).unwrap());
+            header.set_payload_sha512(vec![1, 2, 3]);
+            header.set_requester_node_id("test-node".into());
+            let header_bytes = header.write_to_bytes().unwrap();
+
+            let sign_url = format!("{}/biome/wrapped_keys/{}/sign", url, key.public_key);
+
+            let sign_response = client
+                .post(&sign_url)
+                .header("Authorization", format!("Bearer {}", login.token))
+                .json(&PostSignature {
+                    hashed_password: "Admin2193!".to_string(),
+                    header: to_hex(&header_bytes),
+                })
+                .send()
+                .unwrap();
+            assert_eq!(sign_response.status().as_u16(), 200);
+            let signature = sign_response.json::<SignatureResponse>().unwrap().signature;
+            assert!(Secp256k1Context::new()
+                .new_verifier()
+                .verify(
+                    &header_bytes,
+                    &Signature::new(parse_hex(&signature).unwrap()),
+                    &PublicKey::new(parse_hex(&key.public_key).unwrap()),
+                )
+                .unwrap());
+
+            assert_eq!(
+                client
+                    .post(&sign_url)
+                    .header("Authorization", format!("Bearer {}", login.token))
+                    .json(&PostSignature {
+                        hashed_password: "wrong password".to_string(),
+                        header: to_hex(&header_bytes),
+                    })
+                    .send()
+                    .unwrap()
+                    .status()
+                    .as_u16(),
+                400
+            );
+
+            header.set_requester(vec![2; 33]);
+            assert_eq!(
+                client
+                    .post(&sign_url)
+                    .header("Authorization", format!("Bearer {}", login.token))
+                    .json(&PostSignature {
+                        hashed_password: "Admin2193!".to_string(),
+                        header: to_hex(&header.write_to_bytes().unwrap()),
+                    })
+                    .send()
+                    .unwrap()
+                    .status()
+                    .as_u16(),
+                400
+            );
+
+            let usage_response = client
+                .get(&format!(
+                    "{}/biome/wrapped_keys/{}/usage",
+                    url, key.public_key
+                ))
+                .header("Authorization", format!("Bearer {}", login.token))
+                .send()
+                .unwrap();
+            assert_eq!(usage_response.status().as_u16(), 200);
+            let usage = usage_response.json::<GetKeyUsageResponse>().unwrap().data;
+            assert_eq!(usage.len(), 1);
+            assert_eq!(usage[0].public_key, key.public_key);
+            assert_eq!(usage[0].action, "CIRCUIT_CREATE_REQUEST");
+            assert_eq!(usage[0].payload_sha512, "010203");
+        });
+    }
 }
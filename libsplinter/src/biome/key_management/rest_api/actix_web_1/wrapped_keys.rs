@@ -0,0 +1,481 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Provides REST endpoints for keys whose private keys are wrapped by the server, so that the
+//! server can sign circuit management payloads on a user's behalf.
+
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use cylinder::{secp256k1::Secp256k1Context, Context, PrivateKey};
+use protobuf::Message;
+
+use crate::actix_web::HttpResponse;
+#[cfg(feature = "biome-login-lockout")]
+use crate::biome::credentials::rest_api::{
+    is_locked_out, record_failed_login, reset_failed_logins, BiomeCredentialsRestConfig,
+};
+use crate::biome::credentials::store::CredentialsStore;
+use crate::biome::key_management::{
+    rest_api::resources::{NewWrappedKey, ResponseKey, ResponseKeyUsage, SignatureRequest},
+    store::{KeyStore, KeyStoreError, KeyUsage},
+    wrapping::{is_wrapped, unwrap_private_key, wrap_private_key, KeyWrapper},
+    Key,
+};
+use crate::futures::{Future, IntoFuture};
+use crate::hex::{parse_hex, to_hex};
+use crate::protocol;
+use crate::protos::admin::CircuitManagementPayload_Header;
+#[cfg(feature = "authorization")]
+use crate::rest_api::auth::authorization::Permission;
+use crate::rest_api::{
+    actix_web_1::{into_bytes, HandlerFunction, Method, ProtocolVersionRangeGuard, Resource},
+    auth::identity::Identity,
+    ErrorResponse,
+};
+
+/// Defines a REST endpoint for generating a new key that is wrapped by the server
+pub fn make_wrapped_keys_route(
+    key_store: Arc<dyn KeyStore>,
+    key_wrapper: Arc<dyn KeyWrapper>,
+) -> Resource {
+    let resource =
+        Resource::build("/biome/wrapped_keys").add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::BIOME_WRAPPED_KEYS_PROTOCOL_MIN,
+            protocol::BIOME_PROTOCOL_VERSION,
+        ));
+    #[cfg(feature = "authorization")]
+    {
+        resource.add_method(
+            Method::Post,
+            Permission::AllowAuthenticated,
+            handle_post(key_store, key_wrapper),
+        )
+    }
+    #[cfg(not(feature = "authorization"))]
+    {
+        resource.add_method(Method::Post, handle_post(key_store, key_wrapper))
+    }
+}
+
+/// Defines a REST endpoint method to generate a new wrapped key and add it to the underlying
+/// storage
+fn handle_post(key_store: Arc<dyn KeyStore>, key_wrapper: Arc<dyn KeyWrapper>) -> HandlerFunction {
+    Box::new(move |request, payload| {
+        let key_store = key_store.clone();
+        let key_wrapper = key_wrapper.clone();
+
+        let user = match request.extensions().get::<Identity>() {
+            Some(Identity::User(user)) => user.clone(),
+            _ => {
+                return Box::new(
+                    HttpResponse::Unauthorized()
+                        .json(ErrorResponse::unauthorized())
+                        .into_future(),
+                )
+            }
+        };
+
+        Box::new(into_bytes(payload).and_then(move |bytes| {
+            let new_key = match serde_json::from_slice::<NewWrappedKey>(&bytes) {
+                Ok(val) => val,
+                Err(err) => {
+                    debug!("Error parsing payload {}", err);
+                    return HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request(&format!(
+                            "Failed to parse payload: {}",
+                            err
+                        )))
+                        .into_future();
+                }
+            };
+
+            let context = Secp256k1Context::new();
+            let private_key = context.new_random_private_key();
+            let public_key = match context.get_public_key(&private_key) {
+                Ok(public_key) => public_key.as_hex(),
+                Err(err) => {
+                    error!("Failed to derive public key: {}", err);
+                    return HttpResponse::InternalServerError()
+                        .json(ErrorResponse::internal_error())
+                        .into_future();
+                }
+            };
+            let wrapped_private_key =
+                match wrap_private_key(&*key_wrapper, &user, &public_key, private_key.as_slice()) {
+                    Ok(wrapped_private_key) => wrapped_private_key,
+                    Err(err) => {
+                        error!("Failed to wrap private key: {}", err);
+                        return HttpResponse::InternalServerError()
+                            .json(ErrorResponse::internal_error())
+                            .into_future();
+                    }
+                };
+
+            let key = Key::new(
+                &public_key,
+                &wrapped_private_key,
+                &user,
+                &new_key.display_name,
+            );
+            let response_key = ResponseKey::from(&key);
+
+            match key_store.add_key(key.clone()) {
+                Ok(()) => HttpResponse::Ok()
+                    .json(json!({ "message": "Key added successfully", "data": response_key }))
+                    .into_future(),
+                Err(err) => {
+                    debug!("Failed to add new key to database {}", err);
+                    match err {
+                        KeyStoreError::UserDoesNotExistError(msg) => HttpResponse::BadRequest()
+                            .json(ErrorResponse::bad_request(&msg))
+                            .into_future(),
+                        _ => HttpResponse::InternalServerError()
+                            .json(ErrorResponse::internal_error())
+                            .into_future(),
+                    }
+                }
+            }
+        }))
+    })
+}
+
+/// Defines a REST endpoint for signing a circuit management payload header with a wrapped key
+pub fn make_wrapped_key_sign_route(
+    key_store: Arc<dyn KeyStore>,
+    key_wrapper: Arc<dyn KeyWrapper>,
+    credentials_store: Arc<dyn CredentialsStore>,
+    #[cfg(feature = "biome-login-lockout")] credentials_config: Arc<BiomeCredentialsRestConfig>,
+) -> Resource {
+    let resource = Resource::build("/biome/wrapped_keys/{public_key}/sign").add_request_guard(
+        ProtocolVersionRangeGuard::new(
+            protocol::BIOME_WRAPPED_KEYS_PROTOCOL_MIN,
+            protocol::BIOME_PROTOCOL_VERSION,
+        ),
+    );
+    #[cfg(feature = "authorization")]
+    {
+        resource.add_method(
+            Method::Post,
+            Permission::AllowAuthenticated,
+            handle_sign(
+                key_store,
+                key_wrapper,
+                credentials_store,
+                #[cfg(feature = "biome-login-lockout")]
+                credentials_config,
+            ),
+        )
+    }
+    #[cfg(not(feature = "authorization"))]
+    {
+        resource.add_method(
+            Method::Post,
+            handle_sign(
+                key_store,
+                key_wrapper,
+                credentials_store,
+                #[cfg(feature = "biome-login-lockout")]
+                credentials_config,
+            ),
+        )
+    }
+}
+
+/// Defines a REST endpoint method to sign a circuit management payload header
+///
+/// The user's password is required with every request to unlock the key, and every signature is
+/// recorded in the key's usage before it is returned. A wrong password counts as a failed login.
+fn handle_sign(
+    key_store: Arc<dyn KeyStore>,
+    key_wrapper: Arc<dyn KeyWrapper>,
+    credentials_store: Arc<dyn CredentialsStore>,
+    #[cfg(feature = "biome-login-lockout")] credentials_config: Arc<BiomeCredentialsRestConfig>,
+) -> HandlerFunction {
+    Box::new(move |request, payload| {
+        let key_store = key_store.clone();
+        let key_wrapper = key_wrapper.clone();
+        let credentials_store = credentials_store.clone();
+        #[cfg(feature = "biome-login-lockout")]
+        let credentials_config = credentials_config.clone();
+
+        let public_key = match request.match_info().get("public_key") {
+            Some(id) => id.to_owned(),
+            None => {
+                error!("Public key is not in path request");
+                return Box::new(
+                    HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request(
+                            &"Failed to process request: no public key".to_string(),
+                        ))
+                        .into_future(),
+                );
+            }
+        };
+
+        let user = match request.extensions().get::<Identity>() {
+            Some(Identity::User(user)) => user.clone(),
+            _ => {
+                return Box::new(
+                    HttpResponse::Unauthorized()
+                        .json(ErrorResponse::unauthorized())
+                        .into_future(),
+                )
+            }
+        };
+
+        Box::new(into_bytes(payload).and_then(move |bytes| {
+            let signature_request = match serde_json::from_slice::<SignatureRequest>(&bytes) {
+                Ok(val) => val,
+                Err(err) => {
+                    debug!("Error parsing payload {}", err);
+                    return HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request(&format!(
+                            "Failed to parse payload: {}",
+                            err
+                        )))
+                        .into_future();
+                }
+            };
+
+            sign_header(
+                &*key_store,
+                &*key_wrapper,
+                &*credentials_store,
+                #[cfg(feature = "biome-login-lockout")]
+                &credentials_config,
+                &user,
+                &public_key,
+                &signature_request,
+            )
+            .into_future()
+        }))
+    })
+}
+
+fn sign_header(
+    key_store: &dyn KeyStore,
+    key_wrapper: &dyn KeyWrapper,
+    credentials_store: &dyn CredentialsStore,
+    #[cfg(feature = "biome-login-lockout")] credentials_config: &BiomeCredentialsRestConfig,
+    user: &str,
+    public_key: &str,
+    signature_request: &SignatureRequest,
+) -> HttpResponse {
+    let credentials = match credentials_store.fetch_credential_by_user_id(user) {
+        Ok(credentials) => credentials,
+        Err(err) => {
+            error!("Failed to fetch credentials: {}", err);
+            return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+        }
+    };
+
+    // The password is checked the same way as at login, so a locked out user cannot sign
+    #[cfg(feature = "biome-login-lockout")]
+    match is_locked_out(credentials_store, user) {
+        Ok(false) => (),
+        Ok(true) => {
+            return HttpResponse::Forbidden().json(ErrorResponse::forbidden("Account is locked"));
+        }
+        Err(err) => {
+            error!("Failed to check lockout: {}", err);
+            return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+        }
+    }
+
+    match credentials.verify_password(&signature_request.hashed_password) {
+        Ok(true) => (),
+        Ok(false) => {
+            #[cfg(feature = "biome-login-lockout")]
+            if let Err(err) = record_failed_login(credentials_store, credentials_config, user) {
+                error!("Failed to record failed login: {}", err);
+                return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+            }
+            return HttpResponse::BadRequest().json(ErrorResponse::bad_request("Invalid password"));
+        }
+        Err(err) => {
+            error!("Failed to verify password: {}", err);
+            return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+        }
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    if let Err(err) = reset_failed_logins(credentials_store, user) {
+        error!("Failed to reset failed logins: {}", err);
+        return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+    }
+
+    let key = match key_store.fetch_key(public_key, user) {
+        Ok(key) if is_wrapped(&key) => key,
+        Ok(_) => {
+            return HttpResponse::BadRequest().json(ErrorResponse::bad_request(
+                "Key is not wrapped by the server",
+            ));
+        }
+        Err(KeyStoreError::NotFoundError(msg)) => {
+            debug!("Failed to fetch key: {}", msg);
+            return HttpResponse::NotFound().json(ErrorResponse::not_found(&msg));
+        }
+        Err(err) => {
+            error!("Failed to fetch key: {}", err);
+            return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+        }
+    };
+
+    let header_bytes = match parse_hex(&signature_request.header) {
+        Ok(header_bytes) => header_bytes,
+        Err(err) => {
+            return HttpResponse::BadRequest().json(ErrorResponse::bad_request(&format!(
+                "Header is not valid hex: {}",
+                err
+            )));
+        }
+    };
+    let header = match CircuitManagementPayload_Header::parse_from_bytes(&header_bytes) {
+        Ok(header) => header,
+        Err(err) => {
+            return HttpResponse::BadRequest().json(ErrorResponse::bad_request(&format!(
+                "Failed to parse header: {}",
+                err
+            )));
+        }
+    };
+    // Only sign payloads that are submitted as the key's owner
+    if to_hex(header.get_requester()) != key.public_key {
+        return HttpResponse::BadRequest().json(ErrorResponse::bad_request(
+            "Header requester does not match the key",
+        ));
+    }
+
+    let context = Secp256k1Context::new();
+    let private_key = match unwrap_private_key(key_wrapper, &key) {
+        Ok(private_key) => PrivateKey::new(private_key),
+        Err(err) => {
+            error!("Failed to unwrap private key: {}", err);
+            return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+        }
+    };
+    match context.get_public_key(&private_key) {
+        Ok(derived) if derived.as_hex() == key.public_key => (),
+        Ok(_) => {
+            error!(
+                "Unwrapped private key does not match public key {}",
+                key.public_key
+            );
+            return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+        }
+        Err(err) => {
+            error!("Failed to derive public key: {}", err);
+            return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+        }
+    }
+
+    let signature = match context.new_signer(private_key).sign(&header_bytes) {
+        Ok(signature) => signature.take_bytes(),
+        Err(err) => {
+            error!("Failed to sign header: {}", err);
+            return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+        }
+    };
+
+    // The signature is only returned if its use could be recorded
+    let usage = KeyUsage {
+        public_key: key.public_key.clone(),
+        user_id: user.to_string(),
+        used_at: SystemTime::now(),
+        action: format!("{:?}", header.get_action()),
+        payload_sha512: to_hex(header.get_payload_sha512()),
+    };
+    if let Err(err) = key_store.add_key_usage(usage) {
+        error!("Failed to record key usage: {}", err);
+        return HttpResponse::InternalServerError().json(ErrorResponse::internal_error());
+    }
+
+    HttpResponse::Ok().json(json!({ "signature": to_hex(&signature) }))
+}
+
+/// Defines a REST endpoint for listing the recorded uses of a wrapped key
+pub fn make_wrapped_key_usage_route(key_store: Arc<dyn KeyStore>) -> Resource {
+    let resource = Resource::build("/biome/wrapped_keys/{public_key}/usage").add_request_guard(
+        ProtocolVersionRangeGuard::new(
+            protocol::BIOME_WRAPPED_KEYS_PROTOCOL_MIN,
+            protocol::BIOME_PROTOCOL_VERSION,
+        ),
+    );
+    #[cfg(feature = "authorization")]
+    {
+        resource.add_method(
+            Method::Get,
+            Permission::AllowAuthenticated,
+            handle_list_usage(key_store),
+        )
+    }
+    #[cfg(not(feature = "authorization"))]
+    {
+        resource.add_method(Method::Get, handle_list_usage(key_store))
+    }
+}
+
+/// Defines a REST endpoint method to list the authorized user's recorded uses of a key
+fn handle_list_usage(key_store: Arc<dyn KeyStore>) -> HandlerFunction {
+    Box::new(move |request, _| {
+        let key_store = key_store.clone();
+
+        let public_key = match request.match_info().get("public_key") {
+            Some(id) => id.to_owned(),
+            None => {
+                error!("Public key is not in path request");
+                return Box::new(
+                    HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request(
+                            &"Failed to process request: no public key".to_string(),
+                        ))
+                        .into_future(),
+                );
+            }
+        };
+
+        let user = match request.extensions().get::<Identity>() {
+            Some(Identity::User(user)) => user.clone(),
+            _ => {
+                return Box::new(
+                    HttpResponse::Unauthorized()
+                        .json(ErrorResponse::unauthorized())
+                        .into_future(),
+                )
+            }
+        };
+
+        match key_store.list_key_usage(&public_key, &user) {
+            Ok(key_usage) => Box::new(
+                HttpResponse::Ok()
+                    .json(json!({
+                        "data": key_usage
+                            .iter()
+                            .map(ResponseKeyUsage::from)
+                            .collect::<Vec<ResponseKeyUsage>>()
+                    }))
+                    .into_future(),
+            ),
+            Err(err) => {
+                error!("Failed to list key usage: {}", err);
+                Box::new(
+                    HttpResponse::InternalServerError()
+                        .json(ErrorResponse::internal_error())
+                        .into_future(),
+                )
+            }
+        }
+    })
+}
@@ -14,6 +14,11 @@
 
 //! Defines structures used in key management.
 
+#[cfg(feature = "biome-key-wrapping")]
+use std::time::UNIX_EPOCH;
+
+#[cfg(feature = "biome-key-wrapping")]
+use crate::biome::key_management::store::KeyUsage;
 use crate::biome::key_management::Key;
 
 #[derive(Deserialize)]
@@ -47,3 +52,45 @@ impl<'a> From<&'a Key> for ResponseKey<'a> {
         }
     }
 }
+
+#[cfg(feature = "biome-key-wrapping")]
+#[derive(Deserialize)]
+pub(crate) struct NewWrappedKey {
+    pub display_name: String,
+}
+
+#[cfg(feature = "biome-key-wrapping")]
+#[derive(Deserialize)]
+pub(crate) struct SignatureRequest {
+    pub hashed_password: String,
+    /// The hex-encoded, serialized `CircuitManagementPayload` header to sign
+    pub header: String,
+}
+
+#[cfg(feature = "biome-key-wrapping")]
+#[derive(Serialize)]
+pub(crate) struct ResponseKeyUsage<'a> {
+    public_key: &'a str,
+    user_id: &'a str,
+    /// Seconds since the Unix epoch
+    used_at: u64,
+    action: &'a str,
+    payload_sha512: &'a str,
+}
+
+#[cfg(feature = "biome-key-wrapping")]
+impl<'a> From<&'a KeyUsage> for ResponseKeyUsage<'a> {
+    fn from(usage: &'a KeyUsage) -> Self {
+        ResponseKeyUsage {
+            public_key: &usage.public_key,
+            user_id: &usage.user_id,
+            used_at: usage
+                .used_at
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or(0),
+            action: &usage.action,
+            payload_sha512: &usage.payload_sha512,
+        }
+    }
+}
@@ -20,11 +20,18 @@ use diesel::r2d2::{ConnectionManager, Pool};
 
 #[cfg(feature = "biome-credentials")]
 use crate::biome::credentials::store::PasswordEncryptionCost;
+#[cfg(feature = "biome-key-wrapping")]
+use crate::biome::key_management::store::KeyUsage;
 use crate::biome::key_management::store::{KeyStore, KeyStoreError};
 use crate::biome::key_management::Key;
 
 #[cfg(feature = "biome-credentials")]
 use operations::update_keys_and_password::KeyStoreUpdateKeysAndPasswordOperation as _;
+#[cfg(feature = "biome-key-wrapping")]
+use operations::{
+    add_key_usage::KeyStoreAddKeyUsageOperation as _,
+    list_key_usage::KeyStoreListKeyUsageOperation as _,
+};
 use operations::{
     fetch_key::KeyStoreFetchKeyOperation as _, insert_key::KeyStoreInsertKeyOperation as _,
     list_keys::KeyStoreListKeysOperation as _, list_keys::KeyStoreListKeysWithUserIDOperation as _,
@@ -99,6 +106,20 @@ impl KeyStore for DieselKeyStore<diesel::pg::PgConnection> {
             keys,
         )
     }
+
+    #[cfg(feature = "biome-key-wrapping")]
+    fn add_key_usage(&self, usage: KeyUsage) -> Result<(), KeyStoreError> {
+        KeyStoreOperations::new(&*self.connection_pool.get()?).add_key_usage(usage)
+    }
+
+    #[cfg(feature = "biome-key-wrapping")]
+    fn list_key_usage(
+        &self,
+        public_key: &str,
+        user_id: &str,
+    ) -> Result<Vec<KeyUsage>, KeyStoreError> {
+        KeyStoreOperations::new(&*self.connection_pool.get()?).list_key_usage(public_key, user_id)
+    }
 }
 
 #[cfg(feature = "sqlite")]
@@ -151,12 +172,29 @@ impl KeyStore for DieselKeyStore<diesel::sqlite::SqliteConnection> {
             keys,
         )
     }
+
+    #[cfg(feature = "biome-key-wrapping")]
+    fn add_key_usage(&self, usage: KeyUsage) -> Result<(), KeyStoreError> {
+        KeyStoreOperations::new(&*self.connection_pool.get()?).add_key_usage(usage)
+    }
+
+    #[cfg(feature = "biome-key-wrapping")]
+    fn list_key_usage(
+        &self,
+        public_key: &str,
+        user_id: &str,
+    ) -> Result<Vec<KeyUsage>, KeyStoreError> {
+        KeyStoreOperations::new(&*self.connection_pool.get()?).list_key_usage(public_key, user_id)
+    }
 }
 
 #[cfg(all(test, feature = "sqlite"))]
 pub mod tests {
     use super::*;
 
+    #[cfg(feature = "biome-key-wrapping")]
+    use std::time::{Duration, UNIX_EPOCH};
+
     #[cfg(feature = "biome-credentials")]
     use crate::biome::credentials::store::{
         diesel::DieselCredentialsStore, CredentialsBuilder, CredentialsStore,
@@ -411,6 +449,119 @@ pub mod tests {
             .expect("Failed to verify password"));
     }
 
+    #[cfg(feature = "biome-key-wrapping")]
+    /// Verify that `update_keys_and_password` keeps server-wrapped keys, which the client cannot
+    /// re-encrypt.
+    ///
+    /// 1. Create a connection pool for an in-memory SQLite database and run migrations.
+    /// 2. Add credentials, a client-encrypted key and a server-wrapped key.
+    /// 3. Replace the keys, passing a tampered copy of the wrapped key along with a new key.
+    /// 4. Verify that the client-encrypted key was replaced and that the stored wrapped key is
+    ///    unchanged.
+    #[test]
+    fn sqlite_update_keys_and_password_keeps_wrapped_keys() {
+        let pool = create_connection_pool_and_migrate();
+
+        let cred_store = DieselCredentialsStore::new(pool.clone());
+        let key_store = DieselKeyStore::new(pool);
+
+        cred_store
+            .add_credentials(
+                CredentialsBuilder::default()
+                    .with_user_id("user")
+                    .with_username("username")
+                    .with_password("pwd1")
+                    .with_password_encryption_cost(PasswordEncryptionCost::Low)
+                    .build()
+                    .expect("Failed to build cred"),
+            )
+            .expect("Failed to add cred");
+
+        let key1 = Key::new("pubkey1", "privkey1", "user", "name1");
+        let wrapped_key = Key::new("pubkey2", "server-wrapped:abcd", "user", "name2");
+        key_store.add_key(key1).expect("Failed to add key1");
+        key_store
+            .add_key(wrapped_key.clone())
+            .expect("Failed to add wrapped key");
+
+        let key3 = Key::new("pubkey3", "privkey3", "user", "name3");
+        let tampered_key = Key::new("pubkey2", "server-wrapped:ef01", "user", "name2");
+        key_store
+            .update_keys_and_password(
+                "user",
+                "pwd2",
+                PasswordEncryptionCost::Low,
+                &[key3.clone(), tampered_key],
+            )
+            .expect("Failed to update keys and password");
+
+        let keys = key_store
+            .list_keys(Some("user"))
+            .expect("Failed to list keys");
+        assert_eq!(keys.len(), 2);
+        assert!(keys.contains(&key3));
+        assert!(keys.contains(&wrapped_key));
+    }
+
+    #[cfg(feature = "biome-key-wrapping")]
+    /// Verify that a SQLite-backed `DieselKeyStore` correctly supports recording key usage.
+    ///
+    /// 1. Create a connection pool for an in-memory SQLite database and run migrations.
+    /// 2. Create the `DieselKeyStore`.
+    /// 3. Record two uses of one key and one use of another key.
+    /// 4. Verify that `list_key_usage` returns only the uses of the given key, oldest first.
+    /// 5. Remove the key and verify that its uses are still listed.
+    #[test]
+    fn sqlite_key_usage() {
+        let pool = create_connection_pool_and_migrate();
+
+        let store = DieselKeyStore::new(pool);
+
+        store
+            .add_key(Key::new("pubkey1", "privkey1", "user1", "name1"))
+            .expect("Failed to add key");
+
+        let usage = |public_key: &str, action: &str| KeyUsage {
+            public_key: public_key.into(),
+            user_id: "user1".into(),
+            used_at: UNIX_EPOCH + Duration::from_secs(1000),
+            action: action.into(),
+            payload_sha512: "abcd".into(),
+        };
+        let usage1 = usage("pubkey1", "CIRCUIT_CREATE_REQUEST");
+        let usage2 = usage("pubkey1", "CIRCUIT_PROPOSAL_VOTE");
+        store
+            .add_key_usage(usage1.clone())
+            .expect("Failed to add usage1");
+        store
+            .add_key_usage(usage("pubkey2", "CIRCUIT_CREATE_REQUEST"))
+            .expect("Failed to add usage for pubkey2");
+        store
+            .add_key_usage(usage2.clone())
+            .expect("Failed to add usage2");
+
+        assert_eq!(
+            store
+                .list_key_usage("pubkey1", "user1")
+                .expect("Failed to list key usage"),
+            vec![usage1.clone(), usage2.clone()],
+        );
+        assert!(store
+            .list_key_usage("pubkey1", "user2")
+            .expect("Failed to list key usage")
+            .is_empty());
+
+        store
+            .remove_key("pubkey1", "user1")
+            .expect("Failed to remove key");
+        assert_eq!(
+            store
+                .list_key_usage("pubkey1", "user1")
+                .expect("Failed to list key usage"),
+            vec![usage1, usage2],
+        );
+    }
+
     /// Creates a conneciton pool for an in-memory SQLite database with only a single connection
     /// available. Each connection is backed by a different in-memory SQLite database, so limiting
     /// the pool to a single connection insures that the same DB is used for all operations.
@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "biome-key-wrapping")]
+use super::schema::key_usage;
 use super::schema::keys;
 
 #[derive(Insertable, Queryable, Identifiable, PartialEq, Debug)]
@@ -23,3 +25,25 @@ pub struct KeyModel {
     pub user_id: String,
     pub display_name: String,
 }
+
+#[cfg(feature = "biome-key-wrapping")]
+#[derive(Insertable, PartialEq, Debug)]
+#[table_name = "key_usage"]
+pub struct NewKeyUsageModel {
+    pub public_key: String,
+    pub user_id: String,
+    pub used_at: i64,
+    pub action: String,
+    pub payload_sha512: String,
+}
+
+#[cfg(feature = "biome-key-wrapping")]
+#[derive(Queryable, PartialEq, Debug)]
+pub struct KeyUsageModel {
+    pub id: i64,
+    pub public_key: String,
+    pub user_id: String,
+    pub used_at: i64,
+    pub action: String,
+    pub payload_sha512: String,
+}
@@ -0,0 +1,79 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::convert::TryFrom;
+use std::time::UNIX_EPOCH;
+
+use super::KeyStoreOperations;
+use crate::biome::key_management::store::diesel::models::NewKeyUsageModel;
+use crate::biome::key_management::store::diesel::schema::key_usage;
+use crate::biome::key_management::store::{KeyStoreError, KeyUsage};
+
+use diesel::{dsl::insert_into, prelude::*};
+
+pub(in crate::biome::key_management) trait KeyStoreAddKeyUsageOperation {
+    fn add_key_usage(&self, usage: KeyUsage) -> Result<(), KeyStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> KeyStoreAddKeyUsageOperation for KeyStoreOperations<'a, diesel::pg::PgConnection> {
+    fn add_key_usage(&self, usage: KeyUsage) -> Result<(), KeyStoreError> {
+        insert_into(key_usage::table)
+            .values(into_model(usage)?)
+            .execute(self.conn)
+            .map(|_| ())
+            .map_err(|err| KeyStoreError::OperationError {
+                context: "Failed to add key usage".to_string(),
+                source: Box::new(err),
+            })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> KeyStoreAddKeyUsageOperation for KeyStoreOperations<'a, diesel::sqlite::SqliteConnection> {
+    fn add_key_usage(&self, usage: KeyUsage) -> Result<(), KeyStoreError> {
+        insert_into(key_usage::table)
+            .values(into_model(usage)?)
+            .execute(self.conn)
+            .map(|_| ())
+            .map_err(|err| KeyStoreError::OperationError {
+                context: "Failed to add key usage".to_string(),
+                source: Box::new(err),
+            })
+    }
+}
+
+fn into_model(usage: KeyUsage) -> Result<NewKeyUsageModel, KeyStoreError> {
+    let used_at = usage
+        .used_at
+        .duration_since(UNIX_EPOCH)
+        .map_err(|err| KeyStoreError::OperationError {
+            context: "Key usage time is before the epoch".to_string(),
+            source: Box::new(err),
+        })
+        .and_then(|duration| {
+            i64::try_from(duration.as_secs()).map_err(|err| KeyStoreError::OperationError {
+                context: "Key usage time is out of range".to_string(),
+                source: Box::new(err),
+            })
+        })?;
+
+    Ok(NewKeyUsageModel {
+        public_key: usage.public_key,
+        user_id: usage.user_id,
+        used_at,
+        action: usage.action,
+        payload_sha512: usage.payload_sha512,
+    })
+}
@@ -0,0 +1,64 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::{Duration, UNIX_EPOCH};
+
+use super::KeyStoreOperations;
+use crate::biome::key_management::store::diesel::models::KeyUsageModel;
+use crate::biome::key_management::store::diesel::schema::key_usage;
+use crate::biome::key_management::store::{KeyStoreError, KeyUsage};
+
+use diesel::prelude::*;
+
+pub(in crate::biome::key_management) trait KeyStoreListKeyUsageOperation {
+    fn list_key_usage(
+        &self,
+        public_key: &str,
+        user_id: &str,
+    ) -> Result<Vec<KeyUsage>, KeyStoreError>;
+}
+
+impl<'a, C> KeyStoreListKeyUsageOperation for KeyStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+    i64: diesel::deserialize::FromSql<diesel::sql_types::BigInt, C::Backend>,
+    String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
+{
+    fn list_key_usage(
+        &self,
+        public_key: &str,
+        user_id: &str,
+    ) -> Result<Vec<KeyUsage>, KeyStoreError> {
+        let key_usage = key_usage::table
+            .filter(key_usage::public_key.eq(public_key))
+            .filter(key_usage::user_id.eq(user_id))
+            .order(key_usage::id.asc())
+            .load::<KeyUsageModel>(self.conn)
+            .map_err(|err| KeyStoreError::OperationError {
+                context: "Failed to list key usage".to_string(),
+                source: Box::new(err),
+            })?
+            .into_iter()
+            .map(|usage| KeyUsage {
+                public_key: usage.public_key,
+                user_id: usage.user_id,
+                // A negative time can only come from a corrupt row; treat it as the epoch
+                used_at: UNIX_EPOCH + Duration::from_secs(usage.used_at.max(0) as u64),
+                action: usage.action,
+                payload_sha512: usage.payload_sha512,
+            })
+            .collect();
+        Ok(key_usage)
+    }
+}
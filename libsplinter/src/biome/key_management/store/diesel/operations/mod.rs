@@ -12,8 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "biome-key-wrapping")]
+pub(super) mod add_key_usage;
 pub(super) mod fetch_key;
 pub(super) mod insert_key;
+#[cfg(feature = "biome-key-wrapping")]
+pub(super) mod list_key_usage;
 pub(super) mod list_keys;
 pub(super) mod remove_key;
 pub(super) mod update_key;
@@ -16,9 +16,13 @@ use super::KeyStoreOperations;
 use crate::biome::credentials::store::{diesel::schema::user_credentials, PasswordEncryptionCost};
 use crate::biome::key_management::store::diesel::models::KeyModel;
 use crate::biome::key_management::store::diesel::schema::keys;
+#[cfg(feature = "biome-key-wrapping")]
+use crate::biome::key_management::wrapping::{is_wrapped, WRAPPED_KEY_PREFIX};
 use crate::biome::key_management::{store::KeyStoreError, Key};
 
 use bcrypt::hash;
+#[cfg(feature = "biome-key-wrapping")]
+use diesel::dsl::not;
 use diesel::{
     dsl::{delete, insert_into},
     prelude::*,
@@ -46,10 +50,7 @@ impl<'a> KeyStoreUpdateKeysAndPasswordOperation
         password_encryption_cost: PasswordEncryptionCost,
         keys: &[Key],
     ) -> Result<(), KeyStoreError> {
-        let replacement_keys = keys
-            .iter()
-            .map(|key| key.clone().into())
-            .collect::<Vec<KeyModel>>();
+        let replacement_keys = into_replacement_models(keys);
 
         let replacement_password = hash(updated_password, password_encryption_cost.to_value())
             .map_err(|err| KeyStoreError::OperationError {
@@ -59,9 +60,15 @@ impl<'a> KeyStoreUpdateKeysAndPasswordOperation
 
         self.conn
             .transaction::<(), _, _>(|| {
-                if let Err(err) =
-                    delete(keys::table.filter(keys::user_id.eq(user_id))).execute(self.conn)
-                {
+                // Server-wrapped keys cannot be re-encrypted by the client, so they are kept as
+                // they are
+                #[cfg(feature = "biome-key-wrapping")]
+                let replaced_keys = keys::table.filter(keys::user_id.eq(user_id)).filter(not(
+                    keys::encrypted_private_key.like(format!("{}%", WRAPPED_KEY_PREFIX)),
+                ));
+                #[cfg(not(feature = "biome-key-wrapping"))]
+                let replaced_keys = keys::table.filter(keys::user_id.eq(user_id));
+                if let Err(err) = delete(replaced_keys).execute(self.conn) {
                     return Err(err);
                 }
                 if let Err(err) = insert_into(keys::table)
@@ -125,10 +132,7 @@ impl<'a> KeyStoreUpdateKeysAndPasswordOperation
         password_encryption_cost: PasswordEncryptionCost,
         keys: &[Key],
     ) -> Result<(), KeyStoreError> {
-        let replacement_keys = keys
-            .iter()
-            .map(|key| key.clone().into())
-            .collect::<Vec<KeyModel>>();
+        let replacement_keys = into_replacement_models(keys);
 
         let replacement_password = hash(updated_password, password_encryption_cost.to_value())
             .map_err(|err| KeyStoreError::OperationError {
@@ -138,9 +142,15 @@ impl<'a> KeyStoreUpdateKeysAndPasswordOperation
 
         self.conn
             .transaction::<(), _, _>(|| {
-                if let Err(err) =
-                    delete(keys::table.filter(keys::user_id.eq(user_id))).execute(self.conn)
-                {
+                // Server-wrapped keys cannot be re-encrypted by the client, so they are kept as
+                // they are
+                #[cfg(feature = "biome-key-wrapping")]
+                let replaced_keys = keys::table.filter(keys::user_id.eq(user_id)).filter(not(
+                    keys::encrypted_private_key.like(format!("{}%", WRAPPED_KEY_PREFIX)),
+                ));
+                #[cfg(not(feature = "biome-key-wrapping"))]
+                let replaced_keys = keys::table.filter(keys::user_id.eq(user_id));
+                if let Err(err) = delete(replaced_keys).execute(self.conn) {
                     return Err(err);
                 }
                 if let Err(err) = insert_into(keys::table)
@@ -192,3 +202,19 @@ impl<'a> KeyStoreUpdateKeysAndPasswordOperation
         Ok(())
     }
 }
+
+/// Converts the keys provided by the client into models, leaving out server-wrapped keys, which
+/// are carried over from storage instead
+#[cfg(feature = "biome-key-wrapping")]
+fn into_replacement_models(keys: &[Key]) -> Vec<KeyModel> {
+    keys.iter()
+        .filter(|key| !is_wrapped(key))
+        .map(|key| key.clone().into())
+        .collect()
+}
+
+/// Converts the keys provided by the client into models
+#[cfg(not(feature = "biome-key-wrapping"))]
+fn into_replacement_models(keys: &[Key]) -> Vec<KeyModel> {
+    keys.iter().map(|key| key.clone().into()).collect()
+}
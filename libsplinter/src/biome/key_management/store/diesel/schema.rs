@@ -20,3 +20,14 @@ table! {
         display_name -> Text,
     }
 }
+
+table! {
+    key_usage (id) {
+        id -> Int8,
+        public_key -> Text,
+        user_id -> Text,
+        used_at -> Int8,
+        action -> Text,
+        payload_sha512 -> Text,
+    }
+}
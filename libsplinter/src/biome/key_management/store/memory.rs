@@ -17,6 +17,8 @@ use std::sync::{Arc, Mutex};
 use crate::biome::credentials::store::{
     memory::MemoryCredentialsStore, CredentialsStore, PasswordEncryptionCost,
 };
+#[cfg(feature = "biome-key-wrapping")]
+use crate::biome::key_management::store::KeyUsage;
 use crate::biome::key_management::{
     store::{error::KeyStoreError, KeyStore},
     Key,
@@ -27,6 +29,8 @@ pub struct MemoryKeyStore {
     inner: Arc<Mutex<HashMap<(String, String), Key>>>,
     #[cfg(feature = "biome-credentials")]
     credentials_store: MemoryCredentialsStore,
+    #[cfg(feature = "biome-key-wrapping")]
+    key_usage: Arc<Mutex<Vec<KeyUsage>>>,
 }
 
 impl MemoryKeyStore {
@@ -35,6 +39,8 @@ impl MemoryKeyStore {
         MemoryKeyStore {
             inner: Arc::new(Mutex::new(HashMap::new())),
             credentials_store,
+            #[cfg(feature = "biome-key-wrapping")]
+            key_usage: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
@@ -160,4 +166,35 @@ impl KeyStore for MemoryKeyStore {
 
         Ok(())
     }
+    #[cfg(feature = "biome-key-wrapping")]
+    fn add_key_usage(&self, usage: KeyUsage) -> Result<(), KeyStoreError> {
+        self.key_usage
+            .lock()
+            .map_err(|_| KeyStoreError::StorageError {
+                context: "Cannot access key store: mutex lock poisoned".to_string(),
+                source: None,
+            })?
+            .push(usage);
+        Ok(())
+    }
+
+    #[cfg(feature = "biome-key-wrapping")]
+    fn list_key_usage(
+        &self,
+        public_key: &str,
+        user_id: &str,
+    ) -> Result<Vec<KeyUsage>, KeyStoreError> {
+        let key_usage = self
+            .key_usage
+            .lock()
+            .map_err(|_| KeyStoreError::StorageError {
+                context: "Cannot access key store: mutex lock poisoned".to_string(),
+                source: None,
+            })?;
+        Ok(key_usage
+            .iter()
+            .filter(|usage| usage.public_key == public_key && usage.user_id == user_id)
+            .cloned()
+            .collect())
+    }
 }
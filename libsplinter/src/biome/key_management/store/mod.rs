@@ -17,6 +17,9 @@ pub(in crate::biome) mod diesel;
 pub mod error;
 pub(in crate::biome) mod memory;
 
+#[cfg(feature = "biome-key-wrapping")]
+use std::time::SystemTime;
+
 #[cfg(feature = "biome-credentials")]
 use crate::biome::credentials::store::PasswordEncryptionCost;
 
@@ -24,6 +27,20 @@ use super::Key;
 
 pub use error::KeyStoreError;
 
+/// Records a use of a server-wrapped key by the server on behalf of its owner
+#[cfg(feature = "biome-key-wrapping")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyUsage {
+    pub public_key: String,
+    pub user_id: String,
+    /// The time at which the key was used
+    pub used_at: SystemTime,
+    /// The circuit management action that was signed
+    pub action: String,
+    /// The hex-encoded SHA-512 hash of the payload that was signed
+    pub payload_sha512: String,
+}
+
 /// Defines methods for CRUD operations and fetching and listing keys
 /// without defining a storage strategy
 pub trait KeyStore: Sync + Send {
@@ -87,6 +104,27 @@ pub trait KeyStore: Sync + Send {
         password_encryption_cost: PasswordEncryptionCost,
         keys: &[Key],
     ) -> Result<(), KeyStoreError>;
+
+    /// Records a use of a server-wrapped key. Usage records are kept when the key is removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `usage` - The key usage to be recorded
+    #[cfg(feature = "biome-key-wrapping")]
+    fn add_key_usage(&self, usage: KeyUsage) -> Result<(), KeyStoreError>;
+
+    /// Lists the recorded uses of a key, oldest first
+    ///
+    /// # Arguments
+    ///
+    /// * `public_key`: The public key of the key whose uses are listed.
+    /// * `user_id`: The ID owner of the key whose uses are listed.
+    #[cfg(feature = "biome-key-wrapping")]
+    fn list_key_usage(
+        &self,
+        public_key: &str,
+        user_id: &str,
+    ) -> Result<Vec<KeyUsage>, KeyStoreError>;
 }
 
 impl<KS> KeyStore for Box<KS>
@@ -128,4 +166,18 @@ where
     ) -> Result<(), KeyStoreError> {
         (**self).update_keys_and_password(user_id, updated_password, password_encryption_cost, keys)
     }
+
+    #[cfg(feature = "biome-key-wrapping")]
+    fn add_key_usage(&self, usage: KeyUsage) -> Result<(), KeyStoreError> {
+        (**self).add_key_usage(usage)
+    }
+
+    #[cfg(feature = "biome-key-wrapping")]
+    fn list_key_usage(
+        &self,
+        public_key: &str,
+        user_id: &str,
+    ) -> Result<Vec<KeyUsage>, KeyStoreError> {
+        (**self).list_key_usage(public_key, user_id)
+    }
 }
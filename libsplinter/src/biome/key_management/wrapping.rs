@@ -0,0 +1,324 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Server-side wrapping of private keys with a node-level key-encryption key (KEK).
+//!
+//! Normally, the private key of a Biome [`Key`] is encrypted by the client and can never be used
+//! by the server. A key that is generated by the server is instead wrapped by a [`KeyWrapper`],
+//! which lets the server unwrap it to sign on the user's behalf. The KEK itself never leaves the
+//! key wrapper, so a key wrapper may be backed by an external key management service;
+//! [`LocalKeyWrapper`] keeps the KEK in a local file.
+//!
+//! [`Key`]: ../struct.Key.html
+//! [`KeyWrapper`]: trait.KeyWrapper.html
+//! [`LocalKeyWrapper`]: struct.LocalKeyWrapper.html
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Write};
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+
+use openssl::rand::rand_bytes;
+use openssl::symm::{decrypt_aead, encrypt_aead, Cipher};
+
+use crate::error::InternalError;
+use crate::hex::{parse_hex, to_hex};
+
+use super::Key;
+
+/// Prefix of the `encrypted_private_key` of a key that is wrapped by the server
+pub const WRAPPED_KEY_PREFIX: &str = "server-wrapped:";
+
+const KEK_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 12;
+const TAG_LENGTH: usize = 16;
+
+/// Wraps and unwraps private keys with a key-encryption key
+///
+/// The associated data is authenticated along with the key, so a wrapped key can only be
+/// unwrapped with the same associated data it was wrapped with.
+pub trait KeyWrapper: Send + Sync {
+    /// Wraps a private key
+    ///
+    /// # Arguments
+    ///
+    /// * `private_key` - The private key to wrap
+    /// * `associated_data` - Data the wrapped key is bound to
+    fn wrap_key(
+        &self,
+        private_key: &[u8],
+        associated_data: &[u8],
+    ) -> Result<Vec<u8>, InternalError>;
+
+    /// Unwraps a private key that was wrapped with `wrap_key`
+    ///
+    /// # Arguments
+    ///
+    /// * `wrapped_key` - The wrapped private key
+    /// * `associated_data` - The data the key was wrapped with
+    fn unwrap_key(
+        &self,
+        wrapped_key: &[u8],
+        associated_data: &[u8],
+    ) -> Result<Vec<u8>, InternalError>;
+}
+
+/// A `KeyWrapper` that holds its key-encryption key in memory, wrapping keys with AES-256-GCM
+pub struct LocalKeyWrapper {
+    kek: [u8; KEK_LENGTH],
+}
+
+impl LocalKeyWrapper {
+    /// Creates a new LocalKeyWrapper with the given key-encryption key
+    pub fn new(kek: [u8; KEK_LENGTH]) -> Self {
+        LocalKeyWrapper { kek }
+    }
+
+    /// Creates a new LocalKeyWrapper with the hex-encoded key-encryption key stored in a file
+    ///
+    /// If the file does not exist, a random key-encryption key is generated and written to it.
+    /// On Unix, the file is created readable by its owner only.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file the key-encryption key is stored in
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, InternalError> {
+        let path = path.as_ref();
+        let hex = match fs::read_to_string(path) {
+            Ok(hex) => hex,
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                let mut kek = [0; KEK_LENGTH];
+                rand_bytes(&mut kek).map_err(|err| InternalError::from_source(Box::new(err)))?;
+                let hex = to_hex(&kek);
+                match create_private_file(path) {
+                    Ok(mut file) => {
+                        file.write_all(hex.as_bytes()).map_err(|err| {
+                            InternalError::from_source_with_message(
+                                Box::new(err),
+                                format!("Failed to write key-encryption key to {:?}", path),
+                            )
+                        })?;
+                        hex
+                    }
+                    // Another caller created the file first; use its key
+                    Err(err) if err.kind() == ErrorKind::AlreadyExists => fs::read_to_string(path)
+                        .map_err(|err| {
+                            InternalError::from_source_with_message(
+                                Box::new(err),
+                                format!("Failed to read key-encryption key from {:?}", path),
+                            )
+                        })?,
+                    Err(err) => {
+                        return Err(InternalError::from_source_with_message(
+                            Box::new(err),
+                            format!("Failed to create key-encryption key file {:?}", path),
+                        ))
+                    }
+                }
+            }
+            Err(err) => {
+                return Err(InternalError::from_source_with_message(
+                    Box::new(err),
+                    format!("Failed to read key-encryption key from {:?}", path),
+                ))
+            }
+        };
+
+        let bytes = parse_hex(hex.trim()).map_err(|err| {
+            InternalError::from_source_with_message(
+                Box::new(err),
+                format!("Key-encryption key in {:?} is not valid hex", path),
+            )
+        })?;
+        if bytes.len() != KEK_LENGTH {
+            return Err(InternalError::with_message(format!(
+                "Key-encryption key in {:?} must be {} bytes",
+                path, KEK_LENGTH
+            )));
+        }
+        let mut kek = [0; KEK_LENGTH];
+        kek.copy_from_slice(&bytes);
+
+        Ok(LocalKeyWrapper::new(kek))
+    }
+}
+
+/// Creates a new file, readable by its owner only where the platform supports it
+fn create_private_file(path: &Path) -> std::io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    options.open(path)
+}
+
+impl KeyWrapper for LocalKeyWrapper {
+    fn wrap_key(
+        &self,
+        private_key: &[u8],
+        associated_data: &[u8],
+    ) -> Result<Vec<u8>, InternalError> {
+        let mut nonce = [0; NONCE_LENGTH];
+        rand_bytes(&mut nonce).map_err(|err| InternalError::from_source(Box::new(err)))?;
+
+        let mut tag = [0; TAG_LENGTH];
+        let ciphertext = encrypt_aead(
+            Cipher::aes_256_gcm(),
+            &self.kek,
+            Some(&nonce),
+            associated_data,
+            private_key,
+            &mut tag,
+        )
+        .map_err(|err| InternalError::from_source(Box::new(err)))?;
+
+        let mut wrapped = nonce.to_vec();
+        wrapped.extend(ciphertext);
+        wrapped.extend_from_slice(&tag);
+        Ok(wrapped)
+    }
+
+    fn unwrap_key(
+        &self,
+        wrapped_key: &[u8],
+        associated_data: &[u8],
+    ) -> Result<Vec<u8>, InternalError> {
+        if wrapped_key.len() < NONCE_LENGTH + TAG_LENGTH {
+            return Err(InternalError::with_message(
+                "Wrapped key is too short".to_string(),
+            ));
+        }
+        let (nonce, rest) = wrapped_key.split_at(NONCE_LENGTH);
+        let (ciphertext, tag) = rest.split_at(rest.len() - TAG_LENGTH);
+
+        decrypt_aead(
+            Cipher::aes_256_gcm(),
+            &self.kek,
+            Some(nonce),
+            associated_data,
+            ciphertext,
+            tag,
+        )
+        .map_err(|err| {
+            InternalError::from_source_with_message(
+                Box::new(err),
+                "Failed to unwrap key".to_string(),
+            )
+        })
+    }
+}
+
+/// Wraps a user's private key, returning the value to store as the key's `encrypted_private_key`
+///
+/// The wrapped key is bound to the user and public key, so it cannot be unwrapped as part of any
+/// other key.
+///
+/// # Arguments
+///
+/// * `key_wrapper` - The key wrapper to wrap the private key with
+/// * `user_id` - The ID of the user who owns the key
+/// * `public_key` - The hex-encoded public key of the key pair
+/// * `private_key` - The private key of the key pair
+pub fn wrap_private_key(
+    key_wrapper: &dyn KeyWrapper,
+    user_id: &str,
+    public_key: &str,
+    private_key: &[u8],
+) -> Result<String, InternalError> {
+    let wrapped = key_wrapper.wrap_key(private_key, &associated_data(user_id, public_key))?;
+    Ok(format!("{}{}", WRAPPED_KEY_PREFIX, to_hex(&wrapped)))
+}
+
+/// Unwraps the private key of a key that was wrapped with `wrap_private_key`
+///
+/// # Arguments
+///
+/// * `key_wrapper` - The key wrapper the private key was wrapped with
+/// * `key` - The key whose private key is unwrapped
+pub fn unwrap_private_key(
+    key_wrapper: &dyn KeyWrapper,
+    key: &Key,
+) -> Result<Vec<u8>, InternalError> {
+    let wrapped = key
+        .encrypted_private_key
+        .strip_prefix(WRAPPED_KEY_PREFIX)
+        .ok_or_else(|| InternalError::with_message("Key is not wrapped by the server".to_string()))
+        .and_then(|hex| parse_hex(hex).map_err(|err| InternalError::from_source(Box::new(err))))?;
+    key_wrapper.unwrap_key(&wrapped, &associated_data(&key.user_id, &key.public_key))
+}
+
+/// Returns whether or not a key's private key is wrapped by the server
+pub fn is_wrapped(key: &Key) -> bool {
+    key.encrypted_private_key.starts_with(WRAPPED_KEY_PREFIX)
+}
+
+fn associated_data(user_id: &str, public_key: &str) -> Vec<u8> {
+    format!("{}:{}", user_id, public_key).into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempdir::TempDir;
+
+    /// Verify that a wrapped private key can be unwrapped, but only as part of the same user's
+    /// key and with the same key-encryption key.
+    #[test]
+    fn wrap_and_unwrap_private_key() {
+        let key_wrapper = LocalKeyWrapper::new([1; KEK_LENGTH]);
+
+        let wrapped = wrap_private_key(&key_wrapper, "alice", "abcd", b"private key")
+            .expect("Failed to wrap key");
+        let mut key = Key::new("abcd", &wrapped, "alice", "key");
+        assert!(is_wrapped(&key));
+        assert_eq!(
+            unwrap_private_key(&key_wrapper, &key).expect("Failed to unwrap key"),
+            b"private key"
+        );
+
+        assert!(unwrap_private_key(&LocalKeyWrapper::new([2; KEK_LENGTH]), &key).is_err());
+
+        key.user_id = "bob".into();
+        assert!(unwrap_private_key(&key_wrapper, &key).is_err());
+
+        let client_key = Key::new("abcd", "encrypted by client", "alice", "key");
+        assert!(!is_wrapped(&client_key));
+        assert!(unwrap_private_key(&key_wrapper, &client_key).is_err());
+    }
+
+    /// Verify that `LocalKeyWrapper::from_file` generates a key-encryption key when the file does
+    /// not exist, and uses the same key afterwards.
+    #[test]
+    fn local_key_wrapper_from_file() {
+        let temp_dir = TempDir::new("local_key_wrapper").expect("Failed to create temp dir");
+        let path = temp_dir.path().join("kek");
+
+        let wrapped = LocalKeyWrapper::from_file(&path)
+            .expect("Failed to create key wrapper")
+            .wrap_key(b"private key", b"")
+            .expect("Failed to wrap key");
+        assert_eq!(
+            LocalKeyWrapper::from_file(&path)
+                .expect("Failed to load key wrapper")
+                .unwrap_key(&wrapped, b"")
+                .expect("Failed to unwrap key"),
+            b"private key"
+        );
+
+        fs::write(&path, "not hex").expect("Failed to overwrite key file");
+        assert!(LocalKeyWrapper::from_file(&path).is_err());
+    }
+}
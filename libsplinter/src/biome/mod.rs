@@ -57,6 +57,13 @@ pub use key_management::store::memory::MemoryKeyStore;
 #[cfg(feature = "biome-key-management")]
 pub use key_management::store::KeyStore;
 
+#[cfg(all(feature = "biome-notifications", feature = "diesel"))]
+pub use notifications::store::diesel::DieselNotificationStore;
+#[cfg(feature = "biome-notifications")]
+pub use notifications::store::memory::MemoryNotificationStore;
+#[cfg(feature = "biome-notifications")]
+pub use notifications::store::NotificationStore;
+
 #[cfg(all(feature = "oauth", feature = "diesel"))]
 pub use oauth::store::diesel::DieselOAuthUserSessionStore;
 #[cfg(feature = "oauth")]
@@ -77,3 +84,5 @@ pub use refresh_tokens::store::diesel::DieselRefreshTokenStore;
 pub use refresh_tokens::store::memory::MemoryRefreshTokenStore;
 #[cfg(feature = "biome-credentials")]
 pub use refresh_tokens::store::RefreshTokenStore;
+#[cfg(feature = "biome-sessions")]
+pub use refresh_tokens::store::{RefreshTokenSession, RefreshTokenSessionBuilder};
@@ -0,0 +1,293 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Creates Biome notifications from admin service events.
+//!
+//! An [`AdminEventNotifier`] is registered with the admin service as an event subscriber for a
+//! circuit management type. Each proposal or vote event it receives is turned into a notification
+//! addressed to the Biome users selected by its [`NotificationRecipients`].
+//!
+//! [`AdminEventNotifier`]: struct.AdminEventNotifier.html
+//! [`NotificationRecipients`]: trait.NotificationRecipients.html
+
+use crate::admin::service::{AdminServiceEventSubscriber, AdminSubscriberError};
+use crate::admin::store::{AdminServiceEvent, EventType};
+use crate::error::InternalError;
+use crate::hex::to_hex;
+
+use super::store::{NotificationBuilder, NotificationStore, NotificationStoreError};
+
+/// Selects the Biome users that should be notified of an admin service event.
+pub trait NotificationRecipients: Send {
+    /// Returns the user IDs of the users to notify of the given event. If no users are returned,
+    /// no notification is created.
+    fn recipients(&self, event: &AdminServiceEvent) -> Result<Vec<String>, InternalError>;
+}
+
+impl<F> NotificationRecipients for F
+where
+    F: Fn(&AdminServiceEvent) -> Result<Vec<String>, InternalError> + Send,
+{
+    fn recipients(&self, event: &AdminServiceEvent) -> Result<Vec<String>, InternalError> {
+        (*self)(event)
+    }
+}
+
+/// An admin service event subscriber that creates a notification for each circuit proposal and
+/// vote event.
+///
+/// The ID of each notification is derived from the ID of its event, so an event that is
+/// delivered more than once, such as when a subscriber catches up on past events, only results
+/// in a single notification.
+pub struct AdminEventNotifier {
+    notification_store: Box<dyn NotificationStore>,
+    recipients: Box<dyn NotificationRecipients>,
+}
+
+impl AdminEventNotifier {
+    /// Creates a new `AdminEventNotifier`
+    ///
+    /// # Arguments
+    ///
+    /// * `notification_store` - The store the notifications will be added to
+    /// * `recipients` - Selects the users to notify of each event
+    pub fn new(
+        notification_store: Box<dyn NotificationStore>,
+        recipients: Box<dyn NotificationRecipients>,
+    ) -> Self {
+        Self {
+            notification_store,
+            recipients,
+        }
+    }
+}
+
+impl AdminServiceEventSubscriber for AdminEventNotifier {
+    fn handle_event(&self, event: &AdminServiceEvent) -> Result<(), AdminSubscriberError> {
+        let proposal = event.proposal();
+        let circuit_name = proposal
+            .circuit()
+            .display_name()
+            .as_deref()
+            .unwrap_or_else(|| proposal.circuit_id());
+
+        let (event_type, title, body, requester) = match event.event_type() {
+            EventType::ProposalSubmitted => (
+                "ProposalSubmitted",
+                "New circuit proposal",
+                format!(
+                    "{} proposed circuit {}",
+                    proposal.requester_node_id(),
+                    circuit_name
+                ),
+                proposal.requester(),
+            ),
+            EventType::ProposalVote { requester } => (
+                "ProposalVote",
+                "Circuit proposal vote",
+                format!(
+                    "{} voted on the proposal for circuit {}",
+                    voter_node_id(event, requester).unwrap_or("A member"),
+                    circuit_name
+                ),
+                requester.as_slice(),
+            ),
+            EventType::ProposalAccepted { requester } => (
+                "ProposalAccepted",
+                "Circuit proposal accepted",
+                format!("The proposal for circuit {} was accepted", circuit_name),
+                requester.as_slice(),
+            ),
+            EventType::ProposalRejected { requester } => (
+                "ProposalRejected",
+                "Circuit proposal rejected",
+                format!(
+                    "{} rejected the proposal for circuit {}",
+                    voter_node_id(event, requester).unwrap_or("A member"),
+                    circuit_name
+                ),
+                requester.as_slice(),
+            ),
+            // Only proposal and vote events are of interest to users
+            EventType::CircuitReady | EventType::CircuitDisbanded => return Ok(()),
+        };
+
+        let recipients = self
+            .recipients
+            .recipients(event)
+            .map_err(|err| AdminSubscriberError::UnableToHandleEvent(err.to_string()))?;
+        if recipients.is_empty() {
+            return Ok(());
+        }
+
+        let notification = NotificationBuilder::new()
+            .with_id(format!("admin-event-{}", event.event_id()))
+            .with_title(title.to_string())
+            .with_body(body)
+            .with_properties(vec![
+                ("event_type".into(), event_type.into()),
+                ("event_id".into(), event.event_id().to_string()),
+                ("circuit_id".into(), proposal.circuit_id().into()),
+                ("circuit_hash".into(), proposal.circuit_hash().into()),
+                ("requester".into(), to_hex(requester)),
+                (
+                    "requester_node_id".into(),
+                    proposal.requester_node_id().into(),
+                ),
+            ])
+            .with_recipients(recipients)
+            .build()
+            .map_err(|err| AdminSubscriberError::UnableToHandleEvent(err.to_string()))?;
+
+        match self.notification_store.add_notification(notification) {
+            Ok(()) => Ok(()),
+            // The event has already been handled
+            Err(NotificationStoreError::ConstraintViolation(_)) => Ok(()),
+            Err(err) => Err(AdminSubscriberError::UnableToHandleEvent(format!(
+                "Unable to add notification for event {}: {}",
+                event.event_id(),
+                err
+            ))),
+        }
+    }
+}
+
+/// Returns the node ID of the vote cast by the given public key, if the vote is in the proposal
+fn voter_node_id<'a>(event: &'a AdminServiceEvent, public_key: &[u8]) -> Option<&'a str> {
+    event
+        .proposal()
+        .votes()
+        .iter()
+        .find(|vote| vote.public_key() == public_key)
+        .map(|vote| vote.voter_node_id())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::admin::store::{
+        AdminServiceEventBuilder, CircuitProposal, CircuitProposalBuilder, ProposalType,
+        ProposedCircuitBuilder, Vote, VoteRecordBuilder,
+    };
+    use crate::biome::MemoryNotificationStore;
+
+    /// Verify that the `AdminEventNotifier` creates a notification for proposal and vote events,
+    /// addressed to the selected recipients, and that it ignores circuit events, repeated events,
+    /// and events for which no recipients are selected.
+    #[test]
+    fn test_admin_event_notifier() {
+        let store = MemoryNotificationStore::new();
+        let notifier = AdminEventNotifier::new(
+            Box::new(store.clone()),
+            Box::new(
+                |event: &AdminServiceEvent| -> Result<Vec<String>, InternalError> {
+                    if *event.event_id() == 4 {
+                        Ok(vec![])
+                    } else {
+                        Ok(vec!["alice".to_string(), "bob".to_string()])
+                    }
+                },
+            ),
+        );
+
+        notifier
+            .handle_event(&build_event(1, EventType::ProposalSubmitted))
+            .expect("Unable to handle event");
+        notifier
+            .handle_event(&build_event(
+                2,
+                EventType::ProposalVote {
+                    requester: b"voter".to_vec(),
+                },
+            ))
+            .expect("Unable to handle event");
+        // A repeated event is ignored
+        notifier
+            .handle_event(&build_event(2, EventType::ProposalSubmitted))
+            .expect("Unable to handle event");
+        notifier
+            .handle_event(&build_event(3, EventType::CircuitReady))
+            .expect("Unable to handle event");
+        notifier
+            .handle_event(&build_event(4, EventType::ProposalSubmitted))
+            .expect("Unable to handle event");
+
+        for user in &["alice", "bob"] {
+            let mut notifications = store
+                .list_notifications(user)
+                .expect("Unable to list notifications");
+            notifications.sort_by(|a, b| a.notification().id().cmp(b.notification().id()));
+
+            assert_eq!(notifications.len(), 2);
+
+            let submitted = notifications[0].notification();
+            assert_eq!(submitted.id(), "admin-event-1");
+            assert_eq!(submitted.title(), "New circuit proposal");
+            assert_eq!(
+                submitted.body(),
+                "acme-node-000 proposed circuit Test Circuit"
+            );
+            assert!(submitted
+                .properties()
+                .contains(&("circuit_id".to_string(), "WBKLF-BBBBB".to_string())));
+
+            let vote = notifications[1].notification();
+            assert_eq!(vote.id(), "admin-event-2");
+            assert_eq!(
+                vote.body(),
+                "bubba-node-000 voted on the proposal for circuit Test Circuit"
+            );
+            assert!(vote
+                .properties()
+                .contains(&("requester".to_string(), to_hex(b"voter"))));
+        }
+    }
+
+    fn build_event(event_id: i64, event_type: EventType) -> AdminServiceEvent {
+        AdminServiceEventBuilder::new()
+            .with_event_id(event_id)
+            .with_event_type(&event_type)
+            .with_proposal(&build_proposal())
+            .build()
+            .expect("Unable to build event")
+    }
+
+    fn build_proposal() -> CircuitProposal {
+        CircuitProposalBuilder::default()
+            .with_proposal_type(&ProposalType::Create)
+            .with_circuit_id("WBKLF-BBBBB")
+            .with_circuit_hash("7ddc426972710adc0b2ecd49e89a9dd805fb9206bf516079724c887bedbcdf1d")
+            .with_circuit(
+                &ProposedCircuitBuilder::default()
+                    .with_circuit_id("WBKLF-BBBBB")
+                    .with_roster(&[])
+                    .with_members(&[])
+                    .with_circuit_management_type("gameroom")
+                    .with_display_name("Test Circuit")
+                    .build()
+                    .expect("Unable to build circuit"),
+            )
+            .with_requester(b"requester")
+            .with_requester_node_id("acme-node-000")
+            .with_votes(&[VoteRecordBuilder::new()
+                .with_public_key(b"voter")
+                .with_vote(&Vote::Accept)
+                .with_voter_node_id("bubba-node-000")
+                .build()
+                .expect("Unable to build vote record")])
+            .build()
+            .expect("Unable to build proposal")
+    }
+}
@@ -17,4 +17,8 @@
 
 //! Provides an API for notifications.
 
+#[cfg(feature = "admin-service")]
+pub mod admin;
+#[cfg(feature = "rest-api")]
+pub mod rest_api;
 pub mod store;
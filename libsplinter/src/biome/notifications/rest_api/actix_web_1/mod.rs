@@ -0,0 +1,48 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod notifications;
+
+use std::sync::Arc;
+
+use crate::biome::notifications::store::NotificationStore;
+use crate::rest_api::{Resource, RestResourceProvider};
+
+/// Provides the following REST API endpoints for Biome notifications:
+///
+/// * `GET /biome/notifications` - Get the notifications addressed to the authenticated user,
+///   newest first
+/// * `DELETE /biome/notifications/{id}` - Remove the notification with the specified ID for the
+///   authenticated user
+/// * `PATCH /biome/notifications/{id}/read` - Mark the notification with the specified ID as read
+///   for the authenticated user
+pub struct BiomeNotificationsRestResourceProvider {
+    notification_store: Arc<dyn NotificationStore>,
+}
+
+impl BiomeNotificationsRestResourceProvider {
+    pub fn new(notification_store: Arc<dyn NotificationStore>) -> Self {
+        Self { notification_store }
+    }
+}
+
+impl RestResourceProvider for BiomeNotificationsRestResourceProvider {
+    fn resources(&self) -> Vec<Resource> {
+        vec![
+            notifications::make_notifications_route(self.notification_store.clone()),
+            notifications::make_notification_route(self.notification_store.clone()),
+            notifications::make_notification_read_route(self.notification_store.clone()),
+        ]
+    }
+}
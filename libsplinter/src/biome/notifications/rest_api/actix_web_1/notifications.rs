@@ -0,0 +1,242 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use crate::actix_web::HttpResponse;
+use crate::biome::notifications::{
+    rest_api::resources::NotificationResponse,
+    store::{NotificationStore, NotificationStoreError},
+};
+use crate::futures::IntoFuture;
+use crate::protocol;
+#[cfg(feature = "authorization")]
+use crate::rest_api::auth::authorization::Permission;
+use crate::rest_api::{
+    actix_web_1::{HandlerFunction, Method, ProtocolVersionRangeGuard, Resource},
+    auth::identity::Identity,
+    ErrorResponse,
+};
+
+/// Defines a REST endpoint for listing the authenticated user's notifications
+pub fn make_notifications_route(notification_store: Arc<dyn NotificationStore>) -> Resource {
+    let resource =
+        Resource::build("/biome/notifications").add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::BIOME_NOTIFICATIONS_PROTOCOL_MIN,
+            protocol::BIOME_PROTOCOL_VERSION,
+        ));
+    #[cfg(feature = "authorization")]
+    {
+        resource.add_method(
+            Method::Get,
+            Permission::AllowAuthenticated,
+            handle_list(notification_store),
+        )
+    }
+    #[cfg(not(feature = "authorization"))]
+    {
+        resource.add_method(Method::Get, handle_list(notification_store))
+    }
+}
+
+/// Defines a REST endpoint for removing one of the authenticated user's notifications
+pub fn make_notification_route(notification_store: Arc<dyn NotificationStore>) -> Resource {
+    let resource = Resource::build("/biome/notifications/{id}").add_request_guard(
+        ProtocolVersionRangeGuard::new(
+            protocol::BIOME_NOTIFICATIONS_PROTOCOL_MIN,
+            protocol::BIOME_PROTOCOL_VERSION,
+        ),
+    );
+    #[cfg(feature = "authorization")]
+    {
+        resource.add_method(
+            Method::Delete,
+            Permission::AllowAuthenticated,
+            handle_delete(notification_store),
+        )
+    }
+    #[cfg(not(feature = "authorization"))]
+    {
+        resource.add_method(Method::Delete, handle_delete(notification_store))
+    }
+}
+
+/// Defines a REST endpoint for marking one of the authenticated user's notifications as read
+pub fn make_notification_read_route(notification_store: Arc<dyn NotificationStore>) -> Resource {
+    let resource = Resource::build("/biome/notifications/{id}/read").add_request_guard(
+        ProtocolVersionRangeGuard::new(
+            protocol::BIOME_NOTIFICATIONS_PROTOCOL_MIN,
+            protocol::BIOME_PROTOCOL_VERSION,
+        ),
+    );
+    #[cfg(feature = "authorization")]
+    {
+        resource.add_method(
+            Method::Patch,
+            Permission::AllowAuthenticated,
+            handle_mark_read(notification_store),
+        )
+    }
+    #[cfg(not(feature = "authorization"))]
+    {
+        resource.add_method(Method::Patch, handle_mark_read(notification_store))
+    }
+}
+
+/// Defines a REST endpoint method to list the authenticated user's notifications
+fn handle_list(notification_store: Arc<dyn NotificationStore>) -> HandlerFunction {
+    Box::new(move |request, _| {
+        let notification_store = notification_store.clone();
+
+        let user = match request.extensions().get::<Identity>() {
+            Some(Identity::User(user)) => user.clone(),
+            _ => {
+                return Box::new(
+                    HttpResponse::Unauthorized()
+                        .json(ErrorResponse::unauthorized())
+                        .into_future(),
+                )
+            }
+        };
+
+        match notification_store.list_notifications(&user) {
+            Ok(notifications) => Box::new(
+                HttpResponse::Ok()
+                    .json(json!({
+                        "data": notifications
+                            .iter()
+                            .map(NotificationResponse::from)
+                            .collect::<Vec<_>>()
+                    }))
+                    .into_future(),
+            ),
+            Err(err) => {
+                error!("Failed to list notifications: {}", err);
+                Box::new(
+                    HttpResponse::InternalServerError()
+                        .json(ErrorResponse::internal_error())
+                        .into_future(),
+                )
+            }
+        }
+    })
+}
+
+/// Defines a REST endpoint method to remove one of the authenticated user's notifications
+fn handle_delete(notification_store: Arc<dyn NotificationStore>) -> HandlerFunction {
+    Box::new(move |request, _| {
+        let notification_store = notification_store.clone();
+
+        let notification_id = match request.match_info().get("id") {
+            Some(id) => id.to_owned(),
+            None => {
+                error!("Notification ID is not in path request");
+                return Box::new(
+                    HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request(
+                            &"Failed to process request: no notification id".to_string(),
+                        ))
+                        .into_future(),
+                );
+            }
+        };
+
+        let user = match request.extensions().get::<Identity>() {
+            Some(Identity::User(user)) => user.clone(),
+            _ => {
+                return Box::new(
+                    HttpResponse::Unauthorized()
+                        .json(ErrorResponse::unauthorized())
+                        .into_future(),
+                )
+            }
+        };
+
+        match notification_store.remove_notification(&user, &notification_id) {
+            Ok(()) => Box::new(
+                HttpResponse::Ok()
+                    .json(json!({ "message": "Notification successfully deleted" }))
+                    .into_future(),
+            ),
+            Err(err) => {
+                Box::new(not_found_or_internal_error("delete", &notification_id, err).into_future())
+            }
+        }
+    })
+}
+
+/// Defines a REST endpoint method to mark one of the authenticated user's notifications as read
+fn handle_mark_read(notification_store: Arc<dyn NotificationStore>) -> HandlerFunction {
+    Box::new(move |request, _| {
+        let notification_store = notification_store.clone();
+
+        let notification_id = match request.match_info().get("id") {
+            Some(id) => id.to_owned(),
+            None => {
+                error!("Notification ID is not in path request");
+                return Box::new(
+                    HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request(
+                            &"Failed to process request: no notification id".to_string(),
+                        ))
+                        .into_future(),
+                );
+            }
+        };
+
+        let user = match request.extensions().get::<Identity>() {
+            Some(Identity::User(user)) => user.clone(),
+            _ => {
+                return Box::new(
+                    HttpResponse::Unauthorized()
+                        .json(ErrorResponse::unauthorized())
+                        .into_future(),
+                )
+            }
+        };
+
+        match notification_store.mark_read(&user, &notification_id) {
+            Ok(()) => Box::new(
+                HttpResponse::Ok()
+                    .json(json!({ "message": "Notification marked as read" }))
+                    .into_future(),
+            ),
+            Err(err) => Box::new(
+                not_found_or_internal_error("mark as read", &notification_id, err).into_future(),
+            ),
+        }
+    })
+}
+
+/// Maps an error from the store to a 404 if the notification does not exist for the user, or
+/// to a 500 otherwise
+fn not_found_or_internal_error(
+    operation: &str,
+    notification_id: &str,
+    err: NotificationStoreError,
+) -> HttpResponse {
+    match err {
+        NotificationStoreError::InvalidArgument(_) => {
+            debug!("Failed to {} notification: {}", operation, err);
+            HttpResponse::NotFound().json(ErrorResponse::not_found(&format!(
+                "Notification not found: {}",
+                notification_id
+            )))
+        }
+        _ => {
+            error!("Failed to {} notification: {}", operation, err);
+            HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+        }
+    }
+}
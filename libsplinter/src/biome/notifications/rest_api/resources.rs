@@ -0,0 +1,53 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Defines structures used in the notifications REST API.
+
+use std::collections::BTreeMap;
+use std::time::UNIX_EPOCH;
+
+use crate::biome::notifications::store::UserNotification;
+
+#[derive(Serialize)]
+pub(crate) struct NotificationResponse<'a> {
+    id: &'a str,
+    title: &'a str,
+    body: &'a str,
+    /// Seconds since the Unix epoch
+    created: u64,
+    properties: BTreeMap<&'a str, &'a str>,
+    unread: bool,
+}
+
+impl<'a> From<&'a UserNotification> for NotificationResponse<'a> {
+    fn from(user_notification: &'a UserNotification) -> Self {
+        let notification = user_notification.notification();
+        NotificationResponse {
+            id: notification.id(),
+            title: notification.title(),
+            body: notification.body(),
+            created: notification
+                .created()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or(0),
+            properties: notification
+                .properties()
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.as_str()))
+                .collect(),
+            unread: user_notification.unread(),
+        }
+    }
+}
@@ -1,19 +1,343 @@
-/*
- * Copyright 2018-2021 Cargill Incorporated
- *
- * Licensed under the Apache License, Version 2.0 (the "License");
- * you may not use this file except in compliance with the License.
- * You may obtain a copy of the License at
- *
- *     http://www.apache.org/licenses/LICENSE-2.0
- *
- * Unless required by applicable law or agreed to in writing, software
- * distributed under the License is distributed on an "AS IS" BASIS,
- * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
- * See the License for the specific language governing permissions and
- * limitations under the License.
- * -----------------------------------------------------------------------------
- */
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Database-backed implementation of the [NotificationStore], powered by [diesel].
 
 pub(in crate::biome) mod models;
-mod schema;
+mod operations;
+pub(in crate::biome) mod schema;
+
+use std::convert::TryFrom;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use diesel::r2d2::{ConnectionManager, Pool};
+
+use crate::error::InternalError;
+
+use super::{Notification, NotificationStore, NotificationStoreError, UserNotification};
+
+use operations::{
+    add_notification::NotificationStoreAddNotification as _,
+    list_notifications::NotificationStoreListNotifications as _,
+    mark_read::NotificationStoreMarkRead as _,
+    remove_notification::NotificationStoreRemoveNotification as _, NotificationStoreOperations,
+};
+
+/// Manages creating, reading, and removing notifications in the database
+pub struct DieselNotificationStore<C: diesel::Connection + 'static> {
+    connection_pool: Pool<ConnectionManager<C>>,
+}
+
+impl<C: diesel::Connection> DieselNotificationStore<C> {
+    /// Creates a new DieselNotificationStore
+    ///
+    /// # Arguments
+    ///
+    ///  * `connection_pool`: connection pool to the database
+    pub fn new(connection_pool: Pool<ConnectionManager<C>>) -> Self {
+        DieselNotificationStore { connection_pool }
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl NotificationStore for DieselNotificationStore<diesel::pg::PgConnection> {
+    fn add_notification(&self, notification: Notification) -> Result<(), NotificationStoreError> {
+        let connection = self.connection_pool.get()?;
+        NotificationStoreOperations::new(&*connection).add_notification(notification)
+    }
+
+    fn list_notifications(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<UserNotification>, NotificationStoreError> {
+        let connection = self.connection_pool.get()?;
+        NotificationStoreOperations::new(&*connection).list_notifications(user_id)
+    }
+
+    fn mark_read(
+        &self,
+        user_id: &str,
+        notification_id: &str,
+    ) -> Result<(), NotificationStoreError> {
+        let connection = self.connection_pool.get()?;
+        NotificationStoreOperations::new(&*connection).mark_read(user_id, notification_id)
+    }
+
+    fn remove_notification(
+        &self,
+        user_id: &str,
+        notification_id: &str,
+    ) -> Result<(), NotificationStoreError> {
+        let connection = self.connection_pool.get()?;
+        NotificationStoreOperations::new(&*connection).remove_notification(user_id, notification_id)
+    }
+
+    fn clone_box(&self) -> Box<dyn NotificationStore> {
+        Box::new(Self {
+            connection_pool: self.connection_pool.clone(),
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl NotificationStore for DieselNotificationStore<diesel::sqlite::SqliteConnection> {
+    fn add_notification(&self, notification: Notification) -> Result<(), NotificationStoreError> {
+        let connection = self.connection_pool.get()?;
+        NotificationStoreOperations::new(&*connection).add_notification(notification)
+    }
+
+    fn list_notifications(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<UserNotification>, NotificationStoreError> {
+        let connection = self.connection_pool.get()?;
+        NotificationStoreOperations::new(&*connection).list_notifications(user_id)
+    }
+
+    fn mark_read(
+        &self,
+        user_id: &str,
+        notification_id: &str,
+    ) -> Result<(), NotificationStoreError> {
+        let connection = self.connection_pool.get()?;
+        NotificationStoreOperations::new(&*connection).mark_read(user_id, notification_id)
+    }
+
+    fn remove_notification(
+        &self,
+        user_id: &str,
+        notification_id: &str,
+    ) -> Result<(), NotificationStoreError> {
+        let connection = self.connection_pool.get()?;
+        NotificationStoreOperations::new(&*connection).remove_notification(user_id, notification_id)
+    }
+
+    fn clone_box(&self) -> Box<dyn NotificationStore> {
+        Box::new(Self {
+            connection_pool: self.connection_pool.clone(),
+        })
+    }
+}
+
+/// Converts a `SystemTime` to the number of seconds since the epoch, as stored in the database
+fn to_timestamp(time: SystemTime) -> Result<i64, NotificationStoreError> {
+    time.duration_since(UNIX_EPOCH)
+        .map_err(|err| NotificationStoreError::Internal(InternalError::from_source(Box::new(err))))
+        .and_then(|duration| {
+            i64::try_from(duration.as_secs()).map_err(|_| {
+                NotificationStoreError::Internal(InternalError::with_message(
+                    "'created' timestamp could not be converted from u64 to i64".to_string(),
+                ))
+            })
+        })
+}
+
+/// Converts a number of seconds since the epoch, as stored in the database, to a `SystemTime`
+fn from_timestamp(timestamp: i64) -> Result<SystemTime, NotificationStoreError> {
+    u64::try_from(timestamp)
+        .ok()
+        .and_then(|secs| UNIX_EPOCH.checked_add(Duration::from_secs(secs)))
+        .ok_or_else(|| {
+            NotificationStoreError::Internal(InternalError::with_message(
+                "'created' timestamp could not be represented as a `SystemTime`".to_string(),
+            ))
+        })
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+pub mod tests {
+    use super::*;
+
+    use crate::biome::notifications::store::NotificationBuilder;
+    use crate::migrations::run_sqlite_migrations;
+
+    use diesel::{
+        prelude::*,
+        r2d2::{ConnectionManager, Pool},
+        sqlite::SqliteConnection,
+    };
+
+    /// Verify that a SQLite-backed `DieselNotificationStore` correctly supports adding and listing
+    /// notifications.
+    ///
+    /// 1. Create a connection pool for an in-memory SQLite database and run migrations.
+    /// 2. Create the `DieselNotificationStore`.
+    /// 3. Add two notifications, one of which is addressed to two users.
+    /// 4. Verify that `list_notifications` returns both notifications for the first user, newest
+    ///    first, with all fields intact.
+    /// 5. Verify that `list_notifications` only returns the shared notification for the second user.
+    /// 6. Verify that adding a notification with a duplicate ID returns an error.
+    #[test]
+    fn sqlite_add_and_list_notifications() {
+        let pool = create_connection_pool_and_migrate();
+
+        let notification_store = DieselNotificationStore::new(pool);
+
+        let shared = build_notification("shared", 10, &["alice", "bob"]);
+        notification_store
+            .add_notification(shared.clone())
+            .expect("Unable to add notification");
+        notification_store
+            .add_notification(build_notification("alice-only", 20, &["alice"]))
+            .expect("Unable to add notification");
+
+        let notifications = notification_store
+            .list_notifications("alice")
+            .expect("Unable to list notifications");
+        assert_eq!(notifications.len(), 2);
+        assert_eq!(notifications[0].notification().id(), "alice-only");
+        assert_eq!(notifications[1].notification(), &shared);
+        assert!(notifications.iter().all(|n| n.unread()));
+
+        let notifications = notification_store
+            .list_notifications("bob")
+            .expect("Unable to list notifications");
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].notification(), &shared);
+
+        assert!(notification_store
+            .add_notification(build_notification("shared", 30, &["bob"]))
+            .is_err());
+    }
+
+    /// Verify that a SQLite-backed `DieselNotificationStore` tracks the read state of a
+    /// notification separately for each recipient.
+    ///
+    /// 1. Create a connection pool for an in-memory SQLite database and run migrations.
+    /// 2. Create the `DieselNotificationStore`.
+    /// 3. Add a notification addressed to two users.
+    /// 4. Mark the notification as read for the first user.
+    /// 5. Verify that the notification is read for the first user but unread for the second.
+    /// 6. Verify that marking the notification as read for a user it is not addressed to returns
+    ///    an error.
+    #[test]
+    fn sqlite_mark_read() {
+        let pool = create_connection_pool_and_migrate();
+
+        let notification_store = DieselNotificationStore::new(pool);
+
+        notification_store
+            .add_notification(build_notification("id", 10, &["alice", "bob"]))
+            .expect("Unable to add notification");
+
+        notification_store
+            .mark_read("alice", "id")
+            .expect("Unable to mark notification as read");
+
+        assert!(!notification_store
+            .list_notifications("alice")
+            .expect("Unable to list notifications")[0]
+            .unread());
+        assert!(notification_store
+            .list_notifications("bob")
+            .expect("Unable to list notifications")[0]
+            .unread());
+
+        assert!(notification_store.mark_read("carol", "id").is_err());
+    }
+
+    /// Verify that a SQLite-backed `DieselNotificationStore` correctly supports removing
+    /// notifications.
+    ///
+    /// 1. Create a connection pool for an in-memory SQLite database and run migrations.
+    /// 2. Create the `DieselNotificationStore`.
+    /// 3. Add a notification addressed to two users.
+    /// 4. Remove the notification for the first user.
+    /// 5. Verify that the notification is no longer listed for the first user, but is still
+    ///    listed for the second.
+    /// 6. Verify that removing the notification again for the first user returns an error.
+    /// 7. Remove the notification for the second user and verify that it has been removed
+    ///    entirely.
+    #[test]
+    fn sqlite_remove_notification() {
+        let pool = create_connection_pool_and_migrate();
+
+        let notification_store = DieselNotificationStore::new(pool.clone());
+
+        notification_store
+            .add_notification(build_notification("id", 10, &["alice", "bob"]))
+            .expect("Unable to add notification");
+
+        notification_store
+            .remove_notification("alice", "id")
+            .expect("Unable to remove notification");
+
+        assert!(notification_store
+            .list_notifications("alice")
+            .expect("Unable to list notifications")
+            .is_empty());
+        assert_eq!(
+            notification_store
+                .list_notifications("bob")
+                .expect("Unable to list notifications")
+                .len(),
+            1
+        );
+
+        assert!(notification_store
+            .remove_notification("alice", "id")
+            .is_err());
+
+        notification_store
+            .remove_notification("bob", "id")
+            .expect("Unable to remove notification");
+
+        let conn = pool.get().expect("Failed to get connection");
+        assert_eq!(
+            schema::notifications::table
+                .count()
+                .get_result::<i64>(&*conn)
+                .expect("Failed to count notifications"),
+            0
+        );
+        assert_eq!(
+            schema::notification_properties::table
+                .count()
+                .get_result::<i64>(&*conn)
+                .expect("Failed to count notification properties"),
+            0
+        );
+    }
+
+    fn build_notification(id: &str, created: u64, recipients: &[&str]) -> Notification {
+        NotificationBuilder::new()
+            .with_id(id.into())
+            .with_title("title".into())
+            .with_body("body".into())
+            .with_created(UNIX_EPOCH + Duration::from_secs(created))
+            .with_properties(vec![
+                ("circuit_id".into(), "abcde-01234".into()),
+                ("event_type".into(), "ProposalSubmitted".into()),
+            ])
+            .with_recipients(recipients.iter().map(|r| r.to_string()).collect())
+            .build()
+            .expect("Unable to build notification")
+    }
+
+    /// Creates a connection pool for an in-memory SQLite database with only a single connection
+    /// available. Each connection is backed by a different in-memory SQLite database, so limiting
+    /// the pool to a single connection insures that the same DB is used for all operations.
+    fn create_connection_pool_and_migrate() -> Pool<ConnectionManager<SqliteConnection>> {
+        let connection_manager = ConnectionManager::<SqliteConnection>::new(":memory:");
+        let pool = Pool::builder()
+            .max_size(1)
+            .build(connection_manager)
+            .expect("Failed to build connection pool");
+
+        run_sqlite_migrations(&*pool.get().expect("Failed to get connection for migrations"))
+            .expect("Failed to run migrations");
+
+        pool
+    }
+}
@@ -15,33 +15,41 @@
  * -----------------------------------------------------------------------------
  */
 
-use std::time::SystemTime;
-
 use super::schema::{notification_properties, notifications, user_notifications};
 
-#[derive(Insertable, Queryable)]
+#[derive(Insertable, Queryable, Identifiable, PartialEq, Debug)]
 #[table_name = "notifications"]
-pub struct Notification {
+pub struct NotificationModel {
     pub id: String,
     pub payload_title: String,
     pub payload_body: String,
-    pub created: SystemTime,
-    pub recipients: Vec<String>,
+    pub created: i64,
 }
 
-#[derive(Insertable, Queryable)]
+#[derive(Insertable, Queryable, Identifiable, Associations, PartialEq, Debug)]
 #[table_name = "user_notifications"]
-pub struct UserNotification {
+#[belongs_to(NotificationModel, foreign_key = "notification_id")]
+#[primary_key(notification_id, user_id)]
+pub struct UserNotificationModel {
     pub notification_id: String,
     pub user_id: String,
     pub unread: bool,
 }
 
-#[derive(Insertable, Queryable)]
+#[derive(Queryable, Identifiable, Associations, PartialEq, Debug)]
 #[table_name = "notification_properties"]
-pub struct NotificationProperty {
+#[belongs_to(NotificationModel, foreign_key = "notification_id")]
+pub struct NotificationPropertyModel {
     pub id: i64,
     pub notification_id: String,
     pub property: String,
     pub property_value: String,
 }
+
+#[derive(Insertable, PartialEq, Debug)]
+#[table_name = "notification_properties"]
+pub struct NewNotificationPropertyModel {
+    pub notification_id: String,
+    pub property: String,
+    pub property_value: String,
+}
@@ -0,0 +1,126 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diesel::{dsl::insert_into, prelude::*};
+
+use crate::biome::notifications::store::{
+    diesel::{
+        models::{NewNotificationPropertyModel, NotificationModel, UserNotificationModel},
+        schema::{notification_properties, notifications, user_notifications},
+    },
+    Notification, NotificationStoreError,
+};
+
+use super::NotificationStoreOperations;
+
+pub trait NotificationStoreAddNotification {
+    fn add_notification(&self, notification: Notification) -> Result<(), NotificationStoreError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> NotificationStoreAddNotification
+    for NotificationStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn add_notification(&self, notification: Notification) -> Result<(), NotificationStoreError> {
+        let (notification, properties, recipients) = into_models(notification)?;
+
+        self.conn.transaction::<(), _, _>(|| {
+            insert_into(notifications::table)
+                .values(&notification)
+                .execute(self.conn)?;
+
+            insert_into(notification_properties::table)
+                .values(&properties)
+                .execute(self.conn)?;
+
+            insert_into(user_notifications::table)
+                .values(&recipients)
+                .execute(self.conn)?;
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> NotificationStoreAddNotification
+    for NotificationStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn add_notification(&self, notification: Notification) -> Result<(), NotificationStoreError> {
+        let (notification, properties, recipients) = into_models(notification)?;
+
+        self.conn.transaction::<(), _, _>(|| {
+            insert_into(notifications::table)
+                .values(&notification)
+                .execute(self.conn)?;
+
+            insert_into(notification_properties::table)
+                .values(&properties)
+                .execute(self.conn)?;
+
+            insert_into(user_notifications::table)
+                .values(&recipients)
+                .execute(self.conn)?;
+
+            Ok(())
+        })
+    }
+}
+
+/// Splits a notification into the models for each of the notification tables
+fn into_models(
+    notification: Notification,
+) -> Result<
+    (
+        NotificationModel,
+        Vec<NewNotificationPropertyModel>,
+        Vec<UserNotificationModel>,
+    ),
+    NotificationStoreError,
+> {
+    let id = notification.id;
+
+    let properties = notification
+        .properties
+        .into_iter()
+        .map(|(property, property_value)| NewNotificationPropertyModel {
+            notification_id: id.clone(),
+            property,
+            property_value,
+        })
+        .collect();
+
+    let recipients = notification
+        .recipients
+        .into_iter()
+        .map(|user_id| UserNotificationModel {
+            notification_id: id.clone(),
+            user_id,
+            unread: true,
+        })
+        .collect();
+
+    let created = super::super::to_timestamp(notification.created)?;
+
+    Ok((
+        NotificationModel {
+            id,
+            payload_title: notification.title,
+            payload_body: notification.body,
+            created,
+        },
+        properties,
+        recipients,
+    ))
+}
@@ -0,0 +1,104 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diesel::prelude::*;
+
+use crate::biome::notifications::store::{
+    diesel::{
+        models::{NotificationModel, NotificationPropertyModel, UserNotificationModel},
+        schema::{notification_properties, notifications, user_notifications},
+    },
+    Notification, NotificationStoreError, UserNotification,
+};
+
+use super::NotificationStoreOperations;
+
+pub trait NotificationStoreListNotifications {
+    fn list_notifications(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<UserNotification>, NotificationStoreError>;
+}
+
+impl<'a, C> NotificationStoreListNotifications for NotificationStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+    i64: diesel::deserialize::FromSql<diesel::sql_types::BigInt, C::Backend>,
+    String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
+    bool: diesel::deserialize::FromSql<diesel::sql_types::Bool, C::Backend>,
+    C::Backend: diesel::sql_types::HasSqlType<diesel::sql_types::Bool>,
+{
+    fn list_notifications(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<UserNotification>, NotificationStoreError> {
+        self.conn.transaction::<_, _, _>(|| {
+            let notifications = notifications::table
+                .inner_join(user_notifications::table)
+                .filter(user_notifications::user_id.eq(user_id))
+                .select(notifications::all_columns)
+                .order(notifications::created.desc())
+                .load::<NotificationModel>(self.conn)?;
+
+            // Avoid the extra queries, and an empty list in the `IN` statements generated by
+            // `eq_any`, if the user has no notifications
+            if notifications.is_empty() {
+                return Ok(vec![]);
+            }
+
+            let ids = notifications.iter().map(|n| &n.id).collect::<Vec<_>>();
+
+            let properties = notification_properties::table
+                .filter(notification_properties::notification_id.eq_any(&ids))
+                .order(notification_properties::id)
+                .load::<NotificationPropertyModel>(self.conn)?
+                .grouped_by(&notifications);
+            let recipients = user_notifications::table
+                .filter(user_notifications::notification_id.eq_any(&ids))
+                .load::<UserNotificationModel>(self.conn)?
+                .grouped_by(&notifications);
+
+            notifications
+                .into_iter()
+                .zip(properties)
+                .zip(recipients)
+                .map(|((notification, properties), recipients)| {
+                    let unread = recipients
+                        .iter()
+                        .find(|recipient| recipient.user_id == user_id)
+                        .map(|recipient| recipient.unread)
+                        .unwrap_or(false);
+
+                    Ok(UserNotification {
+                        notification: Notification {
+                            id: notification.id,
+                            title: notification.payload_title,
+                            body: notification.payload_body,
+                            created: super::super::from_timestamp(notification.created)?,
+                            properties: properties
+                                .into_iter()
+                                .map(|property| (property.property, property.property_value))
+                                .collect(),
+                            recipients: recipients
+                                .into_iter()
+                                .map(|recipient| recipient.user_id)
+                                .collect(),
+                        },
+                        unread,
+                    })
+                })
+                .collect()
+        })
+    }
+}
@@ -0,0 +1,59 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diesel::{dsl::update, prelude::*};
+
+use crate::biome::notifications::store::{
+    diesel::schema::user_notifications, NotificationStoreError,
+};
+use crate::error::InvalidArgumentError;
+
+use super::NotificationStoreOperations;
+
+pub trait NotificationStoreMarkRead {
+    fn mark_read(&self, user_id: &str, notification_id: &str)
+        -> Result<(), NotificationStoreError>;
+}
+
+impl<'a, C> NotificationStoreMarkRead for NotificationStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+    C::Backend: diesel::sql_types::HasSqlType<diesel::sql_types::Bool>,
+    bool: diesel::serialize::ToSql<diesel::sql_types::Bool, C::Backend>,
+{
+    fn mark_read(
+        &self,
+        user_id: &str,
+        notification_id: &str,
+    ) -> Result<(), NotificationStoreError> {
+        let updated = update(
+            user_notifications::table
+                .filter(user_notifications::notification_id.eq(notification_id))
+                .filter(user_notifications::user_id.eq(user_id)),
+        )
+        .set(user_notifications::unread.eq(false))
+        .execute(self.conn)?;
+
+        if updated == 0 {
+            return Err(NotificationStoreError::InvalidArgument(
+                InvalidArgumentError::new(
+                    "notification_id".to_string(),
+                    "A notification with the given ID does not exist for the user".to_string(),
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,269 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A memory-backed implementation of the [NotificationStore]
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::error::{
+    ConstraintViolationError, ConstraintViolationType, InternalError, InvalidArgumentError,
+};
+
+use super::{Notification, NotificationStore, NotificationStoreError, UserNotification};
+
+#[derive(Default)]
+struct Inner {
+    notifications: HashMap<String, Notification>,
+    // The unread flag of each notification for each of its recipients, keyed by
+    // (user_id, notification_id)
+    user_notifications: HashMap<(String, String), bool>,
+}
+
+#[derive(Default, Clone)]
+pub struct MemoryNotificationStore {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl MemoryNotificationStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl NotificationStore for MemoryNotificationStore {
+    fn add_notification(&self, notification: Notification) -> Result<(), NotificationStoreError> {
+        let mut inner = self.inner.lock().map_err(|_| {
+            NotificationStoreError::Internal(InternalError::with_message(
+                "Cannot access notification store: mutex lock poisoned".to_string(),
+            ))
+        })?;
+
+        if inner.notifications.contains_key(&notification.id) {
+            return Err(NotificationStoreError::ConstraintViolation(
+                ConstraintViolationError::with_violation_type(ConstraintViolationType::Unique),
+            ));
+        }
+
+        for recipient in notification.recipients.iter() {
+            inner
+                .user_notifications
+                .insert((recipient.clone(), notification.id.clone()), true);
+        }
+        inner
+            .notifications
+            .insert(notification.id.clone(), notification);
+        Ok(())
+    }
+
+    fn list_notifications(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<UserNotification>, NotificationStoreError> {
+        let inner = self.inner.lock().map_err(|_| {
+            NotificationStoreError::Internal(InternalError::with_message(
+                "Cannot access notification store: mutex lock poisoned".to_string(),
+            ))
+        })?;
+
+        let mut notifications = inner
+            .user_notifications
+            .iter()
+            .filter(|((recipient, _), _)| recipient == user_id)
+            .filter_map(|((_, notification_id), unread)| {
+                inner
+                    .notifications
+                    .get(notification_id)
+                    .map(|notification| UserNotification {
+                        notification: notification.clone(),
+                        unread: *unread,
+                    })
+            })
+            .collect::<Vec<_>>();
+        notifications.sort_by_key(|n| Reverse(n.notification.created));
+
+        Ok(notifications)
+    }
+
+    fn mark_read(
+        &self,
+        user_id: &str,
+        notification_id: &str,
+    ) -> Result<(), NotificationStoreError> {
+        let mut inner = self.inner.lock().map_err(|_| {
+            NotificationStoreError::Internal(InternalError::with_message(
+                "Cannot access notification store: mutex lock poisoned".to_string(),
+            ))
+        })?;
+
+        match inner
+            .user_notifications
+            .get_mut(&(user_id.to_string(), notification_id.to_string()))
+        {
+            Some(unread) => {
+                *unread = false;
+                Ok(())
+            }
+            None => Err(NotificationStoreError::InvalidArgument(
+                InvalidArgumentError::new(
+                    "notification_id".to_string(),
+                    "A notification with the given ID does not exist for the user".to_string(),
+                ),
+            )),
+        }
+    }
+
+    fn remove_notification(
+        &self,
+        user_id: &str,
+        notification_id: &str,
+    ) -> Result<(), NotificationStoreError> {
+        let mut inner = self.inner.lock().map_err(|_| {
+            NotificationStoreError::Internal(InternalError::with_message(
+                "Cannot access notification store: mutex lock poisoned".to_string(),
+            ))
+        })?;
+
+        if inner
+            .user_notifications
+            .remove(&(user_id.to_string(), notification_id.to_string()))
+            .is_none()
+        {
+            return Err(NotificationStoreError::InvalidArgument(
+                InvalidArgumentError::new(
+                    "notification_id".to_string(),
+                    "A notification with the given ID does not exist for the user".to_string(),
+                ),
+            ));
+        }
+
+        // Remove the notification itself once no recipients remain
+        if !inner
+            .user_notifications
+            .keys()
+            .any(|(_, id)| id == notification_id)
+        {
+            inner.notifications.remove(notification_id);
+        }
+
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn NotificationStore> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use crate::biome::notifications::store::NotificationBuilder;
+
+    /// Verify that the `MemoryNotificationStore` lists each user's notifications, newest first,
+    /// and tracks the read state of a notification separately for each recipient.
+    #[test]
+    fn memory_list_and_mark_read() {
+        let store = MemoryNotificationStore::new();
+
+        store
+            .add_notification(build_notification("old", 10, &["alice", "bob"]))
+            .expect("Unable to add notification");
+        store
+            .add_notification(build_notification("new", 20, &["alice"]))
+            .expect("Unable to add notification");
+
+        assert!(store
+            .add_notification(build_notification("old", 30, &["alice"]))
+            .is_err());
+
+        let notifications = store
+            .list_notifications("alice")
+            .expect("Unable to list notifications");
+        assert_eq!(
+            notifications
+                .iter()
+                .map(|n| n.notification().id())
+                .collect::<Vec<_>>(),
+            vec!["new", "old"]
+        );
+        assert!(notifications.iter().all(|n| n.unread()));
+
+        store
+            .mark_read("alice", "old")
+            .expect("Unable to mark read");
+        assert!(store.mark_read("carol", "old").is_err());
+
+        let alice = store
+            .list_notifications("alice")
+            .expect("Unable to list notifications");
+        assert!(!alice[1].unread());
+        let bob = store
+            .list_notifications("bob")
+            .expect("Unable to list notifications");
+        assert_eq!(bob.len(), 1);
+        assert!(bob[0].unread());
+    }
+
+    /// Verify that removing a notification for one recipient leaves it in place for the others,
+    /// and that it is removed entirely once all recipients have removed it.
+    #[test]
+    fn memory_remove_notification() {
+        let store = MemoryNotificationStore::new();
+
+        store
+            .add_notification(build_notification("id", 10, &["alice", "bob"]))
+            .expect("Unable to add notification");
+
+        store
+            .remove_notification("alice", "id")
+            .expect("Unable to remove notification");
+        assert!(store.remove_notification("alice", "id").is_err());
+        assert!(store
+            .list_notifications("alice")
+            .expect("Unable to list notifications")
+            .is_empty());
+        assert_eq!(
+            store
+                .list_notifications("bob")
+                .expect("Unable to list notifications")
+                .len(),
+            1
+        );
+
+        store
+            .remove_notification("bob", "id")
+            .expect("Unable to remove notification");
+        assert!(store
+            .inner
+            .lock()
+            .expect("lock poisoned")
+            .notifications
+            .is_empty());
+    }
+
+    fn build_notification(id: &str, created: u64, recipients: &[&str]) -> Notification {
+        NotificationBuilder::new()
+            .with_id(id.into())
+            .with_title("title".into())
+            .with_body("body".into())
+            .with_created(UNIX_EPOCH + Duration::from_secs(created))
+            .with_recipients(recipients.iter().map(|r| r.to_string()).collect())
+            .build()
+            .expect("Unable to build notification")
+    }
+}
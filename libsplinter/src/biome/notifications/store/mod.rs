@@ -12,7 +12,273 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-//! Defines a basic representation of a notification.
+//! Defines a basic representation of a notification and an API to manage notifications.
 
-#[cfg(feature = "diesel")]
+#[cfg(any(feature = "postgres", feature = "sqlite"))]
 pub(in crate::biome) mod diesel;
+pub mod error;
+pub(in crate::biome) mod memory;
+
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::InvalidStateError;
+
+pub use error::NotificationStoreError;
+
+/// A notification that is addressed to one or more Biome users
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Notification {
+    id: String,
+    title: String,
+    body: String,
+    created: SystemTime,
+    properties: Vec<(String, String)>,
+    recipients: Vec<String>,
+}
+
+impl Notification {
+    /// Returns the ID of the notification
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the title of the notification
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Returns the body of the notification
+    pub fn body(&self) -> &str {
+        &self.body
+    }
+
+    /// Returns the time the notification was created
+    pub fn created(&self) -> SystemTime {
+        self.created
+    }
+
+    /// Returns the properties of the notification as key/value pairs
+    pub fn properties(&self) -> &[(String, String)] {
+        &self.properties
+    }
+
+    /// Returns the user IDs of the users the notification is addressed to
+    pub fn recipients(&self) -> &[String] {
+        &self.recipients
+    }
+}
+
+/// Builder for `Notification`.
+#[derive(Default)]
+pub struct NotificationBuilder {
+    id: Option<String>,
+    title: Option<String>,
+    body: Option<String>,
+    created: Option<SystemTime>,
+    properties: Vec<(String, String)>,
+    recipients: Vec<String>,
+}
+
+impl NotificationBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the ID for the notification
+    pub fn with_id(mut self, id: String) -> NotificationBuilder {
+        self.id = Some(id);
+        self
+    }
+
+    /// Sets the title for the notification
+    pub fn with_title(mut self, title: String) -> NotificationBuilder {
+        self.title = Some(title);
+        self
+    }
+
+    /// Sets the body for the notification
+    pub fn with_body(mut self, body: String) -> NotificationBuilder {
+        self.body = Some(body);
+        self
+    }
+
+    /// Sets the creation time for the notification; defaults to the time the notification is built
+    pub fn with_created(mut self, created: SystemTime) -> NotificationBuilder {
+        self.created = Some(created);
+        self
+    }
+
+    /// Sets the properties for the notification
+    pub fn with_properties(mut self, properties: Vec<(String, String)>) -> NotificationBuilder {
+        self.properties = properties;
+        self
+    }
+
+    /// Sets the user IDs of the users the notification is addressed to
+    pub fn with_recipients(mut self, recipients: Vec<String>) -> NotificationBuilder {
+        self.recipients = recipients;
+        self
+    }
+
+    /// Builds the notification
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidStateError` if the ID, title, or body is not set, or if no recipients
+    /// are set.
+    pub fn build(self) -> Result<Notification, InvalidStateError> {
+        let id = self.id.ok_or_else(|| {
+            InvalidStateError::with_message("An id is required to build a Notification".into())
+        })?;
+        let title = self.title.ok_or_else(|| {
+            InvalidStateError::with_message("A title is required to build a Notification".into())
+        })?;
+        let body = self.body.ok_or_else(|| {
+            InvalidStateError::with_message("A body is required to build a Notification".into())
+        })?;
+
+        if self.recipients.is_empty() {
+            return Err(InvalidStateError::with_message(
+                "At least one recipient is required to build a Notification".into(),
+            ));
+        }
+
+        Ok(Notification {
+            id,
+            title,
+            body,
+            created: self.created.unwrap_or_else(SystemTime::now),
+            properties: self.properties,
+            recipients: self.recipients,
+        })
+    }
+}
+
+/// A notification as seen by one of its recipients
+#[derive(Clone, Debug, PartialEq)]
+pub struct UserNotification {
+    notification: Notification,
+    unread: bool,
+}
+
+impl UserNotification {
+    /// Returns the notification
+    pub fn notification(&self) -> &Notification {
+        &self.notification
+    }
+
+    /// Returns whether or not the recipient has read the notification
+    pub fn unread(&self) -> bool {
+        self.unread
+    }
+}
+
+/// Defines methods for creating, reading, and removing notifications without defining a storage
+/// strategy
+pub trait NotificationStore: Sync + Send {
+    /// Adds a notification to the underlying storage. The notification will be unread for each of
+    /// its recipients.
+    ///
+    /// # Arguments
+    ///
+    ///  * `notification` - The notification to be added
+    ///
+    /// # Errors
+    ///
+    /// Returns a NotificationStoreError if the implementation cannot add the notification or if a
+    /// notification with the same ID already exists.
+    fn add_notification(&self, notification: Notification) -> Result<(), NotificationStoreError>;
+
+    /// Lists the notifications addressed to a user, newest first.
+    ///
+    /// # Arguments
+    ///
+    ///  * `user_id` - The unique identifier of the user
+    ///
+    /// # Errors
+    ///
+    /// Returns a NotificationStoreError if the implementation cannot fetch the notifications.
+    fn list_notifications(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<UserNotification>, NotificationStoreError>;
+
+    /// Marks a notification as read for a user.
+    ///
+    /// # Arguments
+    ///
+    ///  * `user_id` - The unique identifier of the user
+    ///  * `notification_id` - The unique identifier of the notification
+    ///
+    /// # Errors
+    ///
+    /// Returns a NotificationStoreError if the implementation cannot update the notification or
+    /// if the notification is not addressed to the user.
+    fn mark_read(&self, user_id: &str, notification_id: &str)
+        -> Result<(), NotificationStoreError>;
+
+    /// Removes a notification for a user. The notification itself is removed once it has been
+    /// removed for all of its recipients.
+    ///
+    /// # Arguments
+    ///
+    ///  * `user_id` - The unique identifier of the user
+    ///  * `notification_id` - The unique identifier of the notification
+    ///
+    /// # Errors
+    ///
+    /// Returns a NotificationStoreError if the implementation cannot remove the notification or
+    /// if the notification is not addressed to the user.
+    fn remove_notification(
+        &self,
+        user_id: &str,
+        notification_id: &str,
+    ) -> Result<(), NotificationStoreError>;
+
+    /// Clone into a boxed, dynamically dispatched store
+    fn clone_box(&self) -> Box<dyn NotificationStore>;
+}
+
+impl Clone for Box<dyn NotificationStore> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+impl<NS> NotificationStore for Box<NS>
+where
+    NS: NotificationStore + ?Sized,
+{
+    fn add_notification(&self, notification: Notification) -> Result<(), NotificationStoreError> {
+        (**self).add_notification(notification)
+    }
+
+    fn list_notifications(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<UserNotification>, NotificationStoreError> {
+        (**self).list_notifications(user_id)
+    }
+
+    fn mark_read(
+        &self,
+        user_id: &str,
+        notification_id: &str,
+    ) -> Result<(), NotificationStoreError> {
+        (**self).mark_read(user_id, notification_id)
+    }
+
+    fn remove_notification(
+        &self,
+        user_id: &str,
+        notification_id: &str,
+    ) -> Result<(), NotificationStoreError> {
+        (**self).remove_notification(user_id, notification_id)
+    }
+
+    fn clone_box(&self) -> Box<dyn NotificationStore> {
+        (**self).clone_box()
+    }
+}
@@ -16,10 +16,26 @@ mod models;
 mod operations;
 mod schema;
 
+use std::convert::TryFrom;
+#[cfg(feature = "biome-sessions")]
+use std::time::Duration;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use diesel::r2d2::{ConnectionManager, Pool};
 
+#[cfg(feature = "biome-sessions")]
+use crate::biome::refresh_tokens::store::RefreshTokenSession;
 use crate::biome::refresh_tokens::store::{RefreshTokenError, RefreshTokenStore};
 
+#[cfg(feature = "biome-sessions")]
+use models::RefreshToken;
+#[cfg(feature = "biome-sessions")]
+use operations::{
+    add_session::RefreshTokenStoreAddSessionOperation,
+    list_sessions::RefreshTokenStoreListSessionsOperation,
+    remove_session::RefreshTokenStoreRemoveSessionOperation,
+    update_last_used::RefreshTokenStoreUpdateLastUsedOperation,
+};
 use operations::{
     add_token::RefreshTokenStoreAddTokenOperation,
     fetch_token::RefreshTokenStoreFetchTokenOperation,
@@ -51,6 +67,29 @@ impl RefreshTokenStore for DieselRefreshTokenStore<diesel::pg::PgConnection> {
     fn fetch_token(&self, user_id: &str) -> Result<String, RefreshTokenError> {
         RefreshTokenStoreOperations::new(&*self.connection_pool.get()?).fetch_token(user_id)
     }
+    #[cfg(feature = "biome-sessions")]
+    fn add_session(&self, session: RefreshTokenSession) -> Result<(), RefreshTokenError> {
+        RefreshTokenStoreOperations::new(&*self.connection_pool.get()?).add_session(session)
+    }
+    #[cfg(feature = "biome-sessions")]
+    fn list_sessions(&self, user_id: &str) -> Result<Vec<RefreshTokenSession>, RefreshTokenError> {
+        RefreshTokenStoreOperations::new(&*self.connection_pool.get()?).list_sessions(user_id)
+    }
+    #[cfg(feature = "biome-sessions")]
+    fn update_last_used(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        last_used: SystemTime,
+    ) -> Result<(), RefreshTokenError> {
+        RefreshTokenStoreOperations::new(&*self.connection_pool.get()?)
+            .update_last_used(user_id, session_id, last_used)
+    }
+    #[cfg(feature = "biome-sessions")]
+    fn remove_session(&self, user_id: &str, session_id: &str) -> Result<(), RefreshTokenError> {
+        RefreshTokenStoreOperations::new(&*self.connection_pool.get()?)
+            .remove_session(user_id, session_id)
+    }
 }
 
 #[cfg(feature = "sqlite")]
@@ -67,12 +106,81 @@ impl RefreshTokenStore for DieselRefreshTokenStore<diesel::sqlite::SqliteConnect
     fn fetch_token(&self, user_id: &str) -> Result<String, RefreshTokenError> {
         RefreshTokenStoreOperations::new(&*self.connection_pool.get()?).fetch_token(user_id)
     }
+    #[cfg(feature = "biome-sessions")]
+    fn add_session(&self, session: RefreshTokenSession) -> Result<(), RefreshTokenError> {
+        RefreshTokenStoreOperations::new(&*self.connection_pool.get()?).add_session(session)
+    }
+    #[cfg(feature = "biome-sessions")]
+    fn list_sessions(&self, user_id: &str) -> Result<Vec<RefreshTokenSession>, RefreshTokenError> {
+        RefreshTokenStoreOperations::new(&*self.connection_pool.get()?).list_sessions(user_id)
+    }
+    #[cfg(feature = "biome-sessions")]
+    fn update_last_used(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        last_used: SystemTime,
+    ) -> Result<(), RefreshTokenError> {
+        RefreshTokenStoreOperations::new(&*self.connection_pool.get()?)
+            .update_last_used(user_id, session_id, last_used)
+    }
+    #[cfg(feature = "biome-sessions")]
+    fn remove_session(&self, user_id: &str, session_id: &str) -> Result<(), RefreshTokenError> {
+        RefreshTokenStoreOperations::new(&*self.connection_pool.get()?)
+            .remove_session(user_id, session_id)
+    }
+}
+
+#[cfg(feature = "biome-sessions")]
+impl TryFrom<RefreshToken> for RefreshTokenSession {
+    type Error = RefreshTokenError;
+
+    fn try_from(token: RefreshToken) -> Result<Self, Self::Error> {
+        Ok(RefreshTokenSession {
+            session_id: token.session_id,
+            user_id: token.user_id,
+            token: token.token,
+            display_name: token.display_name,
+            user_agent: token.user_agent,
+            created: from_timestamp(token.created)?,
+            last_used: from_timestamp(token.last_used)?,
+        })
+    }
+}
+
+/// Converts a `SystemTime` to the number of seconds since the epoch, as stored in the database
+fn to_timestamp(time: SystemTime) -> Result<i64, RefreshTokenError> {
+    time.duration_since(UNIX_EPOCH)
+        .map_err(|err| RefreshTokenError::StorageError {
+            context: "Session timestamp is earlier than the epoch".to_string(),
+            source: Some(Box::new(err)),
+        })
+        .and_then(|duration| {
+            i64::try_from(duration.as_secs()).map_err(|err| RefreshTokenError::StorageError {
+                context: "Session timestamp could not be converted from u64 to i64".to_string(),
+                source: Some(Box::new(err)),
+            })
+        })
+}
+
+/// Converts a number of seconds since the epoch, as stored in the database, to a `SystemTime`
+#[cfg(feature = "biome-sessions")]
+fn from_timestamp(timestamp: i64) -> Result<SystemTime, RefreshTokenError> {
+    u64::try_from(timestamp)
+        .ok()
+        .and_then(|secs| UNIX_EPOCH.checked_add(Duration::from_secs(secs)))
+        .ok_or_else(|| RefreshTokenError::StorageError {
+            context: "Session timestamp could not be represented as a `SystemTime`".to_string(),
+            source: None,
+        })
 }
 
 #[cfg(all(test, feature = "sqlite"))]
 pub mod tests {
     use super::*;
 
+    #[cfg(feature = "biome-sessions")]
+    use crate::biome::refresh_tokens::store::RefreshTokenSessionBuilder;
     use crate::migrations::run_sqlite_migrations;
 
     use diesel::{
@@ -190,6 +298,93 @@ pub mod tests {
         }
     }
 
+    /// Verify that a SQLite-backed `DieselRefreshTokenStore` correctly supports several sessions
+    /// per user.
+    ///
+    /// 1. Create a connection pool for an in-memory SQLite database and run migrations.
+    /// 2. Create the `DieselRefreshTokenStore`.
+    /// 3. Add two sessions for a user and one for another user.
+    /// 4. Verify that the user's sessions are listed most recently used first, with their
+    ///    metadata.
+    /// 5. Update the last used time of the older session and verify that it is listed first.
+    /// 6. Remove a single session and verify that the user's other session remains.
+    /// 7. Verify that removing a non-existent session returns a
+    ///    `RefreshTokenError::NotFoundError`.
+    #[cfg(feature = "biome-sessions")]
+    #[test]
+    fn sqlite_sessions() {
+        let pool = create_connection_pool_and_migrate();
+
+        let store = DieselRefreshTokenStore::new(pool);
+
+        let laptop = RefreshTokenSessionBuilder::new()
+            .with_session_id("laptop".into())
+            .with_user_id("user".into())
+            .with_token("token1".into())
+            .with_display_name("Laptop".into())
+            .with_user_agent("Mozilla/5.0".into())
+            .with_created(UNIX_EPOCH + Duration::from_secs(10))
+            .build()
+            .expect("Failed to build session");
+        let phone = RefreshTokenSessionBuilder::new()
+            .with_session_id("phone".into())
+            .with_user_id("user".into())
+            .with_token("token2".into())
+            .with_created(UNIX_EPOCH + Duration::from_secs(20))
+            .build()
+            .expect("Failed to build session");
+
+        store
+            .add_session(laptop.clone())
+            .expect("Failed to add session");
+        store
+            .add_session(phone.clone())
+            .expect("Failed to add session");
+        store
+            .add_token("other", "token3")
+            .expect("Failed to add token3");
+
+        assert_eq!(
+            store
+                .list_sessions("user")
+                .expect("Failed to list sessions"),
+            vec![phone.clone(), laptop],
+        );
+
+        store
+            .update_last_used("user", "laptop", UNIX_EPOCH + Duration::from_secs(30))
+            .expect("Failed to update last used");
+        assert_eq!(
+            store.fetch_token("user").expect("Failed to fetch token"),
+            "token1",
+        );
+
+        store
+            .remove_session("user", "laptop")
+            .expect("Failed to remove session");
+        assert_eq!(
+            store
+                .list_sessions("user")
+                .expect("Failed to list sessions"),
+            vec![phone],
+        );
+        assert_eq!(
+            store
+                .list_sessions("other")
+                .expect("Failed to list sessions")
+                .len(),
+            1,
+        );
+
+        match store.remove_session("user", "laptop") {
+            Err(RefreshTokenError::NotFoundError(_)) => {}
+            res => panic!(
+                "Expected Err(RefreshTokenError::NotFoundError), got {:?} instead",
+                res
+            ),
+        }
+    }
+
     /// Creates a conneciton pool for an in-memory SQLite database with only a single connection
     /// available. Each connection is backed by a different in-memory SQLite database, so limiting
     /// the pool to a single connection insures that the same DB is used for all operations.
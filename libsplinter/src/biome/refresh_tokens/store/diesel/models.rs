@@ -21,6 +21,11 @@ pub struct RefreshToken {
     pub id: i64,
     pub user_id: String,
     pub token: String,
+    pub session_id: String,
+    pub display_name: Option<String>,
+    pub user_agent: Option<String>,
+    pub created: i64,
+    pub last_used: i64,
 }
 
 #[derive(AsChangeset, Insertable, PartialEq, Debug)]
@@ -29,3 +34,15 @@ pub struct NewRefreshToken<'a> {
     pub user_id: &'a str,
     pub token: &'a str,
 }
+
+#[derive(Insertable, PartialEq, Debug)]
+#[table_name = "refresh_tokens"]
+pub struct NewRefreshTokenSession<'a> {
+    pub user_id: &'a str,
+    pub token: &'a str,
+    pub session_id: &'a str,
+    pub display_name: Option<&'a str>,
+    pub user_agent: Option<&'a str>,
+    pub created: i64,
+    pub last_used: i64,
+}
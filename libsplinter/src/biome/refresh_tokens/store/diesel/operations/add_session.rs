@@ -0,0 +1,71 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use super::RefreshTokenStoreOperations;
+use crate::biome::refresh_tokens::store::{
+    diesel::{models::NewRefreshTokenSession, schema::refresh_tokens, to_timestamp},
+    RefreshTokenError, RefreshTokenSession,
+};
+use diesel::{dsl::insert_into, prelude::*};
+
+pub(in crate::biome) trait RefreshTokenStoreAddSessionOperation {
+    fn add_session(&self, session: RefreshTokenSession) -> Result<(), RefreshTokenError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> RefreshTokenStoreAddSessionOperation
+    for RefreshTokenStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn add_session(&self, session: RefreshTokenSession) -> Result<(), RefreshTokenError> {
+        insert_into(refresh_tokens::table)
+            .values(NewRefreshTokenSession {
+                user_id: &session.user_id,
+                token: &session.token,
+                session_id: &session.session_id,
+                display_name: session.display_name.as_deref(),
+                user_agent: session.user_agent.as_deref(),
+                created: to_timestamp(session.created)?,
+                last_used: to_timestamp(session.last_used)?,
+            })
+            .execute(self.conn)
+            .map_err(|err| RefreshTokenError::OperationError {
+                context: "Failed to create session".to_string(),
+                source: Box::new(err),
+            })?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> RefreshTokenStoreAddSessionOperation
+    for RefreshTokenStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn add_session(&self, session: RefreshTokenSession) -> Result<(), RefreshTokenError> {
+        insert_into(refresh_tokens::table)
+            .values(NewRefreshTokenSession {
+                user_id: &session.user_id,
+                token: &session.token,
+                session_id: &session.session_id,
+                display_name: session.display_name.as_deref(),
+                user_agent: session.user_agent.as_deref(),
+                created: to_timestamp(session.created)?,
+                last_used: to_timestamp(session.last_used)?,
+            })
+            .execute(self.conn)
+            .map_err(|err| RefreshTokenError::OperationError {
+                context: "Failed to create session".to_string(),
+                source: Box::new(err),
+            })?;
+        Ok(())
+    }
+}
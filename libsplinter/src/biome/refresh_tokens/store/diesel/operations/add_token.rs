@@ -12,12 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::time::SystemTime;
+
 use super::RefreshTokenStoreOperations;
 use crate::biome::refresh_tokens::store::{
-    diesel::{models::NewRefreshToken, schema::refresh_tokens},
+    diesel::{models::NewRefreshTokenSession, schema::refresh_tokens, to_timestamp},
     RefreshTokenError,
 };
 use diesel::{dsl::insert_into, prelude::*};
+use uuid::Uuid;
 
 pub(in crate::biome) trait RefreshTokenStoreAddTokenOperation {
     fn add_token(&self, user_id: &str, token: &str) -> Result<(), RefreshTokenError>;
@@ -28,8 +31,17 @@ impl<'a> RefreshTokenStoreAddTokenOperation
     for RefreshTokenStoreOperations<'a, diesel::pg::PgConnection>
 {
     fn add_token(&self, user_id: &str, token: &str) -> Result<(), RefreshTokenError> {
+        let now = to_timestamp(SystemTime::now())?;
         insert_into(refresh_tokens::table)
-            .values(NewRefreshToken { user_id, token })
+            .values(NewRefreshTokenSession {
+                user_id,
+                token,
+                session_id: &Uuid::new_v4().to_string(),
+                display_name: None,
+                user_agent: None,
+                created: now,
+                last_used: now,
+            })
             .execute(self.conn)
             .map_err(|err| RefreshTokenError::OperationError {
                 context: "Failed to create token".to_string(),
@@ -44,8 +56,17 @@ impl<'a> RefreshTokenStoreAddTokenOperation
     for RefreshTokenStoreOperations<'a, diesel::sqlite::SqliteConnection>
 {
     fn add_token(&self, user_id: &str, token: &str) -> Result<(), RefreshTokenError> {
+        let now = to_timestamp(SystemTime::now())?;
         insert_into(refresh_tokens::table)
-            .values(NewRefreshToken { user_id, token })
+            .values(NewRefreshTokenSession {
+                user_id,
+                token,
+                session_id: &Uuid::new_v4().to_string(),
+                display_name: None,
+                user_agent: None,
+                created: now,
+                last_used: now,
+            })
             .execute(self.conn)
             .map_err(|err| RefreshTokenError::OperationError {
                 context: "Failed to create token".to_string(),
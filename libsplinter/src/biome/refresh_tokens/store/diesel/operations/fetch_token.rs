@@ -33,6 +33,7 @@ where
         refresh_tokens::table
             .select(refresh_tokens::all_columns)
             .filter(refresh_tokens::user_id.eq(user_id))
+            .order((refresh_tokens::last_used.desc(), refresh_tokens::id.desc()))
             .first::<RefreshToken>(self.conn)
             .map(|t| t.token)
             .map_err(|err| {
@@ -0,0 +1,47 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::convert::TryFrom;
+
+use super::RefreshTokenStoreOperations;
+use crate::biome::refresh_tokens::store::{
+    diesel::{models::RefreshToken, schema::refresh_tokens},
+    RefreshTokenError, RefreshTokenSession,
+};
+use diesel::prelude::*;
+
+pub(in crate::biome) trait RefreshTokenStoreListSessionsOperation {
+    fn list_sessions(&self, user_id: &str) -> Result<Vec<RefreshTokenSession>, RefreshTokenError>;
+}
+
+impl<'a, C> RefreshTokenStoreListSessionsOperation for RefreshTokenStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+    i64: diesel::deserialize::FromSql<diesel::sql_types::BigInt, C::Backend>,
+    String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
+{
+    fn list_sessions(&self, user_id: &str) -> Result<Vec<RefreshTokenSession>, RefreshTokenError> {
+        refresh_tokens::table
+            .select(refresh_tokens::all_columns)
+            .filter(refresh_tokens::user_id.eq(user_id))
+            .order((refresh_tokens::last_used.desc(), refresh_tokens::id.desc()))
+            .load::<RefreshToken>(self.conn)
+            .map_err(|err| RefreshTokenError::OperationError {
+                context: format!("Failed to list sessions for user {}", user_id),
+                source: Box::new(err),
+            })?
+            .into_iter()
+            .map(RefreshTokenSession::try_from)
+            .collect()
+    }
+}
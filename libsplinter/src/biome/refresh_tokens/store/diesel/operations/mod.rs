@@ -12,9 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "biome-sessions")]
+pub(super) mod add_session;
 pub(super) mod add_token;
 pub(super) mod fetch_token;
+#[cfg(feature = "biome-sessions")]
+pub(super) mod list_sessions;
+#[cfg(feature = "biome-sessions")]
+pub(super) mod remove_session;
 pub(super) mod remove_token;
+#[cfg(feature = "biome-sessions")]
+pub(super) mod update_last_used;
 pub(super) mod update_token;
 
 pub(super) struct RefreshTokenStoreOperations<'a, C> {
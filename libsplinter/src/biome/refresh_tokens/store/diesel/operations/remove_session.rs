@@ -0,0 +1,50 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use super::RefreshTokenStoreOperations;
+use crate::biome::refresh_tokens::store::{diesel::schema::refresh_tokens, RefreshTokenError};
+use diesel::{dsl::delete, prelude::*};
+
+pub(in crate::biome) trait RefreshTokenStoreRemoveSessionOperation {
+    fn remove_session(&self, user_id: &str, session_id: &str) -> Result<(), RefreshTokenError>;
+}
+
+impl<'a, C> RefreshTokenStoreRemoveSessionOperation for RefreshTokenStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+{
+    fn remove_session(&self, user_id: &str, session_id: &str) -> Result<(), RefreshTokenError> {
+        let deleted = delete(
+            refresh_tokens::table
+                .filter(refresh_tokens::user_id.eq(user_id))
+                .filter(refresh_tokens::session_id.eq(session_id)),
+        )
+        .execute(self.conn)
+        .map_err(|err| RefreshTokenError::OperationError {
+            context: format!(
+                "Failed to delete session {} for user {}",
+                session_id, user_id
+            ),
+            source: Box::new(err),
+        })?;
+
+        if deleted == 0 {
+            return Err(RefreshTokenError::NotFoundError(format!(
+                "Session {} not found for user {}",
+                session_id, user_id
+            )));
+        }
+
+        Ok(())
+    }
+}
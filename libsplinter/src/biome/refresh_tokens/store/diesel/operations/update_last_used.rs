@@ -0,0 +1,67 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::time::SystemTime;
+
+use super::RefreshTokenStoreOperations;
+use crate::biome::refresh_tokens::store::{
+    diesel::{schema::refresh_tokens, to_timestamp},
+    RefreshTokenError,
+};
+use diesel::{dsl::update, prelude::*};
+
+pub(in crate::biome) trait RefreshTokenStoreUpdateLastUsedOperation {
+    fn update_last_used(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        last_used: SystemTime,
+    ) -> Result<(), RefreshTokenError>;
+}
+
+impl<'a, C> RefreshTokenStoreUpdateLastUsedOperation for RefreshTokenStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+    i64: diesel::serialize::ToSql<diesel::sql_types::BigInt, C::Backend>,
+{
+    fn update_last_used(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        last_used: SystemTime,
+    ) -> Result<(), RefreshTokenError> {
+        let updated = update(
+            refresh_tokens::table
+                .filter(refresh_tokens::user_id.eq(user_id))
+                .filter(refresh_tokens::session_id.eq(session_id)),
+        )
+        .set(refresh_tokens::last_used.eq(to_timestamp(last_used)?))
+        .execute(self.conn)
+        .map_err(|err| RefreshTokenError::OperationError {
+            context: format!(
+                "Failed to update session {} for user {}",
+                session_id, user_id
+            ),
+            source: Box::new(err),
+        })?;
+
+        if updated == 0 {
+            return Err(RefreshTokenError::NotFoundError(format!(
+                "Session {} not found for user {}",
+                session_id, user_id
+            )));
+        }
+
+        Ok(())
+    }
+}
@@ -17,5 +17,10 @@ table! {
         id -> Int8,
         user_id -> Text,
         token -> Text,
+        session_id -> Text,
+        display_name -> Nullable<Text>,
+        user_agent -> Nullable<Text>,
+        created -> BigInt,
+        last_used -> BigInt,
     }
 }
@@ -12,25 +12,34 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "biome-sessions")]
+use std::cmp::Reverse;
+#[cfg(not(feature = "biome-sessions"))]
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+#[cfg(feature = "biome-sessions")]
+use std::time::SystemTime;
 
 use crate::biome::refresh_tokens::store::{error::RefreshTokenError, RefreshTokenStore};
+#[cfg(feature = "biome-sessions")]
+use crate::biome::refresh_tokens::store::{RefreshTokenSession, RefreshTokenSessionBuilder};
 
 #[derive(Default, Clone)]
 pub struct MemoryRefreshTokenStore {
+    #[cfg(feature = "biome-sessions")]
+    inner: Arc<Mutex<Vec<RefreshTokenSession>>>,
+    #[cfg(not(feature = "biome-sessions"))]
     inner: Arc<Mutex<HashMap<String, String>>>,
 }
 
 impl MemoryRefreshTokenStore {
     pub fn new() -> Self {
-        Self {
-            inner: Arc::new(Mutex::new(HashMap::new())),
-        }
+        Self::default()
     }
 }
 
 impl RefreshTokenStore for MemoryRefreshTokenStore {
+    #[cfg(not(feature = "biome-sessions"))]
     fn add_token(&self, user_id: &str, token: &str) -> Result<(), RefreshTokenError> {
         let mut inner = self
             .inner
@@ -43,6 +52,7 @@ impl RefreshTokenStore for MemoryRefreshTokenStore {
         Ok(())
     }
 
+    #[cfg(not(feature = "biome-sessions"))]
     fn remove_token(&self, user_id: &str) -> Result<(), RefreshTokenError> {
         let mut inner = self
             .inner
@@ -62,6 +72,7 @@ impl RefreshTokenStore for MemoryRefreshTokenStore {
         }
     }
 
+    #[cfg(not(feature = "biome-sessions"))]
     fn update_token(&self, user_id: &str, token: &str) -> Result<(), RefreshTokenError> {
         let mut inner = self
             .inner
@@ -82,6 +93,7 @@ impl RefreshTokenStore for MemoryRefreshTokenStore {
         }
     }
 
+    #[cfg(not(feature = "biome-sessions"))]
     fn fetch_token(&self, user_id: &str) -> Result<String, RefreshTokenError> {
         let inner = self
             .inner
@@ -100,4 +112,269 @@ impl RefreshTokenStore for MemoryRefreshTokenStore {
             )))
         }
     }
+
+    #[cfg(feature = "biome-sessions")]
+    fn add_token(&self, user_id: &str, token: &str) -> Result<(), RefreshTokenError> {
+        let session = RefreshTokenSessionBuilder::new()
+            .with_user_id(user_id.to_string())
+            .with_token(token.to_string())
+            .build()
+            .map_err(|err| RefreshTokenError::StorageError {
+                context: "Failed to build session for refresh token".to_string(),
+                source: Some(Box::new(err)),
+            })?;
+        self.add_session(session)
+    }
+
+    #[cfg(feature = "biome-sessions")]
+    fn remove_token(&self, user_id: &str) -> Result<(), RefreshTokenError> {
+        let mut inner = self
+            .inner
+            .lock()
+            .map_err(|_| RefreshTokenError::StorageError {
+                context: "Cannot access refresh token store: mutex lock poisoned".to_string(),
+                source: None,
+            })?;
+
+        let count = inner.len();
+        inner.retain(|session| session.user_id != user_id);
+        if inner.len() < count {
+            Ok(())
+        } else {
+            Err(RefreshTokenError::NotFoundError(format!(
+                "User id {} not found.",
+                user_id
+            )))
+        }
+    }
+
+    #[cfg(feature = "biome-sessions")]
+    fn update_token(&self, user_id: &str, token: &str) -> Result<(), RefreshTokenError> {
+        let mut inner = self
+            .inner
+            .lock()
+            .map_err(|_| RefreshTokenError::StorageError {
+                context: "Cannot access refresh token store: mutex lock poisoned".to_string(),
+                source: None,
+            })?;
+
+        let mut found = false;
+        for session in inner
+            .iter_mut()
+            .filter(|session| session.user_id == user_id)
+        {
+            session.token = token.to_string();
+            found = true;
+        }
+
+        if found {
+            Ok(())
+        } else {
+            Err(RefreshTokenError::NotFoundError(format!(
+                "User id {} not found.",
+                user_id
+            )))
+        }
+    }
+
+    #[cfg(feature = "biome-sessions")]
+    fn fetch_token(&self, user_id: &str) -> Result<String, RefreshTokenError> {
+        self.list_sessions(user_id)?
+            .into_iter()
+            .next()
+            .map(|session| session.token)
+            .ok_or_else(|| {
+                RefreshTokenError::NotFoundError(format!("User id {} not found.", user_id))
+            })
+    }
+
+    #[cfg(feature = "biome-sessions")]
+    fn add_session(&self, session: RefreshTokenSession) -> Result<(), RefreshTokenError> {
+        let mut inner = self
+            .inner
+            .lock()
+            .map_err(|_| RefreshTokenError::StorageError {
+                context: "Cannot access refresh token store: mutex lock poisoned".to_string(),
+                source: None,
+            })?;
+
+        if inner
+            .iter()
+            .any(|s| s.user_id == session.user_id && s.session_id == session.session_id)
+        {
+            return Err(RefreshTokenError::StorageError {
+                context: format!(
+                    "Session {} already exists for user {}",
+                    session.session_id, session.user_id
+                ),
+                source: None,
+            });
+        }
+
+        inner.push(session);
+        Ok(())
+    }
+
+    #[cfg(feature = "biome-sessions")]
+    fn list_sessions(&self, user_id: &str) -> Result<Vec<RefreshTokenSession>, RefreshTokenError> {
+        let inner = self
+            .inner
+            .lock()
+            .map_err(|_| RefreshTokenError::StorageError {
+                context: "Cannot access refresh token store: mutex lock poisoned".to_string(),
+                source: None,
+            })?;
+
+        let mut sessions = inner
+            .iter()
+            .filter(|session| session.user_id == user_id)
+            .cloned()
+            .collect::<Vec<_>>();
+        sessions.sort_by_key(|session| Reverse(session.last_used));
+
+        Ok(sessions)
+    }
+
+    #[cfg(feature = "biome-sessions")]
+    fn update_last_used(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        last_used: SystemTime,
+    ) -> Result<(), RefreshTokenError> {
+        let mut inner = self
+            .inner
+            .lock()
+            .map_err(|_| RefreshTokenError::StorageError {
+                context: "Cannot access refresh token store: mutex lock poisoned".to_string(),
+                source: None,
+            })?;
+
+        match inner
+            .iter_mut()
+            .find(|session| session.user_id == user_id && session.session_id == session_id)
+        {
+            Some(session) => {
+                session.last_used = last_used;
+                Ok(())
+            }
+            None => Err(RefreshTokenError::NotFoundError(format!(
+                "Session {} not found for user {}",
+                session_id, user_id
+            ))),
+        }
+    }
+
+    #[cfg(feature = "biome-sessions")]
+    fn remove_session(&self, user_id: &str, session_id: &str) -> Result<(), RefreshTokenError> {
+        let mut inner = self
+            .inner
+            .lock()
+            .map_err(|_| RefreshTokenError::StorageError {
+                context: "Cannot access refresh token store: mutex lock poisoned".to_string(),
+                source: None,
+            })?;
+
+        let count = inner.len();
+        inner.retain(|session| !(session.user_id == user_id && session.session_id == session_id));
+        if inner.len() < count {
+            Ok(())
+        } else {
+            Err(RefreshTokenError::NotFoundError(format!(
+                "Session {} not found for user {}",
+                session_id, user_id
+            )))
+        }
+    }
+}
+
+#[cfg(all(test, feature = "biome-sessions"))]
+mod tests {
+    use super::*;
+
+    use std::time::{Duration, UNIX_EPOCH};
+
+    /// Verify that the `MemoryRefreshTokenStore` keeps a separate session for each login of a
+    /// user, lists them most recently used first, and removes a single session without affecting
+    /// the others.
+    #[test]
+    fn memory_multiple_sessions() {
+        let store = MemoryRefreshTokenStore::new();
+
+        store
+            .add_session(build_session("laptop", "user", "token1", 10))
+            .expect("Failed to add session");
+        store
+            .add_session(build_session("phone", "user", "token2", 20))
+            .expect("Failed to add session");
+        store
+            .add_session(build_session("laptop", "other", "token3", 30))
+            .expect("Failed to add session");
+
+        assert!(store
+            .add_session(build_session("laptop", "user", "token4", 40))
+            .is_err());
+
+        let sessions = store
+            .list_sessions("user")
+            .expect("Failed to list sessions");
+        assert_eq!(
+            sessions.iter().map(|s| s.session_id()).collect::<Vec<_>>(),
+            vec!["phone", "laptop"]
+        );
+
+        store
+            .update_last_used("user", "laptop", UNIX_EPOCH + Duration::from_secs(50))
+            .expect("Failed to update last used");
+        assert_eq!(
+            store.fetch_token("user").expect("Failed to fetch token"),
+            "token1"
+        );
+
+        store
+            .remove_session("user", "laptop")
+            .expect("Failed to remove session");
+        match store.remove_session("user", "laptop") {
+            Err(RefreshTokenError::NotFoundError(_)) => {}
+            res => panic!(
+                "Expected Err(RefreshTokenError::NotFoundError), got {:?} instead",
+                res
+            ),
+        }
+        assert_eq!(
+            store
+                .list_sessions("user")
+                .expect("Failed to list sessions")
+                .len(),
+            1
+        );
+        assert_eq!(
+            store
+                .list_sessions("other")
+                .expect("Failed to list sessions")
+                .len(),
+            1
+        );
+
+        store.remove_token("user").expect("Failed to remove tokens");
+        assert!(store
+            .list_sessions("user")
+            .expect("Failed to list sessions")
+            .is_empty());
+    }
+
+    fn build_session(
+        session_id: &str,
+        user_id: &str,
+        token: &str,
+        created: u64,
+    ) -> RefreshTokenSession {
+        RefreshTokenSessionBuilder::new()
+            .with_session_id(session_id.into())
+            .with_user_id(user_id.into())
+            .with_token(token.into())
+            .with_created(UNIX_EPOCH + Duration::from_secs(created))
+            .build()
+            .expect("Failed to build session")
+    }
 }
@@ -17,8 +17,161 @@ pub(crate) mod diesel;
 mod error;
 pub(in crate::biome) mod memory;
 
+#[cfg(feature = "biome-sessions")]
+use std::time::SystemTime;
+
+#[cfg(feature = "biome-sessions")]
+use uuid::Uuid;
+
+#[cfg(feature = "biome-sessions")]
+use crate::error::InvalidStateError;
+
 pub use error::RefreshTokenError;
 
+/// A login session of a user, identified by the refresh token issued for it
+#[cfg(feature = "biome-sessions")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RefreshTokenSession {
+    session_id: String,
+    user_id: String,
+    token: String,
+    display_name: Option<String>,
+    user_agent: Option<String>,
+    created: SystemTime,
+    last_used: SystemTime,
+}
+
+#[cfg(feature = "biome-sessions")]
+impl RefreshTokenSession {
+    /// Returns the ID of the session
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// Returns the ID of the user the session belongs to
+    pub fn user_id(&self) -> &str {
+        &self.user_id
+    }
+
+    /// Returns the refresh token of the session
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Returns the name given to the session, such as the name of the device, if any
+    pub fn display_name(&self) -> Option<&str> {
+        self.display_name.as_deref()
+    }
+
+    /// Returns the user agent of the client that started the session, if known
+    pub fn user_agent(&self) -> Option<&str> {
+        self.user_agent.as_deref()
+    }
+
+    /// Returns the time the session was started
+    pub fn created(&self) -> SystemTime {
+        self.created
+    }
+
+    /// Returns the time the refresh token of the session was last used
+    pub fn last_used(&self) -> SystemTime {
+        self.last_used
+    }
+}
+
+/// Builder for `RefreshTokenSession`.
+#[cfg(feature = "biome-sessions")]
+#[derive(Default)]
+pub struct RefreshTokenSessionBuilder {
+    session_id: Option<String>,
+    user_id: Option<String>,
+    token: Option<String>,
+    display_name: Option<String>,
+    user_agent: Option<String>,
+    created: Option<SystemTime>,
+    last_used: Option<SystemTime>,
+}
+
+#[cfg(feature = "biome-sessions")]
+impl RefreshTokenSessionBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the ID of the session; defaults to a random UUID
+    pub fn with_session_id(mut self, session_id: String) -> RefreshTokenSessionBuilder {
+        self.session_id = Some(session_id);
+        self
+    }
+
+    /// Sets the ID of the user the session belongs to
+    pub fn with_user_id(mut self, user_id: String) -> RefreshTokenSessionBuilder {
+        self.user_id = Some(user_id);
+        self
+    }
+
+    /// Sets the refresh token of the session
+    pub fn with_token(mut self, token: String) -> RefreshTokenSessionBuilder {
+        self.token = Some(token);
+        self
+    }
+
+    /// Sets the name of the session, such as the name of the device
+    pub fn with_display_name(mut self, display_name: String) -> RefreshTokenSessionBuilder {
+        self.display_name = Some(display_name);
+        self
+    }
+
+    /// Sets the user agent of the client that started the session
+    pub fn with_user_agent(mut self, user_agent: String) -> RefreshTokenSessionBuilder {
+        self.user_agent = Some(user_agent);
+        self
+    }
+
+    /// Sets the time the session was started; defaults to the time the session is built
+    pub fn with_created(mut self, created: SystemTime) -> RefreshTokenSessionBuilder {
+        self.created = Some(created);
+        self
+    }
+
+    /// Sets the time the session was last used; defaults to the time the session was started
+    pub fn with_last_used(mut self, last_used: SystemTime) -> RefreshTokenSessionBuilder {
+        self.last_used = Some(last_used);
+        self
+    }
+
+    /// Builds the session
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidStateError` if the user ID or token is not set.
+    pub fn build(self) -> Result<RefreshTokenSession, InvalidStateError> {
+        let user_id = self.user_id.ok_or_else(|| {
+            InvalidStateError::with_message(
+                "A user id is required to build a RefreshTokenSession".into(),
+            )
+        })?;
+        let token = self.token.ok_or_else(|| {
+            InvalidStateError::with_message(
+                "A token is required to build a RefreshTokenSession".into(),
+            )
+        })?;
+        let created = self.created.unwrap_or_else(SystemTime::now);
+
+        Ok(RefreshTokenSession {
+            session_id: self
+                .session_id
+                .unwrap_or_else(|| Uuid::new_v4().to_string()),
+            user_id,
+            token,
+            display_name: self.display_name,
+            user_agent: self.user_agent,
+            created,
+            last_used: self.last_used.unwrap_or(created),
+        })
+    }
+}
+
 /// Defines methods for CRUD operations for handling refresh tokens
 pub trait RefreshTokenStore: Send + Sync {
     /// Adds a refresh token to underlying storage
@@ -29,14 +182,14 @@ pub trait RefreshTokenStore: Send + Sync {
     ///   * `token` - A refresh token for user
     fn add_token(&self, user_id: &str, token: &str) -> Result<(), RefreshTokenError>;
 
-    /// Removes a token in underlying storage
+    /// Removes all of the user's tokens, and thereby all of their sessions, in underlying storage
     ///
     /// # Arguments
     ///
     ///   * `user_id` - The user whom which the token is for
     fn remove_token(&self, user_id: &str) -> Result<(), RefreshTokenError>;
 
-    /// Update a refresh token to underlying storage
+    /// Update all of the user's refresh tokens in underlying storage
     ///
     /// # Arguments
     ///
@@ -44,12 +197,54 @@ pub trait RefreshTokenStore: Send + Sync {
     ///   * `token` - A refresh token for user
     fn update_token(&self, user_id: &str, token: &str) -> Result<(), RefreshTokenError>;
 
-    /// Fetch a token from underlying storage
+    /// Fetch the token of the user's most recently used session from underlying storage
     ///
     /// # Arguments
     ///
     ///   * `user_id` - The user whom which the token is for
     fn fetch_token(&self, user_id: &str) -> Result<String, RefreshTokenError>;
+
+    /// Adds a session to underlying storage
+    ///
+    /// # Arguments
+    ///
+    ///   * `session` - The session to be added
+    #[cfg(feature = "biome-sessions")]
+    fn add_session(&self, session: RefreshTokenSession) -> Result<(), RefreshTokenError>;
+
+    /// Lists the sessions of a user, most recently used first
+    ///
+    /// # Arguments
+    ///
+    ///   * `user_id` - The user whom which the sessions are for
+    #[cfg(feature = "biome-sessions")]
+    fn list_sessions(&self, user_id: &str) -> Result<Vec<RefreshTokenSession>, RefreshTokenError>;
+
+    /// Records that the refresh token of a session has been used
+    ///
+    /// # Arguments
+    ///
+    ///   * `user_id` - The user whom which the session is for
+    ///   * `session_id` - The ID of the session
+    ///   * `last_used` - The time the refresh token was used
+    #[cfg(feature = "biome-sessions")]
+    fn update_last_used(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        last_used: SystemTime,
+    ) -> Result<(), RefreshTokenError>;
+
+    /// Removes a single session of a user from underlying storage
+    ///
+    /// Returns a `RefreshTokenError::NotFoundError` if the user has no session with the given ID.
+    ///
+    /// # Arguments
+    ///
+    ///   * `user_id` - The user whom which the session is for
+    ///   * `session_id` - The ID of the session
+    #[cfg(feature = "biome-sessions")]
+    fn remove_session(&self, user_id: &str, session_id: &str) -> Result<(), RefreshTokenError>;
 }
 
 impl<RTS> RefreshTokenStore for Box<RTS>
@@ -71,4 +266,29 @@ where
     fn fetch_token(&self, user_id: &str) -> Result<String, RefreshTokenError> {
         (**self).fetch_token(user_id)
     }
+
+    #[cfg(feature = "biome-sessions")]
+    fn add_session(&self, session: RefreshTokenSession) -> Result<(), RefreshTokenError> {
+        (**self).add_session(session)
+    }
+
+    #[cfg(feature = "biome-sessions")]
+    fn list_sessions(&self, user_id: &str) -> Result<Vec<RefreshTokenSession>, RefreshTokenError> {
+        (**self).list_sessions(user_id)
+    }
+
+    #[cfg(feature = "biome-sessions")]
+    fn update_last_used(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        last_used: SystemTime,
+    ) -> Result<(), RefreshTokenError> {
+        (**self).update_last_used(user_id, session_id, last_used)
+    }
+
+    #[cfg(feature = "biome-sessions")]
+    fn remove_session(&self, user_id: &str, session_id: &str) -> Result<(), RefreshTokenError> {
+        (**self).remove_session(user_id, session_id)
+    }
 }
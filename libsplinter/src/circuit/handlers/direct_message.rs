@@ -15,9 +15,13 @@
 use crate::circuit::handlers::create_message;
 use crate::circuit::routing::{RoutingTableReader, ServiceId};
 use crate::network::dispatch::{DispatchError, Handler, MessageContext, MessageSender, PeerId};
+#[cfg(feature = "network-rate-limit")]
+use crate::network::rate_limit::RateLimiter;
 use crate::protos::circuit::{
     CircuitDirectMessage, CircuitError, CircuitError_Error, CircuitMessageType,
 };
+#[cfg(feature = "network-rate-limit")]
+use crate::protos::circuit::{NetworkError, NetworkError_Error};
 
 use protobuf::Message;
 
@@ -25,6 +29,8 @@ use protobuf::Message;
 pub struct CircuitDirectMessageHandler {
     node_id: String,
     routing_table: Box<dyn RoutingTableReader>,
+    #[cfg(feature = "network-rate-limit")]
+    rate_limiter: Option<RateLimiter>,
 }
 
 impl Handler for CircuitDirectMessageHandler {
@@ -108,14 +114,15 @@ impl Handler for CircuitDirectMessageHandler {
                     {
                         let node_id = service.node_id().to_string();
                         // If the service is on this node send message to the service, otherwise
-                        // send the message to the node the service is connected to
+                        // send the message to the node the service is connected to. Only messages
+                        // that leave this node are subject to the rate limit.
                         if node_id != self.node_id {
                             let msg_bytes = context.message_bytes().to_vec();
                             let network_msg_bytes = create_message(
                                 msg_bytes,
                                 CircuitMessageType::CIRCUIT_DIRECT_MESSAGE,
                             )?;
-                            (network_msg_bytes, node_id)
+                            self.apply_rate_limit(&msg, context, (network_msg_bytes, node_id))?
                         } else {
                             let msg_bytes = context.message_bytes().to_vec();
                             let network_msg_bytes = create_message(
@@ -202,8 +209,69 @@ impl CircuitDirectMessageHandler {
         CircuitDirectMessageHandler {
             node_id,
             routing_table,
+            #[cfg(feature = "network-rate-limit")]
+            rate_limiter: None,
         }
     }
+
+    /// Limits the rate at which direct messages are forwarded to other nodes, per peer and per
+    /// circuit. Messages delivered to services connected to this node are not limited.
+    ///
+    /// A message that would exceed a limit is not forwarded; instead, the peer that sent it
+    /// receives a `NetworkError` with `ERROR_QUEUE_FULL`.
+    #[cfg(feature = "network-rate-limit")]
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Checks a message that is about to be forwarded against the rate limiter.
+    ///
+    /// Returns the message and recipient unchanged if the message is within the limits, or a
+    /// `NetworkError` addressed to the peer the message came from if it is not.
+    #[cfg(feature = "network-rate-limit")]
+    fn apply_rate_limit(
+        &self,
+        msg: &CircuitDirectMessage,
+        context: &MessageContext<PeerId, CircuitMessageType>,
+        forward: (Vec<u8>, String),
+    ) -> Result<(Vec<u8>, String), DispatchError> {
+        let rate_limiter = match &self.rate_limiter {
+            Some(rate_limiter) => rate_limiter,
+            None => return Ok(forward),
+        };
+
+        let (_, recipient) = &forward;
+        match rate_limiter.try_acquire(recipient, msg.get_circuit(), msg.get_payload().len()) {
+            Ok(()) => Ok(forward),
+            Err(err) => {
+                debug!(
+                    "Rejecting direct message {} on {}: {}",
+                    msg.get_correlation_id(),
+                    msg.get_circuit(),
+                    err
+                );
+                let mut error_message = NetworkError::new();
+                error_message.set_correlation_id(msg.get_correlation_id().into());
+                error_message.set_error(NetworkError_Error::ERROR_QUEUE_FULL);
+                error_message.set_error_message(err.to_string());
+
+                let msg_bytes = error_message.write_to_bytes()?;
+                let network_msg_bytes =
+                    create_message(msg_bytes, CircuitMessageType::NETWORK_ERROR_MESSAGE)?;
+                Ok((network_msg_bytes, context.source_peer_id().to_string()))
+            }
+        }
+    }
+    #[cfg(not(feature = "network-rate-limit"))]
+    fn apply_rate_limit(
+        &self,
+        _msg: &CircuitDirectMessage,
+        _context: &MessageContext<PeerId, CircuitMessageType>,
+        forward: (Vec<u8>, String),
+    ) -> Result<(Vec<u8>, String), DispatchError> {
+        Ok(forward)
+    }
 }
 
 #[cfg(test)]
@@ -592,6 +660,144 @@ mod tests {
         )
     }
 
+    // Test that once the rate limit for a circuit is reached, direct messages are no longer
+    // forwarded to other nodes and the sender receives a NetworkError with ERROR_QUEUE_FULL
+    // instead, while messages for services on the local node are still delivered
+    #[cfg(feature = "network-rate-limit")]
+    #[test]
+    fn test_circuit_direct_message_handler_rate_limited() {
+        use crate::network::rate_limit::{RateLimit, RateLimiter};
+
+        // Set up dispatcher and mock sender
+        let mock_sender = MockSender::new();
+        let mut dispatcher = Dispatcher::new(Box::new(mock_sender.clone()));
+
+        let table = RoutingTable::default();
+        let reader: Box<dyn RoutingTableReader> = Box::new(table.clone());
+        let mut writer: Box<dyn RoutingTableWriter> = Box::new(table.clone());
+
+        let node_123 = CircuitNode::new("123".to_string(), vec!["123.0.0.1:0".to_string()]);
+        let node_345 = CircuitNode::new("345".to_string(), vec!["123.0.0.1:1".to_string()]);
+
+        let mut service_abc = Service::new(
+            "abc".to_string(),
+            "test".to_string(),
+            "123".to_string(),
+            vec![],
+        );
+        let mut service_def = Service::new(
+            "def".to_string(),
+            "test".to_string(),
+            "345".to_string(),
+            vec![],
+        );
+
+        service_abc.set_peer_id("abc_network".to_string());
+        service_def.set_peer_id("def_network".to_string());
+
+        // Add circuit and service to splinter state
+        let circuit = Circuit::new(
+            "alpha".into(),
+            vec![service_abc.clone(), service_def.clone()],
+            vec!["123".into(), "345".into()],
+        );
+
+        writer
+            .add_circuit(
+                circuit.circuit_id().into(),
+                circuit,
+                vec![node_123, node_345],
+            )
+            .expect("Unable to add circuits");
+
+        // Add direct message handler, allowing one message per second on each circuit
+        let rate_limiter = RateLimiter::new(
+            RateLimit::new(),
+            RateLimit::new().with_messages_per_second(1),
+        );
+        let handler = CircuitDirectMessageHandler::new("345".to_string(), reader)
+            .with_rate_limiter(rate_limiter);
+        dispatcher.set_handler(Box::new(handler));
+
+        // Create the direct message
+        let mut direct_message = CircuitDirectMessage::new();
+        direct_message.set_circuit("alpha".into());
+        direct_message.set_sender("def".into());
+        direct_message.set_recipient("abc".into());
+        direct_message.set_payload(b"test".to_vec());
+        direct_message.set_correlation_id("1234".into());
+        let direct_bytes = direct_message.write_to_bytes().unwrap();
+
+        // The first message is forwarded to the node the service is connected to
+        dispatcher
+            .dispatch(
+                "def".into(),
+                &CircuitMessageType::CIRCUIT_DIRECT_MESSAGE,
+                direct_bytes.clone(),
+            )
+            .unwrap();
+
+        let (id, message) = mock_sender.next_outbound().expect("No message was sent");
+        assert_network_message(
+            message,
+            id.into(),
+            "123",
+            CircuitMessageType::CIRCUIT_DIRECT_MESSAGE,
+            |msg: CircuitDirectMessage| {
+                assert_eq!(msg.get_correlation_id(), "1234");
+            },
+        );
+
+        // The second is rejected back to the sender
+        dispatcher
+            .dispatch(
+                "def".into(),
+                &CircuitMessageType::CIRCUIT_DIRECT_MESSAGE,
+                direct_bytes,
+            )
+            .unwrap();
+
+        let (id, message) = mock_sender.next_outbound().expect("No message was sent");
+        assert_network_message(
+            message,
+            id.into(),
+            "def",
+            CircuitMessageType::NETWORK_ERROR_MESSAGE,
+            |msg: NetworkError| {
+                assert_eq!(msg.get_error(), NetworkError_Error::ERROR_QUEUE_FULL);
+                assert_eq!(msg.get_correlation_id(), "1234");
+            },
+        );
+
+        // A message for the service on this node is delivered despite the exhausted limit
+        let mut local_message = CircuitDirectMessage::new();
+        local_message.set_circuit("alpha".into());
+        local_message.set_sender("abc".into());
+        local_message.set_recipient("def".into());
+        local_message.set_payload(b"test".to_vec());
+        local_message.set_correlation_id("5678".into());
+        let local_bytes = local_message.write_to_bytes().unwrap();
+
+        dispatcher
+            .dispatch(
+                "123".into(),
+                &CircuitMessageType::CIRCUIT_DIRECT_MESSAGE,
+                local_bytes,
+            )
+            .unwrap();
+
+        let (id, message) = mock_sender.next_outbound().expect("No message was sent");
+        assert_network_message(
+            message,
+            id.into(),
+            "def_network",
+            CircuitMessageType::CIRCUIT_DIRECT_MESSAGE,
+            |msg: CircuitDirectMessage| {
+                assert_eq!(msg.get_correlation_id(), "5678");
+            },
+        );
+    }
+
     fn assert_network_message<M: protobuf::Message, F: Fn(M)>(
         message: Vec<u8>,
         recipient: String,
@@ -15,10 +15,15 @@
 //! The API that defines interactions between consensus and a Splinter service.
 
 pub mod error;
+#[cfg(feature = "consensus-raft")]
+pub mod raft;
+#[cfg(feature = "consensus-simulation")]
+pub mod simulation;
 pub mod two_phase;
 
 use std::convert::{TryFrom, TryInto};
 use std::sync::mpsc::Receiver;
+use std::time::Instant;
 
 use protobuf::error::ProtobufError;
 use protobuf::Message;
@@ -175,6 +180,32 @@ pub trait ProposalManager: Send {
 
     /// Consensus has rejected the given proposal.
     fn reject_proposal(&self, id: &ProposalId) -> Result<(), ProposalManagerError>;
+
+    /// Returns the data another node's manager needs to reconstruct the given proposal with
+    /// `add_proposal_payload`.
+    ///
+    /// Consensus algorithms that replicate proposals themselves use this, so that a node that did
+    /// not receive a proposal from its peer is still able to apply it.
+    ///
+    /// The default implementation returns `None`, since this is only useful for some managers.
+    fn get_proposal_payload(
+        &self,
+        _id: &ProposalId,
+    ) -> Result<Option<Vec<u8>>, ProposalManagerError> {
+        Ok(None)
+    }
+
+    /// Reconstructs a proposal from the data returned by another node's `get_proposal_payload`,
+    /// as if the proposal had been received from a peer.
+    ///
+    /// The default implementation does nothing, since this is only useful for some managers.
+    fn add_proposal_payload(
+        &self,
+        _id: &ProposalId,
+        _payload: &[u8],
+    ) -> Result<(), ProposalManagerError> {
+        Ok(())
+    }
 }
 
 /// Messages the `ProposalManager` sends to consensus
@@ -281,6 +312,25 @@ pub trait ConsensusEngine: Send {
     ) -> Result<(), ConsensusEngineError>;
 }
 
+/// The source of the current time for a consensus engine's timeouts.
+///
+/// Engines use the system clock unless they are given another one, such as the virtual clock of a
+/// simulation.
+pub trait Clock: Send {
+    /// Returns the current time.
+    fn now(&self) -> Instant;
+}
+
+/// A `Clock` that returns the system's monotonic time.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
 pub struct StartupState {
     /// The identifier of this consensus engine within the consensus network
     pub id: PeerId,
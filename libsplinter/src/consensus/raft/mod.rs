@@ -0,0 +1,1823 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A leader-based consensus algorithm modeled on Raft
+//!
+//! Unlike two-phase commit, which requires every node to agree, this algorithm commits a proposal
+//! once a majority of the nodes agree, so the network keeps making progress while a minority of
+//! its nodes are offline.
+//!
+//! The nodes elect a leader for each term using randomized election timeouts. Only the leader
+//! creates proposals, one at a time. For each proposal, the leader checks the proposal and asks
+//! the other nodes to check it; once a majority has found it valid, the leader appends an `APPLY`
+//! entry for it to the replicated log, and if that becomes impossible or the proposal times out, a
+//! `REJECT` entry. An entry is committed once it is stored by a majority, and every node applies
+//! committed entries to its proposal manager in log order.
+//!
+//! Each `APPLY` entry carries the payload returned by the leader's proposal manager for the
+//! proposal, so a node that did not receive the proposal from the leader, such as one that was
+//! offline at the time, reconstructs it from the log before applying it. Nodes only discard
+//! applied entries that every node has stored, so a node that rejoins is always sent the entries
+//! it missed, at the cost of the log growing while any node is offline. A node that has lost its
+//! stored state cannot be brought up to date from the log; it stops accepting entries from the
+//! leader and reports that it must be resynchronized.
+
+pub mod store;
+
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use protobuf::{Message, RepeatedField};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+#[cfg(feature = "consensus-simulation")]
+use crate::consensus::simulation::SimulatedEngine;
+use crate::consensus::{
+    Clock, ConsensusEngine, ConsensusEngineError, ConsensusMessage, ConsensusNetworkSender, PeerId,
+    ProposalId, ProposalManager, ProposalUpdate, StartupState, SystemClock,
+};
+use crate::protos::raft::{
+    RaftLogEntry as RaftLogEntryProto, RaftLogEntry_Type, RaftMessage, RaftMessage_Type,
+};
+
+use self::store::{MemoryRaftStore, RaftCommand, RaftLogEntry, RaftState, RaftStore};
+
+const MESSAGE_RECV_TIMEOUT_MILLIS: u64 = 10;
+const PROPOSAL_RECV_TIMEOUT_MILLIS: u64 = 10;
+
+const DEFAULT_ELECTION_TIMEOUT_MILLIS: u64 = 1000;
+const DEFAULT_HEARTBEAT_INTERVAL_MILLIS: u64 = 200;
+
+/// The maximum number of entries sent to a follower in a single message.
+const MAX_ENTRIES_PER_MESSAGE: usize = 64;
+/// The number of applied entries kept in the log beyond those every node is known to have.
+const RETAINED_ENTRIES: u64 = 1024;
+
+enum Role {
+    Follower,
+    /// Holds the nodes that have voted for this node in the current term.
+    Candidate(HashSet<PeerId>),
+    Leader(LeaderState),
+}
+
+struct LeaderState {
+    next_index: HashMap<PeerId, u64>,
+    match_index: HashMap<PeerId, u64>,
+    heartbeat_due: Instant,
+    proposal: LeaderProposal,
+}
+
+/// The leader's progress with its current proposal.
+enum LeaderProposal {
+    Idle,
+    AwaitingProposal,
+    Checking(ProposalId),
+    Verifying {
+        proposal_id: ProposalId,
+        valid: HashSet<PeerId>,
+        invalid: HashSet<PeerId>,
+        deadline: Instant,
+    },
+    /// The decision has been appended to the log and is waiting to be applied.
+    Deciding,
+}
+
+/// A verification request from the leader that has not been answered yet.
+struct PendingVerification {
+    leader_id: PeerId,
+    proposal_id: ProposalId,
+    applied_index: u64,
+    checking: bool,
+}
+
+pub struct RaftEngine {
+    id: PeerId,
+    peers: Vec<PeerId>,
+    role: Role,
+    leader_id: Option<PeerId>,
+    state: RaftState,
+    store: Box<dyn RaftStore>,
+    commit_index: u64,
+    election_timeout: Duration,
+    election_deadline: Instant,
+    heartbeat_interval: Duration,
+    proposal_timeout: Duration,
+    proposals_received: HashSet<ProposalId>,
+    verification: Option<PendingVerification>,
+    /// The proposal this node has checked and found valid, which is waiting for a decision
+    prepared: Option<ProposalId>,
+    /// A committed proposal that must be checked before it can be accepted
+    applying: Option<ProposalId>,
+    /// The index up to which the leader has reported that every node has stored its entries
+    compact_index: u64,
+    /// Set once the leader has discarded entries this node does not have
+    resync_required: bool,
+    clock: Box<dyn Clock>,
+    /// Chooses the election timeouts
+    rng: StdRng,
+}
+
+impl RaftEngine {
+    /// Creates a new engine. If the nodes have not verified a proposal within the proposal
+    /// timeout, the leader rejects it.
+    pub fn new(proposal_timeout: Duration) -> Self {
+        RaftEngine {
+            id: PeerId::default(),
+            peers: vec![],
+            role: Role::Follower,
+            leader_id: None,
+            state: RaftState::default(),
+            store: Box::new(MemoryRaftStore::new()),
+            commit_index: 0,
+            election_timeout: Duration::from_millis(DEFAULT_ELECTION_TIMEOUT_MILLIS),
+            election_deadline: Instant::now(),
+            heartbeat_interval: Duration::from_millis(DEFAULT_HEARTBEAT_INTERVAL_MILLIS),
+            proposal_timeout,
+            proposals_received: HashSet::new(),
+            verification: None,
+            prepared: None,
+            applying: None,
+            compact_index: 0,
+            resync_required: false,
+            clock: Box::new(SystemClock),
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    /// Sets the minimum time a node waits to hear from a leader before it starts an election. The
+    /// actual timeout is chosen at random between this value and twice this value.
+    pub fn with_election_timeout(mut self, election_timeout: Duration) -> Self {
+        self.election_timeout = election_timeout;
+        self
+    }
+
+    /// Sets how often the leader sends entries or heartbeats to the other nodes. This should be
+    /// well below the election timeout.
+    pub fn with_heartbeat_interval(mut self, heartbeat_interval: Duration) -> Self {
+        self.heartbeat_interval = heartbeat_interval;
+        self
+    }
+
+    /// Sets the store used to persist the engine's state. By default, the state is only kept in
+    /// memory.
+    pub fn with_store(mut self, store: Box<dyn RaftStore>) -> Self {
+        self.store = store;
+        self
+    }
+
+    /// The number of nodes, including this one, that make up a majority.
+    fn quorum(&self) -> usize {
+        let members = self.peers.len() + 1;
+        members / 2 + 1
+    }
+
+    fn is_leader(&self) -> bool {
+        matches!(self.role, Role::Leader(_))
+    }
+
+    fn reset_election_deadline(&mut self) {
+        let timeout_millis = self.election_timeout.as_millis() as u64;
+        let jitter = self.rng.gen_range(0, timeout_millis.max(1));
+        self.election_deadline = self.clock.now() + Duration::from_millis(timeout_millis + jitter);
+    }
+
+    fn save_hard_state(&mut self) -> Result<(), ConsensusEngineError> {
+        self.store.save_hard_state(
+            self.state.current_term,
+            self.state.voted_for.as_ref(),
+            self.state.applied_index,
+        )?;
+        Ok(())
+    }
+
+    fn new_message(&self, message_type: RaftMessage_Type) -> RaftMessage {
+        let mut message = RaftMessage::new();
+        message.set_message_type(message_type);
+        message.set_term(self.state.current_term);
+        message
+    }
+
+    /// Sends a message to a peer. Peers may be offline, so failures to send are logged rather
+    /// than returned.
+    fn send_to(
+        &self,
+        network_sender: &dyn ConsensusNetworkSender,
+        peer_id: &PeerId,
+        message: RaftMessage,
+    ) -> Result<(), ConsensusEngineError> {
+        if let Err(err) = network_sender.send_to(peer_id, message.write_to_bytes()?) {
+            debug!("Unable to send raft message to {}: {}", peer_id, err);
+        }
+        Ok(())
+    }
+
+    fn broadcast(
+        &self,
+        network_sender: &dyn ConsensusNetworkSender,
+        message: RaftMessage,
+    ) -> Result<(), ConsensusEngineError> {
+        if let Err(err) = network_sender.broadcast(message.write_to_bytes()?) {
+            debug!("Unable to broadcast raft message: {}", err);
+        }
+        Ok(())
+    }
+
+    /// Moves to a newer term if the given term is newer than the current one.
+    fn observe_term(
+        &mut self,
+        term: u64,
+        proposal_manager: &dyn ProposalManager,
+    ) -> Result<(), ConsensusEngineError> {
+        if term > self.state.current_term {
+            debug!(
+                "Moving from term {} to term {}",
+                self.state.current_term, term
+            );
+            self.state.current_term = term;
+            self.state.voted_for = None;
+            self.save_hard_state()?;
+            self.become_follower(None, proposal_manager);
+        }
+        Ok(())
+    }
+
+    fn become_follower(
+        &mut self,
+        leader_id: Option<PeerId>,
+        proposal_manager: &dyn ProposalManager,
+    ) {
+        if self.is_leader() {
+            info!(
+                "Stepping down as leader in term {}",
+                self.state.current_term
+            );
+            if let Err(err) = proposal_manager.should_build_proposals(false) {
+                error!(
+                    "Unable to stop proposal manager from building proposals: {}",
+                    err
+                );
+            }
+        }
+        if let Some(new_leader_id) = leader_id.as_ref() {
+            if self.leader_id.as_ref() != Some(new_leader_id) {
+                info!(
+                    "Following leader {} in term {}",
+                    new_leader_id, self.state.current_term
+                );
+            }
+        }
+        self.role = Role::Follower;
+        self.leader_id = leader_id;
+    }
+
+    fn start_election(
+        &mut self,
+        network_sender: &dyn ConsensusNetworkSender,
+        proposal_manager: &dyn ProposalManager,
+    ) -> Result<(), ConsensusEngineError> {
+        self.state.current_term += 1;
+        self.state.voted_for = Some(self.id.clone());
+        self.save_hard_state()?;
+
+        debug!("Starting election for term {}", self.state.current_term);
+
+        let mut votes = HashSet::new();
+        votes.insert(self.id.clone());
+        self.role = Role::Candidate(votes);
+        self.leader_id = None;
+        self.reset_election_deadline();
+
+        if self.quorum() == 1 {
+            return self.become_leader(network_sender, proposal_manager);
+        }
+
+        let mut message = self.new_message(RaftMessage_Type::REQUEST_VOTE);
+        let request = message.mut_request_vote();
+        request.set_last_log_index(self.state.last_index());
+        request.set_last_log_term(self.state.last_term());
+
+        self.broadcast(network_sender, message)
+    }
+
+    fn become_leader(
+        &mut self,
+        network_sender: &dyn ConsensusNetworkSender,
+        proposal_manager: &dyn ProposalManager,
+    ) -> Result<(), ConsensusEngineError> {
+        info!("Elected leader for term {}", self.state.current_term);
+
+        let next_index = self.state.last_index() + 1;
+        self.role = Role::Leader(LeaderState {
+            next_index: self
+                .peers
+                .iter()
+                .map(|peer_id| (peer_id.clone(), next_index))
+                .collect(),
+            match_index: self
+                .peers
+                .iter()
+                .map(|peer_id| (peer_id.clone(), 0))
+                .collect(),
+            heartbeat_due: self.clock.now() + self.heartbeat_interval,
+            proposal: LeaderProposal::Idle,
+        });
+        self.leader_id = Some(self.id.clone());
+        self.verification = None;
+
+        if let Err(err) = proposal_manager.should_build_proposals(true) {
+            error!("Unable to have proposal manager build proposals: {}", err);
+        }
+
+        // Entries from earlier terms are committed along with the first entry of this term
+        let term = self.state.current_term;
+        self.append_to_log(vec![RaftLogEntry {
+            term,
+            command: RaftCommand::Noop,
+        }])?;
+        self.advance_commit_index();
+        self.send_append_entries_to_all(network_sender)?;
+        self.apply_committed(proposal_manager)
+    }
+
+    /// Appends entries to the leader's own log.
+    fn append_to_log(&mut self, entries: Vec<RaftLogEntry>) -> Result<(), ConsensusEngineError> {
+        let index = self.state.last_index() + 1;
+        self.store.append_entries(index, &entries)?;
+        self.state.replace_entries(index, &entries);
+        Ok(())
+    }
+
+    /// Commits the newest entry of the current term that is stored by a majority of the nodes,
+    /// along with all entries before it.
+    fn advance_commit_index(&mut self) {
+        if let Role::Leader(leader) = &self.role {
+            let mut match_indexes = leader
+                .match_index
+                .values()
+                .copied()
+                .chain(std::iter::once(self.state.last_index()))
+                .collect::<Vec<_>>();
+            match_indexes.sort_unstable_by(|a, b| b.cmp(a));
+
+            let majority_index = match_indexes[self.quorum() - 1];
+            if majority_index > self.commit_index
+                && self.state.term_at(majority_index) == Some(self.state.current_term)
+            {
+                self.commit_index = majority_index;
+            }
+        }
+    }
+
+    fn send_append_entries_to_all(
+        &mut self,
+        network_sender: &dyn ConsensusNetworkSender,
+    ) -> Result<(), ConsensusEngineError> {
+        for peer_id in self.peers.clone() {
+            self.send_append_entries(&peer_id, network_sender)?;
+        }
+        if let Role::Leader(leader) = &mut self.role {
+            leader.heartbeat_due = self.clock.now() + self.heartbeat_interval;
+        }
+        Ok(())
+    }
+
+    fn send_append_entries(
+        &self,
+        peer_id: &PeerId,
+        network_sender: &dyn ConsensusNetworkSender,
+    ) -> Result<(), ConsensusEngineError> {
+        let next_index = match &self.role {
+            Role::Leader(leader) => match leader.next_index.get(peer_id) {
+                Some(next_index) => *next_index,
+                None => return Ok(()),
+            },
+            _ => return Ok(()),
+        };
+
+        // If the entries the peer needs have been discarded, it cannot catch up
+        let (prev_log_index, compacted) = if next_index <= self.state.snapshot_index {
+            (self.state.snapshot_index, true)
+        } else {
+            (next_index - 1, false)
+        };
+
+        let mut message = self.new_message(RaftMessage_Type::APPEND_ENTRIES);
+        let append_entries = message.mut_append_entries();
+        append_entries.set_prev_log_index(prev_log_index);
+        append_entries.set_prev_log_term(self.state.term_at(prev_log_index).unwrap_or(0));
+        append_entries.set_entries(RepeatedField::from_vec(
+            self.state
+                .entries_from(prev_log_index + 1, MAX_ENTRIES_PER_MESSAGE)
+                .iter()
+                .map(entry_to_proto)
+                .collect(),
+        ));
+        append_entries.set_leader_commit(self.commit_index);
+        append_entries.set_compacted(compacted);
+        append_entries.set_compact_index(self.compaction_limit());
+
+        self.send_to(network_sender, peer_id, message)
+    }
+
+    fn handle_consensus_msg(
+        &mut self,
+        consensus_msg: ConsensusMessage,
+        network_sender: &dyn ConsensusNetworkSender,
+        proposal_manager: &dyn ProposalManager,
+    ) -> Result<(), ConsensusEngineError> {
+        let message: RaftMessage = Message::parse_from_bytes(&consensus_msg.message)?;
+        let origin_id = consensus_msg.origin_id;
+
+        if !self.peers.contains(&origin_id) {
+            warn!("Ignoring raft message from unknown peer {}", origin_id);
+            return Ok(());
+        }
+
+        self.observe_term(message.get_term(), proposal_manager)?;
+
+        match message.get_message_type() {
+            RaftMessage_Type::REQUEST_VOTE => {
+                self.handle_request_vote(origin_id, &message, network_sender)
+            }
+            RaftMessage_Type::REQUEST_VOTE_RESPONSE => {
+                let granted = message.get_request_vote_response().get_vote_granted();
+                if message.get_term() == self.state.current_term && granted {
+                    if let Role::Candidate(votes) = &mut self.role {
+                        debug!("Received vote from {}", origin_id);
+                        votes.insert(origin_id);
+                        if votes.len() >= self.quorum() {
+                            self.become_leader(network_sender, proposal_manager)?;
+                        }
+                    }
+                }
+                Ok(())
+            }
+            RaftMessage_Type::APPEND_ENTRIES => {
+                self.handle_append_entries(origin_id, &message, network_sender, proposal_manager)
+            }
+            RaftMessage_Type::APPEND_ENTRIES_RESPONSE => {
+                if message.get_term() == self.state.current_term {
+                    self.handle_append_entries_response(
+                        origin_id,
+                        &message,
+                        network_sender,
+                        proposal_manager,
+                    )?;
+                }
+                Ok(())
+            }
+            RaftMessage_Type::VERIFICATION_REQUEST => {
+                if message.get_term() < self.state.current_term {
+                    debug!(
+                        "Ignoring verification request from stale leader {}",
+                        origin_id
+                    );
+                    return Ok(());
+                }
+                if self.leader_id.as_ref() != Some(&origin_id) {
+                    self.become_follower(Some(origin_id.clone()), proposal_manager);
+                }
+
+                let request = message.get_verification_request();
+                let proposal_id = ProposalId::from(request.get_proposal_id());
+                debug!("Proposal verification request received: {}", proposal_id);
+
+                self.verification = Some(PendingVerification {
+                    leader_id: origin_id,
+                    proposal_id,
+                    applied_index: request.get_applied_index(),
+                    checking: false,
+                });
+                self.check_pending_verification(proposal_manager)
+            }
+            RaftMessage_Type::VERIFICATION_RESPONSE => {
+                if message.get_term() == self.state.current_term {
+                    let response = message.get_verification_response();
+                    self.handle_verification_response(
+                        origin_id,
+                        ProposalId::from(response.get_proposal_id()),
+                        response.get_valid(),
+                        network_sender,
+                        proposal_manager,
+                    )?;
+                }
+                Ok(())
+            }
+            RaftMessage_Type::UNSET_TYPE => {
+                warn!(
+                    "Ignoring improperly specified raft message from {}",
+                    origin_id
+                );
+                Ok(())
+            }
+        }
+    }
+
+    fn handle_request_vote(
+        &mut self,
+        candidate_id: PeerId,
+        message: &RaftMessage,
+        network_sender: &dyn ConsensusNetworkSender,
+    ) -> Result<(), ConsensusEngineError> {
+        let request = message.get_request_vote();
+
+        let log_is_current = request.get_last_log_term() > self.state.last_term()
+            || (request.get_last_log_term() == self.state.last_term()
+                && request.get_last_log_index() >= self.state.last_index());
+        let vote_granted = message.get_term() == self.state.current_term
+            && self
+                .state
+                .voted_for
+                .as_ref()
+                .map(|voted_for| voted_for == &candidate_id)
+                .unwrap_or(true)
+            && log_is_current;
+
+        if vote_granted {
+            debug!(
+                "Voting for {} in term {}",
+                candidate_id, self.state.current_term
+            );
+            self.state.voted_for = Some(candidate_id.clone());
+            self.save_hard_state()?;
+            self.reset_election_deadline();
+        }
+
+        let mut response = self.new_message(RaftMessage_Type::REQUEST_VOTE_RESPONSE);
+        response
+            .mut_request_vote_response()
+            .set_vote_granted(vote_granted);
+
+        self.send_to(network_sender, &candidate_id, response)
+    }
+
+    fn handle_append_entries(
+        &mut self,
+        leader_id: PeerId,
+        message: &RaftMessage,
+        network_sender: &dyn ConsensusNetworkSender,
+        proposal_manager: &dyn ProposalManager,
+    ) -> Result<(), ConsensusEngineError> {
+        let mut response = self.new_message(RaftMessage_Type::APPEND_ENTRIES_RESPONSE);
+
+        if message.get_term() < self.state.current_term {
+            response
+                .mut_append_entries_response()
+                .set_match_index(self.state.last_index());
+            return self.send_to(network_sender, &leader_id, response);
+        }
+
+        if !matches!(self.role, Role::Follower) || self.leader_id.as_ref() != Some(&leader_id) {
+            self.become_follower(Some(leader_id.clone()), proposal_manager);
+        }
+        self.reset_election_deadline();
+
+        let append_entries = message.get_append_entries();
+        let prev_log_index = append_entries.get_prev_log_index();
+        let prev_log_term = append_entries.get_prev_log_term();
+
+        // Skipping the discarded entries would leave this node's state diverged from the other
+        // nodes, so nothing more is accepted from the leader
+        if append_entries.get_compacted() && !self.log_matches(prev_log_index, prev_log_term) {
+            if !self.resync_required {
+                error!(
+                    "Leader {} has discarded entries {} through {}, which this node has not \
+                     received; this node must be resynchronized before it can apply any more \
+                     entries",
+                    leader_id,
+                    self.state.last_index() + 1,
+                    prev_log_index
+                );
+                self.resync_required = true;
+            }
+            return Ok(());
+        }
+
+        if !self.log_matches(prev_log_index, prev_log_term) {
+            let hint = if prev_log_index > self.state.last_index() {
+                self.state.last_index()
+            } else {
+                prev_log_index - 1
+            };
+            response.mut_append_entries_response().set_match_index(hint);
+            return self.send_to(network_sender, &leader_id, response);
+        }
+
+        let entries = append_entries
+            .get_entries()
+            .iter()
+            .map(entry_from_proto)
+            .collect::<Vec<_>>();
+
+        // Skip the entries this node already has; replace everything from the first conflict on
+        for (offset, entry) in entries.iter().enumerate() {
+            let index = prev_log_index + 1 + offset as u64;
+            if index <= self.state.snapshot_index {
+                continue;
+            }
+            if self.state.term_at(index) != Some(entry.term) {
+                self.store.append_entries(index, &entries[offset..])?;
+                self.state.replace_entries(index, &entries[offset..]);
+                break;
+            }
+        }
+
+        let last_new_index = prev_log_index + entries.len() as u64;
+        if append_entries.get_leader_commit() > self.commit_index {
+            self.commit_index = append_entries.get_leader_commit().min(last_new_index);
+        }
+        self.compact_index = append_entries.get_compact_index().min(last_new_index);
+
+        let response_body = response.mut_append_entries_response();
+        response_body.set_success(true);
+        response_body.set_match_index(last_new_index);
+        self.send_to(network_sender, &leader_id, response)?;
+
+        self.apply_committed(proposal_manager)
+    }
+
+    /// Determines if the log contains an entry at the given index with the given term. Discarded
+    /// entries were committed, so they always match.
+    fn log_matches(&self, index: u64, term: u64) -> bool {
+        index <= self.state.snapshot_index || self.state.term_at(index) == Some(term)
+    }
+
+    fn handle_append_entries_response(
+        &mut self,
+        peer_id: PeerId,
+        message: &RaftMessage,
+        network_sender: &dyn ConsensusNetworkSender,
+        proposal_manager: &dyn ProposalManager,
+    ) -> Result<(), ConsensusEngineError> {
+        let response = message.get_append_entries_response();
+        let last_index = self.state.last_index();
+
+        let resend = match &mut self.role {
+            Role::Leader(leader) => {
+                if response.get_success() {
+                    let match_index = response.get_match_index().min(last_index);
+                    let peer_match = leader.match_index.entry(peer_id.clone()).or_insert(0);
+                    *peer_match = (*peer_match).max(match_index);
+                    leader.next_index.insert(peer_id.clone(), *peer_match + 1);
+                    *peer_match < last_index
+                } else {
+                    let next_index = leader.next_index.entry(peer_id.clone()).or_insert(1);
+                    *next_index = (*next_index - 1).min(response.get_match_index() + 1).max(1);
+                    true
+                }
+            }
+            _ => return Ok(()),
+        };
+
+        if resend {
+            self.send_append_entries(&peer_id, network_sender)?;
+        }
+
+        self.advance_commit_index();
+        self.apply_committed(proposal_manager)
+    }
+
+    fn handle_verification_response(
+        &mut self,
+        peer_id: PeerId,
+        proposal_id: ProposalId,
+        verified: bool,
+        network_sender: &dyn ConsensusNetworkSender,
+        proposal_manager: &dyn ProposalManager,
+    ) -> Result<(), ConsensusEngineError> {
+        let quorum = self.quorum();
+        let members = self.peers.len() + 1;
+
+        let decision = match &mut self.role {
+            Role::Leader(LeaderState {
+                proposal:
+                    LeaderProposal::Verifying {
+                        proposal_id: verifying_id,
+                        valid,
+                        invalid,
+                        ..
+                    },
+                ..
+            }) if verifying_id == &proposal_id => {
+                if verified {
+                    debug!("Proposal {} verified by peer {}", proposal_id, peer_id);
+                    invalid.remove(&peer_id);
+                    valid.insert(peer_id);
+                } else {
+                    debug!("Proposal {} failed by peer {}", proposal_id, peer_id);
+                    valid.remove(&peer_id);
+                    invalid.insert(peer_id);
+                }
+
+                if valid.len() >= quorum {
+                    Some(true)
+                } else if invalid.len() > members - quorum {
+                    Some(false)
+                } else {
+                    None
+                }
+            }
+            _ => {
+                debug!(
+                    "Ignoring verification response for proposal {} from {}",
+                    proposal_id, peer_id
+                );
+                None
+            }
+        };
+
+        match decision {
+            Some(apply) => self.decide(proposal_id, apply, network_sender, proposal_manager),
+            None => Ok(()),
+        }
+    }
+
+    /// Appends the leader's decision for its proposal to the log and replicates it.
+    fn decide(
+        &mut self,
+        proposal_id: ProposalId,
+        apply: bool,
+        network_sender: &dyn ConsensusNetworkSender,
+        proposal_manager: &dyn ProposalManager,
+    ) -> Result<(), ConsensusEngineError> {
+        if apply {
+            debug!("A majority has verified proposal {}; applying", proposal_id);
+        } else {
+            debug!("Rejecting proposal {}", proposal_id);
+        }
+
+        if let Role::Leader(leader) = &mut self.role {
+            leader.proposal = LeaderProposal::Deciding;
+        }
+
+        let command = if apply {
+            let payload = match proposal_manager.get_proposal_payload(&proposal_id) {
+                Ok(payload) => payload.unwrap_or_default(),
+                Err(err) => {
+                    error!(
+                        "Unable to get payload of proposal {}; nodes that have not received it \
+                         will be unable to apply it: {}",
+                        proposal_id, err
+                    );
+                    vec![]
+                }
+            };
+            RaftCommand::Apply {
+                proposal_id,
+                payload,
+            }
+        } else {
+            RaftCommand::Reject(proposal_id)
+        };
+        let term = self.state.current_term;
+        self.append_to_log(vec![RaftLogEntry { term, command }])?;
+
+        self.advance_commit_index();
+        self.send_append_entries_to_all(network_sender)?;
+        self.apply_committed(proposal_manager)
+    }
+
+    /// Applies committed entries to the proposal manager in log order.
+    fn apply_committed(
+        &mut self,
+        proposal_manager: &dyn ProposalManager,
+    ) -> Result<(), ConsensusEngineError> {
+        while self.applying.is_none() && self.state.applied_index < self.commit_index {
+            let index = self.state.applied_index + 1;
+            let command = match self.state.entry(index) {
+                Some(entry) => entry.command.clone(),
+                None => {
+                    error!("Committed entry {} is missing from the log", index);
+                    break;
+                }
+            };
+
+            match command {
+                RaftCommand::Noop => (),
+                RaftCommand::Apply {
+                    proposal_id,
+                    payload,
+                } => {
+                    if self.prepared.as_ref() == Some(&proposal_id) {
+                        self.accept(proposal_id, proposal_manager);
+                    } else {
+                        if !payload.is_empty() && !self.proposals_received.contains(&proposal_id) {
+                            // This node never received the proposal, so it is reconstructed from
+                            // the log
+                            debug!("Adding committed proposal {} from the log", proposal_id);
+                            if let Err(err) =
+                                proposal_manager.add_proposal_payload(&proposal_id, &payload)
+                            {
+                                error!("Unable to add committed proposal {}: {}", proposal_id, err);
+                            }
+                        }
+
+                        // This node has not checked the proposal, which must be done before it can
+                        // be accepted
+                        debug!("Checking committed proposal {}", proposal_id);
+                        match proposal_manager.check_proposal(&proposal_id) {
+                            Ok(()) => {
+                                self.applying = Some(proposal_id);
+                                return Ok(());
+                            }
+                            Err(err) => error!(
+                                "Unable to apply committed proposal {}: {}",
+                                proposal_id, err
+                            ),
+                        }
+                    }
+                }
+                RaftCommand::Reject(proposal_id) => self.reject(proposal_id, proposal_manager),
+            }
+
+            self.finish_applying(index)?;
+        }
+
+        Ok(())
+    }
+
+    fn accept(&mut self, proposal_id: ProposalId, proposal_manager: &dyn ProposalManager) {
+        debug!("Accepting proposal {}", proposal_id);
+        if let Err(err) = proposal_manager.accept_proposal(&proposal_id, None) {
+            error!("Failed to accept proposal {}: {}", proposal_id, err);
+        }
+        self.proposals_received.remove(&proposal_id);
+        self.prepared = None;
+    }
+
+    fn reject(&mut self, proposal_id: ProposalId, proposal_manager: &dyn ProposalManager) {
+        debug!("Rejecting proposal {}", proposal_id);
+        if let Err(err) = proposal_manager.reject_proposal(&proposal_id) {
+            debug!("Failed to reject proposal {}: {}", proposal_id, err);
+        }
+        self.proposals_received.remove(&proposal_id);
+        if self.prepared.as_ref() == Some(&proposal_id) {
+            self.prepared = None;
+        }
+    }
+
+    /// Returns the index up to which every node is known to have stored the leader's entries. The
+    /// leader determines it from the nodes' match indexes; followers use the value last reported
+    /// by the leader.
+    fn compaction_limit(&self) -> u64 {
+        match &self.role {
+            Role::Leader(leader) => leader
+                .match_index
+                .values()
+                .copied()
+                .min()
+                .unwrap_or_else(|| self.state.last_index()),
+            _ => self.compact_index,
+        }
+    }
+
+    /// Records that the entry at the given index has been applied, and discards old entries from
+    /// the log that every node has stored.
+    fn finish_applying(&mut self, index: u64) -> Result<(), ConsensusEngineError> {
+        self.state.applied_index = index;
+        self.save_hard_state()?;
+
+        let compact_index = index
+            .saturating_sub(RETAINED_ENTRIES)
+            .min(self.compaction_limit());
+        if compact_index > self.state.snapshot_index + RETAINED_ENTRIES {
+            let compact_term = self
+                .state
+                .term_at(compact_index)
+                .expect("Entries after the snapshot are in the log");
+            self.store.compact(compact_index, compact_term)?;
+            self.state.compact(compact_index, compact_term);
+        }
+
+        Ok(())
+    }
+
+    /// Checks the proposal the leader has asked this node to verify, once this node has received
+    /// the proposal and applied every entry the leader had applied when it asked.
+    fn check_pending_verification(
+        &mut self,
+        proposal_manager: &dyn ProposalManager,
+    ) -> Result<(), ConsensusEngineError> {
+        let applied_index = self.state.applied_index;
+        let ready = self.applying.is_none();
+
+        if let Some(verification) = &mut self.verification {
+            if !verification.checking
+                && ready
+                && applied_index >= verification.applied_index
+                && self.proposals_received.contains(&verification.proposal_id)
+            {
+                debug!("Checking proposal {}", verification.proposal_id);
+                verification.checking = true;
+                proposal_manager.check_proposal(&verification.proposal_id)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_proposal_update(
+        &mut self,
+        update: ProposalUpdate,
+        network_sender: &dyn ConsensusNetworkSender,
+        proposal_manager: &dyn ProposalManager,
+    ) -> Result<(), ConsensusEngineError> {
+        match update {
+            ProposalUpdate::ProposalCreated(proposal) => {
+                let leader = match &mut self.role {
+                    Role::Leader(leader)
+                        if matches!(leader.proposal, LeaderProposal::AwaitingProposal) =>
+                    {
+                        leader
+                    }
+                    _ => {
+                        warn!("Received unexpected ProposalCreated message");
+                        return Ok(());
+                    }
+                };
+
+                match proposal {
+                    Some(proposal) => {
+                        debug!("Proposal created, checking proposal {}", proposal.id);
+                        leader.proposal = LeaderProposal::Checking(proposal.id.clone());
+                        proposal_manager.check_proposal(&proposal.id)?;
+                    }
+                    None => leader.proposal = LeaderProposal::Idle,
+                }
+            }
+            ProposalUpdate::ProposalReceived(proposal, peer_id) => {
+                debug!("Proposal received from {}: {}", peer_id, proposal.id);
+                self.proposals_received.insert(proposal.id);
+                self.check_pending_verification(proposal_manager)?;
+            }
+            ProposalUpdate::ProposalValid(proposal_id) => {
+                self.handle_check_result(proposal_id, true, network_sender, proposal_manager)?;
+            }
+            ProposalUpdate::ProposalInvalid(proposal_id) => {
+                self.handle_check_result(proposal_id, false, network_sender, proposal_manager)?;
+            }
+            ProposalUpdate::ProposalAccepted(proposal_id) => {
+                info!("proposal accepted: {}", proposal_id);
+            }
+            ProposalUpdate::ProposalAcceptFailed(proposal_id, err) => {
+                error!(
+                    "failed to accept proposal {} due to error: {}",
+                    proposal_id, err
+                );
+            }
+            other => {
+                debug!("ignoring update: {:?}", other);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_check_result(
+        &mut self,
+        proposal_id: ProposalId,
+        valid: bool,
+        network_sender: &dyn ConsensusNetworkSender,
+        proposal_manager: &dyn ProposalManager,
+    ) -> Result<(), ConsensusEngineError> {
+        // A committed proposal that this node had not checked
+        if self.applying.as_ref() == Some(&proposal_id) {
+            self.applying = None;
+            if valid {
+                self.accept(proposal_id, proposal_manager);
+            } else {
+                error!(
+                    "Proposal {} was committed by the network but is invalid on this node",
+                    proposal_id
+                );
+                self.reject(proposal_id, proposal_manager);
+            }
+            self.finish_applying(self.state.applied_index + 1)?;
+            return self.apply_committed(proposal_manager);
+        }
+
+        // The leader's own proposal
+        if let Role::Leader(leader) = &mut self.role {
+            if matches!(&leader.proposal, LeaderProposal::Checking(id) if id == &proposal_id) {
+                if !valid {
+                    return self.decide(proposal_id, false, network_sender, proposal_manager);
+                }
+
+                self.prepared = Some(proposal_id.clone());
+
+                let mut valid = HashSet::new();
+                valid.insert(self.id.clone());
+                leader.proposal = LeaderProposal::Verifying {
+                    proposal_id: proposal_id.clone(),
+                    valid,
+                    invalid: HashSet::new(),
+                    deadline: self.clock.now() + self.proposal_timeout,
+                };
+
+                if self.quorum() == 1 {
+                    return self.decide(proposal_id, true, network_sender, proposal_manager);
+                }
+
+                debug!("Requesting verification of proposal {}", proposal_id);
+
+                let mut request = self.new_message(RaftMessage_Type::VERIFICATION_REQUEST);
+                let request_body = request.mut_verification_request();
+                request_body.set_proposal_id(proposal_id.into());
+                request_body.set_applied_index(self.state.applied_index);
+
+                return self.broadcast(network_sender, request);
+            }
+        }
+
+        // A proposal the leader asked this node to verify
+        match self.verification.take() {
+            Some(verification)
+                if verification.checking && verification.proposal_id == proposal_id =>
+            {
+                if valid {
+                    self.prepared = Some(proposal_id.clone());
+                }
+
+                debug!(
+                    "Sending {} response for proposal {}",
+                    if valid { "verified" } else { "failed" },
+                    proposal_id
+                );
+
+                let mut response = self.new_message(RaftMessage_Type::VERIFICATION_RESPONSE);
+                let response_body = response.mut_verification_response();
+                response_body.set_proposal_id(proposal_id.into());
+                response_body.set_valid(valid);
+
+                self.send_to(network_sender, &verification.leader_id, response)
+            }
+            verification => {
+                self.verification = verification;
+                warn!("Got check result for unknown proposal: {}", proposal_id);
+                Ok(())
+            }
+        }
+    }
+
+    /// Sets up the engine's peers and loads its persisted state, before it handles any messages.
+    fn start(
+        &mut self,
+        proposal_manager: &dyn ProposalManager,
+        startup_state: StartupState,
+    ) -> Result<(), ConsensusEngineError> {
+        self.id = startup_state.id;
+        self.peers = startup_state.peer_ids;
+
+        self.state = self.store.load()?;
+        self.commit_index = self.state.applied_index;
+        self.reset_election_deadline();
+
+        if let Err(err) = proposal_manager.should_build_proposals(false) {
+            error!(
+                "Unable to stop proposal manager from building proposals: {}",
+                err
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Performs the work that is driven by time: starting elections, sending heartbeats, timing
+    /// out proposals and asking for new ones.
+    fn handle_timers(
+        &mut self,
+        network_sender: &dyn ConsensusNetworkSender,
+        proposal_manager: &dyn ProposalManager,
+    ) -> Result<(), ConsensusEngineError> {
+        let now = self.clock.now();
+        let caught_up =
+            self.applying.is_none() && self.state.applied_index == self.state.last_index();
+
+        let leader = match &mut self.role {
+            Role::Leader(leader) => leader,
+            _ => {
+                if now >= self.election_deadline {
+                    self.start_election(network_sender, proposal_manager)?;
+                }
+                return self.check_pending_verification(proposal_manager);
+            }
+        };
+
+        let timed_out = match &leader.proposal {
+            LeaderProposal::Verifying {
+                proposal_id,
+                deadline,
+                ..
+            } if now >= *deadline => Some(proposal_id.clone()),
+            _ => None,
+        };
+
+        if matches!(leader.proposal, LeaderProposal::Deciding) && caught_up {
+            leader.proposal = LeaderProposal::Idle;
+        }
+
+        if matches!(leader.proposal, LeaderProposal::Idle) && caught_up {
+            match proposal_manager.create_proposal(None, vec![]) {
+                Ok(()) => leader.proposal = LeaderProposal::AwaitingProposal,
+                Err(err) => error!("Error while creating proposal: {}", err),
+            }
+        }
+
+        let heartbeat_due = now >= leader.heartbeat_due;
+
+        if let Some(proposal_id) = timed_out {
+            warn!("Proposal timed out; rejecting: {}", proposal_id);
+            self.decide(proposal_id, false, network_sender, proposal_manager)?;
+        } else if heartbeat_due {
+            self.send_append_entries_to_all(network_sender)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn entry_to_proto(entry: &RaftLogEntry) -> RaftLogEntryProto {
+    let mut proto = RaftLogEntryProto::new();
+    proto.set_term(entry.term);
+    match &entry.command {
+        RaftCommand::Noop => proto.set_entry_type(RaftLogEntry_Type::NOOP),
+        RaftCommand::Apply {
+            proposal_id,
+            payload,
+        } => {
+            proto.set_entry_type(RaftLogEntry_Type::APPLY);
+            proto.set_proposal_id(proposal_id.as_ref().to_vec());
+            proto.set_payload(payload.clone());
+        }
+        RaftCommand::Reject(proposal_id) => {
+            proto.set_entry_type(RaftLogEntry_Type::REJECT);
+            proto.set_proposal_id(proposal_id.as_ref().to_vec());
+        }
+    }
+    proto
+}
+
+fn entry_from_proto(proto: &RaftLogEntryProto) -> RaftLogEntry {
+    let command = match proto.get_entry_type() {
+        RaftLogEntry_Type::APPLY => RaftCommand::Apply {
+            proposal_id: proto.get_proposal_id().into(),
+            payload: proto.get_payload().to_vec(),
+        },
+        RaftLogEntry_Type::REJECT => RaftCommand::Reject(proto.get_proposal_id().into()),
+        // An unset entry type is treated as a no-op, which affects no proposal
+        RaftLogEntry_Type::NOOP | RaftLogEntry_Type::UNSET_TYPE => RaftCommand::Noop,
+    };
+
+    RaftLogEntry {
+        term: proto.get_term(),
+        command,
+    }
+}
+
+impl ConsensusEngine for RaftEngine {
+    fn name(&self) -> &str {
+        "raft"
+    }
+
+    fn version(&self) -> &str {
+        "0.1"
+    }
+
+    fn additional_protocols(&self) -> Vec<(String, String)> {
+        vec![]
+    }
+
+    fn run(
+        &mut self,
+        consensus_messages: Receiver<ConsensusMessage>,
+        proposal_updates: Receiver<ProposalUpdate>,
+        network_sender: Box<dyn ConsensusNetworkSender>,
+        proposal_manager: Box<dyn ProposalManager>,
+        startup_state: StartupState,
+    ) -> Result<(), ConsensusEngineError> {
+        let message_timeout = Duration::from_millis(MESSAGE_RECV_TIMEOUT_MILLIS);
+        let proposal_timeout = Duration::from_millis(PROPOSAL_RECV_TIMEOUT_MILLIS);
+
+        self.start(&*proposal_manager, startup_state)?;
+
+        loop {
+            if let Err(err) = self.handle_timers(&*network_sender, &*proposal_manager) {
+                error!("error while handling raft timers: {}", err);
+            }
+
+            // Get and handle a consensus message if there is one
+            match consensus_messages.recv_timeout(message_timeout) {
+                Ok(consensus_message) => {
+                    if let Err(err) = self.handle_consensus_msg(
+                        consensus_message,
+                        &*network_sender,
+                        &*proposal_manager,
+                    ) {
+                        error!("error while handling consensus message: {}", err);
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => {
+                    info!("consensus message receiver disconnected");
+                    break;
+                }
+            }
+
+            // Get and handle a proposal update if there is one
+            match proposal_updates.recv_timeout(proposal_timeout) {
+                Ok(ProposalUpdate::Shutdown) => {
+                    info!("received shutdown");
+                    break;
+                }
+                Ok(update) => {
+                    if let Err(err) =
+                        self.handle_proposal_update(update, &*network_sender, &*proposal_manager)
+                    {
+                        error!("error while handling proposal update: {}", err);
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => {
+                    info!("proposal update receiver disconnected");
+                    break;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "consensus-simulation")]
+impl SimulatedEngine for RaftEngine {
+    fn start(
+        &mut self,
+        clock: Box<dyn Clock>,
+        seed: u64,
+        // Raft sends nothing until its timers fire
+        _network_sender: &dyn ConsensusNetworkSender,
+        proposal_manager: &dyn ProposalManager,
+        startup_state: StartupState,
+    ) -> Result<(), ConsensusEngineError> {
+        self.clock = clock;
+        self.rng = StdRng::seed_from_u64(seed);
+        self.start(proposal_manager, startup_state)
+    }
+
+    fn handle_consensus_msg(
+        &mut self,
+        consensus_msg: ConsensusMessage,
+        network_sender: &dyn ConsensusNetworkSender,
+        proposal_manager: &dyn ProposalManager,
+    ) -> Result<(), ConsensusEngineError> {
+        self.handle_consensus_msg(consensus_msg, network_sender, proposal_manager)
+    }
+
+    fn handle_proposal_update(
+        &mut self,
+        update: ProposalUpdate,
+        network_sender: &dyn ConsensusNetworkSender,
+        proposal_manager: &dyn ProposalManager,
+    ) -> Result<(), ConsensusEngineError> {
+        self.handle_proposal_update(update, network_sender, proposal_manager)
+    }
+
+    fn handle_timers(
+        &mut self,
+        network_sender: &dyn ConsensusNetworkSender,
+        proposal_manager: &dyn ProposalManager,
+    ) -> Result<(), ConsensusEngineError> {
+        self.handle_timers(network_sender, proposal_manager)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::mpsc::{channel, Sender};
+
+    use crate::consensus::tests::{MockConsensusNetworkSender, MockProposalManager};
+    use crate::consensus::Proposal;
+
+    const PROPOSAL_TIMEOUT_MILLIS: u64 = 5000;
+    const WAIT_TIMEOUT_MILLIS: u64 = 5000;
+
+    /// Polls the given function until it returns a value, panicking if it does not return one in
+    /// time.
+    fn wait_for<T, F: Fn() -> Option<T>>(f: F) -> T {
+        let deadline = Instant::now() + Duration::from_millis(WAIT_TIMEOUT_MILLIS);
+        loop {
+            if let Some(value) = f() {
+                return value;
+            }
+            if Instant::now() > deadline {
+                panic!("timed out waiting for engine");
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    /// Waits for a message of the given type to be sent directly to the given peer.
+    fn wait_for_sent(
+        network: &MockConsensusNetworkSender,
+        peer_id: &PeerId,
+        message_type: RaftMessage_Type,
+        skip: usize,
+    ) -> RaftMessage {
+        wait_for(|| {
+            network
+                .sent_messages()
+                .iter()
+                .filter(|(_, recipient)| recipient == peer_id)
+                .map(|(bytes, _)| {
+                    RaftMessage::parse_from_bytes(bytes).expect("failed to parse message")
+                })
+                .filter(|message| message.get_message_type() == message_type)
+                .nth(skip)
+        })
+    }
+
+    /// Waits for a message of the given type to be broadcast.
+    fn wait_for_broadcast(
+        network: &MockConsensusNetworkSender,
+        message_type: RaftMessage_Type,
+    ) -> RaftMessage {
+        wait_for(|| {
+            network
+                .broadcast_messages()
+                .iter()
+                .map(|bytes| RaftMessage::parse_from_bytes(bytes).expect("failed to parse message"))
+                .find(|message| message.get_message_type() == message_type)
+        })
+    }
+
+    fn send(sender: &Sender<ConsensusMessage>, message: RaftMessage, origin_id: u8) {
+        sender
+            .send(ConsensusMessage::new(
+                message.write_to_bytes().expect("failed to write message"),
+                vec![origin_id].into(),
+            ))
+            .expect("failed to send message");
+    }
+
+    fn new_message(message_type: RaftMessage_Type, term: u64) -> RaftMessage {
+        let mut message = RaftMessage::new();
+        message.set_message_type(message_type);
+        message.set_term(term);
+        message
+    }
+
+    fn append_entries(
+        term: u64,
+        prev_log_index: u64,
+        entries: Vec<RaftLogEntry>,
+        leader_commit: u64,
+    ) -> RaftMessage {
+        let mut message = new_message(RaftMessage_Type::APPEND_ENTRIES, term);
+        let append_entries = message.mut_append_entries();
+        append_entries.set_prev_log_index(prev_log_index);
+        append_entries.set_prev_log_term(if prev_log_index == 0 { 0 } else { term });
+        append_entries.set_entries(RepeatedField::from_vec(
+            entries.iter().map(entry_to_proto).collect(),
+        ));
+        append_entries.set_leader_commit(leader_commit);
+        message
+    }
+
+    fn append_entries_response(term: u64, match_index: u64) -> RaftMessage {
+        let mut message = new_message(RaftMessage_Type::APPEND_ENTRIES_RESPONSE, term);
+        let response = message.mut_append_entries_response();
+        response.set_success(true);
+        response.set_match_index(match_index);
+        message
+    }
+
+    fn start_engine(
+        engine: RaftEngine,
+        id: u8,
+        peer_ids: Vec<u8>,
+    ) -> (
+        Sender<ConsensusMessage>,
+        Sender<ProposalUpdate>,
+        MockConsensusNetworkSender,
+        MockProposalManager,
+        std::thread::JoinHandle<()>,
+    ) {
+        let (update_tx, update_rx) = channel();
+        let (consensus_msg_tx, consensus_msg_rx) = channel();
+
+        let manager = MockProposalManager::new(update_tx.clone());
+        let network = MockConsensusNetworkSender::new();
+        let startup_state = StartupState {
+            id: vec![id].into(),
+            peer_ids: peer_ids.into_iter().map(|id| vec![id].into()).collect(),
+            last_proposal: None,
+        };
+
+        let mut engine = engine;
+        let engine_manager = manager.clone();
+        let engine_network = network.clone();
+        let thread = std::thread::spawn(move || {
+            engine
+                .run(
+                    consensus_msg_rx,
+                    update_rx,
+                    Box::new(engine_network),
+                    Box::new(engine_manager),
+                    startup_state,
+                )
+                .expect("engine failed")
+        });
+
+        (consensus_msg_tx, update_tx, network, manager, thread)
+    }
+
+    /// Verify that the engine properly shuts down when it receives the Shutdown update.
+    #[test]
+    fn test_shutdown() {
+        let engine = RaftEngine::new(Duration::from_millis(PROPOSAL_TIMEOUT_MILLIS));
+        let (_consensus_msg_tx, update_tx, _, _, thread) = start_engine(engine, 0, vec![1]);
+
+        update_tx
+            .send(ProposalUpdate::Shutdown)
+            .expect("failed to send shutdown");
+        thread.join().expect("failed to join engine thread");
+    }
+
+    /// Verify that a node with no peers elects itself leader and accepts its own proposals
+    /// without waiting for anyone else.
+    #[test]
+    fn test_single_node() {
+        let store = MemoryRaftStore::new();
+        let engine = RaftEngine::new(Duration::from_millis(PROPOSAL_TIMEOUT_MILLIS))
+            .with_election_timeout(Duration::from_millis(20))
+            .with_store(Box::new(store.clone()));
+        let (_consensus_msg_tx, update_tx, _, manager, thread) = start_engine(engine, 0, vec![]);
+
+        wait_for(|| {
+            manager
+                .accepted_proposals()
+                .iter()
+                .find(|(id, _)| id == &ProposalId::from(vec![1]))
+                .cloned()
+        });
+
+        update_tx
+            .send(ProposalUpdate::Shutdown)
+            .expect("failed to send shutdown");
+        thread.join().expect("failed to join engine thread");
+
+        let state = store.load().expect("failed to load state");
+        assert_eq!(state.current_term, 1);
+        assert_eq!(
+            state.entry(1).map(|entry| &entry.command),
+            Some(&RaftCommand::Noop)
+        );
+        assert_eq!(
+            state.entry(2).map(|entry| &entry.command),
+            Some(&RaftCommand::Apply {
+                proposal_id: vec![1].into(),
+                payload: vec![],
+            })
+        );
+    }
+
+    /// Test the leader of a 3 node network in which one node is offline. The leader is elected
+    /// with a single vote from the other online node, and it commits a proposal once that node has
+    /// verified the proposal and stored the decision.
+    #[test]
+    fn test_leader_with_offline_peer() {
+        let engine = RaftEngine::new(Duration::from_millis(PROPOSAL_TIMEOUT_MILLIS))
+            .with_election_timeout(Duration::from_millis(200));
+        let (consensus_msg_tx, update_tx, network, manager, thread) =
+            start_engine(engine, 0, vec![1, 2]);
+        let peer_id: PeerId = vec![1].into();
+
+        // Vote for the node
+        let request = wait_for_broadcast(&network, RaftMessage_Type::REQUEST_VOTE);
+        let term = request.get_term();
+        assert_eq!(request.get_request_vote().get_last_log_index(), 0);
+        let mut vote = new_message(RaftMessage_Type::REQUEST_VOTE_RESPONSE, term);
+        vote.mut_request_vote_response().set_vote_granted(true);
+        send(&consensus_msg_tx, vote, 1);
+
+        // The new leader replicates a no-op entry for its term
+        let append = wait_for_sent(&network, &peer_id, RaftMessage_Type::APPEND_ENTRIES, 0);
+        assert_eq!(append.get_term(), term);
+        assert_eq!(append.get_append_entries().get_entries().len(), 1);
+        assert_eq!(
+            append.get_append_entries().get_entries()[0].get_entry_type(),
+            RaftLogEntry_Type::NOOP
+        );
+        send(&consensus_msg_tx, append_entries_response(term, 1), 1);
+
+        // Once the no-op is committed, the leader creates a proposal and requests verification
+        let request = wait_for_broadcast(&network, RaftMessage_Type::VERIFICATION_REQUEST);
+        let verification_request = request.get_verification_request();
+        assert_eq!(verification_request.get_proposal_id(), &[1]);
+        assert_eq!(verification_request.get_applied_index(), 1);
+        assert!(manager.accepted_proposals().is_empty());
+
+        let mut response = new_message(RaftMessage_Type::VERIFICATION_RESPONSE, term);
+        response
+            .mut_verification_response()
+            .set_proposal_id(vec![1]);
+        response.mut_verification_response().set_valid(true);
+        send(&consensus_msg_tx, response, 1);
+
+        // The decision is replicated and, once stored by the peer, applied
+        let append = wait_for(|| {
+            network
+                .sent_messages()
+                .iter()
+                .filter(|(_, recipient)| recipient == &peer_id)
+                .map(|(bytes, _)| {
+                    RaftMessage::parse_from_bytes(bytes).expect("failed to parse message")
+                })
+                .find(|message| {
+                    message
+                        .get_append_entries()
+                        .get_entries()
+                        .iter()
+                        .any(|entry| entry.get_entry_type() == RaftLogEntry_Type::APPLY)
+                })
+        });
+        assert_eq!(
+            append.get_append_entries().get_entries()[0].get_proposal_id(),
+            &[1]
+        );
+        assert!(manager.accepted_proposals().is_empty());
+        send(&consensus_msg_tx, append_entries_response(term, 2), 1);
+
+        wait_for(|| manager.accepted_proposals().first().cloned());
+        assert_eq!(manager.accepted_proposals()[0].0, ProposalId::from(vec![1]));
+        assert!(manager.rejected_proposals().is_empty());
+
+        update_tx
+            .send(ProposalUpdate::Shutdown)
+            .expect("failed to send shutdown");
+        thread.join().expect("failed to join engine thread");
+    }
+
+    /// Test a follower in a 3 node network: it stores the leader's entries, verifies the leader's
+    /// proposal once it has applied the entries the leader had, and accepts the proposal when the
+    /// leader commits it.
+    #[test]
+    fn test_follower() {
+        let engine = RaftEngine::new(Duration::from_millis(PROPOSAL_TIMEOUT_MILLIS))
+            .with_election_timeout(Duration::from_secs(60));
+        let (consensus_msg_tx, update_tx, network, manager, thread) =
+            start_engine(engine, 1, vec![0, 2]);
+        let leader_id: PeerId = vec![0].into();
+
+        let noop = RaftLogEntry {
+            term: 1,
+            command: RaftCommand::Noop,
+        };
+        send(&consensus_msg_tx, append_entries(1, 0, vec![noop], 1), 0);
+
+        let response = wait_for_sent(
+            &network,
+            &leader_id,
+            RaftMessage_Type::APPEND_ENTRIES_RESPONSE,
+            0,
+        );
+        assert!(response.get_append_entries_response().get_success());
+        assert_eq!(response.get_append_entries_response().get_match_index(), 1);
+
+        // The proposal is verified once it has been received
+        let mut request = new_message(RaftMessage_Type::VERIFICATION_REQUEST, 1);
+        request.mut_verification_request().set_proposal_id(vec![1]);
+        request.mut_verification_request().set_applied_index(1);
+        send(&consensus_msg_tx, request, 0);
+
+        let proposal = Proposal {
+            id: vec![1].into(),
+            ..Default::default()
+        };
+        update_tx
+            .send(ProposalUpdate::ProposalReceived(
+                proposal,
+                leader_id.clone(),
+            ))
+            .expect("failed to send proposal");
+
+        let response = wait_for_sent(
+            &network,
+            &leader_id,
+            RaftMessage_Type::VERIFICATION_RESPONSE,
+            0,
+        );
+        assert_eq!(response.get_verification_response().get_proposal_id(), &[1]);
+        assert!(response.get_verification_response().get_valid());
+        assert!(manager.accepted_proposals().is_empty());
+
+        // The proposal is accepted once the leader commits the decision
+        let apply = RaftLogEntry {
+            term: 1,
+            command: RaftCommand::Apply {
+                proposal_id: vec![1].into(),
+                payload: vec![],
+            },
+        };
+        send(&consensus_msg_tx, append_entries(1, 1, vec![apply], 2), 0);
+
+        wait_for(|| manager.accepted_proposals().first().cloned());
+        assert_eq!(manager.accepted_proposals()[0].0, ProposalId::from(vec![1]));
+
+        let response = wait_for_sent(
+            &network,
+            &leader_id,
+            RaftMessage_Type::APPEND_ENTRIES_RESPONSE,
+            1,
+        );
+        assert!(response.get_append_entries_response().get_success());
+        assert_eq!(response.get_append_entries_response().get_match_index(), 2);
+
+        update_tx
+            .send(ProposalUpdate::Shutdown)
+            .expect("failed to send shutdown");
+        thread.join().expect("failed to join engine thread");
+    }
+
+    /// Verify that a node grants at most one vote per term.
+    #[test]
+    fn test_vote_once_per_term() {
+        let engine = RaftEngine::new(Duration::from_millis(PROPOSAL_TIMEOUT_MILLIS))
+            .with_election_timeout(Duration::from_secs(60));
+        let (consensus_msg_tx, update_tx, network, _, thread) = start_engine(engine, 0, vec![1, 2]);
+
+        send(
+            &consensus_msg_tx,
+            new_message(RaftMessage_Type::REQUEST_VOTE, 1),
+            1,
+        );
+        let response = wait_for_sent(
+            &network,
+            &vec![1].into(),
+            RaftMessage_Type::REQUEST_VOTE_RESPONSE,
+            0,
+        );
+        assert!(response.get_request_vote_response().get_vote_granted());
+
+        send(
+            &consensus_msg_tx,
+            new_message(RaftMessage_Type::REQUEST_VOTE, 1),
+            2,
+        );
+        let response = wait_for_sent(
+            &network,
+            &vec![2].into(),
+            RaftMessage_Type::REQUEST_VOTE_RESPONSE,
+            0,
+        );
+        assert!(!response.get_request_vote_response().get_vote_granted());
+
+        // A candidate in a later term may receive a vote
+        send(
+            &consensus_msg_tx,
+            new_message(RaftMessage_Type::REQUEST_VOTE, 2),
+            2,
+        );
+        let response = wait_for_sent(
+            &network,
+            &vec![2].into(),
+            RaftMessage_Type::REQUEST_VOTE_RESPONSE,
+            1,
+        );
+        assert_eq!(response.get_term(), 2);
+        assert!(response.get_request_vote_response().get_vote_granted());
+
+        update_tx
+            .send(ProposalUpdate::Shutdown)
+            .expect("failed to send shutdown");
+        thread.join().expect("failed to join engine thread");
+    }
+
+    /// Verify that the leader does not discard entries that a follower has not stored, and that it
+    /// discards them once every follower has stored them.
+    #[test]
+    fn test_compaction_limited_by_match_index() {
+        let (update_tx, _update_rx) = channel();
+        let manager = MockProposalManager::new(update_tx);
+
+        let entries = 3 * RETAINED_ENTRIES;
+        let mut engine = RaftEngine::new(Duration::from_millis(PROPOSAL_TIMEOUT_MILLIS));
+        engine.peers = vec![vec![1].into(), vec![2].into()];
+        engine.role = Role::Leader(LeaderState {
+            next_index: HashMap::new(),
+            match_index: vec![
+                (PeerId::from(vec![1]), entries),
+                (PeerId::from(vec![2]), 10),
+            ]
+            .into_iter()
+            .collect(),
+            heartbeat_due: Instant::now(),
+            proposal: LeaderProposal::Idle,
+        });
+
+        let noop = RaftLogEntry {
+            term: 1,
+            command: RaftCommand::Noop,
+        };
+
+        // Node 2 has only stored the first entries, so none are discarded
+        engine
+            .append_to_log(vec![noop.clone(); entries as usize])
+            .expect("failed to append entries");
+        engine.commit_index = entries;
+        engine
+            .apply_committed(&manager)
+            .expect("failed to apply entries");
+        assert_eq!(engine.state.applied_index, entries);
+        assert_eq!(engine.state.snapshot_index, 0);
+        assert!(engine.state.entry(11).is_some());
+
+        // Once node 2 has stored every entry, the old entries are discarded
+        if let Role::Leader(leader) = &mut engine.role {
+            leader.match_index.insert(vec![2].into(), entries);
+        }
+        engine
+            .append_to_log(vec![noop])
+            .expect("failed to append entry");
+        engine.commit_index = entries + 1;
+        engine
+            .apply_committed(&manager)
+            .expect("failed to apply entry");
+        assert_eq!(engine.state.applied_index, entries + 1);
+        assert_eq!(engine.state.snapshot_index, entries + 1 - RETAINED_ENTRIES);
+        assert!(engine.state.entry(entries + 1).is_some());
+    }
+
+    /// Verify that a follower that is sent entries after ones the leader has discarded, which it
+    /// has not received, does not skip ahead: it neither stores nor applies the entries, nor
+    /// reports them as stored.
+    #[test]
+    fn test_follower_does_not_skip_compacted_entries() {
+        let store = MemoryRaftStore::new();
+        let engine = RaftEngine::new(Duration::from_millis(PROPOSAL_TIMEOUT_MILLIS))
+            .with_election_timeout(Duration::from_secs(60))
+            .with_store(Box::new(store.clone()));
+        let (consensus_msg_tx, update_tx, network, manager, thread) =
+            start_engine(engine, 1, vec![0, 2]);
+
+        let apply = RaftLogEntry {
+            term: 1,
+            command: RaftCommand::Apply {
+                proposal_id: vec![1].into(),
+                payload: vec![1],
+            },
+        };
+        let mut message = append_entries(1, 2000, vec![apply], 2001);
+        message.mut_append_entries().set_compacted(true);
+        send(&consensus_msg_tx, message, 0);
+
+        // Messages are handled in order, so once this vote is answered the entries were handled
+        send(
+            &consensus_msg_tx,
+            new_message(RaftMessage_Type::REQUEST_VOTE, 1),
+            2,
+        );
+        wait_for_sent(
+            &network,
+            &vec![2].into(),
+            RaftMessage_Type::REQUEST_VOTE_RESPONSE,
+            0,
+        );
+
+        assert!(
+            !network
+                .sent_messages()
+                .iter()
+                .map(|(bytes, _)| RaftMessage::parse_from_bytes(bytes)
+                    .expect("failed to parse message"))
+                .any(|message| message.get_message_type()
+                    == RaftMessage_Type::APPEND_ENTRIES_RESPONSE)
+        );
+        assert!(manager.accepted_proposals().is_empty());
+
+        update_tx
+            .send(ProposalUpdate::Shutdown)
+            .expect("failed to send shutdown");
+        thread.join().expect("failed to join engine thread");
+
+        let state = store.load().expect("failed to load state");
+        assert_eq!(state.snapshot_index, 0);
+        assert_eq!(state.applied_index, 0);
+        assert_eq!(state.last_index(), 0);
+    }
+}
@@ -0,0 +1,336 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::consensus::PeerId;
+use crate::hex::{as_hex, deserialize_hex, parse_hex, to_hex};
+
+use super::{RaftCommand, RaftLogEntry, RaftState, RaftStore, RaftStoreError};
+
+/// A `RaftStore` backed by a file with one JSON record per line.
+///
+/// Each change is appended to the file and synced to disk before the method returns. When the log
+/// is compacted, the file is rewritten to contain only the current state. A partially written
+/// record at the end of the file, left by a crash in the middle of an append, is discarded when
+/// the file is opened.
+pub struct FileRaftStore {
+    path: PathBuf,
+    file: File,
+    state: RaftState,
+}
+
+impl FileRaftStore {
+    /// Opens the store at the given path, creating it if it does not exist.
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self, RaftStoreError> {
+        let path = path.as_ref().to_path_buf();
+
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+
+        let mut contents = fs::read_to_string(&path)?;
+        if !contents.is_empty() && !contents.ends_with('\n') {
+            let complete_len = contents.rfind('\n').map(|idx| idx + 1).unwrap_or(0);
+            warn!(
+                "Discarding partially written record at the end of raft store {}",
+                path.display()
+            );
+            file.set_len(complete_len as u64)?;
+            file.sync_all()?;
+            contents.truncate(complete_len);
+        }
+
+        let mut state = RaftState::default();
+        for line in contents.lines().filter(|line| !line.is_empty()) {
+            let record: StoreRecord = serde_json::from_str(line)
+                .map_err(|err| RaftStoreError::InvalidRecord(format!("{}: {}", err, line)))?;
+            match record {
+                StoreRecord::HardState {
+                    current_term,
+                    voted_for,
+                    applied_index,
+                } => {
+                    state.current_term = current_term;
+                    state.voted_for = voted_for
+                        .map(|voted_for| parse_hex(&voted_for).map(PeerId::from))
+                        .transpose()
+                        .map_err(|err| RaftStoreError::InvalidRecord(err.to_string()))?;
+                    state.applied_index = applied_index;
+                }
+                StoreRecord::Entries { index, entries } => {
+                    let entries = entries
+                        .into_iter()
+                        .map(RaftLogEntry::from)
+                        .collect::<Vec<_>>();
+                    state.replace_entries(index, &entries);
+                }
+                StoreRecord::Compact { index, term } => state.compact(index, term),
+            }
+        }
+
+        Ok(FileRaftStore { path, file, state })
+    }
+
+    fn append_record(&mut self, record: &StoreRecord) -> Result<(), RaftStoreError> {
+        let mut line = serde_json::to_string(record)
+            .map_err(|err| RaftStoreError::InvalidRecord(err.to_string()))?;
+        line.push('\n');
+
+        self.file.write_all(line.as_bytes())?;
+        self.file.sync_data()?;
+
+        Ok(())
+    }
+}
+
+impl RaftStore for FileRaftStore {
+    fn load(&self) -> Result<RaftState, RaftStoreError> {
+        Ok(self.state.clone())
+    }
+
+    fn save_hard_state(
+        &mut self,
+        current_term: u64,
+        voted_for: Option<&PeerId>,
+        applied_index: u64,
+    ) -> Result<(), RaftStoreError> {
+        self.append_record(&StoreRecord::HardState {
+            current_term,
+            voted_for: voted_for.map(|voted_for| to_hex(voted_for.as_ref())),
+            applied_index,
+        })?;
+
+        self.state.current_term = current_term;
+        self.state.voted_for = voted_for.cloned();
+        self.state.applied_index = applied_index;
+
+        Ok(())
+    }
+
+    fn append_entries(
+        &mut self,
+        index: u64,
+        entries: &[RaftLogEntry],
+    ) -> Result<(), RaftStoreError> {
+        self.append_record(&StoreRecord::Entries {
+            index,
+            entries: entries.iter().map(EntryRecord::from).collect(),
+        })?;
+
+        self.state.replace_entries(index, entries);
+
+        Ok(())
+    }
+
+    fn compact(&mut self, index: u64, term: u64) -> Result<(), RaftStoreError> {
+        let mut state = self.state.clone();
+        state.compact(index, term);
+
+        let records = vec![
+            StoreRecord::HardState {
+                current_term: state.current_term,
+                voted_for: state
+                    .voted_for
+                    .as_ref()
+                    .map(|voted_for| to_hex(voted_for.as_ref())),
+                applied_index: state.applied_index,
+            },
+            StoreRecord::Compact {
+                index: state.snapshot_index,
+                term: state.snapshot_term,
+            },
+            StoreRecord::Entries {
+                index: state.snapshot_index + 1,
+                entries: state.entries.iter().map(EntryRecord::from).collect(),
+            },
+        ];
+
+        let mut contents = String::new();
+        for record in records {
+            contents.push_str(
+                &serde_json::to_string(&record)
+                    .map_err(|err| RaftStoreError::InvalidRecord(err.to_string()))?,
+            );
+            contents.push('\n');
+        }
+
+        let temp_path = self.path.with_extension("temp");
+        let mut temp_file = File::create(&temp_path)?;
+        temp_file.write_all(contents.as_bytes())?;
+        temp_file.sync_all()?;
+
+        fs::rename(&temp_path, &self.path)?;
+
+        self.file = OpenOptions::new().append(true).open(&self.path)?;
+        self.state = state;
+
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(tag = "record", rename_all = "snake_case")]
+enum StoreRecord {
+    HardState {
+        current_term: u64,
+        voted_for: Option<String>,
+        applied_index: u64,
+    },
+    Entries {
+        index: u64,
+        entries: Vec<EntryRecord>,
+    },
+    Compact {
+        index: u64,
+        term: u64,
+    },
+}
+
+#[derive(Deserialize, Serialize)]
+struct EntryRecord {
+    term: u64,
+    #[serde(flatten)]
+    command: CommandRecord,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+enum CommandRecord {
+    Noop,
+    Apply {
+        #[serde(serialize_with = "as_hex", deserialize_with = "deserialize_hex")]
+        proposal_id: Vec<u8>,
+        #[serde(
+            default,
+            skip_serializing_if = "Vec::is_empty",
+            serialize_with = "as_hex",
+            deserialize_with = "deserialize_hex"
+        )]
+        payload: Vec<u8>,
+    },
+    Reject {
+        #[serde(serialize_with = "as_hex", deserialize_with = "deserialize_hex")]
+        proposal_id: Vec<u8>,
+    },
+}
+
+impl From<&RaftLogEntry> for EntryRecord {
+    fn from(entry: &RaftLogEntry) -> Self {
+        let command = match &entry.command {
+            RaftCommand::Noop => CommandRecord::Noop,
+            RaftCommand::Apply {
+                proposal_id,
+                payload,
+            } => CommandRecord::Apply {
+                proposal_id: proposal_id.as_ref().to_vec(),
+                payload: payload.clone(),
+            },
+            RaftCommand::Reject(proposal_id) => CommandRecord::Reject {
+                proposal_id: proposal_id.as_ref().to_vec(),
+            },
+        };
+
+        EntryRecord {
+            term: entry.term,
+            command,
+        }
+    }
+}
+
+impl From<EntryRecord> for RaftLogEntry {
+    fn from(record: EntryRecord) -> Self {
+        let command = match record.command {
+            CommandRecord::Noop => RaftCommand::Noop,
+            CommandRecord::Apply {
+                proposal_id,
+                payload,
+            } => RaftCommand::Apply {
+                proposal_id: proposal_id.into(),
+                payload,
+            },
+            CommandRecord::Reject { proposal_id } => RaftCommand::Reject(proposal_id.into()),
+        };
+
+        RaftLogEntry {
+            term: record.term,
+            command,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempdir::TempDir;
+
+    fn entry(term: u64, command: RaftCommand) -> RaftLogEntry {
+        RaftLogEntry { term, command }
+    }
+
+    /// Verify that the hard state and log entries written to a file store are restored when the
+    /// store is reopened, both before and after the log is compacted.
+    #[test]
+    fn test_reopen_and_compact() {
+        let temp_dir = TempDir::new("test_reopen_and_compact").expect("Failed to create dir");
+        let path = temp_dir.path().join("raft.store");
+
+        let mut store = FileRaftStore::new(&path).expect("Failed to open store");
+        store
+            .save_hard_state(2, Some(&vec![0].into()), 1)
+            .expect("Failed to save hard state");
+        store
+            .append_entries(
+                1,
+                &[
+                    entry(1, RaftCommand::Noop),
+                    entry(
+                        1,
+                        RaftCommand::Apply {
+                            proposal_id: vec![1].into(),
+                            payload: vec![3, 4],
+                        },
+                    ),
+                    entry(1, RaftCommand::Reject(vec![2].into())),
+                ],
+            )
+            .expect("Failed to append entries");
+        store
+            .append_entries(3, &[entry(2, RaftCommand::Noop)])
+            .expect("Failed to append entries");
+        let expected = store.load().expect("Failed to load state");
+        drop(store);
+
+        let mut store = FileRaftStore::new(&path).expect("Failed to reopen store");
+        let state = store.load().expect("Failed to load state");
+        assert_eq!(state, expected);
+        assert_eq!(state.current_term, 2);
+        assert_eq!(state.voted_for, Some(vec![0].into()));
+        assert_eq!(state.last_index(), 3);
+        assert_eq!(state.last_term(), 2);
+
+        store.compact(2, 1).expect("Failed to compact store");
+        let expected = store.load().expect("Failed to load state");
+        drop(store);
+
+        let store = FileRaftStore::new(&path).expect("Failed to reopen store");
+        let state = store.load().expect("Failed to load state");
+        assert_eq!(state, expected);
+        assert_eq!(state.snapshot_index, 2);
+        assert_eq!(state.entries, vec![entry(2, RaftCommand::Noop)]);
+    }
+}
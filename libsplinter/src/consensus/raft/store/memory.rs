@@ -0,0 +1,81 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::{Arc, Mutex};
+
+use crate::consensus::PeerId;
+
+use super::{RaftLogEntry, RaftState, RaftStore, RaftStoreError};
+
+/// An in-memory `RaftStore`.
+///
+/// Clones share the same state, so a clone kept outside of an engine can be used to inspect the
+/// state or to restart another engine from it. This store does not survive a process restart and
+/// is intended for testing.
+#[derive(Clone, Default)]
+pub struct MemoryRaftStore {
+    state: Arc<Mutex<RaftState>>,
+}
+
+impl MemoryRaftStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl RaftStore for MemoryRaftStore {
+    fn load(&self) -> Result<RaftState, RaftStoreError> {
+        Ok(self
+            .state
+            .lock()
+            .map_err(|_| RaftStoreError::PoisonedLock)?
+            .clone())
+    }
+
+    fn save_hard_state(
+        &mut self,
+        current_term: u64,
+        voted_for: Option<&PeerId>,
+        applied_index: u64,
+    ) -> Result<(), RaftStoreError> {
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|_| RaftStoreError::PoisonedLock)?;
+        state.current_term = current_term;
+        state.voted_for = voted_for.cloned();
+        state.applied_index = applied_index;
+        Ok(())
+    }
+
+    fn append_entries(
+        &mut self,
+        index: u64,
+        entries: &[RaftLogEntry],
+    ) -> Result<(), RaftStoreError> {
+        self.state
+            .lock()
+            .map_err(|_| RaftStoreError::PoisonedLock)?
+            .replace_entries(index, entries);
+        Ok(())
+    }
+
+    fn compact(&mut self, index: u64, term: u64) -> Result<(), RaftStoreError> {
+        self.state
+            .lock()
+            .map_err(|_| RaftStoreError::PoisonedLock)?
+            .compact(index, term);
+        Ok(())
+    }
+}
@@ -0,0 +1,245 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Persistent state of the Raft consensus engine.
+//!
+//! A Raft node must not forget its current term, the vote it cast in that term, or the entries in
+//! its log when it restarts; otherwise it could vote twice in the same term or acknowledge an
+//! entry it no longer has.
+
+mod file;
+mod memory;
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use crate::consensus::error::ConsensusEngineError;
+use crate::consensus::{PeerId, ProposalId};
+
+pub use file::FileRaftStore;
+pub use memory::MemoryRaftStore;
+
+/// A command replicated through the Raft log.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RaftCommand {
+    /// Marks the start of a leader's term.
+    Noop,
+    /// The proposal will be accepted by every node. The payload, if not empty, allows a node that
+    /// did not receive the proposal to reconstruct it.
+    Apply {
+        proposal_id: ProposalId,
+        payload: Vec<u8>,
+    },
+    /// The proposal will be rejected by every node.
+    Reject(ProposalId),
+}
+
+/// An entry in the Raft log.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RaftLogEntry {
+    /// The term of the leader that created the entry
+    pub term: u64,
+    pub command: RaftCommand,
+}
+
+/// The persistent state of a Raft node.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RaftState {
+    /// The latest term this node has seen
+    pub current_term: u64,
+    /// The node this node voted for in the current term
+    pub voted_for: Option<PeerId>,
+    /// The index of the last entry applied to the proposal manager
+    pub applied_index: u64,
+    /// The index of the last entry discarded from the log
+    pub snapshot_index: u64,
+    /// The term of the last entry discarded from the log
+    pub snapshot_term: u64,
+    /// The entries following `snapshot_index`
+    pub entries: Vec<RaftLogEntry>,
+}
+
+impl RaftState {
+    /// Returns the index of the last entry in the log.
+    pub fn last_index(&self) -> u64 {
+        self.snapshot_index + self.entries.len() as u64
+    }
+
+    /// Returns the term of the last entry in the log.
+    pub fn last_term(&self) -> u64 {
+        self.entries
+            .last()
+            .map(|entry| entry.term)
+            .unwrap_or(self.snapshot_term)
+    }
+
+    /// Returns the entry at the given index, if it is in the log.
+    pub fn entry(&self, index: u64) -> Option<&RaftLogEntry> {
+        if index <= self.snapshot_index {
+            return None;
+        }
+        self.entries.get((index - self.snapshot_index - 1) as usize)
+    }
+
+    /// Returns the term of the entry at the given index, if it is known.
+    pub fn term_at(&self, index: u64) -> Option<u64> {
+        if index == self.snapshot_index {
+            Some(self.snapshot_term)
+        } else {
+            self.entry(index).map(|entry| entry.term)
+        }
+    }
+
+    /// Returns up to `max` entries, starting at the given index.
+    pub fn entries_from(&self, index: u64, max: usize) -> &[RaftLogEntry] {
+        let start = (index.max(self.snapshot_index + 1) - self.snapshot_index - 1) as usize;
+        if start >= self.entries.len() {
+            return &[];
+        }
+        let end = self.entries.len().min(start + max);
+        &self.entries[start..end]
+    }
+
+    /// Replaces the entries at and after the given index with the given entries.
+    pub fn replace_entries(&mut self, index: u64, entries: &[RaftLogEntry]) {
+        let keep = index.saturating_sub(self.snapshot_index + 1) as usize;
+        self.entries.truncate(keep);
+        self.entries.extend_from_slice(entries);
+    }
+
+    /// Discards the entries up to and including the given index, which has the given term. If the
+    /// log does not reach the index, all entries are discarded.
+    pub fn compact(&mut self, index: u64, term: u64) {
+        if index <= self.snapshot_index {
+            return;
+        }
+        let discard = ((index - self.snapshot_index) as usize).min(self.entries.len());
+        self.entries.drain(..discard);
+        self.snapshot_index = index;
+        self.snapshot_term = term;
+    }
+}
+
+/// Durable storage for the Raft consensus engine's state.
+///
+/// Every method that modifies the state must make the change durable before it returns.
+pub trait RaftStore: Send {
+    /// Returns the stored state.
+    fn load(&self) -> Result<RaftState, RaftStoreError>;
+
+    /// Saves the current term, the vote cast in that term, and the index of the last applied
+    /// entry.
+    fn save_hard_state(
+        &mut self,
+        current_term: u64,
+        voted_for: Option<&PeerId>,
+        applied_index: u64,
+    ) -> Result<(), RaftStoreError>;
+
+    /// Replaces the entries at and after the given index with the given entries.
+    fn append_entries(
+        &mut self,
+        index: u64,
+        entries: &[RaftLogEntry],
+    ) -> Result<(), RaftStoreError>;
+
+    /// Discards the entries up to and including the given index, which has the given term.
+    fn compact(&mut self, index: u64, term: u64) -> Result<(), RaftStoreError>;
+}
+
+/// Errors that may occur while reading or writing the Raft engine's state.
+#[derive(Debug)]
+pub enum RaftStoreError {
+    /// The state could not be read or written.
+    Io(io::Error),
+    /// A record in the store could not be parsed.
+    InvalidRecord(String),
+    /// The store's internal lock was poisoned.
+    PoisonedLock,
+}
+
+impl Error for RaftStoreError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RaftStoreError::Io(err) => Some(err),
+            RaftStoreError::InvalidRecord(_) => None,
+            RaftStoreError::PoisonedLock => None,
+        }
+    }
+}
+
+impl fmt::Display for RaftStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RaftStoreError::Io(err) => write!(f, "unable to access raft store: {}", err),
+            RaftStoreError::InvalidRecord(msg) => write!(f, "invalid raft store record: {}", msg),
+            RaftStoreError::PoisonedLock => write!(f, "raft store lock was poisoned"),
+        }
+    }
+}
+
+impl From<io::Error> for RaftStoreError {
+    fn from(err: io::Error) -> Self {
+        RaftStoreError::Io(err)
+    }
+}
+
+impl From<RaftStoreError> for ConsensusEngineError {
+    fn from(err: RaftStoreError) -> Self {
+        ConsensusEngineError(Box::new(err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(term: u64, id: u8) -> RaftLogEntry {
+        RaftLogEntry {
+            term,
+            command: RaftCommand::Apply {
+                proposal_id: vec![id].into(),
+                payload: vec![],
+            },
+        }
+    }
+
+    /// Verify that replacing entries truncates any conflicting suffix, and that compacting the log
+    /// keeps the index and term lookups consistent.
+    #[test]
+    fn test_replace_and_compact() {
+        let mut state = RaftState::default();
+        state.replace_entries(1, &[entry(1, 1), entry(1, 2), entry(1, 3)]);
+        assert_eq!(state.last_index(), 3);
+
+        state.replace_entries(3, &[entry(2, 4), entry(2, 5)]);
+        assert_eq!(state.last_index(), 4);
+        assert_eq!(state.last_term(), 2);
+        assert_eq!(state.term_at(2), Some(1));
+
+        state.compact(2, 1);
+        assert_eq!(state.entry(2), None);
+        assert_eq!(state.term_at(2), Some(1));
+        assert_eq!(state.entry(3), Some(&entry(2, 4)));
+        assert_eq!(state.entries_from(1, 10), &[entry(2, 4), entry(2, 5)][..]);
+        assert_eq!(state.entries_from(4, 10), &[entry(2, 5)][..]);
+        assert_eq!(state.last_index(), 4);
+
+        state.compact(10, 3);
+        assert_eq!(state.last_index(), 10);
+        assert_eq!(state.last_term(), 3);
+        assert!(state.entries.is_empty());
+    }
+}
@@ -0,0 +1,788 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A harness for testing consensus engines by simulation
+//!
+//! A [`Simulation`] runs several consensus engines on a single thread, connected by a virtual
+//! network whose delay, message loss and partitions are controlled by the simulation. Each engine is given
+//! a proposal manager that creates proposals, shares them with the other nodes and records which
+//! proposals the node accepts and rejects. After every tick of the simulation's clock, the
+//! simulation checks that:
+//!
+//! * every node accepts the same proposals in the same order (a node may be behind the others)
+//! * no node accepts a proposal twice, or accepts an invalid or unknown proposal
+//! * no proposal is accepted by one node and rejected by another
+//!
+//! If any of these is violated, the simulation stops and reports the seed and tick at which it
+//! happened.
+//!
+//! # Determinism
+//!
+//! The simulation owns the engines' clock and schedules their work. Each engine implements
+//! [`SimulatedEngine`], and on every tick the simulation delivers the messages that are due, lets
+//! each engine in turn handle its proposal updates and timers, and then advances a virtual clock by
+//! the tick duration; no real time passes, and the engines measure their timeouts against the
+//! virtual clock.
+//!
+//! Every decision the virtual network makes (how long each message is delayed and whether it is
+//! lost), the validity of every proposal, and the random choices of the engines are derived from
+//! the simulation's seed, so running the same engines with the same seed replays the same run.
+//!
+//! ```no_run
+//! # use std::time::Duration;
+//! # use splinter::consensus::simulation::SimulationBuilder;
+//! # use splinter::consensus::two_phase::v2::TwoPhaseEngine;
+//! let report = SimulationBuilder::new()
+//!     .with_nodes(3)
+//!     .with_seed(42)
+//!     .with_delay(0, 3)
+//!     .with_partition(100, vec![vec![0, 1], vec![2]])
+//!     .with_heal(200)
+//!     .build()
+//!     .expect("invalid simulation")
+//!     .run(|_| Box::new(TwoPhaseEngine::new(Duration::from_millis(500))))
+//!     .expect("simulation failed");
+//! ```
+
+mod clock;
+mod network;
+mod proposal_manager;
+
+use std::error::Error;
+use std::fmt;
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::consensus::{
+    Clock, ConsensusEngineError, ConsensusMessage, ConsensusNetworkSender, PeerId, ProposalId,
+    ProposalManager, ProposalUpdate, StartupState,
+};
+
+use self::clock::VirtualClock;
+use self::network::{Envelope, Outbox, Payload, SimulatedNetworkSender, VirtualNetwork};
+use self::proposal_manager::{lock_history, History, SimulatedProposalManager};
+
+const DEFAULT_NODES: usize = 3;
+const DEFAULT_TICK_MILLIS: u64 = 10;
+const DEFAULT_MAX_TICKS: u64 = 1000;
+const DEFAULT_MAX_DELAY: u64 = 2;
+const DEFAULT_MAX_PROPOSALS: u64 = 10;
+
+/// The largest number of nodes a simulation supports; each node's peer ID is a single byte.
+const MAX_NODES: usize = 256;
+
+/// Returns the peer ID of the given node.
+pub fn peer_id(node: usize) -> PeerId {
+    vec![node as u8].into()
+}
+
+/// Returns the node with the given peer ID.
+pub fn node_index(peer_id: &PeerId) -> Option<usize> {
+    match peer_id.as_ref() {
+        [node] => Some(*node as usize),
+        _ => None,
+    }
+}
+
+/// A consensus engine that a simulation drives on its own thread and clock.
+///
+/// Rather than running its own loop, as it does in `ConsensusEngine::run`, the engine is handed
+/// each message and proposal update by the simulation, and is asked to do its time-driven work on
+/// every tick. As in `ConsensusEngine::run`, an error returned while handling a message, update or
+/// timer is logged and the engine carries on.
+pub trait SimulatedEngine {
+    /// Prepares the engine to handle messages. The engine must measure its timeouts with the given
+    /// clock, and derive any random choices it makes from the given seed.
+    fn start(
+        &mut self,
+        clock: Box<dyn Clock>,
+        seed: u64,
+        network_sender: &dyn ConsensusNetworkSender,
+        proposal_manager: &dyn ProposalManager,
+        startup_state: StartupState,
+    ) -> Result<(), ConsensusEngineError>;
+
+    /// Handles a message from another node.
+    fn handle_consensus_msg(
+        &mut self,
+        consensus_msg: ConsensusMessage,
+        network_sender: &dyn ConsensusNetworkSender,
+        proposal_manager: &dyn ProposalManager,
+    ) -> Result<(), ConsensusEngineError>;
+
+    /// Handles an update from the node's proposal manager.
+    fn handle_proposal_update(
+        &mut self,
+        update: ProposalUpdate,
+        network_sender: &dyn ConsensusNetworkSender,
+        proposal_manager: &dyn ProposalManager,
+    ) -> Result<(), ConsensusEngineError>;
+
+    /// Performs the work that is driven by time or by the engine's own state, such as expiring
+    /// timeouts and creating proposals.
+    fn handle_timers(
+        &mut self,
+        network_sender: &dyn ConsensusNetworkSender,
+        proposal_manager: &dyn ProposalManager,
+    ) -> Result<(), ConsensusEngineError>;
+}
+
+/// A change to the virtual network, scheduled for a tick.
+enum Fault {
+    Partition(Vec<Vec<usize>>),
+    Heal,
+}
+
+/// Builds a [`Simulation`].
+pub struct SimulationBuilder {
+    nodes: usize,
+    seed: Option<u64>,
+    tick: Duration,
+    max_ticks: u64,
+    min_delay: u64,
+    max_delay: u64,
+    loss_rate: f64,
+    max_proposals: u64,
+    invalid_rate: f64,
+    target_accepted: Option<usize>,
+    faults: Vec<(u64, Fault)>,
+}
+
+impl Default for SimulationBuilder {
+    fn default() -> Self {
+        SimulationBuilder {
+            nodes: DEFAULT_NODES,
+            seed: None,
+            tick: Duration::from_millis(DEFAULT_TICK_MILLIS),
+            max_ticks: DEFAULT_MAX_TICKS,
+            min_delay: 0,
+            max_delay: DEFAULT_MAX_DELAY,
+            loss_rate: 0.0,
+            max_proposals: DEFAULT_MAX_PROPOSALS,
+            invalid_rate: 0.0,
+            target_accepted: None,
+            faults: vec![],
+        }
+    }
+}
+
+impl SimulationBuilder {
+    /// Constructs a new builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of nodes in the simulation (default: 3).
+    pub fn with_nodes(mut self, nodes: usize) -> Self {
+        self.nodes = nodes;
+        self
+    }
+
+    /// Sets the seed from which all of the simulation's random decisions are derived. If not set,
+    /// a random seed is chosen; it is included in the simulation's report and errors so the run
+    /// can be replayed.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Sets the virtual time that passes in each tick of the simulation's clock (default: 10ms).
+    pub fn with_tick_duration(mut self, tick: Duration) -> Self {
+        self.tick = tick;
+        self
+    }
+
+    /// Sets the number of ticks after which the simulation stops (default: 1000).
+    pub fn with_max_ticks(mut self, max_ticks: u64) -> Self {
+        self.max_ticks = max_ticks;
+        self
+    }
+
+    /// Sets the range, in ticks, from which the delay of each message is chosen (default: 0 to
+    /// 2).
+    pub fn with_delay(mut self, min_delay: u64, max_delay: u64) -> Self {
+        self.min_delay = min_delay;
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Sets the probability that any message is lost (default: 0).
+    pub fn with_loss_rate(mut self, loss_rate: f64) -> Self {
+        self.loss_rate = loss_rate;
+        self
+    }
+
+    /// Sets the number of proposals each node's proposal manager will create (default: 10).
+    pub fn with_max_proposals(mut self, max_proposals: u64) -> Self {
+        self.max_proposals = max_proposals;
+        self
+    }
+
+    /// Sets the probability that a created proposal is invalid (default: 0).
+    pub fn with_invalid_rate(mut self, invalid_rate: f64) -> Self {
+        self.invalid_rate = invalid_rate;
+        self
+    }
+
+    /// Stops the simulation early once every node has accepted the given number of proposals.
+    pub fn with_target_accepted(mut self, target_accepted: usize) -> Self {
+        self.target_accepted = Some(target_accepted);
+        self
+    }
+
+    /// Splits the network into the given groups of nodes at the given tick. Nodes that are not in
+    /// any group are isolated. Messages in flight between nodes that are separated are lost.
+    pub fn with_partition(mut self, tick: u64, groups: Vec<Vec<usize>>) -> Self {
+        self.faults.push((tick, Fault::Partition(groups)));
+        self
+    }
+
+    /// Removes any partition at the given tick.
+    pub fn with_heal(mut self, tick: u64) -> Self {
+        self.faults.push((tick, Fault::Heal));
+        self
+    }
+
+    /// Builds the simulation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidConfiguration` error if the number of nodes is zero or too large, the
+    /// delay range is empty, or a rate is not between 0 and 1.
+    pub fn build(mut self) -> Result<Simulation, SimulationError> {
+        if self.nodes == 0 || self.nodes > MAX_NODES {
+            return Err(SimulationError::InvalidConfiguration(format!(
+                "number of nodes must be between 1 and {}",
+                MAX_NODES
+            )));
+        }
+        if self.min_delay > self.max_delay {
+            return Err(SimulationError::InvalidConfiguration(
+                "minimum delay is greater than maximum delay".into(),
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.loss_rate) || !(0.0..=1.0).contains(&self.invalid_rate) {
+            return Err(SimulationError::InvalidConfiguration(
+                "loss and invalid rates must be between 0 and 1".into(),
+            ));
+        }
+
+        self.faults.sort_by_key(|(tick, _)| *tick);
+
+        Ok(Simulation {
+            nodes: self.nodes,
+            seed: self.seed.unwrap_or_else(rand::random),
+            tick: self.tick,
+            max_ticks: self.max_ticks,
+            min_delay: self.min_delay,
+            max_delay: self.max_delay,
+            loss_rate: self.loss_rate,
+            max_proposals: self.max_proposals,
+            invalid_rate: self.invalid_rate,
+            target_accepted: self.target_accepted,
+            faults: self.faults,
+        })
+    }
+}
+
+/// Runs consensus engines over a virtual network and checks that they agree.
+pub struct Simulation {
+    nodes: usize,
+    seed: u64,
+    tick: Duration,
+    max_ticks: u64,
+    min_delay: u64,
+    max_delay: u64,
+    loss_rate: f64,
+    max_proposals: u64,
+    invalid_rate: f64,
+    target_accepted: Option<usize>,
+    faults: Vec<(u64, Fault)>,
+}
+
+/// An engine and the components it is driven with.
+struct SimulatedNode {
+    engine: Box<dyn SimulatedEngine>,
+    network_sender: SimulatedNetworkSender,
+    proposal_manager: SimulatedProposalManager,
+    update_rx: Receiver<ProposalUpdate>,
+}
+
+impl SimulatedNode {
+    fn handle_consensus_msg(&mut self, node: usize, consensus_msg: ConsensusMessage) {
+        if let Err(err) = self.engine.handle_consensus_msg(
+            consensus_msg,
+            &self.network_sender,
+            &self.proposal_manager,
+        ) {
+            error!(
+                "Node {}: error while handling consensus message: {}",
+                node, err
+            );
+        }
+    }
+
+    fn handle_proposal_update(&mut self, node: usize, update: ProposalUpdate) {
+        if let Err(err) =
+            self.engine
+                .handle_proposal_update(update, &self.network_sender, &self.proposal_manager)
+        {
+            error!(
+                "Node {}: error while handling proposal update: {}",
+                node, err
+            );
+        }
+    }
+
+    /// Handles the updates the proposal manager has sent so far. Updates sent while these are
+    /// handled wait for the next call, so that an engine and its manager cannot keep each other
+    /// busy forever.
+    fn handle_pending_updates(&mut self, node: usize) {
+        let updates = self.update_rx.try_iter().collect::<Vec<_>>();
+        for update in updates {
+            self.handle_proposal_update(node, update);
+        }
+    }
+
+    /// Runs the engine's share of a tick.
+    fn step(&mut self, node: usize) {
+        self.handle_pending_updates(node);
+        if let Err(err) = self
+            .engine
+            .handle_timers(&self.network_sender, &self.proposal_manager)
+        {
+            error!("Node {}: error while handling timers: {}", node, err);
+        }
+        self.handle_pending_updates(node);
+    }
+}
+
+impl Simulation {
+    /// Returns the seed the simulation uses.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Runs the simulation on the current thread, using the given function to create the engine
+    /// for each node.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvariantViolated` error, with the seed and tick, as soon as the nodes disagree
+    /// about the proposals they have accepted, and an `EngineFailed` error if an engine fails to
+    /// start.
+    pub fn run<F>(self, mut engine_factory: F) -> Result<SimulationReport, SimulationError>
+    where
+        F: FnMut(usize) -> Box<dyn SimulatedEngine>,
+    {
+        let history = Arc::new(Mutex::new(History::new(self.nodes)));
+        let (outbox_tx, outbox_rx) = channel();
+        let clock = VirtualClock::new();
+        // The engines' seeds are drawn from their own generator, so that they are independent of
+        // the network's decisions
+        let mut engine_seeds = StdRng::seed_from_u64(!self.seed);
+
+        let mut nodes = vec![];
+        for node in 0..self.nodes {
+            nodes.push(self.start_node(
+                node,
+                engine_factory(node),
+                clock.clone(),
+                engine_seeds.gen(),
+                Outbox::new(node, outbox_tx.clone()),
+                history.clone(),
+            )?);
+        }
+        drop(outbox_tx);
+
+        let mut network =
+            VirtualNetwork::new(self.seed, self.min_delay, self.max_delay, self.loss_rate);
+
+        let ticks = self.run_ticks(&mut nodes, &outbox_rx, &mut network, &clock, &history)?;
+
+        let history = lock_history(&history)?;
+        Ok(SimulationReport {
+            seed: self.seed,
+            ticks,
+            accepted: history.accepted.clone(),
+            rejected: history.rejected.clone(),
+            messages_sent: network.messages_sent,
+            messages_dropped: network.messages_dropped,
+        })
+    }
+
+    fn start_node(
+        &self,
+        node: usize,
+        mut engine: Box<dyn SimulatedEngine>,
+        clock: VirtualClock,
+        seed: u64,
+        outbox: Outbox,
+        history: Arc<Mutex<History>>,
+    ) -> Result<SimulatedNode, SimulationError> {
+        let peers = (0..self.nodes)
+            .filter(|peer| *peer != node)
+            .collect::<Vec<_>>();
+
+        let (update_tx, update_rx) = channel();
+
+        let proposal_manager = SimulatedProposalManager::new(
+            node,
+            peers.clone(),
+            self.seed,
+            self.max_proposals,
+            self.invalid_rate,
+            update_tx,
+            outbox.clone(),
+            history,
+        );
+        let network_sender = SimulatedNetworkSender::new(peers.clone(), outbox);
+        let startup_state = StartupState {
+            id: peer_id(node),
+            peer_ids: peers.into_iter().map(peer_id).collect(),
+            last_proposal: None,
+        };
+
+        engine
+            .start(
+                Box::new(clock),
+                seed,
+                &network_sender,
+                &proposal_manager,
+                startup_state,
+            )
+            .map_err(|err| SimulationError::EngineFailed {
+                node,
+                message: err.to_string(),
+            })?;
+
+        Ok(SimulatedNode {
+            engine,
+            network_sender,
+            proposal_manager,
+            update_rx,
+        })
+    }
+
+    /// Advances the clock until the last tick or the target is reached, and returns the number of
+    /// ticks that passed.
+    fn run_ticks(
+        &self,
+        nodes: &mut [SimulatedNode],
+        outbox_rx: &Receiver<Envelope>,
+        network: &mut VirtualNetwork,
+        clock: &VirtualClock,
+        history: &Mutex<History>,
+    ) -> Result<u64, SimulationError> {
+        let mut faults = self.faults.iter().peekable();
+
+        for tick in 0..self.max_ticks {
+            while let Some((fault_tick, fault)) = faults.peek() {
+                if *fault_tick > tick {
+                    break;
+                }
+                match fault {
+                    Fault::Partition(groups) => {
+                        debug!("Tick {}: partitioning nodes into {:?}", tick, groups);
+                        network.partition(self.nodes, groups)
+                    }
+                    Fault::Heal => {
+                        debug!("Tick {}: healing partition", tick);
+                        network.heal()
+                    }
+                }
+                faults.next();
+            }
+
+            network.submit(tick, outbox_rx.try_iter().collect());
+
+            for envelope in network.deliver(tick) {
+                let node = &mut nodes[envelope.to];
+                match envelope.payload {
+                    Payload::Consensus(message) => node.handle_consensus_msg(
+                        envelope.to,
+                        ConsensusMessage::new(message, peer_id(envelope.from)),
+                    ),
+                    Payload::Proposal(proposal) => {
+                        lock_history(history)?.known[envelope.to].insert(proposal.id.clone());
+                        node.handle_proposal_update(
+                            envelope.to,
+                            ProposalUpdate::ProposalReceived(proposal, peer_id(envelope.from)),
+                        );
+                    }
+                }
+            }
+
+            for (index, node) in nodes.iter_mut().enumerate() {
+                node.step(index);
+            }
+
+            {
+                let history = lock_history(history)?;
+                history.check_invariants().map_err(|message| {
+                    SimulationError::InvariantViolated {
+                        seed: self.seed,
+                        tick,
+                        message,
+                    }
+                })?;
+
+                if let Some(target) = self.target_accepted {
+                    if history
+                        .accepted
+                        .iter()
+                        .all(|accepted| accepted.len() >= target)
+                    {
+                        return Ok(tick + 1);
+                    }
+                }
+            }
+
+            clock.advance(self.tick);
+        }
+
+        Ok(self.max_ticks)
+    }
+}
+
+/// The outcome of a simulation that completed without violating any invariants.
+#[derive(Debug)]
+pub struct SimulationReport {
+    /// The seed the simulation used
+    pub seed: u64,
+    /// The number of ticks that passed
+    pub ticks: u64,
+    /// The proposals accepted by each node, in order
+    pub accepted: Vec<Vec<ProposalId>>,
+    /// The proposals rejected by each node, in order
+    pub rejected: Vec<Vec<ProposalId>>,
+    /// The number of messages sent over the virtual network
+    pub messages_sent: u64,
+    /// The number of messages lost or blocked by a partition
+    pub messages_dropped: u64,
+}
+
+/// Errors that may occur while running a simulation.
+#[derive(Debug)]
+pub enum SimulationError {
+    /// The simulation's settings are invalid.
+    InvalidConfiguration(String),
+    /// A consensus engine failed to start.
+    EngineFailed { node: usize, message: String },
+    /// The nodes violated a safety invariant; running the simulation again with the same seed
+    /// replays the network that led to it.
+    InvariantViolated {
+        seed: u64,
+        tick: u64,
+        message: String,
+    },
+    /// The simulation itself failed.
+    Internal(String),
+}
+
+impl Error for SimulationError {}
+
+impl fmt::Display for SimulationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SimulationError::InvalidConfiguration(msg) => {
+                write!(f, "invalid simulation configuration: {}", msg)
+            }
+            SimulationError::EngineFailed { node, message } => {
+                write!(f, "consensus engine of node {} failed: {}", node, message)
+            }
+            SimulationError::InvariantViolated {
+                seed,
+                tick,
+                message,
+            } => write!(
+                f,
+                "safety invariant violated at tick {} (seed {}): {}",
+                tick, seed, message
+            ),
+            SimulationError::Internal(msg) => write!(f, "simulation failed: {}", msg),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "consensus-raft")]
+    use crate::consensus::raft::RaftEngine;
+    use crate::consensus::two_phase::v2::TwoPhaseEngine;
+
+    fn id(node: u8, count: u64) -> ProposalId {
+        let mut id = vec![node];
+        id.extend_from_slice(&count.to_be_bytes());
+        id.into()
+    }
+
+    /// Verify that the invariant check accepts nodes that are behind, and catches nodes that
+    /// accept different proposals, accept a proposal twice, accept an invalid proposal, or
+    /// disagree about whether a proposal was accepted.
+    #[test]
+    fn test_check_invariants() {
+        let mut history = History::new(2);
+        history.created.insert(id(0, 0), true);
+        history.created.insert(id(0, 1), true);
+        history.created.insert(id(0, 2), false);
+
+        history.accepted[0] = vec![id(0, 0), id(0, 1)];
+        history.accepted[1] = vec![id(0, 0)];
+        assert!(history.check_invariants().is_ok());
+
+        history.accepted[1] = vec![id(0, 1)];
+        assert!(history.check_invariants().is_err());
+
+        history.accepted[1] = vec![id(0, 0), id(0, 0)];
+        assert!(history.check_invariants().is_err());
+
+        history.accepted[1] = vec![id(0, 0), id(0, 1), id(0, 2)];
+        assert!(history.check_invariants().is_err());
+
+        history.accepted[1] = vec![id(0, 0)];
+        history.rejected[1] = vec![id(0, 1)];
+        assert!(history.check_invariants().is_err());
+    }
+
+    /// Verify that the same seed produces the same message delays and losses.
+    #[test]
+    fn test_network_replay() {
+        let run = || {
+            let mut network = VirtualNetwork::new(7, 0, 5, 0.3);
+            let (sender, receiver) = channel();
+            let outbox = Outbox::new(0, sender);
+            for _ in 0..20 {
+                outbox
+                    .send(1, Payload::Consensus(vec![]))
+                    .expect("failed to send");
+            }
+            network.submit(0, receiver.try_iter().collect());
+            (0..10)
+                .map(|tick| {
+                    network
+                        .deliver(tick)
+                        .into_iter()
+                        .map(|envelope| envelope.sequence)
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let deliveries = run();
+        assert_eq!(deliveries, run());
+
+        // Messages on a link are never reordered
+        let sequences = deliveries.into_iter().flatten().collect::<Vec<_>>();
+        let mut sorted = sequences.clone();
+        sorted.sort_unstable();
+        assert_eq!(sequences, sorted);
+        assert!(sequences.len() < 20);
+    }
+
+    /// Verify that two-phase commit keeps every node in agreement while one node is partitioned
+    /// from the others and after the partition heals, and that running the simulation again with
+    /// the same seed replays it exactly.
+    ///
+    /// The coordinator timeout is 100 ticks of the virtual clock, so the proposals that the
+    /// partitioned node cannot verify time out and are rejected without any real time passing.
+    #[test]
+    fn test_two_phase_partition() {
+        let run = || {
+            SimulationBuilder::new()
+                .with_nodes(3)
+                .with_seed(1)
+                .with_tick_duration(Duration::from_millis(10))
+                .with_max_ticks(600)
+                .with_delay(0, 3)
+                .with_invalid_rate(0.2)
+                .with_partition(60, vec![vec![0, 1], vec![2]])
+                .with_heal(250)
+                .with_max_proposals(20)
+                .build()
+                .expect("failed to build simulation")
+                .run(|_| Box::new(TwoPhaseEngine::new(Duration::from_millis(1000))))
+                .expect("simulation failed")
+        };
+
+        let report = run();
+        assert_eq!(report.seed, 1);
+        assert_eq!(report.ticks, 600);
+        assert!(!report.accepted[0].is_empty());
+        assert!(!report.rejected[0].is_empty());
+        assert!(report.messages_dropped > 0);
+
+        let replay = run();
+        assert_eq!(replay.accepted, report.accepted);
+        assert_eq!(replay.rejected, report.rejected);
+        assert_eq!(replay.messages_sent, report.messages_sent);
+        assert_eq!(replay.messages_dropped, report.messages_dropped);
+    }
+
+    /// Verify that raft consensus keeps every node in agreement while messages are delayed, that
+    /// every node accepts the proposals, and that running the simulation again with the same seed
+    /// replays it exactly.
+    ///
+    /// With 5ms ticks, the election timeouts are 30 to 60 ticks and the leader sends heartbeats
+    /// every 6 ticks of the virtual clock.
+    #[cfg(feature = "consensus-raft")]
+    #[test]
+    fn test_raft_delays() {
+        let run = || {
+            SimulationBuilder::new()
+                .with_nodes(3)
+                .with_seed(2)
+                .with_tick_duration(Duration::from_millis(5))
+                .with_max_ticks(2000)
+                .with_delay(0, 4)
+                .with_target_accepted(3)
+                .build()
+                .expect("failed to build simulation")
+                .run(|_| {
+                    Box::new(
+                        RaftEngine::new(Duration::from_millis(500))
+                            .with_election_timeout(Duration::from_millis(150))
+                            .with_heartbeat_interval(Duration::from_millis(30)),
+                    )
+                })
+                .expect("simulation failed")
+        };
+
+        let report = run();
+        assert!(report.ticks < 2000);
+        assert!(report.accepted.iter().all(|accepted| accepted.len() >= 3));
+
+        let replay = run();
+        assert_eq!(replay.ticks, report.ticks);
+        assert_eq!(replay.accepted, report.accepted);
+        assert_eq!(replay.messages_sent, report.messages_sent);
+    }
+
+    /// Verify that the builder rejects invalid settings.
+    #[test]
+    fn test_invalid_configuration() {
+        assert!(SimulationBuilder::new().with_nodes(0).build().is_err());
+        assert!(SimulationBuilder::new().with_delay(3, 1).build().is_err());
+        assert!(SimulationBuilder::new()
+            .with_loss_rate(1.5)
+            .build()
+            .is_err());
+    }
+}
@@ -0,0 +1,225 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::consensus::{ConsensusNetworkSender, ConsensusSendError, PeerId, Proposal};
+
+use super::node_index;
+
+/// What a message carries over the virtual network.
+pub(super) enum Payload {
+    Consensus(Vec<u8>),
+    Proposal(Proposal),
+}
+
+/// A message sent by one node to another.
+pub(super) struct Envelope {
+    pub from: usize,
+    pub to: usize,
+    /// The position of the message among all messages sent by `from`
+    pub sequence: u64,
+    pub payload: Payload,
+}
+
+/// Used by a node's network sender and proposal manager to send messages into the virtual
+/// network.
+#[derive(Clone)]
+pub(super) struct Outbox {
+    node: usize,
+    sender: Sender<Envelope>,
+    sequence: Arc<AtomicU64>,
+}
+
+impl Outbox {
+    pub fn new(node: usize, sender: Sender<Envelope>) -> Self {
+        Outbox {
+            node,
+            sender,
+            sequence: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn send(&self, to: usize, payload: Payload) -> Result<(), ConsensusSendError> {
+        let envelope = Envelope {
+            from: self.node,
+            to,
+            sequence: self.sequence.fetch_add(1, Ordering::SeqCst),
+            payload,
+        };
+        self.sender
+            .send(envelope)
+            .map_err(|err| ConsensusSendError::Internal(Box::new(err)))
+    }
+}
+
+/// The `ConsensusNetworkSender` given to each engine in a simulation.
+pub(super) struct SimulatedNetworkSender {
+    peers: Vec<usize>,
+    outbox: Outbox,
+}
+
+impl SimulatedNetworkSender {
+    pub fn new(peers: Vec<usize>, outbox: Outbox) -> Self {
+        SimulatedNetworkSender { peers, outbox }
+    }
+}
+
+impl ConsensusNetworkSender for SimulatedNetworkSender {
+    fn send_to(&self, peer_id: &PeerId, message: Vec<u8>) -> Result<(), ConsensusSendError> {
+        match node_index(peer_id) {
+            Some(node) if self.peers.contains(&node) => {
+                self.outbox.send(node, Payload::Consensus(message))
+            }
+            _ => Err(ConsensusSendError::UnknownPeer(peer_id.clone())),
+        }
+    }
+
+    fn broadcast(&self, message: Vec<u8>) -> Result<(), ConsensusSendError> {
+        for node in &self.peers {
+            self.outbox
+                .send(*node, Payload::Consensus(message.clone()))?;
+        }
+        Ok(())
+    }
+}
+
+struct InFlight {
+    deliver_at: u64,
+    /// The order in which the network accepted the message, used to break ties
+    order: u64,
+    envelope: Envelope,
+}
+
+/// Decides the fate of every message sent between the nodes of a simulation.
+///
+/// Messages on the same link are delivered in the order they were sent, as they are over a
+/// Splinter connection. All random decisions are drawn from a single generator in a fixed order,
+/// so the same seed and the same messages always produce the same delays and losses.
+pub(super) struct VirtualNetwork {
+    rng: StdRng,
+    min_delay: u64,
+    max_delay: u64,
+    loss_rate: f64,
+    /// The group each node belongs to; nodes can only reach nodes in the same group
+    groups: Option<Vec<Option<usize>>>,
+    last_delivery: HashMap<(usize, usize), u64>,
+    in_flight: Vec<InFlight>,
+    next_order: u64,
+    pub messages_sent: u64,
+    pub messages_dropped: u64,
+}
+
+impl VirtualNetwork {
+    pub fn new(seed: u64, min_delay: u64, max_delay: u64, loss_rate: f64) -> Self {
+        VirtualNetwork {
+            rng: StdRng::seed_from_u64(seed),
+            min_delay,
+            max_delay,
+            loss_rate,
+            groups: None,
+            last_delivery: HashMap::new(),
+            in_flight: vec![],
+            next_order: 0,
+            messages_sent: 0,
+            messages_dropped: 0,
+        }
+    }
+
+    /// Splits the network into the given groups. Nodes that are not in any group are isolated.
+    pub fn partition(&mut self, nodes: usize, groups: &[Vec<usize>]) {
+        let mut node_groups = vec![None; nodes];
+        for (group, members) in groups.iter().enumerate() {
+            for node in members {
+                if let Some(node_group) = node_groups.get_mut(*node) {
+                    *node_group = Some(group);
+                }
+            }
+        }
+        self.groups = Some(node_groups);
+    }
+
+    /// Removes any partition.
+    pub fn heal(&mut self) {
+        self.groups = None;
+    }
+
+    fn connected(&self, from: usize, to: usize) -> bool {
+        match &self.groups {
+            Some(groups) => match (groups.get(from), groups.get(to)) {
+                (Some(Some(from_group)), Some(Some(to_group))) => from_group == to_group,
+                _ => false,
+            },
+            None => true,
+        }
+    }
+
+    /// Accepts the messages sent during the given tick.
+    pub fn submit(&mut self, now: u64, mut envelopes: Vec<Envelope>) {
+        // Messages are sorted by sender before any random decisions are made, so that the
+        // decisions do not depend on the order in which the nodes were stepped
+        envelopes.sort_by_key(|envelope| (envelope.from, envelope.sequence));
+
+        for envelope in envelopes {
+            self.messages_sent += 1;
+
+            let lost = self.loss_rate > 0.0 && self.rng.gen_bool(self.loss_rate);
+            let delay = self.rng.gen_range(self.min_delay, self.max_delay + 1);
+
+            if lost || !self.connected(envelope.from, envelope.to) {
+                self.messages_dropped += 1;
+                continue;
+            }
+
+            let link = (envelope.from, envelope.to);
+            let deliver_at = (now + delay).max(*self.last_delivery.get(&link).unwrap_or(&0));
+            self.last_delivery.insert(link, deliver_at);
+
+            self.in_flight.push(InFlight {
+                deliver_at,
+                order: self.next_order,
+                envelope,
+            });
+            self.next_order += 1;
+        }
+    }
+
+    /// Returns the messages due to be delivered at the given tick. Messages between nodes that
+    /// have been partitioned while the messages were in flight are dropped.
+    pub fn deliver(&mut self, now: u64) -> Vec<Envelope> {
+        let (mut due, in_flight): (Vec<_>, Vec<_>) = self
+            .in_flight
+            .drain(..)
+            .partition(|in_flight| in_flight.deliver_at <= now);
+        self.in_flight = in_flight;
+
+        due.sort_by_key(|in_flight| (in_flight.deliver_at, in_flight.order));
+
+        let mut delivered = vec![];
+        for in_flight in due {
+            if self.connected(in_flight.envelope.from, in_flight.envelope.to) {
+                delivered.push(in_flight.envelope);
+            } else {
+                self.messages_dropped += 1;
+            }
+        }
+        delivered
+    }
+}
@@ -0,0 +1,279 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::consensus::error::ProposalManagerError;
+use crate::consensus::{Proposal, ProposalId, ProposalManager, ProposalUpdate};
+
+use super::network::{Outbox, Payload};
+use super::SimulationError;
+
+/// Everything the nodes of a simulation have done with proposals.
+#[derive(Default)]
+pub(super) struct History {
+    /// Every proposal created by any node, and whether or not it is valid
+    pub created: HashMap<ProposalId, bool>,
+    /// The proposals each node has created or received
+    pub known: Vec<HashSet<ProposalId>>,
+    /// The proposals each node has accepted, in order
+    pub accepted: Vec<Vec<ProposalId>>,
+    /// The proposals each node has rejected, in order
+    pub rejected: Vec<Vec<ProposalId>>,
+}
+
+impl History {
+    pub fn new(nodes: usize) -> Self {
+        History {
+            created: HashMap::new(),
+            known: vec![HashSet::new(); nodes],
+            accepted: vec![vec![]; nodes],
+            rejected: vec![vec![]; nodes],
+        }
+    }
+
+    /// Checks the safety properties every consensus engine must provide.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        for (node, accepted) in self.accepted.iter().enumerate() {
+            let mut seen = HashSet::new();
+            for id in accepted {
+                if !seen.insert(id) {
+                    return Err(format!("node {} accepted proposal {} twice", node, id));
+                }
+                match self.created.get(id) {
+                    Some(true) => (),
+                    Some(false) => {
+                        return Err(format!("node {} accepted invalid proposal {}", node, id))
+                    }
+                    None => return Err(format!("node {} accepted unknown proposal {}", node, id)),
+                }
+            }
+        }
+
+        // Every node must accept the same proposals in the same order, although some nodes may
+        // be behind the others
+        if let Some((longest_node, longest)) = self
+            .accepted
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, accepted)| accepted.len())
+        {
+            for (node, accepted) in self.accepted.iter().enumerate() {
+                if let Some(position) = accepted
+                    .iter()
+                    .zip(longest.iter())
+                    .position(|(a, b)| a != b)
+                {
+                    return Err(format!(
+                        "nodes {} and {} accepted different proposals at position {}: {} and {}",
+                        node, longest_node, position, accepted[position], longest[position]
+                    ));
+                }
+            }
+        }
+
+        for (node, rejected) in self.rejected.iter().enumerate() {
+            for id in rejected {
+                if let Some(accepting_node) = self
+                    .accepted
+                    .iter()
+                    .position(|accepted| accepted.contains(id))
+                {
+                    return Err(format!(
+                        "proposal {} was accepted by node {} but rejected by node {}",
+                        id, accepting_node, node
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub(super) fn lock_history(
+    history: &Mutex<History>,
+) -> Result<MutexGuard<'_, History>, SimulationError> {
+    history
+        .lock()
+        .map_err(|_| SimulationError::Internal("simulation history lock poisoned".into()))
+}
+
+/// The `ProposalManager` given to each engine in a simulation.
+///
+/// Each proposal is sent to the other nodes over the virtual network, the way a service sends the
+/// proposals it creates. Whether a proposal is valid is decided when it is created, using the
+/// simulation's seed, so every node agrees on it.
+pub(super) struct SimulatedProposalManager {
+    node: usize,
+    peers: Vec<usize>,
+    seed: u64,
+    max_proposals: u64,
+    invalid_rate: f64,
+    created: AtomicU64,
+    update_sender: Sender<ProposalUpdate>,
+    outbox: Outbox,
+    history: Arc<Mutex<History>>,
+}
+
+impl SimulatedProposalManager {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        node: usize,
+        peers: Vec<usize>,
+        seed: u64,
+        max_proposals: u64,
+        invalid_rate: f64,
+        update_sender: Sender<ProposalUpdate>,
+        outbox: Outbox,
+        history: Arc<Mutex<History>>,
+    ) -> Self {
+        SimulatedProposalManager {
+            node,
+            peers,
+            seed,
+            max_proposals,
+            invalid_rate,
+            created: AtomicU64::new(0),
+            update_sender,
+            outbox,
+            history,
+        }
+    }
+
+    fn history(&self) -> Result<MutexGuard<'_, History>, ProposalManagerError> {
+        self.history.lock().map_err(|_| {
+            ProposalManagerError::Internal(Box::new(SimulationError::Internal(
+                "simulation history lock poisoned".into(),
+            )))
+        })
+    }
+}
+
+impl ProposalManager for SimulatedProposalManager {
+    fn create_proposal(
+        &self,
+        previous_proposal_id: Option<ProposalId>,
+        consensus_data: Vec<u8>,
+    ) -> Result<(), ProposalManagerError> {
+        let count = self.created.load(Ordering::SeqCst);
+        if count >= self.max_proposals {
+            self.update_sender
+                .send(ProposalUpdate::ProposalCreated(None))?;
+            return Ok(());
+        }
+        self.created.store(count + 1, Ordering::SeqCst);
+
+        let mut id = vec![self.node as u8];
+        id.extend_from_slice(&count.to_be_bytes());
+
+        let valid = self.invalid_rate <= 0.0
+            || !StdRng::seed_from_u64(self.seed ^ ((self.node as u64) << 32) ^ count)
+                .gen_bool(self.invalid_rate);
+
+        let proposal = Proposal {
+            id: id.into(),
+            previous_id: previous_proposal_id.unwrap_or_default(),
+            proposal_height: count + 1,
+            summary: vec![valid as u8],
+            consensus_data,
+        };
+
+        {
+            let mut history = self.history()?;
+            history.created.insert(proposal.id.clone(), valid);
+            history.known[self.node].insert(proposal.id.clone());
+        }
+
+        for peer in &self.peers {
+            self.outbox
+                .send(*peer, Payload::Proposal(proposal.clone()))
+                .map_err(|err| ProposalManagerError::Internal(Box::new(err)))?;
+        }
+
+        self.update_sender
+            .send(ProposalUpdate::ProposalCreated(Some(proposal)))?;
+
+        Ok(())
+    }
+
+    fn check_proposal(&self, id: &ProposalId) -> Result<(), ProposalManagerError> {
+        let valid = {
+            let history = self.history()?;
+            if !history.known[self.node].contains(id) {
+                return Err(ProposalManagerError::UnknownProposal(id.clone()));
+            }
+            history.created.get(id).copied().unwrap_or(false)
+        };
+
+        if valid {
+            self.update_sender
+                .send(ProposalUpdate::ProposalValid(id.clone()))?;
+        } else {
+            self.update_sender
+                .send(ProposalUpdate::ProposalInvalid(id.clone()))?;
+        }
+
+        Ok(())
+    }
+
+    fn accept_proposal(
+        &self,
+        id: &ProposalId,
+        _consensus_data: Option<Vec<u8>>,
+    ) -> Result<(), ProposalManagerError> {
+        self.history()?.accepted[self.node].push(id.clone());
+
+        self.update_sender
+            .send(ProposalUpdate::ProposalAccepted(id.clone()))?;
+
+        Ok(())
+    }
+
+    fn reject_proposal(&self, id: &ProposalId) -> Result<(), ProposalManagerError> {
+        self.history()?.rejected[self.node].push(id.clone());
+        Ok(())
+    }
+
+    fn get_proposal_payload(
+        &self,
+        id: &ProposalId,
+    ) -> Result<Option<Vec<u8>>, ProposalManagerError> {
+        // Every node can look up a proposal's validity in the history, so the payload only needs
+        // to identify the proposal
+        if self.history()?.known[self.node].contains(id) {
+            Ok(Some(id.as_ref().to_vec()))
+        } else {
+            Err(ProposalManagerError::UnknownProposal(id.clone()))
+        }
+    }
+
+    fn add_proposal_payload(
+        &self,
+        id: &ProposalId,
+        payload: &[u8],
+    ) -> Result<(), ProposalManagerError> {
+        if payload != id.as_ref() {
+            return Err(ProposalManagerError::UnknownProposal(id.clone()));
+        }
+        self.history()?.known[self.node].insert(id.clone());
+        Ok(())
+    }
+}
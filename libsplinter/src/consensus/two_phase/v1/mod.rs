@@ -258,11 +258,16 @@ impl TwoPhaseEngine {
                         self.state = State::Idle;
                     }
                 }
-                TwoPhaseMessage_ProposalResult::UNSET_RESULT => warn!(
+                TwoPhaseMessage_ProposalResult::UNKNOWN
+                | TwoPhaseMessage_ProposalResult::UNSET_RESULT => warn!(
                     "Ignoring improperly specified proposal result from {}",
                     consensus_msg.origin_id
                 ),
             },
+            TwoPhaseMessage_Type::PROPOSAL_RESULT_REQUEST => warn!(
+                "Ignoring proposal result request from {}; not supported by this version",
+                consensus_msg.origin_id
+            ),
             TwoPhaseMessage_Type::UNSET_TYPE => warn!(
                 "Ignoring improperly specified two-phase message from {}",
                 consensus_msg.origin_id
@@ -428,7 +433,8 @@ impl TwoPhaseEngine {
             TwoPhaseMessage_ProposalResult::REJECT => {
                 proposal_manager.reject_proposal(&proposal_id)?;
             }
-            TwoPhaseMessage_ProposalResult::UNSET_RESULT => {
+            TwoPhaseMessage_ProposalResult::UNKNOWN
+            | TwoPhaseMessage_ProposalResult::UNSET_RESULT => {
                 warn!(
                     "Unset proposal result when completing proposal {}",
                     proposal_id
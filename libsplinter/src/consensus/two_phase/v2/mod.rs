@@ -20,11 +20,24 @@
 //!
 //! # Known limitations of this 2PC implementation
 //!
-//! A limitation of this implementation is that it is not fully resilient to crashes; for instance,
-//! if the coordinator commits a proposal but crashes before it is able to send the `APPLY` message
-//! to the other nodes, the network will be out of sync because the coordinator does not know to
-//! send the message when it restarts. This limitation will be solved by re-implementing 2PC as a
-//! stateless algorithm.
+//! Without a write-ahead log this implementation is not resilient to crashes; for instance, if the
+//! coordinator commits a proposal but crashes before it is able to send the `APPLY` message to the
+//! other nodes, the network will be out of sync because the coordinator does not know to send the
+//! message when it restarts.
+//!
+//! With the `consensus-two-phase-wal` feature, a log store may be provided with
+//! `TwoPhaseEngine::with_log_store`, and the engine records each state transition before acting on
+//! it. On startup, the coordinator finishes any proposal it was evaluating (rejecting it if no
+//! decision was recorded) and broadcasts the result again, while a participant that had verified a
+//! proposal asks the coordinator for its result before it evaluates anything else. Applying a
+//! recovered result still requires the proposal manager to know the proposal after the restart; if
+//! it does not, the error is logged and the node moves on.
+//!
+//! The coordinator keeps its most recent decisions, and its log retains them when it is
+//! compacted, so that it can answer a recovering participant. If the coordinator has no record of
+//! a proposal, either because the decision has fallen out of that window or because the
+//! coordinator has no log, it answers `UNKNOWN` rather than presuming that the proposal was
+//! rejected, and the participant keeps the proposal in doubt until an operator intervenes.
 //!
 //! # Differences from previous version
 //!
@@ -39,16 +52,22 @@
 //!   the only node that can reliably produce proposals that are based on the most current state.
 
 mod timing;
+#[cfg(feature = "consensus-two-phase-wal")]
+pub mod wal;
 
+#[cfg(feature = "consensus-two-phase-wal")]
+use std::collections::HashMap;
 use std::collections::{HashSet, VecDeque};
 use std::sync::mpsc::{Receiver, RecvTimeoutError};
 use std::time::Duration;
 
 use protobuf::Message;
 
+#[cfg(feature = "consensus-simulation")]
+use crate::consensus::simulation::SimulatedEngine;
 use crate::consensus::{
-    ConsensusEngine, ConsensusEngineError, ConsensusMessage, ConsensusNetworkSender, PeerId,
-    ProposalId, ProposalManager, ProposalUpdate, StartupState,
+    Clock, ConsensusEngine, ConsensusEngineError, ConsensusMessage, ConsensusNetworkSender, PeerId,
+    ProposalId, ProposalManager, ProposalUpdate, StartupState, SystemClock,
 };
 use crate::protos::two_phase::{
     TwoPhaseMessage, TwoPhaseMessage_ProposalResult, TwoPhaseMessage_ProposalVerificationResponse,
@@ -56,10 +75,22 @@ use crate::protos::two_phase::{
 };
 
 use self::timing::Timeout;
+#[cfg(feature = "consensus-two-phase-wal")]
+use self::wal::{TwoPhaseLogEntry, TwoPhaseLogStore};
 
 const MESSAGE_RECV_TIMEOUT_MILLIS: u64 = 100;
 const PROPOSAL_RECV_TIMEOUT_MILLIS: u64 = 100;
 
+/// The number of log entries, beyond the recorded decisions, after which the log is rewritten to
+/// contain only the decisions.
+#[cfg(feature = "consensus-two-phase-wal")]
+const LOG_COMPACTION_THRESHOLD: usize = 256;
+
+/// The number of the coordinator's most recent decisions that are kept to answer recovering
+/// participants.
+#[cfg(feature = "consensus-two-phase-wal")]
+const DECISIONS_RETAINED: usize = 1024;
+
 #[derive(Debug)]
 enum State {
     Idle,
@@ -112,6 +143,14 @@ impl TwoPhaseProposal {
     }
 }
 
+/// How far a proposal found in the write-ahead log had progressed when the engine stopped.
+#[cfg(feature = "consensus-two-phase-wal")]
+enum RecoveredProgress {
+    Evaluating,
+    Voted(bool),
+    Decided(bool),
+}
+
 pub struct TwoPhaseEngine {
     id: PeerId,
     verifiers: HashSet<PeerId>,
@@ -119,6 +158,17 @@ pub struct TwoPhaseEngine {
     coordinator_timeout: Timeout,
     proposals_received: HashSet<ProposalId>,
     verification_request_backlog: VecDeque<ProposalId>,
+    #[cfg(feature = "consensus-two-phase-wal")]
+    log: Option<Box<dyn TwoPhaseLogStore>>,
+    #[cfg(feature = "consensus-two-phase-wal")]
+    log_len: usize,
+    #[cfg(feature = "consensus-two-phase-wal")]
+    decisions: HashMap<ProposalId, bool>,
+    #[cfg(feature = "consensus-two-phase-wal")]
+    decision_order: VecDeque<ProposalId>,
+    in_doubt: Option<ProposalId>,
+    recovery_timeout: Timeout,
+    clock: Box<dyn Clock>,
 }
 
 impl TwoPhaseEngine {
@@ -130,9 +180,28 @@ impl TwoPhaseEngine {
             coordinator_timeout: Timeout::new(coordinator_timeout_duration),
             proposals_received: HashSet::new(),
             verification_request_backlog: VecDeque::new(),
+            #[cfg(feature = "consensus-two-phase-wal")]
+            log: None,
+            #[cfg(feature = "consensus-two-phase-wal")]
+            log_len: 0,
+            #[cfg(feature = "consensus-two-phase-wal")]
+            decisions: HashMap::new(),
+            #[cfg(feature = "consensus-two-phase-wal")]
+            decision_order: VecDeque::new(),
+            in_doubt: None,
+            recovery_timeout: Timeout::new(coordinator_timeout_duration),
+            clock: Box::new(SystemClock),
         }
     }
 
+    /// Records the engine's state transitions in the given store, and recovers from the entries
+    /// already in it when the engine is started.
+    #[cfg(feature = "consensus-two-phase-wal")]
+    pub fn with_log_store(mut self, store: Box<dyn TwoPhaseLogStore>) -> Self {
+        self.log = Some(store);
+        self
+    }
+
     /// Determines if this node is the coordinator.
     fn is_coordinator(&self) -> bool {
         &self.id == self.coordinator_id()
@@ -166,10 +235,19 @@ impl TwoPhaseEngine {
                         proposal_id
                     );
                     self.verification_request_backlog.push_back(proposal_id);
+                } else if self.in_doubt.is_some() {
+                    debug!(
+                        "Awaiting result of recovered proposal, backlogging verification \
+                         request: {}",
+                        proposal_id
+                    );
+                    self.verification_request_backlog.push_back(proposal_id);
                 } else {
                     // Try to get the proposal from the backlog
                     if self.proposals_received.remove(&proposal_id) {
                         debug!("Checking proposal {}", proposal_id);
+                        #[cfg(feature = "consensus-two-phase-wal")]
+                        self.write_ahead(TwoPhaseLogEntry::Evaluating(proposal_id.clone()))?;
                         proposal_manager.check_proposal(&proposal_id)?;
                         self.state = State::EvaluatingProposal(TwoPhaseProposal::new(proposal_id));
                     } else {
@@ -237,10 +315,13 @@ impl TwoPhaseEngine {
             }
             TwoPhaseMessage_Type::PROPOSAL_RESULT => match two_phase_msg.get_proposal_result() {
                 TwoPhaseMessage_ProposalResult::APPLY => {
-                    if self.state.is_evaluating_proposal_with_id(&proposal_id) {
+                    if self.in_doubt.as_ref() == Some(&proposal_id) {
+                        self.apply_recovered_result(proposal_id, true, proposal_manager)?;
+                    } else if self.state.is_evaluating_proposal_with_id(&proposal_id) {
                         debug!("Accepting proposal {}", proposal_id);
                         proposal_manager.accept_proposal(&proposal_id, None)?;
                         self.state = State::Idle;
+                        self.log_completed(proposal_id)?;
                     } else {
                         warn!(
                             "Received unexpected apply result for proposal {}",
@@ -249,12 +330,33 @@ impl TwoPhaseEngine {
                     }
                 }
                 TwoPhaseMessage_ProposalResult::REJECT => {
-                    debug!("Rejecting proposal {}", proposal_id);
-                    proposal_manager.reject_proposal(&proposal_id)?;
+                    if self.in_doubt.as_ref() == Some(&proposal_id) {
+                        self.apply_recovered_result(proposal_id, false, proposal_manager)?;
+                    } else {
+                        debug!("Rejecting proposal {}", proposal_id);
+                        proposal_manager.reject_proposal(&proposal_id)?;
 
-                    // Only update state if this was the currently evaluating proposal
-                    if self.state.is_evaluating_proposal_with_id(&proposal_id) {
-                        self.state = State::Idle;
+                        // Only update state if this was the currently evaluating proposal
+                        if self.state.is_evaluating_proposal_with_id(&proposal_id) {
+                            self.state = State::Idle;
+                            self.log_completed(proposal_id)?;
+                        }
+                    }
+                }
+                TwoPhaseMessage_ProposalResult::UNKNOWN => {
+                    if self.in_doubt.as_ref() == Some(&proposal_id) {
+                        // Applying either result could leave this node out of sync with the
+                        // others, so the proposal stays in doubt and the request is retried
+                        error!(
+                            "Coordinator {} has no record of the result of proposal {}; the \
+                             proposal remains in doubt and must be resolved by an operator",
+                            consensus_msg.origin_id, proposal_id
+                        );
+                    } else {
+                        warn!(
+                            "Received unexpected unknown result for proposal {}",
+                            proposal_id
+                        );
                     }
                 }
                 TwoPhaseMessage_ProposalResult::UNSET_RESULT => warn!(
@@ -262,6 +364,47 @@ impl TwoPhaseEngine {
                     consensus_msg.origin_id
                 ),
             },
+            TwoPhaseMessage_Type::PROPOSAL_RESULT_REQUEST => {
+                if !self.is_coordinator() {
+                    warn!(
+                        "Ignoring proposal result request from {}; this node is not the \
+                         coordinator",
+                        consensus_msg.origin_id
+                    );
+                } else if self.state.is_evaluating_proposal_with_id(&proposal_id) {
+                    debug!(
+                        "Proposal {} still being evaluated; ignoring result request from {}",
+                        proposal_id, consensus_msg.origin_id
+                    );
+                } else {
+                    // Without a recorded decision, the result cannot be presumed; the proposal may
+                    // have been decided before this node's history was lost
+                    let proposal_result = match self.recorded_decision(&proposal_id) {
+                        Some(true) => TwoPhaseMessage_ProposalResult::APPLY,
+                        Some(false) => TwoPhaseMessage_ProposalResult::REJECT,
+                        None => {
+                            warn!(
+                                "No decision recorded for proposal {}; answering result request \
+                                 from {} with unknown",
+                                proposal_id, consensus_msg.origin_id
+                            );
+                            TwoPhaseMessage_ProposalResult::UNKNOWN
+                        }
+                    };
+
+                    debug!(
+                        "Sending result of proposal {} to {}",
+                        proposal_id, consensus_msg.origin_id
+                    );
+
+                    let mut result = TwoPhaseMessage::new();
+                    result.set_message_type(TwoPhaseMessage_Type::PROPOSAL_RESULT);
+                    result.set_proposal_id(proposal_id.into());
+                    result.set_proposal_result(proposal_result);
+
+                    network_sender.send_to(&consensus_msg.origin_id, result.write_to_bytes()?)?;
+                }
+            }
             TwoPhaseMessage_Type::UNSET_TYPE => warn!(
                 "Ignoring improperly specified two-phase message from {}",
                 consensus_msg.origin_id
@@ -324,6 +467,12 @@ impl TwoPhaseEngine {
                     } else {
                         debug!("Sending verified response for proposal {}", proposal_id);
 
+                        #[cfg(feature = "consensus-two-phase-wal")]
+                        self.write_ahead(TwoPhaseLogEntry::Voted {
+                            proposal_id: proposal_id.clone(),
+                            verified: true,
+                        })?;
+
                         let mut response = TwoPhaseMessage::new();
                         response
                             .set_message_type(TwoPhaseMessage_Type::PROPOSAL_VERIFICATION_RESPONSE);
@@ -353,6 +502,12 @@ impl TwoPhaseEngine {
                     } else {
                         debug!("Sending failed response for proposal {}", proposal_id);
 
+                        #[cfg(feature = "consensus-two-phase-wal")]
+                        self.write_ahead(TwoPhaseLogEntry::Voted {
+                            proposal_id: proposal_id.clone(),
+                            verified: false,
+                        })?;
+
                         let mut response = TwoPhaseMessage::new();
                         response
                             .set_message_type(TwoPhaseMessage_Type::PROPOSAL_VERIFICATION_RESPONSE);
@@ -392,10 +547,12 @@ impl TwoPhaseEngine {
         proposal_manager: &dyn ProposalManager,
     ) -> Result<(), ConsensusEngineError> {
         debug!("Checking proposal {}", proposal_id);
+        #[cfg(feature = "consensus-two-phase-wal")]
+        self.write_ahead(TwoPhaseLogEntry::Evaluating(proposal_id.clone()))?;
         match proposal_manager.check_proposal(&proposal_id) {
             Ok(_) => {
                 self.state = State::EvaluatingProposal(TwoPhaseProposal::new(proposal_id));
-                self.coordinator_timeout.start();
+                self.coordinator_timeout.start(self.clock.now());
             }
             Err(err) => {
                 debug!(
@@ -420,25 +577,47 @@ impl TwoPhaseEngine {
         network_sender: &dyn ConsensusNetworkSender,
         proposal_manager: &dyn ProposalManager,
     ) -> Result<(), ConsensusEngineError> {
-        match proposal_result {
-            TwoPhaseMessage_ProposalResult::APPLY => {
-                proposal_manager.accept_proposal(&proposal_id, None)?;
-            }
-            TwoPhaseMessage_ProposalResult::REJECT => {
-                proposal_manager.reject_proposal(&proposal_id)?;
-            }
-            TwoPhaseMessage_ProposalResult::UNSET_RESULT => {
+        let apply = match proposal_result {
+            TwoPhaseMessage_ProposalResult::APPLY => true,
+            TwoPhaseMessage_ProposalResult::REJECT => false,
+            TwoPhaseMessage_ProposalResult::UNKNOWN
+            | TwoPhaseMessage_ProposalResult::UNSET_RESULT => {
                 warn!(
                     "Unset proposal result when completing proposal {}",
                     proposal_id
                 );
                 return Ok(());
             }
+        };
+
+        #[cfg(feature = "consensus-two-phase-wal")]
+        self.write_ahead(TwoPhaseLogEntry::Decided {
+            proposal_id: proposal_id.clone(),
+            apply,
+        })?;
+        #[cfg(feature = "consensus-two-phase-wal")]
+        self.record_decision(proposal_id.clone(), apply);
+
+        if apply {
+            proposal_manager.accept_proposal(&proposal_id, None)?;
+        } else {
+            proposal_manager.reject_proposal(&proposal_id)?;
         }
 
         self.state = State::Idle;
         self.coordinator_timeout.stop();
 
+        self.broadcast_result(proposal_id.clone(), proposal_result, network_sender)?;
+
+        self.log_completed(proposal_id)
+    }
+
+    fn broadcast_result(
+        &self,
+        proposal_id: ProposalId,
+        proposal_result: TwoPhaseMessage_ProposalResult,
+        network_sender: &dyn ConsensusNetworkSender,
+    ) -> Result<(), ConsensusEngineError> {
         let mut result = TwoPhaseMessage::new();
         result.set_message_type(TwoPhaseMessage_Type::PROPOSAL_RESULT);
         result.set_proposal_id(proposal_id.into());
@@ -449,6 +628,248 @@ impl TwoPhaseEngine {
         Ok(())
     }
 
+    /// Appends an entry to the write-ahead log, if one is configured.
+    #[cfg(feature = "consensus-two-phase-wal")]
+    fn write_ahead(&mut self, entry: TwoPhaseLogEntry) -> Result<(), ConsensusEngineError> {
+        if let Some(log) = self.log.as_mut() {
+            log.append(entry)?;
+            self.log_len += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Records that the result of a proposal has been applied locally. Once the log has grown
+    /// past the compaction threshold, it is rewritten to contain only the retained decisions.
+    #[cfg(feature = "consensus-two-phase-wal")]
+    fn log_completed(&mut self, proposal_id: ProposalId) -> Result<(), ConsensusEngineError> {
+        self.write_ahead(TwoPhaseLogEntry::Completed(proposal_id))?;
+
+        if self.log_len > self.decisions.len() + LOG_COMPACTION_THRESHOLD {
+            if let Some(log) = self.log.as_mut() {
+                let decisions = &self.decisions;
+                let entries = self
+                    .decision_order
+                    .iter()
+                    .filter_map(|proposal_id| {
+                        decisions
+                            .get(proposal_id)
+                            .map(|apply| TwoPhaseLogEntry::Decided {
+                                proposal_id: proposal_id.clone(),
+                                apply: *apply,
+                            })
+                    })
+                    .collect::<Vec<_>>();
+                self.log_len = entries.len();
+                log.rewrite(entries)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "consensus-two-phase-wal"))]
+    fn log_completed(&mut self, _proposal_id: ProposalId) -> Result<(), ConsensusEngineError> {
+        Ok(())
+    }
+
+    /// Records the coordinator's decision on a proposal, dropping the oldest decision once more
+    /// than `DECISIONS_RETAINED` are kept.
+    #[cfg(feature = "consensus-two-phase-wal")]
+    fn record_decision(&mut self, proposal_id: ProposalId, apply: bool) {
+        if self.decisions.insert(proposal_id.clone(), apply).is_none() {
+            self.decision_order.push_back(proposal_id);
+        }
+
+        while self.decision_order.len() > DECISIONS_RETAINED {
+            if let Some(oldest) = self.decision_order.pop_front() {
+                self.decisions.remove(&oldest);
+            }
+        }
+    }
+
+    #[cfg(feature = "consensus-two-phase-wal")]
+    fn recorded_decision(&self, proposal_id: &ProposalId) -> Option<bool> {
+        self.decisions.get(proposal_id).copied()
+    }
+
+    #[cfg(not(feature = "consensus-two-phase-wal"))]
+    fn recorded_decision(&self, _proposal_id: &ProposalId) -> Option<bool> {
+        None
+    }
+
+    /// Replays the write-ahead log and resolves the proposal that was in flight when the engine
+    /// stopped, if any.
+    #[cfg(feature = "consensus-two-phase-wal")]
+    fn recover(
+        &mut self,
+        network_sender: &dyn ConsensusNetworkSender,
+        proposal_manager: &dyn ProposalManager,
+    ) -> Result<(), ConsensusEngineError> {
+        let entries = match &self.log {
+            Some(log) => log.entries()?,
+            None => return Ok(()),
+        };
+        self.log_len = entries.len();
+
+        let mut in_flight: Option<(ProposalId, RecoveredProgress)> = None;
+        for entry in entries {
+            match entry {
+                TwoPhaseLogEntry::Evaluating(proposal_id) => {
+                    in_flight = Some((proposal_id, RecoveredProgress::Evaluating));
+                }
+                TwoPhaseLogEntry::Voted {
+                    proposal_id,
+                    verified,
+                } => {
+                    if let Some((id, progress)) = &mut in_flight {
+                        if id == &proposal_id {
+                            *progress = RecoveredProgress::Voted(verified);
+                        }
+                    }
+                }
+                TwoPhaseLogEntry::Decided { proposal_id, apply } => {
+                    if let Some((id, progress)) = &mut in_flight {
+                        if id == &proposal_id {
+                            *progress = RecoveredProgress::Decided(apply);
+                        }
+                    }
+                    self.record_decision(proposal_id, apply);
+                }
+                TwoPhaseLogEntry::Completed(proposal_id) => {
+                    if matches!(&in_flight, Some((id, _)) if id == &proposal_id) {
+                        in_flight = None;
+                    }
+                }
+            }
+        }
+
+        let (proposal_id, progress) = match in_flight {
+            Some(in_flight) => in_flight,
+            None => return Ok(()),
+        };
+
+        if self.is_coordinator() {
+            let apply = match progress {
+                RecoveredProgress::Decided(apply) => apply,
+                _ => {
+                    self.write_ahead(TwoPhaseLogEntry::Decided {
+                        proposal_id: proposal_id.clone(),
+                        apply: false,
+                    })?;
+                    self.record_decision(proposal_id.clone(), false);
+                    false
+                }
+            };
+
+            self.apply_recovered_result(proposal_id.clone(), apply, proposal_manager)?;
+
+            let proposal_result = if apply {
+                TwoPhaseMessage_ProposalResult::APPLY
+            } else {
+                TwoPhaseMessage_ProposalResult::REJECT
+            };
+            if let Err(err) =
+                self.broadcast_result(proposal_id.clone(), proposal_result, network_sender)
+            {
+                warn!(
+                    "Unable to broadcast result of recovered proposal {}: {}",
+                    proposal_id, err
+                );
+            }
+        } else if let RecoveredProgress::Voted(true) = progress {
+            // The coordinator may have applied the proposal; it must be asked for the result
+            info!(
+                "Proposal {} was verified before restart; requesting result from coordinator",
+                proposal_id
+            );
+            self.state = State::EvaluatingProposal(TwoPhaseProposal::new(proposal_id.clone()));
+            self.in_doubt = Some(proposal_id.clone());
+            if let Err(err) = self.request_recovered_result(network_sender) {
+                warn!(
+                    "Unable to request result of recovered proposal {}: {}",
+                    proposal_id, err
+                );
+            }
+        } else {
+            // This node did not verify the proposal, so the coordinator cannot have applied it
+            self.apply_recovered_result(proposal_id, false, proposal_manager)?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "consensus-two-phase-wal"))]
+    fn recover(
+        &mut self,
+        _network_sender: &dyn ConsensusNetworkSender,
+        _proposal_manager: &dyn ProposalManager,
+    ) -> Result<(), ConsensusEngineError> {
+        Ok(())
+    }
+
+    /// Applies the result of a proposal that was in flight when the engine stopped. The proposal
+    /// manager may no longer know the proposal, so its errors are logged rather than returned.
+    fn apply_recovered_result(
+        &mut self,
+        proposal_id: ProposalId,
+        apply: bool,
+        proposal_manager: &dyn ProposalManager,
+    ) -> Result<(), ConsensusEngineError> {
+        let res = if apply {
+            info!("Accepting recovered proposal {}", proposal_id);
+            proposal_manager.accept_proposal(&proposal_id, None)
+        } else {
+            info!("Rejecting recovered proposal {}", proposal_id);
+            proposal_manager.reject_proposal(&proposal_id)
+        };
+        if let Err(err) = res {
+            error!(
+                "Failed to apply result of recovered proposal {}: {}",
+                proposal_id, err
+            );
+        }
+
+        self.in_doubt = None;
+        self.recovery_timeout.stop();
+        self.state = State::Idle;
+
+        self.log_completed(proposal_id)
+    }
+
+    /// Asks the coordinator for the result of the in-doubt proposal.
+    fn request_recovered_result(
+        &mut self,
+        network_sender: &dyn ConsensusNetworkSender,
+    ) -> Result<(), ConsensusEngineError> {
+        if let Some(proposal_id) = &self.in_doubt {
+            // Started first so that a failed send is retried
+            self.recovery_timeout.start(self.clock.now());
+
+            debug!("Requesting result of proposal {}", proposal_id);
+
+            let mut request = TwoPhaseMessage::new();
+            request.set_message_type(TwoPhaseMessage_Type::PROPOSAL_RESULT_REQUEST);
+            request.set_proposal_id(proposal_id.clone().into());
+
+            network_sender.send_to(self.coordinator_id(), request.write_to_bytes()?)?;
+        }
+
+        Ok(())
+    }
+
+    /// If the result of the in-doubt proposal has not been received in time, request it again.
+    fn rerequest_result_if_timed_out(
+        &mut self,
+        network_sender: &dyn ConsensusNetworkSender,
+    ) -> Result<(), ConsensusEngineError> {
+        if self.in_doubt.is_some() && self.recovery_timeout.check_expired(self.clock.now()) {
+            self.request_recovered_result(network_sender)?;
+        }
+
+        Ok(())
+    }
+
     /// If the coordinator timeout has expired, abort the current proposal.
     fn abort_proposal_if_timed_out(
         &mut self,
@@ -456,7 +877,7 @@ impl TwoPhaseEngine {
         proposal_manager: &dyn ProposalManager,
     ) -> Result<(), ConsensusEngineError> {
         if let State::EvaluatingProposal(ref tpc_proposal) = self.state {
-            if self.coordinator_timeout.check_expired() {
+            if self.coordinator_timeout.check_expired(self.clock.now()) {
                 warn!(
                     "Proposal timed out; rejecting: {}",
                     tpc_proposal.proposal_id()
@@ -490,6 +911,8 @@ impl TwoPhaseEngine {
                 self.proposals_received.remove(&proposal_id);
 
                 debug!("Checking proposal from backlog: {}", proposal_id);
+                #[cfg(feature = "consensus-two-phase-wal")]
+                self.write_ahead(TwoPhaseLogEntry::Evaluating(proposal_id.clone()))?;
                 proposal_manager.check_proposal(&proposal_id)?;
                 self.state = State::EvaluatingProposal(TwoPhaseProposal::new(proposal_id));
             }
@@ -507,6 +930,45 @@ impl TwoPhaseEngine {
             }
         }
     }
+
+    /// Sets up the engine's peers and recovers its progress, before it handles any messages.
+    fn start(
+        &mut self,
+        network_sender: &dyn ConsensusNetworkSender,
+        proposal_manager: &dyn ProposalManager,
+        startup_state: StartupState,
+    ) -> Result<(), ConsensusEngineError> {
+        self.id = startup_state.id;
+        self.verifiers.insert(self.id.clone()); // This node is a verifier
+
+        for id in startup_state.peer_ids {
+            self.verifiers.insert(id);
+        }
+
+        self.recover(network_sender, proposal_manager)
+    }
+
+    /// Performs the work that is driven by time or by the engine's own state: timing out
+    /// proposals, evaluating backlogged proposals and asking for new ones.
+    fn handle_timers(
+        &mut self,
+        network_sender: &dyn ConsensusNetworkSender,
+        proposal_manager: &dyn ProposalManager,
+    ) {
+        if let Err(err) = self.rerequest_result_if_timed_out(network_sender) {
+            error!("Failed to request result of recovered proposal: {}", err);
+        }
+
+        if let Err(err) = self.abort_proposal_if_timed_out(network_sender, proposal_manager) {
+            error!("Failed to abort timed-out proposal: {}", err);
+        }
+
+        if let Err(err) = self.handle_backlogged_verification_request(proposal_manager) {
+            error!("Failed to handle backlogged verification request: {}", err);
+        }
+
+        self.get_next_proposal(proposal_manager);
+    }
 }
 
 impl ConsensusEngine for TwoPhaseEngine {
@@ -533,24 +995,10 @@ impl ConsensusEngine for TwoPhaseEngine {
         let message_timeout = Duration::from_millis(MESSAGE_RECV_TIMEOUT_MILLIS);
         let proposal_timeout = Duration::from_millis(PROPOSAL_RECV_TIMEOUT_MILLIS);
 
-        self.id = startup_state.id;
-        self.verifiers.insert(self.id.clone()); // This node is a verifier
-
-        for id in startup_state.peer_ids {
-            self.verifiers.insert(id);
-        }
+        self.start(&*network_sender, &*proposal_manager, startup_state)?;
 
         loop {
-            if let Err(err) = self.abort_proposal_if_timed_out(&*network_sender, &*proposal_manager)
-            {
-                error!("Failed to abort timed-out proposal: {}", err);
-            }
-
-            if let Err(err) = self.handle_backlogged_verification_request(&*proposal_manager) {
-                error!("Failed to handle backlogged verification request: {}", err);
-            }
-
-            self.get_next_proposal(&*proposal_manager);
+            self.handle_timers(&*network_sender, &*proposal_manager);
 
             // Get and handle a consensus message if there is one
             match consensus_messages.recv_timeout(message_timeout) {
@@ -595,6 +1043,49 @@ impl ConsensusEngine for TwoPhaseEngine {
     }
 }
 
+#[cfg(feature = "consensus-simulation")]
+impl SimulatedEngine for TwoPhaseEngine {
+    fn start(
+        &mut self,
+        clock: Box<dyn Clock>,
+        // Two-phase commit makes no random choices
+        _seed: u64,
+        network_sender: &dyn ConsensusNetworkSender,
+        proposal_manager: &dyn ProposalManager,
+        startup_state: StartupState,
+    ) -> Result<(), ConsensusEngineError> {
+        self.clock = clock;
+        self.start(network_sender, proposal_manager, startup_state)
+    }
+
+    fn handle_consensus_msg(
+        &mut self,
+        consensus_msg: ConsensusMessage,
+        network_sender: &dyn ConsensusNetworkSender,
+        proposal_manager: &dyn ProposalManager,
+    ) -> Result<(), ConsensusEngineError> {
+        self.handle_consensus_msg(consensus_msg, network_sender, proposal_manager)
+    }
+
+    fn handle_proposal_update(
+        &mut self,
+        update: ProposalUpdate,
+        network_sender: &dyn ConsensusNetworkSender,
+        proposal_manager: &dyn ProposalManager,
+    ) -> Result<(), ConsensusEngineError> {
+        self.handle_proposal_update(update, network_sender, proposal_manager)
+    }
+
+    fn handle_timers(
+        &mut self,
+        network_sender: &dyn ConsensusNetworkSender,
+        proposal_manager: &dyn ProposalManager,
+    ) -> Result<(), ConsensusEngineError> {
+        self.handle_timers(network_sender, proposal_manager);
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
@@ -605,6 +1096,9 @@ pub mod tests {
     use crate::consensus::tests::{MockConsensusNetworkSender, MockProposalManager};
     use crate::consensus::Proposal;
 
+    #[cfg(feature = "consensus-two-phase-wal")]
+    use super::wal::MemoryTwoPhaseLogStore;
+
     const COORDINATOR_TIMEOUT_MILLIS: u64 = 5000;
 
     /// Verify that the engine properly shuts down when it receives the Shutdown update.
@@ -653,6 +1147,17 @@ pub mod tests {
             coordinator_timeout: Timeout::new(Duration::from_millis(COORDINATOR_TIMEOUT_MILLIS)),
             proposals_received: HashSet::new(),
             verification_request_backlog: VecDeque::new(),
+            #[cfg(feature = "consensus-two-phase-wal")]
+            log: None,
+            #[cfg(feature = "consensus-two-phase-wal")]
+            log_len: 0,
+            #[cfg(feature = "consensus-two-phase-wal")]
+            decisions: HashMap::new(),
+            #[cfg(feature = "consensus-two-phase-wal")]
+            decision_order: VecDeque::new(),
+            in_doubt: None,
+            recovery_timeout: Timeout::new(Duration::from_millis(COORDINATOR_TIMEOUT_MILLIS)),
+            clock: Box::new(SystemClock),
         };
         assert_eq!(coordinator.coordinator_id(), &peer_ids[0]);
         assert!(coordinator.is_coordinator());
@@ -664,6 +1169,17 @@ pub mod tests {
             coordinator_timeout: Timeout::new(Duration::from_millis(COORDINATOR_TIMEOUT_MILLIS)),
             proposals_received: HashSet::new(),
             verification_request_backlog: VecDeque::new(),
+            #[cfg(feature = "consensus-two-phase-wal")]
+            log: None,
+            #[cfg(feature = "consensus-two-phase-wal")]
+            log_len: 0,
+            #[cfg(feature = "consensus-two-phase-wal")]
+            decisions: HashMap::new(),
+            #[cfg(feature = "consensus-two-phase-wal")]
+            decision_order: VecDeque::new(),
+            in_doubt: None,
+            recovery_timeout: Timeout::new(Duration::from_millis(COORDINATOR_TIMEOUT_MILLIS)),
+            clock: Box::new(SystemClock),
         };
         assert_eq!(other_node.coordinator_id(), &peer_ids[0]);
         assert!(!other_node.is_coordinator());
@@ -1065,4 +1581,273 @@ pub mod tests {
             .expect("failed to send shutdown");
         thread.join().expect("failed to join engine thread");
     }
+
+    /// Test that a participant that verified a proposal before restarting asks the coordinator
+    /// for the proposal's result, applies the result it receives, and records the proposal as
+    /// completed in its log.
+    #[cfg(feature = "consensus-two-phase-wal")]
+    #[test]
+    fn test_participant_recovery() {
+        let (update_tx, update_rx) = channel();
+        let (consensus_msg_tx, consensus_msg_rx) = channel();
+
+        let manager = MockProposalManager::new(update_tx.clone());
+        manager.set_return_proposal(false);
+        let network = MockConsensusNetworkSender::new();
+        let startup_state = StartupState {
+            id: vec![1].into(),
+            peer_ids: vec![vec![0].into()],
+            last_proposal: None,
+        };
+
+        let mut log = MemoryTwoPhaseLogStore::new();
+        log.append(TwoPhaseLogEntry::Evaluating(vec![1].into()))
+            .expect("failed to append entry");
+        log.append(TwoPhaseLogEntry::Voted {
+            proposal_id: vec![1].into(),
+            verified: true,
+        })
+        .expect("failed to append entry");
+
+        let mut engine = TwoPhaseEngine::new(Duration::from_millis(COORDINATOR_TIMEOUT_MILLIS))
+            .with_log_store(Box::new(log.clone()));
+        let network_clone = network.clone();
+        let manager_clone = manager.clone();
+        let thread = std::thread::spawn(move || {
+            engine
+                .run(
+                    consensus_msg_rx,
+                    update_rx,
+                    Box::new(network_clone),
+                    Box::new(manager_clone),
+                    startup_state,
+                )
+                .expect("engine failed")
+        });
+
+        // Check that the result of the proposal is requested from the coordinator
+        loop {
+            if let Some((msg, peer_id)) = network.sent_messages().get(0) {
+                let msg: TwoPhaseMessage =
+                    Message::parse_from_bytes(msg).expect("failed to parse message");
+                assert_eq!(peer_id, &vec![0].into());
+                assert_eq!(
+                    msg.get_message_type(),
+                    TwoPhaseMessage_Type::PROPOSAL_RESULT_REQUEST
+                );
+                assert_eq!(msg.get_proposal_id(), vec![1].as_slice());
+                break;
+            }
+        }
+
+        // Receive the Apply result
+        let mut result = TwoPhaseMessage::new();
+        result.set_message_type(TwoPhaseMessage_Type::PROPOSAL_RESULT);
+        result.set_proposal_id(vec![1]);
+        result.set_proposal_result(TwoPhaseMessage_ProposalResult::APPLY);
+        let message_bytes = result
+            .write_to_bytes()
+            .expect("failed to write apply result to bytes");
+
+        consensus_msg_tx
+            .send(ConsensusMessage::new(message_bytes, vec![0].into()))
+            .expect("failed to send apply result");
+
+        // Verify the proposal was accepted and recorded as completed
+        loop {
+            if let Some((id, _)) = manager.accepted_proposals().get(0) {
+                assert_eq!(id, &vec![1].into());
+                break;
+            }
+        }
+        loop {
+            let entries = log.entries().expect("failed to get log entries");
+            if let Some(entry) = entries.get(2) {
+                assert_eq!(entry, &TwoPhaseLogEntry::Completed(vec![1].into()));
+                break;
+            }
+        }
+
+        update_tx
+            .send(ProposalUpdate::Shutdown)
+            .expect("failed to send shutdown");
+        thread.join().expect("failed to join engine thread");
+    }
+
+    /// Test that a coordinator that restarts before deciding a proposal rejects it, broadcasts
+    /// the result, and answers a participant's result request for the proposal with the same
+    /// result. A request for a proposal without a recorded decision is answered with unknown.
+    #[cfg(feature = "consensus-two-phase-wal")]
+    #[test]
+    fn test_coordinator_recovery() {
+        let (update_tx, update_rx) = channel();
+        let (consensus_msg_tx, consensus_msg_rx) = channel();
+
+        let manager = MockProposalManager::new(update_tx.clone());
+        manager.set_return_proposal(false);
+        let network = MockConsensusNetworkSender::new();
+        let startup_state = StartupState {
+            id: vec![0].into(),
+            peer_ids: vec![vec![1].into(), vec![2].into()],
+            last_proposal: None,
+        };
+
+        let mut log = MemoryTwoPhaseLogStore::new();
+        log.append(TwoPhaseLogEntry::Evaluating(vec![9].into()))
+            .expect("failed to append entry");
+
+        let mut engine = TwoPhaseEngine::new(Duration::from_millis(COORDINATOR_TIMEOUT_MILLIS))
+            .with_log_store(Box::new(log.clone()));
+        let network_clone = network.clone();
+        let manager_clone = manager.clone();
+        let thread = std::thread::spawn(move || {
+            engine
+                .run(
+                    consensus_msg_rx,
+                    update_rx,
+                    Box::new(network_clone),
+                    Box::new(manager_clone),
+                    startup_state,
+                )
+                .expect("engine failed")
+        });
+
+        // Verify the Reject message is broadcast for the recovered proposal
+        loop {
+            if let Some(msg) = network.broadcast_messages().get(0) {
+                let msg: TwoPhaseMessage =
+                    Message::parse_from_bytes(msg).expect("failed to parse message");
+                assert_eq!(
+                    msg.get_message_type(),
+                    TwoPhaseMessage_Type::PROPOSAL_RESULT
+                );
+                assert_eq!(
+                    msg.get_proposal_result(),
+                    TwoPhaseMessage_ProposalResult::REJECT
+                );
+                assert_eq!(msg.get_proposal_id(), vec![9].as_slice());
+                break;
+            }
+        }
+
+        assert_eq!(manager.rejected_proposals().get(0), Some(&vec![9].into()));
+        assert_eq!(
+            log.entries().expect("failed to get log entries"),
+            vec![
+                TwoPhaseLogEntry::Evaluating(vec![9].into()),
+                TwoPhaseLogEntry::Decided {
+                    proposal_id: vec![9].into(),
+                    apply: false,
+                },
+                TwoPhaseLogEntry::Completed(vec![9].into()),
+            ]
+        );
+
+        // Receive a result request from a recovering participant
+        let mut request = TwoPhaseMessage::new();
+        request.set_message_type(TwoPhaseMessage_Type::PROPOSAL_RESULT_REQUEST);
+        request.set_proposal_id(vec![9]);
+        let message_bytes = request
+            .write_to_bytes()
+            .expect("failed to write result request to bytes");
+
+        consensus_msg_tx
+            .send(ConsensusMessage::new(message_bytes, vec![1].into()))
+            .expect("failed to send result request");
+
+        // Check that the Reject result is sent to the participant
+        loop {
+            if let Some((msg, peer_id)) = network.sent_messages().get(0) {
+                let msg: TwoPhaseMessage =
+                    Message::parse_from_bytes(msg).expect("failed to parse message");
+                assert_eq!(peer_id, &vec![1].into());
+                assert_eq!(
+                    msg.get_proposal_result(),
+                    TwoPhaseMessage_ProposalResult::REJECT
+                );
+                assert_eq!(msg.get_proposal_id(), vec![9].as_slice());
+                break;
+            }
+        }
+
+        // Receive a result request for a proposal the coordinator has no record of
+        let mut request = TwoPhaseMessage::new();
+        request.set_message_type(TwoPhaseMessage_Type::PROPOSAL_RESULT_REQUEST);
+        request.set_proposal_id(vec![7]);
+        let message_bytes = request
+            .write_to_bytes()
+            .expect("failed to write result request to bytes");
+
+        consensus_msg_tx
+            .send(ConsensusMessage::new(message_bytes, vec![2].into()))
+            .expect("failed to send result request");
+
+        // Check that the result is reported as unknown rather than rejected
+        loop {
+            if let Some((msg, peer_id)) = network.sent_messages().get(1) {
+                let msg: TwoPhaseMessage =
+                    Message::parse_from_bytes(msg).expect("failed to parse message");
+                assert_eq!(peer_id, &vec![2].into());
+                assert_eq!(
+                    msg.get_proposal_result(),
+                    TwoPhaseMessage_ProposalResult::UNKNOWN
+                );
+                assert_eq!(msg.get_proposal_id(), vec![7].as_slice());
+                break;
+            }
+        }
+
+        update_tx
+            .send(ProposalUpdate::Shutdown)
+            .expect("failed to send shutdown");
+        thread.join().expect("failed to join engine thread");
+    }
+
+    /// Test that the coordinator's log and recorded decisions stay bounded over many proposals,
+    /// and that the most recent decisions are kept.
+    ///
+    /// 1. Record the decision and completion of three times as many proposals as are retained.
+    /// 2. Verify that the log holds no more than the retained decisions plus the compaction
+    ///    threshold, and that only the retained number of decisions are recorded.
+    /// 3. Verify that the oldest decision has been dropped, and that the most recent decisions can
+    ///    still be answered and are in the log.
+    #[cfg(feature = "consensus-two-phase-wal")]
+    #[test]
+    fn test_log_and_decisions_bounded() {
+        let log = MemoryTwoPhaseLogStore::new();
+        let mut engine = TwoPhaseEngine::new(Duration::from_millis(COORDINATOR_TIMEOUT_MILLIS))
+            .with_log_store(Box::new(log.clone()));
+
+        let proposals = DECISIONS_RETAINED * 3;
+        for i in 0..proposals {
+            let proposal_id = ProposalId::from((i as u32).to_be_bytes().to_vec());
+            engine
+                .write_ahead(TwoPhaseLogEntry::Decided {
+                    proposal_id: proposal_id.clone(),
+                    apply: i % 2 == 0,
+                })
+                .expect("failed to write decision");
+            engine.record_decision(proposal_id.clone(), i % 2 == 0);
+            engine
+                .log_completed(proposal_id)
+                .expect("failed to complete proposal");
+        }
+
+        let entries = log.entries().expect("failed to get log entries");
+        assert!(entries.len() <= DECISIONS_RETAINED + LOG_COMPACTION_THRESHOLD);
+        assert_eq!(engine.decisions.len(), DECISIONS_RETAINED);
+        assert_eq!(engine.decision_order.len(), DECISIONS_RETAINED);
+
+        let oldest = ProposalId::from(0u32.to_be_bytes().to_vec());
+        assert_eq!(engine.recorded_decision(&oldest), None);
+
+        for i in (proposals - DECISIONS_RETAINED)..proposals {
+            let proposal_id = ProposalId::from((i as u32).to_be_bytes().to_vec());
+            assert_eq!(engine.recorded_decision(&proposal_id), Some(i % 2 == 0));
+            assert!(entries.contains(&TwoPhaseLogEntry::Decided {
+                proposal_id,
+                apply: i % 2 == 0,
+            }));
+        }
+    }
 }
@@ -24,6 +24,9 @@ enum TimeoutState {
 
 /// A timer that expires after a given duration. Check back on this timer every so often to see if
 /// it's expired.
+///
+/// The current time is given by the caller, so that the engine's clock decides when the timer
+/// expires.
 #[derive(Debug)]
 pub struct Timeout {
     state: TimeoutState,
@@ -41,8 +44,10 @@ impl Timeout {
     }
 
     /// Update the timer state, and check if the timer is expired
-    pub fn check_expired(&mut self) -> bool {
-        if self.state == TimeoutState::Active && Instant::now() - self.start > self.duration {
+    pub fn check_expired(&mut self, now: Instant) -> bool {
+        if self.state == TimeoutState::Active
+            && now.saturating_duration_since(self.start) > self.duration
+        {
             self.state = TimeoutState::Expired;
         }
         match self.state {
@@ -51,9 +56,9 @@ impl Timeout {
         }
     }
 
-    pub fn start(&mut self) {
+    pub fn start(&mut self, now: Instant) {
         self.state = TimeoutState::Active;
-        self.start = Instant::now();
+        self.start = now;
     }
 
     pub fn stop(&mut self) {
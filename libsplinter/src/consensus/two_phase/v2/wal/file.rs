@@ -0,0 +1,260 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::consensus::ProposalId;
+use crate::hex::{as_hex, deserialize_hex};
+
+use super::{TwoPhaseLogEntry, TwoPhaseLogError, TwoPhaseLogStore};
+
+/// A `TwoPhaseLogStore` backed by a file with one JSON entry per line.
+///
+/// Every append is synced to disk before it returns. A partially written entry at the end of the
+/// file, left by a crash in the middle of an append, is discarded when the file is opened.
+pub struct FileTwoPhaseLogStore {
+    path: PathBuf,
+    file: File,
+}
+
+impl FileTwoPhaseLogStore {
+    /// Opens the log at the given path, creating it if it does not exist.
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self, TwoPhaseLogError> {
+        let path = path.as_ref().to_path_buf();
+
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+
+        let contents = fs::read_to_string(&path)?;
+        if !contents.is_empty() && !contents.ends_with('\n') {
+            let complete_len = contents.rfind('\n').map(|idx| idx + 1).unwrap_or(0);
+            warn!(
+                "Discarding partially written entry at the end of two-phase log {}",
+                path.display()
+            );
+            file.set_len(complete_len as u64)?;
+            file.sync_all()?;
+        }
+
+        Ok(FileTwoPhaseLogStore { path, file })
+    }
+}
+
+impl TwoPhaseLogStore for FileTwoPhaseLogStore {
+    fn append(&mut self, entry: TwoPhaseLogEntry) -> Result<(), TwoPhaseLogError> {
+        let mut line = serialize_entry(entry)?;
+        line.push('\n');
+
+        self.file.write_all(line.as_bytes())?;
+        self.file.sync_data()?;
+
+        Ok(())
+    }
+
+    fn entries(&self) -> Result<Vec<TwoPhaseLogEntry>, TwoPhaseLogError> {
+        fs::read_to_string(&self.path)?
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(deserialize_entry)
+            .collect()
+    }
+
+    fn rewrite(&mut self, entries: Vec<TwoPhaseLogEntry>) -> Result<(), TwoPhaseLogError> {
+        let mut contents = String::new();
+        for entry in entries {
+            contents.push_str(&serialize_entry(entry)?);
+            contents.push('\n');
+        }
+
+        let temp_path = self.path.with_extension("temp");
+        let mut temp_file = File::create(&temp_path)?;
+        temp_file.write_all(contents.as_bytes())?;
+        temp_file.sync_all()?;
+
+        fs::rename(&temp_path, &self.path)?;
+        sync_parent_dir(&self.path)?;
+
+        self.file = OpenOptions::new().append(true).open(&self.path)?;
+
+        Ok(())
+    }
+}
+
+/// Syncs the directory containing the given path, so that a rename into it is durable.
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> Result<(), TwoPhaseLogError> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    File::open(parent)?.sync_all()?;
+    Ok(())
+}
+
+/// Directories cannot be opened to be synced on this platform; the rename is made durable by the
+/// file system.
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) -> Result<(), TwoPhaseLogError> {
+    Ok(())
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(tag = "entry", rename_all = "snake_case")]
+enum LogRecord {
+    Evaluating {
+        #[serde(serialize_with = "as_hex", deserialize_with = "deserialize_hex")]
+        proposal_id: Vec<u8>,
+    },
+    Voted {
+        #[serde(serialize_with = "as_hex", deserialize_with = "deserialize_hex")]
+        proposal_id: Vec<u8>,
+        verified: bool,
+    },
+    Decided {
+        #[serde(serialize_with = "as_hex", deserialize_with = "deserialize_hex")]
+        proposal_id: Vec<u8>,
+        apply: bool,
+    },
+    Completed {
+        #[serde(serialize_with = "as_hex", deserialize_with = "deserialize_hex")]
+        proposal_id: Vec<u8>,
+    },
+}
+
+fn serialize_entry(entry: TwoPhaseLogEntry) -> Result<String, TwoPhaseLogError> {
+    let record = match entry {
+        TwoPhaseLogEntry::Evaluating(proposal_id) => LogRecord::Evaluating {
+            proposal_id: proposal_id.into(),
+        },
+        TwoPhaseLogEntry::Voted {
+            proposal_id,
+            verified,
+        } => LogRecord::Voted {
+            proposal_id: proposal_id.into(),
+            verified,
+        },
+        TwoPhaseLogEntry::Decided { proposal_id, apply } => LogRecord::Decided {
+            proposal_id: proposal_id.into(),
+            apply,
+        },
+        TwoPhaseLogEntry::Completed(proposal_id) => LogRecord::Completed {
+            proposal_id: proposal_id.into(),
+        },
+    };
+
+    serde_json::to_string(&record).map_err(|err| TwoPhaseLogError::InvalidEntry(err.to_string()))
+}
+
+fn deserialize_entry(line: &str) -> Result<TwoPhaseLogEntry, TwoPhaseLogError> {
+    let record: LogRecord = serde_json::from_str(line)
+        .map_err(|err| TwoPhaseLogError::InvalidEntry(format!("{}: {}", err, line)))?;
+
+    Ok(match record {
+        LogRecord::Evaluating { proposal_id } => {
+            TwoPhaseLogEntry::Evaluating(ProposalId::from(proposal_id))
+        }
+        LogRecord::Voted {
+            proposal_id,
+            verified,
+        } => TwoPhaseLogEntry::Voted {
+            proposal_id: ProposalId::from(proposal_id),
+            verified,
+        },
+        LogRecord::Decided { proposal_id, apply } => TwoPhaseLogEntry::Decided {
+            proposal_id: ProposalId::from(proposal_id),
+            apply,
+        },
+        LogRecord::Completed { proposal_id } => {
+            TwoPhaseLogEntry::Completed(ProposalId::from(proposal_id))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempdir::TempDir;
+
+    /// Verify that entries appended to a file log are read back in order after the log is
+    /// reopened, and that rewriting the log replaces its entries.
+    #[test]
+    fn test_append_reopen_rewrite() {
+        let temp_dir = TempDir::new("test_append_reopen_rewrite").expect("Failed to create dir");
+        let path = temp_dir.path().join("two_phase.wal");
+
+        let entries = vec![
+            TwoPhaseLogEntry::Evaluating(vec![1, 2].into()),
+            TwoPhaseLogEntry::Voted {
+                proposal_id: vec![1, 2].into(),
+                verified: true,
+            },
+            TwoPhaseLogEntry::Decided {
+                proposal_id: vec![1, 2].into(),
+                apply: false,
+            },
+            TwoPhaseLogEntry::Completed(vec![1, 2].into()),
+        ];
+
+        let mut store = FileTwoPhaseLogStore::new(&path).expect("Failed to open log");
+        for entry in entries.iter().cloned() {
+            store.append(entry).expect("Failed to append entry");
+        }
+        drop(store);
+
+        let mut store = FileTwoPhaseLogStore::new(&path).expect("Failed to reopen log");
+        assert_eq!(entries, store.entries().expect("Failed to read entries"));
+
+        store
+            .rewrite(vec![entries[2].clone()])
+            .expect("Failed to rewrite log");
+        store
+            .append(entries[0].clone())
+            .expect("Failed to append entry");
+        assert_eq!(
+            vec![entries[2].clone(), entries[0].clone()],
+            store.entries().expect("Failed to read entries")
+        );
+    }
+
+    /// Verify that a partially written entry at the end of the file is discarded when the log is
+    /// opened, and that entries appended afterwards are intact.
+    #[test]
+    fn test_partial_entry_discarded() {
+        let temp_dir = TempDir::new("test_partial_entry_discarded").expect("Failed to create dir");
+        let path = temp_dir.path().join("two_phase.wal");
+
+        fs::write(
+            &path,
+            "{\"entry\":\"evaluating\",\"proposal_id\":\"01\"}\n{\"entry\":\"comp",
+        )
+        .expect("Failed to write log");
+
+        let mut store = FileTwoPhaseLogStore::new(&path).expect("Failed to open log");
+        store
+            .append(TwoPhaseLogEntry::Completed(vec![1].into()))
+            .expect("Failed to append entry");
+
+        assert_eq!(
+            vec![
+                TwoPhaseLogEntry::Evaluating(vec![1].into()),
+                TwoPhaseLogEntry::Completed(vec![1].into()),
+            ],
+            store.entries().expect("Failed to read entries")
+        );
+    }
+}
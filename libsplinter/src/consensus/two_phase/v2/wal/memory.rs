@@ -0,0 +1,59 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::{Arc, Mutex};
+
+use super::{TwoPhaseLogEntry, TwoPhaseLogError, TwoPhaseLogStore};
+
+/// An in-memory `TwoPhaseLogStore`.
+///
+/// Clones share the same log, so a clone kept outside of an engine can be used to restart another
+/// engine from the same log. This store does not survive a process restart and is intended for
+/// testing.
+#[derive(Clone, Default)]
+pub struct MemoryTwoPhaseLogStore {
+    entries: Arc<Mutex<Vec<TwoPhaseLogEntry>>>,
+}
+
+impl MemoryTwoPhaseLogStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl TwoPhaseLogStore for MemoryTwoPhaseLogStore {
+    fn append(&mut self, entry: TwoPhaseLogEntry) -> Result<(), TwoPhaseLogError> {
+        self.entries
+            .lock()
+            .map_err(|_| TwoPhaseLogError::PoisonedLock)?
+            .push(entry);
+        Ok(())
+    }
+
+    fn entries(&self) -> Result<Vec<TwoPhaseLogEntry>, TwoPhaseLogError> {
+        Ok(self
+            .entries
+            .lock()
+            .map_err(|_| TwoPhaseLogError::PoisonedLock)?
+            .clone())
+    }
+
+    fn rewrite(&mut self, entries: Vec<TwoPhaseLogEntry>) -> Result<(), TwoPhaseLogError> {
+        *self
+            .entries
+            .lock()
+            .map_err(|_| TwoPhaseLogError::PoisonedLock)? = entries;
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Write-ahead log for the two-phase commit engine.
+//!
+//! The engine records each state transition of a proposal before acting on it, so that a node
+//! that restarts can determine which proposal, if any, was in flight when it stopped and how far
+//! it had progressed.
+
+mod file;
+mod memory;
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use crate::consensus::error::ConsensusEngineError;
+use crate::consensus::ProposalId;
+
+pub use file::FileTwoPhaseLogStore;
+pub use memory::MemoryTwoPhaseLogStore;
+
+/// A state transition of the two-phase commit engine.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TwoPhaseLogEntry {
+    /// This node began evaluating the proposal.
+    Evaluating(ProposalId),
+    /// This node, as a participant, sent its verification response for the proposal to the
+    /// coordinator.
+    Voted {
+        proposal_id: ProposalId,
+        verified: bool,
+    },
+    /// This node, as the coordinator, decided whether the proposal will be applied.
+    Decided {
+        proposal_id: ProposalId,
+        apply: bool,
+    },
+    /// The result of the proposal has been applied locally.
+    Completed(ProposalId),
+}
+
+/// Durable storage for the two-phase commit engine's write-ahead log.
+pub trait TwoPhaseLogStore: Send {
+    /// Appends an entry to the end of the log. The entry must be durable when this method returns.
+    fn append(&mut self, entry: TwoPhaseLogEntry) -> Result<(), TwoPhaseLogError>;
+
+    /// Returns all entries in the log, oldest first.
+    fn entries(&self) -> Result<Vec<TwoPhaseLogEntry>, TwoPhaseLogError>;
+
+    /// Atomically replaces the contents of the log with the given entries. This is used to
+    /// discard the entries of completed proposals.
+    fn rewrite(&mut self, entries: Vec<TwoPhaseLogEntry>) -> Result<(), TwoPhaseLogError>;
+}
+
+/// Errors that may occur while reading or writing the two-phase commit write-ahead log.
+#[derive(Debug)]
+pub enum TwoPhaseLogError {
+    /// The log could not be read or written.
+    Io(io::Error),
+    /// An entry in the log could not be parsed.
+    InvalidEntry(String),
+    /// The store's internal lock was poisoned.
+    PoisonedLock,
+}
+
+impl Error for TwoPhaseLogError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TwoPhaseLogError::Io(err) => Some(err),
+            TwoPhaseLogError::InvalidEntry(_) => None,
+            TwoPhaseLogError::PoisonedLock => None,
+        }
+    }
+}
+
+impl fmt::Display for TwoPhaseLogError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TwoPhaseLogError::Io(err) => write!(f, "unable to access two-phase log: {}", err),
+            TwoPhaseLogError::InvalidEntry(msg) => {
+                write!(f, "invalid two-phase log entry: {}", msg)
+            }
+            TwoPhaseLogError::PoisonedLock => write!(f, "two-phase log lock was poisoned"),
+        }
+    }
+}
+
+impl From<io::Error> for TwoPhaseLogError {
+    fn from(err: io::Error) -> Self {
+        TwoPhaseLogError::Io(err)
+    }
+}
+
+impl From<TwoPhaseLogError> for ConsensusEngineError {
+    fn from(err: TwoPhaseLogError) -> Self {
+        ConsensusEngineError(Box::new(err))
+    }
+}
@@ -15,9 +15,7 @@
 use std::error::Error;
 use std::fmt::{self, Write};
 
-#[cfg(feature = "admin-service")]
 use serde::de;
-#[cfg(feature = "admin-service")]
 use serde::{Deserializer, Serializer};
 
 pub fn to_hex(bytes: &[u8]) -> String {
@@ -29,7 +27,6 @@ pub fn to_hex(bytes: &[u8]) -> String {
     buf
 }
 
-#[cfg(feature = "admin-service")]
 pub fn parse_hex(hex: &str) -> Result<Vec<u8>, HexError> {
     if hex.len() % 2 != 0 {
         return Err(HexError {
@@ -51,7 +48,6 @@ pub fn parse_hex(hex: &str) -> Result<Vec<u8>, HexError> {
     Ok(res)
 }
 
-#[cfg(feature = "admin-service")]
 pub fn as_hex<S>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
@@ -59,7 +55,6 @@ where
     serializer.serialize_str(&to_hex(data))
 }
 
-#[cfg(feature = "admin-service")]
 pub fn deserialize_hex<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
 where
     D: Deserializer<'de>,
@@ -117,7 +112,6 @@ impl fmt::Display for HexError {
     }
 }
 
-#[cfg(feature = "admin-service")]
 #[cfg(test)]
 mod tests {
     use super::*;
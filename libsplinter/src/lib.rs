@@ -61,6 +61,8 @@ macro_rules! mutex_lock_unwrap {
 
 #[cfg(feature = "admin-service")]
 pub mod admin;
+#[cfg(feature = "api-keys")]
+pub mod api_keys;
 mod base62;
 #[cfg(feature = "biome")]
 pub mod biome;
@@ -86,6 +88,8 @@ pub mod protos;
 pub mod registry;
 #[cfg(feature = "rest-api")]
 pub mod rest_api;
+#[cfg(feature = "saml")]
+pub mod saml;
 pub mod service;
 pub mod sets;
 #[cfg(feature = "store-factory")]
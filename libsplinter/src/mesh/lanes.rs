@@ -0,0 +1,187 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mio::{Poll, Token};
+use mio_extras::channel as mio_channel;
+
+use std::cell::RefCell;
+use std::io;
+use std::sync::mpsc::TryRecvError;
+
+use crate::network::priority::{MessagePriority, PriorityWeights, WeightedScheduler};
+
+use super::pool::TryEventError;
+use super::InternalEnvelope;
+
+/// The additional outgoing queues of a connection with priority lanes enabled.
+///
+/// The connection's regular outgoing queue carries bulk messages; control and consensus messages
+/// each have a queue of their own. The head of each queue is held in a pending slot, so that the
+/// scheduler can choose between the lanes that have a message waiting.
+pub(super) struct PriorityLanes {
+    control: mio_channel::Receiver<InternalEnvelope>,
+    control_token: Token,
+    consensus: mio_channel::Receiver<InternalEnvelope>,
+    consensus_token: Token,
+    pending: RefCell<[Option<Vec<u8>>; 3]>,
+    scheduler: RefCell<WeightedScheduler>,
+}
+
+impl PriorityLanes {
+    pub fn new(
+        control: mio_channel::Receiver<InternalEnvelope>,
+        control_token: Token,
+        consensus: mio_channel::Receiver<InternalEnvelope>,
+        consensus_token: Token,
+        weights: PriorityWeights,
+    ) -> Self {
+        PriorityLanes {
+            control,
+            control_token,
+            consensus,
+            consensus_token,
+            pending: RefCell::new([None, None, None]),
+            scheduler: RefCell::new(WeightedScheduler::new(weights)),
+        }
+    }
+
+    pub fn tokens(&self) -> [Token; 2] {
+        [self.control_token, self.consensus_token]
+    }
+
+    pub fn deregister(&self, poll: &Poll) -> Result<(), io::Error> {
+        poll.deregister(&self.control)?;
+        poll.deregister(&self.consensus)
+    }
+
+    /// Moves the next envelope of each lane into that lane's pending slot, if the slot is empty.
+    pub fn fill(
+        &self,
+        bulk: &mio_channel::Receiver<InternalEnvelope>,
+    ) -> Result<(), TryEventError> {
+        let mut pending = self.pending.borrow_mut();
+
+        for priority in MessagePriority::ALL.iter().copied() {
+            let slot = &mut pending[priority.index()];
+            if slot.is_some() {
+                continue;
+            }
+
+            let receiver = match priority {
+                MessagePriority::Control => &self.control,
+                MessagePriority::Consensus => &self.consensus,
+                MessagePriority::Bulk => bulk,
+            };
+
+            match receiver.try_recv() {
+                Ok(InternalEnvelope::Message { payload, .. }) => *slot = Some(payload),
+                // won't be sent outgoing
+                Ok(InternalEnvelope::Shutdown) => unreachable!(),
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => return Err(TryEventError::OutgoingDisconnected),
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn has_pending(&self) -> bool {
+        self.pending.borrow().iter().any(Option::is_some)
+    }
+
+    /// Takes the pending payload of the lane selected by the scheduler.
+    pub fn take_next(&self) -> Option<Vec<u8>> {
+        let mut pending = self.pending.borrow_mut();
+        let priority = self
+            .scheduler
+            .borrow_mut()
+            .select(|priority| pending[priority.index()].is_some())?;
+        pending[priority.index()].take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(payload: &str) -> InternalEnvelope {
+        InternalEnvelope::Message {
+            id: 0,
+            payload: payload.as_bytes().to_vec(),
+        }
+    }
+
+    /// Verify that queued control and consensus payloads are taken ahead of a bulk backlog that
+    /// was queued before them, and that the bulk payloads are still taken in order.
+    #[test]
+    fn test_lanes_take_by_priority() {
+        let (bulk_tx, bulk_rx) = mio_channel::sync_channel(8);
+        let (control_tx, control_rx) = mio_channel::sync_channel(8);
+        let (consensus_tx, consensus_rx) = mio_channel::sync_channel(8);
+
+        let lanes = PriorityLanes::new(
+            control_rx,
+            Token(1),
+            consensus_rx,
+            Token(2),
+            PriorityWeights::default(),
+        );
+
+        for i in 0..3 {
+            bulk_tx.try_send(message(&format!("bulk-{}", i))).unwrap();
+        }
+        consensus_tx.try_send(message("consensus-0")).unwrap();
+        control_tx.try_send(message("control-0")).unwrap();
+
+        let mut taken = vec![];
+        loop {
+            lanes.fill(&bulk_rx).expect("Unable to fill lanes");
+            match lanes.take_next() {
+                Some(payload) => taken.push(String::from_utf8(payload).unwrap()),
+                None => break,
+            }
+        }
+
+        assert_eq!(
+            taken,
+            vec!["control-0", "consensus-0", "bulk-0", "bulk-1", "bulk-2"]
+        );
+        assert!(!lanes.has_pending());
+    }
+
+    /// Verify that filling the lanes fails once the senders have been dropped.
+    #[test]
+    fn test_lanes_disconnected() {
+        let (bulk_tx, bulk_rx) = mio_channel::sync_channel::<InternalEnvelope>(1);
+        let (control_tx, control_rx) = mio_channel::sync_channel(1);
+        let (consensus_tx, consensus_rx) = mio_channel::sync_channel(1);
+
+        let lanes = PriorityLanes::new(
+            control_rx,
+            Token(1),
+            consensus_rx,
+            Token(2),
+            PriorityWeights::default(),
+        );
+
+        drop(bulk_tx);
+        drop(control_tx);
+        drop(consensus_tx);
+
+        match lanes.fill(&bulk_rx) {
+            Err(TryEventError::OutgoingDisconnected) => (),
+            res => panic!("Expected OutgoingDisconnected, got {:?}", res),
+        }
+    }
+}
@@ -51,6 +51,8 @@
 
 mod control;
 mod incoming;
+#[cfg(feature = "network-priority")]
+mod lanes;
 mod matrix;
 mod outgoing;
 mod pool;
@@ -70,6 +72,8 @@ use crate::mesh::incoming::Incoming;
 pub use crate::mesh::matrix::{MeshLifeCycle, MeshMatrixReceiver, MeshMatrixSender};
 use crate::mesh::outgoing::Outgoing;
 use crate::mesh::reactor::Reactor;
+#[cfg(feature = "network-priority")]
+use crate::network::priority::{PayloadClassifier, PriorityWeights};
 use crate::threading::lifecycle::ShutdownHandle;
 pub use crate::transport::matrix::ConnectionMatrixEnvelope as Envelope;
 use crate::transport::Connection;
@@ -101,17 +105,46 @@ pub struct Mesh {
     state: Arc<RwLock<MeshState>>,
     incoming: Incoming,
     ctrl: Control,
+    #[cfg(feature = "network-priority")]
+    classifier: Option<Arc<dyn PayloadClassifier>>,
 }
 
 impl Mesh {
     /// Create a new mesh, spawning a background thread for sending and receiving, and setting up
     /// channels to communicate with it.
     pub fn new(incoming_capacity: usize, outgoing_capacity: usize) -> Self {
-        let (ctrl, incoming) = Reactor::spawn(incoming_capacity, outgoing_capacity);
+        let (ctrl, incoming) = Reactor::spawn(
+            incoming_capacity,
+            outgoing_capacity,
+            #[cfg(feature = "network-priority")]
+            None,
+        );
         Mesh {
             state: Arc::new(RwLock::new(MeshState::new())),
             incoming,
             ctrl,
+            #[cfg(feature = "network-priority")]
+            classifier: None,
+        }
+    }
+
+    /// Create a new mesh that gives each connection an outgoing queue per priority class.
+    ///
+    /// Every payload sent is assigned a priority by the classifier. When more than one queue of a
+    /// connection has messages waiting, the queues are serviced according to the given weights.
+    #[cfg(feature = "network-priority")]
+    pub fn new_with_priority(
+        incoming_capacity: usize,
+        outgoing_capacity: usize,
+        classifier: Arc<dyn PayloadClassifier>,
+        weights: PriorityWeights,
+    ) -> Self {
+        let (ctrl, incoming) = Reactor::spawn(incoming_capacity, outgoing_capacity, Some(weights));
+        Mesh {
+            state: Arc::new(RwLock::new(MeshState::new())),
+            incoming,
+            ctrl,
+            classifier: Some(classifier),
         }
     }
 
@@ -157,7 +190,7 @@ impl Mesh {
         let id = envelope.id().to_string();
         if let Some(mesh_id) = state.unique_ids.get_by_key(&id) {
             match state.outgoings.get(mesh_id) {
-                Some(ref outgoing) => match outgoing.send(Vec::from(envelope)) {
+                Some(ref outgoing) => match self.send_outgoing(outgoing, Vec::from(envelope)) {
                     Ok(()) => Ok(()),
                     Err(err) => Err(SendError::from_outgoing_send_error(err, id)),
                 },
@@ -168,6 +201,30 @@ impl Mesh {
         }
     }
 
+    #[cfg(feature = "network-priority")]
+    fn send_outgoing(
+        &self,
+        outgoing: &Outgoing,
+        payload: Vec<u8>,
+    ) -> Result<(), outgoing::SendError> {
+        match &self.classifier {
+            Some(classifier) => {
+                let priority = classifier.classify(&payload);
+                outgoing.send_with_priority(payload, priority)
+            }
+            None => outgoing.send(payload),
+        }
+    }
+
+    #[cfg(not(feature = "network-priority"))]
+    fn send_outgoing(
+        &self,
+        outgoing: &Outgoing,
+        payload: Vec<u8>,
+    ) -> Result<(), outgoing::SendError> {
+        outgoing.send(payload)
+    }
+
     /// Receive a new envelope from the mesh.
     pub fn recv(&self) -> Result<Envelope, RecvError> {
         let internal_envelope = self.incoming.recv().map_err(|_| RecvError::Disconnected)?;
@@ -529,6 +586,144 @@ mod tests {
         test_single_connection_send_receive(tls, "127.0.0.1:0");
     }
 
+    // Test that a Mesh with priority lanes drains backlogged lanes in the configured weight ratio,
+    // serving the control lane first, and that messages on the same lane keep their order
+    #[cfg(feature = "network-priority")]
+    #[test]
+    fn test_priority_connection_send_receive() {
+        use crate::network::priority::MessagePriority;
+        use crate::transport::{DisconnectError, RecvError, SendError};
+        use mio::Evented;
+        use std::sync::mpsc::{Receiver, Sender};
+
+        struct PrefixClassifier;
+
+        impl PayloadClassifier for PrefixClassifier {
+            fn classify(&self, payload: &[u8]) -> MessagePriority {
+                if payload.starts_with(b"control") {
+                    MessagePriority::Control
+                } else if payload.starts_with(b"consensus") {
+                    MessagePriority::Consensus
+                } else {
+                    MessagePriority::Bulk
+                }
+            }
+        }
+
+        // Holds the first send until released, so that the reactor cannot drain the lanes while
+        // the backlog is being queued
+        struct GatedConnection {
+            inner: Box<dyn Connection>,
+            entered: Option<Sender<()>>,
+            release: Receiver<()>,
+        }
+
+        impl Connection for GatedConnection {
+            fn send(&mut self, message: &[u8]) -> Result<(), SendError> {
+                if let Some(entered) = self.entered.take() {
+                    entered.send(()).expect("Unable to signal send");
+                    self.release.recv().expect("Unable to wait for release");
+                }
+                self.inner.send(message)
+            }
+
+            fn recv(&mut self) -> Result<Vec<u8>, RecvError> {
+                self.inner.recv()
+            }
+
+            fn remote_endpoint(&self) -> String {
+                self.inner.remote_endpoint()
+            }
+
+            fn local_endpoint(&self) -> String {
+                self.inner.local_endpoint()
+            }
+
+            fn disconnect(&mut self) -> Result<(), DisconnectError> {
+                self.inner.disconnect()
+            }
+
+            fn evented(&self) -> &dyn Evented {
+                self.inner.evented()
+            }
+        }
+
+        let mut transport = TcpTransport::default();
+        let mut listener = assert_ok(transport.listen("127.0.0.1:0"));
+        let endpoint = listener.endpoint();
+
+        let handle = thread::spawn(move || {
+            let (entered_tx, entered_rx) = channel();
+            let (release_tx, release_rx) = channel();
+            let client = GatedConnection {
+                inner: assert_ok(transport.connect(&endpoint)),
+                entered: Some(entered_tx),
+                release: release_rx,
+            };
+
+            let mesh = Mesh::new_with_priority(
+                32,
+                32,
+                Arc::new(PrefixClassifier),
+                PriorityWeights::new(4, 2, 1),
+            );
+            assert_ok(mesh.add(Box::new(client), "client".to_string()));
+
+            // Wait until the reactor is sending the first message before queuing the backlog
+            assert_ok(mesh.send(Envelope::new("client".to_string(), b"bulk-0".to_vec())));
+            assert_ok(entered_rx.recv());
+
+            let mut payloads = vec![];
+            payloads.extend((1..3).map(|i| format!("bulk-{}", i)));
+            payloads.extend((0..4).map(|i| format!("consensus-{}", i)));
+            payloads.extend((0..8).map(|i| format!("control-{}", i)));
+            for payload in payloads {
+                assert_ok(mesh.send(Envelope::new("client".to_string(), payload.into_bytes())));
+            }
+
+            assert_ok(release_tx.send(()));
+
+            mesh
+        });
+
+        let mesh = Mesh::new(32, 32);
+        let server = assert_ok(listener.accept());
+        assert_ok(mesh.add(server, "server".to_string()));
+
+        let received = (0..15)
+            .map(|_| {
+                let envelope = assert_ok(mesh.recv());
+                String::from_utf8(envelope.payload().to_vec()).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        // With weights of 4, 2 and 1, each round of seven messages taken from the backlogged
+        // lanes starts with control and contains four control, two consensus and one bulk message
+        assert_eq!(
+            vec![
+                "bulk-0",
+                "control-0",
+                "consensus-0",
+                "control-1",
+                "bulk-1",
+                "control-2",
+                "consensus-1",
+                "control-3",
+                "control-4",
+                "consensus-2",
+                "control-5",
+                "bulk-2",
+                "control-6",
+                "consensus-3",
+                "control-7",
+            ],
+            received
+        );
+
+        // keep the client mesh alive until all messages have been received
+        handle.join().unwrap();
+    }
+
     #[test]
     fn test_add_remove_connections_raw() {
         let raw = TcpTransport::default();
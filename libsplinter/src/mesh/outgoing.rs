@@ -16,6 +16,9 @@ use mio_extras::channel::{SyncSender, TrySendError};
 
 use std::io;
 
+#[cfg(feature = "network-priority")]
+use crate::network::priority::MessagePriority;
+
 use super::InternalEnvelope;
 
 /// Handle for sending to a specific connection in the mesh
@@ -23,11 +26,35 @@ use super::InternalEnvelope;
 pub struct Outgoing {
     id: usize,
     tx: SyncSender<InternalEnvelope>,
+    #[cfg(feature = "network-priority")]
+    control_tx: Option<SyncSender<InternalEnvelope>>,
+    #[cfg(feature = "network-priority")]
+    consensus_tx: Option<SyncSender<InternalEnvelope>>,
 }
 
 impl Outgoing {
     pub(super) fn new(id: usize, tx: SyncSender<InternalEnvelope>) -> Self {
-        Outgoing { id, tx }
+        Outgoing {
+            id,
+            tx,
+            #[cfg(feature = "network-priority")]
+            control_tx: None,
+            #[cfg(feature = "network-priority")]
+            consensus_tx: None,
+        }
+    }
+
+    /// Adds the control and consensus lanes of the connection. Without them, all messages are
+    /// sent on the connection's single outgoing queue.
+    #[cfg(feature = "network-priority")]
+    pub(super) fn with_priority_lanes(
+        mut self,
+        control_tx: SyncSender<InternalEnvelope>,
+        consensus_tx: SyncSender<InternalEnvelope>,
+    ) -> Self {
+        self.control_tx = Some(control_tx);
+        self.consensus_tx = Some(consensus_tx);
+        self
     }
 
     pub fn send(&self, payload: Vec<u8>) -> Result<(), SendError> {
@@ -38,6 +65,28 @@ impl Outgoing {
         Ok(())
     }
 
+    /// Sends the payload on the lane for the given priority. Bulk messages, and all messages on a
+    /// connection without priority lanes, are sent on the regular outgoing queue.
+    #[cfg(feature = "network-priority")]
+    pub fn send_with_priority(
+        &self,
+        payload: Vec<u8>,
+        priority: MessagePriority,
+    ) -> Result<(), SendError> {
+        let tx = match priority {
+            MessagePriority::Control => self.control_tx.as_ref(),
+            MessagePriority::Consensus => self.consensus_tx.as_ref(),
+            MessagePriority::Bulk => None,
+        }
+        .unwrap_or(&self.tx);
+
+        tx.try_send(InternalEnvelope::Message {
+            id: self.id,
+            payload,
+        })?;
+        Ok(())
+    }
+
     pub fn id(&self) -> usize {
         self.id
     }
@@ -22,8 +22,12 @@ use std::fmt;
 use std::io;
 use std::sync::mpsc::TryRecvError;
 
+#[cfg(feature = "network-priority")]
+use crate::network::priority::PriorityWeights;
 use crate::transport::{Connection, RecvError, SendError};
 
+#[cfg(feature = "network-priority")]
+use super::lanes::PriorityLanes;
 use super::InternalEnvelope;
 
 /// A structure for holding onto many connections and receivers and assigning new connections
@@ -93,6 +97,47 @@ impl Pool {
         Ok(id)
     }
 
+    /// Add a new connection with priority lanes to the reactor. The `outgoing` queue carries bulk
+    /// messages, while `control` and `consensus` carry messages of the respective priorities.
+    #[cfg(feature = "network-priority")]
+    pub fn add_with_priority(
+        &mut self,
+        connection: Box<dyn Connection>,
+        outgoing: mio_channel::Receiver<InternalEnvelope>,
+        control: mio_channel::Receiver<InternalEnvelope>,
+        consensus: mio_channel::Receiver<InternalEnvelope>,
+        weights: PriorityWeights,
+    ) -> Result<usize, io::Error> {
+        let control_token = self.next_token();
+        let consensus_token = self.next_token();
+
+        self.poll
+            .register(&control, control_token, Ready::readable(), PollOpt::level())?;
+
+        self.poll.register(
+            &consensus,
+            consensus_token,
+            Ready::readable(),
+            PollOpt::level(),
+        )?;
+
+        let id = self.add(connection, outgoing)?;
+
+        self.tokens.insert(control_token, id);
+        self.tokens.insert(consensus_token, id);
+        if let Some(entry) = self.entries.get_mut(&id) {
+            entry.lanes = Some(PriorityLanes::new(
+                control,
+                control_token,
+                consensus,
+                consensus_token,
+                weights,
+            ));
+        }
+
+        Ok(id)
+    }
+
     /// Remove a connection from the reactor, returning it if it exists
     pub fn remove(&mut self, id: usize) -> Result<Option<Box<dyn Connection>>, io::Error> {
         if let Some(entry) = self.entries.remove(&id) {
@@ -102,6 +147,16 @@ impl Pool {
             self.tokens.remove(&connection_token);
             self.tokens.remove(&outgoing_token);
 
+            #[cfg(feature = "network-priority")]
+            {
+                if let Some(lanes) = &entry.lanes {
+                    for token in lanes.tokens().iter() {
+                        self.tokens.remove(token);
+                    }
+                    lanes.deregister(&self.poll)?;
+                }
+            }
+
             let (connection, outgoing) = entry.into_evented();
 
             self.poll.deregister(connection.evented())?;
@@ -190,6 +245,8 @@ struct Entry {
     outgoing_token: Token,
     cached: RefCell<Option<Vec<u8>>>,
     write_evented_guard: RefCell<bool>,
+    #[cfg(feature = "network-priority")]
+    lanes: Option<PriorityLanes>,
 }
 
 impl fmt::Debug for Entry {
@@ -218,6 +275,8 @@ impl Entry {
             outgoing_token,
             cached: RefCell::new(None),
             write_evented_guard: RefCell::new(false),
+            #[cfg(feature = "network-priority")]
+            lanes: None,
         }
     }
 
@@ -243,6 +302,13 @@ impl Entry {
         incoming_tx: &crossbeam_channel::Sender<InternalEnvelope>,
         poll: &Poll,
     ) -> Result<(), TryEventError> {
+        #[cfg(feature = "network-priority")]
+        {
+            if let Some(lanes) = &self.lanes {
+                return self.try_event_with_lanes(lanes, event, incoming_tx, poll);
+            }
+        }
+
         if self.outgoing_wants_read(event) {
             self.try_read_outgoing(poll)
         } else if self.connection_wants_write(event) {
@@ -278,6 +344,89 @@ impl Entry {
         }
     }
 
+    // -- Priority lanes --
+
+    #[cfg(feature = "network-priority")]
+    fn try_event_with_lanes(
+        &self,
+        lanes: &PriorityLanes,
+        event: &Event,
+        incoming_tx: &crossbeam_channel::Sender<InternalEnvelope>,
+        poll: &Poll,
+    ) -> Result<(), TryEventError> {
+        let token = event.token();
+        let is_outgoing = token == self.outgoing_token || lanes.tokens().contains(&token);
+
+        if is_outgoing && event.readiness().is_readable() {
+            lanes.fill(&self.outgoing)?;
+            if self.cached.borrow().is_none() {
+                self.try_send_next_pending(lanes, poll)
+            } else {
+                Ok(())
+            }
+        } else if self.connection_token == token
+            && event.readiness().is_writable()
+            && (self.cached.borrow().is_some() || lanes.has_pending())
+        {
+            if let Some(cached) = self.cached.replace(None) {
+                self.try_send_connection_or_cache(cached, poll)?;
+                self.request_write_if_pending(lanes, poll)
+            } else {
+                self.try_send_next_pending(lanes, poll)
+            }
+        } else if self.connection_wants_read(event) {
+            self.try_read_connection(incoming_tx)
+        } else {
+            Ok(())
+        }
+    }
+
+    // Sends the payload of the lane chosen by the scheduler, if any.
+    #[cfg(feature = "network-priority")]
+    fn try_send_next_pending(
+        &self,
+        lanes: &PriorityLanes,
+        poll: &Poll,
+    ) -> Result<(), TryEventError> {
+        match lanes.take_next() {
+            Some(payload) => {
+                self.try_send_connection_or_cache(payload, poll)?;
+                self.request_write_if_pending(lanes, poll)
+            }
+            None => Ok(()),
+        }
+    }
+
+    // Payloads held in the pending slots no longer raise readable events on their lanes, so keep
+    // the connection registered for writes until they have been sent.
+    #[cfg(feature = "network-priority")]
+    fn request_write_if_pending(
+        &self,
+        lanes: &PriorityLanes,
+        poll: &Poll,
+    ) -> Result<(), TryEventError> {
+        if self.cached.borrow().is_some() {
+            // Already registered for writes
+            return Ok(());
+        }
+
+        lanes.fill(&self.outgoing)?;
+        if lanes.has_pending() && !*self.write_evented_guard.borrow() {
+            let connection = self.connection.borrow();
+            poll.reregister(
+                connection.evented(),
+                self.connection_token,
+                Ready::readable() | Ready::writable(),
+                PollOpt::level(),
+            )
+            .map_err(TryEventError::IoError)?;
+
+            self.write_evented_guard.replace(true);
+        }
+
+        Ok(())
+    }
+
     // -- Connection --
 
     fn connection_wants_write(&self, event: &Event) -> bool {
@@ -28,6 +28,8 @@ use crate::mesh::{
     pool::Pool,
     InternalEnvelope,
 };
+#[cfg(feature = "network-priority")]
+use crate::network::priority::PriorityWeights;
 use crate::transport::Connection;
 
 // Maximum number of events to receive and handle per turn of the reactor
@@ -39,6 +41,8 @@ pub struct Reactor {
     ctrl_token: Token,
     incoming_tx: crossbeam_channel::Sender<InternalEnvelope>,
     outgoing_capacity: usize,
+    #[cfg(feature = "network-priority")]
+    priority_weights: Option<PriorityWeights>,
 }
 
 enum Turn {
@@ -51,6 +55,7 @@ impl Reactor {
         ctrl_rx: mio_channel::Receiver<ControlRequest>,
         incoming_tx: crossbeam_channel::Sender<InternalEnvelope>,
         outgoing_capacity: usize,
+        #[cfg(feature = "network-priority")] priority_weights: Option<PriorityWeights>,
     ) -> Self {
         let mut pool = Pool::new();
 
@@ -64,17 +69,31 @@ impl Reactor {
             ctrl_token,
             incoming_tx,
             outgoing_capacity,
+            #[cfg(feature = "network-priority")]
+            priority_weights,
         }
     }
 
-    pub(super) fn spawn(incoming_capacity: usize, outgoing_capacity: usize) -> (Control, Incoming) {
+    /// Spawns the reactor thread. If `priority_weights` is set, each connection is given an
+    /// outgoing queue per priority class, serviced using the given weights.
+    pub(super) fn spawn(
+        incoming_capacity: usize,
+        outgoing_capacity: usize,
+        #[cfg(feature = "network-priority")] priority_weights: Option<PriorityWeights>,
+    ) -> (Control, Incoming) {
         let (ctrl_tx, ctrl_rx) = mio_channel::channel();
         let (incoming_tx, incoming_rx) = crossbeam_channel::bounded(incoming_capacity);
 
         thread::Builder::new()
             .name(String::from("mesh::Reactor"))
             .spawn(move || {
-                let mut reactor = Reactor::new(ctrl_rx, incoming_tx, outgoing_capacity);
+                let mut reactor = Reactor::new(
+                    ctrl_rx,
+                    incoming_tx,
+                    outgoing_capacity,
+                    #[cfg(feature = "network-priority")]
+                    priority_weights,
+                );
                 reactor.run();
             })
             .expect("Failed to spawn mesh::Reactor thread");
@@ -150,6 +169,28 @@ impl Reactor {
     fn add_connection(&mut self, connection: Box<dyn Connection>) -> AddResponse {
         let (tx, rx) = mio_channel::sync_channel(self.outgoing_capacity);
 
+        #[cfg(feature = "network-priority")]
+        {
+            if let Some(weights) = self.priority_weights.clone() {
+                let (control_tx, control_rx) = mio_channel::sync_channel(self.outgoing_capacity);
+                let (consensus_tx, consensus_rx) =
+                    mio_channel::sync_channel(self.outgoing_capacity);
+
+                return match self.pool.add_with_priority(
+                    connection,
+                    rx,
+                    control_rx,
+                    consensus_rx,
+                    weights,
+                ) {
+                    Ok(id) => {
+                        Ok(Outgoing::new(id, tx).with_priority_lanes(control_tx, consensus_tx))
+                    }
+                    Err(err) => Err(AddError::Io(err)),
+                };
+            }
+        }
+
         match self.pool.add(connection, rx) {
             Ok(id) => Ok(Outgoing::new(id, tx)),
             Err(err) => Err(AddError::Io(err)),
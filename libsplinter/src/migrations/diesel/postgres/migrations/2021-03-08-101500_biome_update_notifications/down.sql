@@ -0,0 +1,29 @@
+-- Copyright 2018-2021 Cargill Incorporated
+--
+-- Licensed under the Apache License, Version 2.0 (the "License");
+-- you may not use this file except in compliance with the License.
+-- You may obtain a copy of the License at
+--
+--     http://www.apache.org/licenses/LICENSE-2.0
+--
+-- Unless required by applicable law or agreed to in writing, software
+-- distributed under the License is distributed on an "AS IS" BASIS,
+-- WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+-- See the License for the specific language governing permissions and
+-- limitations under the License.
+-- -----------------------------------------------------------------------------
+
+ALTER TABLE notifications ADD COLUMN recipients TEXT[] NOT NULL DEFAULT '{}';
+UPDATE notifications SET recipients = ARRAY(
+  SELECT user_id FROM user_notifications WHERE notification_id = notifications.id
+);
+ALTER TABLE notifications ALTER COLUMN recipients DROP DEFAULT;
+
+-- Only one user notification is kept for each notification.
+DELETE FROM user_notifications a USING user_notifications b
+  WHERE a.notification_id = b.notification_id AND a.user_id > b.user_id;
+ALTER TABLE user_notifications DROP CONSTRAINT user_notifications_pkey;
+ALTER TABLE user_notifications ADD PRIMARY KEY (notification_id);
+
+ALTER TABLE notifications
+  ALTER COLUMN created TYPE TIMESTAMP USING to_timestamp(created) AT TIME ZONE 'UTC';
@@ -0,0 +1,29 @@
+-- Copyright 2018-2021 Cargill Incorporated
+--
+-- Licensed under the Apache License, Version 2.0 (the "License");
+-- you may not use this file except in compliance with the License.
+-- You may obtain a copy of the License at
+--
+--     http://www.apache.org/licenses/LICENSE-2.0
+--
+-- Unless required by applicable law or agreed to in writing, software
+-- distributed under the License is distributed on an "AS IS" BASIS,
+-- WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+-- See the License for the specific language governing permissions and
+-- limitations under the License.
+-- -----------------------------------------------------------------------------
+
+-- Creation times are stored as seconds since the epoch.
+ALTER TABLE notifications
+  ALTER COLUMN created TYPE BIGINT USING CAST(EXTRACT(EPOCH FROM created) AS BIGINT);
+
+-- A notification may be sent to more than one user.
+ALTER TABLE user_notifications DROP CONSTRAINT user_notifications_pkey;
+ALTER TABLE user_notifications ADD PRIMARY KEY (notification_id, user_id);
+
+-- The recipients of a notification are the users it has a user notification for.
+INSERT INTO user_notifications (notification_id, user_id, unread)
+  SELECT id, UNNEST(recipients), TRUE FROM notifications
+  ON CONFLICT DO NOTHING;
+
+ALTER TABLE notifications DROP COLUMN recipients;
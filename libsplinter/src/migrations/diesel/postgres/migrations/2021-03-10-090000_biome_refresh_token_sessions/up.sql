@@ -0,0 +1,32 @@
+-- Copyright 2018-2021 Cargill Incorporated
+--
+-- Licensed under the Apache License, Version 2.0 (the "License");
+-- you may not use this file except in compliance with the License.
+-- You may obtain a copy of the License at
+--
+--     http://www.apache.org/licenses/LICENSE-2.0
+--
+-- Unless required by applicable law or agreed to in writing, software
+-- distributed under the License is distributed on an "AS IS" BASIS,
+-- WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+-- See the License for the specific language governing permissions and
+-- limitations under the License.
+-- -----------------------------------------------------------------------------
+
+-- Allow several refresh tokens per user, one for each named session
+ALTER TABLE refresh_tokens ADD COLUMN session_id TEXT NOT NULL DEFAULT '';
+ALTER TABLE refresh_tokens ADD COLUMN display_name TEXT;
+ALTER TABLE refresh_tokens ADD COLUMN user_agent TEXT;
+ALTER TABLE refresh_tokens ADD COLUMN created BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE refresh_tokens ADD COLUMN last_used BIGINT NOT NULL DEFAULT 0;
+
+-- Existing tokens become sessions of their own
+UPDATE refresh_tokens SET
+    session_id = CAST(id AS TEXT),
+    created = CAST(EXTRACT(EPOCH FROM NOW()) AS BIGINT),
+    last_used = CAST(EXTRACT(EPOCH FROM NOW()) AS BIGINT);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_refresh_tokens_user_session ON refresh_tokens (
+    user_id,
+    session_id
+);
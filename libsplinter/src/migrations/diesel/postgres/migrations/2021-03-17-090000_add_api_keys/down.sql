@@ -0,0 +1,33 @@
+-- Copyright 2018-2021 Cargill Incorporated
+--
+-- Licensed under the Apache License, Version 2.0 (the "License");
+-- you may not use this file except in compliance with the License.
+-- You may obtain a copy of the License at
+--
+--     http://www.apache.org/licenses/LICENSE-2.0
+--
+-- Unless required by applicable law or agreed to in writing, software
+-- distributed under the License is distributed on an "AS IS" BASIS,
+-- WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+-- See the License for the specific language governing permissions and
+-- limitations under the License.
+-- -----------------------------------------------------------------------------
+
+DROP TABLE IF EXISTS api_key_scopes;
+DROP TABLE IF EXISTS api_keys;
+
+DELETE FROM assignments WHERE identity IN (
+    SELECT identity FROM identities WHERE identity_type = 'api_key'
+);
+DELETE FROM identities WHERE identity_type = 'api_key';
+
+ALTER TYPE identity_type RENAME TO _identity_type_old;
+
+CREATE TYPE identity_type AS ENUM ('key', 'user');
+
+ALTER TABLE identities
+ALTER COLUMN identity_type
+SET DATA TYPE identity_type
+USING identity_type::text::identity_type;
+
+DROP TYPE _identity_type_old;
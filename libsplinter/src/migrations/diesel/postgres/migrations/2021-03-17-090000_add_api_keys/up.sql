@@ -0,0 +1,42 @@
+-- Copyright 2018-2021 Cargill Incorporated
+--
+-- Licensed under the Apache License, Version 2.0 (the "License");
+-- you may not use this file except in compliance with the License.
+-- You may obtain a copy of the License at
+--
+--     http://www.apache.org/licenses/LICENSE-2.0
+--
+-- Unless required by applicable law or agreed to in writing, software
+-- distributed under the License is distributed on an "AS IS" BASIS,
+-- WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+-- See the License for the specific language governing permissions and
+-- limitations under the License.
+-- -----------------------------------------------------------------------------
+
+-- Values cannot be added to an enum inside of a transaction, so the type is
+-- replaced instead
+ALTER TYPE identity_type RENAME TO _identity_type_old;
+
+CREATE TYPE identity_type AS ENUM ('key', 'user', 'api_key');
+
+ALTER TABLE identities
+ALTER COLUMN identity_type
+SET DATA TYPE identity_type
+USING identity_type::text::identity_type;
+
+DROP TYPE _identity_type_old;
+
+CREATE TABLE IF NOT EXISTS api_keys (
+    id           TEXT    PRIMARY KEY,
+    display_name TEXT    NOT NULL,
+    secret_hash  TEXT    NOT NULL,
+    created_at   BIGINT  NOT NULL,
+    expires_at   BIGINT  NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS api_key_scopes (
+    api_key_id   TEXT    NOT NULL,
+    scope        TEXT    NOT NULL,
+    PRIMARY KEY(api_key_id, scope),
+    FOREIGN KEY (api_key_id) REFERENCES api_keys(id) ON DELETE CASCADE
+);
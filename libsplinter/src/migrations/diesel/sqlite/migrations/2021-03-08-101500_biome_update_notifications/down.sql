@@ -0,0 +1,67 @@
+-- Copyright 2018-2021 Cargill Incorporated
+--
+-- Licensed under the Apache License, Version 2.0 (the "License");
+-- you may not use this file except in compliance with the License.
+-- You may obtain a copy of the License at
+--
+--     http://www.apache.org/licenses/LICENSE-2.0
+--
+-- Unless required by applicable law or agreed to in writing, software
+-- distributed under the License is distributed on an "AS IS" BASIS,
+-- WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+-- See the License for the specific language governing permissions and
+-- limitations under the License.
+-- -----------------------------------------------------------------------------
+
+PRAGMA foreign_keys=off;
+
+-- Rename the existing tables to the old tables.
+ALTER TABLE user_notifications RENAME TO old_user_notifications;
+ALTER TABLE notification_properties RENAME TO old_notification_properties;
+ALTER TABLE notifications RENAME TO old_notifications;
+
+CREATE TABLE notifications (
+  id                        TEXT        PRIMARY KEY,
+  payload_title             TEXT        NOT NULL,
+  payload_body              TEXT        NOT NULL,
+  created                   TIMESTAMP   NOT NULL,
+  recipients                TEXT[]      NOT NULL
+);
+
+CREATE TABLE notification_properties (
+  id                        INTEGER     PRIMARY KEY AUTOINCREMENT,
+  notification_id           TEXT        NOT NULL,
+  property                  TEXT        NOT NULL,
+  property_value            TEXT        NOT NULL,
+  FOREIGN KEY (notification_id) REFERENCES notifications(id) ON DELETE CASCADE
+);
+
+CREATE TABLE user_notifications (
+  notification_id           TEXT        PRIMARY KEY,
+  user_id                   TEXT        NOT NULL,
+  unread                    BOOL        NOT NULL,
+  FOREIGN KEY (notification_id) REFERENCES notifications(id) ON DELETE CASCADE
+);
+
+-- Move the records to the new tables, keeping one user notification for each notification.
+INSERT INTO notifications (id, payload_title, payload_body, created, recipients)
+    SELECT id, payload_title, payload_body, datetime(created, 'unixepoch'), '{}'
+    FROM old_notifications;
+
+INSERT INTO notification_properties (id, notification_id, property, property_value)
+    SELECT id, notification_id, property, property_value
+    FROM old_notification_properties;
+
+INSERT INTO user_notifications (notification_id, user_id, unread)
+    SELECT notification_id, user_id, unread
+    FROM old_user_notifications
+    WHERE rowid IN (
+        SELECT MIN(rowid) FROM old_user_notifications GROUP BY notification_id
+    );
+
+-- Drop the old tables
+DROP TABLE old_user_notifications;
+DROP TABLE old_notification_properties;
+DROP TABLE old_notifications;
+
+PRAGMA foreign_keys=on;
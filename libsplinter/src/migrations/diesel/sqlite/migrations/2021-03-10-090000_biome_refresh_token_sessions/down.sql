@@ -0,0 +1,38 @@
+-- Copyright 2018-2021 Cargill Incorporated
+--
+-- Licensed under the Apache License, Version 2.0 (the "License");
+-- you may not use this file except in compliance with the License.
+-- You may obtain a copy of the License at
+--
+--     http://www.apache.org/licenses/LICENSE-2.0
+--
+-- Unless required by applicable law or agreed to in writing, software
+-- distributed under the License is distributed on an "AS IS" BASIS,
+-- WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+-- See the License for the specific language governing permissions and
+-- limitations under the License.
+-- -----------------------------------------------------------------------------
+
+DROP INDEX IF EXISTS idx_refresh_tokens_user_session;
+
+ALTER TABLE refresh_tokens RENAME TO old_refresh_tokens;
+
+CREATE TABLE IF NOT EXISTS refresh_tokens (
+    id                    INTEGER       PRIMARY KEY AUTOINCREMENT,
+    user_id               TEXT          NOT NULL,
+    token                 TEXT          NOT NULL
+);
+
+INSERT INTO refresh_tokens
+    (
+        id,
+        user_id,
+        token
+    )
+    SELECT
+        id,
+        user_id,
+        token
+    FROM old_refresh_tokens;
+
+DROP TABLE old_refresh_tokens;
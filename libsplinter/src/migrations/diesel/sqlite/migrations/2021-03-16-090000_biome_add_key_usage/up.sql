@@ -0,0 +1,24 @@
+-- Copyright 2018-2021 Cargill Incorporated
+--
+-- Licensed under the Apache License, Version 2.0 (the "License");
+-- you may not use this file except in compliance with the License.
+-- You may obtain a copy of the License at
+--
+--     http://www.apache.org/licenses/LICENSE-2.0
+--
+-- Unless required by applicable law or agreed to in writing, software
+-- distributed under the License is distributed on an "AS IS" BASIS,
+-- WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+-- See the License for the specific language governing permissions and
+-- limitations under the License.
+-- -----------------------------------------------------------------------------
+
+
+CREATE TABLE IF NOT EXISTS key_usage (
+  id                        INTEGER     PRIMARY KEY AUTOINCREMENT,
+  public_key                TEXT        NOT NULL,
+  user_id                   TEXT        NOT NULL,
+  used_at                   BIGINT      NOT NULL,
+  action                    TEXT        NOT NULL,
+  payload_sha512            TEXT        NOT NULL
+);
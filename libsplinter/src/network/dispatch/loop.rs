@@ -17,11 +17,17 @@ use std::error::Error;
 use std::fmt;
 use std::fmt::Debug;
 use std::hash::Hash;
+#[cfg(feature = "network-priority")]
+use std::sync::mpsc::TryRecvError;
 use std::sync::mpsc::{channel, Receiver, RecvError, Sender};
 
 use super::{Dispatcher, PeerId};
 
 use crate::error::InternalError;
+#[cfg(feature = "network-priority")]
+use crate::network::priority::{
+    MessagePriority, PriorityClassifier, PriorityQueue, PriorityWeights,
+};
 use crate::threading::lifecycle::ShutdownHandle;
 
 /// A message to be dispatched.
@@ -65,6 +71,10 @@ where
         DispatchMessageReceiver<MT, Source>,
     )>,
     thread_name: Option<String>,
+    #[cfg(feature = "network-priority")]
+    priority_classifier: Option<Box<dyn PriorityClassifier<MT>>>,
+    #[cfg(feature = "network-priority")]
+    priority_weights: Option<PriorityWeights>,
 }
 
 impl<MT, Source> DispatchLoopBuilder<MT, Source>
@@ -77,6 +87,10 @@ where
             dispatcher: None,
             channel: None,
             thread_name: None,
+            #[cfg(feature = "network-priority")]
+            priority_classifier: None,
+            #[cfg(feature = "network-priority")]
+            priority_weights: None,
         }
     }
 
@@ -101,6 +115,23 @@ where
         self
     }
 
+    /// Enables priority lanes, using the given classifier to assign each message to a lane.
+    ///
+    /// Without a classifier, messages are dispatched in the order they are received.
+    #[cfg(feature = "network-priority")]
+    pub fn with_priority_classifier(mut self, classifier: Box<dyn PriorityClassifier<MT>>) -> Self {
+        self.priority_classifier = Some(classifier);
+        self
+    }
+
+    /// Sets the weights used to schedule between priority lanes. Defaults to
+    /// `PriorityWeights::default()`.
+    #[cfg(feature = "network-priority")]
+    pub fn with_priority_weights(mut self, weights: PriorityWeights) -> Self {
+        self.priority_weights = Some(weights);
+        self
+    }
+
     pub fn build(mut self) -> Result<DispatchLoop<MT, Source>, String> {
         let (tx, rx) = self.channel.take().unwrap_or_else(dispatch_channel);
 
@@ -113,44 +144,34 @@ where
             .thread_name
             .unwrap_or_else(|| format!("DispatchLoop({})", std::any::type_name::<MT>()));
 
+        #[cfg(feature = "network-priority")]
+        let priority = self
+            .priority_classifier
+            .take()
+            .map(|classifier| (classifier, self.priority_weights.take().unwrap_or_default()));
+
         let join_handle = std::thread::Builder::new()
             .name(thread_name)
-            .spawn(move || loop {
-                match rx.receiver.recv() {
-                    Ok(DispatchMessage::Message {
-                        message_type,
-                        message_bytes,
-                        source_id,
-                        parent_context: Some(context),
-                    }) => {
-                        if let Err(err) = dispatcher.dispatch_with_parent_context(
-                            source_id,
-                            &message_type,
-                            message_bytes,
-                            context,
-                        ) {
-                            warn!("Unable to dispatch message: {:?}", err);
-                        }
+            .spawn(move || {
+                #[cfg(feature = "network-priority")]
+                {
+                    if let Some((classifier, weights)) = priority {
+                        run_prioritized(&dispatcher, &rx.receiver, classifier, weights);
+                        return;
                     }
-                    Ok(DispatchMessage::Message {
-                        message_type,
-                        message_bytes,
-                        source_id,
-                        parent_context: None,
-                    }) => {
-                        if let Err(err) =
-                            dispatcher.dispatch(source_id, &message_type, message_bytes)
-                        {
-                            warn!("Unable to dispatch message: {:?}", err);
+                }
+
+                loop {
+                    match rx.receiver.recv() {
+                        Ok(DispatchMessage::Shutdown) => {
+                            debug!("Received shutdown signal");
+                            break;
+                        }
+                        Ok(message) => dispatch_message(&dispatcher, message),
+                        Err(RecvError) => {
+                            error!("Received error from receiver");
+                            break;
                         }
-                    }
-                    Ok(DispatchMessage::Shutdown) => {
-                        debug!("Received shutdown signal");
-                        break;
-                    }
-                    Err(RecvError) => {
-                        error!("Received error from receiver");
-                        break;
                     }
                 }
             });
@@ -165,6 +186,122 @@ where
     }
 }
 
+/// Passes a received message to the dispatcher.
+fn dispatch_message<MT, Source>(
+    dispatcher: &Dispatcher<MT, Source>,
+    message: DispatchMessage<MT, Source>,
+) where
+    MT: Any + Hash + Eq + Debug + Clone,
+    Source: 'static,
+{
+    match message {
+        DispatchMessage::Message {
+            message_type,
+            message_bytes,
+            source_id,
+            parent_context: Some(context),
+        } => {
+            if let Err(err) = dispatcher.dispatch_with_parent_context(
+                source_id,
+                &message_type,
+                message_bytes,
+                context,
+            ) {
+                warn!("Unable to dispatch message: {:?}", err);
+            }
+        }
+        DispatchMessage::Message {
+            message_type,
+            message_bytes,
+            source_id,
+            parent_context: None,
+        } => {
+            if let Err(err) = dispatcher.dispatch(source_id, &message_type, message_bytes) {
+                warn!("Unable to dispatch message: {:?}", err);
+            }
+        }
+        // handled by the caller
+        DispatchMessage::Shutdown => (),
+    }
+}
+
+/// Runs the dispatch loop with a queue per priority class.
+///
+/// Every message that has already arrived is moved into its class's queue before the next
+/// message is dispatched, so that a backlog of low priority messages does not delay higher
+/// priority messages that arrive after it. Messages received before a shutdown signal are still
+/// dispatched.
+#[cfg(feature = "network-priority")]
+fn run_prioritized<MT, Source>(
+    dispatcher: &Dispatcher<MT, Source>,
+    receiver: &Receiver<DispatchMessage<MT, Source>>,
+    classifier: Box<dyn PriorityClassifier<MT>>,
+    weights: PriorityWeights,
+) where
+    MT: Any + Hash + Eq + Debug + Clone,
+    Source: 'static,
+{
+    let mut queue = PriorityQueue::new(weights);
+    let mut receiving = true;
+
+    loop {
+        if queue.is_empty() {
+            if !receiving {
+                break;
+            }
+
+            match receiver.recv() {
+                Ok(DispatchMessage::Shutdown) => {
+                    debug!("Received shutdown signal");
+                    break;
+                }
+                Ok(message) => queue.push(priority_of(&*classifier, &message), message),
+                Err(RecvError) => {
+                    error!("Received error from receiver");
+                    break;
+                }
+            }
+        }
+
+        while receiving {
+            match receiver.try_recv() {
+                Ok(DispatchMessage::Shutdown) => {
+                    debug!("Received shutdown signal");
+                    receiving = false;
+                }
+                Ok(message) => queue.push(priority_of(&*classifier, &message), message),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    error!("Received error from receiver");
+                    receiving = false;
+                }
+            }
+        }
+
+        if let Some(message) = queue.pop() {
+            dispatch_message(dispatcher, message);
+        }
+    }
+}
+
+#[cfg(feature = "network-priority")]
+fn priority_of<MT, Source>(
+    classifier: &dyn PriorityClassifier<MT>,
+    message: &DispatchMessage<MT, Source>,
+) -> MessagePriority
+where
+    MT: Any + Hash + Eq + Debug + Clone,
+{
+    match message {
+        DispatchMessage::Message {
+            message_type,
+            message_bytes,
+            ..
+        } => classifier.classify(message_type, message_bytes),
+        DispatchMessage::Shutdown => MessagePriority::Control,
+    }
+}
+
 /// The Dispatch Loop
 ///
 /// The dispatch loop processes messages that are pulled from a `Receiver<DispatchMessage>` and
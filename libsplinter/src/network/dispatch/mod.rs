@@ -475,6 +475,77 @@ mod tests {
         );
     }
 
+    /// Verify that a dispatch loop with a priority classifier dispatches waiting messages by
+    /// priority rather than by arrival.
+    ///
+    /// This test does the following:
+    ///
+    /// * Queue several bulk messages followed by a control message on a dispatch channel
+    /// * Start a dispatch loop on the channel with a classifier that reads the echo payload
+    /// * Shut down the loop and verify that the control message was handled first and that all
+    ///   of the bulk messages were handled in order
+    #[cfg(feature = "network-priority")]
+    #[test]
+    fn dispatch_loop_with_priority() {
+        use crate::network::priority::{MessagePriority, PriorityClassifier};
+        use crate::threading::lifecycle::ShutdownHandle;
+
+        struct EchoClassifier;
+
+        impl PriorityClassifier<NetworkMessageType> for EchoClassifier {
+            fn classify(&self, _: &NetworkMessageType, message_bytes: &[u8]) -> MessagePriority {
+                match NetworkEcho::parse_from_bytes(message_bytes) {
+                    Ok(echo) if echo.get_payload().starts_with(b"control") => {
+                        MessagePriority::Control
+                    }
+                    _ => MessagePriority::Bulk,
+                }
+            }
+        }
+
+        let network_sender = MockSender::default();
+        let mut dispatcher = Dispatcher::new(Box::new(network_sender));
+
+        let handler = NetworkEchoHandler::default();
+        let echos = handler.echos.clone();
+        dispatcher.set_handler(Box::new(handler));
+
+        let (sender, receiver) = dispatch_channel();
+        for payload in &["bulk-0", "bulk-1", "bulk-2", "control-0"] {
+            let mut echo = NetworkEcho::new();
+            echo.set_payload(payload.as_bytes().to_vec());
+            sender
+                .send(
+                    NetworkMessageType::NETWORK_ECHO,
+                    echo.write_to_bytes().unwrap(),
+                    "TestPeer".into(),
+                )
+                .expect("Unable to queue message");
+        }
+
+        let mut dispatch_loop = DispatchLoopBuilder::new()
+            .with_dispatch_channel((sender, receiver))
+            .with_dispatcher(dispatcher)
+            .with_priority_classifier(Box::new(EchoClassifier))
+            .build()
+            .expect("Unable to build dispatch loop");
+
+        dispatch_loop.signal_shutdown();
+        dispatch_loop
+            .wait_for_shutdown()
+            .expect("Unable to shutdown dispatch loop");
+
+        assert_eq!(
+            vec![
+                "control-0".to_string(),
+                "bulk-0".to_string(),
+                "bulk-1".to_string(),
+                "bulk-2".to_string(),
+            ],
+            echos.lock().unwrap().clone()
+        );
+    }
+
     #[derive(Default)]
     struct NetworkEchoHandler {
         echos: Arc<Mutex<Vec<String>>>,
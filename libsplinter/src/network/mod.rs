@@ -16,4 +16,8 @@ pub mod auth;
 pub mod connection_manager;
 pub mod dispatch;
 pub mod handlers;
+#[cfg(feature = "network-priority")]
+pub mod priority;
+#[cfg(feature = "network-rate-limit")]
+pub mod rate_limit;
 pub(crate) mod reply;
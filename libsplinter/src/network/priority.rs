@@ -0,0 +1,270 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Priority classes for network messages.
+//!
+//! Messages are sorted into a small, fixed set of classes, each with its own queue. Queues are
+//! serviced by weighted round-robin, so that a backlog of bulk data delays control and consensus
+//! traffic by at most a few messages, while bulk data is never starved entirely.
+
+use std::collections::VecDeque;
+
+/// The priority class of a message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MessagePriority {
+    /// Connection management, authorization and admin messages.
+    Control,
+    /// Messages exchanged between consensus engines.
+    Consensus,
+    /// All other traffic, such as service data.
+    Bulk,
+}
+
+impl MessagePriority {
+    /// All priority classes, from highest to lowest.
+    pub const ALL: [MessagePriority; 3] = [
+        MessagePriority::Control,
+        MessagePriority::Consensus,
+        MessagePriority::Bulk,
+    ];
+
+    pub(crate) fn index(self) -> usize {
+        match self {
+            MessagePriority::Control => 0,
+            MessagePriority::Consensus => 1,
+            MessagePriority::Bulk => 2,
+        }
+    }
+}
+
+impl Default for MessagePriority {
+    fn default() -> Self {
+        MessagePriority::Bulk
+    }
+}
+
+/// The relative share of messages taken from each priority class when more than one class has
+/// messages waiting.
+///
+/// With the default weights of 8, 4 and 1, a fully backlogged queue set will yield eight control
+/// messages and four consensus messages for every bulk message. A weight of zero is treated as
+/// one, so that no class is starved.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PriorityWeights {
+    control: u32,
+    consensus: u32,
+    bulk: u32,
+}
+
+impl PriorityWeights {
+    pub fn new(control: u32, consensus: u32, bulk: u32) -> Self {
+        PriorityWeights {
+            control: control.max(1),
+            consensus: consensus.max(1),
+            bulk: bulk.max(1),
+        }
+    }
+
+    pub fn control(&self) -> u32 {
+        self.control
+    }
+
+    pub fn consensus(&self) -> u32 {
+        self.consensus
+    }
+
+    pub fn bulk(&self) -> u32 {
+        self.bulk
+    }
+
+    fn weight(&self, priority: MessagePriority) -> i64 {
+        i64::from(match priority {
+            MessagePriority::Control => self.control,
+            MessagePriority::Consensus => self.consensus,
+            MessagePriority::Bulk => self.bulk,
+        })
+    }
+}
+
+impl Default for PriorityWeights {
+    fn default() -> Self {
+        PriorityWeights::new(8, 4, 1)
+    }
+}
+
+/// Determines the priority class of a typed message, such as those passed through a
+/// `DispatchLoop`.
+pub trait PriorityClassifier<MT>: Send {
+    /// Returns the priority class of the given message.
+    fn classify(&self, message_type: &MT, message_bytes: &[u8]) -> MessagePriority;
+}
+
+/// Determines the priority class of a raw message payload, such as those sent through a `Mesh`.
+pub trait PayloadClassifier: Send + Sync {
+    /// Returns the priority class of the given payload.
+    fn classify(&self, payload: &[u8]) -> MessagePriority;
+}
+
+/// Selects the next priority class to service using smooth weighted round-robin.
+///
+/// Only classes that have messages waiting take part in a selection, so an idle class neither
+/// accumulates credit nor delays the others.
+#[derive(Debug)]
+pub(crate) struct WeightedScheduler {
+    weights: PriorityWeights,
+    current: [i64; 3],
+}
+
+impl WeightedScheduler {
+    pub fn new(weights: PriorityWeights) -> Self {
+        WeightedScheduler {
+            weights,
+            current: [0; 3],
+        }
+    }
+
+    /// Returns the class that should be serviced next, out of the classes for which `is_ready`
+    /// returns true, or `None` if no class is ready.
+    pub fn select<F>(&mut self, is_ready: F) -> Option<MessagePriority>
+    where
+        F: Fn(MessagePriority) -> bool,
+    {
+        let mut total = 0;
+        let mut selected: Option<MessagePriority> = None;
+
+        for priority in MessagePriority::ALL.iter().copied() {
+            if !is_ready(priority) {
+                continue;
+            }
+
+            let weight = self.weights.weight(priority);
+            total += weight;
+            self.current[priority.index()] += weight;
+
+            let is_better = match selected {
+                Some(best) => self.current[priority.index()] > self.current[best.index()],
+                None => true,
+            };
+            if is_better {
+                selected = Some(priority);
+            }
+        }
+
+        if let Some(priority) = selected {
+            self.current[priority.index()] -= total;
+        }
+
+        selected
+    }
+}
+
+/// A set of FIFO queues, one per priority class, drained by weighted round-robin.
+pub(crate) struct PriorityQueue<T> {
+    queues: [VecDeque<T>; 3],
+    scheduler: WeightedScheduler,
+}
+
+impl<T> PriorityQueue<T> {
+    pub fn new(weights: PriorityWeights) -> Self {
+        PriorityQueue {
+            queues: [VecDeque::new(), VecDeque::new(), VecDeque::new()],
+            scheduler: WeightedScheduler::new(weights),
+        }
+    }
+
+    pub fn push(&mut self, priority: MessagePriority, item: T) {
+        self.queues[priority.index()].push_back(item);
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let queues = &self.queues;
+        let priority = self
+            .scheduler
+            .select(|priority| !queues[priority.index()].is_empty())?;
+        self.queues[priority.index()].pop_front()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queues.iter().all(VecDeque::is_empty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that a fully backlogged scheduler services each class in proportion to its weight,
+    /// and that the selections are interleaved rather than grouped.
+    #[test]
+    fn test_scheduler_weighted_selection() {
+        let mut scheduler = WeightedScheduler::new(PriorityWeights::new(2, 1, 1));
+
+        let selections = (0..8)
+            .map(|_| scheduler.select(|_| true).expect("No class selected"))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            selections,
+            vec![
+                MessagePriority::Control,
+                MessagePriority::Consensus,
+                MessagePriority::Bulk,
+                MessagePriority::Control,
+                MessagePriority::Control,
+                MessagePriority::Consensus,
+                MessagePriority::Bulk,
+                MessagePriority::Control,
+            ]
+        );
+    }
+
+    /// Verify that classes without waiting messages are skipped, and that no class is selected
+    /// when none are ready.
+    #[test]
+    fn test_scheduler_skips_empty_classes() {
+        let mut scheduler = WeightedScheduler::new(PriorityWeights::default());
+
+        for _ in 0..4 {
+            assert_eq!(
+                scheduler.select(|priority| priority == MessagePriority::Bulk),
+                Some(MessagePriority::Bulk)
+            );
+        }
+
+        assert_eq!(scheduler.select(|_| false), None);
+    }
+
+    /// Verify that a bulk backlog does not delay control and consensus messages queued behind it,
+    /// and that every queued message is eventually returned.
+    #[test]
+    fn test_priority_queue_ordering() {
+        let mut queue = PriorityQueue::new(PriorityWeights::default());
+
+        for i in 0..10 {
+            queue.push(MessagePriority::Bulk, format!("bulk-{}", i));
+        }
+        queue.push(MessagePriority::Consensus, "consensus-0".to_string());
+        queue.push(MessagePriority::Control, "control-0".to_string());
+
+        assert_eq!(queue.pop(), Some("control-0".to_string()));
+        assert_eq!(queue.pop(), Some("consensus-0".to_string()));
+
+        for i in 0..10 {
+            assert_eq!(queue.pop(), Some(format!("bulk-{}", i)));
+        }
+
+        assert!(queue.is_empty());
+        assert_eq!(queue.pop(), None);
+    }
+}
@@ -0,0 +1,341 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Token-bucket rate limits for outgoing network traffic.
+//!
+//! A `RateLimiter` tracks a bucket per peer and a bucket per circuit. A message is only allowed
+//! through if both the peer it is sent to and the circuit it belongs to have capacity remaining;
+//! otherwise, the caller is expected to reject the message rather than queue it.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// The rate at which messages may be sent to a single peer or on a single circuit.
+///
+/// Each limit allows a burst of up to one second's worth of traffic. A limit that is not set is
+/// not enforced.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RateLimit {
+    messages_per_second: Option<u64>,
+    bytes_per_second: Option<u64>,
+}
+
+impl RateLimit {
+    /// Creates a `RateLimit` that does not limit any traffic.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the number of messages that may be sent per second.
+    pub fn with_messages_per_second(mut self, messages_per_second: u64) -> Self {
+        self.messages_per_second = Some(messages_per_second);
+        self
+    }
+
+    /// Limits the number of payload bytes that may be sent per second.
+    pub fn with_bytes_per_second(mut self, bytes_per_second: u64) -> Self {
+        self.bytes_per_second = Some(bytes_per_second);
+        self
+    }
+
+    pub fn messages_per_second(&self) -> Option<u64> {
+        self.messages_per_second
+    }
+
+    pub fn bytes_per_second(&self) -> Option<u64> {
+        self.bytes_per_second
+    }
+
+    /// Returns true if neither messages nor bytes are limited.
+    pub fn is_unlimited(&self) -> bool {
+        self.messages_per_second.is_none() && self.bytes_per_second.is_none()
+    }
+}
+
+/// Enforces per-peer and per-circuit rate limits.
+///
+/// `RateLimiter` can be cloned cheaply; all clones share the same buckets.
+#[derive(Clone)]
+pub struct RateLimiter {
+    peer_limit: RateLimit,
+    circuit_limit: RateLimit,
+    state: Arc<Mutex<RateLimiterState>>,
+}
+
+#[derive(Default)]
+struct RateLimiterState {
+    peers: HashMap<String, Buckets>,
+    circuits: HashMap<String, Buckets>,
+}
+
+impl RateLimiter {
+    /// Creates a new `RateLimiter`.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer_limit` - The limit applied to the messages sent to each peer
+    /// * `circuit_limit` - The limit applied to the messages sent on each circuit
+    pub fn new(peer_limit: RateLimit, circuit_limit: RateLimit) -> Self {
+        RateLimiter {
+            peer_limit,
+            circuit_limit,
+            state: Arc::new(Mutex::new(RateLimiterState::default())),
+        }
+    }
+
+    /// Attempts to take capacity for a message of `bytes` bytes, sent to `peer_id` on `circuit`.
+    ///
+    /// Capacity is only taken if both the peer and the circuit have room for the message; if
+    /// either does not, an error naming the exceeded limit is returned and neither bucket is
+    /// changed.
+    pub fn try_acquire(
+        &self,
+        peer_id: &str,
+        circuit: &str,
+        bytes: usize,
+    ) -> Result<(), RateLimitError> {
+        self.try_acquire_at(peer_id, circuit, bytes, Instant::now())
+    }
+
+    fn try_acquire_at(
+        &self,
+        peer_id: &str,
+        circuit: &str,
+        bytes: usize,
+        now: Instant,
+    ) -> Result<(), RateLimitError> {
+        if self.peer_limit.is_unlimited() && self.circuit_limit.is_unlimited() {
+            return Ok(());
+        }
+
+        let mut state = mutex_lock_unwrap!(self.state);
+        let state = &mut *state;
+
+        let peer_buckets = state
+            .peers
+            .entry(peer_id.to_string())
+            .or_insert_with(|| Buckets::new(&self.peer_limit, now));
+        let circuit_buckets = state
+            .circuits
+            .entry(circuit.to_string())
+            .or_insert_with(|| Buckets::new(&self.circuit_limit, now));
+
+        peer_buckets.refill(now);
+        circuit_buckets.refill(now);
+
+        if !peer_buckets.allows(bytes) {
+            return Err(RateLimitError::PeerLimitExceeded(peer_id.to_string()));
+        }
+        if !circuit_buckets.allows(bytes) {
+            return Err(RateLimitError::CircuitLimitExceeded(circuit.to_string()));
+        }
+
+        peer_buckets.take(bytes);
+        circuit_buckets.take(bytes);
+
+        Ok(())
+    }
+
+    /// Discards the bucket for the given peer, such as when it has disconnected.
+    pub fn remove_peer(&self, peer_id: &str) {
+        mutex_lock_unwrap!(self.state).peers.remove(peer_id);
+    }
+
+    /// Discards the bucket for the given circuit, such as when it has been removed.
+    pub fn remove_circuit(&self, circuit: &str) {
+        mutex_lock_unwrap!(self.state).circuits.remove(circuit);
+    }
+}
+
+/// The message and byte buckets for a single peer or circuit.
+struct Buckets {
+    messages: Option<TokenBucket>,
+    bytes: Option<TokenBucket>,
+}
+
+impl Buckets {
+    fn new(limit: &RateLimit, now: Instant) -> Self {
+        Buckets {
+            messages: limit
+                .messages_per_second
+                .map(|rate| TokenBucket::new(rate, now)),
+            bytes: limit
+                .bytes_per_second
+                .map(|rate| TokenBucket::new(rate, now)),
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        if let Some(bucket) = self.messages.as_mut() {
+            bucket.refill(now);
+        }
+        if let Some(bucket) = self.bytes.as_mut() {
+            bucket.refill(now);
+        }
+    }
+
+    fn allows(&self, bytes: usize) -> bool {
+        self.messages
+            .as_ref()
+            .map(|b| b.allows(1.0))
+            .unwrap_or(true)
+            && self
+                .bytes
+                .as_ref()
+                .map(|b| b.allows(bytes as f64))
+                .unwrap_or(true)
+    }
+
+    fn take(&mut self, bytes: usize) {
+        if let Some(bucket) = self.messages.as_mut() {
+            bucket.take(1.0);
+        }
+        if let Some(bucket) = self.bytes.as_mut() {
+            bucket.take(bytes as f64);
+        }
+    }
+}
+
+struct TokenBucket {
+    rate: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: u64, now: Instant) -> Self {
+        TokenBucket {
+            rate: rate as f64,
+            tokens: rate as f64,
+            last_refill: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now
+            .checked_duration_since(self.last_refill)
+            .unwrap_or_default()
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.last_refill = now;
+    }
+
+    /// A full bucket always allows a request, even one larger than the bucket's capacity, so
+    /// that large messages are slowed down rather than rejected forever. The bucket is then left
+    /// in debt until it has refilled.
+    fn allows(&self, amount: f64) -> bool {
+        self.tokens >= amount || (self.tokens >= self.rate && self.rate > 0.0)
+    }
+
+    fn take(&mut self, amount: f64) {
+        self.tokens -= amount;
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RateLimitError {
+    PeerLimitExceeded(String),
+    CircuitLimitExceeded(String),
+}
+
+impl Error for RateLimitError {}
+
+impl fmt::Display for RateLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RateLimitError::PeerLimitExceeded(peer_id) => {
+                write!(f, "rate limit exceeded for peer {}", peer_id)
+            }
+            RateLimitError::CircuitLimitExceeded(circuit) => {
+                write!(f, "rate limit exceeded for circuit {}", circuit)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    /// Test that the message limit for a peer is enforced, and that it is restored as time
+    /// passes.
+    #[test]
+    fn test_peer_message_limit() {
+        let limiter = RateLimiter::new(
+            RateLimit::new().with_messages_per_second(2),
+            RateLimit::new(),
+        );
+        let start = Instant::now();
+
+        assert!(limiter.try_acquire_at("peer", "alpha", 10, start).is_ok());
+        assert!(limiter.try_acquire_at("peer", "beta", 10, start).is_ok());
+        assert_eq!(
+            limiter.try_acquire_at("peer", "alpha", 10, start),
+            Err(RateLimitError::PeerLimitExceeded("peer".into()))
+        );
+
+        // Other peers are not affected
+        assert!(limiter.try_acquire_at("other", "alpha", 10, start).is_ok());
+
+        // After half a second, one more message is allowed
+        let later = start + Duration::from_millis(500);
+        assert!(limiter.try_acquire_at("peer", "alpha", 10, later).is_ok());
+        assert!(limiter.try_acquire_at("peer", "alpha", 10, later).is_err());
+    }
+
+    /// Test that the byte limit for a circuit is enforced across peers, and that a rejected
+    /// message does not use up the peer's capacity.
+    #[test]
+    fn test_circuit_byte_limit() {
+        let limiter = RateLimiter::new(
+            RateLimit::new().with_messages_per_second(2),
+            RateLimit::new().with_bytes_per_second(100),
+        );
+        let start = Instant::now();
+
+        assert!(limiter.try_acquire_at("peer_a", "alpha", 60, start).is_ok());
+        assert_eq!(
+            limiter.try_acquire_at("peer_b", "alpha", 60, start),
+            Err(RateLimitError::CircuitLimitExceeded("alpha".into()))
+        );
+
+        // peer_b still has both of its messages available
+        assert!(limiter.try_acquire_at("peer_b", "beta", 60, start).is_ok());
+        assert!(limiter.try_acquire_at("peer_b", "gamma", 60, start).is_ok());
+    }
+
+    /// Test that a message larger than the byte limit is allowed when the bucket is full, and
+    /// that the following messages must wait for the bucket to refill.
+    #[test]
+    fn test_oversized_message() {
+        let limiter = RateLimiter::new(
+            RateLimit::new(),
+            RateLimit::new().with_bytes_per_second(100),
+        );
+        let start = Instant::now();
+
+        assert!(limiter.try_acquire_at("peer", "alpha", 250, start).is_ok());
+
+        let later = start + Duration::from_secs(1);
+        assert!(limiter.try_acquire_at("peer", "alpha", 1, later).is_err());
+
+        let later = start + Duration::from_secs(2);
+        assert!(limiter.try_acquire_at("peer", "alpha", 1, later).is_ok());
+    }
+}
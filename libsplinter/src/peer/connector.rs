@@ -23,6 +23,8 @@ use super::error::{
     PeerRefRemoveError, PeerUnknownAddError,
 };
 use super::notification::{PeerManagerNotification, PeerNotificationIter, SubscriberId};
+#[cfg(feature = "peer-status")]
+use super::status::PeerConnectionStatus;
 use super::{EndpointPeerRef, PeerRef};
 use super::{PeerManagerMessage, PeerManagerRequest};
 
@@ -153,6 +155,28 @@ impl PeerManagerConnector {
             .map_err(|err| PeerListError::ReceiveError(format!("{:?}", err)))?
     }
 
+    /// Requests the connection status of the currently referenced peers.
+    ///
+    /// The status includes whether the peer is connected, the endpoint in use, when the peer was
+    /// last known to be connected, and the most recent connection error.
+    #[cfg(feature = "peer-status")]
+    pub fn list_peer_statuses(&self) -> Result<Vec<PeerConnectionStatus>, PeerListError> {
+        let (sender, recv) = channel();
+        let message = PeerManagerMessage::Request(PeerManagerRequest::ListPeerStatuses { sender });
+
+        match self.sender.send(message) {
+            Ok(()) => (),
+            Err(_) => {
+                return Err(PeerListError::InternalError(
+                    "Unable to send message to PeerManager, receiver dropped".to_string(),
+                ))
+            }
+        };
+
+        recv.recv()
+            .map_err(|err| PeerListError::ReceiveError(format!("{:?}", err)))?
+    }
+
     /// Requests the list of unreferenced peers.
     ///
     /// Unreferenced peers are those peers that have successfully connected from a remote node, but
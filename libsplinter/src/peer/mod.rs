@@ -29,6 +29,8 @@ pub mod interconnect;
 mod notification;
 mod peer_map;
 mod peer_ref;
+#[cfg(feature = "peer-status")]
+mod status;
 
 use std::cmp::min;
 use std::collections::HashMap;
@@ -36,6 +38,8 @@ use std::io::ErrorKind;
 use std::sync::mpsc::{channel, Sender};
 use std::thread;
 use std::time::Instant;
+#[cfg(feature = "peer-status")]
+use std::time::SystemTime;
 
 use uuid::Uuid;
 
@@ -57,6 +61,8 @@ pub use self::notification::{PeerManagerNotification, PeerNotificationIter, Subs
 use self::notification::{Subscriber, SubscriberMap};
 use self::peer_map::{PeerMap, PeerStatus};
 pub use self::peer_ref::{EndpointPeerRef, PeerRef};
+#[cfg(feature = "peer-status")]
+pub use self::status::{PeerConnectionState, PeerConnectionStatus};
 
 /// Internal messages to drive management
 pub(crate) enum PeerManagerMessage {
@@ -104,6 +110,10 @@ pub(crate) enum PeerManagerRequest {
     ListUnreferencedPeers {
         sender: Sender<Result<Vec<String>, PeerListError>>,
     },
+    #[cfg(feature = "peer-status")]
+    ListPeerStatuses {
+        sender: Sender<Result<Vec<PeerConnectionStatus>, PeerListError>>,
+    },
     ConnectionIds {
         sender: Sender<Result<BiHashMap<String, String>, PeerConnectionIdError>>,
     },
@@ -450,6 +460,12 @@ fn handle_request(
                 warn!("Connector dropped before receiving result of list unreferenced peers");
             }
         }
+        #[cfg(feature = "peer-status")]
+        PeerManagerRequest::ListPeerStatuses { sender } => {
+            if sender.send(Ok(peers.peer_statuses())).is_err() {
+                warn!("Connector dropped before receiving result of list peer statuses");
+            }
+        }
         PeerManagerRequest::ConnectionIds { sender } => {
             if sender.send(Ok(peers.connection_ids())).is_err() {
                 warn!("Connector dropped before receiving result of connection IDs");
@@ -904,6 +920,13 @@ fn handle_notifications(
                 peer_metadata.status = PeerStatus::Disconnected {
                     retry_attempts: attempts,
                 };
+                #[cfg(feature = "peer-status")]
+                {
+                    peer_metadata.last_error = Some(format!(
+                        "Unable to reconnect via {} after {} attempts",
+                        endpoint, attempts
+                    ));
+                }
 
                 if let Err(err) = peers.update_peer(peer_metadata) {
                     error!("Unable to update peer: {}", err);
@@ -975,6 +998,11 @@ fn handle_disconnection(
             peer: peer_metadata.id.to_string(),
         };
         info!("Peer {} is currently disconnected", identity);
+        #[cfg(feature = "peer-status")]
+        {
+            peer_metadata.last_seen = Some(SystemTime::now());
+            peer_metadata.last_error = Some(format!("Connection via {} was lost", endpoint));
+        }
         if peer_metadata.endpoints.contains(&endpoint) {
             // allow peer manager to retry connection to that endpoint until the retry max is
             // reached
@@ -1188,6 +1216,13 @@ fn handle_connected(
             // set its status to pending, this will cause the endpoints to be retried at
             // a later time
             peer_metadata.status = PeerStatus::Pending;
+            #[cfg(feature = "peer-status")]
+            {
+                peer_metadata.last_error = Some(format!(
+                    "Presented mismatched identity {} via {}",
+                    identity, endpoint
+                ));
+            }
 
             error!(
                 "Peer {} (via {}) presented a mismatched identity {}",
@@ -1291,6 +1326,10 @@ fn handle_fatal_connection(
 
         // set peer to pending so its endpoints will be retried in the future
         peer_metadata.status = PeerStatus::Pending;
+        #[cfg(feature = "peer-status")]
+        {
+            peer_metadata.last_error = Some(error);
+        }
         if let Err(err) = peers.update_peer(peer_metadata) {
             error!("Unable to update peer: {}", err);
         }
@@ -1842,6 +1881,87 @@ pub mod tests {
         mesh.wait_for_shutdown().expect("Unable to shutdown mesh");
     }
 
+    // Test that list_peer_statuses returns the connection status of each peer
+    //
+    // 1. add test_peer
+    // 2. verify that a Connected notification is received
+    // 3. call list_peer_statuses
+    // 4. verify that test_peer is reported as connected via its endpoint, with no errors
+    #[cfg(feature = "peer-status")]
+    #[test]
+    fn test_peer_manager_list_peer_statuses() {
+        let mut transport = Box::new(InprocTransport::default());
+        let mut listener = transport.listen("inproc://test").unwrap();
+
+        thread::spawn(move || {
+            listener.accept().unwrap();
+        });
+
+        let mut mesh = Mesh::new(512, 128);
+        let mut cm = ConnectionManager::builder()
+            .with_authorizer(Box::new(NoopAuthorizer::new("test_peer")))
+            .with_matrix_life_cycle(mesh.get_life_cycle())
+            .with_matrix_sender(mesh.get_sender())
+            .with_transport(transport.clone())
+            .start()
+            .expect("Unable to start Connection Manager");
+
+        let connector = cm.connector();
+        let mut peer_manager = PeerManager::builder()
+            .with_connector(connector)
+            .with_retry_interval(1)
+            .with_identity("my_id".to_string())
+            .with_strict_ref_counts(true)
+            .start()
+            .expect("Cannot start peer_manager");
+        let peer_connector = peer_manager.connector();
+        let (tx, notification_rx): (
+            Sender<PeerManagerNotification>,
+            mpsc::Receiver<PeerManagerNotification>,
+        ) = channel();
+        peer_connector
+            .subscribe_sender(tx)
+            .expect("Unable to get subscriber");
+        let peer_ref = peer_connector
+            .add_peer_ref("test_peer".to_string(), vec!["inproc://test".to_string()])
+            .expect("Unable to add peer");
+
+        assert_eq!(peer_ref.peer_id(), "test_peer");
+
+        // timeout after 60 seconds
+        let timeout = Duration::from_secs(60);
+        let notification = notification_rx
+            .recv_timeout(timeout)
+            .expect("Unable to get new notifications");
+        assert!(
+            notification
+                == PeerManagerNotification::Connected {
+                    peer: "test_peer".to_string(),
+                }
+        );
+
+        let statuses = peer_connector
+            .list_peer_statuses()
+            .expect("Unable to get peer statuses");
+
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].peer_id, "test_peer");
+        assert_eq!(statuses[0].state, PeerConnectionState::Connected);
+        assert_eq!(statuses[0].active_endpoint, "inproc://test");
+        assert!(statuses[0].last_seen.is_some());
+        assert_eq!(statuses[0].last_error, None);
+
+        peer_manager.signal_shutdown();
+        cm.signal_shutdown();
+        peer_manager
+            .wait_for_shutdown()
+            .expect("Unable to shutdown peer manager");
+        cm.wait_for_shutdown()
+            .expect("Unable to shutdown connection manager");
+        mesh.signal_shutdown();
+        mesh.wait_for_shutdown().expect("Unable to shutdown mesh");
+    }
+
     // Test that list_peer returns the correct list of connection IDs
     //
     // 1. add test_peer
@@ -16,10 +16,14 @@
 
 use std::collections::HashMap;
 use std::time::Instant;
+#[cfg(feature = "peer-status")]
+use std::time::SystemTime;
 
 use crate::collections::BiHashMap;
 
 use super::error::PeerUpdateError;
+#[cfg(feature = "peer-status")]
+use super::status::PeerConnectionStatus;
 
 /// Enum for the current status of a peer
 #[derive(Clone, PartialEq, Debug)]
@@ -49,6 +53,12 @@ pub struct PeerMetadata {
     pub last_connection_attempt: Instant,
     /// How long to wait before trying to reconnect to a peer
     pub retry_frequency: u64,
+    /// The last time the peer was known to be connected, recorded when its connection is lost
+    #[cfg(feature = "peer-status")]
+    pub last_seen: Option<SystemTime>,
+    /// The most recent connection error for the peer
+    #[cfg(feature = "peer-status")]
+    pub last_error: Option<String>,
 }
 
 /// A map of peer IDs to peer metadata, which also maintains a redirect table for updated peer IDs.
@@ -83,6 +93,15 @@ impl PeerMap {
             .collect()
     }
 
+    /// Returns the connection status of each peer
+    #[cfg(feature = "peer-status")]
+    pub fn peer_statuses(&self) -> Vec<PeerConnectionStatus> {
+        self.peers
+            .values()
+            .map(PeerConnectionStatus::from)
+            .collect()
+    }
+
     /// Returns the current map of peer IDs to connection IDs
     pub fn connection_ids(&self) -> BiHashMap<String, String> {
         let mut peer_to_connection_id = BiHashMap::new();
@@ -118,6 +137,10 @@ impl PeerMap {
             connection_id,
             last_connection_attempt: Instant::now(),
             retry_frequency: self.initial_retry_frequency,
+            #[cfg(feature = "peer-status")]
+            last_seen: None,
+            #[cfg(feature = "peer-status")]
+            last_error: None,
         };
 
         self.peers.insert(peer_id.clone(), peer_metadata);
@@ -395,6 +418,10 @@ pub mod tests {
             status: PeerStatus::Connected,
             last_connection_attempt: Instant::now(),
             retry_frequency: 10,
+            #[cfg(feature = "peer-status")]
+            last_seen: None,
+            #[cfg(feature = "peer-status")]
+            last_error: None,
         };
 
         if let Ok(()) = peer_map.update_peer(no_peer_metadata) {
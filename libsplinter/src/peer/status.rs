@@ -0,0 +1,71 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Data structures for reporting the connection status of peers
+
+use std::time::SystemTime;
+
+use super::peer_map::{PeerMetadata, PeerStatus};
+
+/// The state of the connection to a peer
+#[derive(Clone, Debug, PartialEq)]
+pub enum PeerConnectionState {
+    /// The peer is connected and is reachable
+    Connected,
+    /// The peer does not currently have a connection, connection is being attempted
+    Pending,
+    /// The peer's connection has disconnected, reconnection is being attempted
+    Disconnected { retry_attempts: u64 },
+}
+
+/// The connection status of a peer, as tracked by the `PeerManager`
+#[derive(Clone, Debug, PartialEq)]
+pub struct PeerConnectionStatus {
+    /// The unique ID for the peer
+    pub peer_id: String,
+    /// The current state of the peer's connection
+    pub state: PeerConnectionState,
+    /// The endpoint of the peer's current (or most recent) connection
+    pub active_endpoint: String,
+    /// The last time the peer was known to be connected
+    pub last_seen: Option<SystemTime>,
+    /// The most recent connection error for the peer, if any
+    pub last_error: Option<String>,
+}
+
+impl From<&PeerMetadata> for PeerConnectionStatus {
+    fn from(metadata: &PeerMetadata) -> Self {
+        let state = match metadata.status {
+            PeerStatus::Connected => PeerConnectionState::Connected,
+            PeerStatus::Pending => PeerConnectionState::Pending,
+            PeerStatus::Disconnected { retry_attempts } => {
+                PeerConnectionState::Disconnected { retry_attempts }
+            }
+        };
+
+        // A connected peer is being seen right now
+        let last_seen = match state {
+            PeerConnectionState::Connected => Some(SystemTime::now()),
+            _ => metadata.last_seen,
+        };
+
+        PeerConnectionStatus {
+            peer_id: metadata.id.clone(),
+            state,
+            active_endpoint: metadata.active_endpoint.clone(),
+            last_seen,
+            last_error: metadata.last_error.clone(),
+        }
+    }
+}
@@ -53,8 +53,19 @@ pub const AUTHORIZATION_PROTOCOL_VERSION: u32 = 1;
     feature = "rest-api-actix"
 ))]
 pub(crate) const AUTHORIZATION_MAINTENANCE_MIN: u32 = 1;
+#[cfg(all(
+    feature = "authorization-handler-maintenance",
+    feature = "rest-api-actix"
+))]
+pub(crate) const AUTHORIZATION_MAINTENANCE_CHANGES_MIN: u32 = 1;
+#[cfg(all(feature = "authorization-audit", feature = "rest-api-actix"))]
+pub(crate) const AUTHORIZATION_AUDIT_MIN: u32 = 1;
 #[cfg(all(feature = "authorization", feature = "rest-api-actix"))]
 pub(crate) const AUTHORIZATION_PERMISSIONS_MIN: u32 = 1;
+#[cfg(all(feature = "api-keys", feature = "rest-api-actix"))]
+pub(crate) const AUTHORIZATION_API_KEYS_MIN: u32 = 1;
+#[cfg(all(feature = "api-keys", feature = "rest-api-actix"))]
+pub(crate) const AUTHORIZATION_API_KEY_MIN: u32 = 1;
 
 // Authorization (namely RBAC management)  protocol versions
 #[cfg(all(feature = "authorization-handler-rbac", feature = "rest-api-actix"))]
@@ -74,6 +85,18 @@ pub(crate) const OAUTH_LOGIN_MIN: u32 = 1;
 #[cfg(all(feature = "oauth", feature = "rest-api-actix"))]
 pub(crate) const OAUTH_LOGOUT_MIN: u32 = 1;
 
+#[cfg(feature = "saml")]
+pub const SAML_PROTOCOL_VERSION: u32 = 1;
+
+#[cfg(all(feature = "saml", feature = "rest-api-actix"))]
+pub(crate) const SAML_ACS_MIN: u32 = 1;
+#[cfg(all(feature = "saml", feature = "rest-api-actix"))]
+pub(crate) const SAML_LOGIN_MIN: u32 = 1;
+#[cfg(all(feature = "saml", feature = "rest-api-actix"))]
+pub(crate) const SAML_LOGOUT_MIN: u32 = 1;
+#[cfg(all(feature = "saml", feature = "rest-api-actix"))]
+pub(crate) const SAML_METADATA_MIN: u32 = 1;
+
 #[cfg(feature = "registry")]
 pub const REGISTRY_PROTOCOL_VERSION: u32 = 1;
 
@@ -81,6 +104,8 @@ pub const REGISTRY_PROTOCOL_VERSION: u32 = 1;
 pub(crate) const REGISTRY_LIST_NODES_MIN: u32 = 1;
 #[cfg(all(feature = "registry", feature = "rest-api-actix"))]
 pub(crate) const REGISTRY_FETCH_NODE_MIN: u32 = 1;
+#[cfg(all(feature = "registry-subscriptions", feature = "rest-api-actix"))]
+pub(crate) const REGISTRY_SUBSCRIBE_MIN: u32 = 1;
 
 #[cfg(any(
     feature = "biome-credentials",
@@ -100,9 +125,22 @@ pub(crate) const BIOME_USER_PROTOCOL_MIN: u32 = 1;
 pub(crate) const BIOME_LIST_USERS_PROTOCOL_MIN: u32 = 1;
 #[cfg(all(feature = "biome-credentials", feature = "rest-api"))]
 pub(crate) const BIOME_VERIFY_PROTOCOL_MIN: u32 = 1;
+#[cfg(all(feature = "biome-sessions", feature = "rest-api"))]
+pub(crate) const BIOME_SESSIONS_PROTOCOL_MIN: u32 = 1;
+#[cfg(all(feature = "biome-totp", feature = "rest-api"))]
+pub(crate) const BIOME_TOTP_PROTOCOL_MIN: u32 = 1;
+#[cfg(all(feature = "biome-password-reset", feature = "rest-api"))]
+pub(crate) const BIOME_PASSWORD_RESET_PROTOCOL_MIN: u32 = 1;
+#[cfg(all(feature = "biome-login-lockout", feature = "rest-api"))]
+pub(crate) const BIOME_LOGIN_LOCKOUT_PROTOCOL_MIN: u32 = 1;
 
 #[cfg(all(feature = "biome-key-management", feature = "rest-api",))]
 pub(crate) const BIOME_KEYS_PROTOCOL_MIN: u32 = 1;
+#[cfg(all(feature = "biome-key-wrapping", feature = "rest-api"))]
+pub(crate) const BIOME_WRAPPED_KEYS_PROTOCOL_MIN: u32 = 1;
+
+#[cfg(all(feature = "biome-notifications", feature = "rest-api"))]
+pub(crate) const BIOME_NOTIFICATIONS_PROTOCOL_MIN: u32 = 1;
 
 #[cfg(all(feature = "biome-profile", feature = "rest-api",))]
 pub(crate) const BIOME_FETCH_PROFILE_PROTOCOL_MIN: u32 = 1;
@@ -24,8 +24,22 @@ mod models;
 mod operations;
 mod schema;
 
+#[cfg(feature = "registry-subscriptions")]
+use std::sync::{Arc, Mutex, MutexGuard};
+
 use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::Connection;
+
+#[cfg(feature = "registry-subscriptions")]
+use crate::error::InternalError;
 
+#[cfg(feature = "registry-signed-nodes")]
+use super::{
+    signature::{check_new_node, check_node_deletion, check_node_update, check_unsigned_deletion},
+    NodeDeletion,
+};
+#[cfg(feature = "registry-subscriptions")]
+use super::{subscriber::Subscribers, RegistryEvent, RegistrySubscriber};
 use super::{
     MetadataPredicate, Node, NodeIter, RegistryError, RegistryReader, RegistryWriter, RwRegistry,
 };
@@ -36,12 +50,23 @@ use operations::delete_node::RegistryDeleteNodeOperation as _;
 use operations::get_node::RegistryFetchNodeOperation as _;
 use operations::has_node::RegistryHasNodeOperation as _;
 use operations::list_nodes::RegistryListNodesOperation as _;
+#[cfg(feature = "registry-signed-nodes")]
+use operations::node_deletions::RegistryNodeDeletionsOperation as _;
 use operations::update_node::RegistryUpdateNodeOperation as _;
 use operations::RegistryOperations;
 
 /// A database-backed registry, powered by [`Diesel`](https://crates.io/crates/diesel).
+///
+/// Subscribers are notified of the changes made through the registry or its clones, in the order
+/// the changes were made; changes made to the database by other means are not observed.
 pub struct DieselRegistry<C: diesel::Connection + 'static> {
     connection_pool: Pool<ConnectionManager<C>>,
+    #[cfg(feature = "registry-subscriptions")]
+    subscribers: Subscribers,
+    /// Held while a change is written and its events are queued, so the events are queued in the
+    /// order the changes were committed
+    #[cfg(feature = "registry-subscriptions")]
+    write_lock: Arc<Mutex<()>>,
 }
 
 impl<C: diesel::Connection> DieselRegistry<C> {
@@ -51,7 +76,23 @@ impl<C: diesel::Connection> DieselRegistry<C> {
     ///
     ///  * `connection_pool`: connection pool for the database
     pub fn new(connection_pool: Pool<ConnectionManager<C>>) -> Self {
-        DieselRegistry { connection_pool }
+        DieselRegistry {
+            connection_pool,
+            #[cfg(feature = "registry-subscriptions")]
+            subscribers: Subscribers::default(),
+            #[cfg(feature = "registry-subscriptions")]
+            write_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Acquires the lock that is held while a change is written and its events are queued.
+    #[cfg(feature = "registry-subscriptions")]
+    fn lock_writes(&self) -> Result<MutexGuard<()>, RegistryError> {
+        self.write_lock.lock().map_err(|_| {
+            RegistryError::InternalError(InternalError::with_message(
+                "Registry write lock poisoned".into(),
+            ))
+        })
     }
 }
 
@@ -60,6 +101,10 @@ impl Clone for DieselRegistry<diesel::pg::PgConnection> {
     fn clone(&self) -> Self {
         Self {
             connection_pool: self.connection_pool.clone(),
+            #[cfg(feature = "registry-subscriptions")]
+            subscribers: self.subscribers.clone(),
+            #[cfg(feature = "registry-subscriptions")]
+            write_lock: self.write_lock.clone(),
         }
     }
 }
@@ -69,6 +114,10 @@ impl Clone for DieselRegistry<diesel::sqlite::SqliteConnection> {
     fn clone(&self) -> Self {
         Self {
             connection_pool: self.connection_pool.clone(),
+            #[cfg(feature = "registry-subscriptions")]
+            subscribers: self.subscribers.clone(),
+            #[cfg(feature = "registry-subscriptions")]
+            write_lock: self.write_lock.clone(),
         }
     }
 }
@@ -89,6 +138,16 @@ where
     }
 
     fn count_nodes(&self, predicates: &[MetadataPredicate]) -> Result<u32, RegistryError> {
+        // Predicates that cannot be applied as part of a query require loading the nodes
+        #[cfg(feature = "registry-typed-predicates")]
+        {
+            if predicates.iter().any(|predicate| predicate.requires_node()) {
+                return RegistryOperations::new(&*self.connection_pool.get()?)
+                    .list_nodes(predicates)
+                    .map(|nodes| nodes.len() as u32);
+            }
+        }
+
         RegistryOperations::new(&*self.connection_pool.get()?).count_nodes(predicates)
     }
 
@@ -99,35 +158,384 @@ where
     fn has_node(&self, identity: &str) -> Result<bool, RegistryError> {
         RegistryOperations::new(&*self.connection_pool.get()?).has_node(identity)
     }
+
+    #[cfg(feature = "registry-subscriptions")]
+    fn add_subscriber(&self, subscriber: Box<dyn RegistrySubscriber>) -> Result<(), RegistryError> {
+        self.subscribers.add(subscriber)
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl DieselRegistry<diesel::pg::PgConnection> {
+    /// Adds the `added` nodes and replaces the `updated` nodes in a single transaction, so either
+    /// all of the nodes are written or none of them are.
+    ///
+    /// # Arguments
+    ///
+    ///  * `added`: nodes that are not yet in the registry
+    ///  * `updated`: nodes that replace the registry's nodes with the same identities
+    pub fn import_nodes(&self, added: Vec<Node>, updated: Vec<Node>) -> Result<(), RegistryError> {
+        #[cfg(feature = "registry-subscriptions")]
+        let write_guard = self.lock_writes()?;
+
+        #[cfg(feature = "registry-subscriptions")]
+        let events = added
+            .iter()
+            .cloned()
+            .map(RegistryEvent::NodeAdded)
+            .chain(updated.iter().cloned().map(RegistryEvent::NodeUpdated))
+            .collect();
+
+        {
+            let connection = self.connection_pool.get()?;
+            let operations = RegistryOperations::new(&*connection);
+            connection.transaction::<_, RegistryError, _>(|| {
+                for node in added {
+                    #[cfg(feature = "registry-signed-nodes")]
+                    check_new_node(&node, operations.get_deleted_version(&node.identity)?)?;
+                    operations.add_node(node)?;
+                }
+                for node in updated {
+                    #[cfg(feature = "registry-signed-nodes")]
+                    {
+                        if let Some(previous) = operations.get_node(&node.identity)? {
+                            check_node_update(&previous, &node)?;
+                        }
+                    }
+                    operations.update_node(node)?;
+                }
+                Ok(())
+            })?;
+        }
+
+        #[cfg(feature = "registry-subscriptions")]
+        {
+            self.subscribers.queue(events);
+            drop(write_guard);
+            self.subscribers.notify();
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl DieselRegistry<diesel::sqlite::SqliteConnection> {
+    /// Adds the `added` nodes and replaces the `updated` nodes in a single transaction, so either
+    /// all of the nodes are written or none of them are.
+    ///
+    /// # Arguments
+    ///
+    ///  * `added`: nodes that are not yet in the registry
+    ///  * `updated`: nodes that replace the registry's nodes with the same identities
+    pub fn import_nodes(&self, added: Vec<Node>, updated: Vec<Node>) -> Result<(), RegistryError> {
+        #[cfg(feature = "registry-subscriptions")]
+        let write_guard = self.lock_writes()?;
+
+        #[cfg(feature = "registry-subscriptions")]
+        let events = added
+            .iter()
+            .cloned()
+            .map(RegistryEvent::NodeAdded)
+            .chain(updated.iter().cloned().map(RegistryEvent::NodeUpdated))
+            .collect();
+
+        {
+            let connection = self.connection_pool.get()?;
+            let operations = RegistryOperations::new(&*connection);
+            connection.transaction::<_, RegistryError, _>(|| {
+                for node in added {
+                    #[cfg(feature = "registry-signed-nodes")]
+                    check_new_node(&node, operations.get_deleted_version(&node.identity)?)?;
+                    operations.add_node(node)?;
+                }
+                for node in updated {
+                    #[cfg(feature = "registry-signed-nodes")]
+                    {
+                        if let Some(previous) = operations.get_node(&node.identity)? {
+                            check_node_update(&previous, &node)?;
+                        }
+                    }
+                    operations.update_node(node)?;
+                }
+                Ok(())
+            })?;
+        }
+
+        #[cfg(feature = "registry-subscriptions")]
+        {
+            self.subscribers.queue(events);
+            drop(write_guard);
+            self.subscribers.notify();
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(feature = "postgres")]
 impl RegistryWriter for DieselRegistry<diesel::pg::PgConnection> {
     fn add_node(&self, node: Node) -> Result<(), RegistryError> {
-        RegistryOperations::new(&*self.connection_pool.get()?).add_node(node)
+        #[cfg(feature = "registry-subscriptions")]
+        let write_guard = self.lock_writes()?;
+
+        #[cfg(feature = "registry-subscriptions")]
+        let event = RegistryEvent::NodeAdded(node.clone());
+
+        #[cfg(feature = "registry-signed-nodes")]
+        {
+            let connection = self.connection_pool.get()?;
+            let operations = RegistryOperations::new(&*connection);
+            connection.transaction(|| {
+                check_new_node(&node, operations.get_deleted_version(&node.identity)?)?;
+                operations.add_node(node)
+            })?;
+        }
+        #[cfg(not(feature = "registry-signed-nodes"))]
+        {
+            RegistryOperations::new(&*self.connection_pool.get()?).add_node(node)?;
+        }
+
+        #[cfg(feature = "registry-subscriptions")]
+        {
+            self.subscribers.queue(vec![event]);
+            drop(write_guard);
+            self.subscribers.notify();
+        }
+
+        Ok(())
     }
 
     fn update_node(&self, node: Node) -> Result<(), RegistryError> {
-        RegistryOperations::new(&*self.connection_pool.get()?).update_node(node)
+        #[cfg(feature = "registry-subscriptions")]
+        let write_guard = self.lock_writes()?;
+
+        #[cfg(feature = "registry-subscriptions")]
+        let event = RegistryEvent::NodeUpdated(node.clone());
+
+        #[cfg(feature = "registry-signed-nodes")]
+        {
+            let connection = self.connection_pool.get()?;
+            let operations = RegistryOperations::new(&*connection);
+            connection.transaction(|| {
+                if let Some(previous) = operations.get_node(&node.identity)? {
+                    check_node_update(&previous, &node)?;
+                }
+                operations.update_node(node)
+            })?;
+        }
+        #[cfg(not(feature = "registry-signed-nodes"))]
+        {
+            RegistryOperations::new(&*self.connection_pool.get()?).update_node(node)?;
+        }
+
+        #[cfg(feature = "registry-subscriptions")]
+        {
+            self.subscribers.queue(vec![event]);
+            drop(write_guard);
+            self.subscribers.notify();
+        }
+
+        Ok(())
     }
 
     fn delete_node(&self, identity: &str) -> Result<Option<Node>, RegistryError> {
-        RegistryOperations::new(&*self.connection_pool.get()?).delete_node(identity)
+        #[cfg(feature = "registry-subscriptions")]
+        let write_guard = self.lock_writes()?;
+
+        #[cfg(feature = "registry-signed-nodes")]
+        let node = {
+            let connection = self.connection_pool.get()?;
+            let operations = RegistryOperations::new(&*connection);
+            connection.transaction(|| {
+                if let Some(node) = operations.get_node(identity)? {
+                    check_unsigned_deletion(&node)?;
+                }
+                operations.delete_node(identity)
+            })?
+        };
+        #[cfg(not(feature = "registry-signed-nodes"))]
+        let node = RegistryOperations::new(&*self.connection_pool.get()?).delete_node(identity)?;
+
+        #[cfg(feature = "registry-subscriptions")]
+        {
+            self.subscribers.queue(
+                node.iter()
+                    .cloned()
+                    .map(RegistryEvent::NodeRemoved)
+                    .collect(),
+            );
+            drop(write_guard);
+            self.subscribers.notify();
+        }
+
+        Ok(node)
+    }
+
+    #[cfg(feature = "registry-signed-nodes")]
+    fn delete_signed_node(&self, deletion: &NodeDeletion) -> Result<Option<Node>, RegistryError> {
+        #[cfg(feature = "registry-subscriptions")]
+        let write_guard = self.lock_writes()?;
+
+        let node = {
+            let connection = self.connection_pool.get()?;
+            let operations = RegistryOperations::new(&*connection);
+            connection.transaction(|| match operations.get_node(deletion.identity())? {
+                Some(node) => {
+                    check_node_deletion(&node, deletion)?;
+                    operations.record_deletion(deletion.identity(), deletion.version())?;
+                    operations.delete_node(deletion.identity())
+                }
+                None => Ok(None),
+            })?
+        };
+
+        #[cfg(feature = "registry-subscriptions")]
+        {
+            self.subscribers.queue(
+                node.iter()
+                    .cloned()
+                    .map(RegistryEvent::NodeRemoved)
+                    .collect(),
+            );
+            drop(write_guard);
+            self.subscribers.notify();
+        }
+
+        Ok(node)
     }
 }
 
 #[cfg(feature = "sqlite")]
 impl RegistryWriter for DieselRegistry<diesel::sqlite::SqliteConnection> {
     fn add_node(&self, node: Node) -> Result<(), RegistryError> {
-        RegistryOperations::new(&*self.connection_pool.get()?).add_node(node)
+        #[cfg(feature = "registry-subscriptions")]
+        let write_guard = self.lock_writes()?;
+
+        #[cfg(feature = "registry-subscriptions")]
+        let event = RegistryEvent::NodeAdded(node.clone());
+
+        #[cfg(feature = "registry-signed-nodes")]
+        {
+            let connection = self.connection_pool.get()?;
+            let operations = RegistryOperations::new(&*connection);
+            connection.transaction(|| {
+                check_new_node(&node, operations.get_deleted_version(&node.identity)?)?;
+                operations.add_node(node)
+            })?;
+        }
+        #[cfg(not(feature = "registry-signed-nodes"))]
+        {
+            RegistryOperations::new(&*self.connection_pool.get()?).add_node(node)?;
+        }
+
+        #[cfg(feature = "registry-subscriptions")]
+        {
+            self.subscribers.queue(vec![event]);
+            drop(write_guard);
+            self.subscribers.notify();
+        }
+
+        Ok(())
     }
 
     fn update_node(&self, node: Node) -> Result<(), RegistryError> {
-        RegistryOperations::new(&*self.connection_pool.get()?).update_node(node)
+        #[cfg(feature = "registry-subscriptions")]
+        let write_guard = self.lock_writes()?;
+
+        #[cfg(feature = "registry-subscriptions")]
+        let event = RegistryEvent::NodeUpdated(node.clone());
+
+        #[cfg(feature = "registry-signed-nodes")]
+        {
+            let connection = self.connection_pool.get()?;
+            let operations = RegistryOperations::new(&*connection);
+            connection.transaction(|| {
+                if let Some(previous) = operations.get_node(&node.identity)? {
+                    check_node_update(&previous, &node)?;
+                }
+                operations.update_node(node)
+            })?;
+        }
+        #[cfg(not(feature = "registry-signed-nodes"))]
+        {
+            RegistryOperations::new(&*self.connection_pool.get()?).update_node(node)?;
+        }
+
+        #[cfg(feature = "registry-subscriptions")]
+        {
+            self.subscribers.queue(vec![event]);
+            drop(write_guard);
+            self.subscribers.notify();
+        }
+
+        Ok(())
     }
 
     fn delete_node(&self, identity: &str) -> Result<Option<Node>, RegistryError> {
-        RegistryOperations::new(&*self.connection_pool.get()?).delete_node(identity)
+        #[cfg(feature = "registry-subscriptions")]
+        let write_guard = self.lock_writes()?;
+
+        #[cfg(feature = "registry-signed-nodes")]
+        let node = {
+            let connection = self.connection_pool.get()?;
+            let operations = RegistryOperations::new(&*connection);
+            connection.transaction(|| {
+                if let Some(node) = operations.get_node(identity)? {
+                    check_unsigned_deletion(&node)?;
+                }
+                operations.delete_node(identity)
+            })?
+        };
+        #[cfg(not(feature = "registry-signed-nodes"))]
+        let node = RegistryOperations::new(&*self.connection_pool.get()?).delete_node(identity)?;
+
+        #[cfg(feature = "registry-subscriptions")]
+        {
+            self.subscribers.queue(
+                node.iter()
+                    .cloned()
+                    .map(RegistryEvent::NodeRemoved)
+                    .collect(),
+            );
+            drop(write_guard);
+            self.subscribers.notify();
+        }
+
+        Ok(node)
+    }
+
+    #[cfg(feature = "registry-signed-nodes")]
+    fn delete_signed_node(&self, deletion: &NodeDeletion) -> Result<Option<Node>, RegistryError> {
+        #[cfg(feature = "registry-subscriptions")]
+        let write_guard = self.lock_writes()?;
+
+        let node = {
+            let connection = self.connection_pool.get()?;
+            let operations = RegistryOperations::new(&*connection);
+            connection.transaction(|| match operations.get_node(deletion.identity())? {
+                Some(node) => {
+                    check_node_deletion(&node, deletion)?;
+                    operations.record_deletion(deletion.identity(), deletion.version())?;
+                    operations.delete_node(deletion.identity())
+                }
+                None => Ok(None),
+            })?
+        };
+
+        #[cfg(feature = "registry-subscriptions")]
+        {
+            self.subscribers.queue(
+                node.iter()
+                    .cloned()
+                    .map(RegistryEvent::NodeRemoved)
+                    .collect(),
+            );
+            drop(write_guard);
+            self.subscribers.notify();
+        }
+
+        Ok(node)
     }
 }
 
@@ -200,6 +608,53 @@ pub mod tests {
         assert_eq!(node, get_node_1());
     }
 
+    /// Verifies that `import_nodes` writes either all of the nodes or none of them
+    ///
+    /// 1. Setup sqlite database and insert node 1
+    /// 2. Import node 2 along with an update of node 1 that takes node 3's endpoint
+    /// 3. Verify that nothing was written when node 3 itself is also imported
+    /// 4. Import node 2 and an update of node 1
+    /// 5. Verify that both nodes were written
+    #[test]
+    fn test_import_nodes() {
+        let pool = create_connection_pool_and_migrate();
+        let registry = DieselRegistry::new(pool);
+
+        registry
+            .add_node(get_node_1())
+            .expect("Unable to insert node");
+
+        let mut node_1 = get_node_1();
+        node_1.endpoints = get_node_3().endpoints;
+        assert!(registry
+            .import_nodes(vec![get_node_2(), get_node_3()], vec![node_1])
+            .is_err());
+        assert!(!registry
+            .has_node(&get_node_2().identity)
+            .expect("Failed to check node"));
+        assert!(!registry
+            .has_node(&get_node_3().identity)
+            .expect("Failed to check node"));
+
+        let mut node_1 = get_node_1();
+        node_1.display_name = "Changed Name".to_string();
+        registry
+            .import_nodes(vec![get_node_2()], vec![node_1.clone()])
+            .expect("Unable to import nodes");
+        assert_eq!(
+            registry
+                .get_node(&get_node_1().identity)
+                .expect("Failed to fetch node"),
+            Some(node_1)
+        );
+        assert_eq!(
+            registry
+                .get_node(&get_node_2().identity)
+                .expect("Failed to fetch node"),
+            Some(get_node_2())
+        );
+    }
+
     /// Verifies that `update_node` properly updates a node
     ///
     /// 1. Setup sqlite database
@@ -602,6 +1057,82 @@ pub mod tests {
         assert_eq!(nodes, [get_node_1(), get_node_2()]);
     }
 
+    /// Verifies that list_nodes and count_nodes compare numeric metadata by value and support the
+    /// `In`, `Contains`, `Exists`, and `Regex` predicates.
+    ///
+    /// 1. Setup sqlite database
+    /// 2. Insert nodes with a "capacity" of 9 and 10, and a third node without a capacity
+    /// 3. Validate that only the node with a capacity of 10 is returned when filtered by gt 9,
+    ///    which would not be the case if the values were compared as strings
+    /// 4. Validate the `In`, `Contains`, `Exists`, and `Regex` predicates, alone and combined
+    #[cfg(feature = "registry-typed-predicates")]
+    #[test]
+    fn test_list_nodes_filter_metadata_typed() {
+        let pool = create_connection_pool_and_migrate();
+        let registry = DieselRegistry::new(pool);
+
+        let mut node_1 = get_node_1();
+        node_1.metadata.insert("capacity".into(), "9".into());
+        node_1.metadata.insert("features".into(), "gpu,ssd".into());
+        let mut node_2 = get_node_2();
+        node_2.metadata.insert("capacity".into(), "10".into());
+        node_2.metadata.insert("features".into(), "ssd".into());
+        let node_3 = get_node_3();
+
+        registry
+            .add_node(node_1.clone())
+            .expect("Unable to insert node");
+        registry
+            .add_node(node_2.clone())
+            .expect("Unable to insert node");
+        registry
+            .add_node(node_3.clone())
+            .expect("Unable to insert node");
+
+        let filter = vec![MetadataPredicate::Gt("capacity".into(), "9".into())];
+        let nodes = registry
+            .list_nodes(&filter)
+            .expect("Failed to retrieve nodes")
+            .collect::<Vec<_>>();
+        assert_eq!(nodes, [node_2.clone()]);
+        assert_eq!(registry.count_nodes(&filter).expect("Failed to count"), 1);
+
+        let filter = vec![MetadataPredicate::In(
+            "admin".into(),
+            vec!["Bob".into(), "Charlie".into()],
+        )];
+        let nodes = registry
+            .list_nodes(&filter)
+            .expect("Failed to retrieve nodes")
+            .collect::<Vec<_>>();
+        assert_eq!(nodes, [node_1.clone(), node_3.clone()]);
+        assert_eq!(registry.count_nodes(&filter).expect("Failed to count"), 2);
+
+        let filter = vec![MetadataPredicate::In("admin".into(), vec![])];
+        assert_eq!(registry.count_nodes(&filter).expect("Failed to count"), 0);
+
+        let filter = vec![MetadataPredicate::exists("capacity")];
+        assert_eq!(registry.count_nodes(&filter).expect("Failed to count"), 2);
+
+        let filter = vec![
+            MetadataPredicate::contains("features", "ssd"),
+            MetadataPredicate::Le("capacity".into(), "9.5".into()),
+        ];
+        let nodes = registry
+            .list_nodes(&filter)
+            .expect("Failed to retrieve nodes")
+            .collect::<Vec<_>>();
+        assert_eq!(nodes, [node_1]);
+        assert_eq!(registry.count_nodes(&filter).expect("Failed to count"), 1);
+
+        let filter = vec![MetadataPredicate::regex("admin", "^Ch").expect("Invalid regex")];
+        let nodes = registry
+            .list_nodes(&filter)
+            .expect("Failed to retrieve nodes")
+            .collect::<Vec<_>>();
+        assert_eq!(nodes, [node_3]);
+    }
+
     /// Verifies that delete_nodes removes the required node
     ///
     /// 1. Setup sqlite database
@@ -636,6 +1167,74 @@ pub mod tests {
         assert_eq!(nodes, [get_node_1(), get_node_3()]);
     }
 
+    /// Verifies that a signed node is stored with its version and can only be deleted by a signed
+    /// deletion, which is remembered when the node is added again
+    ///
+    /// 1. Setup sqlite database
+    /// 2. Insert a signed node with version 1 and verify it is fetched with its version
+    /// 3. Verify that the node cannot be deleted without a signed deletion
+    /// 4. Delete the node with a signed deletion
+    /// 5. Verify that the node cannot be added again unsigned or with an outdated version
+    /// 6. Verify that the node can be added again with a greater version than the deletion
+    #[cfg(feature = "registry-signed-nodes")]
+    #[test]
+    fn test_delete_signed_node() {
+        use cylinder::{secp256k1::Secp256k1Context, Context};
+
+        let pool = create_connection_pool_and_migrate();
+        let registry = DieselRegistry::new(pool);
+
+        let context = Secp256k1Context::new();
+        let private_key = context.new_random_private_key();
+        let public_key = context
+            .get_public_key(&private_key)
+            .expect("Failed to get public key")
+            .as_hex();
+        let signer = context.new_signer(private_key);
+        let unsigned_node = |version| {
+            Node::builder("Node-123")
+                .with_endpoint("tcps://12.0.0.123:8431")
+                .with_key(public_key.clone())
+                .with_version(version)
+                .build()
+                .expect("Failed to build node")
+        };
+        let signed_node = |version| {
+            unsigned_node(version)
+                .sign(&*signer)
+                .expect("Failed to sign node")
+        };
+
+        registry
+            .add_node(signed_node(1))
+            .expect("Unable to insert node");
+        assert_eq!(
+            registry
+                .get_node("Node-123")
+                .expect("Failed to fetch node")
+                .expect("Node not found"),
+            signed_node(1)
+        );
+
+        assert!(registry.delete_node("Node-123").is_err());
+
+        let deletion =
+            NodeDeletion::sign(&signed_node(1), &*signer).expect("Failed to sign deletion");
+        assert_eq!(
+            registry
+                .delete_signed_node(&deletion)
+                .expect("Unable to delete node"),
+            Some(signed_node(1))
+        );
+
+        assert!(registry.add_node(unsigned_node(0)).is_err());
+        assert!(registry.add_node(signed_node(1)).is_err());
+        assert!(registry.add_node(signed_node(2)).is_err());
+        registry
+            .add_node(signed_node(3))
+            .expect("Unable to insert node");
+    }
+
     /// Verifies that count_nodes returns the correct number of nodes
     ///
     /// 1. Setup sqlite database
@@ -693,6 +1292,127 @@ pub mod tests {
         assert_eq!(count, 2);
     }
 
+    /// Verifies that subscribers are notified of added, updated, and deleted nodes, and that they
+    /// can read the registry while handling an event
+    ///
+    /// 1. Setup sqlite database with a single connection
+    /// 2. Subscribe to the registry with a subscriber that reads the registry for each event
+    /// 3. Add, update, and delete node 1, and delete the nonexistent node 2
+    /// 4. Verify that the subscriber received an event for each change to node 1, and that the
+    ///    node was in the registry for the added and updated events
+    #[cfg(feature = "registry-subscriptions")]
+    #[test]
+    fn test_subscribe() {
+        let pool = create_connection_pool_and_migrate();
+        let registry = DieselRegistry::new(pool);
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        registry
+            .add_subscriber(Box::new(ReadingSubscriber {
+                registry: registry.clone(),
+                sender: std::sync::Mutex::new(sender),
+            }))
+            .expect("Unable to add subscriber");
+
+        let mut updated_node = get_node_1();
+        updated_node.display_name = "Bitwise IO - Node 1 (updated)".into();
+
+        registry
+            .add_node(get_node_1())
+            .expect("Unable to insert node");
+        registry
+            .update_node(updated_node.clone())
+            .expect("Unable to update node");
+        registry
+            .delete_node(&get_node_1().identity)
+            .expect("Unable to delete node");
+        registry
+            .delete_node(&get_node_2().identity)
+            .expect("Unable to delete node");
+
+        assert_eq!(
+            receiver.try_iter().collect::<Vec<_>>(),
+            vec![
+                (RegistryEvent::NodeAdded(get_node_1()), true),
+                (RegistryEvent::NodeUpdated(updated_node.clone()), true),
+                (RegistryEvent::NodeRemoved(updated_node), false),
+            ]
+        );
+    }
+
+    /// Verifies that the events of concurrent changes are sent in the order the changes were
+    /// made, so the last event matches the final state of the registry
+    ///
+    /// 1. Setup sqlite database and add node 1
+    /// 2. Update node 1 from several threads at once
+    /// 3. Verify that every update was sent and the last one is the node in the registry
+    #[cfg(feature = "registry-subscriptions")]
+    #[test]
+    fn test_subscribe_concurrent_updates() {
+        let pool = create_connection_pool_and_migrate();
+        let registry = DieselRegistry::new(pool);
+        registry
+            .add_node(get_node_1())
+            .expect("Unable to insert node");
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        registry
+            .add_subscriber(Box::new(ReadingSubscriber {
+                registry: registry.clone(),
+                sender: std::sync::Mutex::new(sender),
+            }))
+            .expect("Unable to add subscriber");
+
+        let threads = (0..4)
+            .map(|thread| {
+                let registry = registry.clone();
+                std::thread::spawn(move || {
+                    for update in 0..10 {
+                        let mut node = get_node_1();
+                        node.display_name = format!("Node 1 ({}, {})", thread, update);
+                        registry.update_node(node).expect("Unable to update node");
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            thread.join().expect("Update thread panicked");
+        }
+
+        let events = receiver.try_iter().collect::<Vec<_>>();
+        assert_eq!(events.len(), 40);
+        assert_eq!(
+            events.last().map(|(event, _)| event.node().clone()),
+            registry
+                .get_node(&get_node_1().identity)
+                .expect("Failed to fetch node")
+        );
+    }
+
+    #[cfg(feature = "registry-subscriptions")]
+    struct ReadingSubscriber {
+        registry: DieselRegistry<SqliteConnection>,
+        sender: std::sync::Mutex<std::sync::mpsc::Sender<(RegistryEvent, bool)>>,
+    }
+
+    #[cfg(feature = "registry-subscriptions")]
+    impl RegistrySubscriber for ReadingSubscriber {
+        fn handle_event(
+            &self,
+            event: &RegistryEvent,
+        ) -> Result<(), crate::registry::RegistrySubscriberError> {
+            let has_node = self
+                .registry
+                .has_node(&event.node().identity)
+                .expect("Unable to read registry");
+            self.sender
+                .lock()
+                .expect("lock poisoned")
+                .send((event.clone(), has_node))
+                .map_err(|_| crate::registry::RegistrySubscriberError::Unsubscribe)
+        }
+    }
+
     fn get_node_1() -> Node {
         Node::builder("Node-123")
             .with_endpoint("tcps://12.0.0.123:8431")
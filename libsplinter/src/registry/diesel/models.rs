@@ -19,6 +19,8 @@ use crate::registry::Node;
 use super::schema::{
     splinter_nodes, splinter_nodes_endpoints, splinter_nodes_keys, splinter_nodes_metadata,
 };
+#[cfg(feature = "registry-signed-nodes")]
+use super::schema::{splinter_nodes_deletions, splinter_nodes_signatures};
 
 #[derive(Debug, PartialEq, Associations, Identifiable, Insertable, Queryable, QueryableByName)]
 #[table_name = "splinter_nodes"]
@@ -56,6 +58,26 @@ pub struct NodeMetadataModel {
     pub value: String,
 }
 
+#[cfg(feature = "registry-signed-nodes")]
+#[derive(Debug, PartialEq, Associations, Identifiable, Insertable, Queryable)]
+#[table_name = "splinter_nodes_signatures"]
+#[belongs_to(NodesModel, foreign_key = "identity")]
+#[primary_key(identity)]
+pub struct NodeSignaturesModel {
+    pub identity: String,
+    pub signature: String,
+    pub version: i64,
+}
+
+#[cfg(feature = "registry-signed-nodes")]
+#[derive(Debug, PartialEq, Identifiable, Insertable, Queryable)]
+#[table_name = "splinter_nodes_deletions"]
+#[primary_key(identity)]
+pub struct NodeDeletionsModel {
+    pub identity: String,
+    pub version: i64,
+}
+
 impl From<&Node> for NodesModel {
     fn from(node: &Node) -> Self {
         Self {
@@ -101,3 +123,17 @@ impl From<&Node> for Vec<NodeMetadataModel> {
             .collect()
     }
 }
+
+#[cfg(feature = "registry-signed-nodes")]
+impl From<&Node> for Vec<NodeSignaturesModel> {
+    fn from(node: &Node) -> Self {
+        node.signature
+            .iter()
+            .map(|signature| NodeSignaturesModel {
+                identity: node.identity.clone(),
+                signature: signature.clone(),
+                version: node.version as i64,
+            })
+            .collect()
+    }
+}
@@ -17,6 +17,8 @@
 use diesel::{dsl::insert_into, prelude::*};
 
 use crate::error::InvalidStateError;
+#[cfg(feature = "registry-signed-nodes")]
+use crate::registry::diesel::{models::NodeSignaturesModel, schema::splinter_nodes_signatures};
 use crate::registry::{
     diesel::{
         models::{NodeEndpointsModel, NodeKeysModel, NodeMetadataModel, NodesModel},
@@ -79,6 +81,14 @@ impl<'a> RegistryAddNodeOperation for RegistryOperations<'a, diesel::pg::PgConne
                 .values(&metadata)
                 .execute(self.conn)?;
 
+            #[cfg(feature = "registry-signed-nodes")]
+            {
+                let signatures: Vec<NodeSignaturesModel> = Vec::from(&node);
+                insert_into(splinter_nodes_signatures::table)
+                    .values(&signatures)
+                    .execute(self.conn)?;
+            }
+
             Ok(())
         })
     }
@@ -130,6 +140,14 @@ impl<'a> RegistryAddNodeOperation for RegistryOperations<'a, diesel::sqlite::Sql
                 .values(&metadata)
                 .execute(self.conn)?;
 
+            #[cfg(feature = "registry-signed-nodes")]
+            {
+                let signatures: Vec<NodeSignaturesModel> = Vec::from(&node);
+                insert_into(splinter_nodes_signatures::table)
+                    .values(&signatures)
+                    .execute(self.conn)?;
+            }
+
             Ok(())
         })
     }
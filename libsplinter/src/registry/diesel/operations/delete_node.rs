@@ -16,6 +16,8 @@
 
 use diesel::{dsl::delete, prelude::*};
 
+#[cfg(feature = "registry-signed-nodes")]
+use crate::registry::diesel::schema::splinter_nodes_signatures;
 use crate::registry::{diesel::schema::splinter_nodes, Node, RegistryError};
 
 use super::{get_node::RegistryFetchNodeOperation, RegistryOperations};
@@ -27,11 +29,14 @@ pub(in crate::registry::diesel) trait RegistryDeleteNodeOperation {
 impl<'a, C> RegistryDeleteNodeOperation for RegistryOperations<'a, C>
 where
     C: diesel::Connection,
+    i64: diesel::deserialize::FromSql<diesel::sql_types::BigInt, C::Backend>,
     String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
 {
     fn delete_node(&self, identity: &str) -> Result<Option<Node>, RegistryError> {
         self.conn.transaction(|| {
             self.get_node(identity).and_then(|node| {
+                #[cfg(feature = "registry-signed-nodes")]
+                delete(splinter_nodes_signatures::table.find(identity)).execute(self.conn)?;
                 delete(splinter_nodes::table.find(identity)).execute(self.conn)?;
                 Ok(node)
             })
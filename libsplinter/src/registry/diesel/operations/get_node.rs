@@ -17,6 +17,8 @@
 use diesel::prelude::*;
 
 use crate::error::InvalidStateError;
+#[cfg(feature = "registry-signed-nodes")]
+use crate::registry::diesel::{models::NodeSignaturesModel, schema::splinter_nodes_signatures};
 use crate::registry::{
     diesel::{
         models::{NodeEndpointsModel, NodeKeysModel, NodeMetadataModel, NodesModel},
@@ -36,6 +38,7 @@ pub(in crate::registry::diesel) trait RegistryFetchNodeOperation {
 impl<'a, C> RegistryFetchNodeOperation for RegistryOperations<'a, C>
 where
     C: diesel::Connection,
+    i64: diesel::deserialize::FromSql<diesel::sql_types::BigInt, C::Backend>,
     String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
 {
     fn get_node(&self, identity: &str) -> Result<Option<Node>, RegistryError> {
@@ -69,6 +72,20 @@ where
                 for entry in metadata {
                     builder = builder.with_metadata(entry.key, entry.value);
                 }
+
+                #[cfg(feature = "registry-signed-nodes")]
+                {
+                    let signature = splinter_nodes_signatures::table
+                        .find(identity)
+                        .first::<NodeSignaturesModel>(self.conn)
+                        .optional()?;
+                    if let Some(signature) = signature {
+                        builder = builder
+                            .with_signature(signature.signature)
+                            .with_version(signature.version as u64);
+                    }
+                }
+
                 Ok(Some(builder.build().map_err(|err| {
                     RegistryError::InvalidStateError(InvalidStateError::with_message(
                         err.to_string(),
@@ -17,6 +17,8 @@
 use diesel::prelude::*;
 
 use crate::error::InvalidStateError;
+#[cfg(feature = "registry-signed-nodes")]
+use crate::registry::diesel::{models::NodeSignaturesModel, schema::splinter_nodes_signatures};
 use crate::registry::{
     diesel::{
         models::{NodeEndpointsModel, NodeKeysModel, NodeMetadataModel, NodesModel},
@@ -36,6 +38,7 @@ pub(in crate::registry::diesel) trait RegistryListNodesOperation {
 impl<'a, C> RegistryListNodesOperation for RegistryOperations<'a, C>
 where
     C: diesel::Connection,
+    i64: diesel::deserialize::FromSql<diesel::sql_types::BigInt, C::Backend>,
     String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
 {
     fn list_nodes(&self, predicates: &[MetadataPredicate]) -> Result<Vec<Node>, RegistryError> {
@@ -72,12 +75,18 @@ where
                     .load::<NodeKeysModel>(self.conn)?
                     .grouped_by(&nodes);
                 let metadata = splinter_nodes_metadata::table
-                    .filter(splinter_nodes_metadata::identity.eq_any(identities))
+                    .filter(splinter_nodes_metadata::identity.eq_any(&identities))
                     .load::<NodeMetadataModel>(self.conn)?
                     .grouped_by(&nodes);
+                #[cfg(feature = "registry-signed-nodes")]
+                let mut signatures = splinter_nodes_signatures::table
+                    .filter(splinter_nodes_signatures::identity.eq_any(&identities))
+                    .load::<NodeSignaturesModel>(self.conn)?
+                    .grouped_by(&nodes)
+                    .into_iter();
 
                 // Build the `Node`s and return them
-                nodes
+                let nodes = nodes
                     .into_iter()
                     .zip(endpoints.into_iter())
                     .zip(keys.into_iter())
@@ -98,13 +107,31 @@ where
                             builder = builder.with_metadata(entry.key, entry.value);
                         }
 
+                        #[cfg(feature = "registry-signed-nodes")]
+                        {
+                            if let Some(signature) = signatures.next().and_then(|mut s| s.pop()) {
+                                builder = builder
+                                    .with_signature(signature.signature)
+                                    .with_version(signature.version as u64);
+                            }
+                        }
+
                         builder.build().map_err(|err| {
                             RegistryError::InvalidStateError(InvalidStateError::with_message(
                                 err.to_string(),
                             ))
                         })
                     })
-                    .collect::<Result<Vec<_>, _>>()
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                // Apply the predicates that could not be applied as part of the query
+                #[cfg(feature = "registry-typed-predicates")]
+                let nodes = nodes
+                    .into_iter()
+                    .filter(|node| predicates.iter().all(|predicate| predicate.apply(node)))
+                    .collect();
+
+                Ok(nodes)
             }
         })
     }
@@ -20,6 +20,8 @@ pub(super) mod delete_node;
 pub(super) mod get_node;
 pub(super) mod has_node;
 pub(super) mod list_nodes;
+#[cfg(feature = "registry-signed-nodes")]
+pub(super) mod node_deletions;
 pub(super) mod update_node;
 
 use diesel::{
@@ -90,6 +92,7 @@ where
                     )),
                 );
             }
+            #[cfg(not(feature = "registry-typed-predicates"))]
             MetadataPredicate::Gt(key, val) => {
                 query = query.filter(exists(
                     splinter_nodes_metadata::table.filter(
@@ -101,6 +104,7 @@ where
                 ));
             }
 
+            #[cfg(not(feature = "registry-typed-predicates"))]
             MetadataPredicate::Ge(key, val) => {
                 query = query.filter(exists(
                     splinter_nodes_metadata::table.filter(
@@ -112,6 +116,7 @@ where
                 ));
             }
 
+            #[cfg(not(feature = "registry-typed-predicates"))]
             MetadataPredicate::Lt(key, val) => {
                 query = query.filter(exists(
                     splinter_nodes_metadata::table.filter(
@@ -123,6 +128,7 @@ where
                 ));
             }
 
+            #[cfg(not(feature = "registry-typed-predicates"))]
             MetadataPredicate::Le(key, val) => {
                 query = query.filter(exists(
                     splinter_nodes_metadata::table.filter(
@@ -133,6 +139,38 @@ where
                     ),
                 ));
             }
+
+            #[cfg(feature = "registry-typed-predicates")]
+            MetadataPredicate::In(key, vals) if !vals.is_empty() => {
+                query = query.filter(exists(
+                    splinter_nodes_metadata::table.filter(
+                        splinter_nodes_metadata::identity
+                            .eq(splinter_nodes::identity)
+                            .and(splinter_nodes_metadata::key.eq(key))
+                            .and(splinter_nodes_metadata::value.eq_any(vals)),
+                    ),
+                ));
+            }
+
+            // The remaining predicates cannot be expressed in SQL, so only the nodes that have the
+            // key are returned; the predicate itself is applied to the nodes after they are loaded
+            #[cfg(feature = "registry-typed-predicates")]
+            MetadataPredicate::Gt(key, _)
+            | MetadataPredicate::Ge(key, _)
+            | MetadataPredicate::Lt(key, _)
+            | MetadataPredicate::Le(key, _)
+            | MetadataPredicate::In(key, _)
+            | MetadataPredicate::Contains(key, _)
+            | MetadataPredicate::Exists(key)
+            | MetadataPredicate::Regex(key, _) => {
+                query = query.filter(exists(
+                    splinter_nodes_metadata::table.filter(
+                        splinter_nodes_metadata::identity
+                            .eq(splinter_nodes::identity)
+                            .and(splinter_nodes_metadata::key.eq(key)),
+                    ),
+                ));
+            }
         }
     }
 
@@ -0,0 +1,85 @@
+// Copyright 2018-2020 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Provides the operations on the signed deletions recorded by the `DieselRegistry`.
+
+use diesel::{
+    dsl::{delete, insert_into},
+    prelude::*,
+};
+
+use crate::registry::{
+    diesel::{models::NodeDeletionsModel, schema::splinter_nodes_deletions},
+    RegistryError,
+};
+
+use super::RegistryOperations;
+
+pub(in crate::registry::diesel) trait RegistryNodeDeletionsOperation {
+    /// Returns the version of the last signed deletion of the node, if there is one.
+    fn get_deleted_version(&self, identity: &str) -> Result<Option<u64>, RegistryError>;
+
+    /// Records the version of a signed deletion of the node, replacing any previous deletion.
+    fn record_deletion(&self, identity: &str, version: u64) -> Result<(), RegistryError>;
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> RegistryNodeDeletionsOperation for RegistryOperations<'a, diesel::pg::PgConnection> {
+    fn get_deleted_version(&self, identity: &str) -> Result<Option<u64>, RegistryError> {
+        Ok(splinter_nodes_deletions::table
+            .find(identity)
+            .first::<NodeDeletionsModel>(self.conn)
+            .optional()?
+            .map(|deletion| deletion.version as u64))
+    }
+
+    fn record_deletion(&self, identity: &str, version: u64) -> Result<(), RegistryError> {
+        self.conn.transaction::<(), _, _>(|| {
+            delete(splinter_nodes_deletions::table.find(identity)).execute(self.conn)?;
+            insert_into(splinter_nodes_deletions::table)
+                .values(NodeDeletionsModel {
+                    identity: identity.to_string(),
+                    version: version as i64,
+                })
+                .execute(self.conn)?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> RegistryNodeDeletionsOperation
+    for RegistryOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn get_deleted_version(&self, identity: &str) -> Result<Option<u64>, RegistryError> {
+        Ok(splinter_nodes_deletions::table
+            .find(identity)
+            .first::<NodeDeletionsModel>(self.conn)
+            .optional()?
+            .map(|deletion| deletion.version as u64))
+    }
+
+    fn record_deletion(&self, identity: &str, version: u64) -> Result<(), RegistryError> {
+        self.conn.transaction::<(), _, _>(|| {
+            delete(splinter_nodes_deletions::table.find(identity)).execute(self.conn)?;
+            insert_into(splinter_nodes_deletions::table)
+                .values(NodeDeletionsModel {
+                    identity: identity.to_string(),
+                    version: version as i64,
+                })
+                .execute(self.conn)?;
+            Ok(())
+        })
+    }
+}
@@ -20,6 +20,8 @@ use diesel::{
 };
 
 use crate::error::InvalidStateError;
+#[cfg(feature = "registry-signed-nodes")]
+use crate::registry::diesel::{models::NodeSignaturesModel, schema::splinter_nodes_signatures};
 use crate::registry::{
     diesel::{
         models::{NodeEndpointsModel, NodeKeysModel, NodeMetadataModel, NodesModel},
@@ -109,6 +111,20 @@ impl<'a> RegistryUpdateNodeOperation for RegistryOperations<'a, diesel::pg::PgCo
                     .values(&metadata)
                     .execute(self.conn)?;
 
+                #[cfg(feature = "registry-signed-nodes")]
+                {
+                    delete(
+                        splinter_nodes_signatures::table
+                            .filter(splinter_nodes_signatures::identity.eq(&node.identity)),
+                    )
+                    .execute(self.conn)?;
+
+                    let signatures: Vec<NodeSignaturesModel> = Vec::from(&node);
+                    insert_into(splinter_nodes_signatures::table)
+                        .values(&signatures)
+                        .execute(self.conn)?;
+                }
+
                 Ok(())
             } else {
                 Err(RegistryError::InvalidStateError(
@@ -195,6 +211,20 @@ impl<'a> RegistryUpdateNodeOperation for RegistryOperations<'a, diesel::sqlite::
                     .values(&metadata)
                     .execute(self.conn)?;
 
+                #[cfg(feature = "registry-signed-nodes")]
+                {
+                    delete(
+                        splinter_nodes_signatures::table
+                            .filter(splinter_nodes_signatures::identity.eq(&node.identity)),
+                    )
+                    .execute(self.conn)?;
+
+                    let signatures: Vec<NodeSignaturesModel> = Vec::from(&node);
+                    insert_into(splinter_nodes_signatures::table)
+                        .values(&signatures)
+                        .execute(self.conn)?;
+                }
+
                 Ok(())
             } else {
                 Err(RegistryError::InvalidStateError(
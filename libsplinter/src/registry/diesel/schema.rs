@@ -43,9 +43,26 @@ table! {
     }
 }
 
+table! {
+    splinter_nodes_signatures (identity) {
+        identity -> Text,
+        signature -> Text,
+        version -> BigInt,
+    }
+}
+
+table! {
+    splinter_nodes_deletions (identity) {
+        identity -> Text,
+        version -> BigInt,
+    }
+}
+
 allow_tables_to_appear_in_same_query!(
     splinter_nodes,
+    splinter_nodes_deletions,
     splinter_nodes_endpoints,
     splinter_nodes_keys,
-    splinter_nodes_metadata
+    splinter_nodes_metadata,
+    splinter_nodes_signatures
 );
@@ -131,6 +131,74 @@ impl fmt::Display for InvalidNodeError {
     }
 }
 
+/// Represents errors that occur while signing a node or verifying a node's signature
+#[cfg(feature = "registry-signed-nodes")]
+#[derive(Debug)]
+pub enum NodeSignatureError {
+    /// The node is not signed
+    Unsigned,
+    /// The node's signature is not valid for any of the keys that are allowed to sign it
+    InvalidSignature,
+    /// The key used to sign the node is not one of the node's keys
+    UnknownSigningKey(String),
+    /// The node could not be signed
+    SigningFailed(String),
+    /// The version of a signed update or deletion (the first value) is not greater than the
+    /// version it replaces (the second value)
+    OutdatedVersion(u64, u64),
+}
+
+#[cfg(feature = "registry-signed-nodes")]
+impl Error for NodeSignatureError {}
+
+#[cfg(feature = "registry-signed-nodes")]
+impl fmt::Display for NodeSignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NodeSignatureError::Unsigned => write!(f, "node is not signed"),
+            NodeSignatureError::InvalidSignature => {
+                write!(f, "node signature is not valid for any of the allowed keys")
+            }
+            NodeSignatureError::UnknownSigningKey(key) => {
+                write!(f, "signing key {} is not one of the node's keys", key)
+            }
+            NodeSignatureError::SigningFailed(msg) => write!(f, "failed to sign node: {}", msg),
+            NodeSignatureError::OutdatedVersion(version, current) => write!(
+                f,
+                "version {} is not greater than the current version {}",
+                version, current
+            ),
+        }
+    }
+}
+
+/// Returned by a [`RegistrySubscriber`] that could not handle a registry event.
+///
+/// [`RegistrySubscriber`]: trait.RegistrySubscriber.html
+#[cfg(feature = "registry-subscriptions")]
+#[derive(Debug)]
+pub enum RegistrySubscriberError {
+    /// The subscriber failed to handle the event, but should remain subscribed
+    UnableToHandleEvent(String),
+    /// The subscriber no longer wants to receive events
+    Unsubscribe,
+}
+
+#[cfg(feature = "registry-subscriptions")]
+impl Error for RegistrySubscriberError {}
+
+#[cfg(feature = "registry-subscriptions")]
+impl fmt::Display for RegistrySubscriberError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RegistrySubscriberError::UnableToHandleEvent(msg) => {
+                write!(f, "Unable to handle event: {}", msg)
+            }
+            RegistrySubscriberError::Unsubscribe => f.write_str("Unsubscribe"),
+        }
+    }
+}
+
 #[cfg(feature = "diesel")]
 impl From<diesel::result::Error> for RegistryError {
     fn from(err: diesel::result::Error) -> Self {
@@ -0,0 +1,100 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Typed comparisons of node metadata values.
+
+use std::cmp::Ordering;
+
+use semver::Version;
+
+/// Compares two metadata values.
+///
+/// If both values are integers or both are decimal numbers, they are compared numerically. If
+/// both are semantic versions, they are compared by version precedence. Otherwise, they are
+/// compared as strings.
+pub(super) fn compare(left: &str, right: &str) -> Ordering {
+    let (left, right) = (left.trim(), right.trim());
+
+    if let (Ok(left), Ok(right)) = (left.parse::<i64>(), right.parse::<i64>()) {
+        return left.cmp(&right);
+    }
+
+    if let (Some(left), Some(right)) = (parse_number(left), parse_number(right)) {
+        if let Some(ordering) = left.partial_cmp(&right) {
+            return ordering;
+        }
+    }
+
+    if let (Ok(left), Ok(right)) = (Version::parse(left), Version::parse(right)) {
+        return left.cmp(&right);
+    }
+
+    left.cmp(right)
+}
+
+/// Returns whether the given value, read as a comma-separated set, contains the given item.
+pub(super) fn set_contains(value: &str, item: &str) -> bool {
+    value.split(',').any(|member| member.trim() == item.trim())
+}
+
+fn parse_number(value: &str) -> Option<f64> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|number| number.is_finite())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that numbers are compared by value rather than as strings, including integers,
+    /// negative numbers, and decimals.
+    #[test]
+    fn compare_numbers() {
+        assert_eq!(compare("10", "9"), Ordering::Greater);
+        assert_eq!(compare("-10", "9"), Ordering::Less);
+        assert_eq!(compare("2.5", "10"), Ordering::Less);
+        assert_eq!(compare("1.50", "1.5"), Ordering::Equal);
+        assert_eq!(compare(" 42", "42"), Ordering::Equal);
+    }
+
+    /// Verify that semantic versions are compared by precedence, and that a pre-release version
+    /// is lower than the release.
+    #[test]
+    fn compare_versions() {
+        assert_eq!(compare("0.10.0", "0.9.1"), Ordering::Greater);
+        assert_eq!(compare("1.0.0-rc.1", "1.0.0"), Ordering::Less);
+        assert_eq!(compare("2.1.3", "2.1.3"), Ordering::Equal);
+    }
+
+    /// Verify that values that are not both numbers or both versions are compared as strings.
+    #[test]
+    fn compare_strings() {
+        assert_eq!(compare("us-east", "us-west"), Ordering::Less);
+        assert_eq!(compare("10", "abc"), Ordering::Less);
+        assert_eq!(compare("1.0.0", "9"), Ordering::Less);
+        assert_eq!(compare("NaN", "1"), "NaN".cmp("1"));
+    }
+
+    /// Verify that a comma-separated value is treated as a set of items, ignoring whitespace
+    /// around each item, and that partial matches are not accepted.
+    #[test]
+    fn set_contains_items() {
+        assert!(set_contains("gpu, ssd,arm64", "ssd"));
+        assert!(set_contains("gpu", "gpu"));
+        assert!(!set_contains("gpu,ssd", "sd"));
+        assert!(!set_contains("", "gpu"));
+    }
+}
@@ -29,17 +29,44 @@ pub mod client;
 #[cfg(feature = "diesel")]
 mod diesel;
 mod error;
+#[cfg(feature = "registry-typed-predicates")]
+mod metadata;
 #[cfg(feature = "rest-api")]
 mod rest_api;
+#[cfg(feature = "registry-signed-nodes")]
+mod signature;
+#[cfg(feature = "registry-node-status")]
+mod status;
+#[cfg(feature = "registry-subscriptions")]
+mod subscriber;
 mod unified;
 mod yaml;
 
+#[cfg(feature = "registry-typed-predicates")]
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::iter::ExactSizeIterator;
 
+#[cfg(feature = "registry-typed-predicates")]
+use crate::error::InvalidArgumentError;
+#[cfg(any(feature = "registry-signed-nodes", feature = "registry-subscriptions"))]
+use crate::error::InvalidStateError;
+
 #[cfg(feature = "diesel")]
 pub use self::diesel::DieselRegistry;
+#[cfg(feature = "registry-signed-nodes")]
+pub use error::NodeSignatureError;
+#[cfg(feature = "registry-subscriptions")]
+pub use error::RegistrySubscriberError;
 pub use error::{InvalidNodeError, RegistryError};
+#[cfg(all(feature = "registry-node-status", feature = "rest-api"))]
+pub use rest_api::RegistryRestResourceProvider;
+#[cfg(feature = "registry-signed-nodes")]
+pub use signature::{NodeDeletion, NodeSignaturePolicy};
+#[cfg(feature = "registry-node-status")]
+pub use status::NodeStatusReader;
+#[cfg(feature = "registry-subscriptions")]
+pub use subscriber::{RegistryEvent, RegistrySubscriber};
 pub use unified::UnifiedRegistry;
 pub use yaml::{LocalYamlRegistry, YamlNode};
 #[cfg(feature = "registry-remote")]
@@ -60,6 +87,13 @@ pub struct Node {
     keys: Vec<String>,
     /// A map with node metadata.
     metadata: HashMap<String, String>,
+    /// A hex-encoded signature of the node by one of its keys, if the node is signed.
+    #[cfg(feature = "registry-signed-nodes")]
+    signature: Option<String>,
+    /// The version of the node, which is signed with the node; each signed update of the node
+    /// must have a greater version than the node it replaces.
+    #[cfg(feature = "registry-signed-nodes")]
+    version: u64,
 }
 
 impl Node {
@@ -97,6 +131,18 @@ impl Node {
     pub fn metadata(&self) -> &HashMap<String, String> {
         &self.metadata
     }
+
+    /// The hex-encoded signature of the node, if it is signed
+    #[cfg(feature = "registry-signed-nodes")]
+    pub fn signature(&self) -> Option<&str> {
+        self.signature.as_deref()
+    }
+
+    /// The version of the node
+    #[cfg(feature = "registry-signed-nodes")]
+    pub fn version(&self) -> u64 {
+        self.version
+    }
 }
 
 /// A builder for creating new nodes.
@@ -106,6 +152,10 @@ pub struct NodeBuilder {
     display_name: Option<String>,
     keys: Vec<String>,
     metadata: HashMap<String, String>,
+    #[cfg(feature = "registry-signed-nodes")]
+    signature: Option<String>,
+    #[cfg(feature = "registry-signed-nodes")]
+    version: u64,
 }
 
 impl NodeBuilder {
@@ -117,6 +167,10 @@ impl NodeBuilder {
             display_name: None,
             keys: vec![],
             metadata: HashMap::new(),
+            #[cfg(feature = "registry-signed-nodes")]
+            signature: None,
+            #[cfg(feature = "registry-signed-nodes")]
+            version: 0,
         }
     }
 
@@ -156,6 +210,22 @@ impl NodeBuilder {
         self
     }
 
+    /// Set the node's hex-encoded signature. Use [`Node::sign`] to sign a node.
+    ///
+    /// [`Node::sign`]: struct.Node.html#method.sign
+    #[cfg(feature = "registry-signed-nodes")]
+    pub fn with_signature<S: Into<String>>(mut self, signature: S) -> Self {
+        self.signature = Some(signature.into());
+        self
+    }
+
+    /// Set the node's `version`; defaults to 0.
+    #[cfg(feature = "registry-signed-nodes")]
+    pub fn with_version(mut self, version: u64) -> Self {
+        self.version = version;
+        self
+    }
+
     /// Attempt to build the `Node`.
     pub fn build(self) -> Result<Node, InvalidNodeError> {
         let identity = self.identity;
@@ -169,6 +239,10 @@ impl NodeBuilder {
             display_name,
             keys: self.keys,
             metadata: self.metadata,
+            #[cfg(feature = "registry-signed-nodes")]
+            signature: self.signature,
+            #[cfg(feature = "registry-signed-nodes")]
+            version: self.version,
         };
 
         check_node_required_fields_are_not_empty(&node)?;
@@ -185,6 +259,10 @@ impl NodeBuilder {
 ///
 /// If the item is missing in a node's metadata table, the predicate returns false (with the
 /// exception of the `Ne` variant).
+///
+/// With the `registry-typed-predicates` feature, the ordering operators compare values as numbers
+/// when both values are numbers, and as semantic versions when both values are versions; other
+/// values are compared as strings.
 #[derive(Clone)]
 pub enum MetadataPredicate {
     /// Applies the `==` operator.
@@ -199,6 +277,18 @@ pub enum MetadataPredicate {
     Lt(String, String),
     /// Applies the `<=` operator.
     Le(String, String),
+    /// Matches if the value is one of the given values.
+    #[cfg(feature = "registry-typed-predicates")]
+    In(String, Vec<String>),
+    /// Matches if the value, read as a comma-separated set, contains the given item.
+    #[cfg(feature = "registry-typed-predicates")]
+    Contains(String, String),
+    /// Matches if the key is present, whatever its value.
+    #[cfg(feature = "registry-typed-predicates")]
+    Exists(String),
+    /// Matches if the value matches the given regular expression.
+    #[cfg(feature = "registry-typed-predicates")]
+    Regex(String, regex::Regex),
 }
 
 impl MetadataPredicate {
@@ -212,18 +302,85 @@ impl MetadataPredicate {
                 // This returns true, if not found.  I.e. `val != nil == true`
                 node.metadata.get(key).map(|v| v != val).unwrap_or(true)
             }
+            #[cfg(not(feature = "registry-typed-predicates"))]
             MetadataPredicate::Gt(key, val) => {
                 node.metadata.get(key).map(|v| v > val).unwrap_or(false)
             }
+            #[cfg(not(feature = "registry-typed-predicates"))]
             MetadataPredicate::Ge(key, val) => {
                 node.metadata.get(key).map(|v| v >= val).unwrap_or(false)
             }
+            #[cfg(not(feature = "registry-typed-predicates"))]
             MetadataPredicate::Lt(key, val) => {
                 node.metadata.get(key).map(|v| v < val).unwrap_or(false)
             }
+            #[cfg(not(feature = "registry-typed-predicates"))]
             MetadataPredicate::Le(key, val) => {
                 node.metadata.get(key).map(|v| v <= val).unwrap_or(false)
             }
+            #[cfg(feature = "registry-typed-predicates")]
+            MetadataPredicate::Gt(key, val) => node
+                .metadata
+                .get(key)
+                .map(|v| metadata::compare(v, val) == Ordering::Greater)
+                .unwrap_or(false),
+            #[cfg(feature = "registry-typed-predicates")]
+            MetadataPredicate::Ge(key, val) => node
+                .metadata
+                .get(key)
+                .map(|v| metadata::compare(v, val) != Ordering::Less)
+                .unwrap_or(false),
+            #[cfg(feature = "registry-typed-predicates")]
+            MetadataPredicate::Lt(key, val) => node
+                .metadata
+                .get(key)
+                .map(|v| metadata::compare(v, val) == Ordering::Less)
+                .unwrap_or(false),
+            #[cfg(feature = "registry-typed-predicates")]
+            MetadataPredicate::Le(key, val) => node
+                .metadata
+                .get(key)
+                .map(|v| metadata::compare(v, val) != Ordering::Greater)
+                .unwrap_or(false),
+            #[cfg(feature = "registry-typed-predicates")]
+            MetadataPredicate::In(key, vals) => node
+                .metadata
+                .get(key)
+                .map(|v| vals.contains(v))
+                .unwrap_or(false),
+            #[cfg(feature = "registry-typed-predicates")]
+            MetadataPredicate::Contains(key, item) => node
+                .metadata
+                .get(key)
+                .map(|v| metadata::set_contains(v, item))
+                .unwrap_or(false),
+            #[cfg(feature = "registry-typed-predicates")]
+            MetadataPredicate::Exists(key) => node.metadata.contains_key(key),
+            #[cfg(feature = "registry-typed-predicates")]
+            MetadataPredicate::Regex(key, regex) => node
+                .metadata
+                .get(key)
+                .map(|v| regex.is_match(v))
+                .unwrap_or(false),
+        }
+    }
+
+    /// Returns whether or not this predicate can only be applied to a node in memory, rather than
+    /// as part of a database query.
+    #[cfg(all(feature = "diesel", feature = "registry-typed-predicates"))]
+    pub(crate) fn requires_node(&self) -> bool {
+        match self {
+            MetadataPredicate::Eq(..)
+            | MetadataPredicate::Ne(..)
+            | MetadataPredicate::Exists(..) => false,
+            // An empty list cannot be used in an SQL `IN` statement
+            MetadataPredicate::In(_, vals) => vals.is_empty(),
+            MetadataPredicate::Gt(..)
+            | MetadataPredicate::Ge(..)
+            | MetadataPredicate::Lt(..)
+            | MetadataPredicate::Le(..)
+            | MetadataPredicate::Contains(..)
+            | MetadataPredicate::Regex(..) => true,
         }
     }
 
@@ -236,6 +393,30 @@ impl MetadataPredicate {
     pub fn ne<S: Into<String>>(key: S, value: S) -> MetadataPredicate {
         MetadataPredicate::Ne(key.into(), value.into())
     }
+
+    /// Returns the `Contains` predicate for the given key and item
+    #[cfg(feature = "registry-typed-predicates")]
+    pub fn contains<S: Into<String>>(key: S, item: S) -> MetadataPredicate {
+        MetadataPredicate::Contains(key.into(), item.into())
+    }
+
+    /// Returns the `Exists` predicate for the given key
+    #[cfg(feature = "registry-typed-predicates")]
+    pub fn exists<S: Into<String>>(key: S) -> MetadataPredicate {
+        MetadataPredicate::Exists(key.into())
+    }
+
+    /// Returns the `Regex` predicate for the given key and pattern, or an error if the pattern is
+    /// not a valid regular expression
+    #[cfg(feature = "registry-typed-predicates")]
+    pub fn regex<S: Into<String>>(
+        key: S,
+        pattern: &str,
+    ) -> Result<MetadataPredicate, InvalidArgumentError> {
+        let regex = regex::Regex::new(pattern)
+            .map_err(|err| InvalidArgumentError::new("pattern".into(), err.to_string()))?;
+        Ok(MetadataPredicate::Regex(key.into(), regex))
+    }
 }
 
 /// Type returned by the `RegistryReader::list_nodes` method
@@ -279,6 +460,22 @@ pub trait RegistryReader: Send + Sync {
     fn has_node(&self, identity: &str) -> Result<bool, RegistryError> {
         self.get_node(identity).map(|opt| opt.is_some())
     }
+
+    /// Adds a subscriber that is notified each time a node is added to, updated in, or removed
+    /// from the registry. Registries that do not support subscriptions return an error.
+    ///
+    /// # Arguments
+    ///
+    ///  * `subscriber` - The subscriber to notify of changes to the registry.
+    #[cfg(feature = "registry-subscriptions")]
+    fn add_subscriber(
+        &self,
+        _subscriber: Box<dyn RegistrySubscriber>,
+    ) -> Result<(), RegistryError> {
+        Err(RegistryError::InvalidStateError(
+            InvalidStateError::with_message("Registry does not support subscriptions".into()),
+        ))
+    }
 }
 
 /// Defines registry write capabilities.
@@ -293,6 +490,10 @@ pub trait RegistryWriter: Send + Sync {
 
     /// Replaces an existing node with the same identity.
     ///
+    /// If the existing node or the update is signed, the update must be signed by a key that is
+    /// listed in both the existing node and the update, and its version must be greater than the
+    /// existing node's version.
+    ///
     /// # Arguments
     ///
     /// * `node` - The node to be updated in the registry.
@@ -301,10 +502,34 @@ pub trait RegistryWriter: Send + Sync {
 
     /// Deletes a node with the given identity and returns the node if it was in the registry.
     ///
+    /// Signed nodes cannot be deleted this way; use [`delete_signed_node`] instead.
+    ///
+    /// [`delete_signed_node`]: trait.RegistryWriter.html#method.delete_signed_node
+    ///
     /// # Arguments
     ///
     ///  * `identity` - The Splinter identity of the node.
     fn delete_node(&self, identity: &str) -> Result<Option<Node>, RegistryError>;
+
+    /// Deletes a signed node and returns the node if it was in the registry.
+    ///
+    /// The deletion must be signed by one of the node's keys, and its version must be greater
+    /// than the node's version. Signed nodes can only be deleted this way; afterwards, the
+    /// identity can only be added again by a signed node with a greater version than the
+    /// deletion.
+    ///
+    /// # Arguments
+    ///
+    ///  * `deletion` - The signed deletion of the node.
+    #[cfg(feature = "registry-signed-nodes")]
+    fn delete_signed_node(&self, deletion: &NodeDeletion) -> Result<Option<Node>, RegistryError> {
+        Err(RegistryError::InvalidStateError(
+            InvalidStateError::with_message(format!(
+                "Unable to delete node {}: signed deletions are not supported",
+                deletion.identity()
+            )),
+        ))
+    }
 }
 
 /// Provides a marker trait for a clonable, readable and writable registry.
@@ -348,6 +573,11 @@ where
     fn has_node(&self, identity: &str) -> Result<bool, RegistryError> {
         (**self).has_node(identity)
     }
+
+    #[cfg(feature = "registry-subscriptions")]
+    fn add_subscriber(&self, subscriber: Box<dyn RegistrySubscriber>) -> Result<(), RegistryError> {
+        (**self).add_subscriber(subscriber)
+    }
 }
 
 impl<NW> RegistryWriter for Box<NW>
@@ -365,6 +595,11 @@ where
     fn delete_node(&self, identity: &str) -> Result<Option<Node>, RegistryError> {
         (**self).delete_node(identity)
     }
+
+    #[cfg(feature = "registry-signed-nodes")]
+    fn delete_signed_node(&self, deletion: &NodeDeletion) -> Result<Option<Node>, RegistryError> {
+        (**self).delete_signed_node(deletion)
+    }
 }
 
 /// Returns `Err` if not all `nodes` are valid.
@@ -628,6 +863,10 @@ mod tests {
             display_name: "display name".into(),
             keys: vec!["key3".into()],
             metadata: HashMap::new(),
+            #[cfg(feature = "registry-signed-nodes")]
+            signature: None,
+            #[cfg(feature = "registry-signed-nodes")]
+            version: 0,
         };
         match validate_nodes(&[node1.clone(), node2.clone(), empty_identity]) {
             Err(InvalidNodeError::EmptyIdentity) => {}
@@ -643,6 +882,10 @@ mod tests {
             display_name: "display name".into(),
             keys: vec!["key3".into()],
             metadata: HashMap::new(),
+            #[cfg(feature = "registry-signed-nodes")]
+            signature: None,
+            #[cfg(feature = "registry-signed-nodes")]
+            version: 0,
         };
         match validate_nodes(&[node1.clone(), node2.clone(), missing_endpoints]) {
             Err(InvalidNodeError::MissingEndpoints) => {}
@@ -658,6 +901,10 @@ mod tests {
             display_name: "display name".into(),
             keys: vec!["key3".into()],
             metadata: HashMap::new(),
+            #[cfg(feature = "registry-signed-nodes")]
+            signature: None,
+            #[cfg(feature = "registry-signed-nodes")]
+            version: 0,
         };
         match validate_nodes(&[node1.clone(), node2.clone(), empty_endpoint]) {
             Err(InvalidNodeError::EmptyEndpoint) => {}
@@ -673,6 +920,10 @@ mod tests {
             display_name: "".into(),
             keys: vec!["key3".into()],
             metadata: HashMap::new(),
+            #[cfg(feature = "registry-signed-nodes")]
+            signature: None,
+            #[cfg(feature = "registry-signed-nodes")]
+            version: 0,
         };
         match validate_nodes(&[node1.clone(), node2.clone(), empty_display_name]) {
             Err(InvalidNodeError::EmptyDisplayName) => {}
@@ -688,6 +939,10 @@ mod tests {
             display_name: "display name".into(),
             keys: vec![],
             metadata: HashMap::new(),
+            #[cfg(feature = "registry-signed-nodes")]
+            signature: None,
+            #[cfg(feature = "registry-signed-nodes")]
+            version: 0,
         };
         match validate_nodes(&[node1.clone(), node2.clone(), missing_keys]) {
             Err(InvalidNodeError::MissingKeys) => {}
@@ -703,6 +958,10 @@ mod tests {
             display_name: "display name".into(),
             keys: vec!["".into()],
             metadata: HashMap::new(),
+            #[cfg(feature = "registry-signed-nodes")]
+            signature: None,
+            #[cfg(feature = "registry-signed-nodes")]
+            version: 0,
         };
         match validate_nodes(&[node1.clone(), node2.clone(), empty_key]) {
             Err(InvalidNodeError::EmptyKey) => {}
@@ -718,6 +977,10 @@ mod tests {
             display_name: "display name".into(),
             keys: vec!["key3".into()],
             metadata: HashMap::new(),
+            #[cfg(feature = "registry-signed-nodes")]
+            signature: None,
+            #[cfg(feature = "registry-signed-nodes")]
+            version: 0,
         };
         match validate_nodes(&[node1.clone(), node2.clone(), duplicate_identity]) {
             Err(InvalidNodeError::DuplicateIdentity(id)) if &id == "identity1" => {}
@@ -733,6 +996,10 @@ mod tests {
             display_name: "display name".into(),
             keys: vec!["key3".into()],
             metadata: HashMap::new(),
+            #[cfg(feature = "registry-signed-nodes")]
+            signature: None,
+            #[cfg(feature = "registry-signed-nodes")]
+            version: 0,
         };
         match validate_nodes(&[node1.clone(), node2.clone(), duplicate_endpoint]) {
             Err(InvalidNodeError::DuplicateEndpoint(endpoint)) if &endpoint == "endpoint1" => {}
@@ -748,6 +1015,10 @@ mod tests {
             display_name: "display name".into(),
             keys: vec!["key3".into()],
             metadata: HashMap::new(),
+            #[cfg(feature = "registry-signed-nodes")]
+            signature: None,
+            #[cfg(feature = "registry-signed-nodes")]
+            version: 0,
         };
         assert!(validate_nodes(&[node1, node2, valid_node3]).is_ok());
     }
@@ -14,11 +14,14 @@
 
 //! This module provides the following endpoints:
 //!
-//! * `GET /registry/nodes` for listing nodes in the registry
+//! * `GET /registry/nodes` for listing nodes in the registry, optionally including each node's
+//!   live connection status (requires the `registry-node-status` feature)
 //! * `POST /registry/nodes` for adding a node to the registry
 
 use std::collections::HashMap;
 use std::convert::TryFrom;
+#[cfg(feature = "registry-node-status")]
+use std::sync::{Arc, Mutex};
 
 use crate::actix_web::{error::BlockingError, web, Error, HttpRequest, HttpResponse};
 use crate::error::InvalidStateError;
@@ -27,6 +30,8 @@ use crate::protocol;
 use crate::registry::rest_api::error::RegistryRestApiError;
 #[cfg(feature = "authorization")]
 use crate::registry::rest_api::{REGISTRY_READ_PERMISSION, REGISTRY_WRITE_PERMISSION};
+#[cfg(feature = "registry-node-status")]
+use crate::registry::{rest_api::resources::nodes::NodeStatusResponse, NodeStatusReader};
 use crate::registry::{
     rest_api::resources::nodes::{ListNodesResponse, NewNode, NodeResponse},
     MetadataPredicate, Node, RegistryReader, RegistryWriter, RwRegistry,
@@ -37,9 +42,53 @@ use crate::rest_api::{
     percent_encode_filter_query, ErrorResponse,
 };
 
+#[cfg(not(feature = "registry-typed-predicates"))]
 type Filter = HashMap<String, (String, String)>;
+#[cfg(feature = "registry-typed-predicates")]
+type Filter = HashMap<String, FilterCondition>;
+
+/// A filter on a single metadata key: either an operator and its operand, such as `[">", "10"]`
+/// or `["in", ["a", "b"]]`, or an operator that takes no operand, such as `["exists"]`.
+#[cfg(feature = "registry-typed-predicates")]
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FilterCondition {
+    Binary(String, FilterOperand),
+    Unary([String; 1]),
+}
+
+#[cfg(feature = "registry-typed-predicates")]
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FilterOperand {
+    Value(String),
+    Values(Vec<String>),
+}
 
 pub fn make_nodes_resource(registry: Box<dyn RwRegistry>) -> Resource {
+    build_nodes_resource(
+        registry,
+        #[cfg(feature = "registry-node-status")]
+        None,
+    )
+}
+
+/// Makes the `/registry/nodes` resource, where `GET` requests accept the `include=status` query
+/// parameter to add each node's connection status, as reported by `node_status`, to the response.
+#[cfg(feature = "registry-node-status")]
+pub fn make_nodes_resource_with_status(
+    registry: Box<dyn RwRegistry>,
+    node_status: Box<dyn NodeStatusReader>,
+) -> Resource {
+    build_nodes_resource(registry, Some(Arc::new(Mutex::new(node_status))))
+}
+
+fn build_nodes_resource(
+    registry: Box<dyn RwRegistry>,
+    #[cfg(feature = "registry-node-status")] node_status: Option<
+        Arc<Mutex<Box<dyn NodeStatusReader>>>,
+    >,
+) -> Resource {
     let registry1 = registry.clone();
     let resource =
         Resource::build("/registry/nodes").add_request_guard(ProtocolVersionRangeGuard::new(
@@ -50,7 +99,12 @@ pub fn make_nodes_resource(registry: Box<dyn RwRegistry>) -> Resource {
     {
         resource
             .add_method(Method::Get, REGISTRY_READ_PERMISSION, move |r, _| {
-                list_nodes(r, web::Data::new(registry.clone_box_as_reader()))
+                list_nodes(
+                    r,
+                    web::Data::new(registry.clone_box_as_reader()),
+                    #[cfg(feature = "registry-node-status")]
+                    node_status.clone(),
+                )
             })
             .add_method(Method::Post, REGISTRY_WRITE_PERMISSION, move |_, p| {
                 add_node(p, web::Data::new(registry1.clone()))
@@ -60,7 +114,12 @@ pub fn make_nodes_resource(registry: Box<dyn RwRegistry>) -> Resource {
     {
         resource
             .add_method(Method::Get, move |r, _| {
-                list_nodes(r, web::Data::new(registry.clone_box_as_reader()))
+                list_nodes(
+                    r,
+                    web::Data::new(registry.clone_box_as_reader()),
+                    #[cfg(feature = "registry-node-status")]
+                    node_status.clone(),
+                )
             })
             .add_method(Method::Post, move |_, p| {
                 add_node(p, web::Data::new(registry1.clone()))
@@ -71,6 +130,9 @@ pub fn make_nodes_resource(registry: Box<dyn RwRegistry>) -> Resource {
 fn list_nodes(
     req: HttpRequest,
     registry: web::Data<Box<dyn RegistryReader>>,
+    #[cfg(feature = "registry-node-status")] node_status: Option<
+        Arc<Mutex<Box<dyn NodeStatusReader>>>,
+    >,
 ) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
     let query: web::Query<HashMap<String, String>> =
         if let Ok(q) = web::Query::from_query(req.query_string()) {
@@ -139,6 +201,36 @@ fn list_nodes(
         None => None,
     };
 
+    #[cfg(feature = "registry-node-status")]
+    let node_status = match query.get("include").map(String::as_str) {
+        Some("status") => match node_status {
+            Some(node_status) => {
+                link.push_str("include=status&");
+                Some(node_status)
+            }
+            None => {
+                return Box::new(
+                    HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request(
+                            "Node status is not available from this registry",
+                        ))
+                        .into_future(),
+                )
+            }
+        },
+        Some(value) => {
+            return Box::new(
+                HttpResponse::BadRequest()
+                    .json(ErrorResponse::bad_request(&format!(
+                        "Invalid include value passed: {}",
+                        value
+                    )))
+                    .into_future(),
+            )
+        }
+        None => None,
+    };
+
     let predicates = match to_predicates(filters) {
         Ok(predicates) => predicates,
         Err(err) => {
@@ -159,6 +251,8 @@ fn list_nodes(
         predicates,
         Some(offset),
         Some(limit),
+        #[cfg(feature = "registry-node-status")]
+        node_status,
     ))
 }
 
@@ -168,6 +262,9 @@ fn query_list_nodes(
     filters: Vec<MetadataPredicate>,
     offset: Option<usize>,
     limit: Option<usize>,
+    #[cfg(feature = "registry-node-status")] node_status: Option<
+        Arc<Mutex<Box<dyn NodeStatusReader>>>,
+    >,
 ) -> impl Future<Item = HttpResponse, Error = Error> {
     web::block(move || {
         let nodes = registry
@@ -182,16 +279,62 @@ fn query_list_nodes(
             .take(limit_value)
             .collect::<Vec<_>>();
 
-        Ok((nodes, link, limit, offset, total as usize))
+        #[cfg(feature = "registry-node-status")]
+        let statuses = match node_status {
+            Some(node_status) => Some(
+                node_status
+                    .lock()
+                    .map_err(|_| {
+                        RegistryRestApiError::InternalError(
+                            "Node status reader lock was poisoned".to_string(),
+                        )
+                    })?
+                    .list_node_statuses()
+                    .map_err(|err| RegistryRestApiError::InternalError(err.to_string()))?
+                    .into_iter()
+                    .map(|status| (status.peer_id.clone(), status))
+                    .collect::<HashMap<_, _>>(),
+            ),
+            None => None,
+        };
+
+        Ok((
+            nodes,
+            link,
+            limit,
+            offset,
+            total as usize,
+            #[cfg(feature = "registry-node-status")]
+            statuses,
+        ))
     })
     .then(
         |res: Result<_, BlockingError<RegistryRestApiError>>| match res {
+            #[cfg(not(feature = "registry-node-status"))]
             Ok((nodes, link, limit, offset, total_count)) => {
                 Ok(HttpResponse::Ok().json(ListNodesResponse {
                     data: nodes.iter().map(NodeResponse::from).collect(),
                     paging: get_response_paging_info(limit, offset, &link, total_count),
                 }))
             }
+            #[cfg(feature = "registry-node-status")]
+            Ok((nodes, link, limit, offset, total_count, statuses)) => {
+                Ok(HttpResponse::Ok().json(ListNodesResponse {
+                    data: nodes
+                        .iter()
+                        .map(|node| {
+                            let response = NodeResponse::from(node);
+                            match &statuses {
+                                Some(statuses) => response.with_status(NodeStatusResponse::from(
+                                    statuses.get(&node.identity),
+                                )),
+                                None => response,
+                            }
+                        })
+                        .collect(),
+                    paging: get_response_paging_info(limit, offset, &link, total_count),
+                }))
+            }
             Err(err) => {
                 error!("Unable to list nodes: {}", err);
                 Ok(HttpResponse::InternalServerError().json(ErrorResponse::internal_error()))
@@ -200,24 +343,66 @@ fn query_list_nodes(
     )
 }
 
+#[cfg(not(feature = "registry-typed-predicates"))]
 fn to_predicates(filters: Option<Filter>) -> Result<Vec<MetadataPredicate>, String> {
     match filters {
         Some(filters) => filters
             .into_iter()
-            .map(|(key, (operator, value))| match operator.as_str() {
-                "=" => Ok(MetadataPredicate::Eq(key, value)),
-                ">" => Ok(MetadataPredicate::Gt(key, value)),
-                "<" => Ok(MetadataPredicate::Lt(key, value)),
-                ">=" => Ok(MetadataPredicate::Ge(key, value)),
-                "<=" => Ok(MetadataPredicate::Le(key, value)),
-                "!=" => Ok(MetadataPredicate::Ne(key, value)),
-                _ => Err(format!("{} is not a valid operator", operator)),
+            .map(|(key, (operator, value))| to_predicate(key, operator, value))
+            .collect(),
+        None => Ok(vec![]),
+    }
+}
+
+#[cfg(feature = "registry-typed-predicates")]
+fn to_predicates(filters: Option<Filter>) -> Result<Vec<MetadataPredicate>, String> {
+    match filters {
+        Some(filters) => filters
+            .into_iter()
+            .map(|(key, condition)| match condition {
+                FilterCondition::Binary(operator, FilterOperand::Value(value)) => {
+                    to_predicate(key, operator, value)
+                }
+                FilterCondition::Binary(operator, FilterOperand::Values(values)) => {
+                    match operator.as_str() {
+                        "in" => Ok(MetadataPredicate::In(key, values)),
+                        _ => Err(format!("{} does not take a list of values", operator)),
+                    }
+                }
+                FilterCondition::Unary([operator]) => match operator.as_str() {
+                    "exists" => Ok(MetadataPredicate::Exists(key)),
+                    _ => Err(format!("{} requires a value", operator)),
+                },
             })
             .collect(),
         None => Ok(vec![]),
     }
 }
 
+fn to_predicate(key: String, operator: String, value: String) -> Result<MetadataPredicate, String> {
+    match operator.as_str() {
+        "=" => Ok(MetadataPredicate::Eq(key, value)),
+        ">" => Ok(MetadataPredicate::Gt(key, value)),
+        "<" => Ok(MetadataPredicate::Lt(key, value)),
+        ">=" => Ok(MetadataPredicate::Ge(key, value)),
+        "<=" => Ok(MetadataPredicate::Le(key, value)),
+        "!=" => Ok(MetadataPredicate::Ne(key, value)),
+        #[cfg(feature = "registry-typed-predicates")]
+        "in" => Ok(MetadataPredicate::In(
+            key,
+            value
+                .split(',')
+                .map(|item| item.trim().to_string())
+                .collect(),
+        )),
+        #[cfg(feature = "registry-typed-predicates")]
+        "contains" => Ok(MetadataPredicate::Contains(key, value)),
+        #[cfg(feature = "registry-typed-predicates")]
+        "~=" => MetadataPredicate::regex(key, &value).map_err(|err| err.to_string()),
+        _ => Err(format!("{} is not a valid operator", operator)),
+    }
+}
+
 fn add_node(
     payload: web::Payload,
     registry: web::Data<Box<dyn RwRegistry>>,
@@ -283,7 +468,14 @@ mod tests {
     use reqwest::{blocking::Client, StatusCode, Url};
     use serde_json::{to_value, Value as JsonValue};
 
+    #[cfg(feature = "registry-node-status")]
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[cfg(feature = "registry-node-status")]
+    use crate::error::InternalError;
     use crate::error::InvalidStateError;
+    #[cfg(feature = "registry-node-status")]
+    use crate::peer::{PeerConnectionState, PeerConnectionStatus};
     use crate::registry::{error::RegistryError, NodeIter};
     use crate::rest_api::{
         actix_web_1::{RestApiBuilder, RestApiShutdownHandle},
@@ -427,6 +619,133 @@ mod tests {
         join_handle.join().expect("Unable to join rest api thread");
     }
 
+    #[cfg(feature = "registry-node-status")]
+    #[test]
+    /// Tests that a GET /registry/nodes?include=status request adds the connection status of
+    /// each node to the response, and that nodes without a connection are reported as unknown.
+    fn test_list_nodes_with_status() {
+        let (shutdown_handle, join_handle, bind_url) =
+            run_rest_api_on_open_port(vec![make_nodes_resource_with_status(
+                Box::new(MemRegistry::new(vec![get_node_1(), get_node_2()])),
+                Box::new(MemNodeStatusReader(vec![PeerConnectionStatus {
+                    peer_id: "Node-123".into(),
+                    state: PeerConnectionState::Disconnected { retry_attempts: 2 },
+                    active_endpoint: "12.0.0.123:8431".into(),
+                    last_seen: Some(UNIX_EPOCH + Duration::from_secs(100)),
+                    last_error: Some("Connection refused".into()),
+                }])),
+            )]);
+
+        let url = Url::parse(&format!(
+            "http://{}/registry/nodes?include=status",
+            bind_url
+        ))
+        .expect("Failed to parse URL");
+        let resp = Client::new()
+            .get(url)
+            .header(
+                "SplinterProtocolVersion",
+                protocol::REGISTRY_PROTOCOL_VERSION,
+            )
+            .send()
+            .expect("Failed to perform request");
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: JsonValue = resp.json().expect("Failed to deserialize body");
+
+        let nodes = body
+            .get("data")
+            .expect("No data field in response")
+            .as_array()
+            .expect("data field is not an array")
+            .to_vec();
+        assert_eq!(2, nodes.len());
+
+        let status_of = |identity: &str| {
+            nodes
+                .iter()
+                .find(|node| node.get("identity") == Some(&JsonValue::from(identity)))
+                .and_then(|node| node.get("status"))
+                .cloned()
+                .expect("Node status not in response")
+        };
+
+        assert_eq!(
+            status_of("Node-123"),
+            serde_json::json!({
+                "connection": "disconnected",
+                "endpoint": "12.0.0.123:8431",
+                "last_seen": 100,
+                "last_error": "Connection refused",
+            })
+        );
+        assert_eq!(
+            status_of("Node-456"),
+            serde_json::json!({
+                "connection": "unknown",
+                "endpoint": null,
+                "last_seen": null,
+                "last_error": null,
+            })
+        );
+
+        shutdown_handle
+            .shutdown()
+            .expect("Unable to shutdown rest api");
+        join_handle.join().expect("Unable to join rest api thread");
+    }
+
+    #[cfg(feature = "registry-typed-predicates")]
+    #[test]
+    /// Tests that the filter query parameter is parsed into the typed predicates, including the
+    /// operators that take a list of values or no value, and that invalid filters are rejected.
+    fn test_to_predicates_typed() {
+        let predicates = to_predicates(
+            serde_json::from_str(
+                "{\"capacity\":[\">=\",\"10\"],\"region\":[\"in\",[\"us-east\",\"us-west\"]],\
+                 \"features\":[\"contains\",\"gpu\"],\"admin\":[\"exists\"],\
+                 \"company\":[\"~=\",\"^Bit\"]}",
+            )
+            .expect("Failed to parse filter"),
+        )
+        .expect("Failed to convert filter");
+
+        assert_eq!(predicates.len(), 5);
+        for predicate in predicates {
+            match predicate {
+                MetadataPredicate::Ge(key, value) => {
+                    assert_eq!((key.as_str(), value.as_str()), ("capacity", "10"))
+                }
+                MetadataPredicate::In(key, values) => {
+                    assert_eq!(key, "region");
+                    assert_eq!(values, vec!["us-east".to_string(), "us-west".to_string()]);
+                }
+                MetadataPredicate::Contains(key, item) => {
+                    assert_eq!((key.as_str(), item.as_str()), ("features", "gpu"))
+                }
+                MetadataPredicate::Exists(key) => assert_eq!(key, "admin"),
+                MetadataPredicate::Regex(key, regex) => {
+                    assert_eq!(key, "company");
+                    assert!(regex.is_match("Bitwise IO"));
+                }
+                _ => panic!("Unexpected predicate"),
+            }
+        }
+
+        assert!(to_predicates(
+            serde_json::from_str("{\"company\":[\"~=\",\"(\"]}").expect("Failed to parse filter")
+        )
+        .is_err());
+        assert!(to_predicates(
+            serde_json::from_str("{\"company\":[\"=\",[\"a\"]]}").expect("Failed to parse filter")
+        )
+        .is_err());
+        assert!(to_predicates(
+            serde_json::from_str("{\"company\":[\"=\"]}").expect("Failed to parse filter")
+        )
+        .is_err());
+    }
+
     #[test]
     /// Test the POST /registry/nodes route for adding a node to the registry.
     fn test_add_node() {
@@ -539,6 +858,10 @@ mod tests {
             display_name: "Bitwise IO - Node 1".into(),
             keys: vec!["0123".into()],
             metadata,
+            #[cfg(feature = "registry-signed-nodes")]
+            signature: None,
+            #[cfg(feature = "registry-signed-nodes")]
+            version: 0,
         }
     }
 
@@ -552,6 +875,21 @@ mod tests {
             .expect("Failed to build node2")
     }
 
+    #[cfg(feature = "registry-node-status")]
+    #[derive(Clone)]
+    struct MemNodeStatusReader(Vec<PeerConnectionStatus>);
+
+    #[cfg(feature = "registry-node-status")]
+    impl NodeStatusReader for MemNodeStatusReader {
+        fn list_node_statuses(&self) -> Result<Vec<PeerConnectionStatus>, InternalError> {
+            Ok(self.0.clone())
+        }
+
+        fn clone_box(&self) -> Box<dyn NodeStatusReader> {
+            Box::new(self.clone())
+        }
+    }
+
     #[derive(Clone, Default)]
     struct MemRegistry {
         nodes: Arc<Mutex<HashMap<String, Node>>>,
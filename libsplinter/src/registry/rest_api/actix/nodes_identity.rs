@@ -16,7 +16,8 @@
 //!
 //! * `GET /registry/nodes/{identity}` for fetching a node in the registry
 //! * `PUT /registry/nodes/{identity}` for replacing a node in the registry
-//! * `DELETE /registry/nodes/{identity}` for deleting a node from the registry
+//! * `DELETE /registry/nodes/{identity}` for deleting a node from the registry; a signed node is
+//!   deleted by passing the `version` and `signature` of a signed deletion as query parameters
 
 use std::convert::TryFrom;
 
@@ -27,6 +28,8 @@ use crate::protocol;
 use crate::registry::rest_api::error::RegistryRestApiError;
 #[cfg(feature = "authorization")]
 use crate::registry::rest_api::{REGISTRY_READ_PERMISSION, REGISTRY_WRITE_PERMISSION};
+#[cfg(feature = "registry-signed-nodes")]
+use crate::registry::{rest_api::resources::nodes_identity::DeleteNodeQuery, NodeDeletion};
 use crate::registry::{
     rest_api::resources::nodes_identity::{NewNode, NodeResponse},
     Node, RegistryReader, RegistryWriter, RwRegistry,
@@ -184,8 +187,50 @@ fn delete_node(
         .get("identity")
         .unwrap_or("")
         .to_string();
+
+    #[cfg(feature = "registry-signed-nodes")]
+    let deletion = match web::Query::<DeleteNodeQuery>::from_query(request.query_string()) {
+        Ok(query) => match query.into_inner() {
+            DeleteNodeQuery {
+                version: Some(version),
+                signature: Some(signature),
+            } => Some(NodeDeletion::new(identity.clone(), version, signature)),
+            DeleteNodeQuery {
+                version: None,
+                signature: None,
+            } => None,
+            _ => {
+                return Box::new(
+                    HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request(
+                            "A signed deletion requires both a version and a signature",
+                        ))
+                        .into_future(),
+                )
+            }
+        },
+        Err(err) => {
+            return Box::new(
+                HttpResponse::BadRequest()
+                    .json(ErrorResponse::bad_request(&format!(
+                        "Invalid query: {}",
+                        err
+                    )))
+                    .into_future(),
+            )
+        }
+    };
+
     Box::new(
         web::block(move || {
+            #[cfg(feature = "registry-signed-nodes")]
+            {
+                if let Some(deletion) = deletion {
+                    return registry
+                        .delete_signed_node(&deletion)
+                        .map_err(RegistryRestApiError::from);
+                }
+            }
             registry
                 .delete_node(&identity)
                 .map_err(RegistryRestApiError::from)
@@ -196,6 +241,12 @@ fn delete_node(
                 Ok(None) => {
                     HttpResponse::NotFound().json(ErrorResponse::not_found("Node not found"))
                 }
+                Err(BlockingError::Error(RegistryRestApiError::InvalidStateError(err))) => {
+                    HttpResponse::BadRequest().json(ErrorResponse::bad_request(&format!(
+                        "Unable to delete node: {}",
+                        err
+                    )))
+                }
                 Err(err) => {
                     error!("Unable to delete node: {}", err);
                     HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
@@ -462,6 +513,10 @@ mod tests {
             display_name: "Bitwise IO - Node 1".into(),
             keys: vec!["0123".into()],
             metadata,
+            #[cfg(feature = "registry-signed-nodes")]
+            signature: None,
+            #[cfg(feature = "registry-signed-nodes")]
+            version: 0,
         }
     }
 
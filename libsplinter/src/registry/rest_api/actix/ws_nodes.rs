@@ -0,0 +1,88 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module provides the following endpoint:
+//!
+//! * `GET /ws/registry/nodes` for opening a websocket that receives changes to the registry's
+//!   nodes
+
+use crate::actix_web::{web, Error, HttpRequest, HttpResponse};
+use crate::futures::{future::IntoFuture, Future};
+use crate::protocol;
+#[cfg(feature = "authorization")]
+use crate::registry::rest_api::REGISTRY_READ_PERMISSION;
+use crate::registry::{
+    rest_api::resources::events::RegistryEventResponse, RegistryEvent, RegistryReader,
+    RegistrySubscriber, RegistrySubscriberError, RwRegistry,
+};
+use crate::rest_api::actix_web_1::{
+    new_websocket_event_sender, EventSender, Method, ProtocolVersionRangeGuard, Request, Resource,
+};
+
+pub fn make_ws_nodes_resource(registry: Box<dyn RwRegistry>) -> Resource {
+    let resource =
+        Resource::build("/ws/registry/nodes").add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::REGISTRY_SUBSCRIBE_MIN,
+            protocol::REGISTRY_PROTOCOL_VERSION,
+        ));
+    #[cfg(feature = "authorization")]
+    {
+        resource.add_method(Method::Get, REGISTRY_READ_PERMISSION, move |r, p| {
+            subscribe(r, p, registry.clone_box_as_reader())
+        })
+    }
+    #[cfg(not(feature = "authorization"))]
+    {
+        resource.add_method(Method::Get, move |r, p| {
+            subscribe(r, p, registry.clone_box_as_reader())
+        })
+    }
+}
+
+fn subscribe(
+    request: HttpRequest,
+    payload: web::Payload,
+    registry: Box<dyn RegistryReader>,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    let request = Request::from((request, payload));
+    match new_websocket_event_sender(request, Box::new(std::iter::empty())) {
+        Ok((sender, res)) => {
+            if let Err(err) = registry.add_subscriber(Box::new(WsRegistrySubscriber { sender })) {
+                error!("Unable to add registry subscriber: {}", err);
+                return Box::new(HttpResponse::InternalServerError().finish().into_future());
+            }
+            debug!("Websocket response: {:?}", res);
+            Box::new(res.into_future())
+        }
+        Err(err) => {
+            debug!("Failed to create websocket: {:?}", err);
+            Box::new(HttpResponse::InternalServerError().finish().into_future())
+        }
+    }
+}
+
+struct WsRegistrySubscriber {
+    sender: EventSender<RegistryEventResponse>,
+}
+
+impl RegistrySubscriber for WsRegistrySubscriber {
+    fn handle_event(&self, event: &RegistryEvent) -> Result<(), RegistrySubscriberError> {
+        self.sender
+            .send(RegistryEventResponse::from(event))
+            .map_err(|_| {
+                debug!("Dropping registry event and unsubscribing due to websocket being closed");
+                RegistrySubscriberError::Unsubscribe
+            })
+    }
+}
@@ -23,6 +23,8 @@ use crate::rest_api::actix_web_1::{Resource, RestResourceProvider};
 #[cfg(all(feature = "authorization", feature = "rest-api-actix"))]
 use crate::rest_api::auth::authorization::Permission;
 
+#[cfg(feature = "registry-node-status")]
+use super::NodeStatusReader;
 use super::RwRegistry;
 
 #[cfg(all(feature = "authorization", feature = "rest-api-actix"))]
@@ -45,25 +47,84 @@ const REGISTRY_WRITE_PERMISSION: Permission = Permission::Check {
 /// * `GET /registry/nodes/{identity}` - Fetch a specific node in the registry
 /// * `PUT /registry/nodes/{identity}` - Replace a node in the registry
 /// * `DELETE /registry/nodes/{identity}` - Delete a node from the registry
+/// * `GET /ws/registry/nodes` - Open a websocket that receives changes to the registry's nodes
+///   (requires the `registry-subscriptions` feature)
 ///
 /// These endpoints are only available if the following REST API backend feature is enabled:
 ///
 /// * `rest-api-actix`
 impl RestResourceProvider for dyn RwRegistry {
     fn resources(&self) -> Vec<Resource> {
-        // Allowing unused_mut because resources must be mutable if feature rest-api-actix is
-        // enabled
-        #[allow(unused_mut)]
-        let mut resources = Vec::new();
+        make_resources(
+            self,
+            #[cfg(feature = "registry-node-status")]
+            None,
+        )
+    }
+}
 
-        #[cfg(feature = "rest-api-actix")]
-        {
-            resources.append(&mut vec![
-                actix::nodes_identity::make_nodes_identity_resource(self.clone_box()),
-                actix::nodes::make_nodes_resource(self.clone_box()),
-            ]);
+/// Provides the same endpoints as the `RwRegistry` trait service, with the addition of the
+/// `include=status` query parameter on `GET /registry/nodes`. When it is given, each node in the
+/// response includes its live connection status, as reported by the given `NodeStatusReader`.
+///
+/// These endpoints are only available if the following REST API backend feature is enabled:
+///
+/// * `rest-api-actix`
+#[cfg(feature = "registry-node-status")]
+pub struct RegistryRestResourceProvider {
+    registry: Box<dyn RwRegistry>,
+    node_status: Box<dyn NodeStatusReader>,
+}
+
+#[cfg(feature = "registry-node-status")]
+impl RegistryRestResourceProvider {
+    /// Creates a new `RegistryRestResourceProvider` for the given registry and node status
+    /// reader.
+    pub fn new(registry: Box<dyn RwRegistry>, node_status: Box<dyn NodeStatusReader>) -> Self {
+        Self {
+            registry,
+            node_status,
         }
+    }
+}
+
+#[cfg(feature = "registry-node-status")]
+impl RestResourceProvider for RegistryRestResourceProvider {
+    fn resources(&self) -> Vec<Resource> {
+        make_resources(&*self.registry, Some(self.node_status.clone()))
+    }
+}
+
+#[cfg_attr(not(feature = "rest-api-actix"), allow(unused_variables))]
+fn make_resources(
+    registry: &dyn RwRegistry,
+    #[cfg(feature = "registry-node-status")] node_status: Option<Box<dyn NodeStatusReader>>,
+) -> Vec<Resource> {
+    // Allowing unused_mut because resources must be mutable if feature rest-api-actix is
+    // enabled
+    #[allow(unused_mut)]
+    let mut resources = Vec::new();
 
-        resources
+    #[cfg(feature = "rest-api-actix")]
+    {
+        resources.append(&mut vec![
+            actix::nodes_identity::make_nodes_identity_resource(registry.clone_box()),
+            #[cfg(not(feature = "registry-node-status"))]
+            actix::nodes::make_nodes_resource(registry.clone_box()),
+            #[cfg(feature = "registry-node-status")]
+            match node_status {
+                Some(node_status) => {
+                    actix::nodes::make_nodes_resource_with_status(registry.clone_box(), node_status)
+                }
+                None => actix::nodes::make_nodes_resource(registry.clone_box()),
+            },
+        ]);
+
+        #[cfg(feature = "registry-subscriptions")]
+        resources.push(actix::ws_nodes::make_ws_nodes_resource(
+            registry.clone_box(),
+        ));
     }
+
+    resources
 }
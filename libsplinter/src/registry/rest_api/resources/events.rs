@@ -0,0 +1,51 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::registry::RegistryEvent;
+
+use super::nodes_identity::NewNode;
+
+/// A change to the registry, as sent to websocket subscribers
+#[derive(Debug, Serialize)]
+pub struct RegistryEventResponse {
+    /// One of `node_added`, `node_updated`, or `node_removed`
+    pub event_type: &'static str,
+    pub node: NewNode,
+}
+
+impl From<&RegistryEvent> for RegistryEventResponse {
+    fn from(event: &RegistryEvent) -> Self {
+        let event_type = match event {
+            RegistryEvent::NodeAdded(_) => "node_added",
+            RegistryEvent::NodeUpdated(_) => "node_updated",
+            RegistryEvent::NodeRemoved(_) => "node_removed",
+        };
+        let node = event.node();
+
+        Self {
+            event_type,
+            node: NewNode {
+                identity: node.identity().to_string(),
+                endpoints: node.endpoints().to_vec(),
+                display_name: node.display_name().to_string(),
+                keys: node.keys().to_vec(),
+                metadata: node.metadata().clone(),
+                #[cfg(feature = "registry-signed-nodes")]
+                signature: node.signature().map(String::from),
+                #[cfg(feature = "registry-signed-nodes")]
+                version: node.version(),
+            },
+        }
+    }
+}
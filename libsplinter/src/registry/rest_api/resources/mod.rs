@@ -12,5 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "registry-subscriptions")]
+pub(super) mod events;
 pub(super) mod nodes;
 pub(super) mod nodes_identity;
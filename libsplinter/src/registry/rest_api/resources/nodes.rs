@@ -14,7 +14,11 @@
 
 use std::collections::HashMap;
 use std::convert::TryFrom;
+#[cfg(feature = "registry-node-status")]
+use std::time::UNIX_EPOCH;
 
+#[cfg(feature = "registry-node-status")]
+use crate::peer::{PeerConnectionState, PeerConnectionStatus};
 use crate::registry::{error::InvalidNodeError, Node};
 use crate::rest_api::paging::Paging;
 
@@ -31,6 +35,22 @@ pub struct NodeResponse<'a> {
     pub display_name: &'a str,
     pub keys: &'a [String],
     pub metadata: &'a HashMap<String, String>,
+    #[cfg(feature = "registry-signed-nodes")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<&'a str>,
+    #[cfg(feature = "registry-signed-nodes")]
+    pub version: u64,
+    #[cfg(feature = "registry-node-status")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<NodeStatusResponse>,
+}
+
+#[cfg(feature = "registry-node-status")]
+impl<'a> NodeResponse<'a> {
+    pub fn with_status(mut self, status: NodeStatusResponse) -> Self {
+        self.status = Some(status);
+        self
+    }
 }
 
 impl<'a> From<&'a Node> for NodeResponse<'a> {
@@ -41,6 +61,56 @@ impl<'a> From<&'a Node> for NodeResponse<'a> {
             display_name: &node.display_name,
             keys: &node.keys,
             metadata: &node.metadata,
+            #[cfg(feature = "registry-signed-nodes")]
+            signature: node.signature(),
+            #[cfg(feature = "registry-signed-nodes")]
+            version: node.version(),
+            #[cfg(feature = "registry-node-status")]
+            status: None,
+        }
+    }
+}
+
+/// The live connection status of a node
+#[cfg(feature = "registry-node-status")]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NodeStatusResponse {
+    /// One of `connected`, `pending`, `disconnected`, or `unknown` if the local node is not
+    /// connected to the node
+    pub connection: &'static str,
+    /// The endpoint of the node's current (or most recent) connection
+    pub endpoint: Option<String>,
+    /// The last time the node was known to be connected, in seconds since the Unix epoch
+    pub last_seen: Option<u64>,
+    /// The most recent connection error for the node
+    pub last_error: Option<String>,
+}
+
+#[cfg(feature = "registry-node-status")]
+impl From<Option<&PeerConnectionStatus>> for NodeStatusResponse {
+    fn from(status: Option<&PeerConnectionStatus>) -> Self {
+        match status {
+            Some(status) => Self {
+                connection: match status.state {
+                    PeerConnectionState::Connected => "connected",
+                    PeerConnectionState::Pending => "pending",
+                    PeerConnectionState::Disconnected { .. } => "disconnected",
+                },
+                endpoint: Some(status.active_endpoint.clone()),
+                last_seen: status.last_seen.and_then(|last_seen| {
+                    last_seen
+                        .duration_since(UNIX_EPOCH)
+                        .ok()
+                        .map(|duration| duration.as_secs())
+                }),
+                last_error: status.last_error.clone(),
+            },
+            None => Self {
+                connection: "unknown",
+                endpoint: None,
+                last_seen: None,
+                last_error: None,
+            },
         }
     }
 }
@@ -60,6 +130,14 @@ pub struct NewNode {
     pub keys: Vec<String>,
     /// A map with node metadata.
     pub metadata: HashMap<String, String>,
+    /// A hex-encoded signature of the node by one of its keys.
+    #[cfg(feature = "registry-signed-nodes")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// The version of the node; each signed update must increase it.
+    #[cfg(feature = "registry-signed-nodes")]
+    #[serde(default)]
+    pub version: u64,
 }
 
 impl TryFrom<NewNode> for Node {
@@ -75,6 +153,14 @@ impl TryFrom<NewNode> for Node {
             builder = builder.with_metadata(k, v);
         }
 
+        #[cfg(feature = "registry-signed-nodes")]
+        {
+            if let Some(signature) = node.signature {
+                builder = builder.with_signature(signature);
+            }
+            builder = builder.with_version(node.version);
+        }
+
         builder.build()
     }
 }
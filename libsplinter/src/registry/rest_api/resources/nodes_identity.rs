@@ -24,6 +24,11 @@ pub struct NodeResponse<'a> {
     pub display_name: &'a str,
     pub keys: &'a [String],
     pub metadata: &'a HashMap<String, String>,
+    #[cfg(feature = "registry-signed-nodes")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<&'a str>,
+    #[cfg(feature = "registry-signed-nodes")]
+    pub version: u64,
 }
 
 impl<'a> From<&'a Node> for NodeResponse<'a> {
@@ -34,6 +39,10 @@ impl<'a> From<&'a Node> for NodeResponse<'a> {
             display_name: &node.display_name,
             keys: &node.keys,
             metadata: &node.metadata,
+            #[cfg(feature = "registry-signed-nodes")]
+            signature: node.signature(),
+            #[cfg(feature = "registry-signed-nodes")]
+            version: node.version(),
         }
     }
 }
@@ -53,6 +62,14 @@ pub struct NewNode {
     pub keys: Vec<String>,
     /// A map with node metadata.
     pub metadata: HashMap<String, String>,
+    /// A hex-encoded signature of the node by one of its keys.
+    #[cfg(feature = "registry-signed-nodes")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// The version of the node; each signed update must increase it.
+    #[cfg(feature = "registry-signed-nodes")]
+    #[serde(default)]
+    pub version: u64,
 }
 
 impl TryFrom<NewNode> for Node {
@@ -68,6 +85,24 @@ impl TryFrom<NewNode> for Node {
             builder = builder.with_metadata(k, v);
         }
 
+        #[cfg(feature = "registry-signed-nodes")]
+        {
+            if let Some(signature) = node.signature {
+                builder = builder.with_signature(signature);
+            }
+            builder = builder.with_version(node.version);
+        }
+
         builder.build()
     }
 }
+
+/// Used to deserialize the query of a signed delete request
+#[cfg(feature = "registry-signed-nodes")]
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct DeleteNodeQuery {
+    /// The version of the deletion; must be greater than the node's version.
+    pub version: Option<u64>,
+    /// A hex-encoded signature of the deletion by one of the node's keys.
+    pub signature: Option<String>,
+}
@@ -0,0 +1,603 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Signatures on registry nodes.
+//!
+//! A node may be signed by one of its own keys. Registry writers reject nodes with invalid
+//! signatures, and only accept an update to a signed node if it is signed by a key that belongs to
+//! both the previous version of the node and the update. The node's version is signed with it, and
+//! each signed update must increase the version, so an old signed node cannot be replayed over a
+//! newer one.
+//!
+//! A signed node can only be removed with a [`NodeDeletion`] signed by one of the node's keys.
+//! Registries remember the version of the deletion, so the identity can only be added again by a
+//! signed node with a greater version.
+//!
+//! Registry readers may use a [`NodeSignaturePolicy`] to reject nodes that are unsigned or
+//! invalidly signed.
+//!
+//! [`NodeDeletion`]: struct.NodeDeletion.html
+//! [`NodeSignaturePolicy`]: enum.NodeSignaturePolicy.html
+
+use cylinder::{secp256k1::Secp256k1Context, Context, PublicKey, Signature, Signer, Verifier};
+
+use crate::error::InvalidStateError;
+use crate::hex::{parse_hex, to_hex};
+
+use super::{Node, NodeSignatureError, RegistryError};
+
+/// Determines which nodes a registry reader accepts, based on their signatures.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NodeSignaturePolicy {
+    /// Accept all nodes, whether or not they are signed.
+    AcceptAll,
+    /// Reject nodes that are signed, but whose signature is not valid.
+    RejectInvalid,
+    /// Reject nodes that are unsigned or whose signature is not valid.
+    RequireValid,
+}
+
+impl Default for NodeSignaturePolicy {
+    fn default() -> Self {
+        NodeSignaturePolicy::AcceptAll
+    }
+}
+
+impl NodeSignaturePolicy {
+    /// Returns `true` if the policy accepts the given node; returns `false` otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node to check
+    /// * `verifier` - The verifier used to check the node's signature
+    pub fn accepts(&self, node: &Node, verifier: &dyn Verifier) -> bool {
+        match self {
+            NodeSignaturePolicy::AcceptAll => true,
+            NodeSignaturePolicy::RejectInvalid => {
+                node.signature.is_none() || node.verify_signature(verifier).is_ok()
+            }
+            NodeSignaturePolicy::RequireValid => node.verify_signature(verifier).is_ok(),
+        }
+    }
+}
+
+/// The signed deletion of a node from a registry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeDeletion {
+    identity: String,
+    version: u64,
+    signature: String,
+}
+
+impl NodeDeletion {
+    /// Creates a deletion from its fields; use [`NodeDeletion::sign`] to sign a new deletion.
+    ///
+    /// # Arguments
+    ///
+    /// * `identity` - The identity of the node to delete
+    /// * `version` - The version of the deletion; must be greater than the node's version
+    /// * `signature` - The hex-encoded signature of the deletion by one of the node's keys
+    ///
+    /// [`NodeDeletion::sign`]: struct.NodeDeletion.html#method.sign
+    pub fn new<I, S>(identity: I, version: u64, signature: S) -> Self
+    where
+        I: Into<String>,
+        S: Into<String>,
+    {
+        NodeDeletion {
+            identity: identity.into(),
+            version,
+            signature: signature.into(),
+        }
+    }
+
+    /// Signs the deletion of the given node. The deletion's version is the node's version plus
+    /// one.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node to delete
+    /// * `signer` - The signer to sign the deletion with; its public key must be one of the node's
+    ///   keys
+    pub fn sign(node: &Node, signer: &dyn Signer) -> Result<Self, NodeSignatureError> {
+        let public_key = signer
+            .public_key()
+            .map_err(|err| NodeSignatureError::SigningFailed(err.to_string()))?
+            .as_hex();
+        if !node.has_key(&public_key) {
+            return Err(NodeSignatureError::UnknownSigningKey(public_key));
+        }
+
+        let mut deletion = NodeDeletion {
+            identity: node.identity.clone(),
+            version: node.version + 1,
+            signature: String::new(),
+        };
+        let signature = signer
+            .sign(&deletion.signing_bytes())
+            .map_err(|err| NodeSignatureError::SigningFailed(err.to_string()))?;
+        deletion.signature = to_hex(&signature.take_bytes());
+
+        Ok(deletion)
+    }
+
+    /// The identity of the node to delete
+    pub fn identity(&self) -> &str {
+        &self.identity
+    }
+
+    /// The version of the deletion
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// The hex-encoded signature of the deletion
+    pub fn signature(&self) -> &str {
+        &self.signature
+    }
+
+    /// Returns the bytes that are signed for the deletion. They are tagged, so a signature of a
+    /// deletion can never be mistaken for a signature of a node.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        push_field(&mut bytes, DELETION_TAG);
+        push_field(&mut bytes, &self.identity);
+        push_u64(&mut bytes, self.version);
+        bytes
+    }
+}
+
+const NODE_TAG: &str = "node";
+const DELETION_TAG: &str = "node-deletion";
+
+impl Node {
+    /// Returns the bytes that are signed for the node.
+    ///
+    /// This is an encoding of every field except the signature, including the node's version.
+    /// Endpoints, keys, and metadata are sorted first, so the bytes do not depend on the order in
+    /// which a registry stores them.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut endpoints = self.endpoints.iter().collect::<Vec<_>>();
+        endpoints.sort();
+        let mut keys = self.keys.iter().collect::<Vec<_>>();
+        keys.sort();
+        let mut metadata = self.metadata.iter().collect::<Vec<_>>();
+        metadata.sort();
+
+        let mut bytes = vec![];
+        push_field(&mut bytes, NODE_TAG);
+        push_field(&mut bytes, &self.identity);
+        push_u64(&mut bytes, self.version);
+        push_field(&mut bytes, &self.display_name);
+        push_count(&mut bytes, endpoints.len());
+        for endpoint in endpoints {
+            push_field(&mut bytes, endpoint);
+        }
+        push_count(&mut bytes, keys.len());
+        for key in keys {
+            push_field(&mut bytes, key);
+        }
+        push_count(&mut bytes, metadata.len());
+        for (key, value) in metadata {
+            push_field(&mut bytes, key);
+            push_field(&mut bytes, value);
+        }
+        bytes
+    }
+
+    /// Signs the node, replacing any existing signature.
+    ///
+    /// # Arguments
+    ///
+    /// * `signer` - The signer to sign the node with; its public key must be one of the node's
+    ///   keys
+    pub fn sign(mut self, signer: &dyn Signer) -> Result<Node, NodeSignatureError> {
+        let public_key = signer
+            .public_key()
+            .map_err(|err| NodeSignatureError::SigningFailed(err.to_string()))?
+            .as_hex();
+        if !self.has_key(&public_key) {
+            return Err(NodeSignatureError::UnknownSigningKey(public_key));
+        }
+
+        let signature = signer
+            .sign(&self.signing_bytes())
+            .map_err(|err| NodeSignatureError::SigningFailed(err.to_string()))?;
+        self.signature = Some(to_hex(&signature.take_bytes()));
+
+        Ok(self)
+    }
+
+    /// Verifies that the node is signed by one of its own keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `verifier` - The verifier used to check the node's signature
+    pub fn verify_signature(&self, verifier: &dyn Verifier) -> Result<(), NodeSignatureError> {
+        self.verify_signature_with_keys(self.keys.iter(), verifier)
+    }
+
+    fn verify_signature_with_keys<'a, I>(
+        &self,
+        keys: I,
+        verifier: &dyn Verifier,
+    ) -> Result<(), NodeSignatureError>
+    where
+        I: IntoIterator<Item = &'a String>,
+    {
+        let signature = self
+            .signature
+            .as_ref()
+            .ok_or(NodeSignatureError::Unsigned)?;
+        verify(&self.signing_bytes(), signature, keys, verifier)
+    }
+}
+
+/// Checks that `signature` is a valid hex-encoded signature of `message` by one of `keys`.
+fn verify<'a, I>(
+    message: &[u8],
+    signature: &str,
+    keys: I,
+    verifier: &dyn Verifier,
+) -> Result<(), NodeSignatureError>
+where
+    I: IntoIterator<Item = &'a String>,
+{
+    let signature =
+        Signature::new(parse_hex(signature).map_err(|_| NodeSignatureError::InvalidSignature)?);
+
+    let valid = keys.into_iter().any(|key| match parse_hex(key) {
+        Ok(key) => verifier
+            .verify(message, &signature, &PublicKey::new(key))
+            .unwrap_or(false),
+        Err(_) => false,
+    });
+
+    if valid {
+        Ok(())
+    } else {
+        Err(NodeSignatureError::InvalidSignature)
+    }
+}
+
+/// Checks that a node being added to a registry is either unsigned or signed by one of its own
+/// keys.
+///
+/// If a signed node with the same identity was deleted before, `deleted_version` is the version of
+/// its deletion; the new node must then be signed and have a greater version, so a deleted node
+/// cannot be replayed or replaced by an unsigned one.
+pub(super) fn check_new_node(
+    node: &Node,
+    deleted_version: Option<u64>,
+) -> Result<(), RegistryError> {
+    if let Some(deleted_version) = deleted_version {
+        node.verify_signature(&*new_verifier())
+            .map_err(|err| rejected(node, err))?;
+        if node.version <= deleted_version {
+            return Err(rejected(
+                node,
+                NodeSignatureError::OutdatedVersion(node.version, deleted_version),
+            ));
+        }
+    } else if node.signature.is_some() {
+        node.verify_signature(&*new_verifier())
+            .map_err(|err| rejected(node, err))?;
+    }
+    Ok(())
+}
+
+/// Checks that a node may replace the previous version of the node.
+///
+/// An unsigned node may be replaced by another unsigned node. Otherwise, the update must be signed
+/// by a key that belongs to both versions of the node and have a greater version than the previous
+/// node, so a signed node cannot be replaced by an unsigned one, by someone who does not hold one
+/// of its keys, or by an older signed version of itself.
+pub(super) fn check_node_update(previous: &Node, update: &Node) -> Result<(), RegistryError> {
+    if previous.signature.is_none() && update.signature.is_none() {
+        return Ok(());
+    }
+
+    let keys = update.keys.iter().filter(|key| previous.has_key(key));
+    update
+        .verify_signature_with_keys(keys, &*new_verifier())
+        .map_err(|err| rejected(update, err))?;
+
+    if update.version <= previous.version {
+        return Err(rejected(
+            update,
+            NodeSignatureError::OutdatedVersion(update.version, previous.version),
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that a node may be deleted without a signed deletion, which is only the case if it is
+/// unsigned.
+pub(super) fn check_unsigned_deletion(node: &Node) -> Result<(), RegistryError> {
+    if node.signature.is_some() {
+        return Err(RegistryError::InvalidStateError(
+            InvalidStateError::with_message(format!(
+                "Node {} is signed; it can only be deleted by a deletion signed with one of its \
+                 keys",
+                node.identity
+            )),
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that a signed deletion may delete the node: it must be signed by one of the node's keys,
+/// and its version must be greater than the node's version.
+pub(super) fn check_node_deletion(
+    node: &Node,
+    deletion: &NodeDeletion,
+) -> Result<(), RegistryError> {
+    verify(
+        &deletion.signing_bytes(),
+        &deletion.signature,
+        node.keys.iter(),
+        &*new_verifier(),
+    )
+    .map_err(|err| deletion_rejected(deletion, err))?;
+
+    if deletion.version <= node.version {
+        return Err(deletion_rejected(
+            deletion,
+            NodeSignatureError::OutdatedVersion(deletion.version, node.version),
+        ));
+    }
+    Ok(())
+}
+
+/// Creates the verifier used by registry writers; Splinter node keys are secp256k1 keys.
+pub(super) fn new_verifier() -> Box<dyn Verifier> {
+    Secp256k1Context::new().new_verifier()
+}
+
+fn rejected(node: &Node, err: NodeSignatureError) -> RegistryError {
+    RegistryError::InvalidStateError(InvalidStateError::with_message(format!(
+        "Node {} was rejected: {}",
+        node.identity, err
+    )))
+}
+
+fn deletion_rejected(deletion: &NodeDeletion, err: NodeSignatureError) -> RegistryError {
+    RegistryError::InvalidStateError(InvalidStateError::with_message(format!(
+        "Deletion of node {} was rejected: {}",
+        deletion.identity, err
+    )))
+}
+
+fn push_count(bytes: &mut Vec<u8>, count: usize) {
+    push_u64(bytes, count as u64);
+}
+
+fn push_u64(bytes: &mut Vec<u8>, value: u64) {
+    bytes.extend_from_slice(&value.to_be_bytes());
+}
+
+fn push_field(bytes: &mut Vec<u8>, field: &str) {
+    push_count(bytes, field.len());
+    bytes.extend_from_slice(field.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cylinder::PrivateKey;
+
+    fn new_key() -> (PrivateKey, String) {
+        let context = Secp256k1Context::new();
+        let private_key = context.new_random_private_key();
+        let public_key = context
+            .get_public_key(&private_key)
+            .expect("Failed to get public key")
+            .as_hex();
+        (private_key, public_key)
+    }
+
+    fn new_signer(private_key: PrivateKey) -> Box<dyn Signer> {
+        Secp256k1Context::new().new_signer(private_key)
+    }
+
+    fn new_node(keys: &[&str], endpoint: &str) -> Node {
+        new_node_with_version(keys, endpoint, 0)
+    }
+
+    fn new_node_with_version(keys: &[&str], endpoint: &str, version: u64) -> Node {
+        Node::builder("node-1")
+            .with_endpoint(endpoint)
+            .with_version(version)
+            .with_keys(keys.iter().map(|key| key.to_string()).collect::<Vec<_>>())
+            .with_metadata("company", "Bitwise IO")
+            .build()
+            .expect("Failed to build node")
+    }
+
+    /// Verify that a node signed by one of its keys has a valid signature, that the signature
+    /// does not depend on the order of the node's keys, and that changing any field of the node
+    /// invalidates the signature.
+    #[test]
+    fn sign_and_verify() {
+        let (private_key, public_key) = new_key();
+        let (_, other_key) = new_key();
+        let verifier = new_verifier();
+
+        let node = new_node(&[&public_key, &other_key], "tcps://localhost:8044")
+            .sign(&*new_signer(private_key))
+            .expect("Failed to sign node");
+        assert!(node.verify_signature(&*verifier).is_ok());
+
+        let mut reordered = node.clone();
+        reordered.keys.reverse();
+        assert!(reordered.verify_signature(&*verifier).is_ok());
+
+        let mut tampered = node.clone();
+        tampered.endpoints = vec!["tcps://attacker:8044".into()];
+        assert!(matches!(
+            tampered.verify_signature(&*verifier),
+            Err(NodeSignatureError::InvalidSignature)
+        ));
+
+        let mut bumped = node.clone();
+        bumped.version += 1;
+        assert!(matches!(
+            bumped.verify_signature(&*verifier),
+            Err(NodeSignatureError::InvalidSignature)
+        ));
+
+        let mut unsigned = node;
+        unsigned.signature = None;
+        assert!(matches!(
+            unsigned.verify_signature(&*verifier),
+            Err(NodeSignatureError::Unsigned)
+        ));
+    }
+
+    /// Verify that a node cannot be signed by a key that is not one of its keys.
+    #[test]
+    fn sign_with_unknown_key() {
+        let (private_key, _) = new_key();
+        let (_, other_key) = new_key();
+
+        assert!(matches!(
+            new_node(&[&other_key], "tcps://localhost:8044").sign(&*new_signer(private_key)),
+            Err(NodeSignatureError::UnknownSigningKey(_))
+        ));
+    }
+
+    /// Verify the rules for replacing a node:
+    ///
+    /// * An unsigned node may be replaced by an unsigned node
+    /// * A signed node may not be replaced by an unsigned node
+    /// * An update signed by a key of the previous version may rotate the node's other keys, if it
+    ///   has a greater version
+    /// * An update signed only by a new key is rejected
+    /// * A signed update with the same or an older version is rejected, so an old signed version
+    ///   of the node cannot be replayed
+    #[test]
+    fn node_update_rules() {
+        let (key_1, public_key_1) = new_key();
+        let (key_2, public_key_2) = new_key();
+        let (_, public_key_3) = new_key();
+        let signer_1 = new_signer(key_1);
+
+        let unsigned = new_node(&[&public_key_1], "tcps://localhost:8044");
+        assert!(check_node_update(&unsigned, &unsigned).is_ok());
+
+        let signed = new_node(&[&public_key_1], "tcps://localhost:8044")
+            .sign(&*signer_1)
+            .expect("Failed to sign node");
+        assert!(check_new_node(&signed, None).is_ok());
+        assert!(check_node_update(&signed, &unsigned).is_err());
+
+        let rotated =
+            new_node_with_version(&[&public_key_1, &public_key_3], "tcps://localhost:8045", 1)
+                .sign(&*signer_1)
+                .expect("Failed to sign node");
+        assert!(check_node_update(&signed, &rotated).is_ok());
+        assert!(check_node_update(&rotated, &signed).is_err());
+
+        let same_version = new_node(&[&public_key_1, &public_key_3], "tcps://localhost:8045")
+            .sign(&*signer_1)
+            .expect("Failed to sign node");
+        assert!(check_node_update(&signed, &same_version).is_err());
+
+        let hijacked = new_node_with_version(&[&public_key_2], "tcps://attacker:8044", 1)
+            .sign(&*new_signer(key_2))
+            .expect("Failed to sign node");
+        assert!(check_new_node(&hijacked, None).is_ok());
+        assert!(check_node_update(&signed, &hijacked).is_err());
+    }
+
+    /// Verify the rules for deleting a node and adding it again:
+    ///
+    /// * An unsigned node may be deleted without a signed deletion, but a signed node may not
+    /// * A deletion signed by one of the node's keys, with a greater version, is accepted
+    /// * A deletion signed by another key, or with an outdated version, is rejected
+    /// * After a signed deletion, the identity may only be added again by a signed node with a
+    ///   greater version than the deletion
+    #[test]
+    fn node_deletion_rules() {
+        let (key_1, public_key_1) = new_key();
+        let (key_2, public_key_2) = new_key();
+        let signer_1 = new_signer(key_1);
+        let signer_2 = new_signer(key_2);
+
+        let unsigned = new_node(&[&public_key_1], "tcps://localhost:8044");
+        assert!(check_unsigned_deletion(&unsigned).is_ok());
+
+        let signed = new_node_with_version(&[&public_key_1], "tcps://localhost:8044", 3)
+            .sign(&*signer_1)
+            .expect("Failed to sign node");
+        assert!(check_unsigned_deletion(&signed).is_err());
+
+        let deletion = NodeDeletion::sign(&signed, &*signer_1).expect("Failed to sign deletion");
+        assert_eq!(deletion.version(), 4);
+        assert!(check_node_deletion(&signed, &deletion).is_ok());
+
+        assert!(matches!(
+            NodeDeletion::sign(&signed, &*signer_2),
+            Err(NodeSignatureError::UnknownSigningKey(_))
+        ));
+        let other_owner = new_node_with_version(&[&public_key_2], "tcps://attacker:8044", 3);
+        let forged = NodeDeletion::sign(&other_owner, &*signer_2).expect("Failed to sign deletion");
+        assert!(check_node_deletion(&signed, &forged).is_err());
+        let replayed = NodeDeletion::new("node-1", 5, deletion.signature());
+        assert!(check_node_deletion(&signed, &replayed).is_err());
+
+        let newer = new_node_with_version(&[&public_key_1], "tcps://localhost:8044", 4)
+            .sign(&*signer_1)
+            .expect("Failed to sign node");
+        assert!(check_node_deletion(&newer, &deletion).is_err());
+
+        assert!(check_new_node(&unsigned, Some(deletion.version())).is_err());
+        assert!(check_new_node(&signed, Some(deletion.version())).is_err());
+        assert!(check_new_node(&newer, Some(deletion.version())).is_err());
+        let readded = new_node_with_version(&[&public_key_1], "tcps://localhost:8044", 5)
+            .sign(&*signer_1)
+            .expect("Failed to sign node");
+        assert!(check_new_node(&readded, Some(deletion.version())).is_ok());
+    }
+
+    /// Verify that each signature policy accepts and rejects the expected nodes.
+    #[test]
+    fn signature_policy() {
+        let (private_key, public_key) = new_key();
+        let verifier = new_verifier();
+
+        let unsigned = new_node(&[&public_key], "tcps://localhost:8044");
+        let signed = unsigned
+            .clone()
+            .sign(&*new_signer(private_key))
+            .expect("Failed to sign node");
+        let mut invalid = signed.clone();
+        invalid.display_name = "Changed".into();
+
+        let policy = NodeSignaturePolicy::AcceptAll;
+        assert!(policy.accepts(&unsigned, &*verifier));
+        assert!(policy.accepts(&invalid, &*verifier));
+
+        let policy = NodeSignaturePolicy::RejectInvalid;
+        assert!(policy.accepts(&unsigned, &*verifier));
+        assert!(policy.accepts(&signed, &*verifier));
+        assert!(!policy.accepts(&invalid, &*verifier));
+
+        let policy = NodeSignaturePolicy::RequireValid;
+        assert!(!policy.accepts(&unsigned, &*verifier));
+        assert!(policy.accepts(&signed, &*verifier));
+        assert!(!policy.accepts(&invalid, &*verifier));
+    }
+}
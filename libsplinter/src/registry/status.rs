@@ -0,0 +1,51 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Provides the live connection status of the nodes in a registry.
+
+use crate::error::InternalError;
+use crate::peer::{PeerConnectionStatus, PeerManagerConnector};
+
+/// Provides the live connection status of nodes, keyed by node identity.
+///
+/// Unlike the data stored in a registry, a node's connection status is not static; it reflects
+/// the local node's current view of the network.
+pub trait NodeStatusReader: Send {
+    /// Returns the connection status of each node that the local node is connected, or is
+    /// attempting to connect, to. The `peer_id` of each status is the identity of the node.
+    ///
+    /// Nodes that the local node is not connected to will not have a status.
+    fn list_node_statuses(&self) -> Result<Vec<PeerConnectionStatus>, InternalError>;
+
+    /// Clone implementation for `NodeStatusReader`. The implementation of the `Clone` trait for
+    /// `Box<dyn NodeStatusReader>` calls this method.
+    fn clone_box(&self) -> Box<dyn NodeStatusReader>;
+}
+
+impl Clone for Box<dyn NodeStatusReader> {
+    fn clone(&self) -> Box<dyn NodeStatusReader> {
+        self.clone_box()
+    }
+}
+
+impl NodeStatusReader for PeerManagerConnector {
+    fn list_node_statuses(&self) -> Result<Vec<PeerConnectionStatus>, InternalError> {
+        self.list_peer_statuses()
+            .map_err(|err| InternalError::from_source(Box::new(err)))
+    }
+
+    fn clone_box(&self) -> Box<dyn NodeStatusReader> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,333 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Notifications of changes to the nodes in a registry.
+//!
+//! A [`RegistrySubscriber`] is added to a registry with
+//! [`RegistryReader::add_subscriber`], and receives a [`RegistryEvent`] each time a node is added
+//! to, updated in, or removed from the registry.
+//!
+//! [`RegistryEvent`]: enum.RegistryEvent.html
+//! [`RegistryReader::add_subscriber`]: trait.RegistryReader.html#method.add_subscriber
+//! [`RegistrySubscriber`]: trait.RegistrySubscriber.html
+
+use std::sync::{Arc, Mutex};
+
+use crate::error::InternalError;
+
+use super::{Node, RegistryError, RegistrySubscriberError};
+
+/// A change to the nodes in a registry.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RegistryEvent {
+    /// The node was added to the registry
+    NodeAdded(Node),
+    /// The node was replaced; the event holds the new version of the node
+    NodeUpdated(Node),
+    /// The node was removed from the registry; the event holds the removed node
+    NodeRemoved(Node),
+}
+
+impl RegistryEvent {
+    /// Returns the node the event is about.
+    pub fn node(&self) -> &Node {
+        match self {
+            RegistryEvent::NodeAdded(node)
+            | RegistryEvent::NodeUpdated(node)
+            | RegistryEvent::NodeRemoved(node) => node,
+        }
+    }
+}
+
+/// Receives the changes made to a registry.
+pub trait RegistrySubscriber: Send {
+    /// Handles a change to the registry. Returning `RegistrySubscriberError::Unsubscribe` removes
+    /// the subscriber from the registry.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The change that was made to the registry
+    fn handle_event(&self, event: &RegistryEvent) -> Result<(), RegistrySubscriberError>;
+}
+
+/// The subscribers of a registry, along with the events that have not yet been sent to them.
+///
+/// Registries queue events while they hold their internal locks and call `notify` once the locks
+/// have been released, so that subscribers are free to read the registry while handling an event.
+/// Only one thread sends events at a time; if `notify` is called while events are being sent,
+/// including by a subscriber, the queued events are sent by the thread that is already sending
+/// them. This keeps events in the order they were queued.
+#[derive(Clone, Default)]
+pub(super) struct Subscribers {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    subscribers: Vec<Box<dyn RegistrySubscriber>>,
+    pending: Vec<RegistryEvent>,
+    notifying: bool,
+}
+
+impl Subscribers {
+    /// Adds a subscriber, which will be sent the events queued from now on.
+    pub fn add(&self, subscriber: Box<dyn RegistrySubscriber>) -> Result<(), RegistryError> {
+        self.state
+            .lock()
+            .map_err(|_| {
+                RegistryError::InternalError(InternalError::with_message(
+                    "Registry subscribers lock poisoned".into(),
+                ))
+            })?
+            .subscribers
+            .push(subscriber);
+        Ok(())
+    }
+
+    /// Queues events to be sent by the next call to `notify`. Events are dropped if there are no
+    /// subscribers.
+    pub fn queue(&self, events: Vec<RegistryEvent>) {
+        if events.is_empty() {
+            return;
+        }
+        match self.state.lock() {
+            Ok(mut state) => {
+                if !state.subscribers.is_empty() || state.notifying {
+                    state.pending.extend(events)
+                }
+            }
+            Err(_) => error!("Registry subscribers lock poisoned; dropping registry events"),
+        }
+    }
+
+    /// Sends all queued events to the subscribers.
+    pub fn notify(&self) {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(_) => {
+                error!("Registry subscribers lock poisoned; unable to send registry events");
+                return;
+            }
+        };
+        if state.notifying {
+            return;
+        }
+        state.notifying = true;
+
+        loop {
+            if state.pending.is_empty() {
+                state.notifying = false;
+                return;
+            }
+            let events = std::mem::take(&mut state.pending);
+            let mut subscribers = std::mem::take(&mut state.subscribers);
+            drop(state);
+
+            subscribers.retain(|subscriber| {
+                events
+                    .iter()
+                    .all(|event| match subscriber.handle_event(event) {
+                        Ok(()) => true,
+                        Err(RegistrySubscriberError::Unsubscribe) => false,
+                        Err(err) => {
+                            warn!("Registry subscriber failed to handle event: {}", err);
+                            true
+                        }
+                    })
+            });
+
+            state = match self.state.lock() {
+                Ok(state) => state,
+                Err(_) => {
+                    error!("Registry subscribers lock poisoned; unable to send registry events");
+                    return;
+                }
+            };
+            // Keep any subscribers that were added while the events were being sent
+            subscribers.append(&mut state.subscribers);
+            state.subscribers = subscribers;
+        }
+    }
+
+    /// Queues the given events and sends them to the subscribers. Must not be called while
+    /// holding a lock that subscribers may need.
+    pub fn publish(&self, events: Vec<RegistryEvent>) {
+        self.queue(events);
+        self.notify();
+    }
+
+    /// Returns a subscriber that queues the events it receives, so the events of another registry
+    /// can be passed on to these subscribers.
+    #[cfg(feature = "registry-remote")]
+    pub fn forwarder(&self) -> Box<dyn RegistrySubscriber> {
+        Box::new(Forwarder {
+            subscribers: self.clone(),
+        })
+    }
+}
+
+#[cfg(feature = "registry-remote")]
+struct Forwarder {
+    subscribers: Subscribers,
+}
+
+#[cfg(feature = "registry-remote")]
+impl RegistrySubscriber for Forwarder {
+    fn handle_event(&self, event: &RegistryEvent) -> Result<(), RegistrySubscriberError> {
+        self.subscribers.queue(vec![event.clone()]);
+        Ok(())
+    }
+}
+
+/// Returns the events that turn the `previous` list of nodes into the `current` one. Removals
+/// come first, followed by additions and updates in the order of `current`.
+pub(super) fn diff_nodes(previous: &[Node], current: &[Node]) -> Vec<RegistryEvent> {
+    let mut events = previous
+        .iter()
+        .filter(|node| !current.iter().any(|n| n.identity == node.identity))
+        .cloned()
+        .map(RegistryEvent::NodeRemoved)
+        .collect::<Vec<_>>();
+
+    for node in current {
+        match previous.iter().find(|n| n.identity == node.identity) {
+            None => events.push(RegistryEvent::NodeAdded(node.clone())),
+            Some(previous_node) if previous_node != node => {
+                events.push(RegistryEvent::NodeUpdated(node.clone()))
+            }
+            Some(_) => (),
+        }
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::mpsc::{channel, Sender};
+
+    fn new_node(id: &str, endpoint: &str) -> Node {
+        Node::builder(id)
+            .with_endpoint(endpoint)
+            .with_key("abcd")
+            .build()
+            .expect("Failed to build node")
+    }
+
+    struct ChannelSubscriber {
+        sender: Mutex<Sender<RegistryEvent>>,
+        limit: usize,
+        received: Mutex<usize>,
+    }
+
+    impl ChannelSubscriber {
+        fn new(sender: Sender<RegistryEvent>, limit: usize) -> Box<Self> {
+            Box::new(ChannelSubscriber {
+                sender: Mutex::new(sender),
+                limit,
+                received: Mutex::new(0),
+            })
+        }
+    }
+
+    impl RegistrySubscriber for ChannelSubscriber {
+        fn handle_event(&self, event: &RegistryEvent) -> Result<(), RegistrySubscriberError> {
+            let mut received = self.received.lock().expect("lock poisoned");
+            if *received == self.limit {
+                return Err(RegistrySubscriberError::Unsubscribe);
+            }
+            *received += 1;
+            self.sender
+                .lock()
+                .expect("lock poisoned")
+                .send(event.clone())
+                .map_err(|_| RegistrySubscriberError::Unsubscribe)
+        }
+    }
+
+    /// Verify that `diff_nodes` reports removed, added, and updated nodes, and ignores nodes that
+    /// did not change.
+    #[test]
+    fn diff() {
+        let node_1 = new_node("node-1", "tcps://localhost:8044");
+        let node_2 = new_node("node-2", "tcps://localhost:8045");
+        let node_2_updated = new_node("node-2", "tcps://localhost:8046");
+        let node_3 = new_node("node-3", "tcps://localhost:8047");
+
+        let events = diff_nodes(
+            &[node_1.clone(), node_2, node_3.clone()],
+            &[
+                node_3,
+                node_2_updated.clone(),
+                new_node("node-4", "tcp://a:1"),
+            ],
+        );
+
+        assert_eq!(
+            events,
+            vec![
+                RegistryEvent::NodeRemoved(node_1),
+                RegistryEvent::NodeUpdated(node_2_updated),
+                RegistryEvent::NodeAdded(new_node("node-4", "tcp://a:1")),
+            ]
+        );
+    }
+
+    /// Verify that queued events are sent in order to every subscriber, that events queued
+    /// without subscribers are dropped, and that a subscriber is removed once it unsubscribes.
+    #[test]
+    fn notify_and_unsubscribe() {
+        let subscribers = Subscribers::default();
+        let node = new_node("node-1", "tcps://localhost:8044");
+
+        subscribers.publish(vec![RegistryEvent::NodeAdded(node.clone())]);
+
+        let (sender_1, receiver_1) = channel();
+        let (sender_2, receiver_2) = channel();
+        subscribers
+            .add(ChannelSubscriber::new(sender_1, usize::MAX))
+            .expect("Failed to add subscriber");
+        subscribers
+            .add(ChannelSubscriber::new(sender_2, 1))
+            .expect("Failed to add subscriber");
+
+        subscribers.queue(vec![RegistryEvent::NodeUpdated(node.clone())]);
+        subscribers.publish(vec![RegistryEvent::NodeRemoved(node.clone())]);
+        subscribers.publish(vec![RegistryEvent::NodeAdded(node.clone())]);
+
+        assert_eq!(
+            receiver_1.try_iter().collect::<Vec<_>>(),
+            vec![
+                RegistryEvent::NodeUpdated(node.clone()),
+                RegistryEvent::NodeRemoved(node.clone()),
+                RegistryEvent::NodeAdded(node.clone()),
+            ]
+        );
+        assert_eq!(
+            receiver_2.try_iter().collect::<Vec<_>>(),
+            vec![RegistryEvent::NodeUpdated(node)]
+        );
+        assert_eq!(
+            subscribers
+                .state
+                .lock()
+                .expect("lock poisoned")
+                .subscribers
+                .len(),
+            1
+        );
+    }
+}
@@ -21,11 +21,19 @@
 //! [`RwRegistry`]: ../trait.RwRegistry.html
 
 use std::collections::HashMap;
+#[cfg(feature = "registry-subscriptions")]
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+#[cfg(feature = "registry-subscriptions")]
+use std::sync::Mutex;
 
+#[cfg(feature = "registry-signed-nodes")]
+use super::NodeDeletion;
 use super::{
     MetadataPredicate, Node, NodeIter, RegistryError, RegistryReader, RegistryWriter, RwRegistry,
 };
+#[cfg(feature = "registry-subscriptions")]
+use super::{RegistryEvent, RegistrySubscriber, RegistrySubscriberError};
 
 /// A registry with multiple sources.
 ///
@@ -61,6 +69,13 @@ use super::{
 /// If the same metadata key is set for the node in different registires, the value for that key
 /// from the highest-precedence registry will be used.
 ///
+/// # Subscriptions
+///
+/// A subscriber is added to every source registry that supports subscriptions, and is notified
+/// of changes to the unified view: a change to a node in any source is reported with the node as
+/// it is now provided by the `UnifiedRegistry`, and a node is only reported as added or removed
+/// when it is added to the first source or removed from the last one.
+///
 /// [`RegistryReader`]: ../trait.RegistryReader.html
 /// [`RegistryWriter`]: ../trait.RegistryWriter.html
 /// [`RwRegistry`]: ../trait.RwRegistry.html
@@ -108,6 +123,19 @@ impl UnifiedRegistry {
                 .flatten(),
         )
     }
+
+    /// Counts the source registries that have the node with the given identity.
+    #[cfg(feature = "registry-subscriptions")]
+    fn count_sources_with_node(&self, identity: &str) -> usize {
+        std::iter::once(self.internal_source.has_node(identity))
+            .chain(
+                self.external_sources
+                    .iter()
+                    .map(|source| source.has_node(identity)),
+            )
+            .filter(|res| *res.as_ref().unwrap_or(&false))
+            .count()
+    }
 }
 
 impl RegistryReader for UnifiedRegistry {
@@ -197,6 +225,76 @@ impl RegistryReader for UnifiedRegistry {
                 })
             }))
     }
+
+    #[cfg(feature = "registry-subscriptions")]
+    fn add_subscriber(&self, subscriber: Box<dyn RegistrySubscriber>) -> Result<(), RegistryError> {
+        let subscriber = Arc::new(Mutex::new(subscriber));
+        let unsubscribed = Arc::new(AtomicBool::new(false));
+        let new_source_subscriber = || {
+            Box::new(SourceSubscriber {
+                registry: self.clone(),
+                subscriber: subscriber.clone(),
+                unsubscribed: unsubscribed.clone(),
+            })
+        };
+
+        self.internal_source
+            .add_subscriber(new_source_subscriber())?;
+
+        for source in &self.external_sources {
+            if let Err(err) = source.add_subscriber(new_source_subscriber()) {
+                debug!("Failed to subscribe to source registry: {}", err);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Subscribed to each source of a `UnifiedRegistry`; turns the events of a source into events for
+/// the unified view of the registry.
+#[cfg(feature = "registry-subscriptions")]
+struct SourceSubscriber {
+    registry: UnifiedRegistry,
+    subscriber: Arc<Mutex<Box<dyn RegistrySubscriber>>>,
+    /// Set once the subscriber unsubscribes, so it is removed from all sources
+    unsubscribed: Arc<AtomicBool>,
+}
+
+#[cfg(feature = "registry-subscriptions")]
+impl RegistrySubscriber for SourceSubscriber {
+    fn handle_event(&self, event: &RegistryEvent) -> Result<(), RegistrySubscriberError> {
+        if self.unsubscribed.load(Ordering::SeqCst) {
+            return Err(RegistrySubscriberError::Unsubscribe);
+        }
+
+        let identity = &event.node().identity;
+        let node = self
+            .registry
+            .get_node(identity)
+            .map_err(|err| RegistrySubscriberError::UnableToHandleEvent(err.to_string()))?;
+        let event = match (event, node) {
+            (_, None) => RegistryEvent::NodeRemoved(event.node().clone()),
+            (RegistryEvent::NodeAdded(_), Some(node))
+                if self.registry.count_sources_with_node(identity) == 1 =>
+            {
+                RegistryEvent::NodeAdded(node)
+            }
+            (_, Some(node)) => RegistryEvent::NodeUpdated(node),
+        };
+
+        let result = self
+            .subscriber
+            .lock()
+            .map_err(|_| {
+                RegistrySubscriberError::UnableToHandleEvent("Subscriber lock poisoned".into())
+            })?
+            .handle_event(&event);
+        if let Err(RegistrySubscriberError::Unsubscribe) = result {
+            self.unsubscribed.store(true, Ordering::SeqCst);
+        }
+        result
+    }
 }
 
 impl RegistryWriter for UnifiedRegistry {
@@ -211,6 +309,11 @@ impl RegistryWriter for UnifiedRegistry {
     fn delete_node(&self, identity: &str) -> Result<Option<Node>, RegistryError> {
         self.internal_source.delete_node(identity)
     }
+
+    #[cfg(feature = "registry-signed-nodes")]
+    fn delete_signed_node(&self, deletion: &NodeDeletion) -> Result<Option<Node>, RegistryError> {
+        self.internal_source.delete_signed_node(deletion)
+    }
 }
 
 impl RwRegistry for UnifiedRegistry {
@@ -231,10 +334,14 @@ impl RwRegistry for UnifiedRegistry {
 mod test {
     use std::collections::HashMap;
     use std::iter::FromIterator;
+    #[cfg(feature = "registry-subscriptions")]
+    use std::sync::mpsc::{channel, Sender};
     use std::sync::{Arc, Mutex};
 
     use super::*;
     use crate::error::InvalidStateError;
+    #[cfg(feature = "registry-subscriptions")]
+    use crate::registry::LocalYamlRegistry;
 
     fn new_node(id: &str, endpoint: &str, metadata: &[(&str, &str)]) -> Node {
         let mut builder = Node::builder(id).with_endpoint(endpoint).with_key("abcd");
@@ -659,6 +766,86 @@ mod test {
             .expect("Unable to check writeable for node1"));
     }
 
+    /// Verify that a subscriber of the unified registry is notified of changes to the unified
+    /// view of its sources:
+    ///
+    /// * Adding a node to a read-only source reports the node as added
+    /// * Adding the same node to the internal source reports it as updated, with merged metadata
+    /// * Removing the node from the internal source reports it as updated, since it is still in
+    ///   the read-only source
+    /// * Removing the node from the read-only source reports it as removed
+    #[cfg(feature = "registry-subscriptions")]
+    #[test]
+    fn subscribe() {
+        let temp_dir = tempdir::TempDir::new("unified_subscribe").expect("Failed to create dir");
+        let path = |name: &str| {
+            temp_dir
+                .path()
+                .join(name)
+                .to_str()
+                .expect("Invalid path")
+                .to_string()
+        };
+        let writeable = LocalYamlRegistry::new(&path("writeable.yaml"))
+            .expect("Failed to create writeable registry");
+        let readable = LocalYamlRegistry::new(&path("readable.yaml"))
+            .expect("Failed to create readable registry");
+
+        let unified = UnifiedRegistry::new(
+            Box::new(writeable.clone()),
+            vec![Box::new(readable.clone())],
+        );
+        let (sender, receiver) = channel();
+        unified
+            .add_subscriber(Box::new(ChannelSubscriber(Mutex::new(sender))))
+            .expect("Failed to subscribe");
+
+        let external = new_node("node1", "endpoint1", &[("meta_a", "val_a")]);
+        let internal = new_node("node1", "endpoint2", &[("meta_b", "val_b")]);
+        let merged = new_node(
+            "node1",
+            "endpoint2",
+            &[("meta_a", "val_a"), ("meta_b", "val_b")],
+        );
+
+        readable
+            .add_node(external.clone())
+            .expect("Unable to add node to readable");
+        unified
+            .add_node(internal)
+            .expect("Unable to add node to unified");
+        unified
+            .delete_node("node1")
+            .expect("Unable to delete node from unified");
+        readable
+            .delete_node("node1")
+            .expect("Unable to delete node from readable");
+
+        assert_eq!(
+            receiver.try_iter().collect::<Vec<_>>(),
+            vec![
+                RegistryEvent::NodeAdded(external.clone()),
+                RegistryEvent::NodeUpdated(merged),
+                RegistryEvent::NodeUpdated(external.clone()),
+                RegistryEvent::NodeRemoved(external),
+            ]
+        );
+    }
+
+    #[cfg(feature = "registry-subscriptions")]
+    struct ChannelSubscriber(Mutex<Sender<RegistryEvent>>);
+
+    #[cfg(feature = "registry-subscriptions")]
+    impl RegistrySubscriber for ChannelSubscriber {
+        fn handle_event(&self, event: &RegistryEvent) -> Result<(), RegistrySubscriberError> {
+            self.0
+                .lock()
+                .expect("lock poisoned")
+                .send(event.clone())
+                .map_err(|_| RegistrySubscriberError::Unsubscribe)
+        }
+    }
+
     #[derive(Clone, Default)]
     struct MemRegistry {
         nodes: Arc<Mutex<HashMap<String, Node>>>,
@@ -20,6 +20,8 @@
 //! [`LocalYamlRegistry`]: struct.LocalYamlRegistry.html
 //! [`RwRegistry`]: ../../trait.RwRegistry.html
 
+#[cfg(feature = "registry-signed-nodes")]
+use std::collections::BTreeMap;
 use std::convert::TryFrom;
 use std::fs::File;
 use std::io::Write;
@@ -34,6 +36,17 @@ use crate::registry::{
 
 use crate::error::{InternalError, InvalidStateError};
 
+#[cfg(feature = "registry-signed-nodes")]
+use crate::registry::{
+    signature::{check_new_node, check_node_deletion, check_node_update, check_unsigned_deletion},
+    NodeDeletion,
+};
+#[cfg(feature = "registry-subscriptions")]
+use crate::registry::{
+    subscriber::{diff_nodes, Subscribers},
+    RegistrySubscriber,
+};
+
 use super::YamlNode;
 
 /// A local, read/write registry.
@@ -51,10 +64,19 @@ use super::YamlNode;
 /// file already exists, the registry will attempt to load, parse, and validate it. If the backing
 /// file does not already exist, the registry will attempt to create it.
 ///
+/// Subscribers are notified of changes made through the registry, as well as changes to the
+/// backing file that are found when the registry is read.
+///
+/// When a signed node is deleted, the version of its deletion is recorded in a second YAML file
+/// next to the backing file, with `.deletions` appended to its name. The identity can then only be
+/// added again by a signed node with a greater version.
+///
 /// [`Node`]: struct.Node.html
 #[derive(Clone)]
 pub struct LocalYamlRegistry {
     internal: Arc<Mutex<Internal>>,
+    #[cfg(feature = "registry-subscriptions")]
+    subscribers: Subscribers,
 }
 
 impl LocalYamlRegistry {
@@ -67,14 +89,23 @@ impl LocalYamlRegistry {
     ///
     /// * `file_path` - The path of the backing YAML file.
     pub fn new(file_path: &str) -> Result<LocalYamlRegistry, RegistryError> {
+        #[cfg(feature = "registry-subscriptions")]
+        let subscribers = Subscribers::default();
+
         Ok(LocalYamlRegistry {
-            internal: Arc::new(Mutex::new(Internal::new(file_path)?)),
+            internal: Arc::new(Mutex::new(Internal::new(
+                file_path,
+                #[cfg(feature = "registry-subscriptions")]
+                subscribers.clone(),
+            )?)),
+            #[cfg(feature = "registry-subscriptions")]
+            subscribers,
         })
     }
 
     /// Get all nodes in the registry.
     pub(super) fn get_nodes(&self) -> Result<Vec<Node>, RegistryError> {
-        Ok(self
+        let nodes = self
             .internal
             .lock()
             .map_err(|_| {
@@ -82,19 +113,47 @@ impl LocalYamlRegistry {
                     "YAML registry's internal lock poisoned".into(),
                 ))
             })?
-            .get_nodes())
+            .get_nodes();
+
+        // The backing file may have changed since the last read
+        #[cfg(feature = "registry-subscriptions")]
+        self.subscribers.notify();
+
+        Ok(nodes)
     }
 
     /// Write the given list of nodes to the backing YAML file.
     pub(super) fn write_nodes(&self, nodes: Vec<Node>) -> Result<(), RegistryError> {
-        self.internal
+        let result = self
+            .internal
             .lock()
             .map_err(|_| {
                 RegistryError::InternalError(InternalError::with_message(
                     "YAML registry's internal lock poisoned".into(),
                 ))
             })?
-            .write_nodes(nodes)
+            .write_nodes(nodes);
+
+        #[cfg(feature = "registry-subscriptions")]
+        self.subscribers.notify();
+
+        result
+    }
+
+    /// Get the version of the last signed deletion of the node with the given identity, if any.
+    #[cfg(feature = "registry-signed-nodes")]
+    fn deleted_version(&self, identity: &str) -> Result<Option<u64>, RegistryError> {
+        Ok(self
+            .internal
+            .lock()
+            .map_err(|_| {
+                RegistryError::InternalError(InternalError::with_message(
+                    "YAML registry's internal lock poisoned".into(),
+                ))
+            })?
+            .deletions
+            .get(identity)
+            .copied())
     }
 }
 
@@ -130,10 +189,18 @@ impl RegistryReader for LocalYamlRegistry {
             .iter()
             .any(|node| node.identity == identity))
     }
+
+    #[cfg(feature = "registry-subscriptions")]
+    fn add_subscriber(&self, subscriber: Box<dyn RegistrySubscriber>) -> Result<(), RegistryError> {
+        self.subscribers.add(subscriber)
+    }
 }
 
 impl RegistryWriter for LocalYamlRegistry {
     fn add_node(&self, node: Node) -> Result<(), RegistryError> {
+        #[cfg(feature = "registry-signed-nodes")]
+        check_new_node(&node, self.deleted_version(&node.identity)?)?;
+
         let mut nodes = self.get_nodes()?;
         nodes.push(node);
         self.write_nodes(nodes)
@@ -141,7 +208,10 @@ impl RegistryWriter for LocalYamlRegistry {
 
     fn update_node(&self, node: Node) -> Result<(), RegistryError> {
         let mut nodes = self.get_nodes()?;
-        if nodes.iter().any(|n| n.identity == node.identity) {
+        if let Some(_previous) = nodes.iter().find(|n| n.identity == node.identity) {
+            #[cfg(feature = "registry-signed-nodes")]
+            check_node_update(_previous, &node)?;
+
             nodes.retain(|existing_node| existing_node.identity != node.identity);
             nodes.push(node);
             self.write_nodes(nodes)
@@ -164,12 +234,38 @@ impl RegistryWriter for LocalYamlRegistry {
                 break;
             }
         }
+
+        #[cfg(feature = "registry-signed-nodes")]
+        {
+            if let Some(i) = index {
+                check_unsigned_deletion(&nodes[i])?;
+            }
+        }
+
         let opt = index.map(|i| nodes.remove(i));
 
         self.write_nodes(nodes)?;
 
         Ok(opt)
     }
+
+    #[cfg(feature = "registry-signed-nodes")]
+    fn delete_signed_node(&self, deletion: &NodeDeletion) -> Result<Option<Node>, RegistryError> {
+        let result = self
+            .internal
+            .lock()
+            .map_err(|_| {
+                RegistryError::InternalError(InternalError::with_message(
+                    "YAML registry's internal lock poisoned".into(),
+                ))
+            })?
+            .delete_signed_node(deletion);
+
+        #[cfg(feature = "registry-subscriptions")]
+        self.subscribers.notify();
+
+        result
+    }
 }
 
 impl RwRegistry for LocalYamlRegistry {
@@ -191,14 +287,26 @@ struct Internal {
     file_path: String,
     cached_nodes: Vec<Node>,
     last_read: SystemTime,
+    /// The versions of the signed deletions, by node identity
+    #[cfg(feature = "registry-signed-nodes")]
+    deletions: BTreeMap<String, u64>,
+    #[cfg(feature = "registry-subscriptions")]
+    subscribers: Subscribers,
 }
 
 impl Internal {
-    fn new(file_path: &str) -> Result<Self, RegistryError> {
+    fn new(
+        file_path: &str,
+        #[cfg(feature = "registry-subscriptions")] subscribers: Subscribers,
+    ) -> Result<Self, RegistryError> {
         let mut internal = Self {
             file_path: file_path.into(),
             cached_nodes: vec![],
             last_read: SystemTime::UNIX_EPOCH,
+            #[cfg(feature = "registry-signed-nodes")]
+            deletions: BTreeMap::new(),
+            #[cfg(feature = "registry-subscriptions")]
+            subscribers,
         };
 
         // If file already exists, read it; otherwise initialize it.
@@ -208,9 +316,88 @@ impl Internal {
             internal.write_nodes(vec![])?;
         }
 
+        #[cfg(feature = "registry-signed-nodes")]
+        internal.read_deletions()?;
+
         Ok(internal)
     }
 
+    /// Delete a signed node, after recording the deletion so the node cannot be added again
+    /// without a newer signature.
+    #[cfg(feature = "registry-signed-nodes")]
+    fn delete_signed_node(
+        &mut self,
+        deletion: &NodeDeletion,
+    ) -> Result<Option<Node>, RegistryError> {
+        let mut nodes = self.get_nodes();
+        let index = match nodes
+            .iter()
+            .position(|node| node.identity == deletion.identity())
+        {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        check_node_deletion(&nodes[index], deletion)?;
+
+        let node = nodes.remove(index);
+        self.deletions
+            .insert(deletion.identity().to_string(), deletion.version());
+        self.write_deletions()?;
+        self.write_nodes(nodes)?;
+
+        Ok(Some(node))
+    }
+
+    /// The path of the file that the signed deletions are recorded in.
+    #[cfg(feature = "registry-signed-nodes")]
+    fn deletions_path(&self) -> String {
+        format!("{}.deletions", self.file_path)
+    }
+
+    /// Read the recorded signed deletions, if there are any.
+    #[cfg(feature = "registry-signed-nodes")]
+    fn read_deletions(&mut self) -> Result<(), RegistryError> {
+        let path = self.deletions_path();
+        if !PathBuf::from(&path).is_file() {
+            return Ok(());
+        }
+
+        let file = File::open(&path).map_err(|err| {
+            RegistryError::InternalError(InternalError::from_source_with_message(
+                Box::new(err),
+                format!("Failed to open YAML registry deletions file '{}'", path),
+            ))
+        })?;
+
+        self.deletions = serde_yaml::from_reader(&file).map_err(|err| {
+            RegistryError::InternalError(InternalError::from_source_with_message(
+                Box::new(err),
+                format!("Failed to read YAML registry deletions file '{}'", path),
+            ))
+        })?;
+
+        Ok(())
+    }
+
+    /// Write the recorded signed deletions.
+    #[cfg(feature = "registry-signed-nodes")]
+    fn write_deletions(&self) -> Result<(), RegistryError> {
+        let path = self.deletions_path();
+        let output = serde_yaml::to_vec(&self.deletions).map_err(|err| {
+            RegistryError::InternalError(InternalError::from_source_with_message(
+                Box::new(err),
+                "Failed to write node deletions to YAML".into(),
+            ))
+        })?;
+
+        std::fs::write(&path, &output).map_err(|err| {
+            RegistryError::InternalError(InternalError::from_source_with_message(
+                Box::new(err),
+                format!("Failed to write YAML registry deletions file '{}'", path),
+            ))
+        })
+    }
+
     /// Get the internal list of nodes. If the backing file has been modified since the last read,
     /// attempt to refresh the cache.
     fn get_nodes(&mut self) -> Vec<Node> {
@@ -273,6 +460,10 @@ impl Internal {
             RegistryError::InvalidStateError(InvalidStateError::with_message(err.to_string()))
         })?;
 
+        #[cfg(feature = "registry-subscriptions")]
+        self.subscribers
+            .queue(diff_nodes(&self.cached_nodes, &nodes));
+
         self.cached_nodes = nodes;
         self.last_read = SystemTime::now();
 
@@ -320,6 +511,10 @@ impl Internal {
             ))
         })?;
 
+        #[cfg(feature = "registry-subscriptions")]
+        self.subscribers
+            .queue(diff_nodes(&self.cached_nodes, &nodes));
+
         self.cached_nodes = nodes;
         self.last_read = SystemTime::now();
 
@@ -1171,6 +1366,72 @@ mod test {
         }
     }
 
+    ///
+    /// Verifies that a signed node can only be deleted by a signed deletion, and that the deletion
+    /// is remembered across restarts: the node can then only be added again by a signed node with
+    /// a greater version, not by an unsigned node or a replay of the deleted node.
+    ///
+    #[cfg(feature = "registry-signed-nodes")]
+    #[test]
+    fn test_delete_signed_node() {
+        use cylinder::{secp256k1::Secp256k1Context, Context};
+
+        let temp_dir = TempDir::new("test_delete_signed_node").expect("Failed to create temp dir");
+        let path = temp_dir
+            .path()
+            .join("registry.yaml")
+            .to_str()
+            .expect("Failed to get path")
+            .to_string();
+
+        let context = Secp256k1Context::new();
+        let private_key = context.new_random_private_key();
+        let public_key = context
+            .get_public_key(&private_key)
+            .expect("Failed to get public key")
+            .as_hex();
+        let signer = context.new_signer(private_key);
+        let signed_node = |version| {
+            Node::builder("Node-123")
+                .with_endpoint("tcps://12.0.0.123:8431")
+                .with_key(public_key.clone())
+                .with_version(version)
+                .build()
+                .expect("Failed to build node")
+                .sign(&*signer)
+                .expect("Failed to sign node")
+        };
+
+        let registry = LocalYamlRegistry::new(&path).expect("Failed to create LocalYamlRegistry");
+        registry
+            .add_node(signed_node(1))
+            .expect("Failed to add node");
+
+        assert!(registry.delete_node("Node-123").is_err());
+
+        let deletion =
+            NodeDeletion::sign(&signed_node(1), &*signer).expect("Failed to sign deletion");
+        assert_eq!(
+            registry
+                .delete_signed_node(&deletion)
+                .expect("Failed to delete node"),
+            Some(signed_node(1))
+        );
+
+        let registry = LocalYamlRegistry::new(&path).expect("Failed to create LocalYamlRegistry");
+        let unsigned = Node::builder("Node-123")
+            .with_endpoint("tcps://12.0.0.123:8431")
+            .with_key(public_key.clone())
+            .build()
+            .expect("Failed to build node");
+        assert!(registry.add_node(unsigned).is_err());
+        assert!(registry.add_node(signed_node(1)).is_err());
+        assert!(registry.add_node(signed_node(2)).is_err());
+        registry
+            .add_node(signed_node(3))
+            .expect("Failed to add node");
+    }
+
     ///
     /// Verifies that if the YAML file does not exist on initialization, `LocalYamlRegistry` will
     /// create and initialize it as an empty registry.
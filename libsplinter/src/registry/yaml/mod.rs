@@ -44,6 +44,14 @@ pub struct YamlNode {
     keys: Vec<String>,
     /// A map with node metadata.
     metadata: HashMap<String, String>,
+    /// A hex-encoded signature of the node by one of its keys.
+    #[cfg(feature = "registry-signed-nodes")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    signature: Option<String>,
+    /// The version of the node; each signed update must increase it.
+    #[cfg(feature = "registry-signed-nodes")]
+    #[serde(default, skip_serializing_if = "is_unversioned")]
+    version: u64,
 }
 
 impl YamlNode {
@@ -73,6 +81,12 @@ impl YamlNode {
     }
 }
 
+/// Unversioned nodes are written without a version, so unsigned registry files are unchanged.
+#[cfg(feature = "registry-signed-nodes")]
+fn is_unversioned(version: &u64) -> bool {
+    *version == 0
+}
+
 impl From<Node> for YamlNode {
     fn from(node: Node) -> Self {
         YamlNode {
@@ -81,6 +95,10 @@ impl From<Node> for YamlNode {
             display_name: node.display_name().into(),
             keys: node.keys().into(),
             metadata: node.metadata().clone(),
+            #[cfg(feature = "registry-signed-nodes")]
+            signature: node.signature,
+            #[cfg(feature = "registry-signed-nodes")]
+            version: node.version,
         }
     }
 }
@@ -98,6 +116,14 @@ impl TryFrom<YamlNode> for Node {
             builder = builder.with_metadata(k, v);
         }
 
+        #[cfg(feature = "registry-signed-nodes")]
+        {
+            if let Some(signature) = node.signature {
+                builder = builder.with_signature(signature);
+            }
+            builder = builder.with_version(node.version);
+        }
+
         builder.build()
     }
 }
@@ -29,7 +29,13 @@ use std::sync::{
 use std::thread;
 use std::time::{Duration, Instant};
 
+#[cfg(feature = "registry-remote-signature")]
+use cylinder::{secp256k1::Secp256k1Context, Context, PublicKey, Signature};
 use openssl::hash::{hash, MessageDigest};
+use reqwest::{
+    header::{HeaderName, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+    StatusCode,
+};
 
 use crate::error::{InternalError, InvalidStateError};
 use crate::hex::to_hex;
@@ -39,6 +45,13 @@ use crate::registry::{
 };
 use crate::threading::lifecycle::ShutdownHandle;
 
+#[cfg(feature = "registry-remote-signature")]
+use crate::hex::parse_hex;
+#[cfg(feature = "registry-signed-nodes")]
+use crate::registry::{signature::new_verifier, NodeSignaturePolicy};
+#[cfg(feature = "registry-subscriptions")]
+use crate::registry::{subscriber::Subscribers, RegistrySubscriber};
+
 use super::{LocalYamlRegistry, YamlNode};
 
 /// A remote, read-only registry.
@@ -71,12 +84,25 @@ use super::{LocalYamlRegistry, YamlNode};
 /// and the previously cached registry values will continue to be used. The next time the registry
 /// is read, it will try again to refresh the cache.
 ///
+/// Subscribers are notified of the changes found each time the cache is refreshed.
+///
+/// Refreshes are conditional requests: if the remote server provided an `ETag` or `Last-Modified`
+/// header with the file, the file is only downloaded again if it has changed.
+///
+/// If the registry is constructed with [`new_with_signing_key`], the remote file must have a
+/// detached signature, which is fetched from the file's URL with `.sig` appended. The signature is
+/// the hex-encoded secp256k1 signature of the file's contents by the given key. If the signature
+/// is missing or invalid, the refresh fails and the local cache is not replaced.
+///
 /// [`Node`]: struct.Node.html
 /// [`RegistryReader`]: trait.RegistryReader.html
 /// [`constructor`]: struct.RemoteYamlRegistry.html#method.new
+/// [`new_with_signing_key`]: struct.RemoteYamlRegistry.html#method.new_with_signing_key
 pub struct RemoteYamlRegistry {
     internal: Arc<Mutex<Internal>>,
     shutdown_handle: Option<RemoteYamlShutdownHandle>,
+    #[cfg(feature = "registry-subscriptions")]
+    subscribers: Subscribers,
 }
 
 impl RemoteYamlRegistry {
@@ -98,11 +124,72 @@ impl RemoteYamlRegistry {
         automatic_refresh_period: Option<Duration>,
         forced_refresh_period: Option<Duration>,
     ) -> Result<Self, RegistryError> {
-        let internal = Arc::new(Mutex::new(Internal::new(
+        Self::create(
+            url,
+            cache_dir,
+            automatic_refresh_period,
+            forced_refresh_period,
+            #[cfg(feature = "registry-remote-signature")]
+            None,
+        )
+    }
+
+    /// Construct a new `RemoteYamlRegistry` that only accepts the remote file if it has a valid
+    /// detached signature by the given key.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - URL of the registry's backing YAML file.
+    /// * `cache_dir` - Directory that the local registry cache will be stored in.
+    /// * `automatic_refresh_period` - Amount of time between attempts to automatically fetch and
+    ///   cache the remote YAML file in the background. If `None`, background refreshes will be
+    ///   disabled. The automatic refresh occurs with a tolerance of +/- 1 second.
+    /// * `forced_refresh_period` - Amount of time since the last successful cache refresh before
+    ///   attempting to refresh on every read operation. If `None`, forced refreshes will be
+    ///   disabled.
+    /// * `signing_key` - The public key that the remote file must be signed by.
+    #[cfg(feature = "registry-remote-signature")]
+    pub fn new_with_signing_key(
+        url: &str,
+        cache_dir: &str,
+        automatic_refresh_period: Option<Duration>,
+        forced_refresh_period: Option<Duration>,
+        signing_key: PublicKey,
+    ) -> Result<Self, RegistryError> {
+        Self::create(
             url,
             cache_dir,
+            automatic_refresh_period,
             forced_refresh_period,
-        )?));
+            Some(signing_key),
+        )
+    }
+
+    fn create(
+        url: &str,
+        cache_dir: &str,
+        automatic_refresh_period: Option<Duration>,
+        forced_refresh_period: Option<Duration>,
+        #[cfg(feature = "registry-remote-signature")] signing_key: Option<PublicKey>,
+    ) -> Result<Self, RegistryError> {
+        let internal = Internal::new(
+            url,
+            cache_dir,
+            forced_refresh_period,
+            #[cfg(feature = "registry-remote-signature")]
+            signing_key,
+        )?;
+
+        // Changes to the cache are passed on to the registry's subscribers once the internal lock
+        // has been released
+        #[cfg(feature = "registry-subscriptions")]
+        let subscribers = {
+            let subscribers = Subscribers::default();
+            internal.cache.add_subscriber(subscribers.forwarder())?;
+            subscribers
+        };
+
+        let internal = Arc::new(Mutex::new(internal));
 
         let (running, join_handle) = {
             if let Some(refresh_period) = automatic_refresh_period {
@@ -111,6 +198,8 @@ impl RemoteYamlRegistry {
                 let thread_internal = internal.clone();
                 let thread_url = url.to_string();
                 let thread_running = running.clone();
+                #[cfg(feature = "registry-subscriptions")]
+                let thread_subscribers = subscribers.clone();
                 let join_handle = thread::Builder::new()
                     .name(format!("Remote Registry Automatic Refresh: {}", url))
                     .spawn(move || {
@@ -119,6 +208,8 @@ impl RemoteYamlRegistry {
                             thread_internal,
                             &thread_url,
                             thread_running,
+                            #[cfg(feature = "registry-subscriptions")]
+                            thread_subscribers,
                         )
                     })
                     .map_err(|err| {
@@ -144,23 +235,59 @@ impl RemoteYamlRegistry {
         Ok(Self {
             internal,
             shutdown_handle: Some(shutdown_handle),
+            #[cfg(feature = "registry-subscriptions")]
+            subscribers,
         })
     }
 
+    /// Sets the policy used to decide which nodes from the remote file are provided by the
+    /// registry, based on their signatures. Nodes that the policy rejects are left out of the
+    /// local cache, and a warning is logged. By default, all nodes are accepted.
+    ///
+    /// The nodes are verified each time the remote file is fetched; the nodes that are already
+    /// cached are verified when the policy is set.
+    #[cfg(feature = "registry-signed-nodes")]
+    pub fn with_signature_policy(
+        self,
+        signature_policy: NodeSignaturePolicy,
+    ) -> Result<Self, RegistryError> {
+        self.internal
+            .lock()
+            .map_err(|_| {
+                RegistryError::InternalError(InternalError::with_message(
+                    "Internal lock poisoned".into(),
+                ))
+            })?
+            .set_signature_policy(signature_policy)?;
+
+        // Nodes rejected by the policy have been removed from the cache
+        #[cfg(feature = "registry-subscriptions")]
+        self.subscribers.notify();
+
+        Ok(self)
+    }
+
     pub fn take_shutdown_handle(&mut self) -> Option<RemoteYamlShutdownHandle> {
         self.shutdown_handle.take()
     }
 
     /// Acquire the lock for the internal cache and get the nodes from it.
     fn get_nodes(&self) -> Result<Vec<Node>, RegistryError> {
-        self.internal
+        let nodes = self
+            .internal
             .lock()
             .map_err(|_| {
                 RegistryError::InternalError(InternalError::with_message(
                     "Internal lock poisoned".into(),
                 ))
             })?
-            .get_nodes()
+            .get_nodes();
+
+        // Reading the registry may have refreshed the cache
+        #[cfg(feature = "registry-subscriptions")]
+        self.subscribers.notify();
+
+        nodes
     }
 }
 
@@ -188,6 +315,13 @@ impl RegistryReader for RemoteYamlRegistry {
             .filter(move |node| predicates.iter().all(|predicate| predicate.apply(node)))
             .count() as u32)
     }
+
+    /// Adds a subscriber. Only the nodes accepted by the signature policy are cached, so a cached
+    /// node that is replaced by a rejected update is sent to the subscriber as removed.
+    #[cfg(feature = "registry-subscriptions")]
+    fn add_subscriber(&self, subscriber: Box<dyn RegistrySubscriber>) -> Result<(), RegistryError> {
+        self.subscribers.add(subscriber)
+    }
 }
 
 /// Holds the internal state of the remote registry.
@@ -197,6 +331,20 @@ struct Internal {
     last_refresh_successful: bool,
     forced_refresh_period: Option<Duration>,
     next_forced_refresh: Option<Instant>,
+    /// The validators of the cached version of the remote file
+    validators: CacheValidators,
+    #[cfg(feature = "registry-remote-signature")]
+    signing_key: Option<PublicKey>,
+    /// The policy that the fetched nodes are verified with before they are cached
+    #[cfg(feature = "registry-signed-nodes")]
+    signature_policy: NodeSignaturePolicy,
+}
+
+/// Identifies a version of the remote file, so it is only downloaded again if it has changed.
+#[derive(Default)]
+struct CacheValidators {
+    etag: Option<String>,
+    last_modified: Option<String>,
 }
 
 impl Internal {
@@ -205,6 +353,7 @@ impl Internal {
         url: &str,
         cache_dir: &str,
         forced_refresh_period: Option<Duration>,
+        #[cfg(feature = "registry-remote-signature")] signing_key: Option<PublicKey>,
     ) -> Result<Self, RegistryError> {
         let url = url.to_string();
 
@@ -216,6 +365,11 @@ impl Internal {
             last_refresh_successful: false,
             forced_refresh_period,
             next_forced_refresh: None,
+            validators: CacheValidators::default(),
+            #[cfg(feature = "registry-remote-signature")]
+            signing_key,
+            #[cfg(feature = "registry-signed-nodes")]
+            signature_policy: NodeSignaturePolicy::default(),
         };
 
         // If initial fetch/cache fails, it will be re-attempted on the next registry read, so just
@@ -232,8 +386,8 @@ impl Internal {
 
     /// Attempt to refresh the internal cache and update state accordingly.
     fn refresh_cache(&mut self) -> Result<(), RegistryError> {
-        fetch_nodes_from_remote(&self.url)
-            .and_then(|nodes| self.cache.write_nodes(nodes))
+        self.fetch_from_remote()
+            .and_then(|fetched| self.update_cache(fetched))
             .map_err(|err| {
                 self.last_refresh_successful = false;
                 err
@@ -258,6 +412,69 @@ impl Internal {
             })
     }
 
+    /// Fetch the remote file, unless it has not changed since it was last cached.
+    fn fetch_from_remote(&self) -> Result<Fetched, RegistryError> {
+        fetch_nodes_from_remote(
+            &self.url,
+            &self.validators,
+            #[cfg(feature = "registry-remote-signature")]
+            self.signing_key.as_ref(),
+        )
+    }
+
+    /// Replace the cache's contents with the fetched nodes, if the remote file has changed.
+    fn update_cache(&mut self, fetched: Fetched) -> Result<(), RegistryError> {
+        match fetched {
+            Fetched::NotModified => {
+                debug!("Remote registry file at {} has not changed", self.url);
+                Ok(())
+            }
+            Fetched::Modified(nodes, validators) => {
+                #[cfg(feature = "registry-signed-nodes")]
+                let nodes = self.accepted_nodes(nodes);
+                self.cache.write_nodes(nodes)?;
+                self.validators = validators;
+                Ok(())
+            }
+        }
+    }
+
+    /// Set the signature policy and remove the cached nodes that it rejects.
+    #[cfg(feature = "registry-signed-nodes")]
+    fn set_signature_policy(
+        &mut self,
+        signature_policy: NodeSignaturePolicy,
+    ) -> Result<(), RegistryError> {
+        self.signature_policy = signature_policy;
+
+        let nodes = self.cache.get_nodes()?;
+        let count = nodes.len();
+        let nodes = self.accepted_nodes(nodes);
+        if nodes.len() != count {
+            self.cache.write_nodes(nodes)?;
+        }
+        Ok(())
+    }
+
+    /// Filter out the nodes that the signature policy rejects.
+    #[cfg(feature = "registry-signed-nodes")]
+    fn accepted_nodes(&self, nodes: Vec<Node>) -> Vec<Node> {
+        let verifier = new_verifier();
+        nodes
+            .into_iter()
+            .filter(|node| {
+                let accepted = self.signature_policy.accepts(node, &*verifier);
+                if !accepted {
+                    warn!(
+                        "Ignoring node {} from remote registry '{}'; its signature was rejected",
+                        node.identity, self.url
+                    );
+                }
+                accepted
+            })
+            .collect()
+    }
+
     /// Attempt to refresh the internal cache if necessary and return the cache's contents.
     fn get_nodes(&mut self) -> Result<Vec<Node>, RegistryError> {
         // If the last attempt to refresh the cache wasn't successful, try again
@@ -312,23 +529,70 @@ fn compute_cache_filename(url: &str, cache_dir: &str) -> Result<String, Registry
         .to_string())
 }
 
-/// Fetch, parse, and validate the YAML registry file at the given URL.
-fn fetch_nodes_from_remote(url: &str) -> Result<Vec<Node>, RegistryError> {
-    let bytes = reqwest::blocking::get(url)
+/// The result of fetching the remote registry file.
+enum Fetched {
+    /// The file has not changed since the cached version was fetched
+    NotModified,
+    /// The nodes in the file, along with the validators of this version of the file
+    Modified(Vec<Node>, CacheValidators),
+}
+
+/// Fetch, parse, and validate the YAML registry file at the given URL. The file is not downloaded
+/// if the server reports that it has not changed since the version identified by `validators`.
+/// If a `signing_key` is given, the file's detached signature must be valid for the key.
+fn fetch_nodes_from_remote(
+    url: &str,
+    validators: &CacheValidators,
+    #[cfg(feature = "registry-remote-signature")] signing_key: Option<&PublicKey>,
+) -> Result<Fetched, RegistryError> {
+    let mut request = reqwest::blocking::Client::new().get(url);
+    if let Some(etag) = &validators.etag {
+        request = request.header(IF_NONE_MATCH, etag.as_str());
+    }
+    if let Some(last_modified) = &validators.last_modified {
+        request = request.header(IF_MODIFIED_SINCE, last_modified.as_str());
+    }
+
+    let response = request
+        .send()
         .and_then(|response| response.error_for_status())
         .map_err(|err| {
             RegistryError::InternalError(InternalError::from_source_with_message(
                 Box::new(err),
                 format!("Failed to fetch remote registry file from {}", url),
             ))
-        })?
-        .bytes()
-        .map_err(|err| {
-            RegistryError::InternalError(InternalError::from_source_with_message(
-                Box::new(err),
-                "Failed to get bytes from remote registry file HTTP response".into(),
-            ))
         })?;
+
+    if response.status() == StatusCode::NOT_MODIFIED {
+        return Ok(Fetched::NotModified);
+    }
+
+    let header = |name: HeaderName| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from)
+    };
+    let validators = CacheValidators {
+        etag: header(ETAG),
+        last_modified: header(LAST_MODIFIED),
+    };
+
+    let bytes = response.bytes().map_err(|err| {
+        RegistryError::InternalError(InternalError::from_source_with_message(
+            Box::new(err),
+            "Failed to get bytes from remote registry file HTTP response".into(),
+        ))
+    })?;
+
+    #[cfg(feature = "registry-remote-signature")]
+    {
+        if let Some(signing_key) = signing_key {
+            verify_remote_signature(url, &bytes, signing_key)?;
+        }
+    }
+
     let yaml_nodes: Vec<YamlNode> = serde_yaml::from_slice(&bytes).map_err(|_| {
         RegistryError::InternalError(InternalError::with_message(
             "Failed to deserialize remote registry file: Not a valid YAML sequence of nodes".into(),
@@ -350,7 +614,60 @@ fn fetch_nodes_from_remote(url: &str) -> Result<Vec<Node>, RegistryError> {
         RegistryError::InvalidStateError(InvalidStateError::with_message(err.to_string()))
     })?;
 
-    Ok(nodes)
+    Ok(Fetched::Modified(nodes, validators))
+}
+
+/// Fetch the detached signature of the remote registry file at the given URL and verify that it
+/// is a valid signature of `bytes` by `signing_key`.
+#[cfg(feature = "registry-remote-signature")]
+fn verify_remote_signature(
+    url: &str,
+    bytes: &[u8],
+    signing_key: &PublicKey,
+) -> Result<(), RegistryError> {
+    let signature_url = format!("{}.sig", url);
+    let signature = reqwest::blocking::get(&signature_url)
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.text())
+        .map_err(|err| {
+            RegistryError::InternalError(InternalError::from_source_with_message(
+                Box::new(err),
+                format!(
+                    "Failed to fetch remote registry signature from {}",
+                    signature_url
+                ),
+            ))
+        })?;
+
+    let signature = parse_hex(signature.trim())
+        .map(Signature::new)
+        .map_err(|err| {
+            RegistryError::InvalidStateError(InvalidStateError::with_message(format!(
+                "Remote registry signature from {} is not valid: {}",
+                signature_url, err
+            )))
+        })?;
+
+    let valid = Secp256k1Context::new()
+        .new_verifier()
+        .verify(bytes, &signature, signing_key)
+        .map_err(|err| {
+            RegistryError::InternalError(InternalError::from_source_with_message(
+                Box::new(err),
+                "Failed to verify remote registry signature".into(),
+            ))
+        })?;
+
+    if valid {
+        Ok(())
+    } else {
+        Err(RegistryError::InvalidStateError(
+            InvalidStateError::with_message(format!(
+                "Remote registry file from {} is not signed by the registry's signing key",
+                url
+            )),
+        ))
+    }
 }
 
 /// Infinitely loop, attempting to refresh the `internal` cache every `refresh_period`, until no
@@ -360,6 +677,7 @@ fn automatic_refresh_loop(
     internal: Arc<Mutex<Internal>>,
     url: &str,
     running: Arc<AtomicBool>,
+    #[cfg(feature = "registry-subscriptions")] subscribers: Subscribers,
 ) {
     loop {
         // Wait the `refresh_period`, checking for shutdown every second
@@ -400,6 +718,12 @@ fn automatic_refresh_loop(
                 }
             }
         }
+
+        #[cfg(feature = "registry-subscriptions")]
+        {
+            drop(internal);
+            subscribers.notify();
+        }
     }
 }
 
@@ -434,11 +758,16 @@ mod tests {
     use super::*;
 
     use std::fs::File;
+    use std::sync::atomic::AtomicUsize;
 
-    use actix_web::HttpResponse;
+    use actix_web::{HttpRequest, HttpResponse};
+    #[cfg(feature = "registry-remote-signature")]
+    use cylinder::PrivateKey;
     use futures::future::IntoFuture;
     use tempdir::TempDir;
 
+    #[cfg(all(feature = "registry-signed-nodes", feature = "registry-subscriptions"))]
+    use crate::registry::RegistryEvent;
     use crate::rest_api::actix_web_1::{Method, Resource, RestApiBuilder, RestApiShutdownHandle};
     #[cfg(feature = "authorization")]
     use crate::rest_api::auth::authorization::Permission;
@@ -1069,6 +1398,188 @@ mod tests {
         test_config.shutdown();
     }
 
+    /// Verifies that the remote file is only downloaded again if it has changed, using the `ETag`
+    /// provided by the server.
+    #[test]
+    fn conditional_fetch() {
+        let test_config = TestConfig::setup("conditional_fetch", Some(mock_registry()));
+
+        let refresh_period = Duration::from_millis(10);
+        let mut remote_registry = RemoteYamlRegistry::new(
+            test_config.url(),
+            test_config.path(),
+            None,
+            Some(refresh_period),
+        )
+        .expect("Failed to create registry");
+        verify_internal_cache(&test_config, &remote_registry, mock_registry());
+        assert_eq!(test_config.full_responses(), 1);
+
+        // Verify that a forced refresh of the unchanged file does not download it again
+        std::thread::sleep(refresh_period);
+        verify_internal_cache(&test_config, &remote_registry, mock_registry());
+        assert_eq!(test_config.full_responses(), 1);
+
+        // Verify that the file is downloaded again once it has changed
+        test_config.update_registry(Some(vec![]));
+        std::thread::sleep(refresh_period);
+        verify_internal_cache(&test_config, &remote_registry, vec![]);
+        assert_eq!(test_config.full_responses(), 2);
+
+        let mut shutdown_handle = remote_registry
+            .take_shutdown_handle()
+            .expect("Unable to get shutdown handle");
+        shutdown_handle.signal_shutdown();
+        shutdown_handle
+            .wait_for_shutdown()
+            .expect("Unable to shutdown remote registry");
+        test_config.shutdown();
+    }
+
+    /// Verifies that a registry with a signing key only replaces its cache with remote files that
+    /// have a valid detached signature by that key.
+    #[test]
+    #[cfg(feature = "registry-remote-signature")]
+    fn signed_remote_file() {
+        let context = Secp256k1Context::new();
+        let signing_key = context.new_random_private_key();
+        let public_key = context
+            .get_public_key(&signing_key)
+            .expect("Failed to get public key");
+
+        let test_config = TestConfig::setup("signed_remote_file", Some(mock_registry()));
+        test_config.update_signing_key(Some(signing_key.clone()));
+
+        let refresh_period = Duration::from_millis(10);
+        let mut remote_registry = RemoteYamlRegistry::new_with_signing_key(
+            test_config.url(),
+            test_config.path(),
+            None,
+            Some(refresh_period),
+            public_key,
+        )
+        .expect("Failed to create registry");
+        verify_internal_cache(&test_config, &remote_registry, mock_registry());
+
+        // Verify that a file signed by another key is rejected and the old contents are kept
+        test_config.update_registry(Some(vec![]));
+        test_config.update_signing_key(Some(context.new_random_private_key()));
+        std::thread::sleep(refresh_period);
+        verify_internal_cache(&test_config, &remote_registry, mock_registry());
+
+        // Verify that an unsigned file is rejected and the old contents are kept
+        test_config.update_signing_key(None);
+        verify_internal_cache(&test_config, &remote_registry, mock_registry());
+
+        // Verify that the file is accepted once it is signed by the registry's signing key
+        test_config.update_signing_key(Some(signing_key));
+        verify_internal_cache(&test_config, &remote_registry, vec![]);
+
+        let mut shutdown_handle = remote_registry
+            .take_shutdown_handle()
+            .expect("Unable to get shutdown handle");
+        shutdown_handle.signal_shutdown();
+        shutdown_handle
+            .wait_for_shutdown()
+            .expect("Unable to shutdown remote registry");
+        test_config.shutdown();
+    }
+
+    /// Verifies that a signature policy is applied to the nodes that are already cached when it is
+    /// set, and to the nodes of each fetched file, so only accepted nodes are cached.
+    #[test]
+    #[cfg(feature = "registry-signed-nodes")]
+    fn signature_policy() {
+        use cylinder::{secp256k1::Secp256k1Context, Context};
+
+        let context = Secp256k1Context::new();
+        let private_key = context.new_random_private_key();
+        let public_key = context
+            .get_public_key(&private_key)
+            .expect("Failed to get public key")
+            .as_hex();
+        let signed_node = Node::builder("Node-789")
+            .with_endpoint("tcps://12.0.0.123:8435")
+            .with_key(public_key)
+            .build()
+            .expect("Failed to build node")
+            .sign(&*context.new_signer(private_key))
+            .expect("Failed to sign node");
+
+        let mut registry = mock_registry();
+        registry.push(signed_node.clone());
+        let test_config = TestConfig::setup("signature_policy", Some(registry.clone()));
+
+        let refresh_period = Duration::from_millis(10);
+        let remote_registry = RemoteYamlRegistry::new(
+            test_config.url(),
+            test_config.path(),
+            None,
+            Some(refresh_period),
+        )
+        .expect("Failed to create registry");
+        verify_internal_cache(&test_config, &remote_registry, registry.clone());
+
+        // Verify that the unsigned nodes are removed from the cache when the policy is set
+        let mut remote_registry = remote_registry
+            .with_signature_policy(NodeSignaturePolicy::RequireValid)
+            .expect("Failed to set signature policy");
+        verify_internal_cache(&test_config, &remote_registry, vec![signed_node.clone()]);
+
+        #[cfg(feature = "registry-subscriptions")]
+        let receiver = {
+            let (sender, receiver) = std::sync::mpsc::channel();
+            remote_registry
+                .add_subscriber(Box::new(ChannelSubscriber(Mutex::new(sender))))
+                .expect("Failed to add subscriber");
+            receiver
+        };
+
+        // Verify that a tampered node from a new file is not cached
+        let mut tampered = signed_node.clone();
+        tampered.display_name = "Tampered".into();
+        registry.pop();
+        registry.push(tampered);
+        test_config.update_registry(Some(registry));
+        std::thread::sleep(refresh_period);
+        verify_internal_cache(&test_config, &remote_registry, vec![]);
+
+        // Verify that subscribers are told the previously accepted node was removed
+        #[cfg(feature = "registry-subscriptions")]
+        assert_eq!(
+            receiver
+                .recv_timeout(Duration::from_secs(1))
+                .expect("Failed to receive event"),
+            RegistryEvent::NodeRemoved(signed_node)
+        );
+
+        let mut shutdown_handle = remote_registry
+            .take_shutdown_handle()
+            .expect("Unable to get shutdown handle");
+        shutdown_handle.signal_shutdown();
+        shutdown_handle
+            .wait_for_shutdown()
+            .expect("Unable to shutdown remote registry");
+        test_config.shutdown();
+    }
+
+    #[cfg(all(feature = "registry-signed-nodes", feature = "registry-subscriptions"))]
+    struct ChannelSubscriber(Mutex<std::sync::mpsc::Sender<RegistryEvent>>);
+
+    #[cfg(all(feature = "registry-signed-nodes", feature = "registry-subscriptions"))]
+    impl RegistrySubscriber for ChannelSubscriber {
+        fn handle_event(
+            &self,
+            event: &RegistryEvent,
+        ) -> Result<(), crate::registry::RegistrySubscriberError> {
+            self.0
+                .lock()
+                .expect("lock poisoned")
+                .send(event.clone())
+                .map_err(|_| crate::registry::RegistrySubscriberError::Unsubscribe)
+        }
+    }
+
     /// Verifies that any changes made to the remote file are fetched on restart if the remote file
     /// is available.
     #[test]
@@ -1209,7 +1720,7 @@ mod tests {
     struct TestConfig {
         _temp_dir: TempDir,
         temp_dir_path: String,
-        registry: Arc<Mutex<Option<Vec<Node>>>>,
+        remote_file: RemoteFile,
         registry_url: String,
         rest_api_shutdown_handle: RestApiShutdownHandle,
         rest_api_join_handle: std::thread::JoinHandle<()>,
@@ -1227,15 +1738,16 @@ mod tests {
                 .expect("Failed to get path")
                 .to_string();
 
-            let registry = Arc::new(Mutex::new(registry));
+            let remote_file = RemoteFile::default();
+            *remote_file.registry.lock().expect("Registry lock poisoned") = registry;
 
             let (rest_api_shutdown_handle, rest_api_join_handle, registry_url) =
-                serve_registry(registry.clone());
+                serve_registry(remote_file.clone());
 
             Self {
                 _temp_dir: temp_dir,
                 temp_dir_path,
-                registry,
+                remote_file,
                 registry_url,
                 rest_api_shutdown_handle,
                 rest_api_join_handle,
@@ -1255,7 +1767,28 @@ mod tests {
         /// Updates the `registry` file served up by the REST API; if `registry` is `None`, the
         /// remote file won't be available.
         fn update_registry(&self, registry: Option<Vec<Node>>) {
-            *self.registry.lock().expect("Registry lock poisonsed") = registry;
+            *self
+                .remote_file
+                .registry
+                .lock()
+                .expect("Registry lock poisonsed") = registry;
+        }
+
+        /// Gets the number of times the full registry file has been served, not counting
+        /// responses that reported the file as not modified.
+        fn full_responses(&self) -> usize {
+            self.remote_file.full_responses.load(Ordering::SeqCst)
+        }
+
+        /// Sets the key that the registry file's detached signature is made with; if `key` is
+        /// `None`, the signature won't be available.
+        #[cfg(feature = "registry-remote-signature")]
+        fn update_signing_key(&self, key: Option<PrivateKey>) {
+            *self
+                .remote_file
+                .signing_key
+                .lock()
+                .expect("Signing key lock poisoned") = key;
         }
 
         /// Shuts down the REST API; this should be called at the end of every test that uses
@@ -1270,60 +1803,125 @@ mod tests {
         }
     }
 
-    /// Wraps `run_rest_api_on_open_port`, serving up the given `registry` as a registry YAML file
-    /// that can be fetched at the returned URL. If `registry` is `None`, the registry file will not
-    /// be available.
-    fn serve_registry(
+    /// The state of the remote registry file served by the REST API.
+    #[derive(Clone, Default)]
+    struct RemoteFile {
         registry: Arc<Mutex<Option<Vec<Node>>>>,
+        full_responses: Arc<AtomicUsize>,
+        #[cfg(feature = "registry-remote-signature")]
+        signing_key: Arc<Mutex<Option<PrivateKey>>>,
+    }
+
+    impl RemoteFile {
+        /// Gets the serialized registry file, if it is available.
+        fn bytes(&self) -> Option<Vec<u8>> {
+            self.registry
+                .lock()
+                .expect("Registry lock poisoned")
+                .as_ref()
+                .map(|registry| {
+                    let yaml_registry: Vec<YamlNode> = registry
+                        .iter()
+                        .map(|node| YamlNode::from(node.clone()))
+                        .collect();
+                    serde_yaml::to_vec(&yaml_registry).expect("Failed to serialize registry file")
+                })
+        }
+
+        /// Responds with the registry file, tagged with an `ETag` of its hash. If the request's
+        /// `If-None-Match` header matches the file's `ETag`, the response is `304 Not Modified`.
+        fn registry_response(&self, request: &HttpRequest) -> HttpResponse {
+            let bytes = match self.bytes() {
+                Some(bytes) => bytes,
+                None => return HttpResponse::NotFound().finish(),
+            };
+            let etag = format!(
+                "\"{}\"",
+                to_hex(&hash(MessageDigest::sha256(), &bytes).expect("Failed to hash file"))
+            );
+
+            let if_none_match = request
+                .headers()
+                .get("If-None-Match")
+                .and_then(|value| value.to_str().ok());
+            if if_none_match == Some(etag.as_str()) {
+                return HttpResponse::NotModified()
+                    .header("ETag", etag.as_str())
+                    .finish();
+            }
+
+            self.full_responses.fetch_add(1, Ordering::SeqCst);
+            HttpResponse::Ok().header("ETag", etag.as_str()).body(bytes)
+        }
+
+        /// Responds with the hex-encoded detached signature of the registry file, if both the file
+        /// and the signing key are available.
+        #[cfg(feature = "registry-remote-signature")]
+        fn signature_response(&self) -> HttpResponse {
+            let signing_key = self
+                .signing_key
+                .lock()
+                .expect("Signing key lock poisoned")
+                .clone();
+            match (self.bytes(), signing_key) {
+                (Some(bytes), Some(signing_key)) => {
+                    let signature = Secp256k1Context::new()
+                        .new_signer(signing_key)
+                        .sign(&bytes)
+                        .expect("Failed to sign registry file");
+                    HttpResponse::Ok().body(to_hex(&signature.take_bytes()))
+                }
+                _ => HttpResponse::NotFound().finish(),
+            }
+        }
+    }
+
+    /// Wraps `run_rest_api_on_open_port`, serving up the given `remote_file` as a registry YAML
+    /// file that can be fetched at the returned URL. If the remote file's registry is `None`, the
+    /// registry file will not be available.
+    fn serve_registry(
+        remote_file: RemoteFile,
     ) -> (RestApiShutdownHandle, std::thread::JoinHandle<()>, String) {
-        let mut resource = Resource::build("/registry.yaml");
+        #[cfg(feature = "registry-remote-signature")]
+        let signature_file = remote_file.clone();
+
+        let registry_resource = get_resource("/registry.yaml", move |request| {
+            remote_file.registry_response(request)
+        });
+        #[cfg(not(feature = "registry-remote-signature"))]
+        let resources = vec![registry_resource];
+        #[cfg(feature = "registry-remote-signature")]
+        let resources = vec![
+            registry_resource,
+            get_resource("/registry.yaml.sig", move |_| {
+                signature_file.signature_response()
+            }),
+        ];
+        let (shutdown, join, url) = run_rest_api_on_open_port(resources);
+
+        (shutdown, join, format!("http://{}/registry.yaml", url))
+    }
+
+    /// Builds a resource at `path` that responds to `GET` requests with the given `handler`.
+    fn get_resource<F>(path: &str, handler: F) -> Resource
+    where
+        F: Fn(&HttpRequest) -> HttpResponse + Send + Sync + 'static,
+    {
+        let resource = Resource::build(path);
         #[cfg(feature = "authorization")]
         {
-            resource = resource.add_method(
+            resource.add_method(
                 Method::Get,
                 Permission::AllowUnauthenticated,
-                move |_, _| {
-                    Box::new(match &*registry.lock().expect("Registry lock poisoned") {
-                        Some(registry) => {
-                            let yaml_registry: Vec<YamlNode> = registry
-                                .iter()
-                                .map(|node| YamlNode::from(node.clone()))
-                                .collect();
-                            HttpResponse::Ok()
-                                .body(
-                                    serde_yaml::to_vec(&yaml_registry)
-                                        .expect("Failed to serialize registry file"),
-                                )
-                                .into_future()
-                        }
-                        None => HttpResponse::NotFound().finish().into_future(),
-                    })
-                },
+                move |request, _| Box::new(handler(&request).into_future()),
             )
         }
         #[cfg(not(feature = "authorization"))]
         {
-            resource = resource.add_method(Method::Get, move |_, _| {
-                Box::new(match &*registry.lock().expect("Registry lock poisoned") {
-                    Some(registry) => {
-                        let yaml_registry: Vec<YamlNode> = registry
-                            .iter()
-                            .map(|node| YamlNode::from(node.clone()))
-                            .collect();
-                        HttpResponse::Ok()
-                            .body(
-                                serde_yaml::to_vec(&yaml_registry)
-                                    .expect("Failed to serialize registry file"),
-                            )
-                            .into_future()
-                    }
-                    None => HttpResponse::NotFound().finish().into_future(),
-                })
+            resource.add_method(Method::Get, move |request, _| {
+                Box::new(handler(&request).into_future())
             })
         }
-        let (shutdown, join, url) = run_rest_api_on_open_port(vec![resource]);
-
-        (shutdown, join, format!("http://{}/registry.yaml", url))
     }
 
     fn run_rest_api_on_open_port(
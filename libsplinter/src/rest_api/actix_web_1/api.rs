@@ -13,11 +13,23 @@
 // limitations under the License.
 
 use std::sync::mpsc;
+#[cfg(all(feature = "https-bind", feature = "tls-cert-reload"))]
+use std::sync::{Arc, RwLock};
 use std::thread;
 
 use actix_web::{middleware, App, HttpServer};
 use futures::Future;
+#[cfg(all(feature = "https-bind", feature = "tls-cert-reload"))]
+use openssl::{
+    error::ErrorStack,
+    ssl::{SniError, SslAcceptor, SslContext, SslFiletype, SslMethod, SslRef},
+};
+
+#[cfg(all(feature = "https-bind", feature = "tls-cert-reload"))]
+use crate::error::InternalError;
 
+#[cfg(feature = "authorization-audit")]
+use crate::rest_api::auth::authorization::audit::{AuditSink, BackgroundAuditSink};
 #[cfg(feature = "authorization")]
 use crate::rest_api::auth::authorization::{
     routes::AuthorizationResourceProvider, AuthorizationHandler, PermissionMap,
@@ -36,6 +48,8 @@ use super::RestResourceProvider;
 pub struct RestApiShutdownHandle {
     do_shutdown: Box<dyn Fn() -> Result<(), RestApiServerError> + Send>,
     port_numbers: Vec<u16>,
+    #[cfg(all(feature = "https-bind", feature = "tls-cert-reload"))]
+    tls_reloader: Option<RestApiTlsReloader>,
 }
 
 impl RestApiShutdownHandle {
@@ -46,6 +60,74 @@ impl RestApiShutdownHandle {
     pub fn port_numbers(&self) -> Vec<u16> {
         self.port_numbers.clone()
     }
+
+    /// Returns a reloader for the REST API's certificate and key, if the REST API was bound with
+    /// HTTPS.
+    #[cfg(all(feature = "https-bind", feature = "tls-cert-reload"))]
+    pub fn tls_reloader(&self) -> Option<RestApiTlsReloader> {
+        self.tls_reloader.clone()
+    }
+}
+
+/// Reloads the certificate and private key used by an HTTPS REST API from their files.
+///
+/// Connections that are accepted after a reload are served with the new certificate, while
+/// connections that are already established are unaffected.
+#[cfg(all(feature = "https-bind", feature = "tls-cert-reload"))]
+#[derive(Clone)]
+pub struct RestApiTlsReloader {
+    cert_path: String,
+    key_path: String,
+    context: Arc<RwLock<SslContext>>,
+}
+
+#[cfg(all(feature = "https-bind", feature = "tls-cert-reload"))]
+impl RestApiTlsReloader {
+    fn new(cert_path: String, key_path: String) -> Result<Self, RestApiServerError> {
+        let context = load_ssl_context(&cert_path, &key_path)?;
+
+        Ok(Self {
+            cert_path,
+            key_path,
+            context: Arc::new(RwLock::new(context)),
+        })
+    }
+
+    /// Reads the certificate and private key files again and uses them for new connections.
+    ///
+    /// If the files cannot be loaded, or the key does not match the certificate, an error is
+    /// returned and the previous certificate remains in use.
+    pub fn reload(&self) -> Result<(), RestApiServerError> {
+        let context = load_ssl_context(&self.cert_path, &self.key_path)?;
+
+        *self.context.write().map_err(|_| {
+            RestApiServerError::InternalError(InternalError::with_message(
+                "REST API TLS context lock was poisoned".into(),
+            ))
+        })? = context;
+
+        Ok(())
+    }
+
+    /// Switches an incoming connection over to the most recently loaded certificate. This is
+    /// called by OpenSSL while processing the client hello.
+    fn select_context(&self, ssl: &mut SslRef) -> Result<(), SniError> {
+        let context = self.context.read().map_err(|_| SniError::ALERT_FATAL)?;
+        ssl.set_ssl_context(&context).map_err(|err| {
+            error!("Unable to use reloaded REST API certificate: {}", err);
+            SniError::ALERT_FATAL
+        })
+    }
+}
+
+#[cfg(all(feature = "https-bind", feature = "tls-cert-reload"))]
+fn load_ssl_context(cert_path: &str, key_path: &str) -> Result<SslContext, ErrorStack> {
+    let mut acceptor = SslAcceptor::mozilla_modern(SslMethod::tls())?;
+    acceptor.set_private_key_file(key_path, SslFiletype::PEM)?;
+    acceptor.set_certificate_chain_file(cert_path)?;
+    acceptor.check_private_key()?;
+
+    Ok(acceptor.build().into_context())
 }
 
 /// `RestApi` is used to create an instance of a restful web server.
@@ -57,6 +139,8 @@ pub struct RestApi {
     pub(super) identity_providers: Vec<Box<dyn IdentityProvider>>,
     #[cfg(feature = "authorization")]
     pub(super) authorization_handlers: Vec<Box<dyn AuthorizationHandler>>,
+    #[cfg(feature = "authorization-audit")]
+    pub(super) audit_sink: Option<Box<dyn AuditSink>>,
 }
 
 impl RestApi {
@@ -86,6 +170,15 @@ impl RestApi {
             #[cfg(feature = "authorization")]
             self.authorization_handlers.to_owned(),
         );
+        #[cfg(feature = "authorization-audit")]
+        let authorization = match self.audit_sink {
+            // Audit records are written on a background thread, so requests are not held up by
+            // the audit log
+            Some(audit_sink) => authorization.with_audit_sink(Box::new(
+                BackgroundAuditSink::new(audit_sink).map_err(RestApiServerError::InternalError)?,
+            )),
+            None => authorization,
+        };
 
         #[cfg(feature = "rest-api-cors")]
         let cors = match &whitelist {
@@ -93,6 +186,9 @@ impl RestApi {
             None => Cors::new_allow_any(),
         };
 
+        #[cfg(all(feature = "https-bind", feature = "tls-cert-reload"))]
+        let mut tls_reloader = None;
+
         #[cfg(feature = "https-bind")]
         let bind_info = match self.bind {
             BindConfig::Https {
@@ -102,10 +198,20 @@ impl RestApi {
             } => {
                 let mut acceptor =
                     openssl::ssl::SslAcceptor::mozilla_modern(openssl::ssl::SslMethod::tls())?;
-                acceptor.set_private_key_file(key_path, openssl::ssl::SslFiletype::PEM)?;
+                acceptor.set_private_key_file(&key_path, openssl::ssl::SslFiletype::PEM)?;
                 acceptor.set_certificate_chain_file(&cert_path)?;
                 acceptor.check_private_key()?;
 
+                #[cfg(feature = "tls-cert-reload")]
+                {
+                    let reloader = RestApiTlsReloader::new(cert_path, key_path)?;
+                    let callback_reloader = reloader.clone();
+                    acceptor.set_servername_callback(move |ssl, _| {
+                        callback_reloader.select_context(ssl)
+                    });
+                    tls_reloader = Some(reloader);
+                }
+
                 (bind, Some(acceptor))
             }
             BindConfig::Http(bind) => (bind, None),
@@ -231,6 +337,8 @@ impl RestApi {
             RestApiShutdownHandle {
                 do_shutdown,
                 port_numbers,
+                #[cfg(all(feature = "https-bind", feature = "tls-cert-reload"))]
+                tls_reloader,
             },
             join_handle,
         ))
@@ -340,6 +448,8 @@ impl RestApi {
             RestApiShutdownHandle {
                 do_shutdown,
                 port_numbers,
+                #[cfg(all(feature = "https-bind", feature = "tls-cert-reload"))]
+                tls_reloader: None,
             },
             join_handle,
         ))
@@ -16,6 +16,8 @@ use actix_web::HttpRequest;
 #[cfg(feature = "cylinder-jwt")]
 use cylinder::Verifier;
 
+#[cfg(feature = "api-keys")]
+use crate::api_keys::store::ApiKeyStore;
 #[cfg(feature = "biome-credentials")]
 use crate::biome::credentials::rest_api::BiomeCredentialsRestResourceProvider;
 #[cfg(feature = "oauth")]
@@ -25,11 +27,19 @@ use crate::biome::UserProfileStore;
 #[cfg(feature = "oauth")]
 use crate::rest_api::OAuthConfig;
 use crate::rest_api::{auth::identity::IdentityProvider, RequestError};
+#[cfg(feature = "saml")]
+use crate::saml::SamlServiceProvider;
 
 use super::Resource;
 
 /// Configurations for the various authentication methods supported by the Splinter REST API.
 pub enum AuthConfig {
+    /// API key authentication
+    #[cfg(feature = "api-keys")]
+    ApiKey {
+        /// The store of API keys issued to machine clients
+        api_key_store: Box<dyn ApiKeyStore>,
+    },
     /// Biome credentials authentication
     #[cfg(feature = "biome-credentials")]
     Biome {
@@ -54,6 +64,14 @@ pub enum AuthConfig {
         #[cfg(feature = "biome-profile")]
         user_profile_store: Box<dyn UserProfileStore>,
     },
+    /// SAML 2.0 authentication
+    #[cfg(feature = "saml")]
+    Saml {
+        /// The SAML service provider, which trusts a single identity provider
+        service_provider: SamlServiceProvider,
+        /// The Biome OAuth user session store, which also stores SAML users' sessions
+        session_store: Box<dyn OAuthUserSessionStore>,
+    },
     /// A custom authentication method
     Custom {
         /// REST API resources that would allow a client to receive some authentication credentials
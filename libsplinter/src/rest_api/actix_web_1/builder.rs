@@ -17,21 +17,39 @@ use std::sync::Arc;
 #[cfg(feature = "cylinder-jwt")]
 use std::sync::Mutex;
 
+#[cfg(feature = "api-keys")]
+use crate::api_keys::rest_api::ApiKeyResourceProvider;
 use crate::error::InvalidStateError;
 #[cfg(feature = "oauth")]
 use crate::oauth::{GithubOAuthClientBuilder, OpenIdOAuthClientBuilder};
+#[cfg(feature = "api-keys")]
+use crate::rest_api::auth::authorization::api_key_scopes::ApiKeyScopesAuthorizationHandler;
+#[cfg(feature = "authorization-audit")]
+use crate::rest_api::auth::authorization::audit::{AuditLogResourceProvider, AuditSink};
 #[cfg(feature = "authorization")]
 use crate::rest_api::auth::authorization::AuthorizationHandler;
+#[cfg(feature = "api-keys")]
+use crate::rest_api::auth::identity::api_key::ApiKeyIdentityProvider;
 #[cfg(feature = "cylinder-jwt")]
 use crate::rest_api::auth::identity::cylinder::CylinderKeyIdentityProvider;
+#[cfg(feature = "saml")]
+use crate::rest_api::auth::identity::saml::SamlUserIdentityProvider;
 #[cfg(feature = "oauth")]
 use crate::rest_api::{
     auth::identity::oauth::OAuthUserIdentityProvider, OAuthConfig, OAuthResourceProvider,
 };
 use crate::rest_api::{auth::identity::IdentityProvider, BindConfig, RestApiServerError};
+#[cfg(feature = "saml")]
+use crate::saml::rest_api::SamlResourceProvider;
 
 use super::AuthConfig;
-#[cfg(any(feature = "biome-credentials", feature = "oauth"))]
+#[cfg(any(
+    feature = "api-keys",
+    feature = "authorization-audit",
+    feature = "biome-credentials",
+    feature = "oauth",
+    feature = "saml"
+))]
 use super::RestResourceProvider;
 use super::{Resource, RestApi};
 
@@ -44,6 +62,8 @@ pub struct RestApiBuilder {
     auth_configs: Vec<AuthConfig>,
     #[cfg(feature = "authorization")]
     authorization_handlers: Vec<Box<dyn AuthorizationHandler>>,
+    #[cfg(feature = "authorization-audit")]
+    audit_sink: Option<Box<dyn AuditSink>>,
 }
 
 impl Default for RestApiBuilder {
@@ -56,6 +76,8 @@ impl Default for RestApiBuilder {
             auth_configs: Vec::new(),
             #[cfg(feature = "authorization")]
             authorization_handlers: Vec::new(),
+            #[cfg(feature = "authorization-audit")]
+            audit_sink: None,
         }
     }
 }
@@ -107,6 +129,14 @@ impl RestApiBuilder {
         self
     }
 
+    /// Sets the sink that authorization decisions are recorded in. This also adds the endpoint
+    /// for querying the audit log to the REST API.
+    #[cfg(feature = "authorization-audit")]
+    pub fn with_audit_sink(mut self, audit_sink: Box<dyn AuditSink>) -> Self {
+        self.audit_sink = Some(audit_sink);
+        self
+    }
+
     // Allowing unused_mut because self must be mutable if feature `auth` is enabled
     #[allow(unused_mut)]
     pub fn build(mut self) -> Result<RestApi, RestApiServerError> {
@@ -124,11 +154,37 @@ impl RestApiBuilder {
             }
 
             let mut identity_providers = Vec::<Box<dyn IdentityProvider>>::new();
+            #[cfg(feature = "api-keys")]
+            let mut api_keys_configured = false;
             #[cfg(feature = "oauth")]
             let mut oauth_configured = false;
+            #[cfg(feature = "saml")]
+            let mut saml_configured = false;
 
             for auth_config in self.auth_configs.into_iter() {
                 match auth_config {
+                    #[cfg(feature = "api-keys")]
+                    AuthConfig::ApiKey { api_key_store } => {
+                        if api_keys_configured {
+                            return Err(RestApiServerError::InvalidStateError(
+                                InvalidStateError::with_message(
+                                    "Only one API key store can be configured".to_string(),
+                                ),
+                            ));
+                        }
+
+                        identity_providers
+                            .push(Box::new(ApiKeyIdentityProvider::new(api_key_store.clone())));
+                        // API keys must be restricted to their scopes before any other handler
+                        // has a chance to allow the request
+                        self.authorization_handlers.insert(
+                            0,
+                            Box::new(ApiKeyScopesAuthorizationHandler::new(api_key_store.clone())),
+                        );
+                        self.resources
+                            .append(&mut ApiKeyResourceProvider::new(api_key_store).resources());
+                        api_keys_configured = true;
+                    }
                     #[cfg(feature = "biome-credentials")]
                     AuthConfig::Biome {
                         biome_credentials_resource_provider,
@@ -237,6 +293,29 @@ impl RestApiBuilder {
                         );
                         oauth_configured = true;
                     }
+                    #[cfg(feature = "saml")]
+                    AuthConfig::Saml {
+                        service_provider,
+                        session_store,
+                    } => {
+                        if saml_configured {
+                            return Err(RestApiServerError::InvalidStateError(
+                                InvalidStateError::with_message(
+                                    "Only one SAML identity provider can be configured".to_string(),
+                                ),
+                            ));
+                        }
+
+                        identity_providers.push(Box::new(SamlUserIdentityProvider::new(
+                            session_store.clone(),
+                            service_provider.session_duration(),
+                        )));
+                        self.resources.append(
+                            &mut SamlResourceProvider::new(service_provider, session_store)
+                                .resources(),
+                        );
+                        saml_configured = true;
+                    }
                     AuthConfig::Custom {
                         mut resources,
                         identity_provider,
@@ -250,6 +329,12 @@ impl RestApiBuilder {
             identity_providers
         };
 
+        #[cfg(feature = "authorization-audit")]
+        if let Some(audit_sink) = &self.audit_sink {
+            self.resources
+                .append(&mut AuditLogResourceProvider::new(audit_sink.clone()).resources());
+        }
+
         Ok(RestApi {
             bind,
             resources: self.resources,
@@ -258,6 +343,8 @@ impl RestApiBuilder {
             identity_providers,
             #[cfg(feature = "authorization")]
             authorization_handlers: self.authorization_handlers,
+            #[cfg(feature = "authorization-audit")]
+            audit_sink: self.audit_sink,
         })
     }
 
@@ -282,6 +369,8 @@ impl RestApiBuilder {
             identity_providers: vec![],
             #[cfg(feature = "authorization")]
             authorization_handlers: vec![],
+            #[cfg(feature = "authorization-audit")]
+            audit_sink: None,
         })
     }
 }
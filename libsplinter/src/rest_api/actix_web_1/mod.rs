@@ -20,6 +20,8 @@ mod guard;
 mod resource;
 mod websocket;
 
+#[cfg(all(feature = "https-bind", feature = "tls-cert-reload"))]
+pub use api::RestApiTlsReloader;
 pub use api::{RestApi, RestApiShutdownHandle};
 pub use auth::{get_authorization_token, require_header, AuthConfig};
 pub use builder::RestApiBuilder;
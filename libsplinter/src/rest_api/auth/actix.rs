@@ -31,6 +31,8 @@ use crate::rest_api::ErrorResponse;
 #[cfg(feature = "authorization")]
 use crate::rest_api::Method;
 
+#[cfg(feature = "authorization-audit")]
+use super::authorization::audit::AuditSink;
 #[cfg(feature = "authorization")]
 use super::authorization::{AuthorizationHandler, PermissionMap};
 use super::{authorize, identity::IdentityProvider, AuthorizationResult};
@@ -41,6 +43,8 @@ pub struct Authorization {
     identity_providers: Vec<Box<dyn IdentityProvider>>,
     #[cfg(feature = "authorization")]
     authorization_handlers: Vec<Box<dyn AuthorizationHandler>>,
+    #[cfg(feature = "authorization-audit")]
+    audit_sink: Option<Box<dyn AuditSink>>,
 }
 
 impl Authorization {
@@ -54,8 +58,18 @@ impl Authorization {
             identity_providers,
             #[cfg(feature = "authorization")]
             authorization_handlers,
+            #[cfg(feature = "authorization-audit")]
+            audit_sink: None,
         }
     }
+
+    /// Records the authorization decision for every request that requires authorization in the
+    /// given audit sink.
+    #[cfg(feature = "authorization-audit")]
+    pub fn with_audit_sink(mut self, audit_sink: Box<dyn AuditSink>) -> Self {
+        self.audit_sink = Some(audit_sink);
+        self
+    }
 }
 
 impl<S, B> Transform<S> for Authorization
@@ -76,6 +90,8 @@ where
             identity_providers: self.identity_providers.clone(),
             #[cfg(feature = "authorization")]
             authorization_handlers: self.authorization_handlers.clone(),
+            #[cfg(feature = "authorization-audit")]
+            audit_sink: self.audit_sink.clone(),
             service,
         })
     }
@@ -86,6 +102,8 @@ pub struct AuthorizationMiddleware<S> {
     identity_providers: Vec<Box<dyn IdentityProvider>>,
     #[cfg(feature = "authorization")]
     authorization_handlers: Vec<Box<dyn AuthorizationHandler>>,
+    #[cfg(feature = "authorization-audit")]
+    audit_sink: Option<Box<dyn AuditSink>>,
     service: S,
 }
 
@@ -185,11 +203,23 @@ where
             &self.identity_providers,
             #[cfg(feature = "authorization")]
             &self.authorization_handlers,
+            #[cfg(feature = "authorization-audit")]
+            self.audit_sink.as_deref(),
         ) {
             AuthorizationResult::Authorized(identity) => {
                 debug!("Authenticated user {:?}", identity);
                 req.extensions_mut().insert(identity);
             }
+            #[cfg(feature = "authorization")]
+            AuthorizationResult::AuthorizedForCircuits(identity, circuit_scope) => {
+                debug!(
+                    "Authenticated user {:?} for circuits {:?}",
+                    identity,
+                    circuit_scope.circuits()
+                );
+                req.extensions_mut().insert(identity);
+                req.extensions_mut().insert(circuit_scope);
+            }
             #[cfg(any(
                 feature = "authorization",
                 feature = "biome-credentials",
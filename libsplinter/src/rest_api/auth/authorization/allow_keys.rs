@@ -86,6 +86,10 @@ impl AuthorizationHandler for AllowKeysAuthorizationHandler {
         }
     }
 
+    fn name(&self) -> &str {
+        "allow_keys"
+    }
+
     fn clone_box(&self) -> Box<dyn AuthorizationHandler> {
         Box::new(self.clone())
     }
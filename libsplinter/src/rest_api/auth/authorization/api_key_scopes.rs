@@ -0,0 +1,190 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An authorization handler that restricts API keys to their scopes
+
+use crate::api_keys::store::ApiKeyStore;
+use crate::error::InternalError;
+use crate::rest_api::auth::identity::Identity;
+
+use super::{AuthorizationHandler, AuthorizationHandlerResult};
+
+/// An authorization handler that limits API keys to the permissions in their scopes
+///
+/// The authorization handler only checks [`Identity::ApiKey`] identities; if a different type of
+/// identity is provided, the handler will return [`AuthorizationHandlerResult::Continue`]. If the
+/// API key does not have the requested permission in its scopes, or the key has been removed or
+/// has expired, the handler will return [`AuthorizationHandlerResult::Deny`]; otherwise it will
+/// return [`AuthorizationHandlerResult::Continue`], leaving it to the remaining handlers (such as
+/// role-based authorization) to grant the permission.
+///
+/// This handler must come before any handler that may allow an API key's request, since the first
+/// handler to allow or deny a request determines the result.
+#[derive(Clone)]
+pub struct ApiKeyScopesAuthorizationHandler {
+    api_key_store: Box<dyn ApiKeyStore>,
+}
+
+impl ApiKeyScopesAuthorizationHandler {
+    /// Constructs a new `ApiKeyScopesAuthorizationHandler` that uses the given store.
+    pub fn new(api_key_store: Box<dyn ApiKeyStore>) -> Self {
+        Self { api_key_store }
+    }
+}
+
+impl AuthorizationHandler for ApiKeyScopesAuthorizationHandler {
+    fn has_permission(
+        &self,
+        identity: &Identity,
+        permission_id: &str,
+    ) -> Result<AuthorizationHandlerResult, InternalError> {
+        let key_id = match identity {
+            Identity::ApiKey(key_id) => key_id,
+            _ => return Ok(AuthorizationHandlerResult::Continue),
+        };
+
+        match self
+            .api_key_store
+            .get_api_key(key_id)
+            .map_err(|err| InternalError::from_source(Box::new(err)))?
+        {
+            Some(api_key) if !api_key.is_expired() && api_key.has_scope(permission_id) => {
+                Ok(AuthorizationHandlerResult::Continue)
+            }
+            _ => Ok(AuthorizationHandlerResult::Deny),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "api_key_scopes"
+    }
+
+    fn clone_box(&self) -> Box<dyn AuthorizationHandler> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    use std::time::{Duration, SystemTime};
+
+    use diesel::{
+        r2d2::{ConnectionManager, Pool},
+        sqlite::SqliteConnection,
+    };
+
+    use crate::api_keys::store::{ApiKeyBuilder, DieselApiKeyStore};
+    use crate::migrations::run_sqlite_migrations;
+    use crate::store::ForeignKeyCustomizer;
+
+    /// Verifies that the `ApiKeyScopesAuthorizationHandler` returns
+    /// `AuthorizationHandlerResult::Continue` when an identity other than an API key is passed in.
+    #[test]
+    fn non_api_key_identity() {
+        let handler = ApiKeyScopesAuthorizationHandler::new(create_api_key_store());
+
+        assert!(matches!(
+            handler.has_permission(&Identity::Key("abcdef".into()), "circuit.read"),
+            Ok(AuthorizationHandlerResult::Continue)
+        ));
+    }
+
+    /// Verifies that the `ApiKeyScopesAuthorizationHandler` returns
+    /// `AuthorizationHandlerResult::Continue` for permissions in an API key's scopes and
+    /// `AuthorizationHandlerResult::Deny` for any other permission.
+    ///
+    /// 1. Add an API key with the `circuit.read` scope to the store
+    /// 2. Verify that the `circuit.read` permission returns `Continue`
+    /// 3. Verify that the `circuit.write` permission returns `Deny`
+    /// 4. Remove the key and verify that the `circuit.read` permission returns `Deny`
+    #[test]
+    fn scoped_api_key() {
+        let api_key_store = create_api_key_store();
+        add_api_key(
+            &*api_key_store,
+            "0123456789abcdef",
+            Duration::from_secs(3600),
+        );
+
+        let handler = ApiKeyScopesAuthorizationHandler::new(api_key_store.clone());
+        let identity = Identity::ApiKey("0123456789abcdef".into());
+
+        assert!(matches!(
+            handler.has_permission(&identity, "circuit.read"),
+            Ok(AuthorizationHandlerResult::Continue)
+        ));
+        assert!(matches!(
+            handler.has_permission(&identity, "circuit.write"),
+            Ok(AuthorizationHandlerResult::Deny)
+        ));
+
+        api_key_store
+            .remove_api_key("0123456789abcdef")
+            .expect("Failed to remove API key");
+        assert!(matches!(
+            handler.has_permission(&identity, "circuit.read"),
+            Ok(AuthorizationHandlerResult::Deny)
+        ));
+    }
+
+    /// Verifies that the `ApiKeyScopesAuthorizationHandler` returns
+    /// `AuthorizationHandlerResult::Deny` for an expired API key, even for permissions in its
+    /// scopes.
+    #[test]
+    fn expired_api_key() {
+        let api_key_store = create_api_key_store();
+        add_api_key(&*api_key_store, "0123456789abcdef", Duration::from_secs(0));
+
+        let handler = ApiKeyScopesAuthorizationHandler::new(api_key_store);
+
+        assert!(matches!(
+            handler.has_permission(&Identity::ApiKey("0123456789abcdef".into()), "circuit.read"),
+            Ok(AuthorizationHandlerResult::Deny)
+        ));
+    }
+
+    /// Adds an API key with the `circuit.read` scope that expires after the given duration
+    fn add_api_key(api_key_store: &dyn ApiKeyStore, id: &str, lifetime: Duration) {
+        let created_at = SystemTime::now() - Duration::from_secs(60);
+        api_key_store
+            .add_api_key(
+                ApiKeyBuilder::new()
+                    .with_id(id.into())
+                    .with_display_name("Test key".into())
+                    .with_secret_hash("secret_hash".into())
+                    .with_scopes(vec!["circuit.read".into()])
+                    .with_created_at(created_at)
+                    .with_expires_at(created_at + Duration::from_secs(60) + lifetime)
+                    .build()
+                    .expect("Failed to build API key"),
+            )
+            .expect("Failed to add API key");
+    }
+
+    fn create_api_key_store() -> Box<dyn ApiKeyStore> {
+        let connection_manager = ConnectionManager::<SqliteConnection>::new(":memory:");
+        let pool = Pool::builder()
+            .max_size(1)
+            .connection_customizer(Box::new(ForeignKeyCustomizer))
+            .build(connection_manager)
+            .expect("Failed to build connection pool");
+
+        run_sqlite_migrations(&*pool.get().expect("Failed to get connection for migrations"))
+            .expect("Failed to run migrations");
+
+        Box::new(DieselApiKeyStore::new(pool))
+    }
+}
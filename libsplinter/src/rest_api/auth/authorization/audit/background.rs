@@ -0,0 +1,160 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An [AuditSink] that writes records to another sink on a background thread.
+
+use std::thread;
+
+use crossbeam_channel::Sender;
+
+use crate::error::InternalError;
+
+use super::{AuditRecord, AuditRecordPredicate, AuditSink, AuditSinkError};
+
+/// The number of records that may be queued for the writer thread before recording blocks
+const AUDIT_QUEUE_CAPACITY: usize = 1024;
+
+/// An [AuditSink] that queues records for a background thread, which writes them to the wrapped
+/// sink.
+///
+/// The REST API's authorization middleware records through this sink, so that requests do not
+/// wait for the audit log to be written; recording only blocks if the writer thread has fallen a
+/// full queue behind. Listing records reads from the wrapped sink directly, so records that are
+/// still queued are not listed yet. The writer thread exits once all clones of this sink have been
+/// dropped and the queued records have been written.
+#[derive(Clone)]
+pub(in crate::rest_api) struct BackgroundAuditSink {
+    inner: Box<dyn AuditSink>,
+    sender: Sender<AuditRecord>,
+}
+
+impl BackgroundAuditSink {
+    /// Creates a new BackgroundAuditSink and starts its writer thread.
+    ///
+    /// # Arguments
+    ///
+    ///  * `inner`: the sink that records are written to
+    pub fn new(inner: Box<dyn AuditSink>) -> Result<Self, InternalError> {
+        let (sender, receiver) = crossbeam_channel::bounded(AUDIT_QUEUE_CAPACITY);
+
+        let writer = inner.clone();
+        thread::Builder::new()
+            .name("Audit Writer".into())
+            .spawn(move || {
+                for record in receiver.iter() {
+                    if let Err(err) = writer.record(record) {
+                        error!(
+                            "Unable to record authorization decision in audit log: {}",
+                            err
+                        );
+                    }
+                }
+            })
+            .map_err(|err| InternalError::from_source(Box::new(err)))?;
+
+        Ok(Self { inner, sender })
+    }
+}
+
+impl AuditSink for BackgroundAuditSink {
+    fn record(&self, record: AuditRecord) -> Result<(), AuditSinkError> {
+        self.sender.send(record).map_err(|_| {
+            AuditSinkError::InternalError(InternalError::with_message(
+                "Audit writer thread has stopped".into(),
+            ))
+        })
+    }
+
+    fn list_records(
+        &self,
+        predicates: &[AuditRecordPredicate],
+    ) -> Result<Box<dyn ExactSizeIterator<Item = AuditRecord>>, AuditSinkError> {
+        self.inner.list_records(predicates)
+    }
+
+    fn clone_box(&self) -> Box<dyn AuditSink> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::{Duration, Instant, UNIX_EPOCH};
+
+    use tempdir::TempDir;
+
+    use crate::rest_api::auth::authorization::audit::{
+        AuditOutcome, AuditRecordBuilder, JsonLinesAuditSink,
+    };
+
+    /// Verify that the `BackgroundAuditSink` writes recorded records to the wrapped sink.
+    ///
+    /// 1. Create a `BackgroundAuditSink` that wraps a `JsonLinesAuditSink`.
+    /// 2. Record an allowed request and a denied request.
+    /// 3. Verify that both records are eventually listed, most recent first, and that they were
+    ///    written to the wrapped sink.
+    #[test]
+    fn background_record_and_list() {
+        let temp_dir =
+            TempDir::new("background_record_and_list").expect("Failed to create temp dir");
+        let path = temp_dir.path().join("audit.jsonl");
+
+        let sink = BackgroundAuditSink::new(Box::new(JsonLinesAuditSink::new(path.clone())))
+            .expect("Unable to create sink");
+
+        let start = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        let allowed = AuditRecordBuilder::new()
+            .with_identity("user:some-user-id".into())
+            .with_method("GET".into())
+            .with_path("/admin/circuits".into())
+            .with_outcome(AuditOutcome::Allowed)
+            .with_timestamp(start)
+            .build()
+            .expect("Unable to build record");
+        let denied = AuditRecordBuilder::new()
+            .with_identity("user:some-user-id".into())
+            .with_method("POST".into())
+            .with_path("/admin/submit".into())
+            .with_outcome(AuditOutcome::Denied)
+            .with_timestamp(start + Duration::from_secs(1))
+            .build()
+            .expect("Unable to build record");
+
+        sink.record(allowed.clone()).expect("Unable to record");
+        sink.record(denied.clone()).expect("Unable to record");
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let records = loop {
+            let records = sink
+                .list_records(&[])
+                .expect("Unable to list records")
+                .collect::<Vec<_>>();
+            if records.len() == 2 || Instant::now() > deadline {
+                break records;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        assert_eq!(records, vec![denied.clone(), allowed.clone()]);
+
+        assert_eq!(
+            JsonLinesAuditSink::new(path)
+                .list_records(&[])
+                .expect("Unable to list records")
+                .collect::<Vec<_>>(),
+            vec![denied, allowed]
+        );
+    }
+}
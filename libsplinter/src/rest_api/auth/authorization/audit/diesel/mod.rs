@@ -0,0 +1,278 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Database-backed implementation of the [AuditSink], powered by [diesel].
+
+mod models;
+mod operations;
+mod schema;
+
+use std::convert::TryFrom;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use diesel::r2d2::{ConnectionManager, Pool};
+
+use crate::error::InternalError;
+
+use super::{AuditRecord, AuditRecordPredicate, AuditSink, AuditSinkError};
+
+use operations::add_record::AuditSinkAddRecord as _;
+use operations::list_records::AuditSinkListRecords as _;
+use operations::AuditSinkOperations;
+
+/// A database-backed [AuditSink], powered by [diesel].
+pub struct DieselAuditSink<C: diesel::Connection + 'static> {
+    connection_pool: Pool<ConnectionManager<C>>,
+}
+
+impl<C: diesel::Connection + 'static> DieselAuditSink<C> {
+    /// Creates a new DieselAuditSink
+    ///
+    /// # Arguments
+    ///
+    ///  * `connection_pool`: connection pool to the database
+    pub fn new(connection_pool: Pool<ConnectionManager<C>>) -> Self {
+        Self { connection_pool }
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl AuditSink for DieselAuditSink<diesel::pg::PgConnection> {
+    fn record(&self, record: AuditRecord) -> Result<(), AuditSinkError> {
+        let connection = self.connection_pool.get()?;
+        AuditSinkOperations::new(&*connection).add_record(record)
+    }
+
+    fn list_records(
+        &self,
+        predicates: &[AuditRecordPredicate],
+    ) -> Result<Box<dyn ExactSizeIterator<Item = AuditRecord>>, AuditSinkError> {
+        let connection = self.connection_pool.get()?;
+        AuditSinkOperations::new(&*connection).list_records(predicates)
+    }
+
+    fn clone_box(&self) -> Box<dyn AuditSink> {
+        Box::new(Self {
+            connection_pool: self.connection_pool.clone(),
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl AuditSink for DieselAuditSink<diesel::sqlite::SqliteConnection> {
+    fn record(&self, record: AuditRecord) -> Result<(), AuditSinkError> {
+        let connection = self.connection_pool.get()?;
+        AuditSinkOperations::new(&*connection).add_record(record)
+    }
+
+    fn list_records(
+        &self,
+        predicates: &[AuditRecordPredicate],
+    ) -> Result<Box<dyn ExactSizeIterator<Item = AuditRecord>>, AuditSinkError> {
+        let connection = self.connection_pool.get()?;
+        AuditSinkOperations::new(&*connection).list_records(predicates)
+    }
+
+    fn clone_box(&self) -> Box<dyn AuditSink> {
+        Box::new(Self {
+            connection_pool: self.connection_pool.clone(),
+        })
+    }
+}
+
+impl TryFrom<AuditRecord> for models::NewAuditRecordModel {
+    type Error = AuditSinkError;
+
+    fn try_from(record: AuditRecord) -> Result<Self, Self::Error> {
+        Ok(models::NewAuditRecordModel {
+            identity: record.identity,
+            method: record.method,
+            path: record.path,
+            permission_id: record.permission_id,
+            handler: record.handler,
+            outcome: record.outcome.to_string(),
+            recorded_at: to_timestamp(record.timestamp)?,
+        })
+    }
+}
+
+impl TryFrom<models::AuditRecordModel> for AuditRecord {
+    type Error = AuditSinkError;
+
+    fn try_from(model: models::AuditRecordModel) -> Result<Self, Self::Error> {
+        // The record is created directly, vs using the builder, as it was validated by the
+        // builder before it was added to the database.
+        Ok(AuditRecord {
+            identity: model.identity,
+            method: model.method,
+            path: model.path,
+            permission_id: model.permission_id,
+            handler: model.handler,
+            outcome: model.outcome.parse().map_err(|err| {
+                AuditSinkError::InternalError(InternalError::from_source(Box::new(err)))
+            })?,
+            timestamp: from_timestamp(model.recorded_at)?,
+        })
+    }
+}
+
+/// Converts a `SystemTime` to the number of seconds since the epoch, as stored in the database
+fn to_timestamp(time: SystemTime) -> Result<i64, AuditSinkError> {
+    time.duration_since(UNIX_EPOCH)
+        .map_err(|err| AuditSinkError::InternalError(InternalError::from_source(Box::new(err))))
+        .and_then(|duration| {
+            i64::try_from(duration.as_secs()).map_err(|_| {
+                AuditSinkError::InternalError(InternalError::with_message(
+                    "timestamp could not be converted from u64 to i64".to_string(),
+                ))
+            })
+        })
+}
+
+/// Converts a number of seconds since the epoch, as stored in the database, to a `SystemTime`
+fn from_timestamp(timestamp: i64) -> Result<SystemTime, AuditSinkError> {
+    u64::try_from(timestamp)
+        .ok()
+        .and_then(|secs| UNIX_EPOCH.checked_add(Duration::from_secs(secs)))
+        .ok_or_else(|| {
+            AuditSinkError::InternalError(InternalError::with_message(
+                "timestamp could not be represented as a `SystemTime`".to_string(),
+            ))
+        })
+}
+
+impl From<diesel::result::Error> for AuditSinkError {
+    fn from(err: diesel::result::Error) -> Self {
+        AuditSinkError::InternalError(InternalError::from_source(Box::new(err)))
+    }
+}
+
+impl From<diesel::r2d2::PoolError> for AuditSinkError {
+    fn from(err: diesel::r2d2::PoolError) -> Self {
+        AuditSinkError::InternalError(InternalError::from_source(Box::new(err)))
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    use crate::migrations::run_sqlite_migrations;
+    use crate::rest_api::auth::authorization::audit::{AuditOutcome, AuditRecordBuilder};
+
+    use diesel::{
+        r2d2::{ConnectionManager, Pool},
+        sqlite::SqliteConnection,
+    };
+
+    /// Verify that a SQLite-backed `DieselAuditSink` correctly records audit records and lists
+    /// them with predicates applied.
+    ///
+    /// 1. Create a connection pool for an in-memory SQLite database and run migrations.
+    /// 2. Create the `DieselAuditSink` and record three decisions: an allowed request, a request
+    ///    denied by a handler, and an unauthenticated request.
+    /// 3. Verify that all three records are listed, most recent first.
+    /// 4. Verify that the `Identity`, `Outcome`, `Since`, and `Until` predicates each filter
+    ///    the records correctly, alone and combined.
+    #[test]
+    fn sqlite_record_and_list() {
+        let pool = create_connection_pool_and_migrate();
+
+        let sink = DieselAuditSink::new(pool);
+
+        let start = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        let allowed = AuditRecordBuilder::new()
+            .with_identity("key:abcd".into())
+            .with_method("GET".into())
+            .with_path("/admin/circuits".into())
+            .with_permission_id("circuit.read".into())
+            .with_handler("rbac".into())
+            .with_outcome(AuditOutcome::Allowed)
+            .with_timestamp(start)
+            .build()
+            .expect("Unable to build record");
+        let denied = AuditRecordBuilder::new()
+            .with_identity("key:abcd".into())
+            .with_method("POST".into())
+            .with_path("/admin/submit".into())
+            .with_permission_id("circuit.write".into())
+            .with_handler("maintenance".into())
+            .with_outcome(AuditOutcome::Denied)
+            .with_timestamp(start + Duration::from_secs(10))
+            .build()
+            .expect("Unable to build record");
+        let unauthenticated = AuditRecordBuilder::new()
+            .with_method("GET".into())
+            .with_path("/admin/circuits".into())
+            .with_permission_id("circuit.read".into())
+            .with_outcome(AuditOutcome::Denied)
+            .with_timestamp(start + Duration::from_secs(20))
+            .build()
+            .expect("Unable to build record");
+
+        sink.record(allowed.clone()).expect("Unable to record");
+        sink.record(denied.clone()).expect("Unable to record");
+        sink.record(unauthenticated.clone())
+            .expect("Unable to record");
+
+        let list = |predicates: &[AuditRecordPredicate]| {
+            sink.list_records(predicates)
+                .expect("Unable to list records")
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            list(&[]),
+            vec![unauthenticated.clone(), denied.clone(), allowed.clone()]
+        );
+        assert_eq!(
+            list(&[AuditRecordPredicate::Identity("key:abcd".into())]),
+            vec![denied.clone(), allowed.clone()]
+        );
+        assert_eq!(
+            list(&[AuditRecordPredicate::Outcome(AuditOutcome::Denied)]),
+            vec![unauthenticated.clone(), denied.clone()]
+        );
+        assert_eq!(
+            list(&[
+                AuditRecordPredicate::Since(start + Duration::from_secs(10)),
+                AuditRecordPredicate::Until(start + Duration::from_secs(20)),
+            ]),
+            vec![denied.clone()]
+        );
+        assert_eq!(
+            list(&[
+                AuditRecordPredicate::Identity("key:abcd".into()),
+                AuditRecordPredicate::Outcome(AuditOutcome::Allowed),
+            ]),
+            vec![allowed]
+        );
+    }
+
+    /// Creates a connection pool for an in-memory SQLite database with only a single connection
+    /// available. Each connection is backed by a different in-memory SQLite database, so limiting
+    /// the pool to a single connection ensures that the same DB is used for all operations.
+    fn create_connection_pool_and_migrate() -> Pool<ConnectionManager<SqliteConnection>> {
+        let connection_manager = ConnectionManager::<SqliteConnection>::new(":memory:");
+        let pool = Pool::builder()
+            .max_size(1)
+            .build(connection_manager)
+            .expect("Failed to build connection pool");
+
+        run_sqlite_migrations(&*pool.get().expect("Failed to get connection for migrations"))
+            .expect("Failed to run migrations");
+
+        pool
+    }
+}
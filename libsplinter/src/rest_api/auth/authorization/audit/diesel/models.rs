@@ -0,0 +1,39 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::schema::rest_api_audit_log;
+
+#[derive(Insertable, PartialEq, Debug)]
+#[table_name = "rest_api_audit_log"]
+pub(super) struct NewAuditRecordModel {
+    pub identity: Option<String>,
+    pub method: String,
+    pub path: String,
+    pub permission_id: Option<String>,
+    pub handler: Option<String>,
+    pub outcome: String,
+    pub recorded_at: i64,
+}
+
+#[derive(Queryable, PartialEq, Debug)]
+pub(super) struct AuditRecordModel {
+    pub id: i64,
+    pub identity: Option<String>,
+    pub method: String,
+    pub path: String,
+    pub permission_id: Option<String>,
+    pub handler: Option<String>,
+    pub outcome: String,
+    pub recorded_at: i64,
+}
@@ -0,0 +1,50 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::convert::TryFrom;
+
+use diesel::{dsl::insert_into, prelude::*};
+
+use crate::rest_api::auth::authorization::audit::{
+    diesel::{models::NewAuditRecordModel, schema::rest_api_audit_log},
+    AuditRecord, AuditSinkError,
+};
+
+use super::AuditSinkOperations;
+
+pub trait AuditSinkAddRecord {
+    fn add_record(&self, record: AuditRecord) -> Result<(), AuditSinkError>;
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> AuditSinkAddRecord for AuditSinkOperations<'a, diesel::sqlite::SqliteConnection> {
+    fn add_record(&self, record: AuditRecord) -> Result<(), AuditSinkError> {
+        insert_into(rest_api_audit_log::table)
+            .values(NewAuditRecordModel::try_from(record)?)
+            .execute(self.conn)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> AuditSinkAddRecord for AuditSinkOperations<'a, diesel::pg::PgConnection> {
+    fn add_record(&self, record: AuditRecord) -> Result<(), AuditSinkError> {
+        insert_into(rest_api_audit_log::table)
+            .values(NewAuditRecordModel::try_from(record)?)
+            .execute(self.conn)?;
+
+        Ok(())
+    }
+}
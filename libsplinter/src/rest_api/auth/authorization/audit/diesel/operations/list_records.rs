@@ -0,0 +1,73 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::convert::TryInto;
+
+use diesel::prelude::*;
+
+use crate::rest_api::auth::authorization::audit::{
+    diesel::{models::AuditRecordModel, schema::rest_api_audit_log, to_timestamp},
+    AuditRecord, AuditRecordPredicate, AuditSinkError,
+};
+
+use super::AuditSinkOperations;
+
+pub trait AuditSinkListRecords {
+    fn list_records(
+        &self,
+        predicates: &[AuditRecordPredicate],
+    ) -> Result<Box<dyn ExactSizeIterator<Item = AuditRecord>>, AuditSinkError>;
+}
+
+impl<'a, C> AuditSinkListRecords for AuditSinkOperations<'a, C>
+where
+    C: diesel::Connection,
+    String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
+    i64: diesel::deserialize::FromSql<diesel::sql_types::BigInt, C::Backend>,
+{
+    fn list_records(
+        &self,
+        predicates: &[AuditRecordPredicate],
+    ) -> Result<Box<dyn ExactSizeIterator<Item = AuditRecord>>, AuditSinkError> {
+        let mut query = rest_api_audit_log::table
+            .into_boxed()
+            .select(rest_api_audit_log::all_columns);
+
+        for predicate in predicates {
+            match predicate {
+                AuditRecordPredicate::Identity(identity) => {
+                    query = query.filter(rest_api_audit_log::identity.eq(identity.clone()));
+                }
+                AuditRecordPredicate::Outcome(outcome) => {
+                    query = query.filter(rest_api_audit_log::outcome.eq(outcome.to_string()));
+                }
+                AuditRecordPredicate::Since(since) => {
+                    query = query.filter(rest_api_audit_log::recorded_at.ge(to_timestamp(*since)?));
+                }
+                AuditRecordPredicate::Until(until) => {
+                    query = query.filter(rest_api_audit_log::recorded_at.lt(to_timestamp(*until)?));
+                }
+            }
+        }
+
+        let records = query
+            .order(rest_api_audit_log::id.desc())
+            .load::<AuditRecordModel>(self.conn)?
+            .into_iter()
+            .map(|model| model.try_into())
+            .collect::<Result<Vec<AuditRecord>, _>>()?;
+
+        Ok(Box::new(records.into_iter()))
+    }
+}
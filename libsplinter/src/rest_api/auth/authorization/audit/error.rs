@@ -0,0 +1,55 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::error::{InternalError, InvalidStateError};
+
+/// Errors that may occur during [AuditSink](super::AuditSink) operations.
+#[derive(Debug)]
+pub enum AuditSinkError {
+    InternalError(InternalError),
+    InvalidState(InvalidStateError),
+}
+
+impl fmt::Display for AuditSinkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AuditSinkError::InternalError(err) => err.fmt(f),
+            AuditSinkError::InvalidState(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for AuditSinkError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AuditSinkError::InternalError(err) => Some(err),
+            AuditSinkError::InvalidState(err) => Some(err),
+        }
+    }
+}
+
+impl From<InternalError> for AuditSinkError {
+    fn from(err: InternalError) -> Self {
+        AuditSinkError::InternalError(err)
+    }
+}
+
+impl From<InvalidStateError> for AuditSinkError {
+    fn from(err: InvalidStateError) -> Self {
+        AuditSinkError::InvalidState(err)
+    }
+}
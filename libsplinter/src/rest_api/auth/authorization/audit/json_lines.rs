@@ -0,0 +1,275 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A file-backed implementation of the [AuditSink] that writes one JSON object per line.
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, UNIX_EPOCH};
+
+use crate::error::InternalError;
+
+use super::{AuditRecord, AuditRecordPredicate, AuditSink, AuditSinkError};
+
+/// An [AuditSink] that appends records to a file in the JSON Lines format.
+///
+/// Each record is written as a single JSON object on its own line, with the time of the decision
+/// in seconds since the epoch. Writes made through clones of the same sink are serialized, so
+/// lines are never interleaved; multiple sinks should not be constructed for the same file.
+///
+/// Listing records reads the whole file, so this sink is best suited to shipping the log to an
+/// external system; the [DieselAuditSink](super::DieselAuditSink) is better suited to frequent
+/// queries.
+#[derive(Clone)]
+pub struct JsonLinesAuditSink {
+    path: PathBuf,
+    lock: Arc<Mutex<()>>,
+}
+
+impl JsonLinesAuditSink {
+    /// Creates a new JsonLinesAuditSink. The file will be created when the first record is
+    /// written, if it does not already exist.
+    ///
+    /// # Arguments
+    ///
+    ///  * `path`: the path of the file that records are appended to
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            path: path.into(),
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+}
+
+impl AuditSink for JsonLinesAuditSink {
+    fn record(&self, record: AuditRecord) -> Result<(), AuditSinkError> {
+        let mut line = serde_json::to_string(&JsonAuditRecord::from(record))
+            .map_err(|err| InternalError::from_source(Box::new(err)))?;
+        line.push('\n');
+
+        let _guard = self
+            .lock
+            .lock()
+            .map_err(|_| InternalError::with_message("Audit log lock poisoned".into()))?;
+
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|err| {
+                AuditSinkError::InternalError(InternalError::from_source_with_message(
+                    Box::new(err),
+                    format!("Unable to write to audit log {}", self.path.display()),
+                ))
+            })
+    }
+
+    fn list_records(
+        &self,
+        predicates: &[AuditRecordPredicate],
+    ) -> Result<Box<dyn ExactSizeIterator<Item = AuditRecord>>, AuditSinkError> {
+        let _guard = self
+            .lock
+            .lock()
+            .map_err(|_| InternalError::with_message("Audit log lock poisoned".into()))?;
+
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            // Nothing has been recorded yet
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                return Ok(Box::new(vec![].into_iter()))
+            }
+            Err(err) => {
+                return Err(AuditSinkError::InternalError(
+                    InternalError::from_source_with_message(
+                        Box::new(err),
+                        format!("Unable to open audit log {}", self.path.display()),
+                    ),
+                ))
+            }
+        };
+
+        let mut records = vec![];
+        for (index, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|err| InternalError::from_source(Box::new(err)))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let record = serde_json::from_str::<JsonAuditRecord>(&line)
+                .map_err(|err| {
+                    InternalError::from_source_with_message(
+                        Box::new(err),
+                        format!("Invalid record on line {} of audit log", index + 1),
+                    )
+                })?
+                .into_record()?;
+
+            if predicates
+                .iter()
+                .all(|predicate| predicate.matches(&record))
+            {
+                records.push(record);
+            }
+        }
+
+        // Records are appended, so the most recent is last
+        records.reverse();
+
+        Ok(Box::new(records.into_iter()))
+    }
+
+    fn clone_box(&self) -> Box<dyn AuditSink> {
+        Box::new(self.clone())
+    }
+}
+
+/// The JSON representation of an [AuditRecord], as written to the file
+#[derive(Serialize, Deserialize)]
+struct JsonAuditRecord {
+    identity: Option<String>,
+    method: String,
+    path: String,
+    permission_id: Option<String>,
+    handler: Option<String>,
+    outcome: String,
+    timestamp: u64,
+}
+
+impl From<AuditRecord> for JsonAuditRecord {
+    fn from(record: AuditRecord) -> Self {
+        Self {
+            identity: record.identity,
+            method: record.method,
+            path: record.path,
+            permission_id: record.permission_id,
+            handler: record.handler,
+            outcome: record.outcome.to_string(),
+            timestamp: record
+                .timestamp
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or(0),
+        }
+    }
+}
+
+impl JsonAuditRecord {
+    fn into_record(self) -> Result<AuditRecord, AuditSinkError> {
+        // The record is created directly, vs using the builder, as it was validated by the
+        // builder before it was written to the file.
+        Ok(AuditRecord {
+            identity: self.identity,
+            method: self.method,
+            path: self.path,
+            permission_id: self.permission_id,
+            handler: self.handler,
+            outcome: self
+                .outcome
+                .parse()
+                .map_err(|err| InternalError::from_source(Box::new(err)))?,
+            timestamp: UNIX_EPOCH + Duration::from_secs(self.timestamp),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempdir::TempDir;
+
+    use crate::rest_api::auth::authorization::audit::{AuditOutcome, AuditRecordBuilder};
+
+    /// Verify that the `JsonLinesAuditSink` appends records to its file and lists them with
+    /// predicates applied.
+    ///
+    /// 1. Create a `JsonLinesAuditSink` for a file that does not exist yet and verify that no
+    ///    records are listed.
+    /// 2. Record an allowed request and a denied request.
+    /// 3. Verify that the file contains one JSON object per line.
+    /// 4. Verify that both records are listed, most recent first, and that the `Outcome`
+    ///    predicate filters them.
+    /// 5. Verify that a new sink for the same file lists the same records.
+    #[test]
+    fn json_lines_record_and_list() {
+        let temp_dir =
+            TempDir::new("json_lines_record_and_list").expect("Failed to create temp dir");
+        let path = temp_dir.path().join("audit.jsonl");
+
+        let sink = JsonLinesAuditSink::new(path.clone());
+        assert_eq!(
+            sink.list_records(&[])
+                .expect("Unable to list records")
+                .len(),
+            0
+        );
+
+        let start = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        let allowed = AuditRecordBuilder::new()
+            .with_identity("user:some-user-id".into())
+            .with_method("GET".into())
+            .with_path("/admin/circuits".into())
+            .with_permission_id("circuit.read".into())
+            .with_handler("rbac".into())
+            .with_outcome(AuditOutcome::Allowed)
+            .with_timestamp(start)
+            .build()
+            .expect("Unable to build record");
+        let denied = AuditRecordBuilder::new()
+            .with_identity("user:some-user-id".into())
+            .with_method("POST".into())
+            .with_path("/admin/submit".into())
+            .with_permission_id("circuit.write".into())
+            .with_outcome(AuditOutcome::Denied)
+            .with_timestamp(start + Duration::from_secs(1))
+            .build()
+            .expect("Unable to build record");
+
+        sink.record(allowed.clone()).expect("Unable to record");
+        sink.record(denied.clone()).expect("Unable to record");
+
+        let contents = std::fs::read_to_string(&path).expect("Unable to read audit log");
+        let lines = contents.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        let first: serde_json::Value =
+            serde_json::from_str(lines[0]).expect("Audit log line is not valid JSON");
+        assert_eq!(first["outcome"], "allowed");
+        assert_eq!(first["handler"], "rbac");
+
+        assert_eq!(
+            sink.list_records(&[])
+                .expect("Unable to list records")
+                .collect::<Vec<_>>(),
+            vec![denied.clone(), allowed.clone()]
+        );
+        assert_eq!(
+            sink.list_records(&[AuditRecordPredicate::Outcome(AuditOutcome::Denied)])
+                .expect("Unable to list records")
+                .collect::<Vec<_>>(),
+            vec![denied.clone()]
+        );
+
+        assert_eq!(
+            JsonLinesAuditSink::new(path)
+                .list_records(&[])
+                .expect("Unable to list records")
+                .collect::<Vec<_>>(),
+            vec![denied, allowed]
+        );
+    }
+}
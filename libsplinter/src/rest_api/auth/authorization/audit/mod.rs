@@ -0,0 +1,277 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An audit log of the REST API's authorization decisions
+//!
+//! When an [`AuditSink`] is provided to the REST API, every request to an endpoint that requires
+//! authorization is recorded as an [`AuditRecord`]: the client's identity, the request's method
+//! and path, the permission that was checked, the authorization handler that made the decision,
+//! the outcome, and the time. Requests to endpoints that do not require authorization, and requests
+//! to unknown endpoints, are not recorded.
+//!
+//! This module provides two sinks: [`DieselAuditSink`], which stores records in a database, and
+//! [`JsonLinesAuditSink`], which appends records to a file with one JSON object per line. The REST
+//! API writes records to its sink on a background thread, so that requests do not wait for them to
+//! be written.
+
+mod background;
+#[cfg(feature = "diesel")]
+mod diesel;
+mod error;
+mod json_lines;
+#[cfg(feature = "rest-api-actix")]
+mod routes;
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::SystemTime;
+
+use crate::error::{InvalidArgumentError, InvalidStateError};
+
+#[cfg(feature = "diesel")]
+pub use self::diesel::DieselAuditSink;
+pub(in crate::rest_api) use background::BackgroundAuditSink;
+pub use error::AuditSinkError;
+pub use json_lines::JsonLinesAuditSink;
+#[cfg(feature = "rest-api-actix")]
+pub use routes::AuditLogResourceProvider;
+
+/// The outcome of an authorization decision
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AuditOutcome {
+    /// The request was allowed
+    Allowed,
+    /// The request was denied
+    Denied,
+}
+
+impl fmt::Display for AuditOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AuditOutcome::Allowed => f.write_str("allowed"),
+            AuditOutcome::Denied => f.write_str("denied"),
+        }
+    }
+}
+
+impl FromStr for AuditOutcome {
+    type Err = InvalidArgumentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "allowed" => Ok(AuditOutcome::Allowed),
+            "denied" => Ok(AuditOutcome::Denied),
+            _ => Err(InvalidArgumentError::new(
+                "outcome".into(),
+                format!("unknown audit outcome: {}", s),
+            )),
+        }
+    }
+}
+
+/// A record of a single authorization decision made by the REST API
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuditRecord {
+    identity: Option<String>,
+    method: String,
+    path: String,
+    permission_id: Option<String>,
+    handler: Option<String>,
+    outcome: AuditOutcome,
+    timestamp: SystemTime,
+}
+
+impl AuditRecord {
+    /// Returns the client's identity, prefixed by its type (e.g. "key:<public key>"), if the
+    /// client could be identified.
+    pub fn identity(&self) -> Option<&str> {
+        self.identity.as_deref()
+    }
+
+    /// Returns the HTTP method of the request.
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    /// Returns the path of the request.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the ID of the permission that was checked, if the endpoint requires one.
+    pub fn permission_id(&self) -> Option<&str> {
+        self.permission_id.as_deref()
+    }
+
+    /// Returns the name of the authorization handler that made the decision. This is `None` if
+    /// no handler was consulted or none of them made a decision, in which case the request is
+    /// allowed only if the endpoint does not require a specific permission.
+    pub fn handler(&self) -> Option<&str> {
+        self.handler.as_deref()
+    }
+
+    /// Returns the outcome of the decision.
+    pub fn outcome(&self) -> AuditOutcome {
+        self.outcome
+    }
+
+    /// Returns the time at which the decision was made.
+    pub fn timestamp(&self) -> SystemTime {
+        self.timestamp
+    }
+}
+
+/// A builder to create new audit records.
+#[derive(Default)]
+pub struct AuditRecordBuilder {
+    identity: Option<String>,
+    method: Option<String>,
+    path: Option<String>,
+    permission_id: Option<String>,
+    handler: Option<String>,
+    outcome: Option<AuditOutcome>,
+    timestamp: Option<SystemTime>,
+}
+
+impl AuditRecordBuilder {
+    /// Constructs a new builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the client's identity.
+    pub fn with_identity(mut self, identity: String) -> Self {
+        self.identity = Some(identity);
+        self
+    }
+
+    /// Sets the HTTP method of the request.
+    pub fn with_method(mut self, method: String) -> Self {
+        self.method = Some(method);
+        self
+    }
+
+    /// Sets the path of the request.
+    pub fn with_path(mut self, path: String) -> Self {
+        self.path = Some(path);
+        self
+    }
+
+    /// Sets the ID of the permission that was checked.
+    pub fn with_permission_id(mut self, permission_id: String) -> Self {
+        self.permission_id = Some(permission_id);
+        self
+    }
+
+    /// Sets the name of the authorization handler that made the decision.
+    pub fn with_handler(mut self, handler: String) -> Self {
+        self.handler = Some(handler);
+        self
+    }
+
+    /// Sets the outcome of the decision.
+    pub fn with_outcome(mut self, outcome: AuditOutcome) -> Self {
+        self.outcome = Some(outcome);
+        self
+    }
+
+    /// Sets the time at which the decision was made. Defaults to the current time.
+    pub fn with_timestamp(mut self, timestamp: SystemTime) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Builds the new audit record.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`InvalidStateError`] if the method, path, or outcome was not provided, or if
+    /// the method or path is empty.
+    pub fn build(self) -> Result<AuditRecord, InvalidStateError> {
+        let method = self
+            .method
+            .filter(|method| !method.is_empty())
+            .ok_or_else(|| {
+                InvalidStateError::with_message(
+                    "An audit record requires a non-empty method".into(),
+                )
+            })?;
+
+        let path = self.path.filter(|path| !path.is_empty()).ok_or_else(|| {
+            InvalidStateError::with_message("An audit record requires a non-empty path".into())
+        })?;
+
+        let outcome = self.outcome.ok_or_else(|| {
+            InvalidStateError::with_message("An audit record requires an outcome".into())
+        })?;
+
+        Ok(AuditRecord {
+            identity: self.identity,
+            method,
+            path,
+            permission_id: self.permission_id,
+            handler: self.handler,
+            outcome,
+            timestamp: self.timestamp.unwrap_or_else(SystemTime::now),
+        })
+    }
+}
+
+/// Predicates for filtering the audit records returned by [`AuditSink::list_records`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum AuditRecordPredicate {
+    /// Only include records for the given identity
+    Identity(String),
+    /// Only include records with the given outcome
+    Outcome(AuditOutcome),
+    /// Only include records made at or after the given time
+    Since(SystemTime),
+    /// Only include records made before the given time
+    Until(SystemTime),
+}
+
+impl AuditRecordPredicate {
+    /// Returns whether or not the given record satisfies this predicate.
+    pub fn matches(&self, record: &AuditRecord) -> bool {
+        match self {
+            AuditRecordPredicate::Identity(identity) => {
+                record.identity.as_deref() == Some(identity.as_str())
+            }
+            AuditRecordPredicate::Outcome(outcome) => record.outcome == *outcome,
+            AuditRecordPredicate::Since(since) => record.timestamp >= *since,
+            AuditRecordPredicate::Until(until) => record.timestamp < *until,
+        }
+    }
+}
+
+/// Defines methods for recording and querying the REST API's authorization decisions.
+pub trait AuditSink: Send + Sync {
+    /// Records an authorization decision.
+    fn record(&self, record: AuditRecord) -> Result<(), AuditSinkError>;
+
+    /// Lists the records that satisfy all of the given predicates, from most recent to oldest.
+    fn list_records(
+        &self,
+        predicates: &[AuditRecordPredicate],
+    ) -> Result<Box<dyn ExactSizeIterator<Item = AuditRecord>>, AuditSinkError>;
+
+    /// Clone into a boxed, dynamically dispatched sink
+    fn clone_box(&self) -> Box<dyn AuditSink>;
+}
+
+impl Clone for Box<dyn AuditSink> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
@@ -0,0 +1,259 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module provides the following endpoint:
+//!
+//! * `GET /authorization/audit` for listing the REST API's authorization decisions
+
+use std::time::{Duration, UNIX_EPOCH};
+
+use actix_web::{error::BlockingError, web, Error, HttpRequest, HttpResponse};
+use futures::{future::IntoFuture, Future};
+
+use crate::protocol;
+use crate::rest_api::{
+    actix_web_1::{Method, ProtocolVersionRangeGuard, Resource},
+    auth::authorization::audit::{AuditOutcome, AuditRecordPredicate, AuditSink},
+    paging::get_response_paging_info,
+    ErrorResponse,
+};
+
+use super::{
+    resources::{AuditRecordResponse, ListAuditRecordsQuery, ListAuditRecordsResponse},
+    AUTHORIZATION_AUDIT_READ_PERMISSION,
+};
+
+pub fn make_audit_resource(audit_sink: Box<dyn AuditSink>) -> Resource {
+    Resource::build("/authorization/audit")
+        .add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::AUTHORIZATION_AUDIT_MIN,
+            protocol::AUTHORIZATION_PROTOCOL_VERSION,
+        ))
+        .add_method(
+            Method::Get,
+            AUTHORIZATION_AUDIT_READ_PERMISSION,
+            move |r, _| list_audit_records(r, audit_sink.clone()),
+        )
+}
+
+fn list_audit_records(
+    req: HttpRequest,
+    audit_sink: Box<dyn AuditSink>,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    let query = match web::Query::<ListAuditRecordsQuery>::from_query(req.query_string()) {
+        Ok(query) => query.into_inner(),
+        Err(_) => {
+            return Box::new(
+                HttpResponse::BadRequest()
+                    .json(ErrorResponse::bad_request("Invalid query"))
+                    .into_future(),
+            )
+        }
+    };
+
+    let mut predicates = vec![];
+    let mut new_queries = vec![];
+    if let Some(identity) = query.identity {
+        new_queries.push(format!("identity={}", identity));
+        predicates.push(AuditRecordPredicate::Identity(identity));
+    }
+    if let Some(outcome) = query.outcome {
+        match outcome.parse::<AuditOutcome>() {
+            Ok(parsed) => predicates.push(AuditRecordPredicate::Outcome(parsed)),
+            Err(_) => {
+                return Box::new(
+                    HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request(&format!(
+                            "Invalid outcome value passed: {}. Expected 'allowed' or 'denied'",
+                            outcome
+                        )))
+                        .into_future(),
+                )
+            }
+        }
+        new_queries.push(format!("outcome={}", outcome));
+    }
+    if let Some(since) = query.since {
+        new_queries.push(format!("since={}", since));
+        predicates.push(AuditRecordPredicate::Since(
+            UNIX_EPOCH + Duration::from_secs(since),
+        ));
+    }
+    if let Some(until) = query.until {
+        new_queries.push(format!("until={}", until));
+        predicates.push(AuditRecordPredicate::Until(
+            UNIX_EPOCH + Duration::from_secs(until),
+        ));
+    }
+
+    let mut link = req.uri().path().to_string();
+    if !new_queries.is_empty() {
+        link.push_str(&format!("?{}&", new_queries.join("&")));
+    }
+
+    let limit = query.limit;
+    let offset = query.offset;
+
+    Box::new(
+        web::block(move || {
+            let records = audit_sink
+                .list_records(&predicates)
+                .map_err(|err| err.to_string())?;
+
+            let total = records.len();
+            let records = records
+                .skip(offset)
+                .take(limit)
+                .map(|record| AuditRecordResponse::from(&record))
+                .collect::<Vec<_>>();
+
+            Ok((records, total))
+        })
+        .then(move |res: Result<_, BlockingError<String>>| match res {
+            Ok((records, total)) => Ok(HttpResponse::Ok().json(ListAuditRecordsResponse {
+                data: records,
+                paging: get_response_paging_info(Some(limit), Some(offset), &link, total),
+            })),
+            Err(err) => {
+                error!("Unable to list audit records: {}", err);
+                Ok(HttpResponse::InternalServerError().json(ErrorResponse::internal_error()))
+            }
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use reqwest::{blocking::Client, StatusCode, Url};
+    use tempdir::TempDir;
+
+    use crate::rest_api::actix_web_1::{RestApiBuilder, RestApiShutdownHandle};
+    use crate::rest_api::auth::authorization::audit::{AuditRecordBuilder, JsonLinesAuditSink};
+
+    /// Verifies that the `GET /authorization/audit` endpoint lists records, most recent first,
+    /// applies filters, and pages the results.
+    ///
+    /// 1. Record three decisions (two allowed, one denied) in a `JsonLinesAuditSink`
+    /// 2. Run the REST API with the audit endpoint
+    /// 3. Verify that listing without filters returns all three records, most recent first
+    /// 4. Verify that filtering by outcome returns only the denied record
+    /// 5. Verify that `limit` and `offset` page the results and that the total is reported
+    /// 6. Verify that an invalid outcome is rejected
+    #[test]
+    fn list_audit_records() {
+        let temp_dir = TempDir::new("list_audit_records").expect("Failed to create temp dir");
+        let sink = JsonLinesAuditSink::new(temp_dir.path().join("audit.jsonl"));
+
+        let start = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        for (i, outcome) in [
+            AuditOutcome::Allowed,
+            AuditOutcome::Denied,
+            AuditOutcome::Allowed,
+        ]
+        .iter()
+        .enumerate()
+        {
+            sink.record(
+                AuditRecordBuilder::new()
+                    .with_identity("key:abcd".into())
+                    .with_method("GET".into())
+                    .with_path(format!("/test/{}", i))
+                    .with_permission_id("test.read".into())
+                    .with_outcome(*outcome)
+                    .with_timestamp(start + Duration::from_secs(i as u64))
+                    .build()
+                    .expect("Unable to build record"),
+            )
+            .expect("Unable to record");
+        }
+
+        let (shutdown_handle, join_handle, bind_url) =
+            run_rest_api_on_open_port(vec![make_audit_resource(Box::new(sink))]);
+
+        let url = Url::parse(&format!("http://{}/authorization/audit", bind_url))
+            .expect("Failed to parse URL");
+
+        let list = |query: &[(&str, &str)]| {
+            Client::new()
+                .get(url.clone())
+                .query(query)
+                .header(
+                    "SplinterProtocolVersion",
+                    protocol::AUTHORIZATION_PROTOCOL_VERSION,
+                )
+                .send()
+                .expect("Failed to perform request")
+        };
+
+        let resp = list(&[]);
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: ListAuditRecordsResponse = resp.json().expect("Failed to deserialize body");
+        assert_eq!(
+            body.data
+                .iter()
+                .map(|record| record.path.as_str())
+                .collect::<Vec<_>>(),
+            vec!["/test/2", "/test/1", "/test/0"]
+        );
+        assert_eq!(body.paging.total, 3);
+
+        let resp = list(&[("outcome", "denied")]);
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: ListAuditRecordsResponse = resp.json().expect("Failed to deserialize body");
+        assert_eq!(body.data.len(), 1);
+        assert_eq!(body.data[0].path, "/test/1");
+        assert_eq!(body.data[0].outcome, "denied");
+        assert_eq!(body.data[0].identity.as_deref(), Some("key:abcd"));
+
+        let resp = list(&[("limit", "1"), ("offset", "1")]);
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: ListAuditRecordsResponse = resp.json().expect("Failed to deserialize body");
+        assert_eq!(body.data.len(), 1);
+        assert_eq!(body.data[0].path, "/test/1");
+        assert_eq!(body.paging.total, 3);
+
+        let resp = list(&[("outcome", "maybe")]);
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        shutdown_handle
+            .shutdown()
+            .expect("Unable to shutdown rest api");
+        join_handle.join().expect("Unable to join rest api thread");
+    }
+
+    fn run_rest_api_on_open_port(
+        resources: Vec<Resource>,
+    ) -> (RestApiShutdownHandle, std::thread::JoinHandle<()>, String) {
+        #[cfg(not(feature = "https-bind"))]
+        let bind = "127.0.0.1:0";
+        #[cfg(feature = "https-bind")]
+        let bind = crate::rest_api::BindConfig::Http("127.0.0.1:0".into());
+
+        let result = RestApiBuilder::new()
+            .with_bind(bind)
+            .add_resources(resources.clone())
+            .build_insecure()
+            .expect("Failed to build REST API")
+            .run_insecure();
+        match result {
+            Ok((shutdown_handle, join_handle)) => {
+                let port = shutdown_handle.port_numbers()[0];
+                (shutdown_handle, join_handle, format!("127.0.0.1:{}", port))
+            }
+            Err(err) => panic!("Failed to run REST API: {}", err),
+        }
+    }
+}
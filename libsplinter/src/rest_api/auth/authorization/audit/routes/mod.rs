@@ -0,0 +1,55 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! REST API endpoints for the audit log
+
+mod actix;
+mod resources;
+
+use crate::rest_api::{
+    actix_web_1::{Resource, RestResourceProvider},
+    auth::authorization::Permission,
+};
+
+use super::AuditSink;
+
+const AUTHORIZATION_AUDIT_READ_PERMISSION: Permission = Permission::Check {
+    permission_id: "authorization.audit.read",
+    permission_display_name: "Audit log read",
+    permission_description: "Allows the client to read the REST API audit log",
+};
+
+/// Provides the following endpoint as a REST API resource:
+///
+/// * `GET /authorization/audit` - List the REST API's authorization decisions, most recent first
+///
+/// The endpoint requires the "authorization.audit.read" permission. This permission is not granted
+/// to any role by default, so only administrators (such as identities with the RBAC "admin" role)
+/// may query the audit log unless it is explicitly assigned.
+pub struct AuditLogResourceProvider {
+    audit_sink: Box<dyn AuditSink>,
+}
+
+impl AuditLogResourceProvider {
+    /// Constructs a new resource provider that queries the given sink.
+    pub fn new(audit_sink: Box<dyn AuditSink>) -> Self {
+        Self { audit_sink }
+    }
+}
+
+impl RestResourceProvider for AuditLogResourceProvider {
+    fn resources(&self) -> Vec<Resource> {
+        vec![actix::make_audit_resource(self.audit_sink.clone())]
+    }
+}
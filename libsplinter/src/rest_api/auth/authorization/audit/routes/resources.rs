@@ -0,0 +1,82 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module provides resources for the audit log's REST API endpoint
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::rest_api::{
+    auth::authorization::audit::AuditRecord,
+    paging::{Paging, DEFAULT_LIMIT, DEFAULT_OFFSET},
+};
+
+#[derive(Deserialize)]
+pub struct ListAuditRecordsQuery {
+    pub identity: Option<String>,
+    pub outcome: Option<String>,
+    /// Only include records made at or after this time, in seconds since the epoch
+    pub since: Option<u64>,
+    /// Only include records made before this time, in seconds since the epoch
+    pub until: Option<u64>,
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+    #[serde(default = "default_offset")]
+    pub offset: usize,
+}
+
+fn default_limit() -> usize {
+    DEFAULT_LIMIT
+}
+
+fn default_offset() -> usize {
+    DEFAULT_OFFSET
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct AuditRecordResponse {
+    pub identity: Option<String>,
+    pub method: String,
+    pub path: String,
+    pub permission_id: Option<String>,
+    pub handler: Option<String>,
+    pub outcome: String,
+    /// The time of the decision, in seconds since the epoch
+    pub timestamp: u64,
+}
+
+impl From<&AuditRecord> for AuditRecordResponse {
+    fn from(record: &AuditRecord) -> Self {
+        Self {
+            identity: record.identity().map(String::from),
+            method: record.method().into(),
+            path: record.path().into(),
+            permission_id: record.permission_id().map(String::from),
+            handler: record.handler().map(String::from),
+            outcome: record.outcome().to_string(),
+            timestamp: to_seconds(record.timestamp()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListAuditRecordsResponse {
+    pub data: Vec<AuditRecordResponse>,
+    pub paging: Paging,
+}
+
+fn to_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
@@ -1,4 +1,4 @@
-// Copyright 2018-2020 Cargill Incorporated
+// Copyright 2018-2021 Cargill Incorporated
 //
 // Licensed under the Apache License, Version 2.0 (the "License");
 // you may not use this file except in compliance with the License.
@@ -15,11 +15,10 @@
 //! An authorization handler that allows write permissions to be temporarily revoked
 
 mod routes;
+pub mod store;
 
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
-};
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
 
 use crate::error::InternalError;
 #[cfg(feature = "authorization-handler-rbac")]
@@ -28,8 +27,14 @@ use crate::rest_api::auth::authorization::rbac::store::{
 };
 use crate::rest_api::auth::identity::Identity;
 
+use self::store::{MaintenanceModeChange, MaintenanceModeChangeBuilder, MaintenanceModeStore};
+
 use super::{AuthorizationHandler, AuthorizationHandlerResult};
 
+/// The changed-by value recorded for changes made with
+/// [`MaintenanceModeAuthorizationHandler::set_maintenance_mode`]
+const LOCAL_CHANGED_BY: &str = "local";
+
 /// An authorization handler that allows write permissions to be temporarily revoked
 ///
 /// For the purposes of this authorization handler, a write permission is any permission whose ID
@@ -42,9 +47,18 @@ use super::{AuthorizationHandler, AuthorizationHandlerResult};
 /// non-read permission will always result in a [`AuthorizationHandlerResult::Deny`] result; if
 /// disabled, all permission checks will always result in a [`AuthorizationHandlerResult::Continue`]
 /// result.
+///
+/// Every change to maintenance mode is recorded as a [`MaintenanceModeChange`], which captures who
+/// made the change, when, and why. If a [`MaintenanceModeStore`] is provided, changes are persisted
+/// so maintenance mode survives restarts; otherwise, changes are only kept in memory. A change that
+/// enables maintenance mode may be scheduled to end, after which maintenance mode is considered
+/// disabled.
 #[derive(Clone, Default)]
 pub struct MaintenanceModeAuthorizationHandler {
-    maintenance_mode: Arc<AtomicBool>,
+    current_change: Arc<RwLock<Option<MaintenanceModeChange>>>,
+    // Only used when there is no store
+    changes: Arc<RwLock<Vec<MaintenanceModeChange>>>,
+    store: Option<Box<dyn MaintenanceModeStore>>,
     #[cfg(feature = "authorization-handler-rbac")]
     rbac_store: Option<Box<dyn RoleBasedAuthorizationStore>>,
 }
@@ -63,15 +77,103 @@ impl MaintenanceModeAuthorizationHandler {
         }
     }
 
-    /// Returns whether or not maintenance mode is enabled
+    /// Persists maintenance mode changes in the given store, loading the current state of
+    /// maintenance mode from it
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`InternalError`] if the current change could not be loaded from the store.
+    pub fn with_store(
+        mut self,
+        store: Box<dyn MaintenanceModeStore>,
+    ) -> Result<Self, InternalError> {
+        let current_change = store
+            .get_current_change()
+            .map_err(|err| InternalError::from_source(Box::new(err)))?;
+        self.current_change = Arc::new(RwLock::new(current_change));
+        self.changes = Arc::default();
+        self.store = Some(store);
+        Ok(self)
+    }
+
+    /// Returns whether or not maintenance mode is enabled, taking any scheduled end into account
     pub fn is_maintenance_mode_enabled(&self) -> bool {
-        self.maintenance_mode.load(Ordering::Relaxed)
+        self.current_change()
+            .map(|change| change.is_enabled_at(SystemTime::now()))
+            .unwrap_or(false)
     }
 
-    /// Sets whether or not maintenance mode is enabled
-    pub fn set_maintenance_mode(&self, maintenance_mode: bool) {
-        self.maintenance_mode
-            .store(maintenance_mode, Ordering::Relaxed);
+    /// Returns the most recent maintenance mode change, if any
+    pub fn current_change(&self) -> Option<MaintenanceModeChange> {
+        // The value is only ever replaced as a whole, so a poisoned lock still holds a valid value
+        self.current_change
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+
+    /// Returns all maintenance mode changes, from oldest to most recent
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`InternalError`] if the changes could not be loaded from the store.
+    pub fn list_changes(&self) -> Result<Vec<MaintenanceModeChange>, InternalError> {
+        match &self.store {
+            Some(store) => Ok(store
+                .list_changes()
+                .map_err(|err| InternalError::from_source(Box::new(err)))?
+                .collect()),
+            None => Ok(self
+                .changes
+                .read()
+                .map_err(|_| InternalError::with_message("Changes lock poisoned".into()))?
+                .clone()),
+        }
+    }
+
+    /// Records the given change, which becomes the current state of maintenance mode
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`InternalError`] if the change could not be persisted.
+    pub fn change_maintenance_mode(
+        &self,
+        change: MaintenanceModeChange,
+    ) -> Result<(), InternalError> {
+        let mut current_change = self
+            .current_change
+            .write()
+            .unwrap_or_else(|err| err.into_inner());
+
+        match &self.store {
+            Some(store) => store
+                .add_change(change.clone())
+                .map_err(|err| InternalError::from_source(Box::new(err)))?,
+            None => self
+                .changes
+                .write()
+                .map_err(|_| InternalError::with_message("Changes lock poisoned".into()))?
+                .push(change.clone()),
+        }
+
+        *current_change = Some(change);
+
+        Ok(())
+    }
+
+    /// Sets whether or not maintenance mode is enabled, recording the change as made locally
+    /// without a reason
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`InternalError`] if the change could not be persisted.
+    pub fn set_maintenance_mode(&self, maintenance_mode: bool) -> Result<(), InternalError> {
+        let change = MaintenanceModeChangeBuilder::new()
+            .with_enabled(maintenance_mode)
+            .with_changed_by(LOCAL_CHANGED_BY.into())
+            .build()
+            .map_err(|err| InternalError::from_source(Box::new(err)))?;
+        self.change_maintenance_mode(change)
     }
 }
 
@@ -82,7 +184,7 @@ impl AuthorizationHandler for MaintenanceModeAuthorizationHandler {
         #[allow(unused_variables)] identity: &Identity,
         permission_id: &str,
     ) -> Result<AuthorizationHandlerResult, InternalError> {
-        if !permission_id.ends_with(".read") && self.maintenance_mode.load(Ordering::Relaxed) {
+        if !permission_id.ends_with(".read") && self.is_maintenance_mode_enabled() {
             // Check if the client has the "admin" role, in which case they're not denied permission
             #[cfg(feature = "authorization-handler-rbac")]
             {
@@ -111,6 +213,10 @@ impl AuthorizationHandler for MaintenanceModeAuthorizationHandler {
         }
     }
 
+    fn name(&self) -> &str {
+        "maintenance"
+    }
+
     fn clone_box(&self) -> Box<dyn AuthorizationHandler> {
         Box::new(self.clone())
     }
@@ -120,6 +226,18 @@ impl AuthorizationHandler for MaintenanceModeAuthorizationHandler {
 mod tests {
     use super::*;
 
+    use std::time::Duration;
+
+    #[cfg(feature = "sqlite")]
+    use diesel::{
+        r2d2::{ConnectionManager, Pool},
+        sqlite::SqliteConnection,
+    };
+
+    #[cfg(feature = "sqlite")]
+    use crate::migrations::run_sqlite_migrations;
+    #[cfg(feature = "sqlite")]
+    use crate::rest_api::auth::authorization::maintenance::store::DieselMaintenanceModeStore;
     use crate::rest_api::auth::authorization::rbac::store::{
         Assignment, AssignmentBuilder, Role, RoleBasedAuthorizationStore,
         RoleBasedAuthorizationStoreError,
@@ -148,7 +266,9 @@ mod tests {
             Ok(AuthorizationHandlerResult::Continue)
         ));
 
-        handler.set_maintenance_mode(true);
+        handler
+            .set_maintenance_mode(true)
+            .expect("Unable to set maintenance mode");
         assert_eq!(handler.is_maintenance_mode_enabled(), true);
 
         assert!(matches!(
@@ -174,14 +294,18 @@ mod tests {
             Ok(AuthorizationHandlerResult::Continue)
         ));
 
-        handler.set_maintenance_mode(true);
+        handler
+            .set_maintenance_mode(true)
+            .expect("Unable to set maintenance mode");
         assert_eq!(handler.is_maintenance_mode_enabled(), true);
         assert!(matches!(
             handler.has_permission(&Identity::Custom("identity".into()), "permission"),
             Ok(AuthorizationHandlerResult::Deny)
         ));
 
-        handler.set_maintenance_mode(false);
+        handler
+            .set_maintenance_mode(false)
+            .expect("Unable to set maintenance mode");
         assert_eq!(handler.is_maintenance_mode_enabled(), false);
         assert!(matches!(
             handler.has_permission(&Identity::Custom("identity".into()), "permission"),
@@ -207,7 +331,9 @@ mod tests {
             MockRoleBasedAuthorizationStore,
         )));
 
-        handler.set_maintenance_mode(true);
+        handler
+            .set_maintenance_mode(true)
+            .expect("Unable to set maintenance mode");
         assert_eq!(handler.is_maintenance_mode_enabled(), true);
 
         assert!(matches!(
@@ -229,6 +355,98 @@ mod tests {
         ));
     }
 
+    /// Verifies that maintenance mode is disabled once the scheduled end of the current change has
+    /// passed, and that each change is recorded.
+    ///
+    /// 1. Create a new `MaintenanceModeAuthorizationHandler`
+    /// 2. Enable maintenance mode with a scheduled end in the future and verify that a `Deny`
+    ///    result is returned by `has_permission`
+    /// 3. Enable maintenance mode with a scheduled end that has already passed and verify that
+    ///    maintenance mode is disabled and a `Continue` result is returned by `has_permission`
+    /// 4. Verify that both changes are listed, oldest first
+    #[test]
+    fn auth_handler_scheduled_end() {
+        let handler = MaintenanceModeAuthorizationHandler::default();
+        let now = SystemTime::now();
+
+        let future_end = MaintenanceModeChangeBuilder::new()
+            .with_enabled(true)
+            .with_changed_by("key:abcd".into())
+            .with_changed_at(now)
+            .with_reason("Upgrade".into())
+            .with_ends_at(now + Duration::from_secs(3600))
+            .build()
+            .expect("Unable to build change");
+        handler
+            .change_maintenance_mode(future_end.clone())
+            .expect("Unable to change maintenance mode");
+        assert_eq!(handler.is_maintenance_mode_enabled(), true);
+        assert!(matches!(
+            handler.has_permission(&Identity::Custom("identity".into()), "permission"),
+            Ok(AuthorizationHandlerResult::Deny)
+        ));
+
+        let past_end = MaintenanceModeChangeBuilder::new()
+            .with_enabled(true)
+            .with_changed_by("key:abcd".into())
+            .with_changed_at(now - Duration::from_secs(120))
+            .with_ends_at(now - Duration::from_secs(60))
+            .build()
+            .expect("Unable to build change");
+        handler
+            .change_maintenance_mode(past_end.clone())
+            .expect("Unable to change maintenance mode");
+        assert_eq!(handler.is_maintenance_mode_enabled(), false);
+        assert!(matches!(
+            handler.has_permission(&Identity::Custom("identity".into()), "permission"),
+            Ok(AuthorizationHandlerResult::Continue)
+        ));
+
+        assert_eq!(
+            handler.list_changes().expect("Unable to list changes"),
+            vec![future_end, past_end]
+        );
+    }
+
+    /// Verifies that maintenance mode changes made through a handler with a store are loaded by
+    /// a new handler that uses the same store, as would happen on restart.
+    ///
+    /// 1. Create a `MaintenanceModeAuthorizationHandler` with a SQLite-backed store
+    /// 2. Enable maintenance mode
+    /// 3. Create a second handler with the same store and verify that maintenance mode is enabled
+    ///    and the change is listed
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn auth_handler_persisted() {
+        let connection_manager = ConnectionManager::<SqliteConnection>::new(":memory:");
+        let pool = Pool::builder()
+            .max_size(1)
+            .build(connection_manager)
+            .expect("Failed to build connection pool");
+        run_sqlite_migrations(&*pool.get().expect("Failed to get connection for migrations"))
+            .expect("Failed to run migrations");
+
+        let handler = MaintenanceModeAuthorizationHandler::default()
+            .with_store(Box::new(DieselMaintenanceModeStore::new(pool.clone())))
+            .expect("Unable to load maintenance mode");
+        assert_eq!(handler.is_maintenance_mode_enabled(), false);
+        handler
+            .set_maintenance_mode(true)
+            .expect("Unable to set maintenance mode");
+
+        let restarted_handler = MaintenanceModeAuthorizationHandler::default()
+            .with_store(Box::new(DieselMaintenanceModeStore::new(pool)))
+            .expect("Unable to load maintenance mode");
+        assert_eq!(restarted_handler.is_maintenance_mode_enabled(), true);
+
+        let changes = restarted_handler
+            .list_changes()
+            .expect("Unable to list changes");
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].changed_by(), LOCAL_CHANGED_BY);
+        assert!(changes[0].enabled());
+    }
+
     #[derive(Clone)]
     struct MockRoleBasedAuthorizationStore;
 
@@ -1,4 +1,4 @@
-// Copyright 2018-2020 Cargill Incorporated
+// Copyright 2018-2021 Cargill Incorporated
 //
 // Licensed under the Apache License, Version 2.0 (the "License");
 // you may not use this file except in compliance with the License.
@@ -16,22 +16,33 @@
 //!
 //! * `GET /authorization/maintenance` for checking if maintenance mode is enabled
 //! * `POST /authorization/maintenance` for enabling/disabling maintenance mode
+//! * `GET /authorization/maintenance/changes` for listing the maintenance mode changes
 
-use actix_web::{web, Error, HttpRequest, HttpResponse};
+use std::time::{Duration, UNIX_EPOCH};
+
+use actix_web::{error::BlockingError, web, Error, HttpMessage, HttpRequest, HttpResponse};
 use futures::{future::IntoFuture, Future};
 
 use crate::protocol;
 use crate::rest_api::{
     actix_web_1::{Method, ProtocolVersionRangeGuard, Resource},
-    auth::authorization::maintenance::MaintenanceModeAuthorizationHandler,
+    auth::{
+        authorization::maintenance::{
+            store::MaintenanceModeChangeBuilder, MaintenanceModeAuthorizationHandler,
+        },
+        identity::Identity,
+    },
     ErrorResponse,
 };
 
 use super::{
-    resources::PostMaintenanceModeQuery, AUTHORIZATION_MAINTENANCE_READ_PERMISSION,
-    AUTHORIZATION_MAINTENANCE_WRITE_PERMISSION,
+    resources::{MaintenanceModeChangeResponse, PostMaintenanceModeQuery},
+    AUTHORIZATION_MAINTENANCE_READ_PERMISSION, AUTHORIZATION_MAINTENANCE_WRITE_PERMISSION,
 };
 
+/// The changed-by value recorded when a change is made by a request without an identity
+const UNKNOWN_CHANGED_BY: &str = "unknown";
+
 pub fn make_maintenance_resource(auth_handler: MaintenanceModeAuthorizationHandler) -> Resource {
     let auth_handler1 = auth_handler.clone();
     Resource::build("/authorization/maintenance")
@@ -65,16 +76,105 @@ fn post_maintenance_mode(
     req: HttpRequest,
     auth_handler: MaintenanceModeAuthorizationHandler,
 ) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    let query = match web::Query::<PostMaintenanceModeQuery>::from_query(req.query_string()) {
+        Ok(query) => query.into_inner(),
+        Err(_) => {
+            return Box::new(
+                HttpResponse::BadRequest()
+                    .json(ErrorResponse::bad_request("Invalid query"))
+                    .into_future(),
+            )
+        }
+    };
+
+    let changed_by = req
+        .extensions()
+        .get::<Identity>()
+        .map(Identity::to_string)
+        .unwrap_or_else(|| UNKNOWN_CHANGED_BY.into());
+
+    let mut builder = MaintenanceModeChangeBuilder::new()
+        .with_enabled(query.enabled)
+        .with_changed_by(changed_by);
+    if let Some(reason) = query.reason {
+        builder = builder.with_reason(reason);
+    }
+    if let Some(ends_at) = query.ends_at {
+        match UNIX_EPOCH.checked_add(Duration::from_secs(ends_at)) {
+            Some(ends_at) => builder = builder.with_ends_at(ends_at),
+            None => {
+                return Box::new(
+                    HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request(&format!(
+                            "Invalid ends_at value passed: {}",
+                            ends_at
+                        )))
+                        .into_future(),
+                )
+            }
+        }
+    }
+
+    let change = match builder.build() {
+        Ok(change) => change,
+        Err(err) => {
+            return Box::new(
+                HttpResponse::BadRequest()
+                    .json(ErrorResponse::bad_request(&err.to_string()))
+                    .into_future(),
+            )
+        }
+    };
+
     Box::new(
-        match web::Query::<PostMaintenanceModeQuery>::from_query(req.query_string()) {
-            Ok(query) => {
-                auth_handler.set_maintenance_mode(query.enabled);
-                HttpResponse::Ok().finish().into_future()
+        web::block(move || {
+            auth_handler
+                .change_maintenance_mode(change)
+                .map_err(|err| err.to_string())
+        })
+        .then(|res: Result<_, BlockingError<String>>| match res {
+            Ok(()) => Ok(HttpResponse::Ok().finish()),
+            Err(err) => {
+                error!("Unable to change maintenance mode: {}", err);
+                Ok(HttpResponse::InternalServerError().json(ErrorResponse::internal_error()))
             }
-            _ => HttpResponse::BadRequest()
-                .json(ErrorResponse::bad_request("Invalid query"))
-                .into_future(),
-        },
+        }),
+    )
+}
+
+pub fn make_maintenance_changes_resource(
+    auth_handler: MaintenanceModeAuthorizationHandler,
+) -> Resource {
+    Resource::build("/authorization/maintenance/changes")
+        .add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::AUTHORIZATION_MAINTENANCE_CHANGES_MIN,
+            protocol::AUTHORIZATION_PROTOCOL_VERSION,
+        ))
+        .add_method(
+            Method::Get,
+            AUTHORIZATION_MAINTENANCE_READ_PERMISSION,
+            move |_, _| list_maintenance_mode_changes(auth_handler.clone()),
+        )
+}
+
+fn list_maintenance_mode_changes(
+    auth_handler: MaintenanceModeAuthorizationHandler,
+) -> Box<dyn Future<Item = HttpResponse, Error = Error>> {
+    Box::new(
+        web::block(move || auth_handler.list_changes().map_err(|err| err.to_string())).then(
+            |res: Result<_, BlockingError<String>>| match res {
+                Ok(changes) => Ok(HttpResponse::Ok().json(json!({
+                    "data": changes
+                        .iter()
+                        .map(MaintenanceModeChangeResponse::from)
+                        .collect::<Vec<_>>(),
+                }))),
+                Err(err) => {
+                    error!("Unable to list maintenance mode changes: {}", err);
+                    Ok(HttpResponse::InternalServerError().json(ErrorResponse::internal_error()))
+                }
+            },
+        ),
     )
 }
 
@@ -237,6 +337,104 @@ mod tests {
         join_handle.join().expect("Unable to join rest api thread");
     }
 
+    /// Verifies that changes made with `POST /authorization/maintenance` are recorded with their
+    /// reason and scheduled end, and listed by `GET /authorization/maintenance/changes`
+    ///
+    /// 1. Run the REST API with the maintenance mode endpoints
+    /// 2. Enable maintenance mode with a reason and a scheduled end
+    /// 3. Verify that disabling maintenance mode with a scheduled end is rejected
+    /// 4. Verify that a scheduled end that cannot be represented as a time is rejected
+    /// 5. Verify that `GET /authorization/maintenance/changes` returns the single valid change,
+    ///    recorded as made by an unknown identity since the REST API has no authorization
+    #[test]
+    fn post_with_reason_and_list_changes() {
+        let handler = MaintenanceModeAuthorizationHandler::default();
+
+        let (shutdown_handle, join_handle, bind_url) = run_rest_api_on_open_port(vec![
+            make_maintenance_resource(handler.clone()),
+            make_maintenance_changes_resource(handler.clone()),
+        ]);
+
+        let url = Url::parse(&format!("http://{}/authorization/maintenance", bind_url))
+            .expect("Failed to parse URL");
+        let ends_at = (std::time::SystemTime::now() + Duration::from_secs(3600))
+            .duration_since(UNIX_EPOCH)
+            .expect("Failed to get time")
+            .as_secs();
+
+        // Enable with a reason and a scheduled end
+        let resp = Client::new()
+            .post(url.clone())
+            .query(&[
+                ("enabled", "true"),
+                ("reason", "Upgrading"),
+                ("ends_at", &ends_at.to_string()),
+            ])
+            .header(
+                "SplinterProtocolVersion",
+                protocol::AUTHORIZATION_PROTOCOL_VERSION,
+            )
+            .send()
+            .expect("Failed to perform request");
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(handler.is_maintenance_mode_enabled());
+
+        // Disabling with a scheduled end is invalid
+        let resp = Client::new()
+            .post(url.clone())
+            .query(&[("enabled", "false"), ("ends_at", &ends_at.to_string())])
+            .header(
+                "SplinterProtocolVersion",
+                protocol::AUTHORIZATION_PROTOCOL_VERSION,
+            )
+            .send()
+            .expect("Failed to perform request");
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        assert!(handler.is_maintenance_mode_enabled());
+
+        // A scheduled end that overflows is invalid
+        let resp = Client::new()
+            .post(url)
+            .query(&[("enabled", "true"), ("ends_at", &u64::MAX.to_string())])
+            .header(
+                "SplinterProtocolVersion",
+                protocol::AUTHORIZATION_PROTOCOL_VERSION,
+            )
+            .send()
+            .expect("Failed to perform request");
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        // List changes
+        let url = Url::parse(&format!(
+            "http://{}/authorization/maintenance/changes",
+            bind_url
+        ))
+        .expect("Failed to parse URL");
+        let resp = Client::new()
+            .get(url)
+            .header(
+                "SplinterProtocolVersion",
+                protocol::AUTHORIZATION_PROTOCOL_VERSION,
+            )
+            .send()
+            .expect("Failed to perform request");
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body: serde_json::Value = resp.json().expect("Failed to deserialize body");
+        let changes: Vec<MaintenanceModeChangeResponse> =
+            serde_json::from_value(body["data"].clone()).expect("Failed to deserialize changes");
+        assert_eq!(changes.len(), 1);
+        assert!(changes[0].enabled);
+        assert_eq!(changes[0].changed_by, UNKNOWN_CHANGED_BY);
+        assert_eq!(changes[0].reason.as_deref(), Some("Upgrading"));
+        assert_eq!(changes[0].ends_at, Some(ends_at));
+
+        shutdown_handle
+            .shutdown()
+            .expect("Unable to shutdown rest api");
+        join_handle.join().expect("Unable to join rest api thread");
+    }
+
     fn run_rest_api_on_open_port(
         resources: Vec<Resource>,
     ) -> (RestApiShutdownHandle, std::thread::JoinHandle<()>, String) {
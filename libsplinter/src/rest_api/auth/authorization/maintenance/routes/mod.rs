@@ -1,4 +1,4 @@
-// Copyright 2018-2020 Cargill Incorporated
+// Copyright 2018-2021 Cargill Incorporated
 //
 // Licensed under the Apache License, Version 2.0 (the "License");
 // you may not use this file except in compliance with the License.
@@ -43,6 +43,7 @@ const AUTHORIZATION_MAINTENANCE_WRITE_PERMISSION: Permission = Permission::Check
 ///
 /// * `GET /authorization/maintenance` - Check if maintenance mode is enabled
 /// * `POST /authorization/maintenance` - Enable/disable maintenance mode
+/// * `GET /authorization/maintenance/changes` - List the changes made to maintenance mode
 ///
 /// These endpoints are only available if the following REST API backend feature is enabled:
 ///
@@ -57,6 +58,7 @@ impl RestResourceProvider for MaintenanceModeAuthorizationHandler {
         #[cfg(feature = "rest-api-actix")]
         {
             resources.push(actix::make_maintenance_resource(self.clone()));
+            resources.push(actix::make_maintenance_changes_resource(self.clone()));
         }
 
         resources
@@ -1,4 +1,4 @@
-// Copyright 2018-2020 Cargill Incorporated
+// Copyright 2018-2021 Cargill Incorporated
 //
 // Licensed under the Apache License, Version 2.0 (the "License");
 // you may not use this file except in compliance with the License.
@@ -15,7 +15,43 @@
 //! This module provides resources for the maintenance mode authorization handler's REST API
 //! endpoints
 
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::rest_api::auth::authorization::maintenance::store::MaintenanceModeChange;
+
 #[derive(Deserialize)]
 pub struct PostMaintenanceModeQuery {
     pub enabled: bool,
+    pub reason: Option<String>,
+    /// The time at which maintenance mode will end, in seconds since the epoch
+    pub ends_at: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct MaintenanceModeChangeResponse {
+    pub enabled: bool,
+    pub changed_by: String,
+    /// The time of the change, in seconds since the epoch
+    pub changed_at: u64,
+    pub reason: Option<String>,
+    /// The time at which maintenance mode will end, in seconds since the epoch
+    pub ends_at: Option<u64>,
+}
+
+impl From<&MaintenanceModeChange> for MaintenanceModeChangeResponse {
+    fn from(change: &MaintenanceModeChange) -> Self {
+        Self {
+            enabled: change.enabled(),
+            changed_by: change.changed_by().into(),
+            changed_at: to_seconds(change.changed_at()),
+            reason: change.reason().map(String::from),
+            ends_at: change.ends_at().map(to_seconds),
+        }
+    }
+}
+
+fn to_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
 }
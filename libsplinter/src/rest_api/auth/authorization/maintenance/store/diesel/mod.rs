@@ -0,0 +1,300 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Database-backed implementation of the [MaintenanceModeStore], powered by [diesel].
+
+mod models;
+mod operations;
+mod schema;
+
+use std::convert::TryFrom;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use diesel::r2d2::{ConnectionManager, Pool};
+
+use crate::error::{ConstraintViolationError, ConstraintViolationType, InternalError};
+
+use super::{MaintenanceModeChange, MaintenanceModeStore, MaintenanceModeStoreError};
+
+use operations::add_change::MaintenanceModeStoreAddChange as _;
+use operations::get_current_change::MaintenanceModeStoreGetCurrentChange as _;
+use operations::list_changes::MaintenanceModeStoreListChanges as _;
+use operations::MaintenanceModeStoreOperations;
+
+/// A database-backed [MaintenanceModeStore], powered by [diesel].
+pub struct DieselMaintenanceModeStore<C: diesel::Connection + 'static> {
+    connection_pool: Pool<ConnectionManager<C>>,
+}
+
+impl<C: diesel::Connection + 'static> DieselMaintenanceModeStore<C> {
+    /// Creates a new DieselMaintenanceModeStore
+    ///
+    /// # Arguments
+    ///
+    ///  * `connection_pool`: connection pool to the database
+    pub fn new(connection_pool: Pool<ConnectionManager<C>>) -> Self {
+        Self { connection_pool }
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl MaintenanceModeStore for DieselMaintenanceModeStore<diesel::pg::PgConnection> {
+    fn get_current_change(
+        &self,
+    ) -> Result<Option<MaintenanceModeChange>, MaintenanceModeStoreError> {
+        let connection = self.connection_pool.get()?;
+        MaintenanceModeStoreOperations::new(&*connection).get_current_change()
+    }
+
+    fn list_changes(
+        &self,
+    ) -> Result<Box<dyn ExactSizeIterator<Item = MaintenanceModeChange>>, MaintenanceModeStoreError>
+    {
+        let connection = self.connection_pool.get()?;
+        MaintenanceModeStoreOperations::new(&*connection).list_changes()
+    }
+
+    fn add_change(&self, change: MaintenanceModeChange) -> Result<(), MaintenanceModeStoreError> {
+        let connection = self.connection_pool.get()?;
+        MaintenanceModeStoreOperations::new(&*connection).add_change(change)
+    }
+
+    fn clone_box(&self) -> Box<dyn MaintenanceModeStore> {
+        Box::new(Self {
+            connection_pool: self.connection_pool.clone(),
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl MaintenanceModeStore for DieselMaintenanceModeStore<diesel::sqlite::SqliteConnection> {
+    fn get_current_change(
+        &self,
+    ) -> Result<Option<MaintenanceModeChange>, MaintenanceModeStoreError> {
+        let connection = self.connection_pool.get()?;
+        MaintenanceModeStoreOperations::new(&*connection).get_current_change()
+    }
+
+    fn list_changes(
+        &self,
+    ) -> Result<Box<dyn ExactSizeIterator<Item = MaintenanceModeChange>>, MaintenanceModeStoreError>
+    {
+        let connection = self.connection_pool.get()?;
+        MaintenanceModeStoreOperations::new(&*connection).list_changes()
+    }
+
+    fn add_change(&self, change: MaintenanceModeChange) -> Result<(), MaintenanceModeStoreError> {
+        let connection = self.connection_pool.get()?;
+        MaintenanceModeStoreOperations::new(&*connection).add_change(change)
+    }
+
+    fn clone_box(&self) -> Box<dyn MaintenanceModeStore> {
+        Box::new(Self {
+            connection_pool: self.connection_pool.clone(),
+        })
+    }
+}
+
+impl TryFrom<MaintenanceModeChange> for models::NewMaintenanceModeChangeModel {
+    type Error = MaintenanceModeStoreError;
+
+    fn try_from(change: MaintenanceModeChange) -> Result<Self, Self::Error> {
+        let (enabled, changed_by, changed_at, reason, ends_at) = change.into_parts();
+
+        Ok(models::NewMaintenanceModeChangeModel {
+            enabled,
+            changed_by,
+            changed_at: to_timestamp(changed_at)?,
+            reason,
+            ends_at: ends_at.map(to_timestamp).transpose()?,
+        })
+    }
+}
+
+impl TryFrom<models::MaintenanceModeChangeModel> for MaintenanceModeChange {
+    type Error = MaintenanceModeStoreError;
+
+    fn try_from(model: models::MaintenanceModeChangeModel) -> Result<Self, Self::Error> {
+        // The change is created directly, vs using the builder, as it was validated by the
+        // builder before it was added to the database.
+        Ok(MaintenanceModeChange {
+            enabled: model.enabled,
+            changed_by: model.changed_by,
+            changed_at: from_timestamp(model.changed_at)?,
+            reason: model.reason,
+            ends_at: model.ends_at.map(from_timestamp).transpose()?,
+        })
+    }
+}
+
+/// Converts a `SystemTime` to the number of seconds since the epoch, as stored in the database
+fn to_timestamp(time: SystemTime) -> Result<i64, MaintenanceModeStoreError> {
+    time.duration_since(UNIX_EPOCH)
+        .map_err(|err| {
+            MaintenanceModeStoreError::InternalError(InternalError::from_source(Box::new(err)))
+        })
+        .and_then(|duration| {
+            i64::try_from(duration.as_secs()).map_err(|_| {
+                MaintenanceModeStoreError::InternalError(InternalError::with_message(
+                    "timestamp could not be converted from u64 to i64".to_string(),
+                ))
+            })
+        })
+}
+
+/// Converts a number of seconds since the epoch, as stored in the database, to a `SystemTime`
+fn from_timestamp(timestamp: i64) -> Result<SystemTime, MaintenanceModeStoreError> {
+    u64::try_from(timestamp)
+        .ok()
+        .and_then(|secs| UNIX_EPOCH.checked_add(Duration::from_secs(secs)))
+        .ok_or_else(|| {
+            MaintenanceModeStoreError::InternalError(InternalError::with_message(
+                "timestamp could not be represented as a `SystemTime`".to_string(),
+            ))
+        })
+}
+
+impl From<diesel::result::Error> for MaintenanceModeStoreError {
+    fn from(err: diesel::result::Error) -> Self {
+        match err {
+            diesel::result::Error::DatabaseError(ref kind, _) => match kind {
+                diesel::result::DatabaseErrorKind::UniqueViolation => {
+                    MaintenanceModeStoreError::ConstraintViolation(
+                        ConstraintViolationError::from_source_with_violation_type(
+                            ConstraintViolationType::Unique,
+                            Box::new(err),
+                        ),
+                    )
+                }
+                diesel::result::DatabaseErrorKind::ForeignKeyViolation => {
+                    MaintenanceModeStoreError::ConstraintViolation(
+                        ConstraintViolationError::from_source_with_violation_type(
+                            ConstraintViolationType::ForeignKey,
+                            Box::new(err),
+                        ),
+                    )
+                }
+                _ => MaintenanceModeStoreError::InternalError(InternalError::from_source(
+                    Box::new(err),
+                )),
+            },
+            _ => {
+                MaintenanceModeStoreError::InternalError(InternalError::from_source(Box::new(err)))
+            }
+        }
+    }
+}
+
+impl From<diesel::r2d2::PoolError> for MaintenanceModeStoreError {
+    fn from(err: diesel::r2d2::PoolError) -> Self {
+        MaintenanceModeStoreError::InternalError(InternalError::from_source(Box::new(err)))
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    use crate::migrations::run_sqlite_migrations;
+    use crate::rest_api::auth::authorization::maintenance::store::MaintenanceModeChangeBuilder;
+
+    use diesel::{
+        r2d2::{ConnectionManager, Pool},
+        sqlite::SqliteConnection,
+    };
+
+    /// Verify that a SQLite-backed `DieselMaintenanceModeStore` correctly records and returns
+    /// maintenance mode changes.
+    ///
+    /// 1. Create a connection pool for an in-memory SQLite database and run migrations.
+    /// 2. Create the `DieselMaintenanceModeStore` and verify that there is no current change.
+    /// 3. Add a change that enables maintenance mode with a reason and a scheduled end, and verify
+    ///    that it is the current change with all fields intact.
+    /// 4. Add a change that disables maintenance mode, and verify that it is the current change.
+    /// 5. Verify that `list_changes` returns both changes, oldest first.
+    #[test]
+    fn sqlite_add_get_and_list_changes() {
+        let pool = create_connection_pool_and_migrate();
+
+        let store = DieselMaintenanceModeStore::new(pool);
+
+        assert_eq!(
+            store
+                .get_current_change()
+                .expect("Unable to get current change"),
+            None
+        );
+
+        let enabled_at = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        let enable = MaintenanceModeChangeBuilder::new()
+            .with_enabled(true)
+            .with_changed_by("key:abcd".into())
+            .with_changed_at(enabled_at)
+            .with_reason("Upgrading the database".into())
+            .with_ends_at(enabled_at + Duration::from_secs(3600))
+            .build()
+            .expect("Unable to build change");
+        store
+            .add_change(enable.clone())
+            .expect("Unable to add change");
+
+        assert_eq!(
+            store
+                .get_current_change()
+                .expect("Unable to get current change"),
+            Some(enable.clone())
+        );
+
+        let disable = MaintenanceModeChangeBuilder::new()
+            .with_enabled(false)
+            .with_changed_by("user:some-user-id".into())
+            .with_changed_at(enabled_at + Duration::from_secs(60))
+            .build()
+            .expect("Unable to build change");
+        store
+            .add_change(disable.clone())
+            .expect("Unable to add change");
+
+        assert_eq!(
+            store
+                .get_current_change()
+                .expect("Unable to get current change"),
+            Some(disable.clone())
+        );
+
+        assert_eq!(
+            store
+                .list_changes()
+                .expect("Unable to list changes")
+                .collect::<Vec<_>>(),
+            vec![enable, disable]
+        );
+    }
+
+    /// Creates a connection pool for an in-memory SQLite database with only a single connection
+    /// available. Each connection is backed by a different in-memory SQLite database, so limiting
+    /// the pool to a single connection ensures that the same DB is used for all operations.
+    fn create_connection_pool_and_migrate() -> Pool<ConnectionManager<SqliteConnection>> {
+        let connection_manager = ConnectionManager::<SqliteConnection>::new(":memory:");
+        let pool = Pool::builder()
+            .max_size(1)
+            .build(connection_manager)
+            .expect("Failed to build connection pool");
+
+        run_sqlite_migrations(&*pool.get().expect("Failed to get connection for migrations"))
+            .expect("Failed to run migrations");
+
+        pool
+    }
+}
@@ -0,0 +1,34 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use super::schema::maintenance_mode_changes;
+
+#[derive(Insertable, PartialEq, Debug)]
+#[table_name = "maintenance_mode_changes"]
+pub(super) struct NewMaintenanceModeChangeModel {
+    pub enabled: bool,
+    pub changed_by: String,
+    pub changed_at: i64,
+    pub reason: Option<String>,
+    pub ends_at: Option<i64>,
+}
+
+#[derive(Queryable, PartialEq, Debug)]
+pub(super) struct MaintenanceModeChangeModel {
+    pub id: i64,
+    pub enabled: bool,
+    pub changed_by: String,
+    pub changed_at: i64,
+    pub reason: Option<String>,
+    pub ends_at: Option<i64>,
+}
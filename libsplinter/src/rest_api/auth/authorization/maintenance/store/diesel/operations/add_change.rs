@@ -0,0 +1,53 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::convert::TryFrom;
+
+use diesel::{dsl::insert_into, prelude::*};
+
+use crate::rest_api::auth::authorization::maintenance::store::{
+    diesel::{models::NewMaintenanceModeChangeModel, schema::maintenance_mode_changes},
+    MaintenanceModeChange, MaintenanceModeStoreError,
+};
+
+use super::MaintenanceModeStoreOperations;
+
+pub trait MaintenanceModeStoreAddChange {
+    fn add_change(&self, change: MaintenanceModeChange) -> Result<(), MaintenanceModeStoreError>;
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> MaintenanceModeStoreAddChange
+    for MaintenanceModeStoreOperations<'a, diesel::sqlite::SqliteConnection>
+{
+    fn add_change(&self, change: MaintenanceModeChange) -> Result<(), MaintenanceModeStoreError> {
+        insert_into(maintenance_mode_changes::table)
+            .values(NewMaintenanceModeChangeModel::try_from(change)?)
+            .execute(self.conn)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> MaintenanceModeStoreAddChange
+    for MaintenanceModeStoreOperations<'a, diesel::pg::PgConnection>
+{
+    fn add_change(&self, change: MaintenanceModeChange) -> Result<(), MaintenanceModeStoreError> {
+        insert_into(maintenance_mode_changes::table)
+            .values(NewMaintenanceModeChangeModel::try_from(change)?)
+            .execute(self.conn)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::convert::TryInto;
+
+use diesel::prelude::*;
+
+use crate::rest_api::auth::authorization::maintenance::store::{
+    diesel::{models::MaintenanceModeChangeModel, schema::maintenance_mode_changes},
+    MaintenanceModeChange, MaintenanceModeStoreError,
+};
+
+use super::MaintenanceModeStoreOperations;
+
+pub trait MaintenanceModeStoreListChanges {
+    fn list_changes(
+        &self,
+    ) -> Result<Box<dyn ExactSizeIterator<Item = MaintenanceModeChange>>, MaintenanceModeStoreError>;
+}
+
+impl<'a, C> MaintenanceModeStoreListChanges for MaintenanceModeStoreOperations<'a, C>
+where
+    C: diesel::Connection,
+    String: diesel::deserialize::FromSql<diesel::sql_types::Text, C::Backend>,
+    i64: diesel::deserialize::FromSql<diesel::sql_types::BigInt, C::Backend>,
+    bool: diesel::deserialize::FromSql<diesel::sql_types::Bool, C::Backend>,
+{
+    fn list_changes(
+        &self,
+    ) -> Result<Box<dyn ExactSizeIterator<Item = MaintenanceModeChange>>, MaintenanceModeStoreError>
+    {
+        let changes = maintenance_mode_changes::table
+            .order(maintenance_mode_changes::id)
+            .load::<MaintenanceModeChangeModel>(self.conn)?
+            .into_iter()
+            .map(|model| model.try_into())
+            .collect::<Result<Vec<MaintenanceModeChange>, _>>()?;
+
+        Ok(Box::new(changes.into_iter()))
+    }
+}
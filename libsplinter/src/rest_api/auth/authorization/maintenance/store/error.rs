@@ -0,0 +1,64 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::error::{ConstraintViolationError, InternalError, InvalidStateError};
+
+/// Errors that may occur during [MaintenanceModeStore](super::MaintenanceModeStore) operations.
+#[derive(Debug)]
+pub enum MaintenanceModeStoreError {
+    InternalError(InternalError),
+    InvalidState(InvalidStateError),
+    ConstraintViolation(ConstraintViolationError),
+}
+
+impl fmt::Display for MaintenanceModeStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MaintenanceModeStoreError::InternalError(err) => err.fmt(f),
+            MaintenanceModeStoreError::InvalidState(err) => err.fmt(f),
+            MaintenanceModeStoreError::ConstraintViolation(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for MaintenanceModeStoreError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MaintenanceModeStoreError::InternalError(err) => Some(err),
+            MaintenanceModeStoreError::InvalidState(err) => Some(err),
+            MaintenanceModeStoreError::ConstraintViolation(err) => Some(err),
+        }
+    }
+}
+
+impl From<InternalError> for MaintenanceModeStoreError {
+    fn from(err: InternalError) -> Self {
+        MaintenanceModeStoreError::InternalError(err)
+    }
+}
+
+impl From<InvalidStateError> for MaintenanceModeStoreError {
+    fn from(err: InvalidStateError) -> Self {
+        MaintenanceModeStoreError::InvalidState(err)
+    }
+}
+
+impl From<ConstraintViolationError> for MaintenanceModeStoreError {
+    fn from(err: ConstraintViolationError) -> Self {
+        MaintenanceModeStoreError::ConstraintViolation(err)
+    }
+}
@@ -0,0 +1,274 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module defines the store trait for maintenance mode changes.
+
+#[cfg(feature = "diesel")]
+mod diesel;
+mod error;
+
+use std::time::SystemTime;
+
+use crate::error::InvalidStateError;
+
+#[cfg(feature = "diesel")]
+pub use self::diesel::DieselMaintenanceModeStore;
+
+pub use error::MaintenanceModeStoreError;
+
+/// A record of maintenance mode being enabled or disabled.
+///
+/// The most recent change determines whether or not maintenance mode is enabled; all earlier
+/// changes are kept as an audit trail.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MaintenanceModeChange {
+    enabled: bool,
+    changed_by: String,
+    changed_at: SystemTime,
+    reason: Option<String>,
+    ends_at: Option<SystemTime>,
+}
+
+impl MaintenanceModeChange {
+    /// Returns whether maintenance mode was enabled or disabled by this change.
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Returns a description of the identity that made the change.
+    pub fn changed_by(&self) -> &str {
+        &self.changed_by
+    }
+
+    /// Returns the time at which the change was made.
+    pub fn changed_at(&self) -> SystemTime {
+        self.changed_at
+    }
+
+    /// Returns the reason given for the change, if any.
+    pub fn reason(&self) -> Option<&str> {
+        self.reason.as_deref()
+    }
+
+    /// Returns the time at which maintenance mode is scheduled to end, if any. Only changes that
+    /// enable maintenance mode may have a scheduled end.
+    pub fn ends_at(&self) -> Option<SystemTime> {
+        self.ends_at
+    }
+
+    /// Returns whether or not this change leaves maintenance mode enabled at the given time,
+    /// taking the scheduled end into account.
+    pub fn is_enabled_at(&self, time: SystemTime) -> bool {
+        self.enabled && self.ends_at.map(|ends_at| time < ends_at).unwrap_or(true)
+    }
+
+    /// Converts this change into its constituent parts.  These parts are in the tuple:
+    /// `(enabled, changed_by, changed_at, reason, ends_at)`.
+    pub fn into_parts(self) -> (bool, String, SystemTime, Option<String>, Option<SystemTime>) {
+        (
+            self.enabled,
+            self.changed_by,
+            self.changed_at,
+            self.reason,
+            self.ends_at,
+        )
+    }
+}
+
+/// A builder to create new maintenance mode changes.
+#[derive(Default)]
+pub struct MaintenanceModeChangeBuilder {
+    enabled: Option<bool>,
+    changed_by: Option<String>,
+    changed_at: Option<SystemTime>,
+    reason: Option<String>,
+    ends_at: Option<SystemTime>,
+}
+
+impl MaintenanceModeChangeBuilder {
+    /// Constructs a new builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether the change enables or disables maintenance mode.
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = Some(enabled);
+        self
+    }
+
+    /// Sets the description of the identity that made the change.
+    pub fn with_changed_by(mut self, changed_by: String) -> Self {
+        self.changed_by = Some(changed_by);
+        self
+    }
+
+    /// Sets the time at which the change was made. Defaults to the current time.
+    pub fn with_changed_at(mut self, changed_at: SystemTime) -> Self {
+        self.changed_at = Some(changed_at);
+        self
+    }
+
+    /// Sets the reason for the change.
+    pub fn with_reason(mut self, reason: String) -> Self {
+        self.reason = Some(reason);
+        self
+    }
+
+    /// Sets the time at which maintenance mode will automatically end.
+    pub fn with_ends_at(mut self, ends_at: SystemTime) -> Self {
+        self.ends_at = Some(ends_at);
+        self
+    }
+
+    /// Builds the new maintenance mode change.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`InvalidStateError`] under the following conditions:
+    /// * whether the change enables or disables maintenance mode was not provided
+    /// * no changed-by identity or an empty one was provided
+    /// * an empty reason was provided
+    /// * an end time was provided for a change that disables maintenance mode
+    /// * an end time was provided that is not after the time of the change
+    pub fn build(self) -> Result<MaintenanceModeChange, InvalidStateError> {
+        let enabled = self.enabled.ok_or_else(|| {
+            InvalidStateError::with_message(
+                "A maintenance mode change requires an enabled field".into(),
+            )
+        })?;
+
+        let changed_by = self
+            .changed_by
+            .filter(|changed_by| !changed_by.is_empty())
+            .ok_or_else(|| {
+                InvalidStateError::with_message(
+                    "A maintenance mode change requires a non-empty changed_by field".into(),
+                )
+            })?;
+
+        if let Some(reason) = &self.reason {
+            if reason.is_empty() {
+                return Err(InvalidStateError::with_message(
+                    "A maintenance mode change's reason must not be empty".into(),
+                ));
+            }
+        }
+
+        let changed_at = self.changed_at.unwrap_or_else(SystemTime::now);
+
+        if let Some(ends_at) = self.ends_at {
+            if !enabled {
+                return Err(InvalidStateError::with_message(
+                    "Only enabling maintenance mode may have a scheduled end".into(),
+                ));
+            }
+            if ends_at <= changed_at {
+                return Err(InvalidStateError::with_message(
+                    "Maintenance mode must be scheduled to end after it is enabled".into(),
+                ));
+            }
+        }
+
+        Ok(MaintenanceModeChange {
+            enabled,
+            changed_by,
+            changed_at,
+            reason: self.reason,
+            ends_at: self.ends_at,
+        })
+    }
+}
+
+/// Defines methods for recording and retrieving maintenance mode changes.
+pub trait MaintenanceModeStore: Send + Sync {
+    /// Returns the most recent change, if maintenance mode has ever been changed.
+    fn get_current_change(
+        &self,
+    ) -> Result<Option<MaintenanceModeChange>, MaintenanceModeStoreError>;
+
+    /// Lists all changes, from oldest to most recent.
+    fn list_changes(
+        &self,
+    ) -> Result<Box<dyn ExactSizeIterator<Item = MaintenanceModeChange>>, MaintenanceModeStoreError>;
+
+    /// Records a change, which becomes the current change.
+    fn add_change(&self, change: MaintenanceModeChange) -> Result<(), MaintenanceModeStoreError>;
+
+    /// Clone into a boxed, dynamically dispatched store
+    fn clone_box(&self) -> Box<dyn MaintenanceModeStore>;
+}
+
+impl Clone for Box<dyn MaintenanceModeStore> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    /// Verify that the `MaintenanceModeChangeBuilder` validates its fields, and that a change with
+    /// a scheduled end is only enabled before that end.
+    #[test]
+    fn change_builder() {
+        let changed_at = SystemTime::now();
+
+        let change = MaintenanceModeChangeBuilder::new()
+            .with_enabled(true)
+            .with_changed_by("key:abcd".into())
+            .with_changed_at(changed_at)
+            .with_ends_at(changed_at + Duration::from_secs(60))
+            .build()
+            .expect("Unable to build change");
+        assert!(change.is_enabled_at(changed_at));
+        assert!(change.is_enabled_at(changed_at + Duration::from_secs(59)));
+        assert!(!change.is_enabled_at(changed_at + Duration::from_secs(60)));
+
+        // Missing changed_by
+        assert!(MaintenanceModeChangeBuilder::new()
+            .with_enabled(true)
+            .build()
+            .is_err());
+
+        // Empty reason
+        assert!(MaintenanceModeChangeBuilder::new()
+            .with_enabled(true)
+            .with_changed_by("key:abcd".into())
+            .with_reason(String::new())
+            .build()
+            .is_err());
+
+        // Scheduled end when disabling
+        assert!(MaintenanceModeChangeBuilder::new()
+            .with_enabled(false)
+            .with_changed_by("key:abcd".into())
+            .with_changed_at(changed_at)
+            .with_ends_at(changed_at + Duration::from_secs(60))
+            .build()
+            .is_err());
+
+        // Scheduled end before the change
+        assert!(MaintenanceModeChangeBuilder::new()
+            .with_enabled(true)
+            .with_changed_by("key:abcd".into())
+            .with_changed_at(changed_at)
+            .with_ends_at(changed_at - Duration::from_secs(60))
+            .build()
+            .is_err());
+    }
+}
@@ -16,6 +16,10 @@
 
 #[cfg(feature = "authorization-handler-allow-keys")]
 pub mod allow_keys;
+#[cfg(feature = "api-keys")]
+pub mod api_key_scopes;
+#[cfg(feature = "authorization-audit")]
+pub mod audit;
 #[cfg(feature = "authorization-handler-maintenance")]
 pub mod maintenance;
 mod permission_map;
@@ -41,6 +45,18 @@ pub enum Permission {
         /// A description for the permission
         permission_description: &'static str,
     },
+    /// Check that the authenticated client has the specified permission, either for the whole node
+    /// or for some circuits. A client that only has the permission for some circuits is allowed,
+    /// and the request's [`CircuitScope`] is made available to the endpoint, which must then limit
+    /// its response to those circuits. This may be used by endpoints that list circuit data.
+    CheckForCircuits {
+        /// The permission ID that's passed to [`AuthorizationHandler::has_permission`]
+        permission_id: &'static str,
+        /// The human-readable name for the permission
+        permission_display_name: &'static str,
+        /// A description for the permission
+        permission_description: &'static str,
+    },
     /// Allow any request that has been authenticated (the client's identity has been determined).
     /// This may be used by endpoints that need to know the client's identity but do not require a
     /// special permission to be checked (the Biome key management and OAuth logout routes are an
@@ -70,6 +86,42 @@ pub trait AuthorizationHandler: Send + Sync {
         permission_id: &str,
     ) -> Result<AuthorizationHandlerResult, InternalError>;
 
+    /// Determines if the given identity has the requested permission for a request about the
+    /// given circuit.
+    ///
+    /// By default, the circuit is ignored and this is the same as `has_permission`. Handlers that
+    /// can grant permissions for specific circuits should override this method.
+    fn has_permission_for_circuit(
+        &self,
+        identity: &Identity,
+        permission_id: &str,
+        _circuit_id: &str,
+    ) -> Result<AuthorizationHandlerResult, InternalError> {
+        self.has_permission(identity, permission_id)
+    }
+
+    /// Determines the circuits for which the given identity has the requested permission, when it
+    /// does not have the permission for the whole node. The circuits are given as circuit ID
+    /// patterns, which may contain `*` wildcards.
+    ///
+    /// By default, this returns `None`. Handlers that can grant permissions for specific circuits
+    /// should override this method.
+    fn circuits_with_permission(
+        &self,
+        _identity: &Identity,
+        _permission_id: &str,
+    ) -> Result<Option<Vec<String>>, InternalError> {
+        Ok(None)
+    }
+
+    /// Returns a short name for this handler, which identifies it as the handler that made a
+    /// decision in the REST API's audit log.
+    ///
+    /// By default, this is "custom". The built-in handlers override this method.
+    fn name(&self) -> &str {
+        "custom"
+    }
+
     /// Clone implementation for `AuthorizationHandler`. The implementation of the `Clone` trait for
     /// `Box<dyn AuthorizationHandler>` calls this method.
     fn clone_box(&self) -> Box<dyn AuthorizationHandler>;
@@ -80,3 +132,61 @@ impl Clone for Box<dyn AuthorizationHandler> {
         self.clone_box()
     }
 }
+
+/// The circuits that a client was authorized for by a [`Permission::CheckForCircuits`] permission,
+/// when the client does not have the permission for the whole node.
+///
+/// This is added to the request for the endpoint to limit its response to these circuits.
+#[derive(Clone, Debug)]
+pub struct CircuitScope {
+    circuits: Vec<String>,
+}
+
+impl CircuitScope {
+    pub(in crate::rest_api) fn new(circuits: Vec<String>) -> Self {
+        Self { circuits }
+    }
+
+    /// Returns the circuit ID patterns that the client is authorized for.
+    pub fn circuits(&self) -> &[String] {
+        &self.circuits
+    }
+
+    /// Returns whether or not the client is authorized for the given circuit.
+    pub fn contains(&self, circuit_id: &str) -> bool {
+        self.circuits
+            .iter()
+            .any(|pattern| circuit_pattern_matches(pattern, circuit_id))
+    }
+}
+
+/// Checks if the circuit ID matches the pattern, where each `*` in the pattern matches any
+/// sequence of characters (including an empty one).
+fn circuit_pattern_matches(pattern: &str, circuit_id: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // `split` always returns at least one item
+    let first = parts.next().unwrap_or("");
+    let mut remaining = match circuit_id.strip_prefix(first) {
+        Some(remaining) => remaining,
+        None => return false,
+    };
+
+    let mut parts = parts.peekable();
+    if parts.peek().is_none() {
+        // There are no wildcards, so the pattern must match the whole ID
+        return remaining.is_empty();
+    }
+
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() {
+            // The last part must match the end of the ID
+            return remaining.ends_with(part);
+        }
+        match remaining.find(part) {
+            Some(idx) => remaining = &remaining[idx + part.len()..],
+            None => return false,
+        }
+    }
+
+    true
+}
@@ -49,6 +49,17 @@ impl PermissionMap {
             .map(|(_, perm)| perm)
     }
 
+    /// Gets the ID of the circuit that a request is about, if any. This will attempt to match the
+    /// method and endpoint to a known (method, endpoint) pair and, if the matching endpoint has a
+    /// `{circuit}` or `{circuit_id}` path variable, returns the value of that variable from the
+    /// request's endpoint.
+    pub fn get_circuit_id<'a>(&self, method: &Method, endpoint: &'a str) -> Option<&'a str> {
+        self.internal
+            .iter()
+            .find(|(req, _)| req.matches(&method, endpoint))
+            .and_then(|(req, _)| req.circuit_id(endpoint))
+    }
+
     /// Takes the contents of another `PermissionMap` and merges them into itself. This consumes the
     /// contents of the other map.
     pub fn append(&mut self, other: &mut PermissionMap) {
@@ -60,19 +71,30 @@ impl PermissionMap {
 struct RequestDefinition {
     method: Method,
     path: Vec<PathComponent>,
+    /// The index of the path component that holds the circuit ID, if any
+    circuit_index: Option<usize>,
 }
 
 impl RequestDefinition {
     /// Creates a new request definition
     pub fn new(method: Method, endpoint: &str) -> Self {
-        let path = endpoint
+        let components = endpoint
             .strip_prefix('/')
             .unwrap_or(endpoint)
             .split('/')
-            .map(PathComponent::from)
-            .collect();
+            .collect::<Vec<_>>();
+
+        let circuit_index = components
+            .iter()
+            .position(|component| *component == "{circuit}" || *component == "{circuit_id}");
+
+        let path = components.into_iter().map(PathComponent::from).collect();
 
-        Self { method, path }
+        Self {
+            method,
+            path,
+            circuit_index,
+        }
     }
 
     /// Checks if the given request matches this definition, considering any variable path
@@ -93,6 +115,17 @@ impl RequestDefinition {
                     .unwrap_or(false)
             })
     }
+
+    /// Gets the value of the circuit ID path variable from the given endpoint, if this definition
+    /// has one. The endpoint is assumed to match this definition.
+    pub fn circuit_id<'a>(&self, endpoint: &'a str) -> Option<&'a str> {
+        let circuit_index = self.circuit_index?;
+        endpoint
+            .strip_prefix('/')
+            .unwrap_or(endpoint)
+            .split('/')
+            .nth(circuit_index)
+    }
 }
 
 /// A component of an endpoint path
@@ -210,4 +243,48 @@ mod tests {
             None
         );
     }
+
+    /// Verifies that the `PermissionMap` returns the circuit ID from requests to endpoints with a
+    /// `{circuit}` or `{circuit_id}` path variable, and nothing for other endpoints
+    #[test]
+    fn permission_map_circuit_id() {
+        let perm = Permission::Check {
+            permission_id: "perm",
+            permission_display_name: "",
+            permission_description: "",
+        };
+
+        let mut map = PermissionMap::new();
+        map.add_permission(Method::Get, "/admin/circuits", perm);
+        map.add_permission(Method::Get, "/admin/circuits/{circuit_id}", perm);
+        map.add_permission(Method::Get, "admin/proposals/{circuit_id}", perm);
+        map.add_permission(
+            Method::Post,
+            "/scabbard/{circuit}/{service_id}/batches",
+            perm,
+        );
+        map.add_permission(Method::Get, "/test/endpoint/{variable}", perm);
+
+        assert_eq!(map.get_circuit_id(&Method::Get, "/admin/circuits"), None);
+        assert_eq!(
+            map.get_circuit_id(&Method::Get, "/admin/circuits/abcde-01234"),
+            Some("abcde-01234")
+        );
+        assert_eq!(
+            map.get_circuit_id(&Method::Get, "/admin/proposals/abcde-01234"),
+            Some("abcde-01234")
+        );
+        assert_eq!(
+            map.get_circuit_id(&Method::Post, "/scabbard/abcde-01234/a000/batches"),
+            Some("abcde-01234")
+        );
+        assert_eq!(
+            map.get_circuit_id(&Method::Get, "/test/endpoint/val1"),
+            None
+        );
+        assert_eq!(
+            map.get_circuit_id(&Method::Put, "/admin/circuits/abcde-01234"),
+            None
+        );
+    }
 }
@@ -27,6 +27,11 @@ use super::store::{RoleBasedAuthorizationStore, ADMIN_ROLE_ID};
 /// it has been assigned.  If one of the identity's assigned roles contains the permission, then
 /// the identity is allowed access. If not, the handler defers to the next handler in the chain.
 ///
+/// Assignments that are limited to a set of circuits are only considered for requests about one of
+/// those circuits; requests that are not about a specific circuit require an assignment that
+/// applies to the whole node, unless the endpoint limits its response to the assignment's circuits
+/// (see [`Permission::CheckForCircuits`](crate::rest_api::auth::authorization::Permission)).
+///
 /// It currently does not deny any permissions.
 pub struct RoleBasedAuthorizationHandler {
     role_based_auth_store: Box<dyn RoleBasedAuthorizationStore>,
@@ -41,25 +46,114 @@ impl RoleBasedAuthorizationHandler {
     }
 }
 
+impl RoleBasedAuthorizationHandler {
+    /// Checks the identity's assigned roles for the permission, if the identity's assignment
+    /// applies to the given circuit; if no circuit is given, the assignment must apply to the
+    /// whole node.
+    fn check_assigned_roles(
+        &self,
+        identity: &Identity,
+        permission_id: &str,
+        circuit_id: Option<&str>,
+    ) -> Result<AuthorizationHandlerResult, InternalError> {
+        let identity = match identity.into() {
+            Some(identity) => identity,
+            None => return Ok(AuthorizationHandlerResult::Continue),
+        };
+
+        let assignment_applies = self
+            .role_based_auth_store
+            .get_assignment(&identity)
+            .map_err(|err| InternalError::from_source(Box::new(err)))?
+            .map(|assignment| match circuit_id {
+                Some(circuit_id) => assignment.applies_to_circuit(circuit_id),
+                None => assignment.is_node_wide(),
+            })
+            .unwrap_or(false);
+
+        if !assignment_applies {
+            return Ok(AuthorizationHandlerResult::Continue);
+        }
+
+        Ok(self
+            .role_based_auth_store
+            .get_assigned_roles(&identity)
+            .map_err(|err| InternalError::from_source(Box::new(err)))?
+            .find(|role| {
+                role.id() == ADMIN_ROLE_ID
+                    || role.permissions().iter().any(|perm| perm == permission_id)
+            })
+            .map(|_| AuthorizationHandlerResult::Allow)
+            .unwrap_or(AuthorizationHandlerResult::Continue))
+    }
+
+    /// Checks if one of the identity's assigned roles has the permission, returning the circuits
+    /// that the identity's assignment is limited to. If the assignment applies to the whole node,
+    /// or none of the roles has the permission, no circuits are returned.
+    fn scoped_circuits(
+        &self,
+        identity: &Identity,
+        permission_id: &str,
+    ) -> Result<Option<Vec<String>>, InternalError> {
+        let identity = match identity.into() {
+            Some(identity) => identity,
+            None => return Ok(None),
+        };
+
+        let circuits = match self
+            .role_based_auth_store
+            .get_assignment(&identity)
+            .map_err(|err| InternalError::from_source(Box::new(err)))?
+        {
+            Some(assignment) if !assignment.is_node_wide() => assignment.circuits().to_vec(),
+            _ => return Ok(None),
+        };
+
+        let has_permission = self
+            .role_based_auth_store
+            .get_assigned_roles(&identity)
+            .map_err(|err| InternalError::from_source(Box::new(err)))?
+            .any(|role| {
+                role.id() == ADMIN_ROLE_ID
+                    || role.permissions().iter().any(|perm| perm == permission_id)
+            });
+
+        if has_permission {
+            Ok(Some(circuits))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
 impl AuthorizationHandler for RoleBasedAuthorizationHandler {
     fn has_permission(
         &self,
         identity: &Identity,
         permission_id: &str,
     ) -> Result<AuthorizationHandlerResult, InternalError> {
-        match identity.into() {
-            Some(identity) => Ok(self
-                .role_based_auth_store
-                .get_assigned_roles(&identity)
-                .map_err(|err| InternalError::from_source(Box::new(err)))?
-                .find(|role| {
-                    role.id() == ADMIN_ROLE_ID
-                        || role.permissions().iter().any(|perm| perm == permission_id)
-                })
-                .map(|_| AuthorizationHandlerResult::Allow)
-                .unwrap_or(AuthorizationHandlerResult::Continue)),
-            None => Ok(AuthorizationHandlerResult::Continue),
-        }
+        self.check_assigned_roles(identity, permission_id, None)
+    }
+
+    fn has_permission_for_circuit(
+        &self,
+        identity: &Identity,
+        permission_id: &str,
+        circuit_id: &str,
+    ) -> Result<AuthorizationHandlerResult, InternalError> {
+        self.check_assigned_roles(identity, permission_id, Some(circuit_id))
+    }
+
+    fn circuits_with_permission(
+        &self,
+        identity: &Identity,
+        permission_id: &str,
+    ) -> Result<Option<Vec<String>>, InternalError> {
+        self.scoped_circuits(identity, permission_id)
+    }
+
+    fn name(&self) -> &str {
+        "rbac"
     }
 
     fn clone_box(&self) -> Box<dyn AuthorizationHandler> {
@@ -154,6 +248,107 @@ mod tests {
         assert!(matches!(result, AuthorizationHandlerResult::Continue));
     }
 
+    /// This test checks that an assignment that is limited to a set of circuits only grants its
+    /// permissions for requests about a matching circuit:
+    /// 1. Add a role and an assignment of it that is limited to `abcde-01234` and `fghij-*`
+    /// 2. Verify that Allow is returned for the role's permission on both an exact and a pattern
+    ///    match
+    /// 3. Verify that Continue is returned for the role's permission on a non-matching circuit
+    /// 4. Verify that Continue is returned for the role's permission when no circuit is given
+    /// 5. Verify that the assignment's circuits are returned as the circuits with the role's
+    ///    permission, and that none are returned for a permission the role does not have
+    #[test]
+    fn circuit_scoped_assignment() {
+        let role_based_auth_store = create_role_based_authorization_store();
+
+        let role = RoleBuilder::new()
+            .with_id("test-role-1".into())
+            .with_display_name("Test Role 1".into())
+            .with_permissions(vec!["a".to_string(), "b".to_string(), "c".to_string()])
+            .build()
+            .expect("Unable to build role");
+
+        role_based_auth_store
+            .add_role(role)
+            .expect("Unable to add role");
+
+        let assignment = AssignmentBuilder::new()
+            .with_identity(StoreIdentity::User("some-user-id".into()))
+            .with_roles(vec!["test-role-1".to_string()])
+            .with_circuits(vec!["abcde-01234".to_string(), "fghij-*".to_string()])
+            .build()
+            .expect("Unable to build assignment");
+
+        role_based_auth_store
+            .add_assignment(assignment)
+            .expect("Unable to add assignment");
+
+        let handler = RoleBasedAuthorizationHandler::new(role_based_auth_store);
+        let identity = Identity::User("some-user-id".into());
+
+        let result = handler
+            .has_permission_for_circuit(&identity, "a", "abcde-01234")
+            .expect("Should have returned an auth result");
+        assert!(matches!(result, AuthorizationHandlerResult::Allow));
+
+        let result = handler
+            .has_permission_for_circuit(&identity, "a", "fghij-56789")
+            .expect("Should have returned an auth result");
+        assert!(matches!(result, AuthorizationHandlerResult::Allow));
+
+        let result = handler
+            .has_permission_for_circuit(&identity, "a", "klmno-01234")
+            .expect("Should have returned an auth result");
+        assert!(matches!(result, AuthorizationHandlerResult::Continue));
+
+        let result = handler
+            .has_permission(&identity, "a")
+            .expect("Should have returned an auth result");
+        assert!(matches!(result, AuthorizationHandlerResult::Continue));
+
+        let circuits = handler
+            .circuits_with_permission(&identity, "a")
+            .expect("Should have returned the circuits");
+        assert_eq!(
+            circuits,
+            Some(vec!["abcde-01234".to_string(), "fghij-*".to_string()])
+        );
+
+        let circuits = handler
+            .circuits_with_permission(&identity, "z")
+            .expect("Should have returned the circuits");
+        assert_eq!(circuits, None);
+    }
+
+    /// This test checks that an assignment that applies to the whole node grants its permissions
+    /// for requests about any circuit, and is not limited to any circuits.
+    #[test]
+    fn node_wide_assignment_for_circuit() {
+        let role_based_auth_store = create_role_based_authorization_store();
+
+        let assignment = AssignmentBuilder::new()
+            .with_identity(StoreIdentity::Key("abc123".into()))
+            .with_roles(vec![ADMIN_ROLE_ID.to_string()])
+            .build()
+            .expect("Unable to build assignment");
+
+        role_based_auth_store
+            .add_assignment(assignment)
+            .expect("Unable to add assignment");
+
+        let handler = RoleBasedAuthorizationHandler::new(role_based_auth_store);
+
+        let result = handler
+            .has_permission_for_circuit(&Identity::Key("abc123".into()), "perm", "abcde-01234")
+            .expect("Should have returned an auth result");
+        assert!(matches!(result, AuthorizationHandlerResult::Allow));
+
+        let circuits = handler
+            .circuits_with_permission(&Identity::Key("abc123".into()), "perm")
+            .expect("Should have returned the circuits");
+        assert_eq!(circuits, None);
+    }
+
     /// This test checks that an identity with an assigned role will return Allow when queried.
     fn test_allow_identity_with_assignment(identity: Identity, store_identity: StoreIdentity) {
         let role_based_auth_store = create_role_based_authorization_store();
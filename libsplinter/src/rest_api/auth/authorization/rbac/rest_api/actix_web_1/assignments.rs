@@ -204,6 +204,7 @@ macro_rules! try_identity_from_req {
         let identity = match identity_type.as_str() {
             "key" => Identity::Key(identity),
             "user" => Identity::User(identity),
+            "api_key" => Identity::ApiKey(identity),
             _ => {
                 return Box::new(
                     HttpResponse::BadRequest()
@@ -312,16 +313,18 @@ fn patch_assignment(
 fn update_assignment(
     role_based_auth_store: &dyn RoleBasedAuthorizationStore,
     identity: &Identity,
-    AssignmentUpdatePayload { roles }: AssignmentUpdatePayload,
+    AssignmentUpdatePayload { roles, circuits }: AssignmentUpdatePayload,
 ) -> Result<(), SendableRoleBasedAuthorizationStoreError> {
     role_based_auth_store
         .get_assignment(identity)
         .map_err(SendableRoleBasedAuthorizationStoreError::from)
         .and_then(|assignment_opt| {
             if let Some(assignment) = assignment_opt {
-                let updated_assignment = assignment
-                    .into_update_builder()
-                    .with_roles(roles)
+                let mut update_builder = assignment.into_update_builder().with_roles(roles);
+                if let Some(circuits) = circuits {
+                    update_builder = update_builder.with_circuits(circuits);
+                }
+                let updated_assignment = update_builder
                     .build()
                     .map_err(SendableRoleBasedAuthorizationStoreError::InvalidState)?;
 
@@ -334,6 +337,7 @@ fn update_assignment(
                     match identity {
                         Identity::Key(key) => key,
                         Identity::User(user) => user,
+                        Identity::ApiKey(key_id) => key_id,
                     }
                 )))
             }
@@ -740,6 +744,123 @@ mod tests {
         join_handle.join().expect("Unable to join rest api thread");
     }
 
+    /// Tests a POST /authorization/assignments with an assignment that is limited to a set of
+    /// circuits, and a PATCH that makes the assignment apply to the whole node.
+    /// 1. Add a role to the store
+    /// 2. POST an assignment of the role with two circuit patterns
+    /// 3. Verify the assignment's circuits by GET /authorization/assignments/user/Bob
+    /// 4. PATCH the assignment with an empty list of circuits
+    /// 5. Verify the assignment no longer reports any circuits
+    #[test]
+    fn test_post_and_patch_assignment_circuits_ok() {
+        let role_based_auth_store = MemRoleBasedAuthorizationStore::default();
+
+        let role = RoleBuilder::new()
+            .with_id("role-1".into())
+            .with_display_name("Test Role 1".into())
+            .with_permissions(vec!["a".to_string(), "b".to_string(), "c".to_string()])
+            .build()
+            .expect("Unable to build role");
+
+        role_based_auth_store
+            .add_role(role)
+            .expect("Unable to add role");
+
+        let (shutdown_handle, join_handle, bind_url) = run_rest_api_on_open_port(vec![
+            make_assignments_resource(Box::new(role_based_auth_store.clone())),
+            make_assignment_resource(Box::new(role_based_auth_store.clone())),
+        ]);
+
+        let url = Url::parse(&format!("http://{}/authorization/assignments", bind_url))
+            .expect("Failed to parse URL");
+
+        let resp = Client::new()
+            .post(url)
+            .header(
+                "SplinterProtocolVersion",
+                protocol::AUTHORIZATION_PROTOCOL_VERSION,
+            )
+            .json(&json!({
+                "identity": "Bob",
+                "identity_type": "user",
+                "roles": ["role-1"],
+                "circuits": ["abcde-01234", "fghij-*"],
+            }))
+            .send()
+            .expect("Failed to perform request");
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let url = Url::parse(&format!(
+            "http://{}/authorization/assignments/user/Bob",
+            bind_url
+        ))
+        .expect("Failed to parse URL");
+
+        let resp = Client::new()
+            .get(url.clone())
+            .header(
+                "SplinterProtocolVersion",
+                protocol::AUTHORIZATION_PROTOCOL_VERSION,
+            )
+            .send()
+            .expect("Failed to perform request");
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body: JsonValue = resp.json().expect("Failed to deserialize body");
+        assert_eq!(
+            json!({
+                "data": {
+                    "identity": "Bob",
+                    "identity_type": "user",
+                    "roles": ["role-1"],
+                    "circuits": ["abcde-01234", "fghij-*"],
+                }
+            }),
+            body
+        );
+
+        let resp = Client::new()
+            .patch(url.clone())
+            .header(
+                "SplinterProtocolVersion",
+                protocol::AUTHORIZATION_PROTOCOL_VERSION,
+            )
+            .json(&json!({
+                "roles": ["role-1"],
+                "circuits": [],
+            }))
+            .send()
+            .expect("Failed to perform request");
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let resp = Client::new()
+            .get(url)
+            .header(
+                "SplinterProtocolVersion",
+                protocol::AUTHORIZATION_PROTOCOL_VERSION,
+            )
+            .send()
+            .expect("Failed to perform request");
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body: JsonValue = resp.json().expect("Failed to deserialize body");
+        assert_eq!(
+            json!({
+                "data": {
+                    "identity": "Bob",
+                    "identity_type": "user",
+                    "roles": ["role-1"],
+                }
+            }),
+            body
+        );
+
+        shutdown_handle
+            .shutdown()
+            .expect("Unable to shutdown rest api");
+        join_handle.join().expect("Unable to join rest api thread");
+    }
+
     /// Test a POST /authorization/assignments request with a duplicate assignment returns a 409
     /// 1. Add two roles to the store
     /// 2. POST an assignment which includes the two roles
@@ -1498,6 +1619,7 @@ mod tests {
         match identity {
             Identity::Key(key) => format!("key-{}", key),
             Identity::User(user) => format!("user-{}", user),
+            Identity::ApiKey(key_id) => format!("api_key-{}", key_id),
         }
     }
 }
@@ -31,6 +31,13 @@ pub struct AssignmentResponse<'a> {
     #[serde(flatten)]
     identity: IdentityResponse<'a>,
     roles: &'a [String],
+    #[serde(skip_serializing_if = "is_node_wide")]
+    circuits: &'a [String],
+}
+
+/// Assignments that apply to the whole node have no circuits to report.
+fn is_node_wide(circuits: &&[String]) -> bool {
+    circuits.is_empty()
 }
 
 #[derive(Serialize)]
@@ -39,6 +46,8 @@ pub struct AssignmentResponse<'a> {
 pub enum IdentityResponse<'a> {
     Key(&'a str),
     User(&'a str),
+    #[serde(rename = "api_key")]
+    ApiKey(&'a str),
 }
 
 impl<'a> From<&'a Assignment> for AssignmentResponse<'a> {
@@ -46,6 +55,7 @@ impl<'a> From<&'a Assignment> for AssignmentResponse<'a> {
         Self {
             identity: assignment.identity().into(),
             roles: assignment.roles(),
+            circuits: assignment.circuits(),
         }
     }
 }
@@ -54,6 +64,7 @@ impl<'a> From<&'a Identity> for IdentityResponse<'a> {
     fn from(identity: &'a Identity) -> Self {
         match identity {
             Identity::User(user) => IdentityResponse::User(user),
+            Identity::ApiKey(key_id) => IdentityResponse::ApiKey(key_id),
             Identity::Key(key) => IdentityResponse::Key(key),
         }
     }
@@ -64,6 +75,8 @@ pub struct AssignmentPayload {
     #[serde(flatten)]
     identity: IdentityPayload,
     roles: Vec<String>,
+    #[serde(default)]
+    circuits: Vec<String>,
 }
 
 #[derive(Deserialize)]
@@ -72,20 +85,28 @@ pub struct AssignmentPayload {
 pub enum IdentityPayload {
     Key(String),
     User(String),
+    #[serde(rename = "api_key")]
+    ApiKey(String),
 }
 
 impl TryFrom<AssignmentPayload> for Assignment {
     type Error = InvalidStateError;
 
     fn try_from(
-        AssignmentPayload { identity, roles }: AssignmentPayload,
+        AssignmentPayload {
+            identity,
+            roles,
+            circuits,
+        }: AssignmentPayload,
     ) -> Result<Self, Self::Error> {
         AssignmentBuilder::new()
             .with_identity(match identity {
                 IdentityPayload::Key(key) => Identity::Key(key),
                 IdentityPayload::User(user) => Identity::User(user),
+                IdentityPayload::ApiKey(key_id) => Identity::ApiKey(key_id),
             })
             .with_roles(roles)
+            .with_circuits(circuits)
             .build()
     }
 }
@@ -93,4 +114,7 @@ impl TryFrom<AssignmentPayload> for Assignment {
 #[derive(Deserialize)]
 pub struct AssignmentUpdatePayload {
     pub roles: Vec<String>,
+    /// The updated circuit ID patterns; if not provided, the assignment's circuits are unchanged
+    #[serde(default)]
+    pub circuits: Option<Vec<String>>,
 }
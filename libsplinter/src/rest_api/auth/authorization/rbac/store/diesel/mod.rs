@@ -359,9 +359,15 @@ impl TryFrom<(models::RoleModel, Vec<models::RolePermissionModel>)> for Role {
     }
 }
 
-impl From<Assignment> for (models::IdentityModel, Vec<models::AssignmentModel>) {
+impl From<Assignment>
+    for (
+        models::IdentityModel,
+        Vec<models::AssignmentModel>,
+        Vec<models::AssignmentCircuitModel>,
+    )
+{
     fn from(assignment: Assignment) -> Self {
-        let (identity, roles) = assignment.into_parts();
+        let (identity, roles, circuits) = assignment.into_parts();
 
         let identity_model = match identity {
             Identity::Key(identity) => models::IdentityModel {
@@ -372,6 +378,10 @@ impl From<Assignment> for (models::IdentityModel, Vec<models::AssignmentModel>)
                 identity,
                 identity_type: models::IdentityModelType::User,
             },
+            Identity::ApiKey(identity) => models::IdentityModel {
+                identity,
+                identity_type: models::IdentityModelType::ApiKey,
+            },
         };
 
         let role_models = roles
@@ -382,15 +392,33 @@ impl From<Assignment> for (models::IdentityModel, Vec<models::AssignmentModel>)
             })
             .collect::<Vec<_>>();
 
-        (identity_model, role_models)
+        let circuit_models = circuits
+            .into_iter()
+            .map(|circuit_pattern| models::AssignmentCircuitModel {
+                identity: identity_model.identity.clone(),
+                circuit_pattern,
+            })
+            .collect::<Vec<_>>();
+
+        (identity_model, role_models, circuit_models)
     }
 }
 
-impl TryFrom<(models::IdentityModel, Vec<models::AssignmentModel>)> for Assignment {
+impl
+    TryFrom<(
+        models::IdentityModel,
+        Vec<models::AssignmentModel>,
+        Vec<models::AssignmentCircuitModel>,
+    )> for Assignment
+{
     type Error = InvalidStateError;
 
     fn try_from(
-        (identity_model, assignments): (models::IdentityModel, Vec<models::AssignmentModel>),
+        (identity_model, assignments, circuits): (
+            models::IdentityModel,
+            Vec<models::AssignmentModel>,
+            Vec<models::AssignmentCircuitModel>,
+        ),
     ) -> Result<Self, Self::Error> {
         let models::IdentityModel {
             identity,
@@ -399,6 +427,7 @@ impl TryFrom<(models::IdentityModel, Vec<models::AssignmentModel>)> for Assignme
         let identity = match identity_type {
             models::IdentityModelType::Key => Identity::Key(identity),
             models::IdentityModelType::User => Identity::User(identity),
+            models::IdentityModelType::ApiKey => Identity::ApiKey(identity),
         };
         // We create the assignment directly, vs using the builder, as a deleted role may result
         // in an empty assignment.  The builder prevents the library user from constructing an
@@ -410,6 +439,14 @@ impl TryFrom<(models::IdentityModel, Vec<models::AssignmentModel>)> for Assignme
                 .into_iter()
                 .map(|models::AssignmentModel { role_id, .. }| role_id)
                 .collect(),
+            circuits: circuits
+                .into_iter()
+                .map(
+                    |models::AssignmentCircuitModel {
+                         circuit_pattern, ..
+                     }| circuit_pattern,
+                )
+                .collect(),
         })
     }
 }
@@ -1163,6 +1200,92 @@ mod tests {
         assert!(stored_assignment.roles().is_empty());
     }
 
+    /// This test verifies the following:
+    /// 1. Add a role
+    /// 2. Add an assignment of the role that is limited to two circuit patterns
+    /// 3. Verify that the circuit patterns are returned via the store API
+    /// 4. Update the assignment to apply to the whole node and verify via the store API
+    /// 5. Remove the assignment and verify that it is no longer returned
+    #[test]
+    fn sqlite_circuit_scoped_assignment() {
+        let pool = create_connection_pool_and_migrate();
+
+        let role_based_auth_store = DieselRoleBasedAuthorizationStore::new(pool);
+
+        let role = RoleBuilder::new()
+            .with_id("test-role-1".into())
+            .with_display_name("Test Role 1".into())
+            .with_permissions(vec!["a".to_string(), "b".to_string(), "c".to_string()])
+            .build()
+            .expect("Unable to build role");
+
+        role_based_auth_store
+            .add_role(role)
+            .expect("Unable to add role");
+
+        let assignment = AssignmentBuilder::new()
+            .with_identity(Identity::User("some-user-id".into()))
+            .with_roles(vec!["test-role-1".to_string()])
+            .with_circuits(vec!["abcde-01234".to_string(), "fghij-*".to_string()])
+            .build()
+            .expect("Unable to build assignment");
+
+        role_based_auth_store
+            .add_assignment(assignment)
+            .expect("Unable to add assignment");
+
+        let stored_assignment = role_based_auth_store
+            .get_assignment(&Identity::User("some-user-id".into()))
+            .expect("Unable to get assignment")
+            .expect("Assignment was not found");
+
+        assert_eq!(
+            &vec!["abcde-01234".to_string(), "fghij-*".to_string()],
+            stored_assignment.circuits()
+        );
+        assert!(!stored_assignment.is_node_wide());
+        assert!(stored_assignment.applies_to_circuit("abcde-01234"));
+        assert!(stored_assignment.applies_to_circuit("fghij-56789"));
+        assert!(!stored_assignment.applies_to_circuit("klmno-01234"));
+
+        let listed_assignments = role_based_auth_store
+            .list_assignments()
+            .expect("Unable to list assignments")
+            .collect::<Vec<_>>();
+        assert_eq!(1, listed_assignments.len());
+        assert_eq!(
+            &vec!["abcde-01234".to_string(), "fghij-*".to_string()],
+            listed_assignments[0].circuits()
+        );
+
+        let updated_assignment = stored_assignment
+            .into_update_builder()
+            .with_circuits(vec![])
+            .build()
+            .expect("Unable to build updated assignment");
+
+        role_based_auth_store
+            .update_assignment(updated_assignment)
+            .expect("Unable to update assignment");
+
+        let stored_assignment = role_based_auth_store
+            .get_assignment(&Identity::User("some-user-id".into()))
+            .expect("Unable to get assignment")
+            .expect("Assignment was not found");
+
+        assert!(stored_assignment.is_node_wide());
+        assert!(stored_assignment.applies_to_circuit("klmno-01234"));
+
+        role_based_auth_store
+            .remove_assignment(&Identity::User("some-user-id".into()))
+            .expect("Unable to remove assignment");
+
+        assert!(role_based_auth_store
+            .get_assignment(&Identity::User("some-user-id".into()))
+            .expect("Unable to get assignment")
+            .is_none());
+    }
+
     /// This tests verifies that the `admin` role is present by default and cannot be removed or
     /// modified
     #[test]
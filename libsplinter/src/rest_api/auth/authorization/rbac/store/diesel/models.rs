@@ -35,7 +35,7 @@ use diesel::pg::Pg;
 #[cfg(feature = "sqlite")]
 use diesel::sqlite::Sqlite;
 
-use super::schema::{assignments, identities, role_permissions, roles};
+use super::schema::{assignment_circuits, assignments, identities, role_permissions, roles};
 
 #[derive(Debug, PartialEq, Associations, Identifiable, Insertable, Queryable)]
 #[table_name = "roles"]
@@ -58,6 +58,7 @@ pub(super) struct RolePermissionModel {
 pub(super) enum IdentityModelType {
     Key,
     User,
+    ApiKey,
 }
 
 // This has to be pub, due to its use in the table macro execution for IdentityModel
@@ -125,6 +126,7 @@ impl<DB: Backend> ToSql<IdentityModelTypeMapping, DB> for IdentityModelType {
         match self {
             IdentityModelType::Key => out.write_all(b"key")?,
             IdentityModelType::User => out.write_all(b"user")?,
+            IdentityModelType::ApiKey => out.write_all(b"api_key")?,
         }
         Ok(IsNull::No)
     }
@@ -168,6 +170,7 @@ impl FromSql<IdentityModelTypeMapping, Pg> for IdentityModelType {
         match bytes {
             Some(b"key") => Ok(IdentityModelType::Key),
             Some(b"user") => Ok(IdentityModelType::User),
+            Some(b"api_key") => Ok(IdentityModelType::ApiKey),
             Some(v) => Err(format!(
                 "Unrecognized enum variant: '{}'",
                 String::from_utf8_lossy(v)
@@ -191,6 +194,7 @@ impl FromSql<IdentityModelTypeMapping, Sqlite> for IdentityModelType {
         match bytes.map(|v| v.read_blob()) {
             Some(b"key") => Ok(IdentityModelType::Key),
             Some(b"user") => Ok(IdentityModelType::User),
+            Some(b"api_key") => Ok(IdentityModelType::ApiKey),
             Some(blob) => {
                 Err(format!("Unexpected variant: {}", String::from_utf8_lossy(blob)).into())
             }
@@ -222,3 +226,12 @@ pub(super) struct AssignmentModel {
     pub identity: String,
     pub role_id: String,
 }
+
+#[derive(Debug, PartialEq, Associations, Identifiable, Insertable, Queryable)]
+#[table_name = "assignment_circuits"]
+#[belongs_to(IdentityModel, foreign_key = "identity")]
+#[primary_key(identity, circuit_pattern)]
+pub(super) struct AssignmentCircuitModel {
+    pub identity: String,
+    pub circuit_pattern: String,
+}
@@ -16,8 +16,8 @@ use diesel::{dsl::insert_into, prelude::*};
 
 use crate::rest_api::auth::authorization::rbac::store::{
     diesel::{
-        models::{AssignmentModel, IdentityModel},
-        schema::{assignments, identities},
+        models::{AssignmentCircuitModel, AssignmentModel, IdentityModel},
+        schema::{assignment_circuits, assignments, identities},
     },
     Assignment, RoleBasedAuthorizationStoreError,
 };
@@ -39,7 +39,11 @@ impl<'a> RoleBasedAuthorizationStoreAddAssignment
         &self,
         assignment: Assignment,
     ) -> Result<(), RoleBasedAuthorizationStoreError> {
-        let (identity, assignments): (IdentityModel, Vec<AssignmentModel>) = assignment.into();
+        let (identity, assignments, circuits): (
+            IdentityModel,
+            Vec<AssignmentModel>,
+            Vec<AssignmentCircuitModel>,
+        ) = assignment.into();
         self.conn.transaction::<_, _, _>(|| {
             insert_into(identities::table)
                 .values(identity)
@@ -49,6 +53,12 @@ impl<'a> RoleBasedAuthorizationStoreAddAssignment
                 .values(assignments)
                 .execute(self.conn)?;
 
+            if !circuits.is_empty() {
+                insert_into(assignment_circuits::table)
+                    .values(circuits)
+                    .execute(self.conn)?;
+            }
+
             Ok(())
         })
     }
@@ -62,7 +72,11 @@ impl<'a> RoleBasedAuthorizationStoreAddAssignment
         &self,
         assignment: Assignment,
     ) -> Result<(), RoleBasedAuthorizationStoreError> {
-        let (identity, assignments): (IdentityModel, Vec<AssignmentModel>) = assignment.into();
+        let (identity, assignments, circuits): (
+            IdentityModel,
+            Vec<AssignmentModel>,
+            Vec<AssignmentCircuitModel>,
+        ) = assignment.into();
         self.conn.transaction::<_, _, _>(|| {
             insert_into(identities::table)
                 .values(identity)
@@ -72,6 +86,12 @@ impl<'a> RoleBasedAuthorizationStoreAddAssignment
                 .values(assignments)
                 .execute(self.conn)?;
 
+            if !circuits.is_empty() {
+                insert_into(assignment_circuits::table)
+                    .values(circuits)
+                    .execute(self.conn)?;
+            }
+
             Ok(())
         })
     }
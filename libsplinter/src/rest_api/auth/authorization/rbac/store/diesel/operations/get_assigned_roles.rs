@@ -52,6 +52,7 @@ where
         let search_identity = match identity {
             Identity::Key(ref key) => key,
             Identity::User(ref user_id) => user_id,
+            Identity::ApiKey(ref key_id) => key_id,
         };
         self.conn
             .transaction::<Box<dyn ExactSizeIterator<Item = Role>>, _, _>(|| {
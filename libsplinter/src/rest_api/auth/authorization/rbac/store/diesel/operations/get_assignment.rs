@@ -18,7 +18,10 @@ use diesel::prelude::*;
 
 use crate::rest_api::auth::authorization::rbac::store::{
     diesel::{
-        models::{AssignmentModel, IdentityModel, IdentityModelType, IdentityModelTypeMapping},
+        models::{
+            AssignmentCircuitModel, AssignmentModel, IdentityModel, IdentityModelType,
+            IdentityModelTypeMapping,
+        },
         schema::identities,
     },
     Assignment, Identity, RoleBasedAuthorizationStoreError,
@@ -49,6 +52,7 @@ where
         let search_identity = match identity {
             Identity::Key(ref key) => key,
             Identity::User(ref user_id) => user_id,
+            Identity::ApiKey(ref key_id) => key_id,
         };
         self.conn.transaction(|| {
             let identities = identities::table
@@ -59,11 +63,18 @@ where
                 .load::<AssignmentModel>(self.conn)?
                 .grouped_by(&identities);
 
+            let circuits = AssignmentCircuitModel::belonging_to(&identities)
+                .load::<AssignmentCircuitModel>(self.conn)?
+                .grouped_by(&identities);
+
             identities
                 .into_iter()
                 .zip(assignments)
+                .zip(circuits)
                 .next()
-                .map(|model| model.try_into())
+                .map(|((identity, assignments), circuits)| {
+                    (identity, assignments, circuits).try_into()
+                })
                 .transpose()
                 .map_err(RoleBasedAuthorizationStoreError::from)
         })
@@ -18,7 +18,10 @@ use diesel::prelude::*;
 
 use crate::rest_api::auth::authorization::rbac::store::{
     diesel::{
-        models::{AssignmentModel, IdentityModel, IdentityModelType, IdentityModelTypeMapping},
+        models::{
+            AssignmentCircuitModel, AssignmentModel, IdentityModel, IdentityModelType,
+            IdentityModelTypeMapping,
+        },
         schema::identities,
     },
     Assignment, RoleBasedAuthorizationStoreError,
@@ -53,11 +56,18 @@ where
                     .load::<AssignmentModel>(self.conn)?
                     .grouped_by(&identities);
 
+                let circuits = AssignmentCircuitModel::belonging_to(&identities)
+                    .load::<AssignmentCircuitModel>(self.conn)?
+                    .grouped_by(&identities);
+
                 Ok(Box::new(
                     identities
                         .into_iter()
                         .zip(assignments)
-                        .map(|models| models.try_into())
+                        .zip(circuits)
+                        .map(|((identity, assignments), circuits)| {
+                            (identity, assignments, circuits).try_into()
+                        })
                         .collect::<Result<Vec<_>, _>>()
                         .map_err(RoleBasedAuthorizationStoreError::from)?
                         .into_iter(),
@@ -17,7 +17,7 @@ use diesel::{dsl::delete, prelude::*};
 use crate::rest_api::auth::authorization::rbac::store::{
     diesel::{
         models::IdentityModelTypeMapping,
-        schema::{assignments, identities},
+        schema::{assignment_circuits, assignments, identities},
     },
     Identity, RoleBasedAuthorizationStoreError,
 };
@@ -45,10 +45,16 @@ where
         let search_identity = match identity {
             Identity::Key(ref key) => key,
             Identity::User(ref user_id) => user_id,
+            Identity::ApiKey(ref key_id) => key_id,
         };
         self.conn.transaction::<_, _, _>(|| {
             delete(assignments::table.filter(assignments::identity.eq(search_identity)))
                 .execute(self.conn)?;
+            delete(
+                assignment_circuits::table
+                    .filter(assignment_circuits::identity.eq(search_identity)),
+            )
+            .execute(self.conn)?;
             delete(identities::table.filter(identities::identity.eq(search_identity)))
                 .execute(self.conn)?;
 
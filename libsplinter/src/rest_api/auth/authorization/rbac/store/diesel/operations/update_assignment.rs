@@ -20,8 +20,8 @@ use diesel::{
 use crate::error::{ConstraintViolationError, ConstraintViolationType};
 use crate::rest_api::auth::authorization::rbac::store::{
     diesel::{
-        models::{AssignmentModel, IdentityModel},
-        schema::{assignments, identities},
+        models::{AssignmentCircuitModel, AssignmentModel, IdentityModel},
+        schema::{assignment_circuits, assignments, identities},
     },
     Assignment, RoleBasedAuthorizationStoreError,
 };
@@ -43,7 +43,11 @@ impl<'a> RoleBasedAuthorizationStoreUpdateAssignment
         &self,
         assignment: Assignment,
     ) -> Result<(), RoleBasedAuthorizationStoreError> {
-        let (identity, roles): (IdentityModel, Vec<AssignmentModel>) = assignment.into();
+        let (identity, roles, circuits): (
+            IdentityModel,
+            Vec<AssignmentModel>,
+            Vec<AssignmentCircuitModel>,
+        ) = assignment.into();
         self.conn.transaction::<_, _, _>(|| {
             let count = identities::table
                 .filter(
@@ -69,6 +73,18 @@ impl<'a> RoleBasedAuthorizationStoreUpdateAssignment
                 .values(roles)
                 .execute(self.conn)?;
 
+            delete(
+                assignment_circuits::table
+                    .filter(assignment_circuits::identity.eq(&identity.identity)),
+            )
+            .execute(self.conn)?;
+
+            if !circuits.is_empty() {
+                insert_into(assignment_circuits::table)
+                    .values(circuits)
+                    .execute(self.conn)?;
+            }
+
             Ok(())
         })
     }
@@ -82,7 +98,11 @@ impl<'a> RoleBasedAuthorizationStoreUpdateAssignment
         &self,
         assignment: Assignment,
     ) -> Result<(), RoleBasedAuthorizationStoreError> {
-        let (identity, roles): (IdentityModel, Vec<AssignmentModel>) = assignment.into();
+        let (identity, roles, circuits): (
+            IdentityModel,
+            Vec<AssignmentModel>,
+            Vec<AssignmentCircuitModel>,
+        ) = assignment.into();
         self.conn.transaction::<_, _, _>(|| {
             let count = identities::table
                 .filter(
@@ -108,6 +128,18 @@ impl<'a> RoleBasedAuthorizationStoreUpdateAssignment
                 .values(roles)
                 .execute(self.conn)?;
 
+            delete(
+                assignment_circuits::table
+                    .filter(assignment_circuits::identity.eq(&identity.identity)),
+            )
+            .execute(self.conn)?;
+
+            if !circuits.is_empty() {
+                insert_into(assignment_circuits::table)
+                    .values(circuits)
+                    .execute(self.conn)?;
+            }
+
             Ok(())
         })
     }
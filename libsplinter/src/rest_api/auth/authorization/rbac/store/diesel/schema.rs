@@ -44,3 +44,10 @@ table! {
         role_id -> Text,
     }
 }
+
+table! {
+    assignment_circuits (identity, circuit_pattern) {
+        identity -> Text,
+        circuit_pattern -> Text,
+    }
+}
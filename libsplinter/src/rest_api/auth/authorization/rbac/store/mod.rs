@@ -19,6 +19,7 @@ mod diesel;
 mod error;
 
 use crate::error::InvalidStateError;
+use crate::rest_api::auth::authorization::circuit_pattern_matches;
 
 #[cfg(feature = "diesel")]
 pub use self::diesel::DieselRoleBasedAuthorizationStore;
@@ -201,6 +202,8 @@ pub enum Identity {
     Key(String),
     /// A user ID-based identity.
     User(String),
+    /// An API key-based identity.
+    ApiKey(String),
 }
 
 impl From<&crate::rest_api::auth::identity::Identity> for Option<Identity> {
@@ -214,15 +217,26 @@ impl From<&crate::rest_api::auth::identity::Identity> for Option<Identity> {
             crate::rest_api::auth::identity::Identity::User(user_id) => {
                 Some(Identity::User(user_id.to_string()))
             }
+            #[cfg(feature = "api-keys")]
+            crate::rest_api::auth::identity::Identity::ApiKey(key_id) => {
+                Some(Identity::ApiKey(key_id.to_string()))
+            }
         }
     }
 }
 
 /// An assignment of roles to a particular identity.
+///
+/// An assignment may be limited to a set of circuits, which are given as circuit ID patterns. A
+/// pattern is either a circuit ID or a circuit ID containing `*` wildcards, which match any
+/// sequence of characters (for example, `abcde-*`). An assignment with circuits only grants its
+/// roles' permissions for requests about a matching circuit, or for listing the matching circuits;
+/// an assignment without circuits applies to the whole node.
 #[derive(Clone)]
 pub struct Assignment {
     identity: Identity,
     roles: Vec<String>,
+    circuits: Vec<String>,
 }
 
 impl Assignment {
@@ -236,16 +250,45 @@ impl Assignment {
         &self.roles
     }
 
+    /// Returns the circuit ID patterns that the assignment is limited to. If empty, the
+    /// assignment applies to the whole node.
+    pub fn circuits(&self) -> &[String] {
+        &self.circuits
+    }
+
+    /// Returns whether or not the assignment applies to requests that are not about a specific
+    /// circuit.
+    pub fn is_node_wide(&self) -> bool {
+        self.circuits.is_empty()
+    }
+
+    /// Returns whether or not the assignment applies to requests about the given circuit.
+    pub fn applies_to_circuit(&self, circuit_id: &str) -> bool {
+        self.is_node_wide()
+            || self
+                .circuits
+                .iter()
+                .any(|pattern| circuit_pattern_matches(pattern, circuit_id))
+    }
+
     /// Convert this assignment back into a builder, in order to update its values.
     pub fn into_update_builder(self) -> AssignmentUpdateBuilder {
-        let Assignment { identity, roles } = self;
-        AssignmentUpdateBuilder { identity, roles }
+        let Assignment {
+            identity,
+            roles,
+            circuits,
+        } = self;
+        AssignmentUpdateBuilder {
+            identity,
+            roles,
+            circuits,
+        }
     }
 
     /// Converts this assignment into it's constituent parts.  These parts are in the tuple:
-    /// `(identity, roles)`.
-    pub fn into_parts(self) -> (Identity, Vec<String>) {
-        (self.identity, self.roles)
+    /// `(identity, roles, circuits)`.
+    pub fn into_parts(self) -> (Identity, Vec<String>, Vec<String>) {
+        (self.identity, self.roles, self.circuits)
     }
 }
 
@@ -254,6 +297,7 @@ impl Assignment {
 pub struct AssignmentBuilder {
     identity: Option<Identity>,
     roles: Vec<String>,
+    circuits: Vec<String>,
 }
 
 impl AssignmentBuilder {
@@ -274,6 +318,13 @@ impl AssignmentBuilder {
         self
     }
 
+    /// Sets the circuit ID patterns that the assignment is limited to. By default, the
+    /// assignment applies to the whole node.
+    pub fn with_circuits(mut self, circuits: Vec<String>) -> Self {
+        self.circuits = circuits;
+        self
+    }
+
     /// Builds a new assignment.
     ///
     /// # Errors
@@ -281,6 +332,7 @@ impl AssignmentBuilder {
     /// Returns an [`InvalidStateError`] under the following conditions:
     /// * no identity was provided
     /// * no roles were provided
+    /// * an invalid circuit ID pattern was provided
     pub fn build(self) -> Result<Assignment, InvalidStateError> {
         if self.roles.is_empty() {
             return Err(InvalidStateError::with_message(
@@ -288,11 +340,14 @@ impl AssignmentBuilder {
             ));
         }
 
+        validate_circuit_patterns(&self.circuits)?;
+
         Ok(Assignment {
             identity: self.identity.ok_or_else(|| {
                 InvalidStateError::with_message("An assignment requires an identity field".into())
             })?,
             roles: self.roles,
+            circuits: self.circuits,
         })
     }
 }
@@ -303,6 +358,7 @@ impl AssignmentBuilder {
 pub struct AssignmentUpdateBuilder {
     identity: Identity,
     roles: Vec<String>,
+    circuits: Vec<String>,
 }
 
 impl AssignmentUpdateBuilder {
@@ -312,12 +368,20 @@ impl AssignmentUpdateBuilder {
         self
     }
 
+    /// Updates the circuit ID patterns that the assignment is limited to. An empty list makes the
+    /// assignment apply to the whole node.
+    pub fn with_circuits(mut self, circuits: Vec<String>) -> Self {
+        self.circuits = circuits;
+        self
+    }
+
     /// Builds the updated assignment.
     ///
     /// # Errors
     ///
     /// Returns an [`InvalidStateError`] under the following conditions:
     /// * no roles were provided
+    /// * an invalid circuit ID pattern was provided
     pub fn build(self) -> Result<Assignment, InvalidStateError> {
         if self.roles.is_empty() {
             return Err(InvalidStateError::with_message(
@@ -325,13 +389,41 @@ impl AssignmentUpdateBuilder {
             ));
         }
 
+        validate_circuit_patterns(&self.circuits)?;
+
         Ok(Assignment {
             identity: self.identity,
             roles: self.roles,
+            circuits: self.circuits,
         })
     }
 }
 
+/// Checks that each circuit ID pattern is non-empty and only contains the characters allowed in
+/// circuit IDs, plus the `*` wildcard.
+fn validate_circuit_patterns(circuits: &[String]) -> Result<(), InvalidStateError> {
+    for pattern in circuits {
+        if pattern.is_empty() {
+            return Err(InvalidStateError::with_message(
+                "An assignment's circuit patterns must not be empty".into(),
+            ));
+        }
+
+        if !pattern
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '*')
+        {
+            return Err(InvalidStateError::with_message(format!(
+                "Invalid circuit pattern \"{}\": patterns may only contain ASCII letters, \
+                 numbers, '-', '_', and '*'",
+                pattern
+            )));
+        }
+    }
+
+    Ok(())
+}
+
 /// Defines methods for CRUD operations on Role and assignment data.
 pub trait RoleBasedAuthorizationStore: Send + Sync {
     /// Returns the role for the given ID, if one exists.
@@ -35,15 +35,19 @@ pub fn make_permissions_resource(permissions: Vec<Permission>) -> Resource {
         // Deduplicate and convert to serializable response structs
         .fold(vec![], |mut perms: Vec<PermissionResponse>, perm| {
             // Only interested in assignable permissions
-            if let Permission::Check {
-                permission_id,
-                permission_display_name,
-                permission_description,
-            } = perm
-            {
-                if !perms
+            match perm {
+                Permission::Check {
+                    permission_id,
+                    permission_display_name,
+                    permission_description,
+                }
+                | Permission::CheckForCircuits {
+                    permission_id,
+                    permission_display_name,
+                    permission_description,
+                } if !perms
                     .iter()
-                    .any(|existing_perm| permission_id == existing_perm.permission_id)
+                    .any(|existing_perm| permission_id == existing_perm.permission_id) =>
                 {
                     perms.push(PermissionResponse {
                         permission_id,
@@ -51,6 +55,7 @@ pub fn make_permissions_resource(permissions: Vec<Permission>) -> Resource {
                         permission_description,
                     });
                 }
+                _ => {}
             }
             perms
         });
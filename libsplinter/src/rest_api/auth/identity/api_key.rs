@@ -0,0 +1,200 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An identity provider for machine clients that authenticate with API keys
+
+use crate::api_keys::{store::ApiKeyStore, ApiKeyToken};
+use crate::error::InternalError;
+use crate::rest_api::auth::{AuthorizationHeader, BearerToken};
+
+use super::{Identity, IdentityProvider};
+
+/// An identity provider for clients that present an API key, which returns the key's ID
+///
+/// The key is looked up in the [ApiKeyStore] for every request, so a key that has been removed
+/// from the store will no longer resolve to an identity. Expired keys do not resolve to an
+/// identity, but are kept in the store so administrators can see that they have expired.
+///
+/// This provider only accepts `AuthorizationHeader::Bearer(BearerToken::ApiKey(token))`
+/// authorizations, and the inner token must be in the format `<key ID>.<secret>`.
+#[derive(Clone)]
+pub struct ApiKeyIdentityProvider {
+    api_key_store: Box<dyn ApiKeyStore>,
+}
+
+impl ApiKeyIdentityProvider {
+    /// Creates a new API key identity provider that uses the given store
+    pub fn new(api_key_store: Box<dyn ApiKeyStore>) -> Self {
+        Self { api_key_store }
+    }
+}
+
+impl IdentityProvider for ApiKeyIdentityProvider {
+    fn get_identity(
+        &self,
+        authorization: &AuthorizationHeader,
+    ) -> Result<Option<Identity>, InternalError> {
+        let token = match authorization {
+            AuthorizationHeader::Bearer(BearerToken::ApiKey(token)) => {
+                match token.parse::<ApiKeyToken>() {
+                    Ok(token) => token,
+                    Err(_) => return Ok(None),
+                }
+            }
+            _ => return Ok(None),
+        };
+
+        let api_key = match self
+            .api_key_store
+            .get_api_key(token.id())
+            .map_err(|err| InternalError::from_source(Box::new(err)))?
+        {
+            Some(api_key) if !api_key.is_expired() => api_key,
+            _ => return Ok(None),
+        };
+
+        if token.matches(&api_key)? {
+            Ok(Some(Identity::ApiKey(api_key.id().to_string())))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn IdentityProvider> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    use std::time::{Duration, SystemTime};
+
+    use diesel::{
+        r2d2::{ConnectionManager, Pool},
+        sqlite::SqliteConnection,
+    };
+
+    use crate::api_keys::store::{ApiKeyBuilder, DieselApiKeyStore};
+    use crate::migrations::run_sqlite_migrations;
+    use crate::store::ForeignKeyCustomizer;
+
+    /// Verify that the API key identity provider returns the ID of a valid API key, and rejects
+    /// tokens with the wrong secret and other types of tokens.
+    ///
+    /// 1. Add an API key to a store
+    /// 2. Verify that the key's token resolves to the key's ID
+    /// 3. Verify that a token with the key's ID and a different secret does not resolve
+    /// 4. Verify that the key's token does not resolve when presented as another type of token
+    /// 5. Remove the key and verify that its token no longer resolves
+    #[test]
+    fn get_identity() {
+        let api_key_store = create_api_key_store();
+        let token = add_api_key(&*api_key_store, Duration::from_secs(3600));
+
+        let provider = ApiKeyIdentityProvider::new(api_key_store.clone());
+
+        assert_eq!(
+            provider
+                .get_identity(&AuthorizationHeader::Bearer(BearerToken::ApiKey(
+                    token.to_string()
+                )))
+                .expect("Failed to get identity"),
+            Some(Identity::ApiKey(token.id().to_string()))
+        );
+
+        let other = ApiKeyToken::generate().expect("Failed to generate token");
+        let forged = other.to_string().replacen(other.id(), token.id(), 1);
+        assert_eq!(
+            provider
+                .get_identity(&AuthorizationHeader::Bearer(BearerToken::ApiKey(forged)))
+                .expect("Failed to get identity"),
+            None
+        );
+
+        assert_eq!(
+            provider
+                .get_identity(&AuthorizationHeader::Bearer(BearerToken::Custom(
+                    token.to_string()
+                )))
+                .expect("Failed to get identity"),
+            None
+        );
+
+        api_key_store
+            .remove_api_key(token.id())
+            .expect("Failed to remove API key");
+        assert_eq!(
+            provider
+                .get_identity(&AuthorizationHeader::Bearer(BearerToken::ApiKey(
+                    token.to_string()
+                )))
+                .expect("Failed to get identity"),
+            None
+        );
+    }
+
+    /// Verify that the API key identity provider does not return an identity for an expired key.
+    #[test]
+    fn expired_api_key() {
+        let api_key_store = create_api_key_store();
+        let token = add_api_key(&*api_key_store, Duration::from_secs(0));
+
+        let provider = ApiKeyIdentityProvider::new(api_key_store);
+
+        assert_eq!(
+            provider
+                .get_identity(&AuthorizationHeader::Bearer(BearerToken::ApiKey(
+                    token.to_string()
+                )))
+                .expect("Failed to get identity"),
+            None
+        );
+    }
+
+    /// Adds an API key that expires after the given duration to the store, and returns its token
+    fn add_api_key(api_key_store: &dyn ApiKeyStore, lifetime: Duration) -> ApiKeyToken {
+        let token = ApiKeyToken::generate().expect("Failed to generate token");
+        let created_at = SystemTime::now() - Duration::from_secs(60);
+        api_key_store
+            .add_api_key(
+                ApiKeyBuilder::new()
+                    .with_id(token.id().into())
+                    .with_display_name("Test key".into())
+                    .with_secret_hash(token.secret_hash().expect("Failed to hash secret"))
+                    .with_scopes(vec!["circuit.read".into()])
+                    .with_created_at(created_at)
+                    .with_expires_at(created_at + Duration::from_secs(60) + lifetime)
+                    .build()
+                    .expect("Failed to build API key"),
+            )
+            .expect("Failed to add API key");
+        token
+    }
+
+    fn create_api_key_store() -> Box<dyn ApiKeyStore> {
+        let connection_manager = ConnectionManager::<SqliteConnection>::new(":memory:");
+        let pool = Pool::builder()
+            .max_size(1)
+            .connection_customizer(Box::new(ForeignKeyCustomizer))
+            .build(connection_manager)
+            .expect("Failed to build connection pool");
+
+        run_sqlite_migrations(&*pool.get().expect("Failed to get connection for migrations"))
+            .expect("Failed to run migrations");
+
+        Box::new(DieselApiKeyStore::new(pool))
+    }
+}
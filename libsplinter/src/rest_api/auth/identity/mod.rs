@@ -14,12 +14,18 @@
 
 //! Tools for identifying clients and users
 
+#[cfg(feature = "api-keys")]
+pub mod api_key;
 #[cfg(feature = "biome-credentials")]
 pub mod biome;
 #[cfg(feature = "cylinder-jwt")]
 pub mod cylinder;
 #[cfg(feature = "oauth")]
 pub mod oauth;
+#[cfg(feature = "saml")]
+pub mod saml;
+
+use std::fmt;
 
 use crate::error::InternalError;
 
@@ -28,6 +34,9 @@ use super::AuthorizationHeader;
 /// A REST API client's identity as determined by an [IdentityProvider]
 #[derive(Debug, PartialEq)]
 pub enum Identity {
+    /// An API key ID
+    #[cfg(feature = "api-keys")]
+    ApiKey(String),
     /// A custom identity
     Custom(String),
     /// A public key
@@ -36,6 +45,19 @@ pub enum Identity {
     User(String),
 }
 
+/// Displays the identity prefixed by its type, e.g. "key:<public key>" or "user:<user ID>"
+impl fmt::Display for Identity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            #[cfg(feature = "api-keys")]
+            Identity::ApiKey(id) => write!(f, "api_key:{}", id),
+            Identity::Custom(custom) => write!(f, "custom:{}", custom),
+            Identity::Key(key) => write!(f, "key:{}", key),
+            Identity::User(user_id) => write!(f, "user:{}", user_id),
+        }
+    }
+}
+
 /// A service that fetches identities from a backing provider
 pub trait IdentityProvider: Send + Sync {
     /// Attempts to get the identity that corresponds to the given authorization header. This method
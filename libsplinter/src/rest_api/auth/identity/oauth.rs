@@ -94,6 +94,16 @@ impl IdentityProvider for OAuthUserIdentityProvider {
             None => return Ok(None),
         };
 
+        // SAML sessions share the session store, but are handled by the SAML identity provider
+        #[cfg(feature = "saml")]
+        if session
+            .user()
+            .subject()
+            .starts_with(crate::saml::SAML_SUBJECT_PREFIX)
+        {
+            return Ok(None);
+        }
+
         let user_id = session.user().user_id().to_string();
 
         let time_since_authenticated = session
@@ -0,0 +1,199 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An identity provider for users authenticated by a SAML identity provider
+
+use std::time::Duration;
+
+use crate::biome::OAuthUserSessionStore;
+use crate::error::InternalError;
+use crate::rest_api::auth::{AuthorizationHeader, BearerToken};
+use crate::saml::SAML_SUBJECT_PREFIX;
+
+use super::{Identity, IdentityProvider};
+
+/// An identity provider for users that logged in with a SAML identity provider, which returns a
+/// user's Biome ID
+///
+/// SAML sessions are created by the SAML REST API endpoints when the assertion consumer service
+/// accepts a signed assertion for a user. Sessions are stored in the same [OAuthUserSessionStore]
+/// as OAuth sessions, so SAML users are correlated with Biome user IDs in the same way as OAuth
+/// users. SAML sessions are distinguished from OAuth sessions by their subject identifiers, which
+/// are prefixed with `saml:`.
+///
+/// Unlike OAuth, SAML provides no means of re-authenticating a user without their interaction, so
+/// a session is only valid for a fixed duration after the user logged in. Expired sessions are
+/// removed from the store and the user will need to start a new session by logging in.
+///
+/// This provider only accepts `AuthorizationHeader::Bearer(BearerToken::Saml(token))`
+/// authorizations, and the inner token must be a valid Splinter access token for a SAML user.
+#[derive(Clone)]
+pub struct SamlUserIdentityProvider {
+    session_store: Box<dyn OAuthUserSessionStore>,
+    session_duration: Duration,
+}
+
+impl SamlUserIdentityProvider {
+    /// Creates a new SAML user identity provider
+    ///
+    /// # Arguments
+    ///
+    /// * `session_store` - The store that tracks users' sessions
+    /// * `session_duration` - The amount of time after a user logs in for which their session is
+    ///   valid
+    pub fn new(session_store: Box<dyn OAuthUserSessionStore>, session_duration: Duration) -> Self {
+        Self {
+            session_store,
+            session_duration,
+        }
+    }
+}
+
+impl IdentityProvider for SamlUserIdentityProvider {
+    fn get_identity(
+        &self,
+        authorization: &AuthorizationHeader,
+    ) -> Result<Option<Identity>, InternalError> {
+        let token = match authorization {
+            AuthorizationHeader::Bearer(BearerToken::Saml(token)) => token,
+            _ => return Ok(None),
+        };
+
+        let session = match self
+            .session_store
+            .get_session(token)
+            .map_err(|err| InternalError::from_source(err.into()))?
+        {
+            Some(session) if session.user().subject().starts_with(SAML_SUBJECT_PREFIX) => session,
+            _ => return Ok(None),
+        };
+
+        let time_since_authenticated = session
+            .last_authenticated()
+            .elapsed()
+            .map_err(|err| InternalError::from_source(err.into()))?;
+        if time_since_authenticated >= self.session_duration {
+            self.session_store
+                .remove_session(token)
+                .map_err(|err| InternalError::from_source(err.into()))?;
+            return Ok(None);
+        }
+
+        Ok(Some(Identity::User(session.user().user_id().to_string())))
+    }
+
+    fn clone_box(&self) -> Box<dyn IdentityProvider> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::biome::oauth::store::InsertableOAuthUserSessionBuilder;
+    use crate::biome::MemoryOAuthUserSessionStore;
+
+    /// Verify that the SAML identity provider returns the Biome user ID of a SAML session, and
+    /// ignores OAuth sessions and other types of tokens.
+    ///
+    /// 1. Add a SAML session and an OAuth session to a session store
+    /// 2. Verify that the SAML session's token resolves to its user's Biome ID
+    /// 3. Verify that the OAuth session's token does not resolve to an identity, even when it is
+    ///    presented as a SAML token
+    /// 4. Verify that the SAML session's token does not resolve when presented as another type of
+    ///    token
+    #[test]
+    fn get_identity() {
+        let session_store = MemoryOAuthUserSessionStore::new();
+        add_session(&session_store, "saml_token", "saml:user@example.com");
+        add_session(&session_store, "oauth_token", "oauth_subject");
+
+        let provider =
+            SamlUserIdentityProvider::new(session_store.clone_box(), Duration::from_secs(3600));
+
+        let user_id = session_store
+            .get_user("saml:user@example.com")
+            .expect("Failed to get user")
+            .expect("User not found")
+            .user_id()
+            .to_string();
+        assert_eq!(
+            provider
+                .get_identity(&AuthorizationHeader::Bearer(BearerToken::Saml(
+                    "saml_token".into()
+                )))
+                .expect("Failed to get identity"),
+            Some(Identity::User(user_id))
+        );
+
+        assert_eq!(
+            provider
+                .get_identity(&AuthorizationHeader::Bearer(BearerToken::Saml(
+                    "oauth_token".into()
+                )))
+                .expect("Failed to get identity"),
+            None
+        );
+        assert_eq!(
+            provider
+                .get_identity(&AuthorizationHeader::Bearer(BearerToken::OAuth2(
+                    "saml_token".into()
+                )))
+                .expect("Failed to get identity"),
+            None
+        );
+    }
+
+    /// Verify that the SAML identity provider removes sessions that have expired.
+    ///
+    /// 1. Add a SAML session to a session store
+    /// 2. Create a SAML identity provider with a session duration of zero
+    /// 3. Verify that the session's token does not resolve to an identity
+    /// 4. Verify that the session has been removed from the store
+    #[test]
+    fn expired_session() {
+        let session_store = MemoryOAuthUserSessionStore::new();
+        add_session(&session_store, "saml_token", "saml:user@example.com");
+
+        let provider =
+            SamlUserIdentityProvider::new(session_store.clone_box(), Duration::from_secs(0));
+
+        assert_eq!(
+            provider
+                .get_identity(&AuthorizationHeader::Bearer(BearerToken::Saml(
+                    "saml_token".into()
+                )))
+                .expect("Failed to get identity"),
+            None
+        );
+        assert!(session_store
+            .get_session("saml_token")
+            .expect("Failed to get session")
+            .is_none());
+    }
+
+    fn add_session(session_store: &MemoryOAuthUserSessionStore, token: &str, subject: &str) {
+        session_store
+            .add_session(
+                InsertableOAuthUserSessionBuilder::new()
+                    .with_splinter_access_token(token.into())
+                    .with_subject(subject.into())
+                    .with_oauth_access_token("".into())
+                    .build()
+                    .expect("Failed to build session"),
+            )
+            .expect("Failed to add session");
+    }
+}
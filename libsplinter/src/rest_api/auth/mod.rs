@@ -27,14 +27,21 @@ use crate::error::InvalidArgumentError;
 #[cfg(feature = "authorization")]
 use super::Method;
 
+#[cfg(feature = "authorization-audit")]
+use authorization::audit::{AuditOutcome, AuditRecordBuilder, AuditSink, AuditSinkError};
 #[cfg(feature = "authorization")]
-use authorization::{AuthorizationHandler, AuthorizationHandlerResult, Permission, PermissionMap};
+use authorization::{
+    AuthorizationHandler, AuthorizationHandlerResult, CircuitScope, Permission, PermissionMap,
+};
 use identity::{Identity, IdentityProvider};
 
 /// The possible outcomes of attempting to authorize a client
 enum AuthorizationResult {
     /// The client was authorized to the given identity based on the authorization header
     Authorized(Identity),
+    /// The client was authorized to the given identity, but only for the given circuits
+    #[cfg(feature = "authorization")]
+    AuthorizedForCircuits(Identity, CircuitScope),
     /// The requested endpoint does not require authorization
     #[cfg(any(
         feature = "authorization",
@@ -60,6 +67,8 @@ enum AuthorizationResult {
 /// * `identity_providers` - The identity providers that will be used to check the client's identity
 /// * `authorization_handlers` - The authorization handlers that will be used to check the client's
 ///   permissions
+/// * `audit_sink` - If provided, the decision is recorded in this sink for any endpoint that
+///   requires authorization
 fn authorize(
     #[cfg(feature = "authorization")] method: &Method,
     #[cfg(any(
@@ -78,6 +87,7 @@ fn authorize(
     #[cfg(feature = "authorization")] permission_map: &PermissionMap,
     identity_providers: &[Box<dyn IdentityProvider>],
     #[cfg(feature = "authorization")] authorization_handlers: &[Box<dyn AuthorizationHandler>],
+    #[cfg(feature = "authorization-audit")] audit_sink: Option<&dyn AuditSink>,
 ) -> AuthorizationResult {
     #[cfg(feature = "authorization")]
     {
@@ -89,28 +99,106 @@ fn authorize(
 
         match *permission {
             Permission::AllowUnauthenticated => AuthorizationResult::NoAuthorizationNecessary,
-            Permission::AllowAuthenticated => match get_identity(auth_header, identity_providers) {
-                Some(identity) => AuthorizationResult::Authorized(identity),
-                None => AuthorizationResult::Unauthorized,
-            },
-            Permission::Check { permission_id, .. } => {
-                match get_identity(auth_header, identity_providers) {
-                    Some(identity) => {
-                        for handler in authorization_handlers {
-                            match handler.has_permission(&identity, permission_id) {
-                                Ok(AuthorizationHandlerResult::Allow) => {
-                                    return AuthorizationResult::Authorized(identity)
-                                }
-                                Ok(AuthorizationHandlerResult::Deny) => {
-                                    return AuthorizationResult::Unauthorized
-                                }
-                                Ok(AuthorizationHandlerResult::Continue) => {}
-                                Err(err) => error!("{}", err),
+            Permission::AllowAuthenticated => {
+                let identity = get_identity(auth_header, identity_providers);
+                #[cfg(feature = "authorization-audit")]
+                record_audit(
+                    audit_sink,
+                    method,
+                    endpoint,
+                    identity.as_ref(),
+                    None,
+                    None,
+                    identity.is_some(),
+                );
+                match identity {
+                    Some(identity) => AuthorizationResult::Authorized(identity),
+                    None => AuthorizationResult::Unauthorized,
+                }
+            }
+            Permission::Check { permission_id, .. }
+            | Permission::CheckForCircuits { permission_id, .. } => {
+                // Requests about a specific circuit are checked against that circuit, so that
+                // handlers may grant permissions that are limited to certain circuits
+                let circuit_id = permission_map.get_circuit_id(&method, endpoint);
+                let identity = match get_identity(auth_header, identity_providers) {
+                    Some(identity) => identity,
+                    None => {
+                        #[cfg(feature = "authorization-audit")]
+                        record_audit(
+                            audit_sink,
+                            method,
+                            endpoint,
+                            None,
+                            Some(permission_id),
+                            None,
+                            false,
+                        );
+                        return AuthorizationResult::Unauthorized;
+                    }
+                };
+
+                // The first handler to allow or deny the request decides; if none do, the request
+                // is denied by default
+                let mut decision = None;
+                for handler in authorization_handlers {
+                    let result = match circuit_id {
+                        Some(circuit_id) => {
+                            handler.has_permission_for_circuit(&identity, permission_id, circuit_id)
+                        }
+                        None => handler.has_permission(&identity, permission_id),
+                    };
+                    match result {
+                        Ok(AuthorizationHandlerResult::Allow) => {
+                            decision = Some((handler, true));
+                            break;
+                        }
+                        Ok(AuthorizationHandlerResult::Deny) => {
+                            decision = Some((handler, false));
+                            break;
+                        }
+                        Ok(AuthorizationHandlerResult::Continue) => {}
+                        Err(err) => error!("{}", err),
+                    }
+                }
+
+                // If no handler decided, a client that only has the permission for some circuits
+                // may be allowed, limited to those circuits, when the endpoint supports it
+                let mut circuit_scope = None;
+                if decision.is_none()
+                    && circuit_id.is_none()
+                    && matches!(permission, Permission::CheckForCircuits { .. })
+                {
+                    for handler in authorization_handlers {
+                        match handler.circuits_with_permission(&identity, permission_id) {
+                            Ok(Some(circuits)) => {
+                                decision = Some((handler, true));
+                                circuit_scope = Some(CircuitScope::new(circuits));
+                                break;
                             }
+                            Ok(None) => {}
+                            Err(err) => error!("{}", err),
                         }
-                        // No handler allowed the request, so deny by default
-                        AuthorizationResult::Unauthorized
                     }
+                }
+                let allowed = matches!(decision, Some((_, true)));
+
+                #[cfg(feature = "authorization-audit")]
+                record_audit(
+                    audit_sink,
+                    method,
+                    endpoint,
+                    Some(&identity),
+                    Some(permission_id),
+                    decision.map(|(handler, _)| handler.name()),
+                    allowed,
+                );
+
+                match circuit_scope {
+                    Some(circuit_scope) => {
+                        AuthorizationResult::AuthorizedForCircuits(identity, circuit_scope)
+                    }
+                    None if allowed => AuthorizationResult::Authorized(identity),
                     None => AuthorizationResult::Unauthorized,
                 }
             }
@@ -118,7 +206,7 @@ fn authorize(
     }
     #[cfg(not(feature = "authorization"))]
     {
-        #[cfg(any(feature = "biome-credentials", feature = "oauth"))]
+        #[cfg(any(feature = "biome-credentials", feature = "oauth", feature = "saml"))]
         {
             // Authorization isn't necessary when using one of the authorization endpoints
             let mut is_auth_endpoint = false;
@@ -133,6 +221,11 @@ fn authorize(
             if endpoint == "/oauth/login" || endpoint == "/oauth/callback" {
                 is_auth_endpoint = true;
             }
+            #[cfg(feature = "saml")]
+            if endpoint == "/saml/metadata" || endpoint == "/saml/login" || endpoint == "/saml/acs"
+            {
+                is_auth_endpoint = true;
+            }
             if is_auth_endpoint {
                 return AuthorizationResult::NoAuthorizationNecessary;
             }
@@ -145,6 +238,53 @@ fn authorize(
     }
 }
 
+/// Records an authorization decision in the audit sink, if there is one. Failing to record the
+/// decision is logged, but does not affect the decision.
+#[cfg(feature = "authorization-audit")]
+fn record_audit(
+    audit_sink: Option<&dyn AuditSink>,
+    method: &Method,
+    endpoint: &str,
+    identity: Option<&Identity>,
+    permission_id: Option<&str>,
+    handler: Option<&str>,
+    allowed: bool,
+) {
+    let audit_sink = match audit_sink {
+        Some(audit_sink) => audit_sink,
+        None => return,
+    };
+
+    let mut builder = AuditRecordBuilder::new()
+        .with_method(method.to_string())
+        .with_path(endpoint.to_string())
+        .with_outcome(if allowed {
+            AuditOutcome::Allowed
+        } else {
+            AuditOutcome::Denied
+        });
+    if let Some(identity) = identity {
+        builder = builder.with_identity(identity.to_string());
+    }
+    if let Some(permission_id) = permission_id {
+        builder = builder.with_permission_id(permission_id.to_string());
+    }
+    if let Some(handler) = handler {
+        builder = builder.with_handler(handler.to_string());
+    }
+
+    let result = builder
+        .build()
+        .map_err(AuditSinkError::from)
+        .and_then(|record| audit_sink.record(record));
+    if let Err(err) = result {
+        error!(
+            "Unable to record authorization decision in audit log: {}",
+            err
+        );
+    }
+}
+
 fn get_identity(
     auth_header: Option<&str>,
     identity_providers: &[Box<dyn IdentityProvider>],
@@ -188,6 +328,9 @@ impl FromStr for AuthorizationHeader {
 /// A bearer token of a specific type
 #[derive(PartialEq)]
 pub enum BearerToken {
+    #[cfg(feature = "api-keys")]
+    /// Contains an API key token
+    ApiKey(String),
     #[cfg(feature = "biome-credentials")]
     /// Contains a Biome JWT
     Biome(String),
@@ -200,6 +343,9 @@ pub enum BearerToken {
     #[cfg(feature = "oauth")]
     /// Contains an OAuth2 token
     OAuth2(String),
+    #[cfg(feature = "saml")]
+    /// Contains a Splinter access token for a SAML user's session
+    Saml(String),
 }
 
 /// Parses a bearer token string. This implementation will attempt to parse the token in the format
@@ -211,16 +357,20 @@ impl FromStr for BearerToken {
     fn from_str(str: &str) -> Result<Self, Self::Err> {
         let mut parts = str.splitn(2, ':');
         match (parts.next(), parts.next()) {
-            // Allowing lint in case none of `biome-credentials`, `cylinder-jwt`, or `oauth` are
-            // used
+            // Allowing lint in case none of `api-keys`, `biome-credentials`, `cylinder-jwt`,
+            // `oauth`, or `saml` are used
             #[allow(unused_variables, clippy::match_single_binding)]
             (Some(token_type), Some(token)) => match token_type {
+                #[cfg(feature = "api-keys")]
+                "ApiKey" => Ok(BearerToken::ApiKey(token.to_string())),
                 #[cfg(feature = "biome-credentials")]
                 "Biome" => Ok(BearerToken::Biome(token.to_string())),
                 #[cfg(feature = "cylinder-jwt")]
                 "Cylinder" => Ok(BearerToken::Cylinder(token.to_string())),
                 #[cfg(feature = "oauth")]
                 "OAuth2" => Ok(BearerToken::OAuth2(token.to_string())),
+                #[cfg(feature = "saml")]
+                "SAML" => Ok(BearerToken::Saml(token.to_string())),
                 _ => Ok(BearerToken::Custom(str.to_string())),
             },
             (Some(_), None) => Ok(BearerToken::Custom(str.to_string())),
@@ -235,6 +385,9 @@ mod tests {
 
     use crate::error::InternalError;
 
+    #[cfg(feature = "authorization-audit")]
+    use authorization::audit::{AuditRecord, AuditRecordPredicate};
+
     /// Verfifies that the `AuthorizationHeader` enum is correctly parsed from strings
     #[test]
     fn parse_authorization_header() {
@@ -257,6 +410,12 @@ mod tests {
     /// Verfifies that the `BearerToken` enum is correctly parsed from strings
     #[test]
     fn parse_bearer_token() {
+        #[cfg(feature = "api-keys")]
+        assert!(matches!(
+            "ApiKey:test".parse(),
+            Ok(BearerToken::ApiKey(token)) if token == "test"
+        ));
+
         #[cfg(feature = "biome-credentials")]
         assert!(matches!(
             "Biome:test".parse(),
@@ -275,6 +434,12 @@ mod tests {
             Ok(BearerToken::OAuth2(token)) if token == "test"
         ));
 
+        #[cfg(feature = "saml")]
+        assert!(matches!(
+            "SAML:test".parse(),
+            Ok(BearerToken::Saml(token)) if token == "test"
+        ));
+
         assert!(matches!(
             "Unknown:test".parse(),
             Ok(BearerToken::Custom(token)) if token == "Unknown:test"
@@ -313,6 +478,8 @@ mod tests {
                 &[],
                 #[cfg(feature = "authorization")]
                 &[Box::new(AlwaysAllowAuthorizationHandler)],
+                #[cfg(feature = "authorization-audit")]
+                None,
             ),
             AuthorizationResult::Unauthorized
         ));
@@ -344,6 +511,8 @@ mod tests {
                 &[Box::new(AlwaysRejectIdentityProvider)],
                 #[cfg(feature = "authorization")]
                 &[Box::new(AlwaysAllowAuthorizationHandler)],
+                #[cfg(feature = "authorization-audit")]
+                None,
             ),
             AuthorizationResult::Unauthorized
         ));
@@ -376,6 +545,8 @@ mod tests {
                 &[Box::new(AlwaysAcceptIdentityProvider)],
                 #[cfg(feature = "authorization")]
                 &[Box::new(AlwaysAllowAuthorizationHandler)],
+                #[cfg(feature = "authorization-audit")]
+                None,
             ),
             AuthorizationResult::Unauthorized
         ));
@@ -395,6 +566,8 @@ mod tests {
                 &Default::default(),
                 &[Box::new(AlwaysAcceptIdentityProvider)],
                 &[Box::new(AlwaysAllowAuthorizationHandler)],
+                #[cfg(feature = "authorization-audit")]
+                None,
             ),
             AuthorizationResult::UnknownEndpoint
         ));
@@ -424,6 +597,8 @@ mod tests {
                     &permission_map,
                     &[Box::new(AlwaysRejectIdentityProvider)],
                     &[Box::new(AlwaysAllowAuthorizationHandler)],
+                    #[cfg(feature = "authorization-audit")]
+                    None,
                 ),
                 AuthorizationResult::NoAuthorizationNecessary
             ));
@@ -437,6 +612,8 @@ mod tests {
                     &permission_map,
                     &[Box::new(AlwaysRejectIdentityProvider)],
                     &[Box::new(AlwaysAllowAuthorizationHandler)],
+                    #[cfg(feature = "authorization-audit")]
+                    None,
                 ),
                 AuthorizationResult::NoAuthorizationNecessary
             ));
@@ -576,6 +753,8 @@ mod tests {
                 &[Box::new(AlwaysAcceptIdentityProvider)],
                 #[cfg(feature = "authorization")]
                 &[Box::new(AlwaysAllowAuthorizationHandler)],
+                #[cfg(feature = "authorization-audit")]
+                None,
             ),
             AuthorizationResult::Authorized(identity) if identity == expected_identity
         ));
@@ -619,6 +798,8 @@ mod tests {
                 ],
                 #[cfg(feature = "authorization")]
                 &[Box::new(AlwaysAllowAuthorizationHandler)],
+                #[cfg(feature = "authorization-audit")]
+                None,
             ),
             AuthorizationResult::Authorized(identity) if identity == expected_identity
         ));
@@ -661,6 +842,8 @@ mod tests {
                 ],
                 #[cfg(feature = "authorization")]
                 &[Box::new(AlwaysAllowAuthorizationHandler)],
+                #[cfg(feature = "authorization-audit")]
+                None,
             ),
             AuthorizationResult::Authorized(identity) if identity == expected_identity
         ));
@@ -693,6 +876,8 @@ mod tests {
                 &permission_map,
                 &[Box::new(AlwaysAcceptIdentityProvider)],
                 &[],
+                #[cfg(feature = "authorization-audit")]
+                None,
             ),
             AuthorizationResult::Unauthorized
         ));
@@ -726,6 +911,8 @@ mod tests {
                 &permission_map,
                 &[Box::new(AlwaysAcceptIdentityProvider)],
                 &[Box::new(AlwaysContinueAuthorizationHandler)],
+                #[cfg(feature = "authorization-audit")]
+                None,
             ),
             AuthorizationResult::Unauthorized
         ));
@@ -761,6 +948,8 @@ mod tests {
                     Box::new(AlwaysDenyAuthorizationHandler),
                     Box::new(AlwaysAllowAuthorizationHandler),
                 ],
+                #[cfg(feature = "authorization-audit")]
+                None,
             ),
             AuthorizationResult::Unauthorized
         ));
@@ -803,6 +992,8 @@ mod tests {
                     Box::new(AlwaysAllowAuthorizationHandler),
                     Box::new(AlwaysDenyAuthorizationHandler),
                 ],
+                #[cfg(feature = "authorization-audit")]
+                None,
             ),
             AuthorizationResult::Authorized(identity) if identity == expected_identity
         ));
@@ -845,6 +1036,8 @@ mod tests {
                     Box::new(AlwaysContinueAuthorizationHandler),
                     Box::new(AlwaysAllowAuthorizationHandler),
                 ],
+                #[cfg(feature = "authorization-audit")]
+                None,
             ),
             AuthorizationResult::Authorized(identity) if identity == expected_identity
         ));
@@ -887,11 +1080,171 @@ mod tests {
                     Box::new(AlwaysErrAuthorizationHandler),
                     Box::new(AlwaysAllowAuthorizationHandler),
                 ],
+                #[cfg(feature = "authorization-audit")]
+                None,
             ),
             AuthorizationResult::Authorized(identity) if identity == expected_identity
         ));
     }
 
+    /// Verifies that the `authorize` function returns
+    /// `AuthorizationResult::AuthorizedForCircuits(identity, scope)` when no authorization handler
+    /// allows the request, but one returns circuits with the permission and the endpoint's
+    /// permission is `CheckForCircuits`. Endpoints with a `Check` permission, and requests that
+    /// another handler denies, remain unauthorized.
+    #[cfg(feature = "authorization")]
+    #[test]
+    fn authorize_check_for_circuits() {
+        let expected_auth = "auth".parse().unwrap();
+        let expected_identity = AlwaysAcceptIdentityProvider
+            .get_identity(&expected_auth)
+            .unwrap()
+            .unwrap();
+
+        let permission_map = {
+            let mut map = PermissionMap::new();
+            map.add_permission(
+                Method::Get,
+                "/test/list",
+                Permission::CheckForCircuits {
+                    permission_id: "permission",
+                    permission_display_name: "",
+                    permission_description: "",
+                },
+            );
+            map.add_permission(
+                Method::Get,
+                "/test/endpoint",
+                Permission::Check {
+                    permission_id: "permission",
+                    permission_display_name: "",
+                    permission_description: "",
+                },
+            );
+            map
+        };
+
+        assert!(matches!(
+            authorize(
+                &Method::Get,
+                "/test/list",
+                Some("auth"),
+                &permission_map,
+                &[Box::new(AlwaysAcceptIdentityProvider)],
+                &[Box::new(CircuitScopedAuthorizationHandler)],
+                #[cfg(feature = "authorization-audit")]
+                None,
+            ),
+            AuthorizationResult::AuthorizedForCircuits(identity, scope)
+                if identity == expected_identity
+                    && scope.contains("abcde-01234")
+                    && !scope.contains("fghij-01234")
+        ));
+        assert!(matches!(
+            authorize(
+                &Method::Get,
+                "/test/endpoint",
+                Some("auth"),
+                &permission_map,
+                &[Box::new(AlwaysAcceptIdentityProvider)],
+                &[Box::new(CircuitScopedAuthorizationHandler)],
+                #[cfg(feature = "authorization-audit")]
+                None,
+            ),
+            AuthorizationResult::Unauthorized
+        ));
+        assert!(matches!(
+            authorize(
+                &Method::Get,
+                "/test/list",
+                Some("auth"),
+                &permission_map,
+                &[Box::new(AlwaysAcceptIdentityProvider)],
+                &[
+                    Box::new(AlwaysDenyAuthorizationHandler),
+                    Box::new(CircuitScopedAuthorizationHandler),
+                ],
+                #[cfg(feature = "authorization-audit")]
+                None,
+            ),
+            AuthorizationResult::Unauthorized
+        ));
+    }
+
+    /// Verifies that the `authorize` function records its decisions in the audit sink for
+    /// endpoints that require authorization, including the handler that made the decision, and
+    /// that it does not record requests to endpoints that allow unauthenticated access.
+    #[cfg(feature = "authorization-audit")]
+    #[test]
+    fn authorize_records_audit() {
+        let permission_map = {
+            let mut map = PermissionMap::new();
+            map.add_permission(
+                Method::Get,
+                "/test/endpoint",
+                Permission::Check {
+                    permission_id: "permission",
+                    permission_display_name: "",
+                    permission_description: "",
+                },
+            );
+            map.add_permission(Method::Get, "/test/open", Permission::AllowUnauthenticated);
+            map
+        };
+        let audit_sink = MemoryAuditSink::default();
+
+        assert!(matches!(
+            authorize(
+                &Method::Get,
+                "/test/endpoint",
+                Some("auth"),
+                &permission_map,
+                &[Box::new(AlwaysAcceptIdentityProvider)],
+                &[Box::new(AlwaysAllowAuthorizationHandler)],
+                Some(&audit_sink),
+            ),
+            AuthorizationResult::Authorized(_)
+        ));
+        assert!(matches!(
+            authorize(
+                &Method::Get,
+                "/test/endpoint",
+                Some("auth"),
+                &permission_map,
+                &[Box::new(AlwaysAcceptIdentityProvider)],
+                &[Box::new(AlwaysContinueAuthorizationHandler)],
+                Some(&audit_sink),
+            ),
+            AuthorizationResult::Unauthorized
+        ));
+        assert!(matches!(
+            authorize(
+                &Method::Get,
+                "/test/open",
+                None,
+                &permission_map,
+                &[Box::new(AlwaysAcceptIdentityProvider)],
+                &[Box::new(AlwaysAllowAuthorizationHandler)],
+                Some(&audit_sink),
+            ),
+            AuthorizationResult::NoAuthorizationNecessary
+        ));
+
+        let records = audit_sink.records.lock().expect("audit sink lock poisoned");
+        assert_eq!(records.len(), 2);
+
+        assert_eq!(records[0].identity(), Some("custom:identity"));
+        assert_eq!(records[0].method(), "GET");
+        assert_eq!(records[0].path(), "/test/endpoint");
+        assert_eq!(records[0].permission_id(), Some("permission"));
+        assert_eq!(records[0].handler(), Some("custom"));
+        assert_eq!(records[0].outcome(), AuditOutcome::Allowed);
+
+        assert_eq!(records[1].identity(), Some("custom:identity"));
+        assert_eq!(records[1].handler(), None);
+        assert_eq!(records[1].outcome(), AuditOutcome::Denied);
+    }
+
     /// An identity provider that always returns `Ok(Some(_))`
     #[derive(Clone)]
     struct AlwaysAcceptIdentityProvider;
@@ -1022,4 +1375,65 @@ mod tests {
             Box::new(self.clone())
         }
     }
+
+    /// An authorization handler that only grants permissions for circuits matching `abcde-*`
+    #[cfg(feature = "authorization")]
+    #[derive(Clone)]
+    struct CircuitScopedAuthorizationHandler;
+
+    #[cfg(feature = "authorization")]
+    impl AuthorizationHandler for CircuitScopedAuthorizationHandler {
+        fn has_permission(
+            &self,
+            _identity: &Identity,
+            _permission_id: &str,
+        ) -> Result<AuthorizationHandlerResult, InternalError> {
+            Ok(AuthorizationHandlerResult::Continue)
+        }
+
+        fn circuits_with_permission(
+            &self,
+            _identity: &Identity,
+            _permission_id: &str,
+        ) -> Result<Option<Vec<String>>, InternalError> {
+            Ok(Some(vec!["abcde-*".into()]))
+        }
+
+        fn clone_box(&self) -> Box<dyn AuthorizationHandler> {
+            Box::new(self.clone())
+        }
+    }
+
+    /// An audit sink that keeps its records in memory, in the order they were recorded
+    #[cfg(feature = "authorization-audit")]
+    #[derive(Clone, Default)]
+    struct MemoryAuditSink {
+        records: std::sync::Arc<std::sync::Mutex<Vec<AuditRecord>>>,
+    }
+
+    #[cfg(feature = "authorization-audit")]
+    impl AuditSink for MemoryAuditSink {
+        fn record(&self, record: AuditRecord) -> Result<(), AuditSinkError> {
+            self.records
+                .lock()
+                .map_err(|_| {
+                    AuditSinkError::InternalError(InternalError::with_message(
+                        "audit sink lock poisoned".into(),
+                    ))
+                })?
+                .push(record);
+            Ok(())
+        }
+
+        fn list_records(
+            &self,
+            _predicates: &[AuditRecordPredicate],
+        ) -> Result<Box<dyn ExactSizeIterator<Item = AuditRecord>>, AuditSinkError> {
+            unimplemented!()
+        }
+
+        fn clone_box(&self) -> Box<dyn AuditSink> {
+            Box::new(self.clone())
+        }
+    }
 }
@@ -97,6 +97,8 @@ pub use actix_web_1::{
     Request, RequestGuard, Resource, Response, ResponseError, RestApi, RestApiBuilder,
     RestApiShutdownHandle, RestResourceProvider,
 };
+#[cfg(all(feature = "https-bind", feature = "tls-cert-reload"))]
+pub use actix_web_1::RestApiTlsReloader;
 
 const QUERY_ENCODE_SET: &AsciiSet = &CONTROLS
     .add(b' ')
@@ -42,7 +42,7 @@ impl SecretManager for AutoSecretManager {
     }
 }
 
-fn generate_random_secret() -> String {
+pub(super) fn generate_random_secret() -> String {
     rand::thread_rng()
         .sample_iter(Alphanumeric)
         .take(SECRET_LENGTH)
@@ -0,0 +1,118 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+use crate::error::InternalError;
+
+use super::auto_secret_manager::generate_random_secret;
+use super::{SecretManager, SecretManagerError};
+
+/// A SecretManager that keeps its secret in a file, so the secret survives restarts
+///
+/// If the file does not exist, a random secret is generated and written to it the first time the
+/// secret is requested. The file is created readable by its owner only.
+///
+/// The secret is meant for encrypting stored data, so it cannot be updated: replacing it would
+/// leave everything encrypted with the old secret unreadable.
+pub struct FileSecretManager {
+    path: PathBuf,
+}
+
+impl FileSecretManager {
+    /// Creates a new FileSecretManager
+    ///
+    /// # Arguments
+    ///
+    /// * `path`: the path of the file the secret is stored in
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        FileSecretManager {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    fn create_secret(&self, secret: &str) -> std::io::Result<()> {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&self.path)?;
+        file.write_all(secret.as_bytes())
+    }
+}
+
+impl SecretManager for FileSecretManager {
+    fn secret(&self) -> Result<String, SecretManagerError> {
+        match fs::read_to_string(&self.path) {
+            Ok(secret) => Ok(secret.trim().to_string()),
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                let secret = generate_random_secret();
+                match self.create_secret(&secret) {
+                    Ok(()) => Ok(secret),
+                    // Another caller created the file first; use its secret
+                    Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+                        fs::read_to_string(&self.path)
+                            .map(|secret| secret.trim().to_string())
+                            .map_err(|err| SecretManagerError::SecretError(Box::new(err)))
+                    }
+                    Err(err) => Err(SecretManagerError::SecretError(Box::new(err))),
+                }
+            }
+            Err(err) => Err(SecretManagerError::SecretError(Box::new(err))),
+        }
+    }
+
+    fn update_secret(&mut self) -> Result<(), SecretManagerError> {
+        Err(SecretManagerError::UpdateSecretError(Box::new(
+            InternalError::with_message(format!(
+                "the secret in {} encrypts stored data and cannot be replaced",
+                self.path.display()
+            )),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempdir::TempDir;
+
+    /// Verify that the FileSecretManager generates a secret when its file does not exist, returns
+    /// the same secret afterwards, and refuses to update the secret.
+    #[test]
+    fn file_secret_manager() {
+        let temp_dir = TempDir::new("file_secret_manager").expect("Failed to create temp dir");
+        let path = temp_dir.path().join("secret");
+
+        let mut secret_manager = FileSecretManager::new(&path);
+        let secret = secret_manager.secret().expect("Failed to get secret");
+        assert!(!secret.is_empty());
+        assert_eq!(
+            FileSecretManager::new(&path)
+                .secret()
+                .expect("Failed to get secret"),
+            secret
+        );
+
+        assert!(secret_manager.update_secret().is_err());
+        assert_eq!(
+            secret_manager.secret().expect("Failed to get secret"),
+            secret
+        );
+    }
+}
@@ -16,9 +16,13 @@
 
 mod auto_secret_manager;
 mod error;
+#[cfg(unix)]
+mod file_secret_manager;
 
 pub use auto_secret_manager::AutoSecretManager;
 pub use error::SecretManagerError;
+#[cfg(unix)]
+pub use file_secret_manager::FileSecretManager;
 
 /// Defines a manager for fetching and/or generating a secret.
 pub trait SecretManager: Sync + Send {
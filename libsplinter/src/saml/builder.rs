@@ -0,0 +1,155 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A builder for [SamlServiceProvider](crate::saml::SamlServiceProvider) structs.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use openssl::x509::X509;
+
+use crate::error::{InvalidArgumentError, InvalidStateError};
+
+use super::error::SamlServiceProviderBuildError;
+use super::SamlServiceProvider;
+
+/// The default amount of time a user's session is valid for after they log in
+const DEFAULT_SESSION_DURATION: Duration = Duration::from_secs(8 * 3600); // 8 hours
+
+/// A builder for a new [`SamlServiceProvider`].
+#[derive(Default)]
+pub struct SamlServiceProviderBuilder {
+    sp_entity_id: Option<String>,
+    acs_url: Option<String>,
+    idp_entity_id: Option<String>,
+    idp_sso_url: Option<String>,
+    idp_certificate: Option<Vec<u8>>,
+    session_duration: Option<Duration>,
+}
+
+impl SamlServiceProviderBuilder {
+    /// Constructs a new builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds a `SamlServiceProvider`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SamlServiceProviderBuildError`] if a required value was not set, if either of
+    /// the URLs is invalid, or if the identity provider's certificate is not a valid PEM-encoded
+    /// X.509 certificate.
+    pub fn build(self) -> Result<SamlServiceProvider, SamlServiceProviderBuildError> {
+        let sp_entity_id = self.sp_entity_id.ok_or_else(|| {
+            InvalidStateError::with_message(
+                "A service provider entity ID is required to successfully build a \
+                 SamlServiceProvider"
+                    .into(),
+            )
+        })?;
+        let acs_url = self.acs_url.ok_or_else(|| {
+            InvalidStateError::with_message(
+                "An assertion consumer service URL is required to successfully build a \
+                 SamlServiceProvider"
+                    .into(),
+            )
+        })?;
+        let idp_entity_id = self.idp_entity_id.ok_or_else(|| {
+            InvalidStateError::with_message(
+                "An identity provider entity ID is required to successfully build a \
+                 SamlServiceProvider"
+                    .into(),
+            )
+        })?;
+        let idp_sso_url = self.idp_sso_url.ok_or_else(|| {
+            InvalidStateError::with_message(
+                "An identity provider SSO URL is required to successfully build a \
+                 SamlServiceProvider"
+                    .into(),
+            )
+        })?;
+        let idp_certificate = self.idp_certificate.ok_or_else(|| {
+            InvalidStateError::with_message(
+                "An identity provider certificate is required to successfully build a \
+                 SamlServiceProvider"
+                    .into(),
+            )
+        })?;
+
+        url::Url::parse(&acs_url).map_err(|err| {
+            InvalidArgumentError::new("acs_url".into(), format!("invalid URL: {}", err))
+        })?;
+        url::Url::parse(&idp_sso_url).map_err(|err| {
+            InvalidArgumentError::new("idp_sso_url".into(), format!("invalid URL: {}", err))
+        })?;
+        let idp_public_key = X509::from_pem(&idp_certificate)
+            .and_then(|certificate| certificate.public_key())
+            .map_err(|err| {
+                InvalidArgumentError::new(
+                    "idp_certificate".into(),
+                    format!("invalid PEM-encoded X.509 certificate: {}", err),
+                )
+            })?;
+
+        Ok(SamlServiceProvider {
+            sp_entity_id,
+            acs_url,
+            idp_entity_id,
+            idp_sso_url,
+            idp_public_key,
+            session_duration: self.session_duration.unwrap_or(DEFAULT_SESSION_DURATION),
+            pending_requests: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    /// Sets the entity ID of this service provider, which the identity provider knows it by.
+    pub fn with_sp_entity_id(mut self, sp_entity_id: String) -> Self {
+        self.sp_entity_id = Some(sp_entity_id);
+        self
+    }
+
+    /// Sets the public URL of this service provider's assertion consumer service (the
+    /// `POST /saml/acs` endpoint).
+    pub fn with_acs_url(mut self, acs_url: String) -> Self {
+        self.acs_url = Some(acs_url);
+        self
+    }
+
+    /// Sets the entity ID of the identity provider.
+    pub fn with_idp_entity_id(mut self, idp_entity_id: String) -> Self {
+        self.idp_entity_id = Some(idp_entity_id);
+        self
+    }
+
+    /// Sets the identity provider's single sign-on URL for the HTTP-Redirect binding.
+    pub fn with_idp_sso_url(mut self, idp_sso_url: String) -> Self {
+        self.idp_sso_url = Some(idp_sso_url);
+        self
+    }
+
+    /// Sets the PEM-encoded X.509 certificate the identity provider signs its assertions with.
+    pub fn with_idp_certificate(mut self, idp_certificate: Vec<u8>) -> Self {
+        self.idp_certificate = Some(idp_certificate);
+        self
+    }
+
+    /// Sets the amount of time a user's session is valid for after they log in; the default is 8
+    /// hours.
+    pub fn with_session_duration(mut self, session_duration: Duration) -> Self {
+        self.session_duration = Some(session_duration);
+        self
+    }
+}
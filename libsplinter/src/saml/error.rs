@@ -0,0 +1,89 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::error::{InternalError, InvalidArgumentError, InvalidStateError};
+
+/// An error that may occur when building a SamlServiceProvider
+#[derive(Debug)]
+pub enum SamlServiceProviderBuildError {
+    InvalidStateError(InvalidStateError),
+    InvalidArgumentError(InvalidArgumentError),
+}
+
+impl fmt::Display for SamlServiceProviderBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SamlServiceProviderBuildError::InvalidStateError(err) => f.write_str(&err.to_string()),
+            SamlServiceProviderBuildError::InvalidArgumentError(err) => {
+                f.write_str(&err.to_string())
+            }
+        }
+    }
+}
+
+impl Error for SamlServiceProviderBuildError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SamlServiceProviderBuildError::InvalidStateError(err) => Some(err),
+            SamlServiceProviderBuildError::InvalidArgumentError(err) => Some(err),
+        }
+    }
+}
+
+impl From<InvalidStateError> for SamlServiceProviderBuildError {
+    fn from(err: InvalidStateError) -> Self {
+        SamlServiceProviderBuildError::InvalidStateError(err)
+    }
+}
+
+impl From<InvalidArgumentError> for SamlServiceProviderBuildError {
+    fn from(err: InvalidArgumentError) -> Self {
+        SamlServiceProviderBuildError::InvalidArgumentError(err)
+    }
+}
+
+/// An error that may occur when processing a SAML response
+#[derive(Debug)]
+pub enum SamlResponseError {
+    /// The response is malformed, or failed validation; the user may not be logged in
+    InvalidResponse(String),
+    InternalError(InternalError),
+}
+
+impl fmt::Display for SamlResponseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SamlResponseError::InvalidResponse(msg) => write!(f, "invalid SAML response: {}", msg),
+            SamlResponseError::InternalError(err) => f.write_str(&err.to_string()),
+        }
+    }
+}
+
+impl Error for SamlResponseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SamlResponseError::InvalidResponse(_) => None,
+            SamlResponseError::InternalError(err) => Some(err),
+        }
+    }
+}
+
+impl From<InternalError> for SamlResponseError {
+    fn from(err: InternalError) -> Self {
+        SamlResponseError::InternalError(err)
+    }
+}
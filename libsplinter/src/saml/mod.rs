@@ -0,0 +1,811 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Support for authenticating users with a SAML 2.0 identity provider
+//!
+//! Splinter acts as a SAML service provider using the Web Browser SSO profile
+//! (<https://docs.oasis-open.org/security/saml/v2.0/saml-profiles-2.0-os.pdf>): users are sent to
+//! the identity provider with an `AuthnRequest` using the HTTP-Redirect binding, and the identity
+//! provider returns a signed `Response` to the assertion consumer service using the HTTP-POST
+//! binding.
+//!
+//! Users authenticated by the identity provider are correlated with Biome users in the same way as
+//! OAuth users, so they may be assigned roles and permissions like any other user.
+
+mod builder;
+mod error;
+#[cfg(feature = "rest-api")]
+pub(crate) mod rest_api;
+mod signature;
+mod time;
+mod xml;
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use flate2::{write::DeflateEncoder, Compression};
+use openssl::pkey::{PKey, Public};
+use rand::{thread_rng, Rng};
+
+use crate::error::InternalError;
+use crate::hex::to_hex;
+
+use self::signature::{decode_base64, verify_enveloped_signature};
+use self::time::{format_datetime, parse_datetime};
+use self::xml::{escape, Element};
+
+pub use builder::SamlServiceProviderBuilder;
+pub use error::{SamlResponseError, SamlServiceProviderBuildError};
+
+/// The prefix of the subject identifiers of SAML users' sessions, which distinguishes them from
+/// OAuth users' sessions
+pub const SAML_SUBJECT_PREFIX: &str = "saml:";
+
+const PROTOCOL_NAMESPACE: &str = "urn:oasis:names:tc:SAML:2.0:protocol";
+const ASSERTION_NAMESPACE: &str = "urn:oasis:names:tc:SAML:2.0:assertion";
+const METADATA_NAMESPACE: &str = "urn:oasis:names:tc:SAML:2.0:metadata";
+const HTTP_POST_BINDING: &str = "urn:oasis:names:tc:SAML:2.0:bindings:HTTP-POST";
+const BEARER_CONFIRMATION_METHOD: &str = "urn:oasis:names:tc:SAML:2.0:cm:bearer";
+const SUCCESS_STATUS: &str = "urn:oasis:names:tc:SAML:2.0:status:Success";
+
+/// The amount of time a user has to log in with the identity provider
+const AUTHN_REQUEST_LIFETIME: Duration = Duration::from_secs(600); // 10 minutes
+/// The allowed difference between the identity provider's clock and the local clock
+const CLOCK_SKEW: Duration = Duration::from_secs(180); // 3 minutes
+
+/// A user that has been authenticated by the SAML identity provider
+#[derive(Debug)]
+pub struct SamlUser {
+    subject: String,
+    attributes: BTreeMap<String, Vec<String>>,
+}
+
+impl SamlUser {
+    /// Returns the subject identifier of the user, which is the value of the assertion's `NameID`
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    /// Returns the attributes asserted by the identity provider
+    pub fn attributes(&self) -> &BTreeMap<String, Vec<String>> {
+        &self.attributes
+    }
+}
+
+/// An authentication request that has been sent to the identity provider and is awaiting a
+/// response
+struct PendingAuthnRequest {
+    client_redirect_url: String,
+    expires: SystemTime,
+}
+
+/// A SAML 2.0 service provider for Splinter
+///
+/// The service provider trusts a single identity provider, whose assertions must be signed with
+/// the key of its configured certificate.
+#[derive(Clone)]
+pub struct SamlServiceProvider {
+    sp_entity_id: String,
+    acs_url: String,
+    idp_entity_id: String,
+    idp_sso_url: String,
+    idp_public_key: PKey<Public>,
+    session_duration: Duration,
+    /// Authentication requests that are awaiting a response, by request ID
+    pending_requests: Arc<Mutex<HashMap<String, PendingAuthnRequest>>>,
+}
+
+impl SamlServiceProvider {
+    /// Returns the amount of time a user's session is valid for after they log in
+    pub fn session_duration(&self) -> Duration {
+        self.session_duration
+    }
+
+    /// Returns the SAML metadata that describes this service provider, which is used to register
+    /// the service provider with the identity provider
+    pub fn metadata(&self) -> String {
+        format!(
+            "<md:EntityDescriptor xmlns:md=\"{}\" entityID=\"{}\">\
+             <md:SPSSODescriptor AuthnRequestsSigned=\"false\" WantAssertionsSigned=\"true\" \
+             protocolSupportEnumeration=\"{}\">\
+             <md:AssertionConsumerService Binding=\"{}\" Location=\"{}\" index=\"0\" \
+             isDefault=\"true\"/>\
+             </md:SPSSODescriptor>\
+             </md:EntityDescriptor>",
+            METADATA_NAMESPACE,
+            escape(&self.sp_entity_id),
+            PROTOCOL_NAMESPACE,
+            HTTP_POST_BINDING,
+            escape(&self.acs_url),
+        )
+    }
+
+    /// Generates a new authentication request and returns the identity provider's URL for it,
+    /// using the HTTP-Redirect binding
+    ///
+    /// # Arguments
+    ///
+    /// * `client_redirect_url` - The URL the client will be redirected to once the user has logged
+    ///   in
+    pub fn get_authn_request_url(
+        &self,
+        client_redirect_url: String,
+    ) -> Result<String, InternalError> {
+        let request_id = format!("_{}", to_hex(&thread_rng().gen::<[u8; 20]>()));
+        let now = SystemTime::now();
+
+        let authn_request = format!(
+            "<samlp:AuthnRequest xmlns:samlp=\"{}\" xmlns:saml=\"{}\" ID=\"{}\" Version=\"2.0\" \
+             IssueInstant=\"{}\" Destination=\"{}\" AssertionConsumerServiceURL=\"{}\" \
+             ProtocolBinding=\"{}\">\
+             <saml:Issuer>{}</saml:Issuer>\
+             <samlp:NameIDPolicy AllowCreate=\"true\"/>\
+             </samlp:AuthnRequest>",
+            PROTOCOL_NAMESPACE,
+            ASSERTION_NAMESPACE,
+            request_id,
+            format_datetime(now),
+            escape(&self.idp_sso_url),
+            escape(&self.acs_url),
+            HTTP_POST_BINDING,
+            escape(&self.sp_entity_id),
+        );
+
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(authn_request.as_bytes())
+            .map_err(|err| InternalError::from_source(Box::new(err)))?;
+        let deflated = encoder
+            .finish()
+            .map_err(|err| InternalError::from_source(Box::new(err)))?;
+        let saml_request: String =
+            url::form_urlencoded::byte_serialize(base64::encode(&deflated).as_bytes()).collect();
+
+        let mut pending_requests = self.pending_requests.lock().map_err(|_| {
+            InternalError::with_message("SAML pending request lock was poisoned".into())
+        })?;
+        pending_requests.retain(|_, request| request.expires > now);
+        pending_requests.insert(
+            request_id,
+            PendingAuthnRequest {
+                client_redirect_url,
+                expires: now + AUTHN_REQUEST_LIFETIME,
+            },
+        );
+
+        let separator = if self.idp_sso_url.contains('?') {
+            '&'
+        } else {
+            '?'
+        };
+        Ok(format!(
+            "{}{}SAMLRequest={}",
+            self.idp_sso_url, separator, saml_request
+        ))
+    }
+
+    /// Validates a response that was sent by the identity provider to the assertion consumer
+    /// service, and returns the authenticated user along with the client redirect URL of the
+    /// authentication request the response is for
+    ///
+    /// The response must be for a pending authentication request; each request may only be used
+    /// once. The response or its assertion must be signed by the identity provider, and only the
+    /// signed content is trusted.
+    ///
+    /// # Arguments
+    ///
+    /// * `saml_response` - The base64-encoded `SAMLResponse` form parameter
+    pub fn consume_response(
+        &self,
+        saml_response: &str,
+    ) -> Result<(SamlUser, String), SamlResponseError> {
+        let now = SystemTime::now();
+
+        let document = String::from_utf8(decode_base64(saml_response)?)
+            .map_err(|_| invalid("response is not valid UTF-8"))?;
+        let response = xml::parse(&document)
+            .map_err(|err| SamlResponseError::InvalidResponse(err.to_string()))?;
+        if !response.is(PROTOCOL_NAMESPACE, "Response") {
+            return Err(invalid("document is not a SAML response"));
+        }
+
+        if response
+            .child(ASSERTION_NAMESPACE, "EncryptedAssertion")
+            .is_some()
+        {
+            return Err(invalid("encrypted assertions are not supported"));
+        }
+        let mut assertions = response.children_named(ASSERTION_NAMESPACE, "Assertion");
+        let assertion = assertions
+            .next()
+            .ok_or_else(|| invalid("response has no assertion"))?;
+        if assertions.next().is_some() {
+            return Err(invalid("response has more than one assertion"));
+        }
+
+        let response_signed = verify_enveloped_signature(&response, &self.idp_public_key)?;
+        let assertion_signed = verify_enveloped_signature(assertion, &self.idp_public_key)?;
+        if !response_signed && !assertion_signed {
+            return Err(invalid("neither the response nor its assertion is signed"));
+        }
+
+        if let Some(destination) = response.attribute("Destination") {
+            if destination != self.acs_url {
+                return Err(invalid("response is not for this service provider"));
+            }
+        }
+        if let Some(issuer) = response.child(ASSERTION_NAMESPACE, "Issuer") {
+            if issuer.text() != self.idp_entity_id {
+                return Err(invalid("response was not issued by the identity provider"));
+            }
+        }
+        let status = response
+            .child(PROTOCOL_NAMESPACE, "Status")
+            .and_then(|status| status.child(PROTOCOL_NAMESPACE, "StatusCode"))
+            .and_then(|code| code.attribute("Value"));
+        if status != Some(SUCCESS_STATUS) {
+            return Err(invalid(&format!(
+                "identity provider returned status {}",
+                status.unwrap_or("<none>")
+            )));
+        }
+
+        let request_id = response
+            .attribute("InResponseTo")
+            .ok_or_else(|| invalid("response is not for an authentication request"))?;
+        let pending_request = self
+            .pending_requests
+            .lock()
+            .map_err(|_| {
+                InternalError::with_message("SAML pending request lock was poisoned".into())
+            })?
+            .remove(request_id)
+            .filter(|request| request.expires > now)
+            .ok_or_else(|| invalid("response is not for a pending authentication request"))?;
+
+        let user = self.validate_assertion(assertion, request_id, now)?;
+
+        Ok((user, pending_request.client_redirect_url))
+    }
+
+    /// Validates the assertion of a response and returns the user it identifies
+    fn validate_assertion(
+        &self,
+        assertion: &Element,
+        request_id: &str,
+        now: SystemTime,
+    ) -> Result<SamlUser, SamlResponseError> {
+        match assertion.child(ASSERTION_NAMESPACE, "Issuer") {
+            Some(issuer) if issuer.text() == self.idp_entity_id => (),
+            _ => return Err(invalid("assertion was not issued by the identity provider")),
+        }
+
+        let subject = assertion
+            .child(ASSERTION_NAMESPACE, "Subject")
+            .ok_or_else(|| invalid("assertion has no subject"))?;
+        let name_id = subject
+            .child(ASSERTION_NAMESPACE, "NameID")
+            .map(|name_id| name_id.text().trim().to_string())
+            .filter(|name_id| !name_id.is_empty())
+            .ok_or_else(|| invalid("assertion subject has no NameID"))?;
+
+        let confirmed = subject
+            .children_named(ASSERTION_NAMESPACE, "SubjectConfirmation")
+            .filter(|confirmation| {
+                confirmation.attribute("Method") == Some(BEARER_CONFIRMATION_METHOD)
+            })
+            .filter_map(|confirmation| {
+                confirmation.child(ASSERTION_NAMESPACE, "SubjectConfirmationData")
+            })
+            .any(|data| {
+                data.attribute("Recipient") == Some(&self.acs_url)
+                    && data.attribute("InResponseTo") == Some(request_id)
+                    && data
+                        .attribute("NotOnOrAfter")
+                        .and_then(parse_datetime)
+                        .map(|not_on_or_after| now < not_on_or_after + CLOCK_SKEW)
+                        .unwrap_or(false)
+                    && data.attribute("NotBefore").is_none()
+            });
+        if !confirmed {
+            return Err(invalid("assertion subject could not be confirmed"));
+        }
+
+        let conditions = assertion
+            .child(ASSERTION_NAMESPACE, "Conditions")
+            .ok_or_else(|| invalid("assertion has no conditions"))?;
+        if let Some(not_before) = conditions.attribute("NotBefore") {
+            let not_before = parse_datetime(not_before)
+                .ok_or_else(|| invalid("assertion has an invalid NotBefore condition"))?;
+            if now + CLOCK_SKEW < not_before {
+                return Err(invalid("assertion is not yet valid"));
+            }
+        }
+        if let Some(not_on_or_after) = conditions.attribute("NotOnOrAfter") {
+            let not_on_or_after = parse_datetime(not_on_or_after)
+                .ok_or_else(|| invalid("assertion has an invalid NotOnOrAfter condition"))?;
+            if now >= not_on_or_after + CLOCK_SKEW {
+                return Err(invalid("assertion has expired"));
+            }
+        }
+        let mut audience_restrictions = conditions
+            .children_named(ASSERTION_NAMESPACE, "AudienceRestriction")
+            .peekable();
+        if audience_restrictions.peek().is_none() {
+            return Err(invalid("assertion has no audience restriction"));
+        }
+        for restriction in audience_restrictions {
+            if !restriction
+                .children_named(ASSERTION_NAMESPACE, "Audience")
+                .any(|audience| audience.text().trim() == self.sp_entity_id)
+            {
+                return Err(invalid(
+                    "assertion is not intended for this service provider",
+                ));
+            }
+        }
+
+        let mut attributes: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for attribute in assertion
+            .children_named(ASSERTION_NAMESPACE, "AttributeStatement")
+            .flat_map(|statement| statement.children_named(ASSERTION_NAMESPACE, "Attribute"))
+        {
+            if let Some(name) = attribute.attribute("Name") {
+                attributes.entry(name.to_string()).or_default().extend(
+                    attribute
+                        .children_named(ASSERTION_NAMESPACE, "AttributeValue")
+                        .map(|value| value.text()),
+                );
+            }
+        }
+
+        Ok(SamlUser {
+            subject: name_id,
+            attributes,
+        })
+    }
+}
+
+fn invalid(message: &str) -> SamlResponseError {
+    SamlResponseError::InvalidResponse(message.to_string())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    use std::io::Read;
+
+    use flate2::read::DeflateDecoder;
+    use openssl::asn1::Asn1Time;
+    use openssl::bn::BigNum;
+    use openssl::hash::{hash, MessageDigest};
+    use openssl::pkey::Private;
+    use openssl::rsa::Rsa;
+    use openssl::sign::Signer;
+    use openssl::x509::{X509Builder, X509NameBuilder};
+
+    use super::signature::DSIG_NAMESPACE;
+    use super::xml::canonicalize;
+
+    pub(crate) const SP_ENTITY_ID: &str = "https://splinter.example.com/saml/metadata";
+    pub(crate) const ACS_URL: &str = "https://splinter.example.com/saml/acs";
+    pub(crate) const IDP_ENTITY_ID: &str = "https://idp.example.com";
+    pub(crate) const IDP_SSO_URL: &str = "https://idp.example.com/sso";
+    const CLIENT_REDIRECT_URL: &str = "http://client/redirect";
+
+    /// Which element of a response the stand-in identity provider signs
+    #[derive(Clone, Copy)]
+    pub(crate) enum Signed {
+        Response,
+        Assertion,
+        Nothing,
+    }
+
+    /// A stand-in SAML identity provider, which issues responses signed with a new key
+    pub(crate) struct TestIdentityProvider {
+        key: PKey<Private>,
+        certificate: Vec<u8>,
+    }
+
+    impl TestIdentityProvider {
+        pub(crate) fn new() -> Self {
+            let key = PKey::from_rsa(Rsa::generate(2048).expect("Failed to generate key"))
+                .expect("Failed to create key");
+
+            let mut name = X509NameBuilder::new().expect("Failed to create name");
+            name.append_entry_by_text("CN", "idp.example.com")
+                .expect("Failed to set name");
+            let name = name.build();
+            let mut builder = X509Builder::new().expect("Failed to create certificate");
+            builder.set_version(2).expect("Failed to set version");
+            builder
+                .set_serial_number(
+                    &BigNum::from_u32(1)
+                        .and_then(|serial| serial.to_asn1_integer())
+                        .expect("Failed to create serial number"),
+                )
+                .expect("Failed to set serial number");
+            builder
+                .set_subject_name(&name)
+                .expect("Failed to set subject");
+            builder
+                .set_issuer_name(&name)
+                .expect("Failed to set issuer");
+            builder.set_pubkey(&key).expect("Failed to set public key");
+            builder
+                .set_not_before(&Asn1Time::days_from_now(0).expect("Failed to create time"))
+                .expect("Failed to set not before");
+            builder
+                .set_not_after(&Asn1Time::days_from_now(1).expect("Failed to create time"))
+                .expect("Failed to set not after");
+            builder
+                .sign(&key, MessageDigest::sha256())
+                .expect("Failed to sign certificate");
+            let certificate = builder
+                .build()
+                .to_pem()
+                .expect("Failed to encode certificate");
+
+            Self { key, certificate }
+        }
+
+        /// Returns the PEM-encoded certificate of the identity provider
+        pub(crate) fn certificate(&self) -> Vec<u8> {
+            self.certificate.clone()
+        }
+
+        /// Returns a base64-encoded response to the given request, which authenticates the given
+        /// user for the given audience until the given time
+        pub(crate) fn response(
+            &self,
+            request_id: &str,
+            name_id: &str,
+            audience: &str,
+            not_on_or_after: SystemTime,
+            signed: Signed,
+        ) -> String {
+            let now = SystemTime::now();
+            let response = format!(
+                "<samlp:Response xmlns:samlp=\"{protocol}\" xmlns:saml=\"{assertion}\" \
+                 ID=\"_response\" Version=\"2.0\" IssueInstant=\"{now}\" \
+                 Destination=\"{acs_url}\" InResponseTo=\"{request_id}\">\
+                 <saml:Issuer>{idp}</saml:Issuer>\
+                 {response_signature}\
+                 <samlp:Status><samlp:StatusCode Value=\"{success}\"/></samlp:Status>\
+                 <saml:Assertion ID=\"_assertion\" Version=\"2.0\" IssueInstant=\"{now}\">\
+                 <saml:Issuer>{idp}</saml:Issuer>\
+                 {assertion_signature}\
+                 <saml:Subject>\
+                 <saml:NameID>{name_id}</saml:NameID>\
+                 <saml:SubjectConfirmation Method=\"{bearer}\">\
+                 <saml:SubjectConfirmationData Recipient=\"{acs_url}\" \
+                 InResponseTo=\"{request_id}\" NotOnOrAfter=\"{not_on_or_after}\"/>\
+                 </saml:SubjectConfirmation>\
+                 </saml:Subject>\
+                 <saml:Conditions NotBefore=\"{now}\" NotOnOrAfter=\"{not_on_or_after}\">\
+                 <saml:AudienceRestriction><saml:Audience>{audience}</saml:Audience>\
+                 </saml:AudienceRestriction>\
+                 </saml:Conditions>\
+                 <saml:AttributeStatement>\
+                 <saml:Attribute Name=\"role\">\
+                 <saml:AttributeValue>admin</saml:AttributeValue>\
+                 <saml:AttributeValue>member</saml:AttributeValue>\
+                 </saml:Attribute>\
+                 </saml:AttributeStatement>\
+                 </saml:Assertion>\
+                 </samlp:Response>",
+                protocol = PROTOCOL_NAMESPACE,
+                assertion = ASSERTION_NAMESPACE,
+                now = format_datetime(now),
+                acs_url = ACS_URL,
+                request_id = request_id,
+                idp = IDP_ENTITY_ID,
+                success = SUCCESS_STATUS,
+                name_id = name_id,
+                bearer = BEARER_CONFIRMATION_METHOD,
+                not_on_or_after = format_datetime(not_on_or_after),
+                audience = audience,
+                response_signature = match signed {
+                    Signed::Response => signature_template("_response"),
+                    _ => String::new(),
+                },
+                assertion_signature = match signed {
+                    Signed::Assertion => signature_template("_assertion"),
+                    _ => String::new(),
+                },
+            );
+
+            let response = match signed {
+                Signed::Response => self.sign(&response, "_response"),
+                Signed::Assertion => self.sign(&response, "_assertion"),
+                Signed::Nothing => response,
+            };
+
+            base64::encode(response)
+        }
+
+        /// Fills in the digest and signature value of the signature of the element with the
+        /// given ID
+        fn sign(&self, document: &str, id: &str) -> String {
+            let root = xml::parse(document).expect("Failed to parse response");
+            let element = find_element(&root, id);
+            let signature = element
+                .child(DSIG_NAMESPACE, "Signature")
+                .expect("Signature not found");
+            let digest = hash(
+                MessageDigest::sha256(),
+                canonicalize(element, Some(signature), &[]).as_bytes(),
+            )
+            .expect("Failed to compute digest");
+            let document = document.replace("DIGEST_VALUE", &base64::encode(&digest));
+
+            let root = xml::parse(&document).expect("Failed to parse response");
+            let signed_info = find_element(&root, id)
+                .child(DSIG_NAMESPACE, "Signature")
+                .and_then(|signature| signature.child(DSIG_NAMESPACE, "SignedInfo"))
+                .expect("SignedInfo not found");
+            let mut signer =
+                Signer::new(MessageDigest::sha256(), &self.key).expect("Failed to create signer");
+            signer
+                .update(canonicalize(signed_info, None, &[]).as_bytes())
+                .expect("Failed to update signer");
+            let signature_value = signer.sign_to_vec().expect("Failed to sign");
+
+            document.replace("SIGNATURE_VALUE", &base64::encode(&signature_value))
+        }
+    }
+
+    fn signature_template(id: &str) -> String {
+        format!(
+            "<ds:Signature xmlns:ds=\"{dsig}\"><ds:SignedInfo>\
+             <ds:CanonicalizationMethod Algorithm=\"http://www.w3.org/2001/10/xml-exc-c14n#\"/>\
+             <ds:SignatureMethod \
+             Algorithm=\"http://www.w3.org/2001/04/xmldsig-more#rsa-sha256\"/>\
+             <ds:Reference URI=\"#{id}\"><ds:Transforms>\
+             <ds:Transform Algorithm=\"{dsig}enveloped-signature\"/>\
+             <ds:Transform Algorithm=\"http://www.w3.org/2001/10/xml-exc-c14n#\"/>\
+             </ds:Transforms>\
+             <ds:DigestMethod Algorithm=\"http://www.w3.org/2001/04/xmlenc#sha256\"/>\
+             <ds:DigestValue>DIGEST_VALUE</ds:DigestValue>\
+             </ds:Reference></ds:SignedInfo>\
+             <ds:SignatureValue>SIGNATURE_VALUE</ds:SignatureValue></ds:Signature>",
+            dsig = DSIG_NAMESPACE,
+            id = id,
+        )
+    }
+
+    fn find_element<'a>(root: &'a Element, id: &str) -> &'a Element {
+        if root.attribute("ID") == Some(id) {
+            root
+        } else {
+            root.child(ASSERTION_NAMESPACE, "Assertion")
+                .filter(|assertion| assertion.attribute("ID") == Some(id))
+                .expect("Element to sign not found")
+        }
+    }
+
+    /// Returns the parsed authentication request of an identity provider URL returned by
+    /// `SamlServiceProvider::get_authn_request_url`
+    pub(crate) fn parse_authn_request(authn_request_url: &str) -> Element {
+        let url = url::Url::parse(authn_request_url).expect("Failed to parse URL");
+        let saml_request = url
+            .query_pairs()
+            .find(|(key, _)| key == "SAMLRequest")
+            .map(|(_, value)| value.into_owned())
+            .expect("SAMLRequest not found");
+        let deflated = base64::decode(&saml_request).expect("Failed to decode request");
+        let mut authn_request = String::new();
+        DeflateDecoder::new(&deflated[..])
+            .read_to_string(&mut authn_request)
+            .expect("Failed to inflate request");
+        xml::parse(&authn_request).expect("Failed to parse request")
+    }
+
+    pub(crate) fn new_service_provider(idp: &TestIdentityProvider) -> SamlServiceProvider {
+        SamlServiceProviderBuilder::new()
+            .with_sp_entity_id(SP_ENTITY_ID.into())
+            .with_acs_url(ACS_URL.into())
+            .with_idp_entity_id(IDP_ENTITY_ID.into())
+            .with_idp_sso_url(IDP_SSO_URL.into())
+            .with_idp_certificate(idp.certificate())
+            .build()
+            .expect("Failed to build service provider")
+    }
+
+    /// Starts a new authentication request and returns its ID
+    fn start_request(service_provider: &SamlServiceProvider) -> String {
+        let authn_request_url = service_provider
+            .get_authn_request_url(CLIENT_REDIRECT_URL.into())
+            .expect("Failed to get authentication request URL");
+        parse_authn_request(&authn_request_url)
+            .attribute("ID")
+            .expect("Request has no ID")
+            .to_string()
+    }
+
+    fn in_minutes(minutes: u64) -> SystemTime {
+        SystemTime::now() + Duration::from_secs(minutes * 60)
+    }
+
+    /// Verify that the authentication request sent to the identity provider uses the
+    /// HTTP-Redirect binding and identifies the service provider.
+    #[test]
+    fn authn_request() {
+        let idp = TestIdentityProvider::new();
+        let service_provider = new_service_provider(&idp);
+
+        let authn_request_url = service_provider
+            .get_authn_request_url(CLIENT_REDIRECT_URL.into())
+            .expect("Failed to get authentication request URL");
+        assert!(authn_request_url.starts_with(&format!("{}?SAMLRequest=", IDP_SSO_URL)));
+
+        let authn_request = parse_authn_request(&authn_request_url);
+        assert!(authn_request.is(PROTOCOL_NAMESPACE, "AuthnRequest"));
+        assert_eq!(authn_request.attribute("Destination"), Some(IDP_SSO_URL));
+        assert_eq!(
+            authn_request.attribute("AssertionConsumerServiceURL"),
+            Some(ACS_URL)
+        );
+        assert_eq!(
+            authn_request.attribute("ProtocolBinding"),
+            Some(HTTP_POST_BINDING)
+        );
+        assert_eq!(
+            authn_request
+                .child(ASSERTION_NAMESPACE, "Issuer")
+                .map(|issuer| issuer.text()),
+            Some(SP_ENTITY_ID.to_string())
+        );
+    }
+
+    /// Verify that the service provider accepts a response from the stand-in identity provider
+    /// whose assertion is signed, and that each authentication request can only be used once.
+    ///
+    /// 1. Start an authentication request
+    /// 2. Verify that a response with a signed assertion for the request is accepted, and returns
+    ///    the user's subject and attributes along with the client's redirect URL
+    /// 3. Verify that the same response is rejected when it is replayed
+    #[test]
+    fn consume_signed_assertion() {
+        let idp = TestIdentityProvider::new();
+        let service_provider = new_service_provider(&idp);
+
+        let request_id = start_request(&service_provider);
+        let response = idp.response(
+            &request_id,
+            "alice@example.com",
+            SP_ENTITY_ID,
+            in_minutes(5),
+            Signed::Assertion,
+        );
+
+        let (user, redirect_url) = service_provider
+            .consume_response(&response)
+            .expect("Failed to consume response");
+        assert_eq!(user.subject(), "alice@example.com");
+        assert_eq!(
+            user.attributes().get("role"),
+            Some(&vec!["admin".to_string(), "member".to_string()])
+        );
+        assert_eq!(redirect_url, CLIENT_REDIRECT_URL);
+
+        assert!(matches!(
+            service_provider.consume_response(&response),
+            Err(SamlResponseError::InvalidResponse(_))
+        ));
+    }
+
+    /// Verify that the service provider accepts a response from the stand-in identity provider
+    /// when the response itself is signed rather than its assertion.
+    #[test]
+    fn consume_signed_response() {
+        let idp = TestIdentityProvider::new();
+        let service_provider = new_service_provider(&idp);
+
+        let request_id = start_request(&service_provider);
+        let response = idp.response(
+            &request_id,
+            "alice@example.com",
+            SP_ENTITY_ID,
+            in_minutes(5),
+            Signed::Response,
+        );
+
+        let (user, _) = service_provider
+            .consume_response(&response)
+            .expect("Failed to consume response");
+        assert_eq!(user.subject(), "alice@example.com");
+    }
+
+    /// Verify that the service provider rejects responses that are not signed by the identity
+    /// provider, have been tampered with, are for another audience or request, or have expired.
+    #[test]
+    fn reject_invalid_responses() {
+        let idp = TestIdentityProvider::new();
+        let service_provider = new_service_provider(&idp);
+
+        let assert_rejected = |response: String| {
+            assert!(matches!(
+                service_provider.consume_response(&response),
+                Err(SamlResponseError::InvalidResponse(_))
+            ));
+        };
+
+        // Not signed
+        let request_id = start_request(&service_provider);
+        assert_rejected(idp.response(
+            &request_id,
+            "alice@example.com",
+            SP_ENTITY_ID,
+            in_minutes(5),
+            Signed::Nothing,
+        ));
+
+        // Signed by another identity provider
+        let request_id = start_request(&service_provider);
+        assert_rejected(TestIdentityProvider::new().response(
+            &request_id,
+            "alice@example.com",
+            SP_ENTITY_ID,
+            in_minutes(5),
+            Signed::Assertion,
+        ));
+
+        // Subject changed after signing
+        let request_id = start_request(&service_provider);
+        let response = idp.response(
+            &request_id,
+            "alice@example.com",
+            SP_ENTITY_ID,
+            in_minutes(5),
+            Signed::Assertion,
+        );
+        let tampered = String::from_utf8(base64::decode(&response).expect("Failed to decode"))
+            .expect("Response is not UTF-8")
+            .replace("alice@example.com", "mallory@example.com");
+        assert_rejected(base64::encode(tampered));
+
+        // Intended for another service provider
+        let request_id = start_request(&service_provider);
+        assert_rejected(idp.response(
+            &request_id,
+            "alice@example.com",
+            "https://other.example.com",
+            in_minutes(5),
+            Signed::Assertion,
+        ));
+
+        // Expired
+        let request_id = start_request(&service_provider);
+        assert_rejected(idp.response(
+            &request_id,
+            "alice@example.com",
+            SP_ENTITY_ID,
+            SystemTime::now() - Duration::from_secs(600),
+            Signed::Assertion,
+        ));
+
+        // Not for a pending request
+        assert_rejected(idp.response(
+            "_unknown",
+            "alice@example.com",
+            SP_ENTITY_ID,
+            in_minutes(5),
+            Signed::Assertion,
+        ));
+    }
+}
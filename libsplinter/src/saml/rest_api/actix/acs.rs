@@ -0,0 +1,150 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `POST /saml/acs` endpoint, the assertion consumer service, for receiving the identity
+//! provider's response and starting a session for the user.
+
+use actix_web::{http::header::LOCATION, HttpResponse};
+use futures::{future::IntoFuture, Future};
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+
+use crate::biome::oauth::store::{InsertableOAuthUserSessionBuilder, OAuthUserSessionStore};
+use crate::protocol;
+#[cfg(feature = "authorization")]
+use crate::rest_api::auth::authorization::Permission;
+use crate::rest_api::{
+    actix_web_1::{into_bytes, HandlerFunction, Method, ProtocolVersionRangeGuard, Resource},
+    ErrorResponse,
+};
+use crate::saml::{SamlResponseError, SamlServiceProvider, SAML_SUBJECT_PREFIX};
+
+pub fn make_acs_route(
+    service_provider: SamlServiceProvider,
+    session_store: Box<dyn OAuthUserSessionStore>,
+) -> Resource {
+    let resource = Resource::build("/saml/acs").add_request_guard(ProtocolVersionRangeGuard::new(
+        protocol::SAML_ACS_MIN,
+        protocol::SAML_PROTOCOL_VERSION,
+    ));
+    #[cfg(feature = "authorization")]
+    {
+        resource.add_method(
+            Method::Post,
+            Permission::AllowUnauthenticated,
+            add_consume_response_method(service_provider, session_store),
+        )
+    }
+    #[cfg(not(feature = "authorization"))]
+    {
+        resource.add_method(
+            Method::Post,
+            add_consume_response_method(service_provider, session_store),
+        )
+    }
+}
+
+fn add_consume_response_method(
+    service_provider: SamlServiceProvider,
+    session_store: Box<dyn OAuthUserSessionStore>,
+) -> HandlerFunction {
+    Box::new(move |_, payload| {
+        let service_provider = service_provider.clone();
+        let session_store = session_store.clone();
+        Box::new(into_bytes(payload).and_then(move |bytes| {
+            let saml_response =
+                match url::form_urlencoded::parse(&bytes).find(|(key, _)| key == "SAMLResponse") {
+                    Some((_, saml_response)) => saml_response,
+                    None => {
+                        return HttpResponse::BadRequest()
+                            .json(ErrorResponse::bad_request("No SAMLResponse supplied"))
+                            .into_future()
+                    }
+                };
+
+            let (user, client_redirect_url) =
+                match service_provider.consume_response(&saml_response) {
+                    Ok(result) => result,
+                    Err(SamlResponseError::InvalidResponse(msg)) => {
+                        warn!("Rejected SAML response: {}", msg);
+                        return HttpResponse::Unauthorized()
+                            .json(ErrorResponse::unauthorized())
+                            .into_future();
+                    }
+                    Err(err) => {
+                        error!("{}", err);
+                        return HttpResponse::InternalServerError()
+                            .json(ErrorResponse::internal_error())
+                            .into_future();
+                    }
+                };
+
+            // Generate a Splinter access token for the new session
+            let splinter_access_token = new_splinter_access_token();
+
+            // SAML sessions have no OAuth tokens; the session is valid until it expires
+            let session = match InsertableOAuthUserSessionBuilder::new()
+                .with_splinter_access_token(splinter_access_token.clone())
+                .with_subject(format!("{}{}", SAML_SUBJECT_PREFIX, user.subject()))
+                .with_oauth_access_token(String::new())
+                .build()
+            {
+                Ok(session) => session,
+                Err(err) => {
+                    error!("Unable to build user session: {}", err);
+                    return HttpResponse::InternalServerError()
+                        .json(ErrorResponse::internal_error())
+                        .into_future();
+                }
+            };
+            if let Err(err) = session_store.add_session(session) {
+                error!("Unable to store user session: {}", err);
+                return HttpResponse::InternalServerError()
+                    .json(ErrorResponse::internal_error())
+                    .into_future();
+            }
+
+            // Adding the token and subject to the redirect URL so the client may access these
+            // values after a redirect
+            let separator = if client_redirect_url.contains('?') {
+                '&'
+            } else {
+                '?'
+            };
+            let redirect_url = format!(
+                "{}{}{}",
+                client_redirect_url,
+                separator,
+                generate_redirect_query(&splinter_access_token, user.subject())
+            );
+
+            HttpResponse::Found()
+                .header(LOCATION, redirect_url)
+                .finish()
+                .into_future()
+        }))
+    })
+}
+
+fn generate_redirect_query(splinter_access_token: &str, display_name: &str) -> String {
+    url::form_urlencoded::Serializer::new(String::new())
+        .append_pair("access_token", &format!("SAML:{}", splinter_access_token))
+        .append_pair("display_name", display_name)
+        .finish()
+}
+
+/// Generates a new Splinter access token, which is a string of 32 random alphanumeric characters
+fn new_splinter_access_token() -> String {
+    thread_rng().sample_iter(&Alphanumeric).take(32).collect()
+}
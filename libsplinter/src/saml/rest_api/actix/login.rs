@@ -0,0 +1,99 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `GET /saml/login` endpoint for redirecting a user to the identity provider with an
+//! authentication request.
+
+use std::collections::HashMap;
+
+use actix_web::{http::header::LOCATION, web, HttpResponse};
+use futures::future::IntoFuture;
+
+use crate::protocol;
+#[cfg(feature = "authorization")]
+use crate::rest_api::auth::authorization::Permission;
+use crate::rest_api::{
+    actix_web_1::{HandlerFunction, Method, ProtocolVersionRangeGuard, Resource},
+    ErrorResponse,
+};
+use crate::saml::SamlServiceProvider;
+
+pub fn make_login_route(service_provider: SamlServiceProvider) -> Resource {
+    let resource = Resource::build("/saml/login").add_request_guard(
+        ProtocolVersionRangeGuard::new(protocol::SAML_LOGIN_MIN, protocol::SAML_PROTOCOL_VERSION),
+    );
+    #[cfg(feature = "authorization")]
+    {
+        resource.add_method(
+            Method::Get,
+            Permission::AllowUnauthenticated,
+            add_login_method(service_provider),
+        )
+    }
+    #[cfg(not(feature = "authorization"))]
+    {
+        resource.add_method(Method::Get, add_login_method(service_provider))
+    }
+}
+
+fn add_login_method(service_provider: SamlServiceProvider) -> HandlerFunction {
+    Box::new(move |req, _| {
+        let query: web::Query<HashMap<String, String>> =
+            if let Ok(q) = web::Query::from_query(req.query_string()) {
+                q
+            } else {
+                return Box::new(
+                    HttpResponse::BadRequest()
+                        .json(ErrorResponse::bad_request("Invalid query"))
+                        .into_future(),
+                );
+            };
+        let client_redirect_url = if let Some(redirect_url) = query.get("redirect_url") {
+            redirect_url
+        } else {
+            match req.headers().get("referer").map(|url| url.to_str()) {
+                Some(Ok(url)) => url,
+                Some(Err(_)) => {
+                    return Box::new(
+                        HttpResponse::BadRequest()
+                            .json(ErrorResponse::bad_request(
+                                "Referer header is set, but is not a valid URL",
+                            ))
+                            .into_future(),
+                    )
+                }
+                None => {
+                    return Box::new(
+                        HttpResponse::BadRequest()
+                            .json(ErrorResponse::bad_request("No valid redirect URL supplied"))
+                            .into_future(),
+                    )
+                }
+            }
+        };
+
+        Box::new(
+            match service_provider.get_authn_request_url(client_redirect_url.to_string()) {
+                Ok(authn_request_url) => HttpResponse::Found()
+                    .header(LOCATION, authn_request_url)
+                    .finish(),
+                Err(err) => {
+                    error!("{}", err);
+                    HttpResponse::InternalServerError().json(ErrorResponse::internal_error())
+                }
+            }
+            .into_future(),
+        )
+    })
+}
@@ -0,0 +1,55 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `GET /saml/metadata` endpoint for getting the service provider's SAML metadata.
+
+use actix_web::{http::header::CONTENT_TYPE, HttpResponse};
+use futures::future::IntoFuture;
+
+use crate::protocol;
+use crate::rest_api::actix_web_1::{HandlerFunction, Method, ProtocolVersionRangeGuard, Resource};
+#[cfg(feature = "authorization")]
+use crate::rest_api::auth::authorization::Permission;
+use crate::saml::SamlServiceProvider;
+
+pub fn make_metadata_route(service_provider: SamlServiceProvider) -> Resource {
+    let resource =
+        Resource::build("/saml/metadata").add_request_guard(ProtocolVersionRangeGuard::new(
+            protocol::SAML_METADATA_MIN,
+            protocol::SAML_PROTOCOL_VERSION,
+        ));
+    #[cfg(feature = "authorization")]
+    {
+        resource.add_method(
+            Method::Get,
+            Permission::AllowUnauthenticated,
+            add_fetch_metadata_method(service_provider),
+        )
+    }
+    #[cfg(not(feature = "authorization"))]
+    {
+        resource.add_method(Method::Get, add_fetch_metadata_method(service_provider))
+    }
+}
+
+fn add_fetch_metadata_method(service_provider: SamlServiceProvider) -> HandlerFunction {
+    Box::new(move |_, _| {
+        Box::new(
+            HttpResponse::Ok()
+                .header(CONTENT_TYPE, "application/samlmetadata+xml")
+                .body(service_provider.metadata())
+                .into_future(),
+        )
+    })
+}
@@ -0,0 +1,90 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SAML REST API endpoints
+
+#[cfg(feature = "rest-api-actix")]
+mod actix;
+
+use crate::biome::OAuthUserSessionStore;
+use crate::rest_api::actix_web_1::{Resource, RestResourceProvider};
+
+use super::SamlServiceProvider;
+
+/// Provides the REST API [Resource](../../../rest_api/struct.Resource.html) definitions for SAML
+/// endpoints. The following endpoints are provided:
+///
+/// * `GET /saml/metadata` - Get the SAML metadata of the service provider
+/// * `GET /saml/login` - Redirect the user to the identity provider with an authentication request
+/// * `POST /saml/acs` - Receive the identity provider's response (the assertion consumer service)
+/// * `GET /saml/logout` - End the user's session
+///
+/// These endpoints are only available if the following REST API backend feature is enabled:
+///
+/// * `rest-api-actix`
+#[derive(Clone)]
+pub struct SamlResourceProvider {
+    service_provider: SamlServiceProvider,
+    session_store: Box<dyn OAuthUserSessionStore>,
+}
+
+impl SamlResourceProvider {
+    /// Creates a new `SamlResourceProvider`
+    ///
+    /// SAML users' sessions are stored in the given [OAuthUserSessionStore], which correlates
+    /// them with Biome users.
+    pub fn new(
+        service_provider: SamlServiceProvider,
+        session_store: Box<dyn OAuthUserSessionStore>,
+    ) -> Self {
+        Self {
+            service_provider,
+            session_store,
+        }
+    }
+}
+
+/// `SamlResourceProvider` provides the following endpoints as REST API resources:
+///
+/// * `GET /saml/metadata` - Get the SAML metadata of the service provider
+/// * `GET /saml/login` - Redirect the user to the identity provider with an authentication request
+/// * `POST /saml/acs` - Receive the identity provider's response (the assertion consumer service)
+/// * `GET /saml/logout` - End the user's session
+///
+/// These endpoints are only available if the following REST API backend feature is enabled:
+///
+/// * `rest-api-actix`
+impl RestResourceProvider for SamlResourceProvider {
+    fn resources(&self) -> Vec<Resource> {
+        // Allowing unused_mut because resources must be mutable if feature `rest-api-actix` is
+        // enabled
+        #[allow(unused_mut)]
+        let mut resources = Vec::new();
+
+        #[cfg(feature = "rest-api-actix")]
+        {
+            resources.append(&mut vec![
+                actix::metadata::make_metadata_route(self.service_provider.clone()),
+                actix::login::make_login_route(self.service_provider.clone()),
+                actix::acs::make_acs_route(
+                    self.service_provider.clone(),
+                    self.session_store.clone(),
+                ),
+                actix::logout::make_logout_route(self.session_store.clone()),
+            ]);
+        }
+
+        resources
+    }
+}
@@ -0,0 +1,182 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Verification of enveloped XML signatures (<https://www.w3.org/TR/xmldsig-core1/>) on SAML
+//! messages.
+//!
+//! Only the algorithms that SAML identity providers commonly use are supported: Exclusive XML
+//! Canonicalization, RSA signatures and SHA-256 or SHA-512 digests. SHA-1 is rejected.
+
+use openssl::hash::{hash, MessageDigest};
+use openssl::memcmp;
+use openssl::pkey::{PKey, Public};
+use openssl::sign::Verifier;
+
+use super::error::SamlResponseError;
+use super::xml::{canonicalize, Element};
+
+pub const DSIG_NAMESPACE: &str = "http://www.w3.org/2000/09/xmldsig#";
+const EXC_C14N_NAMESPACE: &str = "http://www.w3.org/2001/10/xml-exc-c14n#";
+const ENVELOPED_SIGNATURE: &str = "http://www.w3.org/2000/09/xmldsig#enveloped-signature";
+const RSA_SHA256: &str = "http://www.w3.org/2001/04/xmldsig-more#rsa-sha256";
+const RSA_SHA512: &str = "http://www.w3.org/2001/04/xmldsig-more#rsa-sha512";
+const SHA256: &str = "http://www.w3.org/2001/04/xmlenc#sha256";
+const SHA512: &str = "http://www.w3.org/2001/04/xmlenc#sha512";
+
+/// Verifies the enveloped signature of an element, if it has one
+///
+/// The signature must be a direct child of the element and must reference the element itself by
+/// its `ID`, so the verified content is exactly the element that was passed in. Callers must only
+/// trust the content of that element.
+///
+/// Returns `Ok(false)` if the element is not signed and `Ok(true)` if its signature is valid.
+///
+/// # Arguments
+///
+/// * `element` - The signed element
+/// * `public_key` - The public key of the trusted signer
+pub fn verify_enveloped_signature(
+    element: &Element,
+    public_key: &PKey<Public>,
+) -> Result<bool, SamlResponseError> {
+    let mut signatures = element.children_named(DSIG_NAMESPACE, "Signature");
+    let signature = match signatures.next() {
+        Some(signature) => signature,
+        None => return Ok(false),
+    };
+    if signatures.next().is_some() {
+        return Err(invalid("element has more than one signature"));
+    }
+
+    let signed_info = signature
+        .child(DSIG_NAMESPACE, "SignedInfo")
+        .ok_or_else(|| invalid("signature has no SignedInfo"))?;
+
+    let canonicalization_method = signed_info
+        .child(DSIG_NAMESPACE, "CanonicalizationMethod")
+        .ok_or_else(|| invalid("signature has no CanonicalizationMethod"))?;
+    if canonicalization_method.attribute("Algorithm") != Some(EXC_C14N_NAMESPACE) {
+        return Err(invalid("unsupported canonicalization method"));
+    }
+
+    let signature_digest = match signed_info
+        .child(DSIG_NAMESPACE, "SignatureMethod")
+        .and_then(|method| method.attribute("Algorithm"))
+    {
+        Some(RSA_SHA256) => MessageDigest::sha256(),
+        Some(RSA_SHA512) => MessageDigest::sha512(),
+        _ => return Err(invalid("unsupported signature method")),
+    };
+
+    let mut references = signed_info.children_named(DSIG_NAMESPACE, "Reference");
+    let reference = references
+        .next()
+        .ok_or_else(|| invalid("signature has no Reference"))?;
+    if references.next().is_some() {
+        return Err(invalid("signature has more than one Reference"));
+    }
+    let id = element
+        .attribute("ID")
+        .ok_or_else(|| invalid("signed element has no ID"))?;
+    if reference.attribute("URI") != Some(&format!("#{}", id)) {
+        return Err(invalid("signature does not reference the signed element"));
+    }
+
+    let mut enveloped = false;
+    let mut inclusive_prefixes = String::new();
+    if let Some(transforms) = reference.child(DSIG_NAMESPACE, "Transforms") {
+        for transform in transforms.children_named(DSIG_NAMESPACE, "Transform") {
+            match transform.attribute("Algorithm") {
+                Some(ENVELOPED_SIGNATURE) => enveloped = true,
+                Some(EXC_C14N_NAMESPACE) => {
+                    inclusive_prefixes = inclusive_prefix_list(transform);
+                }
+                _ => return Err(invalid("unsupported transform")),
+            }
+        }
+    }
+    if !enveloped {
+        return Err(invalid("signature is not an enveloped signature"));
+    }
+
+    let digest = match reference
+        .child(DSIG_NAMESPACE, "DigestMethod")
+        .and_then(|method| method.attribute("Algorithm"))
+    {
+        Some(SHA256) => MessageDigest::sha256(),
+        Some(SHA512) => MessageDigest::sha512(),
+        _ => return Err(invalid("unsupported digest method")),
+    };
+    let expected_digest = reference
+        .child(DSIG_NAMESPACE, "DigestValue")
+        .map(|value| decode_base64(&value.text()))
+        .ok_or_else(|| invalid("signature has no DigestValue"))??;
+
+    let canonical_element = canonicalize(
+        element,
+        Some(signature),
+        &inclusive_prefixes.split_whitespace().collect::<Vec<_>>(),
+    );
+    let actual_digest =
+        hash(digest, canonical_element.as_bytes()).map_err(|err| internal(Box::new(err)))?;
+    if actual_digest.len() != expected_digest.len() || !memcmp::eq(&actual_digest, &expected_digest)
+    {
+        return Err(invalid("digest of the signed element does not match"));
+    }
+
+    let signature_value = signature
+        .child(DSIG_NAMESPACE, "SignatureValue")
+        .map(|value| decode_base64(&value.text()))
+        .ok_or_else(|| invalid("signature has no SignatureValue"))??;
+    let canonical_signed_info = canonicalize(
+        signed_info,
+        None,
+        &inclusive_prefix_list(canonicalization_method)
+            .split_whitespace()
+            .collect::<Vec<_>>(),
+    );
+
+    let mut verifier =
+        Verifier::new(signature_digest, public_key).map_err(|err| internal(Box::new(err)))?;
+    verifier
+        .update(canonical_signed_info.as_bytes())
+        .map_err(|err| internal(Box::new(err)))?;
+    // OpenSSL reports some malformed signatures as errors rather than as invalid signatures
+    match verifier.verify(&signature_value) {
+        Ok(true) => Ok(true),
+        _ => Err(invalid("signature is not valid")),
+    }
+}
+
+/// Returns the `PrefixList` of a canonicalization method's `InclusiveNamespaces`, if it has one
+fn inclusive_prefix_list(method: &Element) -> String {
+    method
+        .child(EXC_C14N_NAMESPACE, "InclusiveNamespaces")
+        .and_then(|inclusive| inclusive.attribute("PrefixList"))
+        .unwrap_or("")
+        .to_string()
+}
+
+pub(super) fn decode_base64(value: &str) -> Result<Vec<u8>, SamlResponseError> {
+    base64::decode(value.split_whitespace().collect::<String>())
+        .map_err(|err| invalid(&format!("invalid base64: {}", err)))
+}
+
+fn invalid(message: &str) -> SamlResponseError {
+    SamlResponseError::InvalidResponse(message.to_string())
+}
+
+fn internal(err: Box<dyn std::error::Error>) -> SamlResponseError {
+    SamlResponseError::InternalError(crate::error::InternalError::from_source(err))
+}
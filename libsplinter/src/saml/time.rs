@@ -0,0 +1,166 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Conversion between `SystemTime` and the `xs:dateTime` values used by SAML, which are always in
+//! UTC.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Formats a time as an `xs:dateTime` in UTC, such as `2021-03-20T10:15:30Z`
+pub fn format_datetime(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    let (year, month, day) = civil_from_days((seconds / SECONDS_PER_DAY) as i64);
+    let seconds_of_day = seconds % SECONDS_PER_DAY;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds_of_day / 3600,
+        seconds_of_day / 60 % 60,
+        seconds_of_day % 60
+    )
+}
+
+/// Parses an `xs:dateTime` with a time zone, such as `2021-03-20T10:15:30.123Z` or
+/// `2021-03-20T12:15:30+02:00`. Fractional seconds are truncated.
+pub fn parse_datetime(value: &str) -> Option<SystemTime> {
+    let bytes = value.as_bytes();
+    if bytes.len() < 20
+        || bytes[4] != b'-'
+        || bytes[7] != b'-'
+        || bytes[10] != b'T'
+        || bytes[13] != b':'
+        || bytes[16] != b':'
+    {
+        return None;
+    }
+    let number = |range: std::ops::Range<usize>| -> Option<i64> {
+        let digits = value.get(range)?;
+        if digits.bytes().all(|b| b.is_ascii_digit()) {
+            digits.parse().ok()
+        } else {
+            None
+        }
+    };
+    let year = number(0..4)?;
+    let month = number(5..7)?;
+    let day = number(8..10)?;
+    let hour = number(11..13)?;
+    let minute = number(14..16)?;
+    let second = number(17..19)?;
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return None;
+    }
+
+    let mut rest = &value[19..];
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let digits = fraction.bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 {
+            return None;
+        }
+        rest = &fraction[digits..];
+    }
+    let offset = match rest {
+        "Z" => 0,
+        _ if rest.len() == 6 && rest.as_bytes()[3] == b':' => {
+            let sign = match rest.as_bytes()[0] {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
+            };
+            let hours: i64 = rest[1..3].parse().ok()?;
+            let minutes: i64 = rest[4..6].parse().ok()?;
+            sign * (hours * 3600 + minutes * 60)
+        }
+        _ => return None,
+    };
+
+    let seconds = days_from_civil(year, month, day) * SECONDS_PER_DAY as i64
+        + hour * 3600
+        + minute * 60
+        + second
+        - offset;
+    if seconds < 0 {
+        return None;
+    }
+    Some(UNIX_EPOCH + Duration::from_secs(seconds as u64))
+}
+
+// The following two conversions between days since the Unix epoch and proleptic Gregorian
+// dates are from <http://howardhinnant.github.io/date_algorithms.html>.
+
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = if days >= 0 { days } else { days - 146_096 } / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that times are formatted as UTC `xs:dateTime` values and parsed back, including
+    /// values with fractional seconds and time zone offsets.
+    #[test]
+    fn format_and_parse_datetime() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_616_235_330);
+        assert_eq!(format_datetime(time), "2021-03-20T10:15:30Z");
+        assert_eq!(parse_datetime("2021-03-20T10:15:30Z"), Some(time));
+        assert_eq!(parse_datetime("2021-03-20T10:15:30.999Z"), Some(time));
+        assert_eq!(parse_datetime("2021-03-20T12:15:30+02:00"), Some(time));
+        assert_eq!(parse_datetime("2021-03-20T09:45:30-00:30"), Some(time));
+        assert_eq!(format_datetime(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            format_datetime(UNIX_EPOCH + Duration::from_secs(951_782_400)),
+            "2000-02-29T00:00:00Z"
+        );
+
+        assert_eq!(parse_datetime("2021-03-20T10:15:30"), None);
+        assert_eq!(parse_datetime("2021-13-20T10:15:30Z"), None);
+        assert_eq!(parse_datetime("2021-03-20 10:15:30Z"), None);
+        assert_eq!(parse_datetime("2021-03-20T10:15:30.Z"), None);
+    }
+}
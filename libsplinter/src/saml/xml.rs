@@ -0,0 +1,681 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A minimal, non-validating XML parser and an implementation of Exclusive XML Canonicalization
+//! (<https://www.w3.org/TR/xml-exc-c14n/>) for verifying signed SAML messages.
+//!
+//! Only the subset of XML that SAML messages use is supported. Document type declarations are
+//! rejected, so no external entities are ever resolved.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+const XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// An error that occurs when a document is not well-formed or uses unsupported XML features
+#[derive(Debug)]
+pub struct XmlError(String);
+
+impl Error for XmlError {}
+
+impl fmt::Display for XmlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid XML: {}", self.0)
+    }
+}
+
+/// The namespace bindings in scope for an element, by prefix; `None` is the default namespace
+type Namespaces = BTreeMap<Option<String>, String>;
+
+/// A node in the content of an element
+#[derive(Debug)]
+pub enum Node {
+    Element(Element),
+    Text(String),
+    /// A processing instruction, containing the target and data
+    ProcessingInstruction(String),
+}
+
+/// An attribute of an element
+#[derive(Debug)]
+pub struct Attribute {
+    prefix: Option<String>,
+    local_name: String,
+    namespace: Option<String>,
+    value: String,
+}
+
+/// An element, with its namespaces resolved
+#[derive(Debug)]
+pub struct Element {
+    prefix: Option<String>,
+    local_name: String,
+    namespace: Option<String>,
+    attributes: Vec<Attribute>,
+    /// All namespace bindings in scope for this element, including the ones it declares
+    namespaces: Namespaces,
+    children: Vec<Node>,
+}
+
+impl Element {
+    /// Returns whether the element has the given namespace and local name
+    pub fn is(&self, namespace: &str, local_name: &str) -> bool {
+        self.namespace.as_deref() == Some(namespace) && self.local_name == local_name
+    }
+
+    /// Returns the value of the attribute with the given local name and no namespace
+    pub fn attribute(&self, local_name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|attr| attr.namespace.is_none() && attr.local_name == local_name)
+            .map(|attr| attr.value.as_str())
+    }
+
+    /// Returns the child elements of this element
+    pub fn child_elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|node| match node {
+            Node::Element(element) => Some(element),
+            _ => None,
+        })
+    }
+
+    /// Returns the child elements with the given namespace and local name
+    pub fn children_named<'a>(
+        &'a self,
+        namespace: &'a str,
+        local_name: &'a str,
+    ) -> impl Iterator<Item = &'a Element> {
+        self.child_elements()
+            .filter(move |element| element.is(namespace, local_name))
+    }
+
+    /// Returns the first child element with the given namespace and local name
+    pub fn child(&self, namespace: &str, local_name: &str) -> Option<&Element> {
+        self.child_elements()
+            .find(|element| element.is(namespace, local_name))
+    }
+
+    /// Returns the concatenated text content of this element's direct children
+    pub fn text(&self) -> String {
+        self.children
+            .iter()
+            .filter_map(|node| match node {
+                Node::Text(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Parses a document, returning its root element
+pub fn parse(document: &str) -> Result<Element, XmlError> {
+    let mut parser = Parser {
+        input: document.as_bytes(),
+        text: document,
+        position: 0,
+    };
+
+    parser.skip_prolog()?;
+    let mut namespaces = Namespaces::new();
+    namespaces.insert(Some("xml".into()), XML_NAMESPACE.into());
+    let root = parser.parse_element(&namespaces)?;
+    parser.skip_misc()?;
+    if parser.position != parser.input.len() {
+        return Err(parser.error("content after the root element"));
+    }
+    Ok(root)
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    text: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> XmlError {
+        XmlError(format!("{} at byte {}", message, self.position))
+    }
+
+    fn starts_with(&self, prefix: &str) -> bool {
+        self.input[self.position..].starts_with(prefix.as_bytes())
+    }
+
+    fn expect(&mut self, prefix: &str) -> Result<(), XmlError> {
+        if self.starts_with(prefix) {
+            self.position += prefix.len();
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", prefix)))
+        }
+    }
+
+    fn skip_whitespace(&mut self) -> bool {
+        let start = self.position;
+        while self.position < self.input.len()
+            && matches!(self.input[self.position], b' ' | b'\t' | b'\r' | b'\n')
+        {
+            self.position += 1;
+        }
+        self.position > start
+    }
+
+    /// Returns the text up to the given delimiter and moves past the delimiter
+    fn take_until(&mut self, delimiter: &str) -> Result<&'a str, XmlError> {
+        match self.text[self.position..].find(delimiter) {
+            Some(length) => {
+                let taken = &self.text[self.position..self.position + length];
+                self.position += length + delimiter.len();
+                Ok(taken)
+            }
+            None => Err(self.error(&format!("expected '{}'", delimiter))),
+        }
+    }
+
+    /// Skips the XML declaration, comments, processing instructions and whitespace before the
+    /// root element
+    fn skip_prolog(&mut self) -> Result<(), XmlError> {
+        // Byte order mark
+        if self.starts_with("\u{feff}") {
+            self.position += "\u{feff}".len();
+        }
+        if self.starts_with("<?xml") {
+            self.take_until("?>")?;
+        }
+        self.skip_misc()?;
+        if self.starts_with("<!DOCTYPE") {
+            return Err(self.error("document type declarations are not supported"));
+        }
+        Ok(())
+    }
+
+    fn skip_misc(&mut self) -> Result<(), XmlError> {
+        loop {
+            self.skip_whitespace();
+            if self.starts_with("<!--") {
+                self.take_until("-->")?;
+            } else if self.starts_with("<?") {
+                self.take_until("?>")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn parse_name(&mut self) -> Result<&'a str, XmlError> {
+        let start = self.position;
+        while self.position < self.input.len() {
+            match self.input[self.position] {
+                b' ' | b'\t' | b'\r' | b'\n' | b'/' | b'>' | b'=' | b'<' | b'"' | b'\'' => break,
+                _ => self.position += 1,
+            }
+        }
+        if self.position == start {
+            return Err(self.error("expected a name"));
+        }
+        Ok(&self.text[start..self.position])
+    }
+
+    fn parse_element(&mut self, parent_namespaces: &Namespaces) -> Result<Element, XmlError> {
+        self.expect("<")?;
+        let qualified_name = self.parse_name()?;
+
+        let mut raw_attributes = vec![];
+        let mut namespaces = parent_namespaces.clone();
+        let empty = loop {
+            let had_whitespace = self.skip_whitespace();
+            if self.starts_with("/>") {
+                self.position += 2;
+                break true;
+            }
+            if self.starts_with(">") {
+                self.position += 1;
+                break false;
+            }
+            if !had_whitespace {
+                return Err(self.error("expected whitespace before attribute"));
+            }
+
+            let name = self.parse_name()?;
+            self.skip_whitespace();
+            self.expect("=")?;
+            self.skip_whitespace();
+            let value = self.parse_attribute_value()?;
+
+            if name == "xmlns" {
+                if value.is_empty() {
+                    namespaces.remove(&None);
+                } else {
+                    namespaces.insert(None, value);
+                }
+            } else if let Some(prefix) = name.strip_prefix("xmlns:") {
+                if value.is_empty() {
+                    return Err(self.error("prefixed namespaces cannot be undeclared"));
+                }
+                namespaces.insert(Some(prefix.to_string()), value);
+            } else {
+                raw_attributes.push((name, value));
+            }
+        };
+
+        let (prefix, local_name) = split_name(qualified_name);
+        let namespace = namespaces.get(&prefix).cloned();
+        if prefix.is_some() && namespace.is_none() {
+            return Err(self.error(&format!("undeclared prefix in '{}'", qualified_name)));
+        }
+
+        let mut attributes = Vec::with_capacity(raw_attributes.len());
+        for (name, value) in raw_attributes {
+            let (prefix, local_name) = split_name(name);
+            // Unprefixed attributes are never in a namespace
+            let namespace = match &prefix {
+                Some(_) => Some(namespaces.get(&prefix).cloned().ok_or_else(|| {
+                    self.error(&format!("undeclared prefix in attribute '{}'", name))
+                })?),
+                None => None,
+            };
+            if attributes.iter().any(|attr: &Attribute| {
+                attr.namespace == namespace && attr.local_name == local_name
+            }) {
+                return Err(self.error(&format!("duplicate attribute '{}'", name)));
+            }
+            attributes.push(Attribute {
+                prefix,
+                local_name: local_name.to_string(),
+                namespace,
+                value,
+            });
+        }
+
+        let mut element = Element {
+            prefix,
+            local_name: local_name.to_string(),
+            namespace,
+            attributes,
+            namespaces,
+            children: vec![],
+        };
+
+        if !empty {
+            self.parse_content(&mut element)?;
+            self.expect("</")?;
+            if self.parse_name()? != qualified_name {
+                return Err(self.error(&format!("mismatched end tag for '{}'", qualified_name)));
+            }
+            self.skip_whitespace();
+            self.expect(">")?;
+        }
+
+        Ok(element)
+    }
+
+    fn parse_content(&mut self, element: &mut Element) -> Result<(), XmlError> {
+        let mut text = String::new();
+        loop {
+            if self.position >= self.input.len() {
+                return Err(self.error("unexpected end of document"));
+            }
+            if self.starts_with("</") {
+                break;
+            } else if self.starts_with("<!--") {
+                self.take_until("-->")?;
+            } else if self.starts_with("<![CDATA[") {
+                self.position += "<![CDATA[".len();
+                text.push_str(&normalize_line_endings(self.take_until("]]>")?));
+            } else if self.starts_with("<?") {
+                self.position += 2;
+                let instruction = self.take_until("?>")?;
+                push_text(&mut element.children, &mut text);
+                element
+                    .children
+                    .push(Node::ProcessingInstruction(instruction.to_string()));
+            } else if self.starts_with("<!") {
+                return Err(self.error("unsupported markup declaration"));
+            } else if self.starts_with("<") {
+                push_text(&mut element.children, &mut text);
+                let child = self.parse_element(&element.namespaces)?;
+                element.children.push(Node::Element(child));
+            } else {
+                let start = self.position;
+                while self.position < self.input.len() && self.input[self.position] != b'<' {
+                    self.position += 1;
+                }
+                let raw = &self.text[start..self.position];
+                text.push_str(&self.unescape(&normalize_line_endings(raw))?);
+            }
+        }
+        push_text(&mut element.children, &mut text);
+        Ok(())
+    }
+
+    fn parse_attribute_value(&mut self) -> Result<String, XmlError> {
+        let quote = if self.starts_with("\"") {
+            "\""
+        } else if self.starts_with("'") {
+            "'"
+        } else {
+            return Err(self.error("expected a quoted attribute value"));
+        };
+        self.position += 1;
+        let raw = self.take_until(quote)?;
+        if raw.contains('<') {
+            return Err(self.error("'<' is not allowed in attribute values"));
+        }
+        // Literal whitespace is normalized to spaces; character references are not
+        let normalized = normalize_line_endings(raw).replace(|c| c == '\t' || c == '\n', " ");
+        self.unescape(&normalized)
+    }
+
+    fn unescape(&self, raw: &str) -> Result<String, XmlError> {
+        let mut unescaped = String::with_capacity(raw.len());
+        let mut rest = raw;
+        while let Some(start) = rest.find('&') {
+            unescaped.push_str(&rest[..start]);
+            let end = rest[start..]
+                .find(';')
+                .ok_or_else(|| self.error("unterminated reference"))?;
+            let reference = &rest[start + 1..start + end];
+            match reference {
+                "lt" => unescaped.push('<'),
+                "gt" => unescaped.push('>'),
+                "amp" => unescaped.push('&'),
+                "apos" => unescaped.push('\''),
+                "quot" => unescaped.push('"'),
+                _ => {
+                    let code = if let Some(hex) = reference.strip_prefix("#x") {
+                        u32::from_str_radix(hex, 16).ok()
+                    } else if let Some(decimal) = reference.strip_prefix('#') {
+                        decimal.parse::<u32>().ok()
+                    } else {
+                        None
+                    };
+                    unescaped.push(code.and_then(std::char::from_u32).ok_or_else(|| {
+                        self.error(&format!("unsupported reference '&{};'", reference))
+                    })?);
+                }
+            }
+            rest = &rest[start + end + 1..];
+        }
+        unescaped.push_str(rest);
+        Ok(unescaped)
+    }
+}
+
+fn split_name(name: &str) -> (Option<String>, &str) {
+    match name.find(':') {
+        Some(index) => (Some(name[..index].to_string()), &name[index + 1..]),
+        None => (None, name),
+    }
+}
+
+fn normalize_line_endings(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+fn push_text(children: &mut Vec<Node>, text: &mut String) {
+    if !text.is_empty() {
+        children.push(Node::Text(std::mem::take(text)));
+    }
+}
+
+/// Canonicalizes an element with Exclusive XML Canonicalization, omitting comments
+///
+/// # Arguments
+///
+/// * `element` - The element to canonicalize, along with its descendants
+/// * `excluded` - A descendant element to leave out, as the enveloped signature transform does
+/// * `inclusive_prefixes` - Prefixes that are treated as in inclusive canonicalization; `#default`
+///   stands for the default namespace
+pub fn canonicalize(
+    element: &Element,
+    excluded: Option<&Element>,
+    inclusive_prefixes: &[&str],
+) -> String {
+    let inclusive_prefixes = inclusive_prefixes
+        .iter()
+        .map(|prefix| match *prefix {
+            "#default" => None,
+            prefix => Some(prefix.to_string()),
+        })
+        .collect::<Vec<_>>();
+    let mut output = String::new();
+    canonicalize_element(
+        element,
+        excluded,
+        &inclusive_prefixes,
+        &Namespaces::new(),
+        &mut output,
+    );
+    output
+}
+
+fn canonicalize_element(
+    element: &Element,
+    excluded: Option<&Element>,
+    inclusive_prefixes: &[Option<String>],
+    rendered: &Namespaces,
+    output: &mut String,
+) {
+    // Namespaces that are visibly utilized by the element or its attributes
+    let mut utilized = vec![element.prefix.clone()];
+    utilized.extend(
+        element
+            .attributes
+            .iter()
+            .filter(|attr| attr.prefix.is_some())
+            .map(|attr| attr.prefix.clone()),
+    );
+    utilized.extend(inclusive_prefixes.iter().cloned());
+
+    let mut declarations = Namespaces::new();
+    for prefix in utilized {
+        if prefix.as_deref() == Some("xml") {
+            continue;
+        }
+        match element.namespaces.get(&prefix) {
+            Some(uri) => {
+                if rendered.get(&prefix) != Some(uri) {
+                    declarations.insert(prefix, uri.clone());
+                }
+            }
+            // An element in no namespace must undeclare a default namespace rendered by an
+            // ancestor
+            None if prefix.is_none()
+                && rendered.get(&None).map_or(false, |uri| !uri.is_empty()) =>
+            {
+                declarations.insert(None, String::new());
+            }
+            None => (),
+        }
+    }
+
+    output.push('<');
+    push_qualified_name(output, &element.prefix, &element.local_name);
+    // Namespace declarations sort by prefix, with the default namespace first
+    for (prefix, uri) in &declarations {
+        match prefix {
+            Some(prefix) => {
+                output.push_str(" xmlns:");
+                output.push_str(prefix);
+            }
+            None => output.push_str(" xmlns"),
+        }
+        output.push_str("=\"");
+        escape_attribute_value(output, uri);
+        output.push('"');
+    }
+
+    // Attributes sort by namespace URI then local name, with unqualified attributes first
+    let mut attributes = element.attributes.iter().collect::<Vec<_>>();
+    attributes.sort_by(|a, b| {
+        (a.namespace.as_deref().unwrap_or(""), &a.local_name)
+            .cmp(&(b.namespace.as_deref().unwrap_or(""), &b.local_name))
+    });
+    for attr in attributes {
+        output.push(' ');
+        push_qualified_name(output, &attr.prefix, &attr.local_name);
+        output.push_str("=\"");
+        escape_attribute_value(output, &attr.value);
+        output.push('"');
+    }
+    output.push('>');
+
+    let mut rendered = rendered.clone();
+    rendered.extend(declarations);
+
+    for child in &element.children {
+        match child {
+            Node::Element(child) => {
+                if excluded.map_or(false, |excluded| std::ptr::eq(child, excluded)) {
+                    continue;
+                }
+                canonicalize_element(child, excluded, inclusive_prefixes, &rendered, output);
+            }
+            Node::Text(text) => escape_text(output, text),
+            Node::ProcessingInstruction(instruction) => {
+                output.push_str("<?");
+                output.push_str(instruction);
+                output.push_str("?>");
+            }
+        }
+    }
+
+    output.push_str("</");
+    push_qualified_name(output, &element.prefix, &element.local_name);
+    output.push('>');
+}
+
+fn push_qualified_name(output: &mut String, prefix: &Option<String>, local_name: &str) {
+    if let Some(prefix) = prefix {
+        output.push_str(prefix);
+        output.push(':');
+    }
+    output.push_str(local_name);
+}
+
+fn escape_text(output: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '\r' => output.push_str("&#xD;"),
+            c => output.push(c),
+        }
+    }
+}
+
+fn escape_attribute_value(output: &mut String, value: &str) {
+    for c in value.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '"' => output.push_str("&quot;"),
+            '\t' => output.push_str("&#x9;"),
+            '\n' => output.push_str("&#xA;"),
+            '\r' => output.push_str("&#xD;"),
+            c => output.push(c),
+        }
+    }
+}
+
+/// Escapes text for use in an element's content or a double-quoted attribute value
+pub fn escape(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    escape_attribute_value(&mut output, text);
+    output.replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that exclusive canonicalization only renders namespaces where they are visibly
+    /// utilized, sorts attributes, and normalizes the document's content.
+    ///
+    /// 1. Parse a document with an unused namespace declaration, unsorted attributes, an empty
+    ///    element, entity and character references, CDATA, and a comment
+    /// 2. Verify the canonical form of the document element
+    /// 3. Verify the canonical form of the child element, which must declare the namespaces it
+    ///    inherited
+    #[test]
+    fn canonicalize_exclusive() {
+        let root = parse(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <a:root xmlns:a=\"urn:a\" xmlns:b=\"urn:b\" xmlns:unused=\"urn:u\">\r\n  \
+             <a:child z=\"1\" b:y=\"2\" a='&lt;\"'><empty/>text &amp; &#x41;\
+             <![CDATA[<cdata>]]><!-- comment --></a:child>\n\
+             </a:root>",
+        )
+        .expect("Failed to parse document");
+
+        assert_eq!(
+            canonicalize(&root, None, &[]),
+            "<a:root xmlns:a=\"urn:a\">\n  \
+             <a:child xmlns:b=\"urn:b\" a=\"&lt;&quot;\" z=\"1\" b:y=\"2\"><empty></empty>\
+             text &amp; A&lt;cdata&gt;</a:child>\n\
+             </a:root>"
+        );
+
+        let child = root.child("urn:a", "child").expect("Child not found");
+        assert_eq!(
+            canonicalize(child, None, &[]),
+            "<a:child xmlns:a=\"urn:a\" xmlns:b=\"urn:b\" a=\"&lt;&quot;\" z=\"1\" b:y=\"2\">\
+             <empty></empty>text &amp; A&lt;cdata&gt;</a:child>"
+        );
+    }
+
+    /// Verify that exclusive canonicalization undeclares the default namespace only when an
+    /// ancestor has rendered one.
+    #[test]
+    fn canonicalize_default_namespace() {
+        let root = parse("<root xmlns=\"urn:d\"><child xmlns=\"\"><x/></child></root>")
+            .expect("Failed to parse document");
+
+        assert_eq!(
+            canonicalize(&root, None, &[]),
+            "<root xmlns=\"urn:d\"><child xmlns=\"\"><x></x></child></root>"
+        );
+
+        let child = root.child_elements().next().expect("Child not found");
+        assert_eq!(canonicalize(child, None, &[]), "<child><x></x></child>");
+    }
+
+    /// Verify that canonicalization leaves out the excluded element, as the enveloped signature
+    /// transform requires, and renders inclusive prefixes at the apex.
+    #[test]
+    fn canonicalize_excluded_and_inclusive_prefixes() {
+        let root = parse("<r xmlns:p=\"urn:p\" xmlns:q=\"urn:q\"><s/><p:x/></r>")
+            .expect("Failed to parse document");
+        let excluded = root.child_elements().next().expect("Child not found");
+
+        assert_eq!(
+            canonicalize(&root, Some(excluded), &["q"]),
+            "<r xmlns:q=\"urn:q\"><p:x xmlns:p=\"urn:p\"></p:x></r>"
+        );
+    }
+
+    /// Verify that documents with document type declarations, undeclared prefixes, duplicate
+    /// attributes, or mismatched tags are rejected.
+    #[test]
+    fn parse_invalid_documents() {
+        assert!(parse("<!DOCTYPE r [<!ENTITY e \"x\">]><r>&e;</r>").is_err());
+        assert!(parse("<p:r/>").is_err());
+        assert!(parse("<r a=\"1\" a=\"2\"/>").is_err());
+        assert!(parse("<r xmlns:p=\"urn:p\" xmlns:q=\"urn:p\" p:a=\"1\" q:a=\"2\"/>").is_err());
+        assert!(parse("<r></s>").is_err());
+        assert!(parse("<r/><s/>").is_err());
+    }
+}
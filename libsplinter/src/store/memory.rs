@@ -27,6 +27,8 @@ use crate::biome::{
 };
 #[cfg(feature = "biome-key-management")]
 use crate::biome::{KeyStore, MemoryKeyStore};
+#[cfg(feature = "biome-notifications")]
+use crate::biome::{MemoryNotificationStore, NotificationStore};
 #[cfg(feature = "biome-profile")]
 use crate::biome::{MemoryUserProfileStore, UserProfileStore};
 use crate::error::InternalError;
@@ -48,6 +50,8 @@ pub struct MemoryStoreFactory {
     biome_oauth_user_session_store: MemoryOAuthUserSessionStore,
     #[cfg(feature = "oauth")]
     inflight_request_store: MemoryInflightOAuthRequestStore,
+    #[cfg(feature = "biome-notifications")]
+    biome_notification_store: MemoryNotificationStore,
     #[cfg(feature = "biome-profile")]
     biome_profile_store: MemoryUserProfileStore,
     // to be used for sqlite in memory implementations
@@ -70,6 +74,9 @@ impl MemoryStoreFactory {
         #[cfg(feature = "oauth")]
         let inflight_request_store = MemoryInflightOAuthRequestStore::new();
 
+        #[cfg(feature = "biome-notifications")]
+        let biome_notification_store = MemoryNotificationStore::new();
+
         #[cfg(feature = "biome-profile")]
         let biome_profile_store = MemoryUserProfileStore::new();
 
@@ -98,6 +105,8 @@ impl MemoryStoreFactory {
             biome_oauth_user_session_store,
             #[cfg(feature = "oauth")]
             inflight_request_store,
+            #[cfg(feature = "biome-notifications")]
+            biome_notification_store,
             #[cfg(feature = "biome-profile")]
             biome_profile_store,
             pool,
@@ -153,8 +162,37 @@ impl StoreFactory for MemoryStoreFactory {
         Box::new(crate::rest_api::auth::authorization::rbac::store::DieselRoleBasedAuthorizationStore::new(self.pool.clone()))
     }
 
+    #[cfg(feature = "biome-notifications")]
+    fn get_biome_notification_store(&self) -> Box<dyn NotificationStore> {
+        Box::new(self.biome_notification_store.clone())
+    }
+
     #[cfg(feature = "biome-profile")]
     fn get_biome_user_profile_store(&self) -> Box<dyn UserProfileStore> {
         Box::new(self.biome_profile_store.clone())
     }
+    #[cfg(feature = "api-keys")]
+    fn get_api_key_store(&self) -> Box<dyn crate::api_keys::store::ApiKeyStore> {
+        Box::new(crate::api_keys::store::DieselApiKeyStore::new(
+            self.pool.clone(),
+        ))
+    }
+    #[cfg(feature = "authorization-handler-maintenance")]
+    fn get_maintenance_mode_store(
+        &self,
+    ) -> Box<dyn crate::rest_api::auth::authorization::maintenance::store::MaintenanceModeStore>
+    {
+        Box::new(
+            crate::rest_api::auth::authorization::maintenance::store::DieselMaintenanceModeStore::new(
+                self.pool.clone(),
+            ),
+        )
+    }
+
+    #[cfg(feature = "authorization-audit")]
+    fn get_audit_sink(&self) -> Box<dyn crate::rest_api::auth::authorization::audit::AuditSink> {
+        Box::new(
+            crate::rest_api::auth::authorization::audit::DieselAuditSink::new(self.pool.clone()),
+        )
+    }
 }
@@ -66,8 +66,22 @@ pub trait StoreFactory {
         &self,
     ) -> Box<dyn crate::rest_api::auth::authorization::rbac::store::RoleBasedAuthorizationStore>;
 
+    #[cfg(feature = "biome-notifications")]
+    fn get_biome_notification_store(&self) -> Box<dyn crate::biome::NotificationStore>;
+
     #[cfg(feature = "biome-profile")]
     fn get_biome_user_profile_store(&self) -> Box<dyn crate::biome::UserProfileStore>;
+
+    #[cfg(feature = "api-keys")]
+    fn get_api_key_store(&self) -> Box<dyn crate::api_keys::store::ApiKeyStore>;
+
+    #[cfg(feature = "authorization-handler-maintenance")]
+    fn get_maintenance_mode_store(
+        &self,
+    ) -> Box<dyn crate::rest_api::auth::authorization::maintenance::store::MaintenanceModeStore>;
+
+    #[cfg(feature = "authorization-audit")]
+    fn get_audit_sink(&self) -> Box<dyn crate::rest_api::auth::authorization::audit::AuditSink>;
 }
 
 /// Creates a `StoreFactory` backed by the given connection
@@ -92,10 +92,41 @@ impl StoreFactory for SqliteStoreFactory {
         )
     }
 
+    #[cfg(feature = "biome-notifications")]
+    fn get_biome_notification_store(&self) -> Box<dyn crate::biome::NotificationStore> {
+        Box::new(crate::biome::DieselNotificationStore::new(
+            self.pool.clone(),
+        ))
+    }
+
     #[cfg(feature = "biome-profile")]
     fn get_biome_user_profile_store(&self) -> Box<dyn crate::biome::UserProfileStore> {
         Box::new(crate::biome::DieselUserProfileStore::new(self.pool.clone()))
     }
+    #[cfg(feature = "api-keys")]
+    fn get_api_key_store(&self) -> Box<dyn crate::api_keys::store::ApiKeyStore> {
+        Box::new(crate::api_keys::store::DieselApiKeyStore::new(
+            self.pool.clone(),
+        ))
+    }
+    #[cfg(feature = "authorization-handler-maintenance")]
+    fn get_maintenance_mode_store(
+        &self,
+    ) -> Box<dyn crate::rest_api::auth::authorization::maintenance::store::MaintenanceModeStore>
+    {
+        Box::new(
+            crate::rest_api::auth::authorization::maintenance::store::DieselMaintenanceModeStore::new(
+                self.pool.clone(),
+            ),
+        )
+    }
+
+    #[cfg(feature = "authorization-audit")]
+    fn get_audit_sink(&self) -> Box<dyn crate::rest_api::auth::authorization::audit::AuditSink> {
+        Box::new(
+            crate::rest_api::auth::authorization::audit::DieselAuditSink::new(self.pool.clone()),
+        )
+    }
 }
 
 #[derive(Default, Debug)]
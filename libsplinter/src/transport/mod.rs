@@ -39,8 +39,12 @@ mod error;
 pub mod inproc;
 pub(crate) mod matrix;
 pub mod multi;
+#[cfg(feature = "quic-transport")]
+pub mod quic;
 #[deprecated(since = "0.3.14", note = "please use splinter::transport::socket")]
 pub mod raw;
+#[cfg(feature = "endpoint-resolution")]
+pub mod resolver;
 pub mod socket;
 pub mod tls;
 #[cfg(feature = "ws-transport")]
@@ -11,10 +11,17 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+#[cfg(feature = "endpoint-resolution")]
+use super::resolver::EndpointResolver;
 use super::{ConnectError, Connection, ListenError, Listener, Transport};
 
 type SendableTransport = Box<dyn Transport + Send>;
 
+/// The maximum number of times an endpoint may be resolved while connecting, which bounds the
+/// resolution of logical endpoints that resolve to other logical endpoints.
+#[cfg(feature = "endpoint-resolution")]
+const MAX_RESOLUTION_DEPTH: usize = 4;
+
 /// A MultiTransport holds a collection of transports, referenced by protocol.
 ///
 /// Endpoints and bind strings are specified using standard url-style strings.  For example,
@@ -22,29 +29,82 @@ type SendableTransport = Box<dyn Transport + Send>;
 ///
 /// Endpoints and bind strings provided without a protocol will use the provided default transport
 /// protocol type.
+///
+/// If the `endpoint-resolution` feature is enabled, logical endpoints accepted by one of the
+/// transport's resolvers are resolved each time they are connected to; each of the resolved
+/// endpoints is tried in order until a connection is made.
 pub struct MultiTransport {
     transports: Vec<SendableTransport>,
+    #[cfg(feature = "endpoint-resolution")]
+    resolvers: Vec<Box<dyn EndpointResolver>>,
 }
 
 impl MultiTransport {
     /// Construct a new MultiTransport
     pub fn new(transports: Vec<SendableTransport>) -> Self {
-        Self { transports }
+        Self {
+            transports,
+            #[cfg(feature = "endpoint-resolution")]
+            resolvers: vec![],
+        }
     }
 
     pub fn add_transport(&mut self, transport: SendableTransport) {
         self.transports.push(transport)
     }
-}
 
-impl Transport for MultiTransport {
-    fn accepts(&self, address: &str) -> bool {
-        self.transports
+    /// Adds a resolver for logical endpoints.
+    #[cfg(feature = "endpoint-resolution")]
+    pub fn add_resolver(&mut self, resolver: Box<dyn EndpointResolver>) {
+        self.resolvers.push(resolver)
+    }
+
+    #[cfg(feature = "endpoint-resolution")]
+    fn connect_resolved(
+        &mut self,
+        endpoint: &str,
+        depth: usize,
+    ) -> Result<Box<dyn Connection>, ConnectError> {
+        let resolver = match self
+            .resolvers
             .iter()
-            .any(|transport| transport.accepts(address))
+            .find(|resolver| resolver.accepts(endpoint))
+        {
+            Some(resolver) => resolver,
+            None => return self.connect_transport(endpoint),
+        };
+
+        if depth >= MAX_RESOLUTION_DEPTH {
+            return Err(ConnectError::ProtocolError(format!(
+                "Unable to resolve {}: too many levels of resolution",
+                endpoint
+            )));
+        }
+
+        let resolved = resolver.resolve(endpoint)?;
+        debug!("Resolved {} to {:?}", endpoint, resolved);
+
+        let mut last_err = ConnectError::ProtocolError(format!(
+            "Unable to resolve {}: no endpoints found",
+            endpoint
+        ));
+        for resolved_endpoint in resolved {
+            match self.connect_resolved(&resolved_endpoint, depth + 1) {
+                Ok(connection) => return Ok(connection),
+                Err(err) => {
+                    debug!(
+                        "Unable to connect to {} (resolved from {}): {}",
+                        resolved_endpoint, endpoint, err
+                    );
+                    last_err = err;
+                }
+            }
+        }
+
+        Err(last_err)
     }
 
-    fn connect(&mut self, endpoint: &str) -> Result<Box<dyn Connection>, ConnectError> {
+    fn connect_transport(&mut self, endpoint: &str) -> Result<Box<dyn Connection>, ConnectError> {
         self.transports
             .iter_mut()
             .find(|transport| transport.accepts(endpoint))
@@ -53,6 +113,36 @@ impl Transport for MultiTransport {
             })
             .and_then(|transport| transport.connect(endpoint))
     }
+}
+
+impl Transport for MultiTransport {
+    fn accepts(&self, address: &str) -> bool {
+        #[cfg(feature = "endpoint-resolution")]
+        {
+            if self
+                .resolvers
+                .iter()
+                .any(|resolver| resolver.accepts(address))
+            {
+                return true;
+            }
+        }
+
+        self.transports
+            .iter()
+            .any(|transport| transport.accepts(address))
+    }
+
+    fn connect(&mut self, endpoint: &str) -> Result<Box<dyn Connection>, ConnectError> {
+        #[cfg(feature = "endpoint-resolution")]
+        {
+            self.connect_resolved(endpoint, 0)
+        }
+        #[cfg(not(feature = "endpoint-resolution"))]
+        {
+            self.connect_transport(endpoint)
+        }
+    }
 
     fn listen(&mut self, bind: &str) -> Result<Box<dyn Listener>, ListenError> {
         self.transports
@@ -70,6 +160,8 @@ mod tests {
     use std::time::Duration;
 
     use super::*;
+    #[cfg(feature = "endpoint-resolution")]
+    use crate::transport::inproc::InprocTransport;
     use crate::transport::{
         socket, socket::tests::create_test_tls_transport, tests, RecvError, SendError,
     };
@@ -154,6 +246,40 @@ mod tests {
         };
     }
 
+    /// Verify that a logical endpoint is resolved when connecting, and that the resolved
+    /// endpoints are tried in order until a connection is made.
+    #[cfg(feature = "endpoint-resolution")]
+    #[test]
+    fn test_connect_resolved_endpoint() {
+        struct TestResolver;
+
+        impl EndpointResolver for TestResolver {
+            fn accepts(&self, endpoint: &str) -> bool {
+                endpoint.starts_with("test://")
+            }
+
+            fn resolve(&self, _endpoint: &str) -> Result<Vec<String>, ConnectError> {
+                Ok(vec![
+                    "inproc://unavailable".to_string(),
+                    "inproc://available".to_string(),
+                ])
+            }
+        }
+
+        let mut inproc_transport = InprocTransport::default();
+        let mut listener = assert_ok!(inproc_transport.listen("inproc://available"));
+
+        let mut transport = MultiTransport::new(vec![Box::new(inproc_transport)]);
+        assert!(!transport.accepts("test://node"));
+
+        transport.add_resolver(Box::new(TestResolver));
+        assert!(transport.accepts("test://node"));
+
+        let connection = assert_ok!(transport.connect("test://node"));
+        assert_eq!("available", connection.remote_endpoint());
+        assert_ok!(listener.accept());
+    }
+
     /// Test that an outbound connection is properly made when using a multi-transport with tls as
     /// an outbound-only transport.
     #[cfg(not(unix))]
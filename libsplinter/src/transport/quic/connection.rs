@@ -0,0 +1,296 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use mio::{Evented, Poll, PollOpt, Ready, Registration, SetReadiness, Token};
+
+use crate::transport::{Connection, DisconnectError, RecvError, SendError};
+
+use super::stream::{decode_frames, encode_frame, lane_stream_id, StreamSelector};
+
+/// The maximum number of bytes that may be queued on a single lane before sends on that lane
+/// return `SendError::WouldBlock`.
+const MAX_PENDING_LANE_BYTES: usize = 16 * 1024 * 1024;
+
+/// The state of a single QUIC connection, shared between the `QuicConnection` handle and the
+/// driver thread which owns the UDP socket.
+pub(super) struct ConnectionState {
+    pub(super) conn: quiche::Connection,
+    /// The peer's address. This is only updated once the connection has validated a new path to
+    /// the peer, which allows a peer to migrate (for example, after a NAT rebinding) without
+    /// interrupting the connection.
+    pub(super) peer_addr: SocketAddr,
+    /// An address the peer has migrated to, which is waiting for path validation.
+    pub(super) pending_peer_addr: Option<SocketAddr>,
+    pub(super) is_server: bool,
+    /// Framed bytes waiting for stream capacity, keyed by stream ID.
+    pending: HashMap<u64, VecDeque<u8>>,
+    /// Partially received frames, keyed by stream ID.
+    partial: HashMap<u64, Vec<u8>>,
+    /// Complete messages that have not yet been returned by `recv`.
+    messages: VecDeque<Vec<u8>>,
+    max_message_size: usize,
+    error: Option<String>,
+    set_readiness: SetReadiness,
+}
+
+impl ConnectionState {
+    pub(super) fn new(
+        conn: quiche::Connection,
+        peer_addr: SocketAddr,
+        is_server: bool,
+        max_message_size: usize,
+        set_readiness: SetReadiness,
+    ) -> Self {
+        ConnectionState {
+            conn,
+            peer_addr,
+            pending_peer_addr: None,
+            is_server,
+            pending: HashMap::new(),
+            partial: HashMap::new(),
+            messages: VecDeque::new(),
+            max_message_size,
+            error: None,
+            set_readiness,
+        }
+    }
+
+    /// Read all readable streams, moving complete messages to the message queue.
+    pub(super) fn read_streams(&mut self, buf: &mut [u8]) {
+        let readable: Vec<u64> = self.conn.readable().collect();
+        for stream_id in readable {
+            loop {
+                match self.conn.stream_recv(stream_id, buf) {
+                    Ok((len, _fin)) => self
+                        .partial
+                        .entry(stream_id)
+                        .or_insert_with(Vec::new)
+                        .extend_from_slice(&buf[..len]),
+                    Err(quiche::Error::Done) => break,
+                    Err(err) => {
+                        debug!("Unable to read QUIC stream {}: {}", stream_id, err);
+                        break;
+                    }
+                }
+            }
+
+            if let Some(partial) = self.partial.get_mut(&stream_id) {
+                match decode_frames(partial, self.max_message_size) {
+                    Ok(messages) => self.messages.extend(messages),
+                    Err(err) => {
+                        self.error = Some(err.clone());
+                        let _ = self.conn.close(true, 0x1, err.as_bytes());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Write as much of the pending data as the streams' flow control allows.
+    pub(super) fn flush_streams(&mut self) {
+        let conn = &mut self.conn;
+        for (stream_id, pending) in self.pending.iter_mut() {
+            while !pending.is_empty() {
+                let (front, _) = pending.as_slices();
+                match conn.stream_send(*stream_id, front, false) {
+                    Ok(0) | Err(quiche::Error::Done) => break,
+                    Ok(written) => {
+                        pending.drain(..written);
+                    }
+                    Err(err) => {
+                        debug!("Unable to write QUIC stream {}: {}", stream_id, err);
+                        break;
+                    }
+                }
+            }
+        }
+        self.pending.retain(|_, pending| !pending.is_empty());
+    }
+
+    /// Update the readiness of the connection's `Evented`.
+    pub(super) fn update_readiness(&self) {
+        let mut readiness = Ready::empty();
+        if !self.messages.is_empty() || self.is_closed() {
+            readiness |= Ready::readable();
+        }
+        if self.conn.is_established() && !self.is_closed() {
+            readiness |= Ready::writable();
+        }
+        if let Err(err) = self.set_readiness.set_readiness(readiness) {
+            error!("Unable to set QUIC connection readiness: {}", err);
+        }
+    }
+
+    pub(super) fn is_closed(&self) -> bool {
+        self.conn.is_closed() || self.error.is_some()
+    }
+
+    fn queue(&mut self, lane: u64, message: &[u8]) -> Result<(), SendError> {
+        let stream_id = lane_stream_id(lane, self.is_server);
+        let pending = self.pending.entry(stream_id).or_insert_with(VecDeque::new);
+        if pending.len() > MAX_PENDING_LANE_BYTES {
+            return Err(SendError::WouldBlock);
+        }
+        pending.extend(encode_frame(message));
+        Ok(())
+    }
+}
+
+/// Wakes a driver thread after a connection has queued data or been closed.
+#[derive(Clone)]
+pub(super) struct Waker {
+    set_readiness: SetReadiness,
+}
+
+impl Waker {
+    pub(super) fn new(set_readiness: SetReadiness) -> Self {
+        Waker { set_readiness }
+    }
+
+    pub(super) fn wake(&self) {
+        if let Err(err) = self.set_readiness.set_readiness(Ready::readable()) {
+            error!("Unable to wake QUIC driver: {}", err);
+        }
+    }
+}
+
+/// A QUIC connection.
+///
+/// The packets for the connection are sent and received by a driver thread that owns the UDP
+/// socket; the connection itself only queues outgoing messages and dequeues incoming messages.
+pub(super) struct QuicConnection {
+    state: Arc<Mutex<ConnectionState>>,
+    registration: Registration,
+    waker: Waker,
+    selector: Arc<dyn StreamSelector>,
+    lanes: u64,
+    remote_endpoint: String,
+    local_endpoint: String,
+}
+
+impl QuicConnection {
+    pub(super) fn new(
+        state: Arc<Mutex<ConnectionState>>,
+        registration: Registration,
+        waker: Waker,
+        selector: Arc<dyn StreamSelector>,
+        lanes: u64,
+        remote_endpoint: String,
+        local_endpoint: String,
+    ) -> Self {
+        QuicConnection {
+            state,
+            registration,
+            waker,
+            selector,
+            lanes,
+            remote_endpoint,
+            local_endpoint,
+        }
+    }
+}
+
+impl Connection for QuicConnection {
+    fn send(&mut self, message: &[u8]) -> Result<(), SendError> {
+        let lane = self.selector.select(message) % self.lanes;
+        {
+            let mut state = mutex_lock_unwrap!(self.state);
+            if state.is_closed() {
+                return Err(SendError::Disconnected);
+            }
+            state.queue(lane, message)?;
+        }
+        self.waker.wake();
+        Ok(())
+    }
+
+    fn recv(&mut self) -> Result<Vec<u8>, RecvError> {
+        let mut state = mutex_lock_unwrap!(self.state);
+        let result = match state.messages.pop_front() {
+            Some(message) => Ok(message),
+            None => match state.error.as_ref() {
+                Some(err) => Err(RecvError::ProtocolError(err.clone())),
+                None if state.conn.is_closed() => Err(RecvError::Disconnected),
+                None => Err(RecvError::WouldBlock),
+            },
+        };
+        state.update_readiness();
+        result
+    }
+
+    fn remote_endpoint(&self) -> String {
+        self.remote_endpoint.clone()
+    }
+
+    fn local_endpoint(&self) -> String {
+        self.local_endpoint.clone()
+    }
+
+    fn disconnect(&mut self) -> Result<(), DisconnectError> {
+        let result = mutex_lock_unwrap!(self.state)
+            .conn
+            .close(true, 0x0, b"disconnect");
+        self.waker.wake();
+        match result {
+            Ok(()) | Err(quiche::Error::Done) => Ok(()),
+            Err(err) => Err(DisconnectError::ProtocolError(err.to_string())),
+        }
+    }
+
+    fn evented(&self) -> &dyn Evented {
+        self
+    }
+}
+
+impl Drop for QuicConnection {
+    fn drop(&mut self) {
+        let _ = mutex_lock_unwrap!(self.state).conn.close(true, 0x0, b"");
+        self.waker.wake();
+    }
+}
+
+impl Evented for QuicConnection {
+    fn register(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        self.registration.register(poll, token, interest, opts)?;
+        mutex_lock_unwrap!(self.state).update_readiness();
+        Ok(())
+    }
+
+    fn reregister(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        self.registration.reregister(poll, token, interest, opts)?;
+        mutex_lock_unwrap!(self.state).update_readiness();
+        Ok(())
+    }
+
+    fn deregister(&self, poll: &Poll) -> io::Result<()> {
+        poll.deregister(&self.registration)
+    }
+}
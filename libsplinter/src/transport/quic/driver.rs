@@ -0,0 +1,452 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The driver thread that sends and receives the UDP datagrams for QUIC connections.
+
+use std::collections::HashMap;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use mio::net::UdpSocket;
+use mio::{Events, Poll, PollOpt, Ready, Registration, SetReadiness, Token};
+use rand::Rng;
+
+use super::connection::{ConnectionState, QuicConnection, Waker};
+use super::stream::StreamSelector;
+use super::transport::QuicConfigs;
+
+const SOCKET: Token = Token(0);
+const WAKER: Token = Token(1);
+
+/// The maximum size of a UDP datagram sent or received by the driver.
+pub(super) const MAX_DATAGRAM_SIZE: usize = 1350;
+
+/// The maximum time the driver waits between checks for shutdown.
+const MAX_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Settings shared by every connection created by a driver.
+#[derive(Clone)]
+pub(super) struct ConnectionSettings {
+    pub(super) selector: Arc<dyn StreamSelector>,
+    pub(super) lanes: u64,
+    pub(super) max_message_size: usize,
+    pub(super) protocol_prefix: &'static str,
+}
+
+/// A notification sent by the driver when a connection's handshake is complete, or has failed.
+pub(super) type HandshakeResult = Result<QuicConnection, String>;
+
+struct DriverConnection {
+    state: Arc<Mutex<ConnectionState>>,
+    /// The handle that is returned once the handshake completes; taken when it is delivered.
+    handle: Option<QuicConnection>,
+}
+
+/// Owns a UDP socket and drives all of the QUIC connections that use it.
+///
+/// A client driver carries a single outbound connection and exits once that connection is
+/// closed. A server driver accepts inbound connections until its listener is dropped, and exits
+/// once all of its connections are closed.
+pub(super) struct Driver {
+    socket: UdpSocket,
+    local_addr: SocketAddr,
+    poll: Poll,
+    waker_registration: Registration,
+    waker_set_readiness: SetReadiness,
+    /// The configurations whose server configuration is used to accept inbound connections, if
+    /// this is a server driver.
+    server_configs: Option<Arc<Mutex<QuicConfigs>>>,
+    settings: ConnectionSettings,
+    connections: HashMap<Vec<u8>, DriverConnection>,
+    /// Maps every connection ID that a peer may use to the connection's key in `connections`.
+    connection_ids: HashMap<Vec<u8>, Vec<u8>>,
+    handshakes: Sender<HandshakeResult>,
+    shutdown: Arc<AtomicBool>,
+}
+
+impl Driver {
+    fn new(
+        socket: UdpSocket,
+        server_configs: Option<Arc<Mutex<QuicConfigs>>>,
+        settings: ConnectionSettings,
+        handshakes: Sender<HandshakeResult>,
+        shutdown: Arc<AtomicBool>,
+    ) -> io::Result<Self> {
+        let local_addr = socket.local_addr()?;
+        let poll = Poll::new()?;
+        let (waker_registration, waker_set_readiness) = Registration::new2();
+        poll.register(&socket, SOCKET, Ready::readable(), PollOpt::level())?;
+        poll.register(
+            &waker_registration,
+            WAKER,
+            Ready::readable(),
+            PollOpt::edge(),
+        )?;
+
+        Ok(Driver {
+            socket,
+            local_addr,
+            poll,
+            waker_registration,
+            waker_set_readiness,
+            server_configs,
+            settings,
+            connections: HashMap::new(),
+            connection_ids: HashMap::new(),
+            handshakes,
+            shutdown,
+        })
+    }
+
+    /// Start a driver for a single outbound connection to the given peer.
+    ///
+    /// The result of the handshake is sent on `handshakes`.
+    pub(super) fn connect(
+        socket: UdpSocket,
+        server_name: &str,
+        peer_addr: SocketAddr,
+        client_config: &mut quiche::Config,
+        settings: ConnectionSettings,
+        handshakes: Sender<HandshakeResult>,
+    ) -> io::Result<()> {
+        let mut driver = Driver::new(
+            socket,
+            None,
+            settings,
+            handshakes,
+            Arc::new(AtomicBool::new(true)),
+        )?;
+
+        let scid = new_connection_id();
+        let conn = quiche::connect(
+            Some(server_name),
+            &quiche::ConnectionId::from_ref(&scid),
+            driver.local_addr,
+            peer_addr,
+            client_config,
+        )
+        .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))?;
+        driver.add_connection(scid, conn, peer_addr, false);
+
+        driver.spawn("QUIC Client Driver")
+    }
+
+    /// Start a driver which accepts inbound connections.
+    ///
+    /// Accepted connections are sent on `handshakes`; the driver stops accepting connections once
+    /// `shutdown` is set.
+    pub(super) fn listen(
+        socket: UdpSocket,
+        server_configs: Arc<Mutex<QuicConfigs>>,
+        settings: ConnectionSettings,
+        handshakes: Sender<HandshakeResult>,
+        shutdown: Arc<AtomicBool>,
+    ) -> io::Result<()> {
+        Driver::new(socket, Some(server_configs), settings, handshakes, shutdown)?
+            .spawn("QUIC Server Driver")
+    }
+
+    fn spawn(mut self, name: &str) -> io::Result<()> {
+        thread::Builder::new()
+            .name(name.into())
+            .spawn(move || self.run())
+            .map(|_| ())
+    }
+
+    fn run(&mut self) {
+        let mut events = Events::with_capacity(256);
+        let mut buf = [0u8; 65535];
+        let mut out = [0u8; MAX_DATAGRAM_SIZE];
+
+        loop {
+            let timeout = self
+                .connections
+                .values()
+                .filter_map(|conn| mutex_lock_unwrap!(conn.state).conn.timeout())
+                .min()
+                .map(|timeout| timeout.min(MAX_POLL_INTERVAL))
+                .unwrap_or(MAX_POLL_INTERVAL);
+
+            if let Err(err) = self.poll.poll(&mut events, Some(timeout)) {
+                error!("QUIC driver unable to poll: {}", err);
+                break;
+            }
+
+            for event in events.iter() {
+                match event.token() {
+                    SOCKET => self.recv_datagrams(&mut buf),
+                    WAKER => {
+                        if let Err(err) = self.waker_set_readiness.set_readiness(Ready::empty()) {
+                            error!("Unable to reset QUIC driver waker: {}", err);
+                        }
+                    }
+                    _ => (),
+                }
+            }
+
+            self.process_connections(&mut buf, &mut out);
+
+            if self.shutdown.load(Ordering::SeqCst) && self.connections.is_empty() {
+                debug!("QUIC driver on {} shutting down", self.local_addr);
+                break;
+            }
+        }
+    }
+
+    fn recv_datagrams(&mut self, buf: &mut [u8]) {
+        loop {
+            let (len, from) = match self.socket.recv_from(buf) {
+                Ok(received) => received,
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => return,
+                Err(err) => {
+                    error!("QUIC driver unable to receive datagram: {}", err);
+                    return;
+                }
+            };
+
+            let packet = &mut buf[..len];
+            let header = match quiche::Header::from_slice(packet, quiche::MAX_CONN_ID_LEN) {
+                Ok(header) => header,
+                Err(err) => {
+                    trace!("Dropping invalid QUIC packet from {}: {}", from, err);
+                    continue;
+                }
+            };
+
+            let key = match self.connection_ids.get(&header.dcid[..]) {
+                Some(key) => key.clone(),
+                None => match self.accept(&header, from) {
+                    Some(key) => key,
+                    None => continue,
+                },
+            };
+
+            if let Some(driver_conn) = self.connections.get(&key) {
+                let mut state = mutex_lock_unwrap!(driver_conn.state);
+                let recv_info = quiche::RecvInfo {
+                    from,
+                    to: self.local_addr,
+                };
+                if let Err(err) = state.conn.recv(packet, recv_info) {
+                    // The packet could not be authenticated, so its source address is ignored
+                    trace!("Unable to process QUIC packet from {}: {}", from, err);
+                    continue;
+                }
+                update_peer_addr(&mut state);
+            }
+        }
+    }
+
+    /// Create a new inbound connection for an initial packet, returning its key.
+    fn accept(&mut self, header: &quiche::Header, from: SocketAddr) -> Option<Vec<u8>> {
+        if self.shutdown.load(Ordering::SeqCst) || header.ty != quiche::Type::Initial {
+            return None;
+        }
+
+        let server_configs = self.server_configs.as_ref()?;
+        if !quiche::version_is_supported(header.version) {
+            trace!(
+                "Dropping QUIC packet with unsupported version from {}",
+                from
+            );
+            return None;
+        }
+
+        let scid = new_connection_id();
+        let conn = match quiche::accept(
+            &quiche::ConnectionId::from_ref(&scid),
+            None,
+            self.local_addr,
+            from,
+            &mut mutex_lock_unwrap!(server_configs).server,
+        ) {
+            Ok(conn) => conn,
+            Err(err) => {
+                warn!("Unable to accept QUIC connection from {}: {}", from, err);
+                return None;
+            }
+        };
+
+        // The peer continues to use the connection ID it chose until it has received a packet
+        // from this side, so route both IDs to the new connection.
+        self.connection_ids
+            .insert(header.dcid.to_vec(), scid.clone());
+        self.add_connection(scid.clone(), conn, from, true);
+
+        Some(scid)
+    }
+
+    fn add_connection(
+        &mut self,
+        scid: Vec<u8>,
+        conn: quiche::Connection,
+        peer_addr: SocketAddr,
+        is_server: bool,
+    ) {
+        let (registration, set_readiness) = Registration::new2();
+        let state = Arc::new(Mutex::new(ConnectionState::new(
+            conn,
+            peer_addr,
+            is_server,
+            self.settings.max_message_size,
+            set_readiness,
+        )));
+
+        let prefix = self.settings.protocol_prefix;
+        let handle = QuicConnection::new(
+            state.clone(),
+            registration,
+            Waker::new(self.waker_set_readiness.clone()),
+            self.settings.selector.clone(),
+            self.settings.lanes,
+            format!("{}{}", prefix, peer_addr),
+            format!("{}{}", prefix, self.local_addr),
+        );
+
+        self.connection_ids.insert(scid.clone(), scid.clone());
+        self.connections.insert(
+            scid,
+            DriverConnection {
+                state,
+                handle: Some(handle),
+            },
+        );
+    }
+
+    fn process_connections(&mut self, buf: &mut [u8], out: &mut [u8]) {
+        let mut closed = vec![];
+
+        for (key, driver_conn) in self.connections.iter_mut() {
+            let mut state = mutex_lock_unwrap!(driver_conn.state);
+            state.conn.on_timeout();
+            state.read_streams(buf);
+            state.flush_streams();
+
+            loop {
+                // The connection only addresses packets to a new peer address once a packet from it
+                // has been authenticated, and limits what it sends there until the path is
+                // validated, so the destination it chooses is used rather than `peer_addr`.
+                let (len, send_info) = match state.conn.send(out) {
+                    Ok(sent) => sent,
+                    Err(quiche::Error::Done) => break,
+                    Err(err) => {
+                        debug!("Unable to prepare QUIC packet: {}", err);
+                        let _ = state.conn.close(false, 0x1, b"internal error");
+                        break;
+                    }
+                };
+
+                if let Err(err) = self.socket.send_to(&out[..len], &send_info.to) {
+                    // Lost datagrams are recovered by QUIC's own loss detection.
+                    if err.kind() != io::ErrorKind::WouldBlock {
+                        debug!("Unable to send QUIC datagram: {}", err);
+                    }
+                    break;
+                }
+            }
+
+            let handshake_complete = state.conn.is_established() || state.is_closed();
+            let established = state.conn.is_established() && !state.is_closed();
+            let peer_addr = state.peer_addr;
+            let is_server = state.is_server;
+            // The lock must be released before the handle is delivered or dropped, as dropping a
+            // connection closes it.
+            drop(state);
+
+            if let Some(handle) = driver_conn.handle.take() {
+                if established {
+                    deliver(&self.handshakes, Ok(handle), peer_addr);
+                } else if !handshake_complete {
+                    driver_conn.handle = Some(handle);
+                } else if is_server {
+                    // A failed inbound handshake is not an error for the listener.
+                    debug!("QUIC handshake with {} failed", peer_addr);
+                } else {
+                    let err = format!("handshake with {} failed", peer_addr);
+                    deliver(&self.handshakes, Err(err), peer_addr);
+                }
+            }
+
+            let state = mutex_lock_unwrap!(driver_conn.state);
+            state.update_readiness();
+            if state.conn.is_closed() {
+                closed.push(key.clone());
+            }
+        }
+
+        for key in closed {
+            self.connections.remove(&key);
+            self.connection_ids.retain(|_, value| value != &key);
+        }
+    }
+}
+
+/// Moves the connection's peer address once the connection reports that the peer has migrated to
+/// a validated path.
+///
+/// Path events are only produced by packets that the connection has authenticated, so a spoofed
+/// datagram cannot change the peer address.
+fn update_peer_addr(state: &mut ConnectionState) {
+    while let Some(event) = state.conn.path_event_next() {
+        match event {
+            quiche::PathEvent::PeerMigrated(local, peer) => {
+                if state.conn.is_path_validated(local, peer).unwrap_or(false) {
+                    migrate(state, peer);
+                } else {
+                    // Wait for the path to be validated before treating it as the peer's address
+                    state.pending_peer_addr = Some(peer);
+                }
+            }
+            quiche::PathEvent::Validated(_, peer) if state.pending_peer_addr == Some(peer) => {
+                migrate(state, peer);
+            }
+            quiche::PathEvent::FailedValidation(_, peer)
+                if state.pending_peer_addr == Some(peer) =>
+            {
+                debug!(
+                    "QUIC peer {} failed to validate new address {}",
+                    state.peer_addr, peer
+                );
+                state.pending_peer_addr = None;
+            }
+            _ => (),
+        }
+    }
+}
+
+fn migrate(state: &mut ConnectionState, peer: SocketAddr) {
+    if state.peer_addr != peer {
+        debug!("QUIC peer {} migrated to {}", state.peer_addr, peer);
+        state.peer_addr = peer;
+    }
+    state.pending_peer_addr = None;
+}
+
+fn deliver(handshakes: &Sender<HandshakeResult>, result: HandshakeResult, peer_addr: SocketAddr) {
+    if handshakes.send(result).is_err() {
+        // Nothing is waiting for the connection; this occurs after the listener has been dropped.
+        trace!("Discarding QUIC connection with {}", peer_addr);
+    }
+}
+
+fn new_connection_id() -> Vec<u8> {
+    let mut scid = vec![0; quiche::MAX_CONN_ID_LEN];
+    rand::thread_rng().fill(&mut scid[..]);
+    scid
+}
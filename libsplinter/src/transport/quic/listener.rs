@@ -0,0 +1,65 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+
+use crate::transport::{AcceptError, Connection, Listener};
+
+use super::driver::HandshakeResult;
+
+pub(super) struct QuicListener {
+    incoming: Receiver<HandshakeResult>,
+    local_endpoint: String,
+    shutdown: Arc<AtomicBool>,
+}
+
+impl QuicListener {
+    pub fn new(
+        incoming: Receiver<HandshakeResult>,
+        local_endpoint: String,
+        shutdown: Arc<AtomicBool>,
+    ) -> Self {
+        QuicListener {
+            incoming,
+            local_endpoint,
+            shutdown,
+        }
+    }
+}
+
+impl Listener for QuicListener {
+    fn accept(&mut self) -> Result<Box<dyn Connection>, AcceptError> {
+        match self.incoming.recv() {
+            Ok(Ok(connection)) => Ok(Box::new(connection)),
+            Ok(Err(err)) => Err(AcceptError::ProtocolError(err)),
+            Err(_) => Err(AcceptError::ProtocolError(
+                "QUIC driver is no longer running".into(),
+            )),
+        }
+    }
+
+    fn endpoint(&self) -> String {
+        self.local_endpoint.clone()
+    }
+}
+
+impl Drop for QuicListener {
+    fn drop(&mut self) {
+        // Stop accepting new connections; the driver continues to run until the connections it
+        // has already accepted are closed.
+        self.shutdown.store(true, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,170 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A QUIC-based transport implementation.
+//!
+//! The `splinter::transport::quic` module provides a `Transport` implementation on top of QUIC,
+//! using endpoints of the format `quic://ip_or_host:port`. Each connection carries messages on a
+//! number of independent streams, so that packet loss affecting one stream does not delay
+//! messages on the others.
+
+mod connection;
+mod driver;
+mod listener;
+mod stream;
+mod transport;
+
+pub use stream::{CircuitStreamSelector, SingleStreamSelector, StreamSelector};
+#[cfg(feature = "tls-cert-reload")]
+pub use transport::QuicReloadHandle;
+pub use transport::{QuicInitError, QuicTransport};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::File;
+    use std::io::Write;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    use tempdir::TempDir;
+
+    use crate::transport::tests;
+    use crate::transport::tls::tests::{make_ca_cert, make_ca_signed_cert};
+    use crate::transport::tls::{TlsConfig, TlsConfigBuilder};
+    use crate::transport::Transport;
+
+    fn write_file(temp_dir: &Path, file_name: &str, bytes: &[u8]) -> String {
+        let path = temp_dir.join(file_name).to_str().unwrap().to_string();
+        let mut file = File::create(&path).unwrap();
+        file.write_all(bytes).unwrap();
+
+        path
+    }
+
+    fn create_test_tls_config(temp_dir: &TempDir, insecure: bool) -> TlsConfig {
+        let mut builder = TlsConfigBuilder::new();
+        let (ca_key, ca_cert) = make_ca_cert();
+
+        if !insecure {
+            builder = builder.with_ca_certs_file(write_file(
+                temp_dir.path(),
+                "ca.cert",
+                &ca_cert.to_pem().unwrap(),
+            ));
+        }
+
+        let (client_key, client_cert) = make_ca_signed_cert(&ca_cert, &ca_key);
+        let (server_key, server_cert) = make_ca_signed_cert(&ca_cert, &ca_key);
+
+        builder
+            .with_client_cert_file(write_file(
+                temp_dir.path(),
+                "client.cert",
+                &client_cert.to_pem().unwrap(),
+            ))
+            .with_client_private_key_file(write_file(
+                temp_dir.path(),
+                "client.key",
+                &client_key.private_key_to_pem_pkcs8().unwrap(),
+            ))
+            .with_server_cert_file(write_file(
+                temp_dir.path(),
+                "server.cert",
+                &server_cert.to_pem().unwrap(),
+            ))
+            .with_server_private_key_file(write_file(
+                temp_dir.path(),
+                "server.key",
+                &server_key.private_key_to_pem_pkcs8().unwrap(),
+            ))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_quic_accepts() {
+        let temp_dir = TempDir::new("test-quic-accepts").unwrap();
+        let config = create_test_tls_config(&temp_dir, true);
+        let transport = QuicTransport::new(&config).unwrap();
+        assert!(transport.accepts("quic://127.0.0.1:18090"));
+        assert!(transport.accepts("quic://somewhere.example.com:18090"));
+        assert!(!transport.accepts("tcps://127.0.0.1:18090"));
+    }
+
+    #[test]
+    fn test_quic_transport() {
+        let temp_dir = TempDir::new("test-quic-transport").unwrap();
+        let config = create_test_tls_config(&temp_dir, true);
+        let transport = QuicTransport::new(&config).unwrap();
+        tests::test_transport(transport, "quic://127.0.0.1:0");
+    }
+
+    #[test]
+    fn test_quic_transport_single_stream() {
+        let temp_dir = TempDir::new("test-quic-single-stream").unwrap();
+        let config = create_test_tls_config(&temp_dir, true);
+        let transport = QuicTransport::new(&config)
+            .unwrap()
+            .with_stream_selector(Arc::new(SingleStreamSelector), 1)
+            .unwrap();
+        tests::test_transport(transport, "quic://127.0.0.1:0");
+    }
+
+    /// Test that reloading a transport's certificates:
+    ///
+    /// 1. Succeeds when the files have been replaced with a new, valid certificate and key
+    /// 2. Fails when the files are invalid, leaving the previous certificates in use
+    /// 3. Leaves the transport usable for new connections in both cases
+    #[cfg(feature = "tls-cert-reload")]
+    #[test]
+    fn test_quic_transport_reload() {
+        let temp_dir = TempDir::new("test-quic-reload").unwrap();
+        let config = create_test_tls_config(&temp_dir, true);
+        let transport = QuicTransport::new(&config).unwrap();
+        let reload_handle = transport.reload_handle();
+
+        // Rotate the server certificate and reload it
+        let (ca_key, ca_cert) = make_ca_cert();
+        let (server_key, server_cert) = make_ca_signed_cert(&ca_cert, &ca_key);
+        write_file(
+            temp_dir.path(),
+            "server.cert",
+            &server_cert.to_pem().unwrap(),
+        );
+        write_file(
+            temp_dir.path(),
+            "server.key",
+            &server_key.private_key_to_pem_pkcs8().unwrap(),
+        );
+        reload_handle
+            .reload()
+            .expect("Unable to reload certificates");
+
+        // Break the server certificate; the reload should be rejected
+        write_file(temp_dir.path(), "server.cert", b"not a certificate");
+        assert!(reload_handle.reload().is_err());
+
+        tests::test_transport(transport, "quic://127.0.0.1:0");
+    }
+
+    #[test]
+    fn test_quic_poll() {
+        let temp_dir = TempDir::new("test-quic-poll").unwrap();
+        let config = create_test_tls_config(&temp_dir, false);
+        let transport = QuicTransport::new(&config).unwrap();
+        tests::test_poll(transport, "quic://127.0.0.1:0");
+    }
+}
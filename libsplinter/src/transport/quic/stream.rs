@@ -0,0 +1,250 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Stream selection and message framing for the QUIC transport.
+//!
+//! Each QUIC connection carries messages on a fixed set of unidirectional streams, referred to as
+//! lanes. Messages that are assigned to the same lane are delivered in order, while messages on
+//! different lanes are independent of one another; a lost packet on one lane does not delay the
+//! delivery of messages on any other lane.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use protobuf::Message;
+
+use crate::protos::circuit::{
+    AdminDirectMessage, CircuitDirectMessage, CircuitMessage, CircuitMessageType,
+};
+use crate::protos::network::{NetworkMessage, NetworkMessageType};
+
+/// The number of bytes used for the length prefix of each message written to a stream.
+const FRAME_HEADER_LEN: usize = 4;
+
+/// Selects the lane a message will be sent on.
+///
+/// Lanes are numbered from `0` to `lanes - 1`, where `lanes` is the number of lanes configured
+/// on the transport. Any value returned that is greater than or equal to `lanes` will be reduced
+/// modulo `lanes`.
+pub trait StreamSelector: Send + Sync {
+    /// Return the lane for the given message.
+    fn select(&self, message: &[u8]) -> u64;
+}
+
+/// A `StreamSelector` that sends every message on the first lane.
+///
+/// This preserves the total ordering of messages on a connection, at the cost of head-of-line
+/// blocking between unrelated messages.
+#[derive(Default)]
+pub struct SingleStreamSelector;
+
+impl StreamSelector for SingleStreamSelector {
+    fn select(&self, _message: &[u8]) -> u64 {
+        0
+    }
+}
+
+/// A `StreamSelector` that assigns lanes by circuit.
+///
+/// Messages that are addressed to a circuit (circuit direct messages and admin direct messages)
+/// are assigned a lane based on the circuit ID, so that messages for the same circuit remain in
+/// order while a slow circuit does not block any other circuit. All other messages, such as
+/// heartbeats, authorization and service connection messages, are sent on lane `0`.
+pub struct CircuitStreamSelector {
+    lanes: u64,
+}
+
+impl CircuitStreamSelector {
+    /// Construct a new `CircuitStreamSelector` which spreads circuits over the given number of
+    /// lanes.
+    ///
+    /// Lane `0` is reserved for messages that are not addressed to a circuit, so circuits are
+    /// spread over lanes `1` through `lanes - 1`.
+    pub fn new(lanes: u64) -> Self {
+        CircuitStreamSelector { lanes }
+    }
+
+    fn circuit_id(message: &[u8]) -> Option<String> {
+        let network_msg = NetworkMessage::parse_from_bytes(message).ok()?;
+        if network_msg.get_message_type() != NetworkMessageType::CIRCUIT {
+            return None;
+        }
+
+        let circuit_msg = CircuitMessage::parse_from_bytes(network_msg.get_payload()).ok()?;
+        match circuit_msg.get_message_type() {
+            CircuitMessageType::CIRCUIT_DIRECT_MESSAGE => {
+                CircuitDirectMessage::parse_from_bytes(circuit_msg.get_payload())
+                    .ok()
+                    .map(|mut msg| msg.take_circuit())
+            }
+            CircuitMessageType::ADMIN_DIRECT_MESSAGE => {
+                AdminDirectMessage::parse_from_bytes(circuit_msg.get_payload())
+                    .ok()
+                    .map(|mut msg| msg.take_circuit())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl StreamSelector for CircuitStreamSelector {
+    fn select(&self, message: &[u8]) -> u64 {
+        if self.lanes < 2 {
+            return 0;
+        }
+
+        match Self::circuit_id(message) {
+            Some(circuit_id) => {
+                let mut hasher = DefaultHasher::new();
+                circuit_id.hash(&mut hasher);
+                1 + hasher.finish() % (self.lanes - 1)
+            }
+            None => 0,
+        }
+    }
+}
+
+/// Return the QUIC stream ID used for sending on the given lane.
+///
+/// Lanes are carried on unidirectional streams; the two low bits of a QUIC stream ID encode the
+/// initiator (client or server) and the directionality of the stream.
+pub(super) fn lane_stream_id(lane: u64, is_server: bool) -> u64 {
+    let initiator = if is_server { 0x1 } else { 0x0 };
+    (lane << 2) | 0x2 | initiator
+}
+
+/// Prefix the message with its length, producing the bytes to write to a stream.
+pub(super) fn encode_frame(message: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(FRAME_HEADER_LEN + message.len());
+    frame.extend_from_slice(&(message.len() as u32).to_be_bytes());
+    frame.extend_from_slice(message);
+    frame
+}
+
+/// Remove all complete messages from the front of the buffered stream data.
+///
+/// Returns an error if a message exceeds the maximum message size.
+pub(super) fn decode_frames(
+    buffer: &mut Vec<u8>,
+    max_message_size: usize,
+) -> Result<Vec<Vec<u8>>, String> {
+    let mut messages = vec![];
+    let mut offset = 0;
+
+    while buffer.len() - offset >= FRAME_HEADER_LEN {
+        let mut header = [0u8; FRAME_HEADER_LEN];
+        header.copy_from_slice(&buffer[offset..offset + FRAME_HEADER_LEN]);
+        let len = u32::from_be_bytes(header) as usize;
+
+        if len > max_message_size {
+            return Err(format!(
+                "message of {} bytes exceeds the maximum of {} bytes",
+                len, max_message_size
+            ));
+        }
+
+        let start = offset + FRAME_HEADER_LEN;
+        if buffer.len() - start < len {
+            break;
+        }
+
+        messages.push(buffer[start..start + len].to_vec());
+        offset = start + len;
+    }
+
+    buffer.drain(..offset);
+
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that frames are decoded only once they are complete, and that any remaining bytes
+    /// are kept for the next call.
+    #[test]
+    fn test_decode_partial_frames() {
+        let mut encoded = encode_frame(b"hello");
+        encoded.extend(encode_frame(b"world"));
+
+        let mut buffer = encoded[..7].to_vec();
+        assert!(decode_frames(&mut buffer, 1024).unwrap().is_empty());
+
+        buffer.extend_from_slice(&encoded[7..12]);
+        assert_eq!(
+            vec![b"hello".to_vec()],
+            decode_frames(&mut buffer, 1024).unwrap()
+        );
+        assert_eq!(&encoded[9..12], &buffer[..]);
+
+        buffer.extend_from_slice(&encoded[12..]);
+        assert_eq!(
+            vec![b"world".to_vec()],
+            decode_frames(&mut buffer, 1024).unwrap()
+        );
+        assert!(buffer.is_empty());
+    }
+
+    /// Verify that a frame larger than the maximum message size is rejected.
+    #[test]
+    fn test_decode_oversized_frame() {
+        let mut buffer = encode_frame(&[0u8; 64]);
+        assert!(decode_frames(&mut buffer, 32).is_err());
+    }
+
+    /// Verify that the circuit stream selector keeps messages for one circuit on the same lane,
+    /// and sends non-circuit messages on lane 0.
+    #[test]
+    fn test_circuit_stream_selector() {
+        let selector = CircuitStreamSelector::new(8);
+
+        let alpha_1 = circuit_direct_message("alpha", b"one");
+        let alpha_2 = circuit_direct_message("alpha", b"two");
+        let lane = selector.select(&alpha_1);
+        assert!(lane >= 1 && lane < 8);
+        assert_eq!(lane, selector.select(&alpha_2));
+
+        let mut heartbeat = NetworkMessage::new();
+        heartbeat.set_message_type(NetworkMessageType::NETWORK_HEARTBEAT);
+        assert_eq!(0, selector.select(&heartbeat.write_to_bytes().unwrap()));
+
+        assert_eq!(0, selector.select(b"not a protobuf message"));
+    }
+
+    /// Verify the stream IDs used for lanes are unidirectional and belong to the sending side.
+    #[test]
+    fn test_lane_stream_id() {
+        assert_eq!(2, lane_stream_id(0, false));
+        assert_eq!(3, lane_stream_id(0, true));
+        assert_eq!(6, lane_stream_id(1, false));
+        assert_eq!(7, lane_stream_id(1, true));
+    }
+
+    fn circuit_direct_message(circuit: &str, payload: &[u8]) -> Vec<u8> {
+        let mut direct_msg = CircuitDirectMessage::new();
+        direct_msg.set_circuit(circuit.into());
+        direct_msg.set_payload(payload.to_vec());
+
+        let mut circuit_msg = CircuitMessage::new();
+        circuit_msg.set_message_type(CircuitMessageType::CIRCUIT_DIRECT_MESSAGE);
+        circuit_msg.set_payload(direct_msg.write_to_bytes().unwrap());
+
+        let mut network_msg = NetworkMessage::new();
+        network_msg.set_message_type(NetworkMessageType::CIRCUIT);
+        network_msg.set_payload(circuit_msg.write_to_bytes().unwrap());
+
+        network_msg.write_to_bytes().unwrap()
+    }
+}
@@ -0,0 +1,383 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs};
+use std::sync::atomic::AtomicBool;
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use mio::net::UdpSocket;
+use url::{ParseError, Url};
+
+use crate::transport::tls::TlsConfig;
+use crate::transport::{ConnectError, Connection, ListenError, Listener, Transport};
+
+use super::driver::{ConnectionSettings, Driver, MAX_DATAGRAM_SIZE};
+use super::listener::QuicListener;
+use super::stream::{CircuitStreamSelector, StreamSelector};
+
+pub(super) const PROTOCOL_PREFIX: &str = "quic://";
+
+/// The ALPN protocol identifier, in wire format, used for Splinter connections.
+const APPLICATION_PROTOCOL: &[u8] = b"\x08splinter";
+
+const DEFAULT_LANES: u64 = 8;
+const DEFAULT_MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const IDLE_TIMEOUT_MILLIS: u64 = 30_000;
+const MAX_CONNECTION_DATA: u64 = 64 * 1024 * 1024;
+const MAX_STREAM_DATA: u64 = 16 * 1024 * 1024;
+
+/// A QUIC-based `Transport`.
+///
+/// Supports endpoints of the format `quic://ip_or_host:port`.
+///
+/// QUIC always uses TLS 1.3, so the transport requires a `TlsConfig`; the server certificate and
+/// key are used for inbound connections and the client certificate and key for outbound
+/// connections. If the configuration has a CA certificates file, the peer's certificate is
+/// verified against it in both directions.
+///
+/// Each connection sends messages on a number of independent streams, or lanes. By default,
+/// messages are assigned to lanes with a [`CircuitStreamSelector`], so that a slow circuit does
+/// not block the messages of any other circuit.
+///
+/// Packets are routed by connection ID rather than by address, so a connection survives a change
+/// in the peer's address, such as a NAT rebinding.
+///
+/// With the `tls-cert-reload` feature, the certificates can be reloaded from their files with a
+/// [`QuicReloadHandle`], which applies to new outbound connections and to the connections accepted
+/// by existing listeners.
+///
+/// # Examples
+///
+/// To connect to the a remote endpoint, send a message, and receive a reply message:
+///
+/// ```rust,no_run
+/// use splinter::transport::Transport as _;
+/// use splinter::transport::quic::QuicTransport;
+/// use splinter::transport::tls::TlsConfigBuilder;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let config = TlsConfigBuilder::new()
+///         .with_ca_certs_file("ca.cert".into())
+///         .with_client_cert_file("client.cert".into())
+///         .with_client_private_key_file("client.key".into())
+///         .with_server_cert_file("server.cert".into())
+///         .with_server_private_key_file("server.key".into())
+///         .build()?;
+///
+///     let mut transport = QuicTransport::new(&config)?;
+///
+///     // Connect to a remote endpoint starting wtih `quic://`.
+///     let mut connection = transport.connect("quic://127.0.0.1:5555")?;
+///
+///     // Send some bytes
+///     connection.send(b"hello world")?;
+///
+///     // Receive a response
+///     let msg = connection.recv()?;
+///
+///     // Disconnect
+///     connection.disconnect()?;
+///
+///     Ok(())
+/// }
+/// ```
+///
+/// [`CircuitStreamSelector`]: struct.CircuitStreamSelector.html
+pub struct QuicTransport {
+    files: Arc<QuicFiles>,
+    configs: Arc<Mutex<QuicConfigs>>,
+    selector: Arc<dyn StreamSelector>,
+    max_message_size: usize,
+}
+
+/// The certificate, key and CA files used by a `QuicTransport`.
+struct QuicFiles {
+    ca_certs_file: Option<String>,
+    client_cert_file: String,
+    client_private_key_file: String,
+    server_cert_file: String,
+    server_private_key_file: String,
+}
+
+/// The QUIC configurations built from a `QuicTransport`'s files.
+///
+/// These are shared with the transport's listeners and replaced as a whole when the certificates
+/// are reloaded; connections that have already been created keep using the previous
+/// configuration.
+pub(super) struct QuicConfigs {
+    client: quiche::Config,
+    pub(super) server: quiche::Config,
+    lanes: u64,
+}
+
+impl QuicConfigs {
+    fn load(files: &QuicFiles, lanes: u64) -> Result<Self, quiche::Error> {
+        Ok(QuicConfigs {
+            client: build_config(
+                &files.client_cert_file,
+                &files.client_private_key_file,
+                files.ca_certs_file.as_deref(),
+                lanes,
+            )?,
+            server: build_config(
+                &files.server_cert_file,
+                &files.server_private_key_file,
+                files.ca_certs_file.as_deref(),
+                lanes,
+            )?,
+            lanes,
+        })
+    }
+}
+
+impl QuicTransport {
+    pub fn new(config: &TlsConfig) -> Result<Self, QuicInitError> {
+        let files = QuicFiles {
+            ca_certs_file: config.ca_certs_file().clone(),
+            client_cert_file: config.client_cert_file().into(),
+            client_private_key_file: config.client_private_key_file().into(),
+            server_cert_file: config.server_cert_file().into(),
+            server_private_key_file: config.server_private_key_file().into(),
+        };
+        let configs = QuicConfigs::load(&files, DEFAULT_LANES)?;
+
+        Ok(QuicTransport {
+            files: Arc::new(files),
+            configs: Arc::new(Mutex::new(configs)),
+            selector: Arc::new(CircuitStreamSelector::new(DEFAULT_LANES)),
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+        })
+    }
+
+    /// Use the given `StreamSelector` to assign messages to lanes, spreading them over the given
+    /// number of lanes.
+    ///
+    /// Both sides of a connection must be configured with at least as many lanes as the other
+    /// side uses.
+    pub fn with_stream_selector(
+        mut self,
+        selector: Arc<dyn StreamSelector>,
+        lanes: u64,
+    ) -> Result<Self, QuicInitError> {
+        let configs = QuicConfigs::load(&self.files, lanes.max(1))?;
+        *mutex_lock_unwrap!(self.configs) = configs;
+        self.selector = selector;
+        Ok(self)
+    }
+
+    /// Set the maximum size of a single message; larger messages received from a peer cause the
+    /// connection to be closed.
+    pub fn with_max_message_size(mut self, max_message_size: usize) -> Self {
+        self.max_message_size = max_message_size;
+        self
+    }
+
+    /// Return a handle that reloads the certificates, keys and CA file used by this transport and
+    /// by any listeners it has created.
+    #[cfg(feature = "tls-cert-reload")]
+    pub fn reload_handle(&self) -> QuicReloadHandle {
+        QuicReloadHandle {
+            files: self.files.clone(),
+            configs: self.configs.clone(),
+        }
+    }
+
+    fn settings(&self) -> ConnectionSettings {
+        ConnectionSettings {
+            selector: self.selector.clone(),
+            lanes: mutex_lock_unwrap!(self.configs).lanes,
+            max_message_size: self.max_message_size,
+            protocol_prefix: PROTOCOL_PREFIX,
+        }
+    }
+}
+
+/// Reloads the certificates, keys and CA file of a `QuicTransport` from their files.
+///
+/// Connections that are made or accepted after a reload use the new certificates, while
+/// connections that are already established are unaffected.
+#[cfg(feature = "tls-cert-reload")]
+#[derive(Clone)]
+pub struct QuicReloadHandle {
+    files: Arc<QuicFiles>,
+    configs: Arc<Mutex<QuicConfigs>>,
+}
+
+#[cfg(feature = "tls-cert-reload")]
+impl QuicReloadHandle {
+    /// Read the certificate, key and CA files again and use them for new connections.
+    ///
+    /// If any of the files cannot be loaded, an error is returned and the previous certificates
+    /// remain in use.
+    pub fn reload(&self) -> Result<(), QuicInitError> {
+        let lanes = mutex_lock_unwrap!(self.configs).lanes;
+        let configs = QuicConfigs::load(&self.files, lanes)?;
+        *mutex_lock_unwrap!(self.configs) = configs;
+        Ok(())
+    }
+}
+
+fn build_config(
+    cert_file: &str,
+    private_key_file: &str,
+    ca_certs_file: Option<&str>,
+    lanes: u64,
+) -> Result<quiche::Config, quiche::Error> {
+    let mut config = quiche::Config::new(quiche::PROTOCOL_VERSION)?;
+
+    config.load_cert_chain_from_pem_file(cert_file)?;
+    config.load_priv_key_from_pem_file(private_key_file)?;
+
+    // if ca_certs_file is provided, verify the peer against it, otherwise skip verification
+    if let Some(ca_certs_file) = ca_certs_file {
+        config.load_verify_locations_from_file(ca_certs_file)?;
+        config.verify_peer(true);
+    } else {
+        config.verify_peer(false);
+    }
+
+    config.set_application_protos_wire_format(APPLICATION_PROTOCOL)?;
+    config.set_max_idle_timeout(IDLE_TIMEOUT_MILLIS);
+    config.set_max_recv_udp_payload_size(MAX_DATAGRAM_SIZE);
+    config.set_max_send_udp_payload_size(MAX_DATAGRAM_SIZE);
+    config.set_initial_max_data(MAX_CONNECTION_DATA);
+    config.set_initial_max_stream_data_uni(MAX_STREAM_DATA);
+    config.set_initial_max_streams_uni(lanes);
+    config.set_initial_max_streams_bidi(0);
+    config.set_disable_active_migration(false);
+
+    Ok(config)
+}
+
+fn endpoint_to_dns_name(endpoint: &str) -> Result<String, ParseError> {
+    let mut address = String::from("quic://");
+    address.push_str(endpoint);
+    let url = Url::parse(&address)?;
+    let dns_name = match url.domain() {
+        Some(d) if d.parse::<Ipv4Addr>().is_ok() => "localhost",
+        Some(d) if d.parse::<Ipv6Addr>().is_ok() => "localhost",
+        Some(d) => d,
+        None => "localhost",
+    };
+    Ok(String::from(dns_name))
+}
+
+fn resolve(address: &str) -> std::io::Result<SocketAddr> {
+    address.to_socket_addrs()?.next().ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::AddrNotAvailable,
+            format!("Unable to resolve {}", address),
+        )
+    })
+}
+
+impl Transport for QuicTransport {
+    fn accepts(&self, address: &str) -> bool {
+        address.starts_with(PROTOCOL_PREFIX)
+    }
+
+    fn connect(&mut self, endpoint: &str) -> Result<Box<dyn Connection>, ConnectError> {
+        let address = endpoint.strip_prefix(PROTOCOL_PREFIX).ok_or_else(|| {
+            ConnectError::ProtocolError(format!("Invalid protocol \"{}\"", endpoint))
+        })?;
+
+        let dns_name = endpoint_to_dns_name(address)?;
+        let peer_addr = resolve(address)?;
+
+        let bind_addr: SocketAddr = if peer_addr.is_ipv4() {
+            (Ipv4Addr::UNSPECIFIED, 0).into()
+        } else {
+            (Ipv6Addr::UNSPECIFIED, 0).into()
+        };
+        let socket = UdpSocket::bind(&bind_addr)?;
+
+        let settings = self.settings();
+        let (tx, rx) = channel();
+        Driver::connect(
+            socket,
+            &dns_name,
+            peer_addr,
+            &mut mutex_lock_unwrap!(self.configs).client,
+            settings,
+            tx,
+        )?;
+
+        match rx.recv_timeout(CONNECT_TIMEOUT) {
+            Ok(Ok(connection)) => Ok(Box::new(connection)),
+            Ok(Err(err)) => Err(ConnectError::ProtocolError(err)),
+            Err(RecvTimeoutError::Timeout) => Err(ConnectError::IoError(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("QUIC handshake with {} timed out", endpoint),
+            ))),
+            Err(RecvTimeoutError::Disconnected) => Err(ConnectError::ProtocolError(
+                "QUIC driver is no longer running".into(),
+            )),
+        }
+    }
+
+    fn listen(&mut self, bind: &str) -> Result<Box<dyn Listener>, ListenError> {
+        let address = bind
+            .strip_prefix(PROTOCOL_PREFIX)
+            .ok_or_else(|| ListenError::ProtocolError(format!("Invalid protocol \"{}\"", bind)))?;
+
+        let bind_addr = resolve(address)
+            .map_err(|err| ListenError::IoError(format!("Failed to resolve {}", address), err))?;
+        let socket = UdpSocket::bind(&bind_addr)
+            .map_err(|err| ListenError::IoError(format!("Failed to bind to {}", address), err))?;
+        let local_endpoint = format!(
+            "{}{}",
+            PROTOCOL_PREFIX,
+            socket.local_addr().map_err(|err| {
+                ListenError::IoError("Failed to get local address".into(), err)
+            })?
+        );
+
+        let (tx, rx) = channel();
+        let shutdown = Arc::new(AtomicBool::new(false));
+        Driver::listen(
+            socket,
+            self.configs.clone(),
+            self.settings(),
+            tx,
+            shutdown.clone(),
+        )
+        .map_err(|err| ListenError::IoError("Failed to start QUIC driver".into(), err))?;
+
+        Ok(Box::new(QuicListener::new(rx, local_endpoint, shutdown)))
+    }
+}
+
+#[derive(Debug)]
+pub enum QuicInitError {
+    ProtocolError(String),
+}
+
+impl std::error::Error for QuicInitError {}
+
+impl std::fmt::Display for QuicInitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            QuicInitError::ProtocolError(msg) => write!(f, "Unable to initialize QUIC: {}", msg),
+        }
+    }
+}
+
+impl From<quiche::Error> for QuicInitError {
+    fn from(error: quiche::Error) -> Self {
+        QuicInitError::ProtocolError(format!("quiche error: {}", error))
+    }
+}
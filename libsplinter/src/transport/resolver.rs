@@ -0,0 +1,318 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Resolution of logical endpoints into concrete endpoints.
+//!
+//! A logical endpoint does not name a host directly; instead, it names something that can be
+//! looked up at connect time, such as a DNS SRV record (`srv+tcps://_splinter._tcp.example.org`)
+//! or a node identity in the registry (`node://<identity>`). Resolvers are added to a
+//! [`MultiTransport`], which resolves logical endpoints each time a connection is made to them,
+//! so the hosts behind a logical endpoint may change without updating the endpoint itself.
+//!
+//! [`MultiTransport`]: ../multi/struct.MultiTransport.html
+
+use std::io;
+
+use rand::{thread_rng, Rng};
+use trust_dns_resolver::Resolver;
+
+#[cfg(feature = "registry")]
+use crate::registry::RegistryReader;
+
+use super::ConnectError;
+
+const SRV_PREFIX: &str = "srv+";
+#[cfg(feature = "registry")]
+const NODE_PREFIX: &str = "node://";
+
+/// Resolves logical endpoints into the concrete endpoints that may be connected to.
+pub trait EndpointResolver: Send {
+    /// Indicates whether or not the given endpoint can be resolved by this resolver.
+    fn accepts(&self, endpoint: &str) -> bool;
+
+    /// Resolves the given endpoint into a list of concrete endpoints, in the order that they
+    /// should be tried.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ConnectError` if the endpoint is invalid or cannot be resolved.
+    fn resolve(&self, endpoint: &str) -> Result<Vec<String>, ConnectError>;
+}
+
+/// Resolves endpoints of the form `srv+<protocol>://<name>` using DNS SRV records.
+///
+/// Each SRV record for `<name>` is resolved to the endpoint `<protocol>://<target>:<port>`. The
+/// endpoints are ordered by the priority of their records, lowest first; records with the same
+/// priority are ordered by weighted random selection, as described in RFC 2782, so the load is
+/// spread across them in proportion to their weights.
+pub struct SrvEndpointResolver {
+    resolver: Resolver,
+}
+
+impl SrvEndpointResolver {
+    /// Creates a new `SrvEndpointResolver` that uses the system's DNS configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `io::Error` if the system's DNS configuration cannot be read.
+    pub fn from_system_conf() -> Result<Self, io::Error> {
+        Ok(Self {
+            resolver: Resolver::from_system_conf()?,
+        })
+    }
+}
+
+impl EndpointResolver for SrvEndpointResolver {
+    fn accepts(&self, endpoint: &str) -> bool {
+        endpoint.starts_with(SRV_PREFIX)
+    }
+
+    fn resolve(&self, endpoint: &str) -> Result<Vec<String>, ConnectError> {
+        let (protocol, name) = parse_srv_endpoint(endpoint)?;
+
+        let lookup = self.resolver.srv_lookup(name).map_err(|err| {
+            ConnectError::ProtocolError(format!("Unable to resolve {}: {}", endpoint, err))
+        })?;
+
+        Ok(order_srv_records(
+            lookup
+                .iter()
+                .map(|srv| SrvRecord {
+                    priority: srv.priority(),
+                    weight: srv.weight(),
+                    target: srv.target().to_utf8(),
+                    port: srv.port(),
+                })
+                .collect(),
+            &mut thread_rng(),
+        )
+        .into_iter()
+        .map(|record| format!("{}://{}:{}", protocol, record.target, record.port))
+        .collect())
+    }
+}
+
+/// Resolves endpoints of the form `node://<identity>` to the endpoints of the node with the given
+/// identity in the registry, in the order they are listed.
+#[cfg(feature = "registry")]
+pub struct RegistryEndpointResolver {
+    registry: Box<dyn RegistryReader>,
+}
+
+#[cfg(feature = "registry")]
+impl RegistryEndpointResolver {
+    /// Creates a new `RegistryEndpointResolver` that looks up nodes in the given registry.
+    pub fn new(registry: Box<dyn RegistryReader>) -> Self {
+        Self { registry }
+    }
+}
+
+#[cfg(feature = "registry")]
+impl EndpointResolver for RegistryEndpointResolver {
+    fn accepts(&self, endpoint: &str) -> bool {
+        endpoint.starts_with(NODE_PREFIX)
+    }
+
+    fn resolve(&self, endpoint: &str) -> Result<Vec<String>, ConnectError> {
+        let identity = &endpoint[NODE_PREFIX.len()..];
+        if identity.is_empty() {
+            return Err(ConnectError::ParseError(format!(
+                "No node identity provided in {}",
+                endpoint
+            )));
+        }
+
+        self.registry
+            .get_node(identity)
+            .map_err(|err| {
+                ConnectError::ProtocolError(format!("Unable to resolve {}: {}", endpoint, err))
+            })?
+            .map(|node| node.endpoints)
+            .ok_or_else(|| {
+                ConnectError::ProtocolError(format!(
+                    "Unable to resolve {}: node is not in the registry",
+                    endpoint
+                ))
+            })
+    }
+}
+
+/// The fields of a DNS SRV record that are used to order the resolved endpoints
+struct SrvRecord {
+    priority: u16,
+    weight: u16,
+    target: String,
+    port: u16,
+}
+
+/// Splits an endpoint of the form `srv+<protocol>://<name>` into its protocol and name.
+fn parse_srv_endpoint(endpoint: &str) -> Result<(&str, &str), ConnectError> {
+    let address = endpoint
+        .strip_prefix(SRV_PREFIX)
+        .ok_or_else(|| ConnectError::ParseError(format!("{} is not an SRV endpoint", endpoint)))?;
+
+    match address.find("://") {
+        Some(index) if index > 0 && index + 3 < address.len() => {
+            Ok((&address[..index], &address[index + 3..]))
+        }
+        _ => Err(ConnectError::ParseError(format!(
+            "Invalid SRV endpoint {}; expected srv+<protocol>://<name>",
+            endpoint
+        ))),
+    }
+}
+
+/// Orders SRV records as described in RFC 2782: by priority (lowest first), then by weighted
+/// random selection within each priority, where the chance of a record being selected next is
+/// proportional to its weight. Records with the target `.` indicate that the service is not
+/// available at the name, so they are removed. The trailing `.` of each fully-qualified target is
+/// also removed.
+fn order_srv_records<R: Rng>(mut records: Vec<SrvRecord>, rng: &mut R) -> Vec<SrvRecord> {
+    records.retain(|record| record.target != ".");
+    for record in records.iter_mut() {
+        if record.target.ends_with('.') {
+            record.target.pop();
+        }
+    }
+
+    // Records with a weight of zero are placed first in their priority, which gives them a small
+    // chance of being selected when the other records have weights.
+    records.sort_by_key(|record| (record.priority, record.weight != 0));
+
+    let mut ordered = Vec::with_capacity(records.len());
+    while !records.is_empty() {
+        let priority = records[0].priority;
+        let count = records
+            .iter()
+            .take_while(|record| record.priority == priority)
+            .count();
+        let mut unordered = records.drain(..count).collect::<Vec<_>>();
+
+        while !unordered.is_empty() {
+            let total_weight: u32 = unordered
+                .iter()
+                .map(|record| u32::from(record.weight))
+                .sum();
+            let selection = rng.gen_range(0, total_weight + 1);
+            let mut running_weight = 0;
+            let index = unordered
+                .iter()
+                .position(|record| {
+                    running_weight += u32::from(record.weight);
+                    running_weight >= selection
+                })
+                .unwrap_or(0);
+            ordered.push(unordered.remove(index));
+        }
+    }
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    /// Verify that SRV endpoints are split into their protocol and name, and that invalid SRV
+    /// endpoints are rejected.
+    #[test]
+    fn test_parse_srv_endpoint() {
+        assert_eq!(
+            parse_srv_endpoint("srv+tcps://_splinter._tcp.example.org")
+                .expect("Unable to parse endpoint"),
+            ("tcps", "_splinter._tcp.example.org")
+        );
+
+        assert!(parse_srv_endpoint("tcps://_splinter._tcp.example.org").is_err());
+        assert!(parse_srv_endpoint("srv+_splinter._tcp.example.org").is_err());
+        assert!(parse_srv_endpoint("srv+://_splinter._tcp.example.org").is_err());
+        assert!(parse_srv_endpoint("srv+tcps://").is_err());
+    }
+
+    /// Verify that SRV records are ordered by priority, that unavailable targets are removed, and
+    /// that the trailing `.` is removed from targets.
+    #[test]
+    fn test_order_srv_records() {
+        let ordered = order_srv_records(
+            vec![
+                record(30, 0, "backup.example.org."),
+                record(10, 10, "primary.example.org."),
+                record(10, 0, "."),
+                record(20, 60, "secondary.example.org."),
+            ],
+            &mut StdRng::seed_from_u64(0),
+        );
+
+        assert_eq!(
+            ordered
+                .iter()
+                .map(|record| record.target.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "primary.example.org",
+                "secondary.example.org",
+                "backup.example.org"
+            ]
+        );
+    }
+
+    /// Verify that records with the same priority are selected at random in proportion to their
+    /// weights, and that a record with a weight of zero is rarely selected first.
+    #[test]
+    fn test_order_srv_records_by_weight() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut first = HashMap::new();
+        for _ in 0..1000 {
+            let ordered = order_srv_records(
+                vec![
+                    record(10, 10, "light.example.org"),
+                    record(10, 0, "idle.example.org"),
+                    record(10, 30, "heavy.example.org"),
+                    record(20, 60, "backup.example.org"),
+                ],
+                &mut rng,
+            );
+            assert_eq!(ordered.len(), 4);
+            assert_eq!(ordered[3].target, "backup.example.org");
+            *first.entry(ordered[0].target.clone()).or_insert(0) += 1;
+        }
+
+        let heavy = first.get("heavy.example.org").copied().unwrap_or(0);
+        let light = first.get("light.example.org").copied().unwrap_or(0);
+        let idle = first.get("idle.example.org").copied().unwrap_or(0);
+        assert!(
+            heavy > 650 && heavy < 850,
+            "heavy selected first {} times",
+            heavy
+        );
+        assert!(
+            light > 150 && light < 350,
+            "light selected first {} times",
+            light
+        );
+        assert!(idle < 50, "idle selected first {} times", idle);
+    }
+
+    fn record(priority: u16, weight: u16, target: &str) -> SrvRecord {
+        SrvRecord {
+            priority,
+            weight,
+            target: target.into(),
+            port: 8044,
+        }
+    }
+}
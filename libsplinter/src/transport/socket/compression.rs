@@ -0,0 +1,428 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Payload compression for socket-based transports.
+//!
+//! Compression is negotiated per connection as part of the frame version handshake; see
+//! `CompressedFrameNegotiation`. Once a connection has agreed on an algorithm, each frame carries a
+//! flag indicating whether its payload was compressed, so that messages smaller than the
+//! configured threshold can be sent as-is. Frames compressed with any other algorithm are
+//! rejected.
+
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// The default minimum payload size, in bytes, for a message to be compressed.
+const DEFAULT_THRESHOLD: usize = 1024;
+
+/// The maximum size of a decompressed payload, in bytes.
+///
+/// A small compressed frame can expand to an arbitrarily large payload, so the size of the
+/// decompressed data is capped at the size of the largest frame that a peer is expected to send.
+pub(super) const MAX_DECOMPRESSED_LENGTH: usize = 64 * 1024 * 1024;
+
+/// A compression algorithm that may be used for frame payloads.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Compression {
+    None = 0,
+    Deflate = 1,
+    Zstd = 2,
+}
+
+impl Compression {
+    /// Return the algorithm for the given identifier, as transmitted in a frame header.
+    pub(super) fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Compression::None),
+            1 => Some(Compression::Deflate),
+            2 => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+
+    /// Return the bit that represents this algorithm during negotiation.
+    pub(super) fn flag(self) -> u16 {
+        match self {
+            Compression::None => 0,
+            algorithm => 1 << (algorithm as u16),
+        }
+    }
+
+    /// Return whether or not this algorithm is available in this build.
+    pub fn is_supported(self) -> bool {
+        match self {
+            Compression::None => true,
+            Compression::Deflate | Compression::Zstd => cfg!(feature = "socket-compression"),
+        }
+    }
+}
+
+impl std::fmt::Display for Compression {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Compression::None => f.write_str("none"),
+            Compression::Deflate => f.write_str("deflate"),
+            Compression::Zstd => f.write_str("zstd"),
+        }
+    }
+}
+
+impl std::str::FromStr for Compression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(Compression::None),
+            "deflate" => Ok(Compression::Deflate),
+            "zstd" => Ok(Compression::Zstd),
+            _ => Err(format!("Unknown compression algorithm: {}", s)),
+        }
+    }
+}
+
+/// The compression settings for a socket-based transport.
+#[derive(Clone, Debug)]
+pub struct CompressionConfig {
+    algorithms: Vec<Compression>,
+    threshold: usize,
+}
+
+impl CompressionConfig {
+    /// Construct a new `CompressionConfig` that offers the given algorithms, in order of
+    /// preference.
+    ///
+    /// Algorithms that are not supported by this build are ignored.
+    pub fn new(algorithms: Vec<Compression>) -> Self {
+        CompressionConfig {
+            algorithms: algorithms
+                .into_iter()
+                .filter(|algorithm| *algorithm != Compression::None && algorithm.is_supported())
+                .collect(),
+            threshold: DEFAULT_THRESHOLD,
+        }
+    }
+
+    /// Set the minimum payload size, in bytes, for a message to be compressed; smaller messages
+    /// are sent uncompressed.
+    pub fn with_threshold(mut self, threshold: usize) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    pub fn algorithms(&self) -> &[Compression] {
+        &self.algorithms
+    }
+
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+}
+
+impl Default for CompressionConfig {
+    /// The default configuration does not offer any compression.
+    fn default() -> Self {
+        CompressionConfig::new(vec![])
+    }
+}
+
+/// Counters describing the effect of compression on the messages sent and received by a
+/// transport.
+#[derive(Debug, Default)]
+pub struct CompressionStats {
+    sent_messages: AtomicU64,
+    sent_compressed_messages: AtomicU64,
+    sent_bytes: AtomicU64,
+    sent_wire_bytes: AtomicU64,
+    received_messages: AtomicU64,
+    received_compressed_messages: AtomicU64,
+    received_bytes: AtomicU64,
+    received_wire_bytes: AtomicU64,
+}
+
+impl CompressionStats {
+    /// The number of messages sent.
+    pub fn sent_messages(&self) -> u64 {
+        self.sent_messages.load(Ordering::Relaxed)
+    }
+
+    /// The number of messages sent with a compressed payload.
+    pub fn sent_compressed_messages(&self) -> u64 {
+        self.sent_compressed_messages.load(Ordering::Relaxed)
+    }
+
+    /// The total size of the payloads sent, before compression.
+    pub fn sent_bytes(&self) -> u64 {
+        self.sent_bytes.load(Ordering::Relaxed)
+    }
+
+    /// The total size of the payloads sent, as written to the connection.
+    pub fn sent_wire_bytes(&self) -> u64 {
+        self.sent_wire_bytes.load(Ordering::Relaxed)
+    }
+
+    /// The number of messages received.
+    pub fn received_messages(&self) -> u64 {
+        self.received_messages.load(Ordering::Relaxed)
+    }
+
+    /// The number of messages received with a compressed payload.
+    pub fn received_compressed_messages(&self) -> u64 {
+        self.received_compressed_messages.load(Ordering::Relaxed)
+    }
+
+    /// The total size of the payloads received, after decompression.
+    pub fn received_bytes(&self) -> u64 {
+        self.received_bytes.load(Ordering::Relaxed)
+    }
+
+    /// The total size of the payloads received, as read from the connection.
+    pub fn received_wire_bytes(&self) -> u64 {
+        self.received_wire_bytes.load(Ordering::Relaxed)
+    }
+
+    /// The ratio of bytes written to the connection to the size of the payloads sent.
+    ///
+    /// A value below `1.0` indicates that compression has reduced the amount of data sent.
+    /// Returns `None` if nothing has been sent.
+    pub fn sent_ratio(&self) -> Option<f64> {
+        ratio(self.sent_wire_bytes(), self.sent_bytes())
+    }
+
+    /// The ratio of bytes read from the connection to the size of the payloads received.
+    ///
+    /// Returns `None` if nothing has been received.
+    pub fn received_ratio(&self) -> Option<f64> {
+        ratio(self.received_wire_bytes(), self.received_bytes())
+    }
+
+    fn record(
+        messages: &AtomicU64,
+        compressed_messages: &AtomicU64,
+        bytes: &AtomicU64,
+        wire_bytes: &AtomicU64,
+        length: usize,
+        wire_length: usize,
+        compressed: bool,
+    ) {
+        messages.fetch_add(1, Ordering::Relaxed);
+        if compressed {
+            compressed_messages.fetch_add(1, Ordering::Relaxed);
+        }
+        bytes.fetch_add(length as u64, Ordering::Relaxed);
+        wire_bytes.fetch_add(wire_length as u64, Ordering::Relaxed);
+    }
+}
+
+fn ratio(wire_bytes: u64, bytes: u64) -> Option<f64> {
+    if bytes == 0 {
+        None
+    } else {
+        Some(wire_bytes as f64 / bytes as f64)
+    }
+}
+
+/// The compression state of a single connection.
+pub(super) struct ConnectionCompression {
+    algorithm: Compression,
+    threshold: usize,
+    stats: Arc<CompressionStats>,
+}
+
+impl ConnectionCompression {
+    pub(super) fn new(
+        algorithm: Compression,
+        threshold: usize,
+        stats: Arc<CompressionStats>,
+    ) -> Self {
+        ConnectionCompression {
+            algorithm,
+            threshold,
+            stats,
+        }
+    }
+
+    /// Return the algorithm negotiated for this connection.
+    pub(super) fn algorithm(&self) -> Compression {
+        self.algorithm
+    }
+
+    /// Return the algorithm to use for a message with the given length.
+    pub(super) fn select(&self, length: usize) -> Compression {
+        if length < self.threshold {
+            Compression::None
+        } else {
+            self.algorithm
+        }
+    }
+
+    pub(super) fn record_sent(&self, length: usize, wire_length: usize, compressed: bool) {
+        let stats = &self.stats;
+        CompressionStats::record(
+            &stats.sent_messages,
+            &stats.sent_compressed_messages,
+            &stats.sent_bytes,
+            &stats.sent_wire_bytes,
+            length,
+            wire_length,
+            compressed,
+        );
+    }
+
+    pub(super) fn record_received(&self, length: usize, wire_length: usize, compressed: bool) {
+        let stats = &self.stats;
+        CompressionStats::record(
+            &stats.received_messages,
+            &stats.received_compressed_messages,
+            &stats.received_bytes,
+            &stats.received_wire_bytes,
+            length,
+            wire_length,
+            compressed,
+        );
+    }
+}
+
+/// Compress the data with the given algorithm.
+pub(super) fn compress(algorithm: Compression, data: &[u8]) -> io::Result<Vec<u8>> {
+    match algorithm {
+        Compression::None => Ok(data.to_vec()),
+        #[cfg(feature = "socket-compression")]
+        Compression::Deflate => {
+            use std::io::Write;
+
+            let mut encoder =
+                flate2::write::DeflateEncoder::new(vec![], flate2::Compression::default());
+            encoder.write_all(data)?;
+            encoder.finish()
+        }
+        #[cfg(feature = "socket-compression")]
+        Compression::Zstd => zstd::stream::encode_all(data, 0),
+        #[cfg(not(feature = "socket-compression"))]
+        _ => Err(unsupported(algorithm)),
+    }
+}
+
+/// Decompress the data with the given algorithm.
+pub(super) fn decompress(algorithm: Compression, data: &[u8]) -> io::Result<Vec<u8>> {
+    match algorithm {
+        Compression::None => Ok(data.to_vec()),
+        #[cfg(feature = "socket-compression")]
+        Compression::Deflate => {
+            use std::io::Read;
+
+            let mut buffer = vec![];
+            flate2::read::DeflateDecoder::new(data)
+                .take(MAX_DECOMPRESSED_LENGTH as u64 + 1)
+                .read_to_end(&mut buffer)?;
+            check_decompressed_length(buffer)
+        }
+        #[cfg(feature = "socket-compression")]
+        Compression::Zstd => {
+            use std::io::Read;
+
+            let mut buffer = vec![];
+            zstd::stream::read::Decoder::new(data)?
+                .take(MAX_DECOMPRESSED_LENGTH as u64 + 1)
+                .read_to_end(&mut buffer)?;
+            check_decompressed_length(buffer)
+        }
+        #[cfg(not(feature = "socket-compression"))]
+        _ => Err(unsupported(algorithm)),
+    }
+}
+
+/// Reject a decompressed payload that exceeds the maximum length, rather than truncating it.
+#[cfg(feature = "socket-compression")]
+fn check_decompressed_length(buffer: Vec<u8>) -> io::Result<Vec<u8>> {
+    if buffer.len() > MAX_DECOMPRESSED_LENGTH {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Decompressed payload exceeds the maximum length of {} bytes",
+                MAX_DECOMPRESSED_LENGTH
+            ),
+        ))
+    } else {
+        Ok(buffer)
+    }
+}
+
+#[cfg(not(feature = "socket-compression"))]
+fn unsupported(algorithm: Compression) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Compression algorithm {} is not supported", algorithm),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that messages below the threshold are not compressed.
+    #[test]
+    fn test_threshold() {
+        let compression =
+            ConnectionCompression::new(Compression::Deflate, 16, Arc::new(Default::default()));
+        assert_eq!(Compression::None, compression.select(15));
+        assert_eq!(Compression::Deflate, compression.select(16));
+    }
+
+    /// Verify that the stats record the ratio of bytes written to bytes sent.
+    #[test]
+    fn test_stats_ratio() {
+        let stats = Arc::new(CompressionStats::default());
+        let compression = ConnectionCompression::new(Compression::Zstd, 0, stats.clone());
+        assert_eq!(None, stats.sent_ratio());
+
+        compression.record_sent(100, 25, true);
+        compression.record_sent(100, 100, false);
+
+        assert_eq!(2, stats.sent_messages());
+        assert_eq!(1, stats.sent_compressed_messages());
+        assert_eq!(Some(0.625), stats.sent_ratio());
+        assert_eq!(None, stats.received_ratio());
+    }
+
+    /// Verify that each supported algorithm round-trips a payload.
+    #[cfg(feature = "socket-compression")]
+    #[test]
+    fn test_round_trip() {
+        let data = b"splinter".repeat(512);
+        for algorithm in &[Compression::Deflate, Compression::Zstd] {
+            let compressed = compress(*algorithm, &data).expect("Unable to compress");
+            assert!(compressed.len() < data.len());
+            assert_eq!(
+                data,
+                decompress(*algorithm, &compressed).expect("Unable to decompress")
+            );
+        }
+    }
+
+    /// Verify that a payload which decompresses beyond the maximum length is rejected, rather
+    /// than truncated.
+    #[cfg(feature = "socket-compression")]
+    #[test]
+    fn test_decompressed_length_limit() {
+        let data = vec![0u8; MAX_DECOMPRESSED_LENGTH + 1];
+        for algorithm in &[Compression::Deflate, Compression::Zstd] {
+            let compressed = compress(*algorithm, &data).expect("Unable to compress");
+            match decompress(*algorithm, &compressed) {
+                Err(err) => assert_eq!(io::ErrorKind::InvalidData, err.kind()),
+                Ok(_) => panic!("Should not have decompressed an oversized payload"),
+            }
+        }
+    }
+}
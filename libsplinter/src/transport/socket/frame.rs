@@ -18,7 +18,10 @@ use std::time::Duration;
 
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 
+use super::compression::{self, Compression};
+
 const HEADER_LENGTH: usize = 6;
+const HEADER_LENGTH_V2: usize = 7;
 
 /// An error that may be returned during frame-related operations
 #[derive(Debug)]
@@ -27,6 +30,7 @@ pub enum FrameError {
     InvalidChecksum,
     InvalidHeaderLength(usize),
     UnsupportedVersion,
+    UnsupportedCompression(u8),
     HandshakeFailure(String),
 }
 
@@ -41,6 +45,9 @@ impl std::fmt::Display for FrameError {
                 HEADER_LENGTH, n
             ),
             FrameError::UnsupportedVersion => f.write_str("Unsupported frame version"),
+            FrameError::UnsupportedCompression(id) => {
+                write!(f, "Unsupported frame compression algorithm {}", id)
+            }
             FrameError::HandshakeFailure(msg) => f.write_str(&msg),
         }
     }
@@ -53,6 +60,7 @@ impl std::error::Error for FrameError {
             FrameError::InvalidChecksum => None,
             FrameError::InvalidHeaderLength(_) => None,
             FrameError::UnsupportedVersion => None,
+            FrameError::UnsupportedCompression(_) => None,
             FrameError::HandshakeFailure(_) => None,
         }
     }
@@ -68,9 +76,24 @@ impl From<io::Error> for FrameError {
 ///
 /// This specifies the version of the frame, based on what value is sent during frame transmission.
 /// It indicates header style and data requirements.
+///
+/// - V1: a frame header contains the length of the data.
+/// - V2: a frame header contains the length of the data, and the compression algorithm used for
+///   the data, if any. The algorithm is agreed upon during frame negotiation.
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum FrameVersion {
     V1 = 1,
+    V2 = 2,
+}
+
+impl FrameVersion {
+    fn from_u16(version: u16) -> Option<Self> {
+        match version {
+            1 => Some(FrameVersion::V1),
+            2 => Some(FrameVersion::V2),
+            _ => None,
+        }
+    }
 }
 
 impl std::fmt::Display for FrameVersion {
@@ -84,6 +107,8 @@ impl std::fmt::Display for FrameVersion {
 /// This struct owns the data that has been transmitted.  It is essentially a receiving frame.
 pub struct Frame {
     data: Vec<u8>,
+    wire_length: usize,
+    compressed: bool,
 }
 
 impl Frame {
@@ -92,16 +117,49 @@ impl Frame {
         self.data
     }
 
-    /// Read a frame from the given reader.
+    /// The length of the data as it was transmitted, which differs from the length of the data
+    /// if it was compressed.
+    pub fn wire_length(&self) -> usize {
+        self.wire_length
+    }
+
+    /// Whether or not the data was compressed when it was transmitted.
+    pub fn is_compressed(&self) -> bool {
+        self.compressed
+    }
+
+    /// Read an uncompressed frame from the given reader.
     ///
     /// # Errors
     ///
     /// This function returns an error if:
     ///
     /// - the header is malformed
+    /// - the frame is compressed
     /// - the data length doesn't match the header length
     /// - an IO error occurs
     pub fn read<R: Read>(reader: &mut R) -> Result<Self, FrameError> {
+        Self::read_compressed(reader, Compression::None)
+    }
+
+    /// Read a frame from the given reader, which may be compressed with the given algorithm.
+    ///
+    /// The algorithm should be the one negotiated for the connection; frames compressed with any
+    /// other algorithm are rejected.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if:
+    ///
+    /// - the header is malformed
+    /// - the frame is compressed with an algorithm other than the given one
+    /// - the data length doesn't match the header length
+    /// - the data cannot be decompressed, or exceeds the maximum decompressed length
+    /// - an IO error occurs
+    pub fn read_compressed<R: Read>(
+        reader: &mut R,
+        compression: Compression,
+    ) -> Result<Self, FrameError> {
         let frame_header = loop {
             match FrameHeader::read(reader) {
                 Err(FrameError::IoError(ref e)) if e.kind() == io::ErrorKind::WouldBlock => {
@@ -113,35 +171,64 @@ impl Frame {
             };
         };
 
-        match frame_header {
-            FrameHeader::V1 { length } => {
-                let mut buffer = vec![0; length as usize];
-                let mut remaining = &mut buffer[..];
-
-                while !remaining.is_empty() {
-                    match reader.read(remaining) {
-                        Ok(0) => break,
-                        Ok(n) => {
-                            let tmp = remaining;
-                            remaining = &mut tmp[n..];
-                        }
-                        Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => {}
-                        Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
-                            thread::sleep(Duration::from_millis(100));
-                        }
-                        Err(e) => return Err(FrameError::IoError(e)),
-                    }
+        let (length, frame_header_compression) = match frame_header {
+            FrameHeader::V1 { length } => (length, Compression::None),
+            FrameHeader::V2 {
+                length,
+                compression,
+            } => (length, compression),
+        };
+
+        let compression = match frame_header_compression {
+            Compression::None => Compression::None,
+            algorithm if algorithm == compression => {
+                if length as usize > compression::MAX_DECOMPRESSED_LENGTH {
+                    return Err(FrameError::IoError(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "Compressed frame exceeds the maximum length",
+                    )));
                 }
-                if !remaining.is_empty() {
-                    Err(FrameError::IoError(io::Error::new(
-                        io::ErrorKind::UnexpectedEof,
-                        "Could not receive complete frame",
-                    )))
-                } else {
-                    Ok(Self { data: buffer })
+                algorithm
+            }
+            algorithm => return Err(FrameError::UnsupportedCompression(algorithm as u8)),
+        };
+
+        let mut buffer = vec![0; length as usize];
+        let mut remaining = &mut buffer[..];
+
+        while !remaining.is_empty() {
+            match reader.read(remaining) {
+                Ok(0) => break,
+                Ok(n) => {
+                    let tmp = remaining;
+                    remaining = &mut tmp[n..];
+                }
+                Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    thread::sleep(Duration::from_millis(100));
                 }
+                Err(e) => return Err(FrameError::IoError(e)),
             }
         }
+        if !remaining.is_empty() {
+            return Err(FrameError::IoError(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Could not receive complete frame",
+            )));
+        }
+
+        match compression {
+            Compression::None => Ok(Self {
+                data: buffer,
+                wire_length: length as usize,
+                compressed: false,
+            }),
+            algorithm => Ok(Self {
+                data: compression::decompress(algorithm, &buffer)?,
+                wire_length: length as usize,
+                compressed: true,
+            }),
+        }
     }
 }
 
@@ -151,23 +238,61 @@ impl Frame {
 pub struct FrameRef<'a> {
     version: FrameVersion,
     data: &'a [u8],
+    compression: Compression,
 }
 
 impl<'a> FrameRef<'a> {
     /// Construct a FrameRef for the given byte slice, which will be transmitted using the given
     /// frame version.
     pub fn new<'b: 'a>(version: FrameVersion, data: &'b [u8]) -> FrameRef<'a> {
-        Self { version, data }
+        Self {
+            version,
+            data,
+            compression: Compression::None,
+        }
+    }
+
+    /// Compress the data with the given algorithm when it is transmitted.
+    ///
+    /// Compression is only supported by frame version 2 and later; the data is sent uncompressed
+    /// when using an earlier version.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
     }
 
     /// Write the frame to the given writer.
     ///
     /// # Errors
     ///
-    /// Returns a FrameError if an IO error occurs.
+    /// Returns a FrameError if the data cannot be compressed, or if an IO error occurs.
     pub fn write<W: Write>(self, writer: &mut W) -> Result<(), FrameError> {
-        let frame_header = match self.version {
-            FrameVersion::V1 => FrameHeader::v1(self.data.len() as u32),
+        self.write_counted(writer).map(|_| ())
+    }
+
+    /// Write the frame to the given writer.
+    ///
+    /// Returns the number of bytes of data written, which differs from the length of the data if
+    /// it was compressed.
+    ///
+    /// # Errors
+    ///
+    /// Returns a FrameError if the data cannot be compressed, or if an IO error occurs.
+    pub fn write_counted<W: Write>(self, writer: &mut W) -> Result<usize, FrameError> {
+        let compressed;
+        let (frame_header, data) = match (self.version, self.compression) {
+            (FrameVersion::V1, _) => (FrameHeader::v1(self.data.len() as u32), self.data),
+            (FrameVersion::V2, Compression::None) => (
+                FrameHeader::v2(self.data.len() as u32, Compression::None),
+                self.data,
+            ),
+            (FrameVersion::V2, algorithm) => {
+                compressed = compression::compress(algorithm, self.data)?;
+                (
+                    FrameHeader::v2(compressed.len() as u32, algorithm),
+                    &compressed[..],
+                )
+            }
         };
         loop {
             match frame_header.write(writer) {
@@ -180,7 +305,7 @@ impl<'a> FrameRef<'a> {
             }
         }
 
-        let mut buffer = &self.data[..];
+        let mut buffer = &data[..];
         while !buffer.is_empty() {
             match writer.write(buffer) {
                 Ok(0) => {
@@ -198,7 +323,7 @@ impl<'a> FrameRef<'a> {
             }
         }
         writer.flush()?;
-        Ok(())
+        Ok(data.len())
     }
 }
 
@@ -207,7 +332,13 @@ impl<'a> FrameRef<'a> {
 /// Each variant corresponds to the implementation for a given version.
 #[derive(Debug, PartialEq)]
 enum FrameHeader {
-    V1 { length: u32 },
+    V1 {
+        length: u32,
+    },
+    V2 {
+        length: u32,
+        compression: Compression,
+    },
 }
 
 impl FrameHeader {
@@ -216,6 +347,14 @@ impl FrameHeader {
         FrameHeader::V1 { length }
     }
 
+    /// Construct a version 2 frame header.
+    fn v2(length: u32, compression: Compression) -> Self {
+        FrameHeader::V2 {
+            length,
+            compression,
+        }
+    }
+
     /// Read a FrameHeader from the given reader.
     ///
     /// This function uses the first 2 bytes of the stream to read the version, and constructs the
@@ -228,6 +367,7 @@ impl FrameHeader {
     /// - the version received does not match any of the existing variants
     /// - the frame header is not the proper length
     /// - the frame version fails its checksum
+    /// - the compression algorithm is not supported
     /// - an IO error occurs
     fn read<R: Read>(reader: &mut R) -> Result<Self, FrameError> {
         let version = reader.read_u16::<BigEndian>()?;
@@ -252,6 +392,33 @@ impl FrameHeader {
                     length: cursor.read_u32::<BigEndian>()?,
                 })
             }
+            2 => {
+                // Header length + checksum byte
+                let mut buffer = [0u8; HEADER_LENGTH_V2 + 1];
+                let mut cursor = Cursor::new(&mut buffer[..]);
+                cursor.write_u16::<BigEndian>(2u16)?;
+
+                let n = reader.read(&mut cursor.get_mut()[std::mem::size_of::<u16>()..])?;
+                if n != HEADER_LENGTH_V2 + 1 - std::mem::size_of::<u16>() {
+                    return Err(FrameError::InvalidHeaderLength(n));
+                }
+
+                let checksum = compute_checksum(&cursor.get_ref()[..HEADER_LENGTH_V2]);
+                if checksum != cursor.get_ref()[HEADER_LENGTH_V2] {
+                    return Err(FrameError::InvalidChecksum);
+                }
+
+                let length = cursor.read_u32::<BigEndian>()?;
+                let compression_id = cursor.read_u8()?;
+                let compression = Compression::from_id(compression_id)
+                    .filter(|compression| compression.is_supported())
+                    .ok_or(FrameError::UnsupportedCompression(compression_id))?;
+
+                Ok(FrameHeader::V2 {
+                    length,
+                    compression,
+                })
+            }
             _ => Err(FrameError::UnsupportedVersion),
         }
     }
@@ -279,6 +446,22 @@ impl FrameHeader {
 
                 writer.write_all(&cursor.into_inner()[..])?;
             }
+            FrameHeader::V2 {
+                length,
+                compression,
+            } => {
+                let mut header_bytes = [0u8; HEADER_LENGTH_V2 + 1];
+                let mut cursor = Cursor::new(&mut header_bytes[..]);
+
+                cursor.write_u16::<BigEndian>(2)?;
+                cursor.write_u32::<BigEndian>(length)?;
+                cursor.write_u8(compression as u8)?;
+
+                cursor.get_mut()[HEADER_LENGTH_V2] =
+                    compute_checksum(&cursor.get_ref()[..HEADER_LENGTH_V2]);
+
+                writer.write_all(&cursor.into_inner()[..])?;
+            }
         }
 
         Ok(())
@@ -384,6 +567,189 @@ impl FrameNegotiation {
     }
 }
 
+/// The frame version and compression algorithm agreed upon by a `CompressedFrameNegotiation`.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct NegotiatedFrame {
+    pub version: FrameVersion,
+    pub compression: Compression,
+}
+
+/// Negotiate the frame version, and the compression algorithm for frame payloads, for a given
+/// socket connection.
+///
+/// If both ends agree on frame version 2 or later, they then negotiate a compression algorithm:
+/// the outbound end transmits the set of algorithms it supports, and the inbound end replies with
+/// its most-preferred algorithm from that set, or `0` (no compression) if there are none in
+/// common.
+pub enum CompressedFrameNegotiation {
+    /// The Outbound variant transmits the min and max supported version, and expects to receive
+    /// either a version in that range, or `0` if the other end cannot support the a version in
+    /// that range.
+    Outbound {
+        min: FrameVersion,
+        max: FrameVersion,
+        compression: Vec<Compression>,
+    },
+    /// The Inbound variant receives the min and max and replies with the highest version it
+    /// supports in that range, or `0` if it does not support any version in that range.
+    Inbound {
+        min: FrameVersion,
+        max: FrameVersion,
+        compression: Vec<Compression>,
+    },
+}
+
+impl CompressedFrameNegotiation {
+    /// Construct the outbound side of a negotiation with the given min,max.
+    pub fn outbound(min: FrameVersion, max: FrameVersion) -> Self {
+        CompressedFrameNegotiation::Outbound {
+            min,
+            max,
+            compression: vec![],
+        }
+    }
+
+    /// Construct the inbound side of a negotiation with the given min,max.
+    pub fn inbound(min: FrameVersion, max: FrameVersion) -> Self {
+        CompressedFrameNegotiation::Inbound {
+            min,
+            max,
+            compression: vec![],
+        }
+    }
+
+    /// Offer the given compression algorithms, in order of preference, during negotiation.
+    pub fn with_compression(mut self, algorithms: &[Compression]) -> Self {
+        match &mut self {
+            CompressedFrameNegotiation::Outbound { compression, .. }
+            | CompressedFrameNegotiation::Inbound { compression, .. } => {
+                *compression = algorithms.to_vec();
+            }
+        }
+        self
+    }
+
+    /// Negotiate the frame version and compression algorithm to use for future communications
+    /// over the given stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns a FrameError if:
+    ///
+    /// - either end cannot agree on a version
+    /// - the other end selects a compression algorithm that was not offered
+    /// - an IO error, if one occurs
+    pub fn negotiate<S: Read + Write>(self, stream: &mut S) -> Result<NegotiatedFrame, FrameError> {
+        match self {
+            CompressedFrameNegotiation::Outbound {
+                min,
+                max,
+                compression,
+            } => {
+                stream
+                    .write_u16::<BigEndian>(min as u16)
+                    .map_err(FrameNegotiation::map_io_err)?;
+                stream
+                    .write_u16::<BigEndian>(max as u16)
+                    .map_err(FrameNegotiation::map_io_err)?;
+
+                let frame_version = stream
+                    .read_u16::<BigEndian>()
+                    .map_err(FrameNegotiation::map_io_err)?;
+
+                let version = FrameVersion::from_u16(frame_version)
+                    .filter(|version| {
+                        *version as u16 >= min as u16 && *version as u16 <= max as u16
+                    })
+                    .ok_or(FrameError::UnsupportedVersion)?;
+
+                if version == FrameVersion::V1 {
+                    return Ok(NegotiatedFrame {
+                        version,
+                        compression: Compression::None,
+                    });
+                }
+
+                let offered = compression
+                    .iter()
+                    .fold(0u16, |flags, algorithm| flags | algorithm.flag());
+                stream
+                    .write_u16::<BigEndian>(offered)
+                    .map_err(FrameNegotiation::map_io_err)?;
+
+                let selected = stream
+                    .read_u16::<BigEndian>()
+                    .map_err(FrameNegotiation::map_io_err)?;
+                // the selected algorithm must be one that was offered
+                let compression = Compression::from_id(selected as u8)
+                    .filter(|_| selected <= u16::from(u8::MAX))
+                    .filter(|algorithm| {
+                        *algorithm == Compression::None || compression.contains(algorithm)
+                    })
+                    .ok_or(FrameError::UnsupportedCompression(selected as u8))?;
+
+                Ok(NegotiatedFrame {
+                    version,
+                    compression,
+                })
+            }
+            CompressedFrameNegotiation::Inbound {
+                min,
+                max,
+                compression,
+            } => {
+                let remote_min = stream
+                    .read_u16::<BigEndian>()
+                    .map_err(FrameNegotiation::map_io_err)?;
+                let remote_max = stream
+                    .read_u16::<BigEndian>()
+                    .map_err(FrameNegotiation::map_io_err)?;
+
+                let version = FrameVersion::from_u16(remote_max.min(max as u16))
+                    .filter(|version| *version as u16 >= remote_min.max(min as u16));
+
+                let version = match version {
+                    Some(version) => {
+                        stream
+                            .write_u16::<BigEndian>(version as u16)
+                            .map_err(FrameNegotiation::map_io_err)?;
+                        version
+                    }
+                    None => {
+                        stream
+                            .write_u16::<BigEndian>(0)
+                            .map_err(FrameNegotiation::map_io_err)?;
+                        return Err(FrameError::UnsupportedVersion);
+                    }
+                };
+
+                if version == FrameVersion::V1 {
+                    return Ok(NegotiatedFrame {
+                        version,
+                        compression: Compression::None,
+                    });
+                }
+
+                let offered = stream
+                    .read_u16::<BigEndian>()
+                    .map_err(FrameNegotiation::map_io_err)?;
+                let compression = compression
+                    .into_iter()
+                    .find(|algorithm| offered & algorithm.flag() != 0)
+                    .unwrap_or(Compression::None);
+                stream
+                    .write_u16::<BigEndian>(compression as u16)
+                    .map_err(FrameNegotiation::map_io_err)?;
+
+                Ok(NegotiatedFrame {
+                    version,
+                    compression,
+                })
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -498,10 +864,53 @@ mod tests {
             .expect("Unable to write frame header");
 
         header_cursor.set_position(0);
-        let FrameHeader::V1 { length } =
-            FrameHeader::read(&mut header_cursor).expect("Unable to read header");
+        let frame_header = FrameHeader::read(&mut header_cursor).expect("Unable to read header");
 
-        assert_eq!(100, length);
+        assert_eq!(FrameHeader::v1(100), frame_header);
+    }
+
+    /// Test a round-trip write and read of a v2 FrameHeader, including its compression
+    /// algorithm.
+    #[test]
+    fn round_trip_v2() {
+        let header_bytes = vec![0u8; HEADER_LENGTH_V2 + 1];
+        let mut header_cursor = Cursor::new(header_bytes);
+
+        FrameHeader::v2(100, Compression::None)
+            .write(&mut header_cursor)
+            .expect("Unable to write frame header");
+
+        header_cursor.set_position(0);
+        let frame_header = FrameHeader::read(&mut header_cursor).expect("Unable to read header");
+
+        assert_eq!(FrameHeader::v2(100, Compression::None), frame_header);
+    }
+
+    /// Test that a v2 frame header with an unknown compression algorithm returns an error when
+    /// read.
+    #[test]
+    fn unknown_compression() {
+        let header_bytes = vec![0u8; HEADER_LENGTH_V2 + 1];
+        let mut header_cursor = Cursor::new(header_bytes);
+
+        header_cursor
+            .write_u16::<BigEndian>(2)
+            .expect("Could not write version to cursor");
+        header_cursor
+            .write_u32::<BigEndian>(2)
+            .expect("Could not write length to cursor");
+        header_cursor
+            .write_u8(9)
+            .expect("Could not write compression to cursor");
+        header_cursor.get_mut()[HEADER_LENGTH_V2] =
+            compute_checksum(&header_cursor.get_ref()[..HEADER_LENGTH_V2]);
+        header_cursor.set_position(0);
+
+        match FrameHeader::read(&mut header_cursor) {
+            Ok(_) => panic!("Should not have produced a frame header"),
+            Err(FrameError::UnsupportedCompression(9)) => (),
+            Err(err) => panic!("Produced invalid error: {}", err),
+        }
     }
 
     /// Test that outbound frame version negotiation works:
@@ -537,6 +946,83 @@ mod tests {
         assert_eq!(FrameVersion::V1, remote_res);
     }
 
+    /// Test that an outbound end that supports v2 negotiates v1 with an inbound end that only
+    /// supports v1, and that no compression is used.
+    #[test]
+    fn outbound_negotiation_v1_fallback() {
+        let (mut tx, mut rx) = stream::byte_stream_pair();
+
+        let (done_tx, done_rx) = std::sync::mpsc::channel();
+        let join_handle = thread::spawn(move || {
+            let res = CompressedFrameNegotiation::inbound(FrameVersion::V1, FrameVersion::V1)
+                .negotiate(&mut rx)
+                .expect("Should have successfully negotiated");
+
+            done_rx.recv().unwrap();
+
+            res
+        });
+
+        let negotiated = CompressedFrameNegotiation::outbound(FrameVersion::V1, FrameVersion::V2)
+            .with_compression(&[Compression::Zstd])
+            .negotiate(&mut tx)
+            .expect("Unable to negotiate a valid version");
+
+        done_tx.send(1u8).expect("unable to send stop signal");
+
+        let remote_res = join_handle.join().expect("Unable to join thread");
+
+        assert_eq!(
+            NegotiatedFrame {
+                version: FrameVersion::V1,
+                compression: Compression::None,
+            },
+            negotiated
+        );
+        assert_eq!(negotiated, remote_res);
+    }
+
+    /// Test that v2 negotiation selects the inbound end's most-preferred compression algorithm
+    /// that the outbound end offered:
+    /// 1. Create a stream pair
+    /// 2. Send one end to a thread, to act as the inbound receiver, preferring deflate over zstd
+    /// 3. Create an outbound negotiation offering only zstd
+    /// 4. Verify that both ends agree on v2 with zstd.
+    #[test]
+    fn compression_negotiation() {
+        let (mut tx, mut rx) = stream::byte_stream_pair();
+
+        let (done_tx, done_rx) = std::sync::mpsc::channel();
+        let join_handle = thread::spawn(move || {
+            let res = CompressedFrameNegotiation::inbound(FrameVersion::V1, FrameVersion::V2)
+                .with_compression(&[Compression::Deflate, Compression::Zstd])
+                .negotiate(&mut rx)
+                .expect("Should have successfully negotiated");
+
+            done_rx.recv().unwrap();
+
+            res
+        });
+
+        let negotiated = CompressedFrameNegotiation::outbound(FrameVersion::V1, FrameVersion::V2)
+            .with_compression(&[Compression::Zstd])
+            .negotiate(&mut tx)
+            .expect("Unable to negotiate a valid version");
+
+        done_tx.send(1u8).expect("unable to send stop signal");
+
+        let remote_res = join_handle.join().expect("Unable to join thread");
+
+        assert_eq!(
+            NegotiatedFrame {
+                version: FrameVersion::V2,
+                compression: Compression::Zstd,
+            },
+            negotiated
+        );
+        assert_eq!(negotiated, remote_res);
+    }
+
     /// Test that outbound frame version negotiation works:
     /// 1. Create a stream pair
     /// 2. Send one end to a thread, to act as the inbound receiver - this stream will return no
@@ -676,6 +1162,59 @@ mod tests {
         assert_eq!(input.to_vec(), frame.data);
     }
 
+    /// Write a compressed v2 frame to a stream and verify that the data read back from the stream
+    /// is decompressed.
+    #[cfg(feature = "socket-compression")]
+    #[test]
+    fn compressed_frame_round_trip() {
+        let input = b"hello world".repeat(100);
+        let frame_ref =
+            FrameRef::new(FrameVersion::V2, &input).with_compression(Compression::Deflate);
+
+        let mut cursor = Cursor::new(vec![]);
+
+        let written = frame_ref
+            .write_counted(&mut cursor)
+            .expect("Unable to write data");
+        assert!(written < input.len());
+
+        cursor.set_position(0);
+
+        let frame = Frame::read_compressed(&mut cursor, Compression::Deflate)
+            .expect("Unable to read frame");
+
+        assert!(frame.is_compressed());
+        assert_eq!(written, frame.wire_length());
+        assert_eq!(input, frame.into_inner());
+    }
+
+    /// Write a compressed v2 frame to a stream and verify that it is rejected when it is read
+    /// back without compression, or with a different algorithm than the one negotiated.
+    #[cfg(feature = "socket-compression")]
+    #[test]
+    fn unnegotiated_compression_rejected() {
+        let input = b"hello world".repeat(100);
+        let mut cursor = Cursor::new(vec![]);
+        FrameRef::new(FrameVersion::V2, &input)
+            .with_compression(Compression::Deflate)
+            .write(&mut cursor)
+            .expect("Unable to write data");
+
+        cursor.set_position(0);
+        match Frame::read(&mut cursor) {
+            Err(FrameError::UnsupportedCompression(1)) => (),
+            Err(err) => panic!("Produced invalid error: {}", err),
+            Ok(_) => panic!("Should not have read a compressed frame"),
+        }
+
+        cursor.set_position(0);
+        match Frame::read_compressed(&mut cursor, Compression::Zstd) {
+            Err(FrameError::UnsupportedCompression(1)) => (),
+            Err(err) => panic!("Produced invalid error: {}", err),
+            Ok(_) => panic!("Should not have read a frame compressed with deflate"),
+        }
+    }
+
     #[cfg(not(target_os = "unix"))]
     mod stream {
         use std::io::{Error as IoError, Read, Write};
@@ -12,11 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod compression;
 mod frame;
 mod tcp;
 mod tls;
 
+pub use compression::{Compression, CompressionConfig, CompressionStats};
 pub use tcp::TcpTransport;
+#[cfg(feature = "tls-cert-reload")]
+pub use tls::TlsReloadHandle;
 pub use tls::{TlsConnection, TlsInitError, TlsTransport};
 
 #[cfg(test)]
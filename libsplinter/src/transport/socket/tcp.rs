@@ -15,18 +15,39 @@
 use mio::{net::TcpStream as MioTcpStream, Evented};
 
 use std::net::{Shutdown, TcpListener as StdTcpListener, TcpStream};
+use std::sync::Arc;
 
 use crate::transport::{
     AcceptError, ConnectError, Connection, DisconnectError, ListenError, Listener, RecvError,
     SendError, Transport,
 };
 
-use super::frame::{Frame, FrameError, FrameNegotiation, FrameRef, FrameVersion};
+use super::compression::{Compression, CompressionConfig, CompressionStats, ConnectionCompression};
+use super::frame::{CompressedFrameNegotiation, Frame, FrameError, FrameRef, FrameVersion};
 
 const PROTOCOL_PREFIX: &str = "tcp://";
 
 #[derive(Default)]
-pub struct TcpTransport {}
+pub struct TcpTransport {
+    compression: CompressionConfig,
+    compression_stats: Arc<CompressionStats>,
+}
+
+impl TcpTransport {
+    /// Offer payload compression on the connections created by this transport.
+    ///
+    /// Compression is only used on a connection if the remote end also supports one of the
+    /// configured algorithms.
+    pub fn with_compression(mut self, compression: CompressionConfig) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Return the compression statistics for all of the connections created by this transport.
+    pub fn compression_stats(&self) -> Arc<CompressionStats> {
+        self.compression_stats.clone()
+    }
+}
 
 impl Transport for TcpTransport {
     fn accepts(&self, address: &str) -> bool {
@@ -49,7 +70,8 @@ impl Transport for TcpTransport {
         // Connect a std::net::TcpStream to make sure connect() block
         let mut stream = TcpStream::connect(address)?;
 
-        let frame_version = FrameNegotiation::outbound(FrameVersion::V1, FrameVersion::V1)
+        let negotiated = CompressedFrameNegotiation::outbound(FrameVersion::V1, FrameVersion::V2)
+            .with_compression(self.compression.algorithms())
             .negotiate(&mut stream)
             .map_err(|err| match err {
                 FrameError::UnsupportedVersion => ConnectError::ProtocolError(
@@ -61,8 +83,13 @@ impl Transport for TcpTransport {
 
         let mio_stream = MioTcpStream::from_stream(stream)?;
         Ok(Box::new(TcpConnection {
-            frame_version,
+            frame_version: negotiated.version,
             stream: mio_stream,
+            compression: ConnectionCompression::new(
+                negotiated.compression,
+                self.compression.threshold(),
+                self.compression_stats.clone(),
+            ),
         }))
     }
 
@@ -84,33 +111,44 @@ impl Transport for TcpTransport {
             listener: StdTcpListener::bind(address).map_err(|err| {
                 ListenError::IoError(format!("Failed to bind to {}", address), err)
             })?,
+            compression: self.compression.clone(),
+            compression_stats: self.compression_stats.clone(),
         }))
     }
 }
 
 struct TcpListener {
     listener: StdTcpListener,
+    compression: CompressionConfig,
+    compression_stats: Arc<CompressionStats>,
 }
 
 impl Listener for TcpListener {
     fn accept(&mut self) -> Result<Box<dyn Connection>, AcceptError> {
         let (mut stream, _) = self.listener.accept()?;
 
-        let frame_version = FrameNegotiation::inbound(FrameVersion::V1)
+        let negotiated = CompressedFrameNegotiation::inbound(FrameVersion::V1, FrameVersion::V2)
+            .with_compression(self.compression.algorithms())
             .negotiate(&mut stream)
             .map_err(|err| match err {
                 FrameError::UnsupportedVersion => AcceptError::ProtocolError(format!(
-                    "Local {} protocol version {} not supported by remote",
+                    "Local {} protocol versions {} to {} not supported by remote",
                     PROTOCOL_PREFIX,
-                    FrameVersion::V1
+                    FrameVersion::V1,
+                    FrameVersion::V2
                 )),
                 FrameError::IoError(err) => AcceptError::from(err),
                 err => AcceptError::ProtocolError(format!("Unexpected protocol error: {}", err)),
             })?;
 
         let connection = TcpConnection {
-            frame_version,
+            frame_version: negotiated.version,
             stream: MioTcpStream::from_stream(stream)?,
+            compression: ConnectionCompression::new(
+                negotiated.compression,
+                self.compression.threshold(),
+                self.compression_stats.clone(),
+            ),
         };
         Ok(Box::new(connection))
     }
@@ -123,22 +161,41 @@ impl Listener for TcpListener {
 struct TcpConnection {
     frame_version: FrameVersion,
     stream: MioTcpStream,
+    compression: ConnectionCompression,
 }
 
 impl Connection for TcpConnection {
     fn send(&mut self, message: &[u8]) -> Result<(), SendError> {
-        match FrameRef::new(self.frame_version, message).write(&mut self.stream) {
+        let algorithm = self.compression.select(message.len());
+        match FrameRef::new(self.frame_version, message)
+            .with_compression(algorithm)
+            .write_counted(&mut self.stream)
+        {
             Err(FrameError::IoError(e)) => Err(SendError::from(e)),
             Err(err) => Err(SendError::ProtocolError(err.to_string())),
-            Ok(_) => Ok(()),
+            Ok(written) => {
+                self.compression.record_sent(
+                    message.len(),
+                    written,
+                    algorithm != Compression::None,
+                );
+                Ok(())
+            }
         }
     }
 
     fn recv(&mut self) -> Result<Vec<u8>, RecvError> {
-        match Frame::read(&mut self.stream) {
+        match Frame::read_compressed(&mut self.stream, self.compression.algorithm()) {
             Err(FrameError::IoError(e)) => Err(RecvError::from(e)),
             Err(err) => Err(RecvError::ProtocolError(err.to_string())),
-            Ok(frame) => Ok(frame.into_inner()),
+            Ok(frame) => {
+                let wire_length = frame.wire_length();
+                let compressed = frame.is_compressed();
+                let data = frame.into_inner();
+                self.compression
+                    .record_received(data.len(), wire_length, compressed);
+                Ok(data)
+            }
         }
     }
 
@@ -196,4 +253,20 @@ mod tests {
         let transport = TcpTransport::default();
         tests::test_poll(transport, "127.0.0.1:0");
     }
+
+    /// Test that messages are compressed when both ends of a connection are configured with
+    /// compression, and that the stats record each compressed message.
+    #[cfg(feature = "socket-compression")]
+    #[test]
+    fn test_transport_compression() {
+        let transport = TcpTransport::default()
+            .with_compression(CompressionConfig::new(vec![Compression::Zstd]).with_threshold(0));
+        let stats = transport.compression_stats();
+
+        tests::test_transport(transport, "127.0.0.1:0");
+
+        assert_eq!(2, stats.sent_compressed_messages());
+        assert_eq!(2, stats.received_compressed_messages());
+        assert_eq!(6, stats.received_bytes());
+    }
 }
@@ -26,35 +26,52 @@ use std::io;
 use std::net::{Ipv4Addr, Ipv6Addr, TcpListener, TcpStream};
 use std::os::unix::io::{AsRawFd, RawFd};
 use std::path::Path;
+use std::sync::{Arc, RwLock};
 
 use crate::transport::{
     AcceptError, ConnectError, Connection, DisconnectError, ListenError, Listener, RecvError,
     SendError, Transport,
 };
 
-use super::frame::{Frame, FrameError, FrameNegotiation, FrameRef, FrameVersion};
+use super::compression::{Compression, CompressionConfig, CompressionStats, ConnectionCompression};
+use super::frame::{CompressedFrameNegotiation, Frame, FrameError, FrameRef, FrameVersion};
 
 /// tls:// is deprecated, tcps:// should be used instead
 const DEPRECATED_PROTOCOL_PREFIX: &str = "tls://";
 const PROTOCOL_PREFIX: &str = "tcps://";
 
 pub struct TlsTransport {
+    #[cfg(feature = "tls-cert-reload")]
+    files: Arc<TlsFiles>,
+    contexts: Arc<RwLock<TlsContexts>>,
+    compression: CompressionConfig,
+    compression_stats: Arc<CompressionStats>,
+}
+
+/// The certificate and key files used to build the transport's TLS contexts.
+struct TlsFiles {
+    ca_cert: Option<String>,
+    client_key: String,
+    client_cert: String,
+    server_key: String,
+    server_cert: String,
+}
+
+/// The TLS contexts used for new connections.
+///
+/// These are replaced as a whole when the certificates are reloaded; connections that have
+/// already been established keep using the context they were created with.
+struct TlsContexts {
     connector: SslConnector,
     acceptor: SslAcceptor,
 }
 
-impl TlsTransport {
-    pub fn new(
-        ca_cert: Option<String>,
-        client_key: String,
-        client_cert: String,
-        server_key: String,
-        server_cert: String,
-    ) -> Result<Self, TlsInitError> {
-        let client_cert_path = Path::new(&client_cert);
-        let client_key_path = Path::new(&client_key);
-        let server_cert_path = Path::new(&server_cert);
-        let server_key_path = Path::new(&server_key);
+impl TlsContexts {
+    fn load(files: &TlsFiles) -> Result<Self, TlsInitError> {
+        let client_cert_path = Path::new(&files.client_cert);
+        let client_key_path = Path::new(&files.client_key);
+        let server_cert_path = Path::new(&files.server_cert);
+        let server_key_path = Path::new(&files.server_key);
 
         // Build TLS Connector
         let mut connector = SslConnector::builder(SslMethod::tls())?;
@@ -70,8 +87,8 @@ impl TlsTransport {
 
         // if ca_cert is provided set as accept cert, otherwise set verify to none
         let (acceptor, connector) = {
-            if let Some(ca_cert) = ca_cert {
-                let ca_cert_path = Path::new(&ca_cert);
+            if let Some(ca_cert) = &files.ca_cert {
+                let ca_cert_path = Path::new(ca_cert);
                 acceptor.set_ca_file(ca_cert_path)?;
                 connector.set_ca_file(ca_cert_path)?;
                 connector.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
@@ -88,13 +105,88 @@ impl TlsTransport {
             }
         };
 
-        Ok(TlsTransport {
+        Ok(TlsContexts {
             connector,
             acceptor,
         })
     }
 }
 
+impl TlsTransport {
+    pub fn new(
+        ca_cert: Option<String>,
+        client_key: String,
+        client_cert: String,
+        server_key: String,
+        server_cert: String,
+    ) -> Result<Self, TlsInitError> {
+        let files = TlsFiles {
+            ca_cert,
+            client_key,
+            client_cert,
+            server_key,
+            server_cert,
+        };
+        let contexts = TlsContexts::load(&files)?;
+
+        Ok(TlsTransport {
+            #[cfg(feature = "tls-cert-reload")]
+            files: Arc::new(files),
+            contexts: Arc::new(RwLock::new(contexts)),
+            compression: CompressionConfig::default(),
+            compression_stats: Arc::new(CompressionStats::default()),
+        })
+    }
+
+    /// Offer payload compression on the connections created by this transport.
+    ///
+    /// Compression is only used on a connection if the remote end also supports one of the
+    /// configured algorithms.
+    pub fn with_compression(mut self, compression: CompressionConfig) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Return the compression statistics for all of the connections created by this transport.
+    pub fn compression_stats(&self) -> Arc<CompressionStats> {
+        self.compression_stats.clone()
+    }
+
+    /// Return a handle that reloads the certificates, keys and CA file used by this transport and
+    /// by any listeners it has created.
+    #[cfg(feature = "tls-cert-reload")]
+    pub fn reload_handle(&self) -> TlsReloadHandle {
+        TlsReloadHandle {
+            files: self.files.clone(),
+            contexts: self.contexts.clone(),
+        }
+    }
+}
+
+/// Reloads the certificates, keys and CA file of a `TlsTransport` from their files.
+///
+/// Connections that are made or accepted after a reload use the new certificates, while
+/// connections that are already established are unaffected.
+#[cfg(feature = "tls-cert-reload")]
+#[derive(Clone)]
+pub struct TlsReloadHandle {
+    files: Arc<TlsFiles>,
+    contexts: Arc<RwLock<TlsContexts>>,
+}
+
+#[cfg(feature = "tls-cert-reload")]
+impl TlsReloadHandle {
+    /// Read the certificate, key and CA files again and use them for new connections.
+    ///
+    /// If any of the files cannot be loaded, or a key does not match its certificate, an error is
+    /// returned and the previous certificates remain in use.
+    pub fn reload(&self) -> Result<(), TlsInitError> {
+        let contexts = TlsContexts::load(&self.files)?;
+        *rwlock_write_unwrap!(self.contexts) = contexts;
+        Ok(())
+    }
+}
+
 fn endpoint_to_dns_name(endpoint: &str) -> Result<String, ParseError> {
     let mut address = String::from("tcp://");
     address.push_str(endpoint);
@@ -133,10 +225,12 @@ impl Transport for TlsTransport {
 
         let dns_name = endpoint_to_dns_name(address)?;
 
+        let connector = rwlock_read_unwrap!(self.contexts).connector.clone();
         let stream = TcpStream::connect(address)?;
-        let mut tls_stream = self.connector.connect(&dns_name, stream)?;
+        let mut tls_stream = connector.connect(&dns_name, stream)?;
 
-        let frame_version = FrameNegotiation::outbound(FrameVersion::V1, FrameVersion::V1)
+        let negotiated = CompressedFrameNegotiation::outbound(FrameVersion::V1, FrameVersion::V2)
+            .with_compression(self.compression.algorithms())
             .negotiate(&mut tls_stream)
             .map_err(|err| match err {
                 FrameError::UnsupportedVersion => ConnectError::ProtocolError(
@@ -148,8 +242,13 @@ impl Transport for TlsTransport {
 
         tls_stream.get_ref().set_nonblocking(true)?;
         let connection = TlsConnection {
-            frame_version,
+            frame_version: negotiated.version,
             stream: tls_stream,
+            compression: ConnectionCompression::new(
+                negotiated.compression,
+                self.compression.threshold(),
+                self.compression_stats.clone(),
+            ),
         };
         Ok(Box::new(connection))
     }
@@ -174,28 +273,35 @@ impl Transport for TlsTransport {
             listener: TcpListener::bind(address).map_err(|err| {
                 ListenError::IoError(format!("Failed to bind to {}", address), err)
             })?,
-            acceptor: self.acceptor.clone(),
+            contexts: self.contexts.clone(),
+            compression: self.compression.clone(),
+            compression_stats: self.compression_stats.clone(),
         }))
     }
 }
 
 pub struct TlsListener {
     listener: TcpListener,
-    acceptor: SslAcceptor,
+    contexts: Arc<RwLock<TlsContexts>>,
+    compression: CompressionConfig,
+    compression_stats: Arc<CompressionStats>,
 }
 
 impl Listener for TlsListener {
     fn accept(&mut self) -> Result<Box<dyn Connection>, AcceptError> {
         let (stream, _) = self.listener.accept()?;
-        let mut tls_stream = self.acceptor.accept(stream)?;
+        let acceptor = rwlock_read_unwrap!(self.contexts).acceptor.clone();
+        let mut tls_stream = acceptor.accept(stream)?;
 
-        let frame_version = FrameNegotiation::inbound(FrameVersion::V1)
+        let negotiated = CompressedFrameNegotiation::inbound(FrameVersion::V1, FrameVersion::V2)
+            .with_compression(self.compression.algorithms())
             .negotiate(&mut tls_stream)
             .map_err(|err| match err {
                 FrameError::UnsupportedVersion => AcceptError::ProtocolError(format!(
-                    "Local {} protocol version {} not supported by remote",
+                    "Local {} protocol versions {} to {} not supported by remote",
                     PROTOCOL_PREFIX,
-                    FrameVersion::V1
+                    FrameVersion::V1,
+                    FrameVersion::V2
                 )),
                 FrameError::IoError(err) => AcceptError::from(err),
                 err => AcceptError::ProtocolError(format!("Unexpected protocol error: {}", err)),
@@ -203,8 +309,13 @@ impl Listener for TlsListener {
 
         tls_stream.get_ref().set_nonblocking(true)?;
         let connection = TlsConnection {
-            frame_version,
+            frame_version: negotiated.version,
             stream: tls_stream,
+            compression: ConnectionCompression::new(
+                negotiated.compression,
+                self.compression.threshold(),
+                self.compression_stats.clone(),
+            ),
         };
         Ok(Box::new(connection))
     }
@@ -217,22 +328,41 @@ impl Listener for TlsListener {
 pub struct TlsConnection {
     frame_version: FrameVersion,
     stream: SslStream<TcpStream>,
+    compression: ConnectionCompression,
 }
 
 impl Connection for TlsConnection {
     fn send(&mut self, message: &[u8]) -> Result<(), SendError> {
-        match FrameRef::new(self.frame_version, message).write(&mut self.stream) {
+        let algorithm = self.compression.select(message.len());
+        match FrameRef::new(self.frame_version, message)
+            .with_compression(algorithm)
+            .write_counted(&mut self.stream)
+        {
             Err(FrameError::IoError(e)) => Err(SendError::from(e)),
             Err(err) => Err(SendError::ProtocolError(err.to_string())),
-            Ok(_) => Ok(()),
+            Ok(written) => {
+                self.compression.record_sent(
+                    message.len(),
+                    written,
+                    algorithm != Compression::None,
+                );
+                Ok(())
+            }
         }
     }
 
     fn recv(&mut self) -> Result<Vec<u8>, RecvError> {
-        match Frame::read(&mut self.stream) {
+        match Frame::read_compressed(&mut self.stream, self.compression.algorithm()) {
             Err(FrameError::IoError(e)) => Err(RecvError::from(e)),
             Err(err) => Err(RecvError::ProtocolError(err.to_string())),
-            Ok(frame) => Ok(frame.into_inner()),
+            Ok(frame) => {
+                let wire_length = frame.wire_length();
+                let compressed = frame.is_compressed();
+                let data = frame.into_inner();
+                self.compression
+                    .record_received(data.len(), wire_length, compressed);
+                Ok(data)
+            }
         }
     }
 
@@ -265,6 +395,11 @@ impl TlsConnection {
         TlsConnection {
             frame_version: FrameVersion::V1,
             stream,
+            compression: ConnectionCompression::new(
+                Compression::None,
+                0,
+                Arc::new(CompressionStats::default()),
+            ),
         }
     }
 }
@@ -457,6 +592,70 @@ pub(crate) mod tests {
         tests::test_transport(transport, "127.0.0.1:0");
     }
 
+    /// Test that reloading a transport's certificates:
+    ///
+    /// 1. Succeeds when the files have been replaced with a new, valid certificate and key
+    /// 2. Fails when the files are invalid, leaving the previous certificates in use
+    /// 3. Leaves the transport usable for new connections in both cases
+    #[cfg(feature = "tls-cert-reload")]
+    #[test]
+    fn test_transport_reload() {
+        let (ca_key, ca_cert) = make_ca_cert();
+        let temp_dir = TempDir::new("tls-transport-reload-test").unwrap();
+        let temp_dir_path = temp_dir.path();
+
+        let ca_path_file = write_file(
+            temp_dir_path.to_path_buf(),
+            "ca.cert",
+            &ca_cert.to_pem().unwrap(),
+        );
+
+        let write_certs = |prefix: &str| {
+            let (key, cert) = make_ca_signed_cert(&ca_cert, &ca_key);
+            let cert_file = write_file(
+                temp_dir_path.to_path_buf(),
+                &format!("{}.cert", prefix),
+                &cert.to_pem().unwrap(),
+            );
+            let key_file = write_file(
+                temp_dir_path.to_path_buf(),
+                &format!("{}.key", prefix),
+                &key.private_key_to_pem_pkcs8().unwrap(),
+            );
+            (key_file, cert_file)
+        };
+
+        let (client_key_file, client_cert_file) = write_certs("client");
+        let (server_key_file, server_cert_file) = write_certs("server");
+
+        let transport = TlsTransport::new(
+            Some(ca_path_file),
+            client_key_file,
+            client_cert_file,
+            server_key_file,
+            server_cert_file,
+        )
+        .unwrap();
+        let reload_handle = transport.reload_handle();
+
+        // Rotate the certificates and reload them
+        write_certs("client");
+        write_certs("server");
+        reload_handle
+            .reload()
+            .expect("Unable to reload certificates");
+
+        // Break the server certificate; the reload should be rejected
+        write_file(
+            temp_dir_path.to_path_buf(),
+            "server.cert",
+            b"not a certificate",
+        );
+        assert!(reload_handle.reload().is_err());
+
+        tests::test_transport(transport, "127.0.0.1:0");
+    }
+
     #[cfg(not(unix))]
     #[test]
     fn test_poll_no_verify() {
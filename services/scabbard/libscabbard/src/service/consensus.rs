@@ -13,12 +13,18 @@
 // limitations under the License.
 
 use std::convert::{TryFrom, TryInto};
+#[cfg(any(feature = "consensus-raft", feature = "two-phase-wal"))]
+use std::path::Path;
 use std::sync::mpsc::{channel, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread::{Builder, JoinHandle};
 use std::time::Duration;
 
 use protobuf::Message;
+#[cfg(feature = "consensus-raft")]
+use splinter::consensus::raft::{store::FileRaftStore, RaftEngine};
+#[cfg(feature = "two-phase-wal")]
+use splinter::consensus::two_phase::v2::wal::FileTwoPhaseLogStore;
 use splinter::consensus::{
     error::{ConsensusSendError, ProposalManagerError},
     two_phase::v1::TwoPhaseEngine as TwoPhaseEngineV1,
@@ -26,7 +32,10 @@ use splinter::consensus::{
     ConsensusEngine, ConsensusMessage, ConsensusNetworkSender, PeerId, Proposal, ProposalId,
     ProposalManager, ProposalUpdate, StartupState,
 };
-use transact::protos::IntoBytes;
+use transact::{
+    protocol::batch::BatchPair,
+    protos::{FromBytes, IntoBytes},
+};
 
 use crate::protos::scabbard::{ProposedBatch, ScabbardMessage, ScabbardMessage_Type};
 
@@ -52,6 +61,11 @@ impl ScabbardConsensusManager {
         state: Arc<Mutex<ScabbardState>>,
         // The coordinator timeout for the two-phase commit consensus engine
         coordinator_timeout: Duration,
+        // The path of the write-ahead log for the version 2 two-phase commit consensus engine
+        #[cfg(feature = "two-phase-wal")] consensus_log_path: &Path,
+        // If set, the raft consensus engine is used instead of two-phase commit, and its state is
+        // kept at this path
+        #[cfg(feature = "consensus-raft")] raft_store_path: Option<&Path>,
     ) -> Result<Self, ScabbardConsensusManagerError> {
         let peer_ids = shared
             .lock()
@@ -79,31 +93,73 @@ impl ScabbardConsensusManager {
             last_proposal: None,
         };
 
+        #[cfg(feature = "two-phase-wal")]
+        let consensus_log = match version {
+            ScabbardVersion::V1 => None,
+            ScabbardVersion::V2 => Some(
+                FileTwoPhaseLogStore::new(consensus_log_path)
+                    .map_err(|err| ScabbardConsensusManagerError(Box::new(err)))?,
+            ),
+        };
+
+        #[cfg(feature = "consensus-raft")]
+        let raft_store = raft_store_path
+            .map(FileRaftStore::new)
+            .transpose()
+            .map_err(|err| ScabbardConsensusManagerError(Box::new(err)))?;
+
         let thread_handle = Builder::new()
             .name(format!("consensus-{}", service_id))
-            .spawn(move || match version {
-                ScabbardVersion::V1 => {
-                    let mut two_phase_engine = TwoPhaseEngineV1::new(coordinator_timeout);
-                    if let Err(err) = two_phase_engine.run(
-                        consensus_msg_rx,
-                        proposal_update_rx,
-                        Box::new(consensus_network_sender),
-                        Box::new(proposal_manager),
-                        startup_state,
-                    ) {
-                        error!("two phase consensus exited with an error: {}", err)
+            .spawn(move || {
+                #[cfg(feature = "consensus-raft")]
+                {
+                    if let Some(raft_store) = raft_store {
+                        let mut raft_engine =
+                            RaftEngine::new(coordinator_timeout).with_store(Box::new(raft_store));
+                        if let Err(err) = raft_engine.run(
+                            consensus_msg_rx,
+                            proposal_update_rx,
+                            Box::new(consensus_network_sender),
+                            Box::new(proposal_manager),
+                            startup_state,
+                        ) {
+                            error!("raft consensus exited with an error: {}", err)
+                        }
+                        return;
                     }
                 }
-                ScabbardVersion::V2 => {
-                    let mut two_phase_engine = TwoPhaseEngineV2::new(coordinator_timeout);
-                    if let Err(err) = two_phase_engine.run(
-                        consensus_msg_rx,
-                        proposal_update_rx,
-                        Box::new(consensus_network_sender),
-                        Box::new(proposal_manager),
-                        startup_state,
-                    ) {
-                        error!("two phase consensus exited with an error: {}", err)
+
+                match version {
+                    ScabbardVersion::V1 => {
+                        let mut two_phase_engine = TwoPhaseEngineV1::new(coordinator_timeout);
+                        if let Err(err) = two_phase_engine.run(
+                            consensus_msg_rx,
+                            proposal_update_rx,
+                            Box::new(consensus_network_sender),
+                            Box::new(proposal_manager),
+                            startup_state,
+                        ) {
+                            error!("two phase consensus exited with an error: {}", err)
+                        }
+                    }
+                    ScabbardVersion::V2 => {
+                        let mut two_phase_engine = TwoPhaseEngineV2::new(coordinator_timeout);
+                        #[cfg(feature = "two-phase-wal")]
+                        {
+                            if let Some(consensus_log) = consensus_log {
+                                two_phase_engine =
+                                    two_phase_engine.with_log_store(Box::new(consensus_log));
+                            }
+                        }
+                        if let Err(err) = two_phase_engine.run(
+                            consensus_msg_rx,
+                            proposal_update_rx,
+                            Box::new(consensus_network_sender),
+                            Box::new(proposal_manager),
+                            startup_state,
+                        ) {
+                            error!("two phase consensus exited with an error: {}", err)
+                        }
                     }
                 }
             })
@@ -172,6 +228,28 @@ impl ScabbardProposalManager {
             state,
         }
     }
+
+    /// Builds the message that carries the given proposal and its batch to other services.
+    fn proposed_batch(
+        &self,
+        proposal: Proposal,
+        batch: BatchPair,
+    ) -> Result<ProposedBatch, ProposalManagerError> {
+        let mut proposed_batch = ProposedBatch::new();
+        proposed_batch.set_proposal(
+            proposal
+                .try_into()
+                .map_err(|err| ProposalManagerError::Internal(Box::new(err)))?,
+        );
+        proposed_batch.set_batch(
+            batch
+                .into_bytes()
+                .map_err(|err| ProposalManagerError::Internal(Box::new(err)))?,
+        );
+        proposed_batch.set_service_id(self.service_id.clone());
+
+        Ok(proposed_batch)
+    }
 }
 
 impl ProposalManager for ScabbardProposalManager {
@@ -211,19 +289,7 @@ impl ProposalManager for ScabbardProposalManager {
             shared.add_open_proposal(proposal.clone(), batch.clone());
 
             // Send the proposal to the other services
-            let mut proposed_batch = ProposedBatch::new();
-            proposed_batch.set_proposal(
-                proposal
-                    .clone()
-                    .try_into()
-                    .map_err(|err| ProposalManagerError::Internal(Box::new(err)))?,
-            );
-            proposed_batch.set_batch(
-                batch
-                    .into_bytes()
-                    .map_err(|err| ProposalManagerError::Internal(Box::new(err)))?,
-            );
-            proposed_batch.set_service_id(self.service_id.clone());
+            let proposed_batch = self.proposed_batch(proposal.clone(), batch)?;
 
             let mut msg = ScabbardMessage::new();
             msg.set_message_type(ScabbardMessage_Type::PROPOSED_BATCH);
@@ -293,6 +359,8 @@ impl ProposalManager for ScabbardProposalManager {
             .lock()
             .map_err(|_| ProposalManagerError::Internal(Box::new(ScabbardError::LockPoisoned)))?;
 
+        #[cfg(feature = "consensus-raft")]
+        shared.remove_proposed_batch_from_queue(id);
         shared.remove_open_proposal(id);
 
         self.state
@@ -315,6 +383,8 @@ impl ProposalManager for ScabbardProposalManager {
             .lock()
             .map_err(|_| ProposalManagerError::Internal(Box::new(ScabbardError::LockPoisoned)))?;
 
+        #[cfg(feature = "consensus-raft")]
+        shared.remove_proposed_batch_from_queue(id);
         shared.remove_open_proposal(id);
 
         self.state
@@ -327,6 +397,48 @@ impl ProposalManager for ScabbardProposalManager {
 
         Ok(())
     }
+
+    fn get_proposal_payload(
+        &self,
+        id: &ProposalId,
+    ) -> Result<Option<Vec<u8>>, ProposalManagerError> {
+        let (proposal, batch) = self
+            .shared
+            .lock()
+            .map_err(|_| ProposalManagerError::Internal(Box::new(ScabbardError::LockPoisoned)))?
+            .get_open_proposal(id)
+            .ok_or_else(|| ProposalManagerError::UnknownProposal(id.clone()))?
+            .clone();
+
+        self.proposed_batch(proposal, batch)?
+            .write_to_bytes()
+            .map(Some)
+            .map_err(|err| ProposalManagerError::Internal(Box::new(err)))
+    }
+
+    fn add_proposal_payload(
+        &self,
+        id: &ProposalId,
+        payload: &[u8],
+    ) -> Result<(), ProposalManagerError> {
+        let proposed_batch = ProposedBatch::parse_from_bytes(payload)
+            .map_err(|err| ProposalManagerError::Internal(Box::new(err)))?;
+
+        let proposal = Proposal::try_from(proposed_batch.get_proposal())
+            .map_err(|err| ProposalManagerError::Internal(Box::new(err)))?;
+        if proposal.id != *id {
+            return Err(ProposalManagerError::UnknownProposal(id.clone()));
+        }
+        let batch = BatchPair::from_bytes(proposed_batch.get_batch())
+            .map_err(|err| ProposalManagerError::Internal(Box::new(err)))?;
+
+        self.shared
+            .lock()
+            .map_err(|_| ProposalManagerError::Internal(Box::new(ScabbardError::LockPoisoned)))?
+            .add_open_proposal(proposal, batch);
+
+        Ok(())
+    }
 }
 
 pub struct ScabbardConsensusNetworkSender {
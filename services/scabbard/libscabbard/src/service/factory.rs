@@ -25,6 +25,8 @@ use splinter::service::{FactoryCreateError, Service, ServiceFactory};
 #[cfg(feature = "service-arg-validation")]
 use crate::hex::parse_hex;
 
+#[cfg(feature = "consensus-raft")]
+use super::ScabbardConsensus;
 use super::{Scabbard, ScabbardVersion, SERVICE_TYPE};
 
 const DEFAULT_STATE_DB_DIR: &str = "/var/lib/splinter";
@@ -176,10 +178,34 @@ impl ServiceArgValidator for ScabbardArgValidator {
             }
         }
 
+        #[cfg(feature = "consensus-raft")]
+        {
+            let version = ScabbardVersion::try_from(args.get("version").map(String::as_str))
+                .map_err(ServiceArgValidationError)?;
+            let consensus = ScabbardConsensus::try_from(args.get("consensus").map(String::as_str))
+                .map_err(ServiceArgValidationError)?;
+            check_consensus_version(consensus, version).map_err(ServiceArgValidationError)?;
+        }
+
         Ok(())
     }
 }
 
+/// Raft consensus relies on batches being shared between services, which scabbard version 1 does
+/// not support.
+#[cfg(feature = "consensus-raft")]
+fn check_consensus_version(
+    consensus: ScabbardConsensus,
+    version: ScabbardVersion,
+) -> Result<(), String> {
+    match (consensus, version) {
+        (ScabbardConsensus::Raft, ScabbardVersion::V1) => {
+            Err("raft consensus requires scabbard version 2".into())
+        }
+        _ => Ok(()),
+    }
+}
+
 impl ServiceFactory for ScabbardFactory {
     fn available_service_types(&self) -> &[String] {
         self.service_types.as_slice()
@@ -196,6 +222,10 @@ impl ServiceFactory for ScabbardFactory {
     ///   commit a proposal before the coordinator rejects it (if not provided, default is 30
     ///   seconds)
     /// - `version`: the protocol version for scabbard (possible values: "1", "2") (default: "1")
+    /// - `consensus`: the consensus algorithm for scabbard (possible values: "2pc", "raft")
+    ///   (default: "2pc"); "raft" requires version "2" and uses `coordinator_timeout` as the time
+    ///   the leader waits for a proposal to be verified (only available with the `consensus-raft`
+    ///   feature)
     fn create(
         &self,
         service_id: String,
@@ -239,6 +269,14 @@ impl ServiceFactory for ScabbardFactory {
             .transpose()?;
         let version = ScabbardVersion::try_from(args.get("version").map(String::as_str))
             .map_err(FactoryCreateError::InvalidArguments)?;
+        #[cfg(feature = "consensus-raft")]
+        let consensus = {
+            let consensus = ScabbardConsensus::try_from(args.get("consensus").map(String::as_str))
+                .map_err(FactoryCreateError::InvalidArguments)?;
+            check_consensus_version(consensus, version)
+                .map_err(FactoryCreateError::InvalidArguments)?;
+            consensus
+        };
 
         let service = Scabbard::new(
             service_id,
@@ -254,6 +292,8 @@ impl ServiceFactory for ScabbardFactory {
             coordinator_timeout,
         )
         .map_err(|err| FactoryCreateError::CreationFailed(Box::new(err)))?;
+        #[cfg(feature = "consensus-raft")]
+        let service = service.with_consensus(consensus);
 
         Ok(Box::new(service))
     }
@@ -340,6 +380,39 @@ mod tests {
         assert_eq!(scabbard.coordinator_timeout, Duration::from_millis(123));
     }
 
+    /// Verify that the `consensus` service argument is properly set for a new `Scabbard` instance,
+    /// and that raft consensus is rejected for scabbard version 1.
+    #[cfg(feature = "consensus-raft")]
+    #[test]
+    fn create_with_raft_consensus() {
+        let factory = get_factory();
+        let mut args = get_mock_args();
+        args.insert("consensus".into(), "raft".into());
+        args.insert("version".into(), "2".into());
+
+        let service = factory
+            .create("".into(), "", "", args.clone())
+            .expect("failed to create service");
+        let scabbard = (&*service)
+            .as_any()
+            .downcast_ref::<Scabbard>()
+            .expect("failed to downcast Service to Scabbard");
+        assert_eq!(scabbard.consensus_algorithm, ScabbardConsensus::Raft);
+
+        args.insert("version".into(), "1".into());
+        assert!(
+            factory.create("".into(), "", "", args.clone()).is_err(),
+            "Creating raft service with version 1 did not fail"
+        );
+
+        args.insert("version".into(), "2".into());
+        args.insert("consensus".into(), "pbft".into());
+        assert!(
+            factory.create("".into(), "", "", args).is_err(),
+            "Creating service with unknown consensus did not fail"
+        );
+    }
+
     /// Verify that `Scabbard` creation fails when the `peer_services` argument isn't specified.
     #[test]
     fn create_without_peer_services() {
@@ -66,6 +66,31 @@ const SERVICE_TYPE: &str = "scabbard";
 
 const DEFAULT_COORDINATOR_TIMEOUT: u64 = 30; // 30 seconds
 
+/// Specifies the consensus algorithm scabbard uses.
+#[cfg(feature = "consensus-raft")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScabbardConsensus {
+    /// Two-phase commit, which requires every service to agree on each batch
+    TwoPhaseCommit,
+    /// Raft-style leader-based consensus, which requires a majority of the services to agree on
+    /// each batch; only supported by scabbard version 2
+    Raft,
+}
+
+#[cfg(feature = "consensus-raft")]
+impl TryFrom<Option<&str>> for ScabbardConsensus {
+    type Error = String;
+
+    fn try_from(str_opt: Option<&str>) -> Result<Self, Self::Error> {
+        match str_opt {
+            Some("2pc") => Ok(Self::TwoPhaseCommit),
+            Some("raft") => Ok(Self::Raft),
+            Some(c) => Err(format!("Unsupported scabbard consensus algorithm: {}", c)),
+            None => Ok(Self::TwoPhaseCommit),
+        }
+    }
+}
+
 /// Specifies the version of scabbard to use.
 #[derive(Clone, Copy)]
 pub enum ScabbardVersion {
@@ -97,6 +122,14 @@ pub struct Scabbard {
     /// The coordinator timeout for the two-phase commit consensus engine
     coordinator_timeout: Duration,
     consensus: Arc<Mutex<Option<ScabbardConsensusManager>>>,
+    /// The path of the two-phase commit engine's write-ahead log
+    #[cfg(feature = "two-phase-wal")]
+    consensus_log_path: PathBuf,
+    #[cfg(feature = "consensus-raft")]
+    consensus_algorithm: ScabbardConsensus,
+    /// The path of the raft engine's persistent state
+    #[cfg(feature = "consensus-raft")]
+    raft_store_path: PathBuf,
 }
 
 impl Scabbard {
@@ -146,6 +179,11 @@ impl Scabbard {
         let coordinator_timeout =
             coordinator_timeout.unwrap_or_else(|| Duration::from_secs(DEFAULT_COORDINATOR_TIMEOUT));
 
+        #[cfg(feature = "two-phase-wal")]
+        let consensus_log_path = compute_consensus_log_path(&state_db_path);
+        #[cfg(feature = "consensus-raft")]
+        let raft_store_path = compute_raft_store_path(&state_db_path);
+
         Ok(Scabbard {
             circuit_id: circuit_id.to_string(),
             service_id,
@@ -154,9 +192,35 @@ impl Scabbard {
             state: Arc::new(Mutex::new(state)),
             coordinator_timeout,
             consensus: Arc::new(Mutex::new(None)),
+            #[cfg(feature = "two-phase-wal")]
+            consensus_log_path,
+            #[cfg(feature = "consensus-raft")]
+            consensus_algorithm: ScabbardConsensus::TwoPhaseCommit,
+            #[cfg(feature = "consensus-raft")]
+            raft_store_path,
         })
     }
 
+    /// Sets the consensus algorithm used by the service. Raft consensus relies on batches being
+    /// shared between services, so it should only be used with scabbard version 2.
+    #[cfg(feature = "consensus-raft")]
+    pub fn with_consensus(mut self, consensus_algorithm: ScabbardConsensus) -> Self {
+        self.consensus_algorithm = consensus_algorithm;
+        self
+    }
+
+    /// Determines if the service uses raft consensus, in which case any service may become the
+    /// leader that proposes batches.
+    #[cfg(feature = "consensus-raft")]
+    fn uses_raft(&self) -> bool {
+        self.consensus_algorithm == ScabbardConsensus::Raft
+    }
+
+    #[cfg(not(feature = "consensus-raft"))]
+    fn uses_raft(&self) -> bool {
+        false
+    }
+
     /// Fetch the value at the given `address` in the scabbard service's state. Returns `None` if
     /// the `address` is not set.
     pub fn get_state_at_address(&self, address: &str) -> Result<Option<Vec<u8>>, ScabbardError> {
@@ -212,19 +276,22 @@ impl Scabbard {
                 match self.version {
                     ScabbardVersion::V1 => shared.add_batch_to_queue(batch),
                     ScabbardVersion::V2 => {
-                        if shared.is_coordinator() {
+                        if self.uses_raft() {
+                            // Any service may be the leader, so every service queues the batch
+                            shared.add_batch_to_queue(batch.clone());
+
+                            let msg_bytes = new_batch_message_bytes(batch)?;
+                            let network_sender =
+                                shared.network_sender().ok_or(ScabbardError::NotConnected)?;
+                            for service in shared.peer_services() {
+                                network_sender
+                                    .send(service, msg_bytes.as_slice())
+                                    .map_err(|err| ScabbardError::Internal(Box::new(err)))?;
+                            }
+                        } else if shared.is_coordinator() {
                             shared.add_batch_to_queue(batch);
                         } else {
-                            let batch_bytes = batch
-                                .into_bytes()
-                                .map_err(|err| ScabbardError::Internal(Box::new(err)))?;
-
-                            let mut msg = ScabbardMessage::new();
-                            msg.set_message_type(ScabbardMessage_Type::NEW_BATCH);
-                            msg.set_new_batch(batch_bytes);
-                            let msg_bytes = msg
-                                .write_to_bytes()
-                                .map_err(|err| ScabbardError::Internal(Box::new(err)))?;
+                            let msg_bytes = new_batch_message_bytes(batch)?;
 
                             shared
                                 .network_sender()
@@ -321,6 +388,14 @@ impl Service for Scabbard {
                 self.shared.clone(),
                 self.state.clone(),
                 self.coordinator_timeout,
+                #[cfg(feature = "two-phase-wal")]
+                &self.consensus_log_path,
+                #[cfg(feature = "consensus-raft")]
+                if self.uses_raft() {
+                    Some(&self.raft_store_path)
+                } else {
+                    None
+                },
             )
             .map_err(|err| {
                 ServiceStartError::Internal(format!("Unable to start consensus: {}", err))
@@ -376,6 +451,22 @@ impl Service for Scabbard {
 
     #[cfg(feature = "circuit-purge")]
     fn purge(&mut self) -> Result<(), splinter::error::InternalError> {
+        #[cfg(feature = "two-phase-wal")]
+        {
+            if self.consensus_log_path.exists() {
+                std::fs::remove_file(&self.consensus_log_path)
+                    .map_err(|err| splinter::error::InternalError::from_source(Box::new(err)))?;
+            }
+        }
+
+        #[cfg(feature = "consensus-raft")]
+        {
+            if self.raft_store_path.exists() {
+                std::fs::remove_file(&self.raft_store_path)
+                    .map_err(|err| splinter::error::InternalError::from_source(Box::new(err)))?;
+            }
+        }
+
         self.state
             .lock()
             .map_err(|_| {
@@ -440,7 +531,7 @@ impl Service for Scabbard {
                             ServiceError::PoisonedLock("shared lock poisoned".into())
                         })?;
 
-                        if shared.is_coordinator() {
+                        if shared.is_coordinator() || self.uses_raft() {
                             let batch =
                                 BatchPair::from_bytes(message.get_new_batch()).map_err(|err| {
                                     ServiceError::UnableToHandleMessage(Box::new(err))
@@ -482,6 +573,30 @@ fn compute_db_paths(
     Ok((state_db_path, receipt_db_path))
 }
 
+/// The write-ahead log of the two-phase commit engine is kept alongside the state database.
+#[cfg(feature = "two-phase-wal")]
+fn compute_consensus_log_path(state_db_path: &Path) -> PathBuf {
+    state_db_path.with_extension("consensus.wal")
+}
+
+/// The state of the raft consensus engine is kept alongside the state database.
+#[cfg(feature = "consensus-raft")]
+fn compute_raft_store_path(state_db_path: &Path) -> PathBuf {
+    state_db_path.with_extension("raft")
+}
+
+fn new_batch_message_bytes(batch: BatchPair) -> Result<Vec<u8>, ScabbardError> {
+    let batch_bytes = batch
+        .into_bytes()
+        .map_err(|err| ScabbardError::Internal(Box::new(err)))?;
+
+    let mut msg = ScabbardMessage::new();
+    msg.set_message_type(ScabbardMessage_Type::NEW_BATCH);
+    msg.set_new_batch(batch_bytes);
+    msg.write_to_bytes()
+        .map_err(|err| ScabbardError::Internal(Box::new(err)))
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
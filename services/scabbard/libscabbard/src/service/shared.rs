@@ -100,6 +100,18 @@ impl ScabbardShared {
         self.batch_queue.pop_front()
     }
 
+    /// Removes the batch of the given open proposal from the queue. With raft consensus, every
+    /// service queues every batch, so a batch must be removed from the queue once any service has
+    /// proposed it.
+    #[cfg(feature = "consensus-raft")]
+    pub fn remove_proposed_batch_from_queue(&mut self, proposal_id: &ProposalId) {
+        if let Some((_, proposed_batch)) = self.open_proposals.get(proposal_id) {
+            let batch_id = proposed_batch.batch().header_signature();
+            self.batch_queue
+                .retain(|batch| batch.batch().header_signature() != batch_id);
+        }
+    }
+
     pub fn network_sender(&self) -> Option<&dyn ServiceNetworkSender> {
         self.network_sender.as_deref()
     }
@@ -298,6 +298,20 @@ impl ConfigBuilder {
                 .ok_or_else(|| {
                     ConfigError::MissingValue("registry forced refresh interval".to_string())
                 })?,
+            #[cfg(feature = "registry-remote-signature")]
+            registry_public_key: self.partial_configs.iter().find_map(|p| {
+                match p.registry_public_key() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "registry-signed-nodes")]
+            registry_signature_policy: self.partial_configs.iter().find_map(|p| {
+                match p.registry_signature_policy() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
             heartbeat: self
                 .partial_configs
                 .iter()
@@ -341,6 +355,76 @@ impl ConfigBuilder {
                     Some(v) => Some((v, p.source())),
                     None => None,
                 }),
+            #[cfg(feature = "biome-notifications")]
+            biome_notification_types: self.partial_configs.iter().find_map(|p| {
+                match p.biome_notification_types() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "biome-login-lockout")]
+            biome_lockout_duration: self.partial_configs.iter().find_map(|p| {
+                match p.biome_lockout_duration() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "biome-login-lockout")]
+            biome_max_failed_logins: self.partial_configs.iter().find_map(|p| {
+                match p.biome_max_failed_logins() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "biome-password-reset")]
+            biome_password_reset_command: self.partial_configs.iter().find_map(|p| {
+                match p.biome_password_reset_command() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "socket-compression")]
+            network_compression: self.partial_configs.iter().find_map(|p| {
+                match p.network_compression() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "socket-compression")]
+            network_compression_threshold: self.partial_configs.iter().find_map(|p| {
+                match p.network_compression_threshold() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "network-rate-limit")]
+            peer_rate_limit_messages: self.partial_configs.iter().find_map(|p| {
+                match p.peer_rate_limit_messages() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "network-rate-limit")]
+            peer_rate_limit_bytes: self.partial_configs.iter().find_map(|p| {
+                match p.peer_rate_limit_bytes() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "network-rate-limit")]
+            circuit_rate_limit_messages: self.partial_configs.iter().find_map(|p| {
+                match p.circuit_rate_limit_messages() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "network-rate-limit")]
+            circuit_rate_limit_bytes: self.partial_configs.iter().find_map(|p| {
+                match p.circuit_rate_limit_bytes() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
             #[cfg(feature = "biome-credentials")]
             enable_biome_credentials: self
                 .partial_configs
@@ -401,6 +485,50 @@ impl ConfigBuilder {
                     None => None,
                 }
             }),
+            #[cfg(feature = "saml")]
+            saml_sp_entity_id: self.partial_configs.iter().find_map(|p| {
+                match p.saml_sp_entity_id() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "saml")]
+            saml_acs_url: self
+                .partial_configs
+                .iter()
+                .find_map(|p| match p.saml_acs_url() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }),
+            #[cfg(feature = "saml")]
+            saml_idp_entity_id: self.partial_configs.iter().find_map(|p| {
+                match p.saml_idp_entity_id() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "saml")]
+            saml_idp_sso_url: self.partial_configs.iter().find_map(|p| {
+                match p.saml_idp_sso_url() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "saml")]
+            saml_idp_certificate: self.partial_configs.iter().find_map(|p| {
+                match p.saml_idp_certificate() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }
+            }),
+            #[cfg(feature = "authorization-audit")]
+            audit_log_file: self
+                .partial_configs
+                .iter()
+                .find_map(|p| match p.audit_log_file() {
+                    Some(v) => Some((v, p.source())),
+                    None => None,
+                }),
             strict_ref_counts: self
                 .partial_configs
                 .iter()
@@ -14,6 +14,8 @@
 
 //! `PartialConfig` builder using values from splinterd command line arguments.
 
+use std::str::FromStr;
+
 use crate::config::{ConfigError, ConfigSource, PartialConfig, PartialConfigBuilder};
 use clap::{ArgMatches, ErrorKind};
 
@@ -22,9 +24,9 @@ pub struct ClapPartialConfigBuilder<'a> {
     matches: ArgMatches<'a>,
 }
 
-// Parses a u64 value from a clap argument.
-fn parse_value(matches: &ArgMatches, arg: &str) -> Result<Option<u64>, ConfigError> {
-    match value_t!(matches.value_of(arg), u64) {
+// Parses a numeric value from a clap argument.
+fn parse_value<T: FromStr>(matches: &ArgMatches, arg: &str) -> Result<Option<T>, ConfigError> {
+    match value_t!(matches.value_of(arg), T) {
         Ok(v) => Ok(Some(v)),
         Err(e) => match e.kind {
             ErrorKind::ValueValidation => Err(ConfigError::InvalidArgument(e.to_string())),
@@ -108,6 +110,24 @@ impl<'a> PartialConfigBuilder for ClapPartialConfigBuilder<'_> {
                 .with_service_endpoint(self.matches.value_of("service_endpoint").map(String::from))
         }
 
+        #[cfg(feature = "registry-remote-signature")]
+        {
+            partial_config = partial_config.with_registry_public_key(
+                self.matches
+                    .value_of("registry_public_key")
+                    .map(String::from),
+            )
+        }
+
+        #[cfg(feature = "registry-signed-nodes")]
+        {
+            partial_config = partial_config.with_registry_signature_policy(
+                self.matches
+                    .value_of("registry_signature_policy")
+                    .map(String::from),
+            )
+        }
+
         #[cfg(feature = "rest-api-cors")]
         {
             partial_config = partial_config.with_whitelist(
@@ -117,6 +137,66 @@ impl<'a> PartialConfigBuilder for ClapPartialConfigBuilder<'_> {
             )
         }
 
+        #[cfg(feature = "biome-notifications")]
+        {
+            partial_config = partial_config.with_biome_notification_types(
+                self.matches
+                    .values_of("biome_notification_types")
+                    .map(|values| values.map(String::from).collect::<Vec<String>>()),
+            )
+        }
+
+        #[cfg(feature = "biome-login-lockout")]
+        {
+            partial_config = partial_config
+                .with_biome_lockout_duration(parse_value(&self.matches, "biome_lockout_duration")?)
+                .with_biome_max_failed_logins(parse_value(
+                    &self.matches,
+                    "biome_max_failed_logins",
+                )?)
+        }
+
+        #[cfg(feature = "biome-password-reset")]
+        {
+            partial_config = partial_config.with_biome_password_reset_command(
+                self.matches
+                    .value_of("biome_password_reset_command")
+                    .map(String::from),
+            )
+        }
+
+        #[cfg(feature = "socket-compression")]
+        {
+            partial_config = partial_config
+                .with_network_compression(
+                    self.matches
+                        .values_of("network_compression")
+                        .map(|values| values.map(String::from).collect::<Vec<String>>()),
+                )
+                .with_network_compression_threshold(parse_value(
+                    &self.matches,
+                    "network_compression_threshold",
+                )?)
+        }
+
+        #[cfg(feature = "network-rate-limit")]
+        {
+            partial_config = partial_config
+                .with_peer_rate_limit_messages(parse_value(
+                    &self.matches,
+                    "peer_rate_limit_messages",
+                )?)
+                .with_peer_rate_limit_bytes(parse_value(&self.matches, "peer_rate_limit_bytes")?)
+                .with_circuit_rate_limit_messages(parse_value(
+                    &self.matches,
+                    "circuit_rate_limit_messages",
+                )?)
+                .with_circuit_rate_limit_bytes(parse_value(
+                    &self.matches,
+                    "circuit_rate_limit_bytes",
+                )?)
+        }
+
         #[cfg(feature = "biome-credentials")]
         {
             partial_config = partial_config.with_enable_biome_credentials(Some(
@@ -171,6 +251,32 @@ impl<'a> PartialConfigBuilder for ClapPartialConfigBuilder<'_> {
                 )
         }
 
+        #[cfg(feature = "saml")]
+        {
+            partial_config = partial_config
+                .with_saml_sp_entity_id(
+                    self.matches.value_of("saml_sp_entity_id").map(String::from),
+                )
+                .with_saml_acs_url(self.matches.value_of("saml_acs_url").map(String::from))
+                .with_saml_idp_entity_id(
+                    self.matches
+                        .value_of("saml_idp_entity_id")
+                        .map(String::from),
+                )
+                .with_saml_idp_sso_url(self.matches.value_of("saml_idp_sso_url").map(String::from))
+                .with_saml_idp_certificate(
+                    self.matches
+                        .value_of("saml_idp_certificate")
+                        .map(String::from),
+                )
+        }
+
+        #[cfg(feature = "authorization-audit")]
+        {
+            partial_config = partial_config
+                .with_audit_log_file(self.matches.value_of("audit_log_file").map(String::from))
+        }
+
         Ok(partial_config)
     }
 }
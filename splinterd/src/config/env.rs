@@ -35,6 +35,16 @@ const OAUTH_CLIENT_SECRET_ENV: &str = "OAUTH_CLIENT_SECRET";
 const OAUTH_REDIRECT_URL_ENV: &str = "OAUTH_REDIRECT_URL";
 #[cfg(feature = "oauth")]
 const OAUTH_OPENID_URL_ENV: &str = "OAUTH_OPENID_URL";
+#[cfg(feature = "saml")]
+const SAML_SP_ENTITY_ID_ENV: &str = "SAML_SP_ENTITY_ID";
+#[cfg(feature = "saml")]
+const SAML_ACS_URL_ENV: &str = "SAML_ACS_URL";
+#[cfg(feature = "saml")]
+const SAML_IDP_ENTITY_ID_ENV: &str = "SAML_IDP_ENTITY_ID";
+#[cfg(feature = "saml")]
+const SAML_IDP_SSO_URL_ENV: &str = "SAML_IDP_SSO_URL";
+#[cfg(feature = "saml")]
+const SAML_IDP_CERTIFICATE_ENV: &str = "SAML_IDP_CERTIFICATE";
 
 pub struct EnvPartialConfigBuilder;
 
@@ -117,6 +127,16 @@ impl PartialConfigBuilder for EnvPartialConfigBuilder {
                 .with_oauth_openid_url(env::var(OAUTH_OPENID_URL_ENV).ok());
         }
 
+        #[cfg(feature = "saml")]
+        {
+            config = config
+                .with_saml_sp_entity_id(env::var(SAML_SP_ENTITY_ID_ENV).ok())
+                .with_saml_acs_url(env::var(SAML_ACS_URL_ENV).ok())
+                .with_saml_idp_entity_id(env::var(SAML_IDP_ENTITY_ID_ENV).ok())
+                .with_saml_idp_sso_url(env::var(SAML_IDP_SSO_URL_ENV).ok())
+                .with_saml_idp_certificate(env::var(SAML_IDP_CERTIFICATE_ENV).ok());
+        }
+
         Ok(config)
     }
 }
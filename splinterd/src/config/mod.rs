@@ -64,6 +64,10 @@ pub struct Config {
     registries: (Vec<String>, ConfigSource),
     registry_auto_refresh: (u64, ConfigSource),
     registry_forced_refresh: (u64, ConfigSource),
+    #[cfg(feature = "registry-remote-signature")]
+    registry_public_key: Option<(String, ConfigSource)>,
+    #[cfg(feature = "registry-signed-nodes")]
+    registry_signature_policy: Option<(String, ConfigSource)>,
     heartbeat: (u64, ConfigSource),
     admin_timeout: (Duration, ConfigSource),
     state_dir: (String, ConfigSource),
@@ -71,6 +75,26 @@ pub struct Config {
     no_tls: (bool, ConfigSource),
     #[cfg(feature = "rest-api-cors")]
     whitelist: Option<(Vec<String>, ConfigSource)>,
+    #[cfg(feature = "biome-notifications")]
+    biome_notification_types: Option<(Vec<String>, ConfigSource)>,
+    #[cfg(feature = "biome-login-lockout")]
+    biome_lockout_duration: Option<(u64, ConfigSource)>,
+    #[cfg(feature = "biome-login-lockout")]
+    biome_max_failed_logins: Option<(u32, ConfigSource)>,
+    #[cfg(feature = "biome-password-reset")]
+    biome_password_reset_command: Option<(String, ConfigSource)>,
+    #[cfg(feature = "socket-compression")]
+    network_compression: Option<(Vec<String>, ConfigSource)>,
+    #[cfg(feature = "socket-compression")]
+    network_compression_threshold: Option<(u64, ConfigSource)>,
+    #[cfg(feature = "network-rate-limit")]
+    peer_rate_limit_messages: Option<(u64, ConfigSource)>,
+    #[cfg(feature = "network-rate-limit")]
+    peer_rate_limit_bytes: Option<(u64, ConfigSource)>,
+    #[cfg(feature = "network-rate-limit")]
+    circuit_rate_limit_messages: Option<(u64, ConfigSource)>,
+    #[cfg(feature = "network-rate-limit")]
+    circuit_rate_limit_bytes: Option<(u64, ConfigSource)>,
     #[cfg(feature = "biome-credentials")]
     enable_biome_credentials: (bool, ConfigSource),
     #[cfg(feature = "oauth")]
@@ -87,6 +111,18 @@ pub struct Config {
     oauth_openid_auth_params: Option<(Vec<(String, String)>, ConfigSource)>,
     #[cfg(feature = "oauth")]
     oauth_openid_scopes: Option<(Vec<String>, ConfigSource)>,
+    #[cfg(feature = "saml")]
+    saml_sp_entity_id: Option<(String, ConfigSource)>,
+    #[cfg(feature = "saml")]
+    saml_acs_url: Option<(String, ConfigSource)>,
+    #[cfg(feature = "saml")]
+    saml_idp_entity_id: Option<(String, ConfigSource)>,
+    #[cfg(feature = "saml")]
+    saml_idp_sso_url: Option<(String, ConfigSource)>,
+    #[cfg(feature = "saml")]
+    saml_idp_certificate: Option<(String, ConfigSource)>,
+    #[cfg(feature = "authorization-audit")]
+    audit_log_file: Option<(String, ConfigSource)>,
     strict_ref_counts: (bool, ConfigSource),
 }
 
@@ -190,6 +226,24 @@ impl Config {
         self.registry_forced_refresh.0
     }
 
+    #[cfg(feature = "registry-remote-signature")]
+    pub fn registry_public_key(&self) -> Option<&str> {
+        if let Some((key, _)) = &self.registry_public_key {
+            Some(key)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "registry-signed-nodes")]
+    pub fn registry_signature_policy(&self) -> Option<&str> {
+        if let Some((policy, _)) = &self.registry_signature_policy {
+            Some(policy)
+        } else {
+            None
+        }
+    }
+
     pub fn heartbeat(&self) -> u64 {
         self.heartbeat.0
     }
@@ -219,6 +273,96 @@ impl Config {
         }
     }
 
+    #[cfg(feature = "biome-notifications")]
+    pub fn biome_notification_types(&self) -> Option<&[String]> {
+        if let Some((list, _)) = &self.biome_notification_types {
+            Some(list)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    pub fn biome_lockout_duration(&self) -> Option<u64> {
+        if let Some((value, _)) = &self.biome_lockout_duration {
+            Some(*value)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    pub fn biome_max_failed_logins(&self) -> Option<u32> {
+        if let Some((value, _)) = &self.biome_max_failed_logins {
+            Some(*value)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "biome-password-reset")]
+    pub fn biome_password_reset_command(&self) -> Option<&str> {
+        if let Some((command, _)) = &self.biome_password_reset_command {
+            Some(command)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "socket-compression")]
+    pub fn network_compression(&self) -> Option<&[String]> {
+        if let Some((list, _)) = &self.network_compression {
+            Some(list)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "socket-compression")]
+    pub fn network_compression_threshold(&self) -> Option<u64> {
+        if let Some((threshold, _)) = &self.network_compression_threshold {
+            Some(*threshold)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "network-rate-limit")]
+    pub fn peer_rate_limit_messages(&self) -> Option<u64> {
+        if let Some((value, _)) = &self.peer_rate_limit_messages {
+            Some(*value)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "network-rate-limit")]
+    pub fn peer_rate_limit_bytes(&self) -> Option<u64> {
+        if let Some((value, _)) = &self.peer_rate_limit_bytes {
+            Some(*value)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "network-rate-limit")]
+    pub fn circuit_rate_limit_messages(&self) -> Option<u64> {
+        if let Some((value, _)) = &self.circuit_rate_limit_messages {
+            Some(*value)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "network-rate-limit")]
+    pub fn circuit_rate_limit_bytes(&self) -> Option<u64> {
+        if let Some((value, _)) = &self.circuit_rate_limit_bytes {
+            Some(*value)
+        } else {
+            None
+        }
+    }
+
     #[cfg(feature = "biome-credentials")]
     pub fn enable_biome_credentials(&self) -> bool {
         self.enable_biome_credentials.0
@@ -287,6 +431,60 @@ impl Config {
         }
     }
 
+    #[cfg(feature = "saml")]
+    pub fn saml_sp_entity_id(&self) -> Option<&str> {
+        if let Some((value, _)) = &self.saml_sp_entity_id {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "saml")]
+    pub fn saml_acs_url(&self) -> Option<&str> {
+        if let Some((value, _)) = &self.saml_acs_url {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "saml")]
+    pub fn saml_idp_entity_id(&self) -> Option<&str> {
+        if let Some((value, _)) = &self.saml_idp_entity_id {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "saml")]
+    pub fn saml_idp_sso_url(&self) -> Option<&str> {
+        if let Some((value, _)) = &self.saml_idp_sso_url {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "saml")]
+    pub fn saml_idp_certificate(&self) -> Option<&str> {
+        if let Some((value, _)) = &self.saml_idp_certificate {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "authorization-audit")]
+    pub fn audit_log_file(&self) -> Option<&str> {
+        if let Some((value, _)) = &self.audit_log_file {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
     pub fn strict_ref_counts(&self) -> bool {
         self.strict_ref_counts.0
     }
@@ -390,6 +588,24 @@ impl Config {
         &self.registry_forced_refresh.1
     }
 
+    #[cfg(feature = "registry-remote-signature")]
+    fn registry_public_key_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.registry_public_key {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "registry-signed-nodes")]
+    fn registry_signature_policy_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.registry_signature_policy {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
     fn heartbeat_source(&self) -> &ConfigSource {
         &self.heartbeat.1
     }
@@ -419,6 +635,96 @@ impl Config {
         }
     }
 
+    #[cfg(feature = "biome-notifications")]
+    fn biome_notification_types_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.biome_notification_types {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    fn biome_lockout_duration_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.biome_lockout_duration {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    fn biome_max_failed_logins_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.biome_max_failed_logins {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "biome-password-reset")]
+    fn biome_password_reset_command_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.biome_password_reset_command {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "socket-compression")]
+    pub fn network_compression_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.network_compression {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "socket-compression")]
+    pub fn network_compression_threshold_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.network_compression_threshold {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "network-rate-limit")]
+    pub fn peer_rate_limit_messages_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.peer_rate_limit_messages {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "network-rate-limit")]
+    pub fn peer_rate_limit_bytes_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.peer_rate_limit_bytes {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "network-rate-limit")]
+    pub fn circuit_rate_limit_messages_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.circuit_rate_limit_messages {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "network-rate-limit")]
+    pub fn circuit_rate_limit_bytes_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.circuit_rate_limit_bytes {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
     #[cfg(feature = "biome-credentials")]
     pub fn enable_biome_credentials_source(&self) -> &ConfigSource {
         &self.enable_biome_credentials.1
@@ -487,6 +793,60 @@ impl Config {
         }
     }
 
+    #[cfg(feature = "saml")]
+    pub fn saml_sp_entity_id_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.saml_sp_entity_id {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "saml")]
+    pub fn saml_acs_url_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.saml_acs_url {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "saml")]
+    pub fn saml_idp_entity_id_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.saml_idp_entity_id {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "saml")]
+    pub fn saml_idp_sso_url_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.saml_idp_sso_url {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "saml")]
+    pub fn saml_idp_certificate_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.saml_idp_certificate {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "authorization-audit")]
+    pub fn audit_log_file_source(&self) -> Option<&ConfigSource> {
+        if let Some((_, source)) = &self.audit_log_file {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
     fn strict_ref_counts_source(&self) -> &ConfigSource {
         &self.strict_ref_counts.1
     }
@@ -592,6 +952,30 @@ impl Config {
             self.registry_forced_refresh(),
             self.registry_forced_refresh_source()
         );
+        #[cfg(feature = "registry-remote-signature")]
+        {
+            if let (Some(key), Some(source)) = (
+                self.registry_public_key(),
+                self.registry_public_key_source(),
+            ) {
+                debug!(
+                    "Config: registry_public_key: {} (source: {:?})",
+                    key, source
+                );
+            }
+        }
+        #[cfg(feature = "registry-signed-nodes")]
+        {
+            if let (Some(policy), Some(source)) = (
+                self.registry_signature_policy(),
+                self.registry_signature_policy_source(),
+            ) {
+                debug!(
+                    "Config: registry_signature_policy: {} (source: {:?})",
+                    policy, source
+                );
+            }
+        }
         debug!(
             "Config: state_dir: {} (source: {:?})",
             self.state_dir(),
@@ -624,6 +1008,16 @@ impl Config {
         );
         #[cfg(feature = "rest-api-cors")]
         self.log_whitelist();
+        #[cfg(feature = "biome-notifications")]
+        self.log_biome_notification_types();
+        #[cfg(feature = "biome-login-lockout")]
+        self.log_biome_lockout();
+        #[cfg(feature = "biome-password-reset")]
+        self.log_biome_password_reset_command();
+        #[cfg(feature = "socket-compression")]
+        self.log_network_compression();
+        #[cfg(feature = "network-rate-limit")]
+        self.log_rate_limits();
         #[cfg(feature = "biome-credentials")]
         debug!(
             "Config: enable_biome_credentials: {:?} (source: {:?})",
@@ -689,6 +1083,46 @@ impl Config {
                 debug!("Config: oauth_scopes: {:?} (source: {:?})", scopes, source,);
             }
         }
+        #[cfg(feature = "saml")]
+        {
+            if let (Some(value), Some(source)) =
+                (self.saml_sp_entity_id(), self.saml_sp_entity_id_source())
+            {
+                debug!(
+                    "Config: saml_sp_entity_id: {} (source: {:?})",
+                    value, source
+                );
+            }
+            if let (Some(value), Some(source)) = (self.saml_acs_url(), self.saml_acs_url_source()) {
+                debug!("Config: saml_acs_url: {} (source: {:?})", value, source);
+            }
+            if let (Some(value), Some(source)) =
+                (self.saml_idp_entity_id(), self.saml_idp_entity_id_source())
+            {
+                debug!(
+                    "Config: saml_idp_entity_id: {} (source: {:?})",
+                    value, source
+                );
+            }
+            if let (Some(value), Some(source)) =
+                (self.saml_idp_sso_url(), self.saml_idp_sso_url_source())
+            {
+                debug!("Config: saml_idp_sso_url: {} (source: {:?})", value, source);
+            }
+            if let (Some(value), Some(source)) = (
+                self.saml_idp_certificate(),
+                self.saml_idp_certificate_source(),
+            ) {
+                debug!(
+                    "Config: saml_idp_certificate: {} (source: {:?})",
+                    value, source
+                );
+            }
+        }
+        #[cfg(feature = "authorization-audit")]
+        if let (Some(value), Some(source)) = (self.audit_log_file(), self.audit_log_file_source()) {
+            debug!("Config: audit_log_file: {} (source: {:?})", value, source);
+        }
         debug!(
             "Config: strict_ref_counts: {:?} (source: {:?})",
             self.strict_ref_counts(),
@@ -702,6 +1136,107 @@ impl Config {
             debug!("Config: whitelist: {:?} (source: {:?})", list, source,);
         }
     }
+
+    #[cfg(feature = "biome-notifications")]
+    fn log_biome_notification_types(&self) {
+        if let (Some(types), Some(source)) = (
+            self.biome_notification_types(),
+            self.biome_notification_types_source(),
+        ) {
+            debug!(
+                "Config: biome_notification_types: {:?} (source: {:?})",
+                types, source
+            );
+        }
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    fn log_biome_lockout(&self) {
+        if let (Some(value), Some(source)) = (
+            self.biome_lockout_duration(),
+            self.biome_lockout_duration_source(),
+        ) {
+            debug!(
+                "Config: biome_lockout_duration: {} (source: {:?})",
+                value, source
+            );
+        }
+        if let (Some(value), Some(source)) = (
+            self.biome_max_failed_logins(),
+            self.biome_max_failed_logins_source(),
+        ) {
+            debug!(
+                "Config: biome_max_failed_logins: {} (source: {:?})",
+                value, source
+            );
+        }
+    }
+
+    #[cfg(feature = "biome-password-reset")]
+    fn log_biome_password_reset_command(&self) {
+        if let (Some(command), Some(source)) = (
+            self.biome_password_reset_command(),
+            self.biome_password_reset_command_source(),
+        ) {
+            debug!(
+                "Config: biome_password_reset_command: {} (source: {:?})",
+                command, source
+            );
+        }
+    }
+
+    #[cfg(feature = "network-rate-limit")]
+    fn log_rate_limits(&self) {
+        let limits = [
+            (
+                "peer_rate_limit_messages",
+                self.peer_rate_limit_messages(),
+                self.peer_rate_limit_messages_source(),
+            ),
+            (
+                "peer_rate_limit_bytes",
+                self.peer_rate_limit_bytes(),
+                self.peer_rate_limit_bytes_source(),
+            ),
+            (
+                "circuit_rate_limit_messages",
+                self.circuit_rate_limit_messages(),
+                self.circuit_rate_limit_messages_source(),
+            ),
+            (
+                "circuit_rate_limit_bytes",
+                self.circuit_rate_limit_bytes(),
+                self.circuit_rate_limit_bytes_source(),
+            ),
+        ];
+        for (name, value, source) in limits.iter() {
+            if let (Some(value), Some(source)) = (value, source) {
+                debug!("Config: {}: {} (source: {:?})", name, value, source);
+            }
+        }
+    }
+
+    #[cfg(feature = "socket-compression")]
+    fn log_network_compression(&self) {
+        if let (Some(list), Some(source)) = (
+            self.network_compression(),
+            self.network_compression_source(),
+        ) {
+            debug!(
+                "Config: network_compression: {:?} (source: {:?})",
+                list, source,
+            );
+        }
+        if let (Some(threshold), Some(source)) = (
+            self.network_compression_threshold(),
+            self.network_compression_threshold_source(),
+        ) {
+            debug!(
+                "Config: network_compression_threshold: {} (source: {:?})",
+                threshold, source,
+            );
+        }
+    }
 }
 
 #[cfg(test)]
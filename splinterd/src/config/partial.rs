@@ -57,6 +57,10 @@ pub struct PartialConfig {
     registries: Option<Vec<String>>,
     registry_auto_refresh: Option<u64>,
     registry_forced_refresh: Option<u64>,
+    #[cfg(feature = "registry-remote-signature")]
+    registry_public_key: Option<String>,
+    #[cfg(feature = "registry-signed-nodes")]
+    registry_signature_policy: Option<String>,
     heartbeat: Option<u64>,
     admin_timeout: Option<Duration>,
     state_dir: Option<String>,
@@ -64,6 +68,26 @@ pub struct PartialConfig {
     no_tls: Option<bool>,
     #[cfg(feature = "rest-api-cors")]
     whitelist: Option<Vec<String>>,
+    #[cfg(feature = "biome-notifications")]
+    biome_notification_types: Option<Vec<String>>,
+    #[cfg(feature = "biome-login-lockout")]
+    biome_lockout_duration: Option<u64>,
+    #[cfg(feature = "biome-login-lockout")]
+    biome_max_failed_logins: Option<u32>,
+    #[cfg(feature = "biome-password-reset")]
+    biome_password_reset_command: Option<String>,
+    #[cfg(feature = "socket-compression")]
+    network_compression: Option<Vec<String>>,
+    #[cfg(feature = "socket-compression")]
+    network_compression_threshold: Option<u64>,
+    #[cfg(feature = "network-rate-limit")]
+    peer_rate_limit_messages: Option<u64>,
+    #[cfg(feature = "network-rate-limit")]
+    peer_rate_limit_bytes: Option<u64>,
+    #[cfg(feature = "network-rate-limit")]
+    circuit_rate_limit_messages: Option<u64>,
+    #[cfg(feature = "network-rate-limit")]
+    circuit_rate_limit_bytes: Option<u64>,
     #[cfg(feature = "biome-credentials")]
     enable_biome_credentials: Option<bool>,
     #[cfg(feature = "oauth")]
@@ -80,6 +104,18 @@ pub struct PartialConfig {
     oauth_openid_auth_params: Option<Vec<(String, String)>>,
     #[cfg(feature = "oauth")]
     oauth_openid_scopes: Option<Vec<String>>,
+    #[cfg(feature = "saml")]
+    saml_sp_entity_id: Option<String>,
+    #[cfg(feature = "saml")]
+    saml_acs_url: Option<String>,
+    #[cfg(feature = "saml")]
+    saml_idp_entity_id: Option<String>,
+    #[cfg(feature = "saml")]
+    saml_idp_sso_url: Option<String>,
+    #[cfg(feature = "saml")]
+    saml_idp_certificate: Option<String>,
+    #[cfg(feature = "authorization-audit")]
+    audit_log_file: Option<String>,
     strict_ref_counts: Option<bool>,
 }
 
@@ -112,6 +148,10 @@ impl PartialConfig {
             registries: None,
             registry_auto_refresh: None,
             registry_forced_refresh: None,
+            #[cfg(feature = "registry-remote-signature")]
+            registry_public_key: None,
+            #[cfg(feature = "registry-signed-nodes")]
+            registry_signature_policy: None,
             heartbeat: None,
             admin_timeout: None,
             state_dir: None,
@@ -119,6 +159,26 @@ impl PartialConfig {
             no_tls: None,
             #[cfg(feature = "rest-api-cors")]
             whitelist: None,
+            #[cfg(feature = "biome-notifications")]
+            biome_notification_types: None,
+            #[cfg(feature = "biome-login-lockout")]
+            biome_lockout_duration: None,
+            #[cfg(feature = "biome-login-lockout")]
+            biome_max_failed_logins: None,
+            #[cfg(feature = "biome-password-reset")]
+            biome_password_reset_command: None,
+            #[cfg(feature = "socket-compression")]
+            network_compression: None,
+            #[cfg(feature = "socket-compression")]
+            network_compression_threshold: None,
+            #[cfg(feature = "network-rate-limit")]
+            peer_rate_limit_messages: None,
+            #[cfg(feature = "network-rate-limit")]
+            peer_rate_limit_bytes: None,
+            #[cfg(feature = "network-rate-limit")]
+            circuit_rate_limit_messages: None,
+            #[cfg(feature = "network-rate-limit")]
+            circuit_rate_limit_bytes: None,
             #[cfg(feature = "biome-credentials")]
             enable_biome_credentials: None,
             #[cfg(feature = "oauth")]
@@ -135,6 +195,18 @@ impl PartialConfig {
             oauth_openid_auth_params: None,
             #[cfg(feature = "oauth")]
             oauth_openid_scopes: None,
+            #[cfg(feature = "saml")]
+            saml_sp_entity_id: None,
+            #[cfg(feature = "saml")]
+            saml_acs_url: None,
+            #[cfg(feature = "saml")]
+            saml_idp_entity_id: None,
+            #[cfg(feature = "saml")]
+            saml_idp_sso_url: None,
+            #[cfg(feature = "saml")]
+            saml_idp_certificate: None,
+            #[cfg(feature = "authorization-audit")]
+            audit_log_file: None,
             strict_ref_counts: None,
         }
     }
@@ -230,6 +302,16 @@ impl PartialConfig {
         self.registry_forced_refresh
     }
 
+    #[cfg(feature = "registry-remote-signature")]
+    pub fn registry_public_key(&self) -> Option<String> {
+        self.registry_public_key.clone()
+    }
+
+    #[cfg(feature = "registry-signed-nodes")]
+    pub fn registry_signature_policy(&self) -> Option<String> {
+        self.registry_signature_policy.clone()
+    }
+
     pub fn heartbeat(&self) -> Option<u64> {
         self.heartbeat
     }
@@ -255,6 +337,56 @@ impl PartialConfig {
         self.whitelist.clone()
     }
 
+    #[cfg(feature = "biome-notifications")]
+    pub fn biome_notification_types(&self) -> Option<Vec<String>> {
+        self.biome_notification_types.clone()
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    pub fn biome_lockout_duration(&self) -> Option<u64> {
+        self.biome_lockout_duration
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    pub fn biome_max_failed_logins(&self) -> Option<u32> {
+        self.biome_max_failed_logins
+    }
+
+    #[cfg(feature = "biome-password-reset")]
+    pub fn biome_password_reset_command(&self) -> Option<String> {
+        self.biome_password_reset_command.clone()
+    }
+
+    #[cfg(feature = "socket-compression")]
+    pub fn network_compression(&self) -> Option<Vec<String>> {
+        self.network_compression.clone()
+    }
+
+    #[cfg(feature = "socket-compression")]
+    pub fn network_compression_threshold(&self) -> Option<u64> {
+        self.network_compression_threshold
+    }
+
+    #[cfg(feature = "network-rate-limit")]
+    pub fn peer_rate_limit_messages(&self) -> Option<u64> {
+        self.peer_rate_limit_messages
+    }
+
+    #[cfg(feature = "network-rate-limit")]
+    pub fn peer_rate_limit_bytes(&self) -> Option<u64> {
+        self.peer_rate_limit_bytes
+    }
+
+    #[cfg(feature = "network-rate-limit")]
+    pub fn circuit_rate_limit_messages(&self) -> Option<u64> {
+        self.circuit_rate_limit_messages
+    }
+
+    #[cfg(feature = "network-rate-limit")]
+    pub fn circuit_rate_limit_bytes(&self) -> Option<u64> {
+        self.circuit_rate_limit_bytes
+    }
+
     #[cfg(feature = "biome-credentials")]
     pub fn enable_biome_credentials(&self) -> Option<bool> {
         self.enable_biome_credentials
@@ -295,6 +427,36 @@ impl PartialConfig {
         self.oauth_openid_scopes.clone()
     }
 
+    #[cfg(feature = "saml")]
+    pub fn saml_sp_entity_id(&self) -> Option<String> {
+        self.saml_sp_entity_id.clone()
+    }
+
+    #[cfg(feature = "saml")]
+    pub fn saml_acs_url(&self) -> Option<String> {
+        self.saml_acs_url.clone()
+    }
+
+    #[cfg(feature = "saml")]
+    pub fn saml_idp_entity_id(&self) -> Option<String> {
+        self.saml_idp_entity_id.clone()
+    }
+
+    #[cfg(feature = "saml")]
+    pub fn saml_idp_sso_url(&self) -> Option<String> {
+        self.saml_idp_sso_url.clone()
+    }
+
+    #[cfg(feature = "saml")]
+    pub fn saml_idp_certificate(&self) -> Option<String> {
+        self.saml_idp_certificate.clone()
+    }
+
+    #[cfg(feature = "authorization-audit")]
+    pub fn audit_log_file(&self) -> Option<String> {
+        self.audit_log_file.clone()
+    }
+
     pub fn strict_ref_counts(&self) -> Option<bool> {
         self.strict_ref_counts
     }
@@ -539,6 +701,34 @@ impl PartialConfig {
         self
     }
 
+    #[cfg(feature = "registry-remote-signature")]
+    /// Adds a `registry_public_key` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry_public_key` - Public key that remote registry files must be signed by.
+    ///
+    pub fn with_registry_public_key(mut self, registry_public_key: Option<String>) -> Self {
+        self.registry_public_key = registry_public_key;
+        self
+    }
+
+    #[cfg(feature = "registry-signed-nodes")]
+    /// Adds a `registry_signature_policy` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry_signature_policy` - Which nodes from remote registries are accepted, based on
+    ///   their signatures.
+    ///
+    pub fn with_registry_signature_policy(
+        mut self,
+        registry_signature_policy: Option<String>,
+    ) -> Self {
+        self.registry_signature_policy = registry_signature_policy;
+        self
+    }
+
     /// Adds a `heartbeat` value to the `PartialConfig` object.
     ///
     /// # Arguments
@@ -610,6 +800,147 @@ impl PartialConfig {
         self
     }
 
+    #[cfg(feature = "biome-notifications")]
+    /// Adds a `biome_notification_types` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `biome_notification_types` - The circuit management types whose admin events are turned
+    ///   into Biome notifications
+    ///
+    pub fn with_biome_notification_types(
+        mut self,
+        biome_notification_types: Option<Vec<String>>,
+    ) -> Self {
+        self.biome_notification_types = biome_notification_types;
+        self
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    /// Adds a `biome_lockout_duration` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `biome_lockout_duration` - The number of seconds a Biome user is locked out for after too
+    ///   many failed logins
+    ///
+    pub fn with_biome_lockout_duration(mut self, biome_lockout_duration: Option<u64>) -> Self {
+        self.biome_lockout_duration = biome_lockout_duration;
+        self
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    /// Adds a `biome_max_failed_logins` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `biome_max_failed_logins` - The number of consecutive failed logins after which a Biome
+    ///   user is locked out
+    ///
+    pub fn with_biome_max_failed_logins(mut self, biome_max_failed_logins: Option<u32>) -> Self {
+        self.biome_max_failed_logins = biome_max_failed_logins;
+        self
+    }
+
+    #[cfg(feature = "biome-password-reset")]
+    /// Adds a `biome_password_reset_command` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `biome_password_reset_command` - The command run to deliver Biome password reset tokens
+    ///
+    pub fn with_biome_password_reset_command(
+        mut self,
+        biome_password_reset_command: Option<String>,
+    ) -> Self {
+        self.biome_password_reset_command = biome_password_reset_command;
+        self
+    }
+
+    #[cfg(feature = "socket-compression")]
+    /// Adds a `network_compression` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `network_compression` - The compression algorithms to offer on network connections, in
+    ///   order of preference
+    ///
+    pub fn with_network_compression(mut self, network_compression: Option<Vec<String>>) -> Self {
+        self.network_compression = network_compression;
+        self
+    }
+
+    #[cfg(feature = "socket-compression")]
+    /// Adds a `network_compression_threshold` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `network_compression_threshold` - The minimum size, in bytes, of a message to be
+    ///   compressed
+    ///
+    pub fn with_network_compression_threshold(
+        mut self,
+        network_compression_threshold: Option<u64>,
+    ) -> Self {
+        self.network_compression_threshold = network_compression_threshold;
+        self
+    }
+
+    #[cfg(feature = "network-rate-limit")]
+    /// Adds a `peer_rate_limit_messages` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer_rate_limit_messages` - The maximum number of circuit messages per second to forward
+    ///   to a single peer
+    ///
+    pub fn with_peer_rate_limit_messages(mut self, peer_rate_limit_messages: Option<u64>) -> Self {
+        self.peer_rate_limit_messages = peer_rate_limit_messages;
+        self
+    }
+
+    #[cfg(feature = "network-rate-limit")]
+    /// Adds a `peer_rate_limit_bytes` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer_rate_limit_bytes` - The maximum number of circuit message bytes per second to
+    ///   forward to a single peer
+    ///
+    pub fn with_peer_rate_limit_bytes(mut self, peer_rate_limit_bytes: Option<u64>) -> Self {
+        self.peer_rate_limit_bytes = peer_rate_limit_bytes;
+        self
+    }
+
+    #[cfg(feature = "network-rate-limit")]
+    /// Adds a `circuit_rate_limit_messages` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit_rate_limit_messages` - The maximum number of messages per second to forward on a
+    ///   single circuit
+    ///
+    pub fn with_circuit_rate_limit_messages(
+        mut self,
+        circuit_rate_limit_messages: Option<u64>,
+    ) -> Self {
+        self.circuit_rate_limit_messages = circuit_rate_limit_messages;
+        self
+    }
+
+    #[cfg(feature = "network-rate-limit")]
+    /// Adds a `circuit_rate_limit_bytes` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit_rate_limit_bytes` - The maximum number of message bytes per second to forward on
+    ///   a single circuit
+    ///
+    pub fn with_circuit_rate_limit_bytes(mut self, circuit_rate_limit_bytes: Option<u64>) -> Self {
+        self.circuit_rate_limit_bytes = circuit_rate_limit_bytes;
+        self
+    }
+
     #[cfg(feature = "biome-credentials")]
     /// Adds an `enable_biome_credentials` value to the `PartialConfig` object.
     ///
@@ -710,6 +1041,78 @@ impl PartialConfig {
         self
     }
 
+    #[cfg(feature = "saml")]
+    /// Adds a `saml_sp_entity_id` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `saml_sp_entity_id` - Add the SAML service provider entity ID to the REST API SAML configuration
+    ///
+    pub fn with_saml_sp_entity_id(mut self, saml_sp_entity_id: Option<String>) -> Self {
+        self.saml_sp_entity_id = saml_sp_entity_id;
+        self
+    }
+
+    #[cfg(feature = "saml")]
+    /// Adds a `saml_acs_url` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `saml_acs_url` - Add the SAML assertion consumer service URL to the REST API SAML configuration
+    ///
+    pub fn with_saml_acs_url(mut self, saml_acs_url: Option<String>) -> Self {
+        self.saml_acs_url = saml_acs_url;
+        self
+    }
+
+    #[cfg(feature = "saml")]
+    /// Adds a `saml_idp_entity_id` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `saml_idp_entity_id` - Add the SAML identity provider entity ID to the REST API SAML configuration
+    ///
+    pub fn with_saml_idp_entity_id(mut self, saml_idp_entity_id: Option<String>) -> Self {
+        self.saml_idp_entity_id = saml_idp_entity_id;
+        self
+    }
+
+    #[cfg(feature = "saml")]
+    /// Adds a `saml_idp_sso_url` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `saml_idp_sso_url` - Add the SAML identity provider SSO URL to the REST API SAML configuration
+    ///
+    pub fn with_saml_idp_sso_url(mut self, saml_idp_sso_url: Option<String>) -> Self {
+        self.saml_idp_sso_url = saml_idp_sso_url;
+        self
+    }
+
+    #[cfg(feature = "saml")]
+    /// Adds a `saml_idp_certificate` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `saml_idp_certificate` - Add the SAML identity provider certificate to the REST API SAML configuration
+    ///
+    pub fn with_saml_idp_certificate(mut self, saml_idp_certificate: Option<String>) -> Self {
+        self.saml_idp_certificate = saml_idp_certificate;
+        self
+    }
+
+    #[cfg(feature = "authorization-audit")]
+    /// Adds an `audit_log_file` value to the `PartialConfig` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `audit_log_file` - The file that the REST API audit log is written to, as JSON lines
+    ///
+    pub fn with_audit_log_file(mut self, audit_log_file: Option<String>) -> Self {
+        self.audit_log_file = audit_log_file;
+        self
+    }
+
     /// Adds a `strict_ref_counts` value to the `PartialConfig` object.
     ///
     /// # Arguments
@@ -51,11 +51,35 @@ struct TomlConfig {
     registries: Option<Vec<String>>,
     registry_auto_refresh: Option<u64>,
     registry_forced_refresh: Option<u64>,
+    #[cfg(feature = "registry-remote-signature")]
+    registry_public_key: Option<String>,
+    #[cfg(feature = "registry-signed-nodes")]
+    registry_signature_policy: Option<String>,
     heartbeat: Option<u64>,
     admin_timeout: Option<u64>,
     version: Option<String>,
     #[cfg(feature = "rest-api-cors")]
     whitelist: Option<Vec<String>>,
+    #[cfg(feature = "biome-notifications")]
+    biome_notification_types: Option<Vec<String>>,
+    #[cfg(feature = "biome-login-lockout")]
+    biome_lockout_duration: Option<u64>,
+    #[cfg(feature = "biome-login-lockout")]
+    biome_max_failed_logins: Option<u32>,
+    #[cfg(feature = "biome-password-reset")]
+    biome_password_reset_command: Option<String>,
+    #[cfg(feature = "socket-compression")]
+    network_compression: Option<Vec<String>>,
+    #[cfg(feature = "socket-compression")]
+    network_compression_threshold: Option<u64>,
+    #[cfg(feature = "network-rate-limit")]
+    peer_rate_limit_messages: Option<u64>,
+    #[cfg(feature = "network-rate-limit")]
+    peer_rate_limit_bytes: Option<u64>,
+    #[cfg(feature = "network-rate-limit")]
+    circuit_rate_limit_messages: Option<u64>,
+    #[cfg(feature = "network-rate-limit")]
+    circuit_rate_limit_bytes: Option<u64>,
     #[cfg(feature = "oauth")]
     oauth_provider: Option<String>,
     #[cfg(feature = "oauth")]
@@ -70,6 +94,18 @@ struct TomlConfig {
     oauth_openid_auth_params: Option<Vec<(String, String)>>,
     #[cfg(feature = "oauth")]
     oauth_openid_scopes: Option<Vec<String>>,
+    #[cfg(feature = "saml")]
+    saml_sp_entity_id: Option<String>,
+    #[cfg(feature = "saml")]
+    saml_acs_url: Option<String>,
+    #[cfg(feature = "saml")]
+    saml_idp_entity_id: Option<String>,
+    #[cfg(feature = "saml")]
+    saml_idp_sso_url: Option<String>,
+    #[cfg(feature = "saml")]
+    saml_idp_certificate: Option<String>,
+    #[cfg(feature = "authorization-audit")]
+    audit_log_file: Option<String>,
 
     // Deprecated values
     cert_dir: Option<String>,
@@ -164,11 +200,58 @@ impl PartialConfigBuilder for TomlPartialConfigBuilder {
             partial_config = partial_config.with_service_endpoint(self.toml_config.service_endpoint)
         }
 
+        #[cfg(feature = "registry-remote-signature")]
+        {
+            partial_config =
+                partial_config.with_registry_public_key(self.toml_config.registry_public_key);
+        }
+
+        #[cfg(feature = "registry-signed-nodes")]
+        {
+            partial_config = partial_config
+                .with_registry_signature_policy(self.toml_config.registry_signature_policy);
+        }
+
         #[cfg(feature = "rest-api-cors")]
         {
             partial_config = partial_config.with_whitelist(self.toml_config.whitelist);
         }
 
+        #[cfg(feature = "biome-notifications")]
+        {
+            partial_config = partial_config
+                .with_biome_notification_types(self.toml_config.biome_notification_types);
+        }
+
+        #[cfg(feature = "biome-login-lockout")]
+        {
+            partial_config = partial_config
+                .with_biome_lockout_duration(self.toml_config.biome_lockout_duration)
+                .with_biome_max_failed_logins(self.toml_config.biome_max_failed_logins);
+        }
+
+        #[cfg(feature = "biome-password-reset")]
+        {
+            partial_config = partial_config
+                .with_biome_password_reset_command(self.toml_config.biome_password_reset_command);
+        }
+
+        #[cfg(feature = "socket-compression")]
+        {
+            partial_config = partial_config
+                .with_network_compression(self.toml_config.network_compression)
+                .with_network_compression_threshold(self.toml_config.network_compression_threshold);
+        }
+
+        #[cfg(feature = "network-rate-limit")]
+        {
+            partial_config = partial_config
+                .with_peer_rate_limit_messages(self.toml_config.peer_rate_limit_messages)
+                .with_peer_rate_limit_bytes(self.toml_config.peer_rate_limit_bytes)
+                .with_circuit_rate_limit_messages(self.toml_config.circuit_rate_limit_messages)
+                .with_circuit_rate_limit_bytes(self.toml_config.circuit_rate_limit_bytes);
+        }
+
         #[cfg(feature = "oauth")]
         {
             partial_config = partial_config
@@ -181,6 +264,21 @@ impl PartialConfigBuilder for TomlPartialConfigBuilder {
                 .with_oauth_openid_scopes(self.toml_config.oauth_openid_scopes);
         }
 
+        #[cfg(feature = "saml")]
+        {
+            partial_config = partial_config
+                .with_saml_sp_entity_id(self.toml_config.saml_sp_entity_id)
+                .with_saml_acs_url(self.toml_config.saml_acs_url)
+                .with_saml_idp_entity_id(self.toml_config.saml_idp_entity_id)
+                .with_saml_idp_sso_url(self.toml_config.saml_idp_sso_url)
+                .with_saml_idp_certificate(self.toml_config.saml_idp_certificate);
+        }
+
+        #[cfg(feature = "authorization-audit")]
+        {
+            partial_config = partial_config.with_audit_log_file(self.toml_config.audit_log_file);
+        }
+
         // deprecated values, only set if the current value was not set
         if partial_config.tls_cert_dir().is_none() {
             partial_config = partial_config.with_tls_cert_dir(self.toml_config.cert_dir)
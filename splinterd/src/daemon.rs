@@ -22,19 +22,37 @@ use std::sync::{mpsc::channel, Arc};
 use std::thread;
 use std::time::Duration;
 
+#[cfg(feature = "registry-remote-signature")]
+use cylinder::PublicKey;
 use cylinder::{secp256k1::Secp256k1Context, VerifierFactory};
 #[cfg(feature = "health-service")]
 use health::HealthService;
 #[cfg(feature = "service-arg-validation")]
 use scabbard::service::ScabbardArgValidator;
 use scabbard::service::ScabbardFactory;
+#[cfg(feature = "tls-cert-reload")]
+use signal_hook::{consts::SIGHUP, iterator::Signals};
 use splinter::admin::rest_api::CircuitResourceProvider;
+#[cfg(feature = "biome-notifications")]
+use splinter::admin::service::AdminCommands;
 use splinter::admin::service::{admin_service_id, AdminService, AdminServiceBuilder};
 use splinter::admin::store::yaml::YamlAdminServiceStore;
+#[cfg(feature = "biome-notifications")]
+use splinter::admin::store::AdminServiceEvent;
 #[cfg(feature = "biome-credentials")]
 use splinter::biome::credentials::rest_api::BiomeCredentialsRestResourceProviderBuilder;
+#[cfg(feature = "biome-login-lockout")]
+use splinter::biome::credentials::rest_api::{
+    BiomeCredentialsRestConfig, BiomeCredentialsRestConfigBuilder,
+};
 #[cfg(feature = "biome-key-management")]
 use splinter::biome::key_management::rest_api::BiomeKeyManagementRestResourceProvider;
+#[cfg(feature = "biome-key-wrapping")]
+use splinter::biome::key_management::wrapping::LocalKeyWrapper;
+#[cfg(feature = "biome-notifications")]
+use splinter::biome::notifications::{
+    admin::AdminEventNotifier, rest_api::BiomeNotificationsRestResourceProvider,
+};
 #[cfg(feature = "biome-profile")]
 use splinter::biome::profile::rest_api::BiomeProfileRestResourceProvider;
 use splinter::circuit::handlers::{
@@ -53,18 +71,28 @@ use splinter::network::dispatch::{
     dispatch_channel, DispatchLoopBuilder, DispatchMessageSender, Dispatcher,
 };
 use splinter::network::handlers::{NetworkEchoHandler, NetworkHeartbeatHandler};
+#[cfg(feature = "network-priority")]
+use splinter::network::priority::PriorityWeights;
+#[cfg(feature = "network-rate-limit")]
+use splinter::network::rate_limit::{RateLimit, RateLimiter};
 use splinter::orchestrator::ServiceOrchestratorBuilder;
 use splinter::peer::interconnect::NetworkMessageSender;
 use splinter::peer::interconnect::PeerInterconnectBuilder;
 use splinter::peer::PeerManager;
 use splinter::protos::circuit::CircuitMessageType;
 use splinter::protos::network::NetworkMessageType;
+#[cfg(feature = "registry-signed-nodes")]
+use splinter::registry::NodeSignaturePolicy;
+#[cfg(feature = "registry-node-status")]
+use splinter::registry::RegistryRestResourceProvider;
 use splinter::registry::{
     LocalYamlRegistry, RegistryReader, RemoteYamlRegistry, RemoteYamlShutdownHandle, RwRegistry,
     UnifiedRegistry,
 };
 #[cfg(feature = "authorization-handler-allow-keys")]
 use splinter::rest_api::auth::authorization::allow_keys::AllowKeysAuthorizationHandler;
+#[cfg(feature = "authorization-audit")]
+use splinter::rest_api::auth::authorization::audit::{AuditSink, JsonLinesAuditSink};
 #[cfg(feature = "authorization-handler-maintenance")]
 use splinter::rest_api::auth::authorization::maintenance::MaintenanceModeAuthorizationHandler;
 #[cfg(feature = "authorization-handler-rbac")]
@@ -79,20 +107,38 @@ use splinter::rest_api::auth::authorization::rbac::{
 use splinter::rest_api::auth::authorization::AuthorizationHandler;
 #[cfg(feature = "authorization")]
 use splinter::rest_api::auth::authorization::Permission;
+#[cfg(all(feature = "biome-totp", unix))]
+use splinter::rest_api::secrets::FileSecretManager;
 #[cfg(feature = "oauth")]
 use splinter::rest_api::OAuthConfig;
+#[cfg(all(feature = "https-bind", feature = "tls-cert-reload"))]
+use splinter::rest_api::RestApiTlsReloader;
 use splinter::rest_api::{
     AuthConfig, Method, Resource, RestApiBuilder, RestApiServerError, RestResourceProvider,
 };
+#[cfg(feature = "saml")]
+use splinter::saml::SamlServiceProviderBuilder;
 use splinter::service;
 #[cfg(feature = "service-arg-validation")]
 use splinter::service::validation::ServiceArgValidator;
 use splinter::threading::lifecycle::ShutdownHandle;
+#[cfg(all(feature = "quic-transport", feature = "tls-cert-reload"))]
+use splinter::transport::quic::QuicReloadHandle;
+#[cfg(feature = "endpoint-resolution")]
+use splinter::transport::resolver::RegistryEndpointResolver;
+#[cfg(feature = "tls-cert-reload")]
+use splinter::transport::socket::TlsReloadHandle;
 use splinter::transport::{
     inproc::InprocTransport, multi::MultiTransport, AcceptError, ConnectError, Connection,
     Incoming, ListenError, Listener, Transport,
 };
 
+#[cfg(feature = "biome-password-reset")]
+use crate::password_reset::CommandPasswordResetSender;
+#[cfg(feature = "network-priority")]
+use crate::priority::SplinterdPriorityClassifier;
+#[cfg(feature = "network-rate-limit")]
+use crate::rate_limit::{start_peer_bucket_cleanup, RateLimitedRoutingTableWriter};
 use crate::routes;
 
 const ADMIN_SERVICE_PROCESSOR_INCOMING_CAPACITY: usize = 8;
@@ -106,6 +152,11 @@ const HEALTH_SERVICE_PROCESSOR_OUTGOING_CAPACITY: usize = 8;
 #[cfg(feature = "health-service")]
 const HEALTH_SERVICE_PROCESSOR_CHANNEL_CAPACITY: usize = 8;
 
+#[cfg(all(feature = "biome-totp", unix))]
+const TOTP_SECRET_FILE: &str = "biome_totp.secret";
+#[cfg(feature = "biome-key-wrapping")]
+const KEY_ENCRYPTION_KEY_FILE: &str = "biome_kek.secret";
+
 pub struct SplinterDaemon {
     #[cfg(feature = "authorization-handler-allow-keys")]
     config_dir: String,
@@ -125,10 +176,22 @@ pub struct SplinterDaemon {
     registries: Vec<String>,
     registry_auto_refresh: u64,
     registry_forced_refresh: u64,
+    #[cfg(feature = "registry-remote-signature")]
+    registry_public_key: Option<PublicKey>,
+    #[cfg(feature = "registry-signed-nodes")]
+    registry_signature_policy: NodeSignaturePolicy,
     storage_type: Option<String>,
     admin_timeout: Duration,
     #[cfg(feature = "rest-api-cors")]
     whitelist: Option<Vec<String>>,
+    #[cfg(feature = "biome-notifications")]
+    biome_notification_types: Vec<String>,
+    #[cfg(feature = "biome-login-lockout")]
+    biome_lockout_duration: Option<u64>,
+    #[cfg(feature = "biome-login-lockout")]
+    biome_max_failed_logins: Option<u32>,
+    #[cfg(feature = "biome-password-reset")]
+    biome_password_reset_command: Option<String>,
     #[cfg(feature = "biome-credentials")]
     enable_biome_credentials: bool,
     #[cfg(feature = "oauth")]
@@ -145,8 +208,28 @@ pub struct SplinterDaemon {
     oauth_openid_auth_params: Option<Vec<(String, String)>>,
     #[cfg(feature = "oauth")]
     oauth_openid_scopes: Option<Vec<String>>,
+    #[cfg(feature = "saml")]
+    saml_sp_entity_id: Option<String>,
+    #[cfg(feature = "saml")]
+    saml_acs_url: Option<String>,
+    #[cfg(feature = "saml")]
+    saml_idp_entity_id: Option<String>,
+    #[cfg(feature = "saml")]
+    saml_idp_sso_url: Option<String>,
+    #[cfg(feature = "saml")]
+    saml_idp_certificate: Option<String>,
+    #[cfg(feature = "authorization-audit")]
+    audit_log_file: Option<String>,
     heartbeat: u64,
     strict_ref_counts: bool,
+    #[cfg(feature = "tls-cert-reload")]
+    tls_reload_handle: Option<TlsReloadHandle>,
+    #[cfg(all(feature = "quic-transport", feature = "tls-cert-reload"))]
+    quic_reload_handle: Option<QuicReloadHandle>,
+    #[cfg(feature = "network-rate-limit")]
+    peer_rate_limit: RateLimit,
+    #[cfg(feature = "network-rate-limit")]
+    circuit_rate_limit: RateLimit,
 }
 
 impl SplinterDaemon {
@@ -216,6 +299,16 @@ impl SplinterDaemon {
         let routing_reader: Box<dyn RoutingTableReader> = Box::new(table.clone());
         let routing_writer: Box<dyn RoutingTableWriter> = Box::new(table);
 
+        #[cfg(feature = "network-rate-limit")]
+        let rate_limiter = RateLimiter::new(
+            self.peer_rate_limit.clone(),
+            self.circuit_rate_limit.clone(),
+        );
+        #[cfg(feature = "network-rate-limit")]
+        let routing_writer: Box<dyn RoutingTableWriter> = Box::new(
+            RateLimitedRoutingTableWriter::new(routing_writer, rate_limiter.clone()),
+        );
+
         // set up the listeners on the transport. This will set up listeners for different
         // transports based on the protocol prefix of the endpoint.
         let network_listeners = self
@@ -279,6 +372,24 @@ impl SplinterDaemon {
         authorizers.add_authorizer("inproc", inproc_authorizer);
         authorizers.add_authorizer("", authorization_manager.authorization_connector());
 
+        let (registry, mut registry_shutdown) = create_registry(
+            &self.state_dir,
+            &self.registries,
+            self.registry_auto_refresh,
+            self.registry_forced_refresh,
+            #[cfg(feature = "registry-remote-signature")]
+            self.registry_public_key.clone(),
+            #[cfg(feature = "registry-signed-nodes")]
+            self.registry_signature_policy,
+            &*store_factory,
+        );
+
+        // Peers may be given by node identity alone, to be resolved using the registry
+        #[cfg(feature = "endpoint-resolution")]
+        transport.add_resolver(Box::new(RegistryEndpointResolver::new(
+            registry.clone_box_as_reader(),
+        )));
+
         let mut connection_manager = ConnectionManager::builder()
             .with_authorizer(Box::new(authorizers))
             .with_matrix_life_cycle(self.mesh.get_life_cycle())
@@ -302,6 +413,9 @@ impl SplinterDaemon {
 
         let peer_connector = peer_manager.connector();
 
+        #[cfg(feature = "network-rate-limit")]
+        start_peer_bucket_cleanup(&peer_connector, rate_limiter.clone())?;
+
         // Listen for services
         Self::listen_for_services(
             connection_connector.clone(),
@@ -359,28 +473,34 @@ impl SplinterDaemon {
             &self.node_id,
             routing_reader.clone(),
             routing_writer.clone(),
+            #[cfg(feature = "network-rate-limit")]
+            rate_limiter,
         );
-        let mut circuit_dispatch_loop = DispatchLoopBuilder::new()
+        let circuit_dispatch_loop_builder = DispatchLoopBuilder::new()
             .with_dispatcher(circuit_dispatcher)
-            .with_thread_name("CircuitDispatchLoop".to_string())
-            .build()
-            .map_err(|err| {
-                StartError::NetworkError(format!("Unable to create circuit dispatch loop: {}", err))
-            })?;
+            .with_thread_name("CircuitDispatchLoop".to_string());
+        #[cfg(feature = "network-priority")]
+        let circuit_dispatch_loop_builder = circuit_dispatch_loop_builder
+            .with_priority_classifier(Box::new(SplinterdPriorityClassifier));
+        let mut circuit_dispatch_loop = circuit_dispatch_loop_builder.build().map_err(|err| {
+            StartError::NetworkError(format!("Unable to create circuit dispatch loop: {}", err))
+        })?;
         let circuit_dispatch_sender = circuit_dispatch_loop.new_dispatcher_sender();
 
         // Set up the Network dispatcher
         let network_dispatcher =
             set_up_network_dispatcher(network_sender, &self.node_id, circuit_dispatch_sender);
 
-        let mut network_dispatch_loop = DispatchLoopBuilder::new()
+        let network_dispatch_loop_builder = DispatchLoopBuilder::new()
             .with_dispatcher(network_dispatcher)
             .with_thread_name("NetworkDispatchLoop".to_string())
-            .with_dispatch_channel((network_dispatcher_sender, network_dispatch_receiver))
-            .build()
-            .map_err(|err| {
-                StartError::NetworkError(format!("Unable to create network dispatch loop: {}", err))
-            })?;
+            .with_dispatch_channel((network_dispatcher_sender, network_dispatch_receiver));
+        #[cfg(feature = "network-priority")]
+        let network_dispatch_loop_builder = network_dispatch_loop_builder
+            .with_priority_classifier(Box::new(SplinterdPriorityClassifier));
+        let mut network_dispatch_loop = network_dispatch_loop_builder.build().map_err(|err| {
+            StartError::NetworkError(format!("Unable to create network dispatch loop: {}", err))
+        })?;
 
         // setup threads to listen on the network ports and add incoming connections to the network
         // these threads will just be dropped on shutdown
@@ -456,12 +576,10 @@ impl SplinterDaemon {
                 )
             })?;
 
-        let (registry, mut registry_shutdown) = create_registry(
-            &self.state_dir,
-            &self.registries,
-            self.registry_auto_refresh,
-            self.registry_forced_refresh,
-            &*store_factory,
+        #[cfg(feature = "registry-node-status")]
+        let registry_resource_provider = RegistryRestResourceProvider::new(
+            registry.clone_box(),
+            Box::new(peer_connector.clone()),
         );
 
         let mut admin_service_builder = AdminServiceBuilder::new();
@@ -491,6 +609,33 @@ impl SplinterDaemon {
             StartError::AdminServiceError(format!("unable to create admin service: {}", err))
         })?;
 
+        // Each proposal and vote event of the configured circuit management types is turned into
+        // a notification for every Biome user
+        #[cfg(feature = "biome-notifications")]
+        for circuit_management_type in &self.biome_notification_types {
+            let credentials_store = store_factory.get_biome_credentials_store();
+            let notifier = AdminEventNotifier::new(
+                store_factory.get_biome_notification_store(),
+                Box::new(
+                    move |_: &AdminServiceEvent| -> Result<Vec<String>, InternalError> {
+                        credentials_store
+                            .list_usernames()
+                            .map(|users| users.into_iter().map(|user| user.user_id).collect())
+                            .map_err(|err| InternalError::from_source(Box::new(err)))
+                    },
+                ),
+            );
+            admin_service
+                .commands()
+                .add_event_subscriber(circuit_management_type, Box::new(notifier))
+                .map_err(|err| {
+                    StartError::AdminServiceError(format!(
+                        "unable to add notifier for {} events: {}",
+                        circuit_management_type, err
+                    ))
+                })?;
+        }
+
         let node_id = self.node_id.clone();
         let display_name = self.display_name.clone();
         #[cfg(feature = "service-endpoint")]
@@ -514,7 +659,12 @@ impl SplinterDaemon {
         #[allow(unused_mut)]
         let mut rest_api_builder = RestApiBuilder::new()
             .with_bind(bind)
-            .add_resources(registry.resources())
+            .add_resources(
+                #[cfg(not(feature = "registry-node-status"))]
+                registry.resources(),
+                #[cfg(feature = "registry-node-status")]
+                registry_resource_provider.resources(),
+            )
             .add_resources(admin_service.resources())
             .add_resources(orchestrator_resources)
             .add_resources(circuit_resource_provider.resources());
@@ -538,6 +688,14 @@ impl SplinterDaemon {
                     MaintenanceModeAuthorizationHandler::new(Some(rbac_store.clone()));
                 #[cfg(not(feature = "authorization-handler-rbac"))]
                 let maintenance_mode_auth_handler = MaintenanceModeAuthorizationHandler::default();
+                let maintenance_mode_auth_handler = maintenance_mode_auth_handler
+                    .with_store(store_factory.get_maintenance_mode_store())
+                    .map_err(|err| {
+                        StartError::StorageError(format!(
+                            "unable to load maintenance mode: {}",
+                            err
+                        ))
+                    })?;
                 rest_api_builder =
                     rest_api_builder.add_resources(maintenance_mode_auth_handler.resources());
                 authorization_handlers.push(Box::new(maintenance_mode_auth_handler));
@@ -555,6 +713,17 @@ impl SplinterDaemon {
                 );
             }
 
+            // Authorization decisions are written to the audit log file if one is configured, and
+            // to the database otherwise
+            #[cfg(feature = "authorization-audit")]
+            {
+                let audit_sink: Box<dyn AuditSink> = match &self.audit_log_file {
+                    Some(path) => Box::new(JsonLinesAuditSink::new(path)),
+                    None => store_factory.get_audit_sink(),
+                };
+                rest_api_builder = rest_api_builder.with_audit_sink(audit_sink);
+            }
+
             rest_api_builder = rest_api_builder
                 .with_authorization_handlers(authorization_handlers)
                 .add_resource(Resource::build("/openapi.yaml").add_method(
@@ -612,6 +781,12 @@ impl SplinterDaemon {
             verifier: Secp256k1Context::new().new_verifier(),
         });
 
+        // Add API keys as an auth provider; keys are issued to machine clients through the REST API
+        #[cfg(feature = "api-keys")]
+        auth_configs.push(AuthConfig::ApiKey {
+            api_key_store: store_factory.get_api_key_store(),
+        });
+
         // Add Biome credentials as an auth provider if it's enabled
         #[cfg(feature = "biome-credentials")]
         if self.enable_biome_credentials {
@@ -628,6 +803,32 @@ impl SplinterDaemon {
                     biome_credentials_builder.with_key_store(store_factory.get_biome_key_store())
             }
 
+            #[cfg(feature = "biome-login-lockout")]
+            {
+                biome_credentials_builder = biome_credentials_builder
+                    .with_credentials_config(self.build_biome_credentials_config()?)
+            }
+
+            // Password reset endpoints are only provided if a command to deliver tokens is set
+            #[cfg(feature = "biome-password-reset")]
+            {
+                if let Some(command) = &self.biome_password_reset_command {
+                    biome_credentials_builder = biome_credentials_builder
+                        .with_password_reset_sender(CommandPasswordResetSender::new(
+                            command.clone(),
+                        ))
+                }
+            }
+
+            // TOTP secrets are encrypted with a key kept in the state directory, so they remain
+            // readable across restarts
+            #[cfg(all(feature = "biome-totp", unix))]
+            {
+                biome_credentials_builder = biome_credentials_builder.with_totp_secret_manager(
+                    FileSecretManager::new(Path::new(&self.state_dir).join(TOTP_SECRET_FILE)),
+                )
+            }
+
             let biome_credentials_resource_provider =
                 biome_credentials_builder.build().map_err(|err| {
                     StartError::RestApiError(format!(
@@ -716,13 +917,106 @@ impl SplinterDaemon {
             }
         }
 
+        #[cfg(feature = "saml")]
+        {
+            // Handle SAML config. If no SAML config values are provided, just skip this;
+            // otherwise, require that all are set.
+            let any_saml_args_provided = self.saml_sp_entity_id.is_some()
+                || self.saml_acs_url.is_some()
+                || self.saml_idp_entity_id.is_some()
+                || self.saml_idp_sso_url.is_some()
+                || self.saml_idp_certificate.is_some();
+            if any_saml_args_provided {
+                let sp_entity_id = self.saml_sp_entity_id.clone().ok_or_else(|| {
+                    StartError::RestApiError(
+                        "missing SAML service provider entity ID configuration".into(),
+                    )
+                })?;
+                let acs_url = self.saml_acs_url.clone().ok_or_else(|| {
+                    StartError::RestApiError(
+                        "missing SAML assertion consumer service URL configuration".into(),
+                    )
+                })?;
+                let idp_entity_id = self.saml_idp_entity_id.clone().ok_or_else(|| {
+                    StartError::RestApiError(
+                        "missing SAML identity provider entity ID configuration".into(),
+                    )
+                })?;
+                let idp_sso_url = self.saml_idp_sso_url.clone().ok_or_else(|| {
+                    StartError::RestApiError(
+                        "missing SAML identity provider SSO URL configuration".into(),
+                    )
+                })?;
+                let idp_certificate_path =
+                    self.saml_idp_certificate.as_deref().ok_or_else(|| {
+                        StartError::RestApiError(
+                            "missing SAML identity provider certificate configuration".into(),
+                        )
+                    })?;
+                let idp_certificate = std::fs::read(idp_certificate_path).map_err(|err| {
+                    StartError::RestApiError(format!(
+                        "unable to read SAML identity provider certificate {}: {}",
+                        idp_certificate_path, err
+                    ))
+                })?;
+
+                let service_provider = SamlServiceProviderBuilder::new()
+                    .with_sp_entity_id(sp_entity_id)
+                    .with_acs_url(acs_url)
+                    .with_idp_entity_id(idp_entity_id)
+                    .with_idp_sso_url(idp_sso_url)
+                    .with_idp_certificate(idp_certificate)
+                    .build()
+                    .map_err(|err| {
+                        StartError::RestApiError(format!("invalid SAML configuration: {}", err))
+                    })?;
+
+                auth_configs.push(AuthConfig::Saml {
+                    service_provider,
+                    session_store: store_factory.get_biome_oauth_user_session_store(),
+                });
+            }
+        }
+
         rest_api_builder = rest_api_builder.with_auth_configs(auth_configs);
 
         #[cfg(feature = "biome-key-management")]
         {
-            rest_api_builder = rest_api_builder.add_resources(
+            let biome_key_management_resource_provider =
                 BiomeKeyManagementRestResourceProvider::new(Arc::new(
                     store_factory.get_biome_key_store(),
+                ));
+
+            // Server-wrapped keys are wrapped with a key-encryption key kept in the state
+            // directory, so they remain usable across restarts
+            #[cfg(feature = "biome-key-wrapping")]
+            let biome_key_management_resource_provider = biome_key_management_resource_provider
+                .with_key_wrapping(
+                    Arc::new(
+                        LocalKeyWrapper::from_file(
+                            Path::new(&self.state_dir).join(KEY_ENCRYPTION_KEY_FILE),
+                        )
+                        .map_err(|err| {
+                            StartError::RestApiError(format!(
+                                "Unable to load Biome key-encryption key: {}",
+                                err
+                            ))
+                        })?,
+                    ),
+                    Arc::new(store_factory.get_biome_credentials_store()),
+                    #[cfg(feature = "biome-login-lockout")]
+                    Arc::new(self.build_biome_credentials_config()?),
+                );
+
+            rest_api_builder =
+                rest_api_builder.add_resources(biome_key_management_resource_provider.resources());
+        }
+
+        #[cfg(feature = "biome-notifications")]
+        {
+            rest_api_builder = rest_api_builder.add_resources(
+                BiomeNotificationsRestResourceProvider::new(Arc::new(
+                    store_factory.get_biome_notification_store(),
                 ))
                 .resources(),
             );
@@ -748,6 +1042,15 @@ impl SplinterDaemon {
 
         let (rest_api_shutdown_handle, rest_api_join_handle) = rest_api_builder.build()?.run()?;
 
+        #[cfg(feature = "tls-cert-reload")]
+        let certificate_reloader = start_certificate_reloader(
+            self.tls_reload_handle.clone(),
+            #[cfg(feature = "quic-transport")]
+            self.quic_reload_handle.clone(),
+            #[cfg(feature = "https-bind")]
+            rest_api_shutdown_handle.tls_reloader(),
+        )?;
+
         let mut admin_shutdown_handle = Self::start_admin_service(admin_connection, admin_service)?;
 
         let (shutdown_tx, shutdown_rx) = channel();
@@ -766,6 +1069,9 @@ impl SplinterDaemon {
 
         running.store(false, Ordering::SeqCst);
 
+        #[cfg(feature = "tls-cert-reload")]
+        certificate_reloader.close();
+
         admin_shutdown_handle.signal_shutdown();
         orchestator_shutdown_handle.signal_shutdown();
         #[cfg(feature = "health-service")]
@@ -827,6 +1133,20 @@ impl SplinterDaemon {
         Ok(())
     }
 
+    #[cfg(feature = "biome-login-lockout")]
+    fn build_biome_credentials_config(&self) -> Result<BiomeCredentialsRestConfig, StartError> {
+        let mut credentials_config = BiomeCredentialsRestConfigBuilder::default();
+        if let Some(duration) = self.biome_lockout_duration {
+            credentials_config = credentials_config.with_lockout_duration_in_secs(duration);
+        }
+        if let Some(max_failed_logins) = self.biome_max_failed_logins {
+            credentials_config = credentials_config.with_max_failed_logins(max_failed_logins);
+        }
+        credentials_config.build().map_err(|err| {
+            StartError::RestApiError(format!("Unable to configure Biome credentials: {}", err))
+        })
+    }
+
     #[cfg(feature = "https-bind")]
     fn build_rest_api_bind(&self) -> Result<splinter::rest_api::BindConfig, StartError> {
         match self.rest_api_endpoint.strip_prefix("http://") {
@@ -976,6 +1296,57 @@ fn start_health_service(
     })
 }
 
+/// Starts a thread that reloads the node's TLS certificates each time the process receives SIGHUP.
+///
+/// Returns a handle that stops the thread.
+#[cfg(feature = "tls-cert-reload")]
+fn start_certificate_reloader(
+    transport_reload_handle: Option<TlsReloadHandle>,
+    #[cfg(feature = "quic-transport")] quic_reload_handle: Option<QuicReloadHandle>,
+    #[cfg(feature = "https-bind")] rest_api_reloader: Option<RestApiTlsReloader>,
+) -> Result<signal_hook::iterator::Handle, StartError> {
+    let mut signals = Signals::new(&[SIGHUP]).map_err(|err| {
+        StartError::TransportError(format!("Unable to register SIGHUP handler: {}", err))
+    })?;
+    let handle = signals.handle();
+
+    thread::Builder::new()
+        .name("CertificateReloader".into())
+        .spawn(move || {
+            for _ in signals.forever() {
+                info!("Received SIGHUP; reloading TLS certificates");
+
+                if let Some(reload_handle) = &transport_reload_handle {
+                    match reload_handle.reload() {
+                        Ok(()) => info!("Reloaded network TLS certificates"),
+                        Err(err) => error!("Unable to reload network TLS certificates: {}", err),
+                    }
+                }
+
+                #[cfg(feature = "quic-transport")]
+                if let Some(reload_handle) = &quic_reload_handle {
+                    match reload_handle.reload() {
+                        Ok(()) => info!("Reloaded QUIC TLS certificates"),
+                        Err(err) => error!("Unable to reload QUIC TLS certificates: {}", err),
+                    }
+                }
+
+                #[cfg(feature = "https-bind")]
+                if let Some(reloader) = &rest_api_reloader {
+                    match reloader.reload() {
+                        Ok(()) => info!("Reloaded REST API TLS certificate"),
+                        Err(err) => error!("Unable to reload REST API TLS certificate: {}", err),
+                    }
+                }
+            }
+        })
+        .map_err(|err| {
+            StartError::TransportError(format!("Unable to start certificate reloader: {}", err))
+        })?;
+
+    Ok(handle)
+}
+
 fn create_store_factory(
     db_url: &str,
 ) -> Result<Box<dyn splinter::store::StoreFactory>, StartError> {
@@ -1008,11 +1379,23 @@ pub struct SplinterDaemonBuilder {
     registries: Vec<String>,
     registry_auto_refresh: Option<u64>,
     registry_forced_refresh: Option<u64>,
+    #[cfg(feature = "registry-remote-signature")]
+    registry_public_key: Option<PublicKey>,
+    #[cfg(feature = "registry-signed-nodes")]
+    registry_signature_policy: NodeSignaturePolicy,
     storage_type: Option<String>,
     heartbeat: Option<u64>,
     admin_timeout: Duration,
     #[cfg(feature = "rest-api-cors")]
     whitelist: Option<Vec<String>>,
+    #[cfg(feature = "biome-notifications")]
+    biome_notification_types: Vec<String>,
+    #[cfg(feature = "biome-login-lockout")]
+    biome_lockout_duration: Option<u64>,
+    #[cfg(feature = "biome-login-lockout")]
+    biome_max_failed_logins: Option<u32>,
+    #[cfg(feature = "biome-password-reset")]
+    biome_password_reset_command: Option<String>,
     #[cfg(feature = "biome-credentials")]
     enable_biome_credentials: Option<bool>,
     #[cfg(feature = "oauth")]
@@ -1029,7 +1412,27 @@ pub struct SplinterDaemonBuilder {
     oauth_openid_auth_params: Option<Vec<(String, String)>>,
     #[cfg(feature = "oauth")]
     oauth_openid_scopes: Option<Vec<String>>,
+    #[cfg(feature = "saml")]
+    saml_sp_entity_id: Option<String>,
+    #[cfg(feature = "saml")]
+    saml_acs_url: Option<String>,
+    #[cfg(feature = "saml")]
+    saml_idp_entity_id: Option<String>,
+    #[cfg(feature = "saml")]
+    saml_idp_sso_url: Option<String>,
+    #[cfg(feature = "saml")]
+    saml_idp_certificate: Option<String>,
+    #[cfg(feature = "authorization-audit")]
+    audit_log_file: Option<String>,
     strict_ref_counts: Option<bool>,
+    #[cfg(feature = "tls-cert-reload")]
+    tls_reload_handle: Option<TlsReloadHandle>,
+    #[cfg(all(feature = "quic-transport", feature = "tls-cert-reload"))]
+    quic_reload_handle: Option<QuicReloadHandle>,
+    #[cfg(feature = "network-rate-limit")]
+    peer_rate_limit: RateLimit,
+    #[cfg(feature = "network-rate-limit")]
+    circuit_rate_limit: RateLimit,
 }
 
 impl SplinterDaemonBuilder {
@@ -1116,6 +1519,18 @@ impl SplinterDaemonBuilder {
         self
     }
 
+    #[cfg(feature = "registry-remote-signature")]
+    pub fn with_registry_public_key(mut self, value: Option<PublicKey>) -> Self {
+        self.registry_public_key = value;
+        self
+    }
+
+    #[cfg(feature = "registry-signed-nodes")]
+    pub fn with_registry_signature_policy(mut self, value: NodeSignaturePolicy) -> Self {
+        self.registry_signature_policy = value;
+        self
+    }
+
     pub fn with_storage_type(mut self, value: Option<String>) -> Self {
         self.storage_type = value;
         self
@@ -1137,6 +1552,30 @@ impl SplinterDaemonBuilder {
         self
     }
 
+    #[cfg(feature = "biome-notifications")]
+    pub fn with_biome_notification_types(mut self, value: Vec<String>) -> Self {
+        self.biome_notification_types = value;
+        self
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    pub fn with_biome_lockout_duration(mut self, value: Option<u64>) -> Self {
+        self.biome_lockout_duration = value;
+        self
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    pub fn with_biome_max_failed_logins(mut self, value: Option<u32>) -> Self {
+        self.biome_max_failed_logins = value;
+        self
+    }
+
+    #[cfg(feature = "biome-password-reset")]
+    pub fn with_biome_password_reset_command(mut self, value: Option<String>) -> Self {
+        self.biome_password_reset_command = value;
+        self
+    }
+
     #[cfg(feature = "biome-credentials")]
     pub fn with_enable_biome_credentials(mut self, value: bool) -> Self {
         self.enable_biome_credentials = Some(value);
@@ -1185,11 +1624,71 @@ impl SplinterDaemonBuilder {
         self
     }
 
+    #[cfg(feature = "saml")]
+    pub fn with_saml_sp_entity_id(mut self, value: Option<String>) -> Self {
+        self.saml_sp_entity_id = value;
+        self
+    }
+
+    #[cfg(feature = "saml")]
+    pub fn with_saml_acs_url(mut self, value: Option<String>) -> Self {
+        self.saml_acs_url = value;
+        self
+    }
+
+    #[cfg(feature = "saml")]
+    pub fn with_saml_idp_entity_id(mut self, value: Option<String>) -> Self {
+        self.saml_idp_entity_id = value;
+        self
+    }
+
+    #[cfg(feature = "saml")]
+    pub fn with_saml_idp_sso_url(mut self, value: Option<String>) -> Self {
+        self.saml_idp_sso_url = value;
+        self
+    }
+
+    #[cfg(feature = "saml")]
+    pub fn with_saml_idp_certificate(mut self, value: Option<String>) -> Self {
+        self.saml_idp_certificate = value;
+        self
+    }
+
+    #[cfg(feature = "authorization-audit")]
+    pub fn with_audit_log_file(mut self, value: Option<String>) -> Self {
+        self.audit_log_file = value;
+        self
+    }
+
     pub fn with_strict_ref_counts(mut self, strict_ref_counts: bool) -> Self {
         self.strict_ref_counts = Some(strict_ref_counts);
         self
     }
 
+    #[cfg(feature = "tls-cert-reload")]
+    pub fn with_tls_reload_handle(mut self, value: Option<TlsReloadHandle>) -> Self {
+        self.tls_reload_handle = value;
+        self
+    }
+
+    #[cfg(all(feature = "quic-transport", feature = "tls-cert-reload"))]
+    pub fn with_quic_reload_handle(mut self, value: Option<QuicReloadHandle>) -> Self {
+        self.quic_reload_handle = value;
+        self
+    }
+
+    #[cfg(feature = "network-rate-limit")]
+    pub fn with_peer_rate_limit(mut self, value: RateLimit) -> Self {
+        self.peer_rate_limit = value;
+        self
+    }
+
+    #[cfg(feature = "network-rate-limit")]
+    pub fn with_circuit_rate_limit(mut self, value: RateLimit) -> Self {
+        self.circuit_rate_limit = value;
+        self
+    }
+
     pub fn build(self) -> Result<SplinterDaemon, CreateError> {
         let heartbeat = self.heartbeat.ok_or_else(|| {
             CreateError::MissingRequiredField("Missing field: heartbeat".to_string())
@@ -1199,7 +1698,15 @@ impl SplinterDaemonBuilder {
             CreateError::MissingRequiredField("Missing field: node_id".to_string())
         })?;
 
+        #[cfg(not(feature = "network-priority"))]
         let mesh = Mesh::new(512, 128);
+        #[cfg(feature = "network-priority")]
+        let mesh = Mesh::new_with_priority(
+            512,
+            128,
+            Arc::new(SplinterdPriorityClassifier),
+            PriorityWeights::default(),
+        );
 
         #[cfg(feature = "authorization-handler-allow-keys")]
         let config_dir = self.config_dir.ok_or_else(|| {
@@ -1288,10 +1795,22 @@ impl SplinterDaemonBuilder {
             registries: self.registries,
             registry_auto_refresh,
             registry_forced_refresh,
+            #[cfg(feature = "registry-remote-signature")]
+            registry_public_key: self.registry_public_key,
+            #[cfg(feature = "registry-signed-nodes")]
+            registry_signature_policy: self.registry_signature_policy,
             storage_type,
             admin_timeout: self.admin_timeout,
             #[cfg(feature = "rest-api-cors")]
             whitelist: self.whitelist,
+            #[cfg(feature = "biome-notifications")]
+            biome_notification_types: self.biome_notification_types,
+            #[cfg(feature = "biome-login-lockout")]
+            biome_lockout_duration: self.biome_lockout_duration,
+            #[cfg(feature = "biome-login-lockout")]
+            biome_max_failed_logins: self.biome_max_failed_logins,
+            #[cfg(feature = "biome-password-reset")]
+            biome_password_reset_command: self.biome_password_reset_command,
             #[cfg(feature = "biome-credentials")]
             enable_biome_credentials,
             #[cfg(feature = "oauth")]
@@ -1308,8 +1827,28 @@ impl SplinterDaemonBuilder {
             oauth_openid_auth_params: self.oauth_openid_auth_params,
             #[cfg(feature = "oauth")]
             oauth_openid_scopes: self.oauth_openid_scopes,
+            #[cfg(feature = "saml")]
+            saml_sp_entity_id: self.saml_sp_entity_id,
+            #[cfg(feature = "saml")]
+            saml_acs_url: self.saml_acs_url,
+            #[cfg(feature = "saml")]
+            saml_idp_entity_id: self.saml_idp_entity_id,
+            #[cfg(feature = "saml")]
+            saml_idp_sso_url: self.saml_idp_sso_url,
+            #[cfg(feature = "saml")]
+            saml_idp_certificate: self.saml_idp_certificate,
+            #[cfg(feature = "authorization-audit")]
+            audit_log_file: self.audit_log_file,
             heartbeat,
             strict_ref_counts,
+            #[cfg(feature = "tls-cert-reload")]
+            tls_reload_handle: self.tls_reload_handle,
+            #[cfg(all(feature = "quic-transport", feature = "tls-cert-reload"))]
+            quic_reload_handle: self.quic_reload_handle,
+            #[cfg(feature = "network-rate-limit")]
+            peer_rate_limit: self.peer_rate_limit,
+            #[cfg(feature = "network-rate-limit")]
+            circuit_rate_limit: self.circuit_rate_limit,
         })
     }
 }
@@ -1339,6 +1878,7 @@ fn set_up_circuit_dispatcher(
     node_id: &str,
     routing_reader: Box<dyn RoutingTableReader>,
     routing_writer: Box<dyn RoutingTableWriter>,
+    #[cfg(feature = "network-rate-limit")] rate_limiter: RateLimiter,
 ) -> Dispatcher<CircuitMessageType> {
     let mut dispatcher = Dispatcher::<CircuitMessageType>::new(Box::new(network_sender));
 
@@ -1355,6 +1895,8 @@ fn set_up_circuit_dispatcher(
 
     let direct_message_handler =
         CircuitDirectMessageHandler::new(node_id.to_string(), routing_reader.clone());
+    #[cfg(feature = "network-rate-limit")]
+    let direct_message_handler = direct_message_handler.with_rate_limiter(rate_limiter);
     dispatcher.set_handler(Box::new(direct_message_handler));
 
     let circuit_error_handler =
@@ -1374,6 +1916,8 @@ fn create_registry(
     registries: &[String],
     auto_refresh_interval: u64,
     forced_refresh_interval: u64,
+    #[cfg(feature = "registry-remote-signature")] public_key: Option<PublicKey>,
+    #[cfg(feature = "registry-signed-nodes")] signature_policy: NodeSignaturePolicy,
     store_factory: &dyn splinter::store::StoreFactory,
 ) -> (Box<dyn RwRegistry>, RegistryShutdownHandle) {
     let mut registry_shutdown_handle = RegistryShutdownHandle::new();
@@ -1417,12 +1961,33 @@ fn create_registry(
                 } else {
                     None
                 };
-                match RemoteYamlRegistry::new(
+                #[cfg(feature = "registry-remote-signature")]
+                let remote_registry = match public_key.clone() {
+                    Some(public_key) => RemoteYamlRegistry::new_with_signing_key(
+                        registry,
+                        state_dir,
+                        auto_refresh_interval,
+                        forced_refresh_interval,
+                        public_key,
+                    ),
+                    None => RemoteYamlRegistry::new(
+                        registry,
+                        state_dir,
+                        auto_refresh_interval,
+                        forced_refresh_interval,
+                    ),
+                };
+                #[cfg(not(feature = "registry-remote-signature"))]
+                let remote_registry = RemoteYamlRegistry::new(
                     registry,
                     state_dir,
                     auto_refresh_interval,
                     forced_refresh_interval,
-                ) {
+                );
+                #[cfg(feature = "registry-signed-nodes")]
+                let remote_registry = remote_registry
+                    .and_then(|registry| registry.with_signature_policy(signature_policy));
+                match remote_registry {
                     Ok(mut registry) => {
                         // this should alwasy return some
                         if let Some(shutdown_handle) = registry.take_shutdown_handle() {
@@ -132,6 +132,7 @@ impl From<ConfigError> for UserError {
 #[derive(Debug)]
 pub enum GetTransportError {
     CertError(String),
+    ConfigError(String),
     TlsTransportError(TlsInitError),
     IoError(io::Error),
 }
@@ -140,6 +141,7 @@ impl Error for GetTransportError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         match self {
             GetTransportError::CertError(_) => None,
+            GetTransportError::ConfigError(_) => None,
             GetTransportError::TlsTransportError(err) => Some(err),
             GetTransportError::IoError(err) => Some(err),
         }
@@ -152,6 +154,9 @@ impl fmt::Display for GetTransportError {
             GetTransportError::CertError(msg) => {
                 write!(f, "unable to retrieve certificate: {}", msg)
             }
+            GetTransportError::ConfigError(msg) => {
+                write!(f, "invalid transport configuration: {}", msg)
+            }
             GetTransportError::TlsTransportError(err) => {
                 write!(f, "unable to create TLS transport: {}", err)
             }
@@ -22,12 +22,22 @@ extern crate clap;
 mod config;
 mod daemon;
 mod error;
+#[cfg(feature = "biome-password-reset")]
+mod password_reset;
+#[cfg(feature = "network-priority")]
+mod priority;
+#[cfg(feature = "network-rate-limit")]
+mod rate_limit;
 mod routes;
 mod transport;
 
 use flexi_logger::{style, DeferredNow, LogSpecBuilder, Logger};
 use log::Record;
 use rand::{thread_rng, Rng};
+#[cfg(feature = "network-rate-limit")]
+use splinter::network::rate_limit::RateLimit;
+#[cfg(feature = "registry-signed-nodes")]
+use splinter::registry::NodeSignaturePolicy;
 
 use crate::config::{
     ClapPartialConfigBuilder, Config, ConfigBuilder, ConfigError, DefaultPartialConfigBuilder,
@@ -36,6 +46,8 @@ use crate::config::{
 use crate::daemon::SplinterDaemonBuilder;
 use clap::{clap_app, crate_version};
 use clap::{Arg, ArgMatches};
+#[cfg(feature = "registry-remote-signature")]
+use cylinder::PublicKey;
 
 use std::env;
 use std::fs::{self, File};
@@ -339,6 +351,23 @@ fn main() {
             .alias("rest-api-key"),
     );
 
+    #[cfg(feature = "registry-remote-signature")]
+    let app = app.arg(
+        Arg::with_name("registry_public_key")
+            .long("registry-public-key")
+            .takes_value(true)
+            .help("Public key that remote Splinter registry files must be signed by"),
+    );
+
+    #[cfg(feature = "registry-signed-nodes")]
+    let app = app.arg(
+        Arg::with_name("registry_signature_policy")
+            .long("registry-signature-policy")
+            .takes_value(true)
+            .possible_values(&["accept-all", "reject-invalid", "require-valid"])
+            .help("Which nodes of remote registries to accept, based on their signatures"),
+    );
+
     #[cfg(feature = "rest-api-cors")]
     let app = app.arg(
         Arg::with_name("whitelist")
@@ -349,6 +378,88 @@ fn main() {
             .help("Whitelisted domains"),
     );
 
+    #[cfg(feature = "biome-notifications")]
+    let app = app.arg(
+        Arg::with_name("biome_notification_types")
+            .long("biome-notification-types")
+            .multiple(true)
+            .takes_value(true)
+            .help("Circuit management types whose proposals create Biome notifications"),
+    );
+
+    #[cfg(feature = "biome-login-lockout")]
+    let app = app
+        .arg(
+            Arg::with_name("biome_lockout_duration")
+                .long("biome-lockout-duration")
+                .takes_value(true)
+                .help("Seconds a Biome user is locked out for after too many failed logins"),
+        )
+        .arg(
+            Arg::with_name("biome_max_failed_logins")
+                .long("biome-max-failed-logins")
+                .takes_value(true)
+                .help("Number of consecutive failed logins after which a Biome user is locked out"),
+        );
+
+    #[cfg(feature = "biome-password-reset")]
+    let app = app.arg(
+        Arg::with_name("biome_password_reset_command")
+            .long("biome-password-reset-command")
+            .takes_value(true)
+            .help("Command run to deliver Biome password reset tokens"),
+    );
+
+    #[cfg(feature = "socket-compression")]
+    let app = app
+        .arg(
+            Arg::with_name("network_compression")
+                .long("network-compression")
+                .multiple(true)
+                .takes_value(true)
+                .possible_values(&["deflate", "zstd"])
+                .help(
+                    "Compression algorithms to offer on tcp:// and tcps:// connections, in order \
+                    of preference",
+                ),
+        )
+        .arg(
+            Arg::with_name("network_compression_threshold")
+                .long("network-compression-threshold")
+                .takes_value(true)
+                .help(
+                    "Minimum size, in bytes, of a message to be compressed; defaults to 1024 \
+                    bytes",
+                ),
+        );
+
+    #[cfg(feature = "network-rate-limit")]
+    let app = app
+        .arg(
+            Arg::with_name("peer_rate_limit_messages")
+                .long("peer-rate-limit-messages")
+                .takes_value(true)
+                .help("Maximum number of circuit messages per second to forward to each peer"),
+        )
+        .arg(
+            Arg::with_name("peer_rate_limit_bytes")
+                .long("peer-rate-limit-bytes")
+                .takes_value(true)
+                .help("Maximum number of circuit message bytes per second to forward to each peer"),
+        )
+        .arg(
+            Arg::with_name("circuit_rate_limit_messages")
+                .long("circuit-rate-limit-messages")
+                .takes_value(true)
+                .help("Maximum number of messages per second to forward on each circuit"),
+        )
+        .arg(
+            Arg::with_name("circuit_rate_limit_bytes")
+                .long("circuit-rate-limit-bytes")
+                .takes_value(true)
+                .help("Maximum number of message bytes per second to forward on each circuit"),
+        );
+
     #[cfg(feature = "biome-credentials")]
     let app = app.arg(
         Arg::with_name("enable_biome_credentials")
@@ -412,6 +523,50 @@ fn main() {
                 .multiple(true),
         );
 
+    #[cfg(feature = "saml")]
+    let app = app
+        .arg(
+            Arg::with_name("saml_sp_entity_id")
+                .long("saml-sp-entity-id")
+                .long_help("Entity ID of the REST API as a SAML service provider")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("saml_acs_url")
+                .long("saml-acs-url")
+                .long_help("Public URL of the REST API's SAML assertion consumer service (`/saml/acs`)")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("saml_idp_entity_id")
+                .long("saml-idp-entity-id")
+                .long_help("Entity ID of the SAML identity provider used by the REST API")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("saml_idp_sso_url")
+                .long("saml-idp-sso-url")
+                .long_help("Single sign-on URL of the SAML identity provider used by the REST API")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("saml_idp_certificate")
+                .long("saml-idp-certificate")
+                .long_help("Path to the PEM-encoded signing certificate of the SAML identity provider used by the REST API")
+                .takes_value(true),
+        );
+
+    #[cfg(feature = "authorization-audit")]
+    let app = app.arg(
+        Arg::with_name("audit_log_file")
+            .long("audit-log-file")
+            .long_help(
+                "File to write the REST API audit log to, as JSON lines; if not set, the audit \
+                 log is kept in the database",
+            )
+            .takes_value(true),
+    );
+
     let matches = app.get_matches();
 
     let log_level = match matches.occurrences_of("verbose") {
@@ -465,6 +620,9 @@ fn start_daemon(matches: ArgMatches) -> Result<(), UserError> {
         }
     }
 
+    #[cfg(feature = "tls-cert-reload")]
+    let (transport, reload_handles) = build_transport(&config)?;
+    #[cfg(not(feature = "tls-cert-reload"))]
     let transport = build_transport(&config)?;
 
     let rest_api_endpoint = config.rest_api_endpoint();
@@ -525,11 +683,60 @@ fn start_daemon(matches: ArgMatches) -> Result<(), UserError> {
         }
     }
 
+    #[cfg(feature = "registry-remote-signature")]
+    {
+        if let Some(key) = config.registry_public_key() {
+            let key = PublicKey::new_from_hex(key).map_err(|err| {
+                UserError::InvalidArgument(format!("Invalid registry public key: {}", err))
+            })?;
+            daemon_builder = daemon_builder.with_registry_public_key(Some(key));
+        }
+    }
+
+    #[cfg(feature = "registry-signed-nodes")]
+    {
+        if let Some(policy) = config.registry_signature_policy() {
+            let policy = match policy {
+                "accept-all" => NodeSignaturePolicy::AcceptAll,
+                "reject-invalid" => NodeSignaturePolicy::RejectInvalid,
+                "require-valid" => NodeSignaturePolicy::RequireValid,
+                _ => {
+                    return Err(UserError::InvalidArgument(format!(
+                        "Invalid registry signature policy: {}",
+                        policy
+                    )))
+                }
+            };
+            daemon_builder = daemon_builder.with_registry_signature_policy(policy);
+        }
+    }
+
     #[cfg(feature = "rest-api-cors")]
     {
         daemon_builder = daemon_builder.with_whitelist(config.whitelist().map(ToOwned::to_owned));
     }
 
+    #[cfg(feature = "biome-notifications")]
+    {
+        if let Some(types) = config.biome_notification_types() {
+            daemon_builder = daemon_builder.with_biome_notification_types(types.to_vec());
+        }
+    }
+
+    #[cfg(feature = "biome-login-lockout")]
+    {
+        daemon_builder = daemon_builder
+            .with_biome_lockout_duration(config.biome_lockout_duration())
+            .with_biome_max_failed_logins(config.biome_max_failed_logins());
+    }
+
+    #[cfg(feature = "biome-password-reset")]
+    {
+        daemon_builder = daemon_builder.with_biome_password_reset_command(
+            config.biome_password_reset_command().map(ToOwned::to_owned),
+        );
+    }
+
     #[cfg(feature = "biome-credentials")]
     {
         daemon_builder =
@@ -548,6 +755,54 @@ fn start_daemon(matches: ArgMatches) -> Result<(), UserError> {
             .with_oauth_openid_scopes(config.oauth_openid_scopes().map(ToOwned::to_owned));
     }
 
+    #[cfg(feature = "saml")]
+    {
+        daemon_builder = daemon_builder
+            .with_saml_sp_entity_id(config.saml_sp_entity_id().map(ToOwned::to_owned))
+            .with_saml_acs_url(config.saml_acs_url().map(ToOwned::to_owned))
+            .with_saml_idp_entity_id(config.saml_idp_entity_id().map(ToOwned::to_owned))
+            .with_saml_idp_sso_url(config.saml_idp_sso_url().map(ToOwned::to_owned))
+            .with_saml_idp_certificate(config.saml_idp_certificate().map(ToOwned::to_owned));
+    }
+
+    #[cfg(feature = "authorization-audit")]
+    {
+        daemon_builder =
+            daemon_builder.with_audit_log_file(config.audit_log_file().map(ToOwned::to_owned));
+    }
+
+    #[cfg(feature = "tls-cert-reload")]
+    {
+        daemon_builder = daemon_builder.with_tls_reload_handle(reload_handles.tls);
+        #[cfg(feature = "quic-transport")]
+        {
+            daemon_builder = daemon_builder.with_quic_reload_handle(reload_handles.quic);
+        }
+    }
+
+    #[cfg(feature = "network-rate-limit")]
+    {
+        let mut peer_rate_limit = RateLimit::new();
+        if let Some(messages) = config.peer_rate_limit_messages() {
+            peer_rate_limit = peer_rate_limit.with_messages_per_second(messages);
+        }
+        if let Some(bytes) = config.peer_rate_limit_bytes() {
+            peer_rate_limit = peer_rate_limit.with_bytes_per_second(bytes);
+        }
+
+        let mut circuit_rate_limit = RateLimit::new();
+        if let Some(messages) = config.circuit_rate_limit_messages() {
+            circuit_rate_limit = circuit_rate_limit.with_messages_per_second(messages);
+        }
+        if let Some(bytes) = config.circuit_rate_limit_bytes() {
+            circuit_rate_limit = circuit_rate_limit.with_bytes_per_second(bytes);
+        }
+
+        daemon_builder = daemon_builder
+            .with_peer_rate_limit(peer_rate_limit)
+            .with_circuit_rate_limit(circuit_rate_limit);
+    }
+
     let mut node = daemon_builder.build().map_err(|err| {
         UserError::daemon_err_with_source("unable to build the Splinter daemon", Box::new(err))
     })?;
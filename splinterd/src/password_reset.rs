@@ -0,0 +1,75 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Delivers Biome password reset tokens by running a command provided by the operator.
+//!
+//! The command is run with the username as its only argument. The token and the time it expires,
+//! in seconds since the epoch, are passed in the `SPLINTER_PASSWORD_RESET_TOKEN` and
+//! `SPLINTER_PASSWORD_RESET_EXPIRES` environment variables so that the token does not show up in
+//! the process list. A typical command looks up the user's email address and mails the token.
+
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use splinter::biome::credentials::password_reset::PasswordResetSender;
+use splinter::error::InternalError;
+
+const TOKEN_ENV: &str = "SPLINTER_PASSWORD_RESET_TOKEN";
+const EXPIRES_ENV: &str = "SPLINTER_PASSWORD_RESET_EXPIRES";
+
+/// A `PasswordResetSender` that hands tokens to an external command
+pub struct CommandPasswordResetSender {
+    command: String,
+}
+
+impl CommandPasswordResetSender {
+    pub fn new(command: String) -> Self {
+        CommandPasswordResetSender { command }
+    }
+}
+
+impl PasswordResetSender for CommandPasswordResetSender {
+    fn send_password_reset_token(
+        &self,
+        username: &str,
+        token: &str,
+        expires: SystemTime,
+    ) -> Result<(), InternalError> {
+        let expires = expires
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .map_err(|err| InternalError::from_source(Box::new(err)))?;
+
+        let status = Command::new(&self.command)
+            .arg(username)
+            .env(TOKEN_ENV, token)
+            .env(EXPIRES_ENV, expires.to_string())
+            .status()
+            .map_err(|err| {
+                InternalError::from_source_with_message(
+                    Box::new(err),
+                    format!("Failed to run password reset command {}", self.command),
+                )
+            })?;
+
+        if status.success() {
+            Ok(())
+        } else {
+            Err(InternalError::with_message(format!(
+                "Password reset command {} failed: {}",
+                self.command, status
+            )))
+        }
+    }
+}
@@ -0,0 +1,156 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Assigns splinterd's network messages to priority lanes.
+//!
+//! Network, authorization and circuit management messages, including all admin messages, are
+//! control traffic. Circuit direct messages that carry a scabbard consensus message are consensus
+//! traffic, and all other circuit direct messages are bulk traffic.
+
+use protobuf::Message;
+use scabbard::protos::scabbard::{ScabbardMessage, ScabbardMessage_Type};
+use splinter::network::priority::{MessagePriority, PayloadClassifier, PriorityClassifier};
+use splinter::protos::circuit::{CircuitDirectMessage, CircuitMessage, CircuitMessageType};
+use splinter::protos::network::{NetworkMessage, NetworkMessageType};
+
+/// Classifies messages at each layer of splinterd's network stack: raw `NetworkMessage` bytes
+/// sent through the mesh, and typed messages passed through the network and circuit dispatch
+/// loops.
+#[derive(Clone, Copy, Default)]
+pub struct SplinterdPriorityClassifier;
+
+impl PayloadClassifier for SplinterdPriorityClassifier {
+    fn classify(&self, payload: &[u8]) -> MessagePriority {
+        match NetworkMessage::parse_from_bytes(payload) {
+            Ok(msg) => classify_network_message(&msg.get_message_type(), msg.get_payload()),
+            Err(_) => MessagePriority::Bulk,
+        }
+    }
+}
+
+impl PriorityClassifier<NetworkMessageType> for SplinterdPriorityClassifier {
+    fn classify(&self, message_type: &NetworkMessageType, message_bytes: &[u8]) -> MessagePriority {
+        classify_network_message(message_type, message_bytes)
+    }
+}
+
+impl PriorityClassifier<CircuitMessageType> for SplinterdPriorityClassifier {
+    fn classify(&self, message_type: &CircuitMessageType, message_bytes: &[u8]) -> MessagePriority {
+        classify_circuit_message(message_type, message_bytes)
+    }
+}
+
+fn classify_network_message(message_type: &NetworkMessageType, bytes: &[u8]) -> MessagePriority {
+    match message_type {
+        NetworkMessageType::CIRCUIT => match CircuitMessage::parse_from_bytes(bytes) {
+            Ok(msg) => classify_circuit_message(&msg.get_message_type(), msg.get_payload()),
+            Err(_) => MessagePriority::Bulk,
+        },
+        _ => MessagePriority::Control,
+    }
+}
+
+fn classify_circuit_message(message_type: &CircuitMessageType, bytes: &[u8]) -> MessagePriority {
+    match message_type {
+        CircuitMessageType::CIRCUIT_DIRECT_MESSAGE => {
+            match CircuitDirectMessage::parse_from_bytes(bytes) {
+                Ok(msg) if is_consensus_message(msg.get_payload()) => MessagePriority::Consensus,
+                _ => MessagePriority::Bulk,
+            }
+        }
+        _ => MessagePriority::Control,
+    }
+}
+
+// Service payloads are opaque to splinterd; a payload that does not parse as a scabbard consensus
+// message is treated as data.
+fn is_consensus_message(payload: &[u8]) -> bool {
+    ScabbardMessage::parse_from_bytes(payload)
+        .map(|msg| msg.get_message_type() == ScabbardMessage_Type::CONSENSUS_MESSAGE)
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network_message(scabbard_message_type: ScabbardMessage_Type) -> Vec<u8> {
+        let mut scabbard_message = ScabbardMessage::new();
+        scabbard_message.set_message_type(scabbard_message_type);
+
+        let mut direct_message = CircuitDirectMessage::new();
+        direct_message.set_circuit("abcde-01234".into());
+        direct_message.set_payload(scabbard_message.write_to_bytes().unwrap());
+
+        let mut circuit_message = CircuitMessage::new();
+        circuit_message.set_message_type(CircuitMessageType::CIRCUIT_DIRECT_MESSAGE);
+        circuit_message.set_payload(direct_message.write_to_bytes().unwrap());
+
+        let mut network_message = NetworkMessage::new();
+        network_message.set_message_type(NetworkMessageType::CIRCUIT);
+        network_message.set_payload(circuit_message.write_to_bytes().unwrap());
+
+        network_message.write_to_bytes().unwrap()
+    }
+
+    /// Verify that scabbard consensus messages are classified as consensus traffic and scabbard
+    /// batches as bulk traffic.
+    #[test]
+    fn test_classify_scabbard_messages() {
+        let classifier = SplinterdPriorityClassifier;
+
+        assert_eq!(
+            MessagePriority::Consensus,
+            PayloadClassifier::classify(
+                &classifier,
+                &network_message(ScabbardMessage_Type::CONSENSUS_MESSAGE)
+            )
+        );
+        assert_eq!(
+            MessagePriority::Bulk,
+            PayloadClassifier::classify(
+                &classifier,
+                &network_message(ScabbardMessage_Type::NEW_BATCH)
+            )
+        );
+    }
+
+    /// Verify that network and circuit management messages are classified as control traffic,
+    /// and that unparseable payloads are classified as bulk traffic.
+    #[test]
+    fn test_classify_control_messages() {
+        let classifier = SplinterdPriorityClassifier;
+
+        assert_eq!(
+            MessagePriority::Control,
+            PriorityClassifier::<NetworkMessageType>::classify(
+                &classifier,
+                &NetworkMessageType::NETWORK_HEARTBEAT,
+                &[]
+            )
+        );
+        assert_eq!(
+            MessagePriority::Control,
+            PriorityClassifier::<CircuitMessageType>::classify(
+                &classifier,
+                &CircuitMessageType::ADMIN_DIRECT_MESSAGE,
+                &[]
+            )
+        );
+        assert_eq!(
+            MessagePriority::Bulk,
+            PayloadClassifier::classify(&classifier, b"not a network message")
+        );
+    }
+}
@@ -0,0 +1,133 @@
+// Copyright 2018-2021 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Keeps splinterd's rate limiter in step with the peers and circuits it limits.
+//!
+//! The rate limiter keeps a bucket for every peer and circuit that a message has been forwarded
+//! to. Buckets are discarded when the peer disconnects or the circuit is removed from the routing
+//! table, so that they do not accumulate for the lifetime of the node.
+
+use std::thread;
+
+use splinter::circuit::routing::{Circuit, CircuitNode, RoutingTableWriter, Service, ServiceId};
+use splinter::error::InternalError;
+use splinter::network::rate_limit::RateLimiter;
+use splinter::peer::{PeerManagerConnector, PeerManagerNotification};
+
+use crate::daemon::StartError;
+
+/// A routing table writer that discards a circuit's rate limit bucket when the circuit is
+/// removed.
+pub struct RateLimitedRoutingTableWriter {
+    inner: Box<dyn RoutingTableWriter>,
+    rate_limiter: RateLimiter,
+}
+
+impl RateLimitedRoutingTableWriter {
+    pub fn new(inner: Box<dyn RoutingTableWriter>, rate_limiter: RateLimiter) -> Self {
+        RateLimitedRoutingTableWriter {
+            inner,
+            rate_limiter,
+        }
+    }
+}
+
+impl RoutingTableWriter for RateLimitedRoutingTableWriter {
+    fn add_service(
+        &mut self,
+        service_id: ServiceId,
+        service: Service,
+    ) -> Result<(), InternalError> {
+        self.inner.add_service(service_id, service)
+    }
+
+    fn remove_service(&mut self, service_id: &ServiceId) -> Result<(), InternalError> {
+        self.inner.remove_service(service_id)
+    }
+
+    fn add_circuit(
+        &mut self,
+        circuit_id: String,
+        circuit: Circuit,
+        nodes: Vec<CircuitNode>,
+    ) -> Result<(), InternalError> {
+        self.inner.add_circuit(circuit_id, circuit, nodes)
+    }
+
+    fn add_circuits(&mut self, circuits: Vec<Circuit>) -> Result<(), InternalError> {
+        self.inner.add_circuits(circuits)
+    }
+
+    fn remove_circuit(&mut self, circuit_id: &str) -> Result<(), InternalError> {
+        self.inner.remove_circuit(circuit_id)?;
+        self.rate_limiter.remove_circuit(circuit_id);
+        Ok(())
+    }
+
+    fn add_node(&mut self, node_id: String, node: CircuitNode) -> Result<(), InternalError> {
+        self.inner.add_node(node_id, node)
+    }
+
+    fn add_nodes(&mut self, nodes: Vec<CircuitNode>) -> Result<(), InternalError> {
+        self.inner.add_nodes(nodes)
+    }
+
+    fn remove_node(&mut self, node_id: &str) -> Result<(), InternalError> {
+        self.inner.remove_node(node_id)
+    }
+
+    fn clone_boxed(&self) -> Box<dyn RoutingTableWriter> {
+        Box::new(RateLimitedRoutingTableWriter {
+            inner: self.inner.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+        })
+    }
+}
+
+/// Starts a thread that discards a peer's rate limit bucket when the peer disconnects.
+///
+/// The thread exits when the peer manager shuts down.
+pub fn start_peer_bucket_cleanup(
+    peer_connector: &PeerManagerConnector,
+    rate_limiter: RateLimiter,
+) -> Result<(), StartError> {
+    let notifications = peer_connector.subscribe().map_err(|err| {
+        StartError::NetworkError(format!(
+            "Unable to subscribe to peer manager notifications: {}",
+            err
+        ))
+    })?;
+
+    thread::Builder::new()
+        .name("RateLimiterPeerCleanup".into())
+        .spawn(move || {
+            for notification in notifications {
+                if let PeerManagerNotification::Disconnected { peer } = notification {
+                    debug!(
+                        "Discarding rate limit bucket for disconnected peer {}",
+                        peer
+                    );
+                    rate_limiter.remove_peer(&peer);
+                }
+            }
+        })
+        .map_err(|err| {
+            StartError::NetworkError(format!(
+                "Unable to start rate limiter peer cleanup: {}",
+                err
+            ))
+        })?;
+
+    Ok(())
+}
@@ -16,8 +16,18 @@ use std::fs;
 use std::path::Path;
 
 use splinter::transport::multi::MultiTransport;
+#[cfg(all(feature = "quic-transport", feature = "tls-cert-reload"))]
+use splinter::transport::quic::QuicReloadHandle;
+#[cfg(feature = "quic-transport")]
+use splinter::transport::quic::QuicTransport;
+#[cfg(feature = "endpoint-resolution")]
+use splinter::transport::resolver::SrvEndpointResolver;
 use splinter::transport::socket::TcpTransport;
+#[cfg(feature = "tls-cert-reload")]
+use splinter::transport::socket::TlsReloadHandle;
 use splinter::transport::socket::TlsTransport;
+#[cfg(feature = "socket-compression")]
+use splinter::transport::socket::{Compression, CompressionConfig};
 use splinter::transport::tls::{TlsConfig, TlsConfigBuilder};
 #[cfg(feature = "ws-transport")]
 use splinter::transport::ws::WsTransport;
@@ -28,12 +38,32 @@ use crate::error::GetTransportError;
 
 type SendableTransport = Box<dyn Transport + Send>;
 
-pub fn build_transport(config: &Config) -> Result<MultiTransport, GetTransportError> {
+/// The handles that reload the certificates of the transports that use TLS, if TLS is enabled.
+#[cfg(feature = "tls-cert-reload")]
+#[derive(Default)]
+pub struct TransportReloadHandles {
+    pub tls: Option<TlsReloadHandle>,
+    #[cfg(feature = "quic-transport")]
+    pub quic: Option<QuicReloadHandle>,
+}
+
+/// The transport built from the config, along with the handles that reload its certificates.
+#[cfg(feature = "tls-cert-reload")]
+type BuiltTransport = (MultiTransport, TransportReloadHandles);
+#[cfg(not(feature = "tls-cert-reload"))]
+type BuiltTransport = MultiTransport;
+
+pub fn build_transport(config: &Config) -> Result<BuiltTransport, GetTransportError> {
     let mut transports: Vec<SendableTransport> = vec![];
+    #[cfg(feature = "tls-cert-reload")]
+    let mut reload_handles = TransportReloadHandles::default();
 
     // add tcp transport
     // this will be default for endpoints without a prefix
-    transports.push(Box::new(TcpTransport::default()));
+    let tcp_transport = TcpTransport::default();
+    #[cfg(feature = "socket-compression")]
+    let tcp_transport = tcp_transport.with_compression(build_compression_config(config)?);
+    transports.push(Box::new(tcp_transport));
 
     // add web socket transport
 
@@ -43,13 +73,20 @@ pub fn build_transport(config: &Config) -> Result<MultiTransport, GetTransportEr
         validate_tls_config(&tls_config)?;
         print_tls_config(&tls_config)?;
 
-        transports.push(Box::new(TlsTransport::new(
+        let tls_transport = TlsTransport::new(
             tls_config.ca_certs_file().to_owned(),
             tls_config.client_private_key_file().to_string(),
             tls_config.client_cert_file().to_string(),
             tls_config.server_private_key_file().to_string(),
             tls_config.server_cert_file().to_string(),
-        )?));
+        )?;
+        #[cfg(feature = "socket-compression")]
+        let tls_transport = tls_transport.with_compression(build_compression_config(config)?);
+        #[cfg(feature = "tls-cert-reload")]
+        {
+            reload_handles.tls = Some(tls_transport.reload_handle());
+        }
+        transports.push(Box::new(tls_transport));
 
         #[cfg(feature = "ws-transport")]
         transports.push(Box::new(WsTransport::new(Some(&tls_config)).map_err(
@@ -57,12 +94,57 @@ pub fn build_transport(config: &Config) -> Result<MultiTransport, GetTransportEr
                 GetTransportError::CertError(format!("Failed to create WebSocket transport: {}", e))
             },
         )?));
+
+        // add quic transport; QUIC always uses TLS, so it is only available when TLS is enabled
+        #[cfg(feature = "quic-transport")]
+        {
+            let quic_transport = QuicTransport::new(&tls_config).map_err(|e| {
+                GetTransportError::CertError(format!("Failed to create QUIC transport: {}", e))
+            })?;
+            #[cfg(feature = "tls-cert-reload")]
+            {
+                reload_handles.quic = Some(quic_transport.reload_handle());
+            }
+            transports.push(Box::new(quic_transport));
+        }
     } else {
         #[cfg(feature = "ws-transport")]
         transports.push(Box::new(WsTransport::default()));
     }
 
-    Ok(MultiTransport::new(transports))
+    // Allowing unused_mut because transport must be mutable if feature endpoint-resolution is
+    // enabled
+    #[allow(unused_mut)]
+    let mut transport = MultiTransport::new(transports);
+
+    // add resolver for DNS SRV endpoints
+    #[cfg(feature = "endpoint-resolution")]
+    transport.add_resolver(Box::new(
+        SrvEndpointResolver::from_system_conf().map_err(GetTransportError::IoError)?,
+    ));
+
+    #[cfg(feature = "tls-cert-reload")]
+    let transport = (transport, reload_handles);
+
+    Ok(transport)
+}
+
+#[cfg(feature = "socket-compression")]
+fn build_compression_config(config: &Config) -> Result<CompressionConfig, GetTransportError> {
+    let algorithms = config
+        .network_compression()
+        .unwrap_or(&[])
+        .iter()
+        .map(|algorithm| algorithm.parse::<Compression>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(GetTransportError::ConfigError)?;
+
+    let mut compression_config = CompressionConfig::new(algorithms);
+    if let Some(threshold) = config.network_compression_threshold() {
+        compression_config = compression_config.with_threshold(threshold as usize);
+    }
+
+    Ok(compression_config)
 }
 
 fn build_tls_config(config: &Config) -> Result<TlsConfig, GetTransportError> {